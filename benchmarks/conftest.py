@@ -0,0 +1,99 @@
+"""Payload corpus for the hot-path benchmarks.
+
+The default corpus covers typical request sizes and runs in seconds. Pass
+``--bench-fixtures`` to add the large real-world sizes (long-context
+documents, long agent sessions, big tool arguments) before a release.
+"""
+
+from __future__ import annotations
+
+import json
+
+import pytest
+
+from prompti.message import Message
+from prompti.model_client import RunParams, ToolParams, ToolSpec
+
+# a paragraph of mixed-language text, roughly what retrieved documents look like
+_PARAGRAPH = (
+    "The quarterly report covers revenue, churn and hiring across all regions. "
+    "季度报告涵盖了各地区的收入、流失率和招聘情况。 Figures are in USD unless noted. "
+)
+
+
+def pytest_addoption(parser: pytest.Parser) -> None:
+    parser.addoption(
+        "--bench-fixtures",
+        action="store_true",
+        default=False,
+        help="Also benchmark the large real-world payload corpus",
+    )
+
+
+def _text(size: int) -> str:
+    return (_PARAGRAPH * (size // len(_PARAGRAPH) + 1))[:size]
+
+
+def _tools(count: int) -> ToolParams:
+    schema = {
+        "type": "object",
+        "properties": {f"field_{i}": {"type": "string", "description": _text(80)} for i in range(8)},
+        "required": ["field_0"],
+    }
+    return ToolParams(
+        tools=[ToolSpec(name=f"tool_{i}", description=_text(200), parameters=schema) for i in range(count)]
+    )
+
+
+def _agent_history(turns: int) -> list[Message]:
+    messages = [Message(role="system", content=_text(2_000))]
+    for i in range(turns):
+        messages.append(Message.create_user(_text(300)))
+        call = {"id": f"call_{i}", "type": "function", "function": {"name": "tool_1", "arguments": '{"field_0": "x"}'}}
+        messages.append(Message(role="assistant", content=None, tool_calls=[call]))
+        messages.append(Message(role="tool", content=_text(1_500), tool_call_id=f"call_{i}"))
+    return messages
+
+
+def _requests(full: bool) -> dict[str, RunParams]:
+    requests = {
+        "chat": RunParams(messages=[Message(role="system", content=_text(300)), Message.create_user(_text(500))]),
+        "rag": RunParams(messages=[Message(role="system", content=_text(60_000)), Message.create_user("Summarize.")]),
+        "agent": RunParams(messages=_agent_history(40), tool_params=_tools(20)),
+    }
+    if full:
+        requests["long_context"] = RunParams(messages=[Message.create_user(_text(1_500_000))])
+        requests["long_agent"] = RunParams(messages=_agent_history(400), tool_params=_tools(60))
+    return requests
+
+
+def _sse_body(chunks: int) -> bytes:
+    lines = []
+    for i in range(chunks):
+        data = {"id": "c", "model": "m", "choices": [{"index": 0, "delta": {"content": _text(i % 7 + 2)}}]}
+        lines.append(f"data: {json.dumps(data, ensure_ascii=False)}\n\n")
+        if i % 200 == 0:
+            lines.append(": keep-alive\n\n")
+    lines.append("data: [DONE]\n\n")
+    return "".join(lines).encode()
+
+
+def _tool_call_deltas(argument_bytes: int) -> list[list[dict]]:
+    arguments = json.dumps({"query": _text(argument_bytes), "filters": {"region": "emea", "limit": 50}})
+    deltas = [[{"index": 0, "id": "call_0", "type": "function", "function": {"name": "search", "arguments": ""}}]]
+    for start in range(0, len(arguments), 16):
+        deltas.append([{"index": 0, "function": {"arguments": arguments[start : start + 16]}}])
+    return deltas
+
+
+def pytest_generate_tests(metafunc: pytest.Metafunc) -> None:
+    full = metafunc.config.getoption("--bench-fixtures")
+    if "request_params" in metafunc.fixturenames:
+        requests = _requests(full)
+        metafunc.parametrize("request_params", list(requests.values()), ids=list(requests))
+    if "sse_body" in metafunc.fixturenames:
+        sizes = {"2k_chunks": 2_000, **({"20k_chunks": 20_000} if full else {})}
+        metafunc.parametrize("sse_body", [_sse_body(n) for n in sizes.values()], ids=list(sizes))
+    if "tool_call_deltas" in metafunc.fixturenames:
+        sizes = {"8kb_args": 8_000, **({"32kb_args": 32_000} if full else {})}
+        metafunc.parametrize("tool_call_deltas", [_tool_call_deltas(n) for n in sizes.values()], ids=list(sizes))
@@ -0,0 +1,55 @@
+"""Benchmarks of the per-request hot paths; run with ``make bench``."""
+
+import httpx
+import pytest
+
+from prompti.model_client import ModelConfig
+from prompti.model_client.openai_client import SyncOpenAIClient
+from prompti.model_client.openai_wire import SSEDecoder
+from prompti.partial_json import ToolCallStream, merge_tool_call_deltas
+
+
+@pytest.fixture(scope="module")
+def client():
+    cfg = ModelConfig(provider="openai", model="gpt-4o", api_key="k")
+    client = SyncOpenAIClient(cfg, client=httpx.Client())
+    yield client
+    client.close()
+
+
+def test_request_serialization(benchmark, client, request_params):
+    benchmark(client._build_request, request_params)
+
+
+def test_canonical_hash(benchmark, request_params):
+    benchmark(request_params.canonical_hash, "gpt-4o")
+
+
+def test_sse_decoding(benchmark, client, sse_body):
+    def decode():
+        decoder = SSEDecoder()
+        for start in range(0, len(sse_body), 1024):
+            for payload in decoder.feed(sse_body[start : start + 1024]):
+                if payload != "[DONE]":
+                    client._parse_stream_chunk(payload)
+
+    benchmark(decode)
+
+
+def test_tool_call_merge(benchmark, tool_call_deltas):
+    def merge():
+        calls = {}
+        for deltas in tool_call_deltas:
+            merge_tool_call_deltas(calls, deltas)
+
+    benchmark(merge)
+
+
+def test_tool_call_partial_parsing(benchmark, tool_call_deltas):
+    def parse():
+        stream = ToolCallStream()
+        for deltas in tool_call_deltas:
+            stream.feed(deltas)
+
+    # every delta reparses the arguments so far, so large arguments take seconds per round
+    benchmark.pedantic(parse, rounds=5)
@@ -0,0 +1,30 @@
+"""Fuzz the parsing of complete chat completion responses with arbitrary bodies.
+
+Run with ``make fuzz`` or ``python fuzz/fuzz_openai_response.py fuzz/corpus/openai_response``.
+Any exception other than :class:`MalformedResponseError` is a bug.
+"""
+
+import sys
+
+import atheris
+
+with atheris.instrument_imports():
+    import httpx
+
+    from prompti.model_client import ModelConfig
+    from prompti.model_client.openai_client import SyncOpenAIClient
+    from prompti.model_client.openai_wire import MalformedResponseError
+
+CLIENT = SyncOpenAIClient(ModelConfig(provider="openai", model="m", api_key="k"), client=httpx.Client())
+
+
+def test_one_input(data: bytes) -> None:
+    try:
+        CLIENT._process_non_streaming_response(httpx.Response(200, content=data))
+    except MalformedResponseError:
+        pass
+
+
+if __name__ == "__main__":
+    atheris.Setup(sys.argv, test_one_input)
+    atheris.Fuzz()
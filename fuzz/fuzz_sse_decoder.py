@@ -0,0 +1,37 @@
+"""Fuzz the SSE decoder and stream chunk parsing with arbitrary bytes.
+
+Run with ``make fuzz`` or ``python fuzz/fuzz_sse_decoder.py fuzz/corpus/sse_decoder``.
+Any exception other than :class:`MalformedResponseError` is a bug.
+"""
+
+import sys
+
+import atheris
+
+with atheris.instrument_imports():
+    import httpx
+
+    from prompti.model_client import ModelConfig
+    from prompti.model_client.openai_client import SyncOpenAIClient
+    from prompti.model_client.openai_wire import DeltaTextAssembler, MalformedResponseError, SSEDecoder
+
+CLIENT = SyncOpenAIClient(ModelConfig(provider="openai", model="m", api_key="k"), client=httpx.Client())
+
+
+def test_one_input(data: bytes) -> None:
+    fdp = atheris.FuzzedDataProvider(data)
+    decoder = SSEDecoder()
+    assembler = DeltaTextAssembler()
+    while fdp.remaining_bytes():
+        # random chunk boundaries, as the network delivers them
+        chunk = fdp.ConsumeBytes(fdp.ConsumeIntInRange(1, 64))
+        for payload in decoder.feed(chunk):
+            try:
+                CLIENT._parse_stream_chunk(payload, assembler)
+            except MalformedResponseError:
+                pass
+
+
+if __name__ == "__main__":
+    atheris.Setup(sys.argv, test_one_input)
+    atheris.Fuzz()
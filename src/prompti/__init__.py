@@ -1,23 +1,15 @@
-"""PromptI: provider-agnostic prompt engine."""
+"""PromptI: provider-agnostic prompt engine.
+
+Message and request types are imported eagerly. Everything else is
+imported on first access, so services that only need the types for
+serialization do not pull in httpx, Jinja or the telemetry stack.
+"""
 
 from __future__ import annotations
 
-from .engine import PromptEngine
-from .experiment import (
-    ExperimentRegistry,
-    ExperimentSplit,
-    GrowthBookRegistry,
-    UnleashRegistry,
-    bucket,
-)
-from .loader import (
-    FileSystemLoader,
-    HTTPLoader,
-    LocalGitRepoLoader,
-    MemoryLoader,
-    TemplateLoader,
-    TemplateNotFoundError,
-)
+import importlib
+from typing import Any
+
 from .message import (
     Message,
     Usage,
@@ -26,22 +18,43 @@ from .message import (
     StreamingChoice,
     StreamingModelResponse,
 )
-from .model_client import (
-    ModelClient,
+from .model_client.types import (
     ModelConfig,
     RunParams,
     ToolChoice,
     ToolParams,
     ToolSpec,
-    create_client,
 )
-from .replay import ModelClientRecorder, ReplayEngine
-from .template import PromptTemplate
+
+# public name -> submodule that defines it
+_LAZY_ATTRS = {
+    "PromptEngine": ".engine",
+    "ExperimentRegistry": ".experiment",
+    "ExperimentSplit": ".experiment",
+    "GrowthBookRegistry": ".experiment",
+    "UnleashRegistry": ".experiment",
+    "bucket": ".experiment",
+    "FileSystemLoader": ".loader",
+    "HTTPLoader": ".loader",
+    "LocalGitRepoLoader": ".loader",
+    "MemoryLoader": ".loader",
+    "TemplateLoader": ".loader",
+    "TemplateNotFoundError": ".loader",
+    "ModelClient": ".model_client",
+    "create_client": ".model_client",
+    "LiteLLMClient": ".model_client",
+    "ModelClientRecorder": ".replay",
+    "ReplayEngine": ".replay",
+    "ReplayClient": ".replay",
+    "TelemetryConfig": ".telemetry",
+    "configure_telemetry": ".telemetry",
+    "PromptTemplate": ".template",
+}
 
 __all__ = [
     "Message",
     "Usage",
-    "Choice", 
+    "Choice",
     "ModelResponse",
     "StreamingChoice",
     "StreamingModelResponse",
@@ -55,7 +68,10 @@ __all__ = [
     "ToolChoice",
     "create_client",
     "ReplayEngine",
+    "ReplayClient",
     "ModelClientRecorder",
+    "TelemetryConfig",
+    "configure_telemetry",
     "ExperimentRegistry",
     "ExperimentSplit",
     "UnleashRegistry",
@@ -67,13 +83,18 @@ __all__ = [
     "FileSystemLoader",
     "MemoryLoader",
     "LocalGitRepoLoader",
+    "LiteLLMClient",
 ]
 
-# Optional imports - only available when litellm is installed
-try:
-    from .model_client import LiteLLMClient  # noqa: F401
 
-    __all__.append("LiteLLMClient")
-except ImportError:
-    # LiteLLMClient not available - litellm dependency not installed
-    pass
+def __getattr__(name: str) -> Any:
+    module = _LAZY_ATTRS.get(name)
+    if module is None:
+        raise AttributeError(f"module {__name__!r} has no attribute {name!r}")
+    value = getattr(importlib.import_module(module, __name__), name)
+    globals()[name] = value
+    return value
+
+
+def __dir__() -> list[str]:
+    return sorted(set(globals()) | set(_LAZY_ATTRS))
@@ -0,0 +1,5 @@
+"""Allow ``python -m prompti`` as an alias for the ``prompti`` command."""
+
+from .cli import run
+
+run()
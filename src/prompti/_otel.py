@@ -0,0 +1,61 @@
+"""OpenTelemetry entry points used by prompti.
+
+``opentelemetry-api`` comes with the ``tracing`` extra. Without it
+``trace.get_tracer`` returns a tracer whose spans do nothing and
+``set_baggage`` is ignored, so callers need no conditionals.
+"""
+
+from __future__ import annotations
+
+from collections.abc import Iterator
+from contextlib import contextmanager
+from typing import Any
+
+try:
+    from opentelemetry import trace
+    from opentelemetry.baggage import set_baggage
+
+    TRACING_ENABLED = True
+except ImportError:  # installed without the ``tracing`` extra
+    TRACING_ENABLED = False
+
+    class _NoopSpan:
+        def set_attribute(self, key: str, value: Any) -> None:
+            pass
+
+        def set_attributes(self, attributes: dict[str, Any]) -> None:
+            pass
+
+        def add_event(self, name: str, attributes: dict[str, Any] | None = None) -> None:
+            pass
+
+        def record_exception(self, exception: BaseException, **kwargs: Any) -> None:
+            pass
+
+        def set_status(self, *args: Any, **kwargs: Any) -> None:
+            pass
+
+        def is_recording(self) -> bool:
+            return False
+
+    class _NoopTracer:
+        @contextmanager
+        def start_as_current_span(self, name: str, *args: Any, **kwargs: Any) -> Iterator[_NoopSpan]:
+            yield _NoopSpan()
+
+    class _NoopTrace:
+        """The subset of ``opentelemetry.trace`` used in prompti."""
+
+        def get_tracer(self, name: str, *args: Any, **kwargs: Any) -> _NoopTracer:
+            return _NoopTracer()
+
+        def get_current_span(self, *args: Any, **kwargs: Any) -> _NoopSpan:
+            return _NoopSpan()
+
+    trace = _NoopTrace()  # type: ignore[assignment]
+
+    def set_baggage(name: str, value: object, context: Any = None) -> None:  # type: ignore[misc]
+        return None
+
+
+__all__ = ["TRACING_ENABLED", "set_baggage", "trace"]
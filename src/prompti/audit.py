@@ -0,0 +1,157 @@
+"""Append-only, hash-chained audit log of model calls."""
+
+from __future__ import annotations
+
+import hashlib
+import json
+import os
+import threading
+from datetime import datetime, timezone
+from pathlib import Path
+from typing import Any
+
+from .message import Usage
+from .model_client.base import EventHook, ModelConfig, RunParams
+
+GENESIS_HASH = "0" * 64
+
+
+class AuditIntegrityError(Exception):
+    """Raised when an audit log entry does not match the hash chain."""
+
+    def __init__(self, line: int, reason: str) -> None:
+        self.line = line
+        self.reason = reason
+        super().__init__(f"Audit log broken at line {line}: {reason}")
+
+
+def _entry_hash(entry: dict[str, Any]) -> str:
+    """Return the SHA-256 of ``entry`` (without its own ``hash``) in canonical JSON."""
+    body = {k: v for k, v in entry.items() if k != "hash"}
+    canonical = json.dumps(body, sort_keys=True, separators=(",", ":"), ensure_ascii=False, default=str)
+    return hashlib.sha256(canonical.encode()).hexdigest()
+
+
+class AuditLogger(EventHook):
+    """Write one JSONL audit entry per model call, chained by SHA-256.
+
+    Every entry records who made the call (user, conversation, source), when,
+    which provider/model answered, the outcome, token usage and any policy
+    decisions stored under ``params.trace_context["audit"]`` by hooks or
+    application code. Each entry embeds the hash of the previous entry, so
+    editing or deleting a line breaks :func:`verify_audit_log`.
+
+    Register it like any :class:`EventHook`, e.g. ``PromptEngine(event_hooks=[AuditLogger(path)])``.
+    """
+
+    def __init__(self, path: str | Path, fsync: bool = False) -> None:
+        """Open (or continue) the audit log at ``path``.
+
+        Args:
+            path: JSONL file to append to; an existing chain is resumed.
+            fsync: Force each entry to disk before returning.
+        """
+        self.path = Path(path)
+        self.fsync = fsync
+        self._lock = threading.Lock()
+        self._seq, self._prev_hash = self._resume()
+
+    def _resume(self) -> tuple[int, str]:
+        """Return the sequence number and hash of the last entry in the file."""
+        if not self.path.exists():
+            return 0, GENESIS_HASH
+        last = None
+        with self.path.open("rb") as fh:
+            for line in fh:
+                if line.strip():
+                    last = line
+        if last is None:
+            return 0, GENESIS_HASH
+        entry = json.loads(last)
+        return entry["seq"], entry["hash"]
+
+    def record(self, event: str, **fields: Any) -> dict[str, Any]:
+        """Append an entry of type ``event`` with ``fields`` and return it."""
+        with self._lock:
+            entry = {
+                "seq": self._seq + 1,
+                "timestamp": datetime.now(timezone.utc).isoformat(),
+                "event": event,
+                **fields,
+                "prev_hash": self._prev_hash,
+            }
+            entry["hash"] = _entry_hash(entry)
+            self.path.parent.mkdir(parents=True, exist_ok=True)
+            with self.path.open("a", encoding="utf-8") as fh:
+                fh.write(json.dumps(entry, ensure_ascii=False, default=str) + "\n")
+                fh.flush()
+                if self.fsync:
+                    os.fsync(fh.fileno())
+            self._seq = entry["seq"]
+            self._prev_hash = entry["hash"]
+            return entry
+
+    def _call_fields(self, cfg: ModelConfig, params: RunParams) -> dict[str, Any]:
+        return {
+            "request_id": params.request_id,
+            "user_id": params.user_id,
+            "conversation_id": params.conversation_id,
+            "source": params.source,
+            "provider": cfg.provider,
+            "model": cfg.model,
+            "request_hash": params.trace_context.get("request_hash") or params.canonical_hash(cfg.model),
+            "policy": params.trace_context.get("audit", {}),
+        }
+
+    def on_complete(self, cfg: ModelConfig, params: RunParams, usage: Usage | None, duration: float) -> None:
+        """Record a successful call."""
+        self.record(
+            "completion",
+            **self._call_fields(cfg, params),
+            outcome="success",
+            usage=usage.model_dump() if usage else None,
+            duration_ms=round(duration * 1000, 3),
+        )
+
+    def on_error(
+        self, cfg: ModelConfig, params: RunParams, error_class: str, error: dict[str, Any] | BaseException
+    ) -> None:
+        """Record a failed call."""
+        message = error.get("message") if isinstance(error, dict) else str(error)
+        self.record(
+            "completion",
+            **self._call_fields(cfg, params),
+            outcome="error",
+            error_class=error_class,
+            error=message,
+        )
+
+
+def verify_audit_log(path: str | Path) -> int:
+    """Check the hash chain of the audit log at ``path``.
+
+    Returns:
+        The number of verified entries.
+
+    Raises:
+        AuditIntegrityError: If an entry was modified, removed or reordered.
+    """
+    prev_hash = GENESIS_HASH
+    count = 0
+    with Path(path).open(encoding="utf-8") as fh:
+        for lineno, line in enumerate(fh, start=1):
+            if not line.strip():
+                continue
+            try:
+                entry = json.loads(line)
+            except json.JSONDecodeError as e:
+                raise AuditIntegrityError(lineno, f"invalid JSON: {e}") from e
+            if entry.get("prev_hash") != prev_hash:
+                raise AuditIntegrityError(lineno, "previous hash does not match")
+            if entry.get("seq") != count + 1:
+                raise AuditIntegrityError(lineno, "sequence number out of order")
+            if entry.get("hash") != _entry_hash(entry):
+                raise AuditIntegrityError(lineno, "entry hash does not match its content")
+            prev_hash = entry["hash"]
+            count += 1
+    return count
@@ -0,0 +1,244 @@
+"""Prompt pipelines whose steps feed each other typed results.
+
+A :class:`Chain` runs templates in order. The output of each step is stored
+under the step's name and is available to the templates of later steps,
+so "summarize -> extract -> classify" needs no glue code::
+
+    class Facts(BaseModel):
+        people: list[str]
+        amounts: list[float]
+
+    chain = (
+        Chain(engine)
+        .step("summary", "summarize", model_cfg={"model": "gpt-4o-mini"})
+        .step("facts", "extract_facts", output=Facts, retries=2)  # {{ summary }} in the template
+        .step("label", "classify")  # {{ facts.people }} in the template
+    )
+    result = await chain.arun({"document": text})
+    result.outputs["facts"].people, result.output
+
+A step without ``output`` yields the completion text. With a pydantic model
+as ``output`` the completion is parsed as JSON (a Markdown fence around it
+is removed) and validated; ``output=dict`` keeps the parsed JSON as is. An
+output that does not parse is requested again up to ``retries`` times;
+transport errors are retried by the step's client, see ``RetryConfig``.
+Each step runs in a ``chain.step`` span inside a ``chain.run`` span, around
+the ``prompt.run`` span of the engine.
+"""
+
+from __future__ import annotations
+
+from dataclasses import dataclass, field
+from time import perf_counter
+from typing import TYPE_CHECKING, Any, Union
+
+from pydantic import BaseModel
+
+from ._otel import trace
+from .message import ModelResponse, StreamingModelResponse
+from .model_client.types import ModelConfig
+from .model_client.voting import parse_json_answer
+from .template import PromptTemplate
+
+if TYPE_CHECKING:
+    from .engine import PromptEngine
+
+__all__ = ["Chain", "ChainError", "ChainResult", "ChainStep", "StepResult"]
+
+_tracer = trace.get_tracer(__name__)
+
+
+@dataclass
+class ChainStep:
+    """One templated request of a :class:`Chain`, see :meth:`Chain.step`."""
+
+    name: str
+    template: str | PromptTemplate
+    output: type[BaseModel] | type[dict] | type[list] | None = None
+    model_cfg: ModelConfig | dict[str, Any] | None = None
+    variables: dict[str, Any] = field(default_factory=dict)
+    version: str | None = None
+    variant: str | None = None
+    retries: int = 0
+
+
+class StepResult(BaseModel):
+    """What one step produced."""
+
+    name: str
+    output: Any = None
+    text: str = ""
+    # the model that answered
+    model: str | None = None
+    # 1 + the times the output was requested again because it did not parse
+    attempts: int = 1
+    latency_s: float = 0.0
+
+
+class ChainResult(BaseModel):
+    """The results of all steps of a chain run, in order."""
+
+    steps: list[StepResult] = []
+    # step outputs by step name
+    outputs: dict[str, Any] = {}
+
+    @property
+    def output(self) -> Any:
+        """The output of the last step."""
+        return self.steps[-1].output if self.steps else None
+
+
+class ChainError(RuntimeError):
+    """A step failed; :attr:`result` holds the steps that completed before it."""
+
+    def __init__(self, step: str, message: str, result: ChainResult) -> None:
+        super().__init__(f"chain step {step!r} failed: {message}")
+        self.step = step
+        self.result = result
+
+
+def _parse(step: ChainStep, text: str) -> Any:
+    if step.output is None:
+        return text.strip()
+    value = parse_json_answer(text)
+    if isinstance(step.output, type) and issubclass(step.output, BaseModel):
+        return step.output.model_validate(value)
+    if not isinstance(value, step.output):
+        raise ValueError(f"expected a JSON {step.output.__name__}, got {type(value).__name__}")
+    return value
+
+
+def _final(responses: list[Union[ModelResponse, StreamingModelResponse]]) -> tuple[str, str | None]:
+    """Return the text and model of a finished call.
+
+    Raises:
+        RuntimeError: If the call ended with an error.
+    """
+    for response in responses:
+        if response.error:
+            raise RuntimeError(str(response.error.get("message") or response.error))
+    text = "".join(r.get_text_content() or "" for r in responses)
+    return text, next((r.model for r in reversed(responses) if r.model), None)
+
+
+class Chain:
+    """Builder and runner of a pipeline of templated requests.
+
+    Args:
+        engine: Resolves the templates and runs the requests.
+        name: Name of the pipeline, recorded on its span.
+    """
+
+    def __init__(self, engine: PromptEngine, name: str = "chain") -> None:
+        self.engine = engine
+        self.name = name
+        self.steps: list[ChainStep] = []
+
+    def step(
+        self,
+        name: str,
+        template: str | PromptTemplate,
+        *,
+        output: type[BaseModel] | type[dict] | type[list] | None = None,
+        model_cfg: ModelConfig | dict[str, Any] | None = None,
+        variables: dict[str, Any] | None = None,
+        version: str | None = None,
+        variant: str | None = None,
+        retries: int = 0,
+    ) -> Chain:
+        """Append a step and return the chain.
+
+        Args:
+            name: Variable the output is stored under for later steps.
+            template: Template name, or the template itself.
+            output: Pydantic model, ``dict`` or ``list`` the completion is
+                parsed into; the text when ``None``.
+            model_cfg: Model of this step, merged over the template's model.
+            variables: Fixed variables of this step, over the chain's.
+            version: Template version.
+            variant: Template variant, otherwise chosen from the variables.
+            retries: Times an output that does not parse is requested again.
+        """
+        if any(s.name == name for s in self.steps):
+            raise ValueError(f"chain already has a step {name!r}")
+        if retries < 0:
+            raise ValueError("retries must not be negative")
+        self.steps.append(
+            ChainStep(name, template, output, model_cfg, dict(variables or {}), version, variant, retries)
+        )
+        return self
+
+    def _call_args(self, step: ChainStep, variables: dict[str, Any]) -> tuple[tuple[Any, ...], dict[str, Any]]:
+        template = step.template if isinstance(step.template, PromptTemplate) else None
+        name = template.name if template is not None else step.template
+        # variants are selected from JSON, templates still render the typed outputs
+        ctx = {k: v.model_dump(mode="json") if isinstance(v, BaseModel) else v for k, v in variables.items()}
+        kwargs = {"version": step.version, "variant": step.variant, "template": template, "ctx": ctx, "stream": False}
+        return (name, variables, step.model_cfg), kwargs
+
+    def _variables(self, inputs: dict[str, Any], result: ChainResult, step: ChainStep) -> dict[str, Any]:
+        return {**inputs, **result.outputs, **step.variables}
+
+    def _record(
+        self, result: ChainResult, step: ChainStep, output: Any, text: str, model: str | None, attempts: int,
+        start: float,
+    ) -> None:
+        latency = perf_counter() - start
+        result.steps.append(
+            StepResult(name=step.name, output=output, text=text, model=model, attempts=attempts, latency_s=latency)
+        )
+        result.outputs[step.name] = output
+
+    async def arun(self, inputs: dict[str, Any] | None = None) -> ChainResult:
+        """Run the steps in order with the variables ``inputs``.
+
+        Raises:
+            ChainError: If a request fails or an output still does not parse after its retries.
+        """
+        inputs = dict(inputs or {})
+        result = ChainResult()
+        with _tracer.start_as_current_span("chain.run", attributes={"chain.name": self.name}):
+            for step in self.steps:
+                with _tracer.start_as_current_span("chain.step", attributes={"chain.step": step.name}) as span:
+                    start = perf_counter()
+                    args, kwargs = self._call_args(step, self._variables(inputs, result, step))
+                    for attempt in range(1, step.retries + 2):
+                        try:
+                            text, model = _final([r async for r in self.engine.acompletion(*args, **kwargs)])
+                        except Exception as e:
+                            raise ChainError(step.name, str(e), result) from e
+                        try:
+                            output = _parse(step, text)
+                            break
+                        except ValueError as e:  # also invalid JSON and pydantic validation errors
+                            error = e
+                    else:
+                        raise ChainError(step.name, f"invalid output after {attempt} attempts: {error}", result)
+                    span.set_attribute("chain.attempts", attempt)
+                    self._record(result, step, output, text, model, attempt, start)
+        return result
+
+    def run(self, inputs: dict[str, Any] | None = None) -> ChainResult:
+        """Sync variant of :meth:`arun`, using the engine's synchronous clients."""
+        inputs = dict(inputs or {})
+        result = ChainResult()
+        with _tracer.start_as_current_span("chain.run", attributes={"chain.name": self.name}):
+            for step in self.steps:
+                with _tracer.start_as_current_span("chain.step", attributes={"chain.step": step.name}) as span:
+                    start = perf_counter()
+                    args, kwargs = self._call_args(step, self._variables(inputs, result, step))
+                    for attempt in range(1, step.retries + 2):
+                        try:
+                            text, model = _final(list(self.engine.completion(*args, **kwargs)))
+                        except Exception as e:
+                            raise ChainError(step.name, str(e), result) from e
+                        try:
+                            output = _parse(step, text)
+                            break
+                        except ValueError as e:  # also invalid JSON and pydantic validation errors
+                            error = e
+                    else:
+                        raise ChainError(step.name, f"invalid output after {attempt} attempts: {error}", result)
+                    span.set_attribute("chain.attempts", attempt)
+                    self._record(result, step, output, text, model, attempt, start)
+        return result
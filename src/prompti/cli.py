@@ -0,0 +1,978 @@
+"""Command-line interface, installed as ``prompti`` with the ``cli`` extra.
+
+Usage: ``prompti [chat] -q 'What is the weather in Tokyo?'``.
+
+Run ``prompti doctor --provider openai`` to check which features a provider or
+OpenAI-compatible gateway supports, and ``prompti config validate <path>`` to
+check a configuration file. ``prompti compare --models a,b -q '...'`` runs the
+same query on several models and prints their answers side by side.
+``prompti tui`` opens an interactive chat (needs the ``tui`` extra).
+``prompti embed --file texts.jsonl --out vectors.jsonl`` embeds texts with an
+embedding model and ``prompti similar --index vectors.jsonl -q '...'`` finds
+the ones closest to a query.
+``python -m prompti`` is equivalent.
+
+Machine-readable output (``--stream-format ndjson|sse``, ``--json``) follows
+the versioned schema in ``docs/CLI_JSON.md``: every JSON document and event
+carries ``schema_version`` and a ``kind``/``type``. ``--schema-version 1``
+(or ``PROMPTI_SCHEMA_VERSION=1``) keeps the unversioned output of earlier
+releases for scripts that have not upgraded yet.
+"""
+
+from __future__ import annotations
+
+import argparse
+import asyncio
+import base64
+import json
+import logging
+import mimetypes
+import os
+import shlex
+import sys
+from datetime import datetime
+from time import perf_counter
+from typing import Any
+
+import httpx
+import yaml
+
+from .compare import _cosine, diff_responses
+from .config_validation import ConfigValidationError, validate_config_file
+from .documents import file_part
+from .engine import Setting
+from .message import ModelResponse, StreamingModelResponse, Usage
+from .model_client import (
+    Message,
+    ModelConfig,
+    RetryConfig,
+    RunParams,
+    ToolChoice,
+    ToolParams,
+    ToolSpec,
+    create_client,
+)
+from .model_client.config_loader import ModelConfigFile
+from .model_client.hints import remediation
+from .partial_json import merge_tool_call_deltas
+from .pricing import get_price_table
+from .streaming import sse_event, stream_events
+
+STREAM_FORMATS = ("text", "ndjson", "sse")
+
+# Version of the JSON written by the CLI, see docs/CLI_JSON.md. Bump it on any
+# change a consumer could notice other than a new optional field.
+SCHEMA_VERSION = 2
+SCHEMA_VERSIONS = (1, 2)
+
+# Providers that fetch ``image_url`` parts themselves; images for any other
+# provider are downloaded and inlined as base64 data URLs.
+URL_IMAGE_PROVIDERS = {"openai", "litellm", "qianfan"}
+
+SUBCOMMANDS = ("chat", "doctor", "config", "compare", "tui", "embed", "similar")
+
+# 1x1 red PNG used by the doctor vision check.
+PROBE_IMAGE = (
+    "data:image/png;base64,"
+    "iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAYAAAAfFcSJAAAADUlEQVR42mP8z8DwHwAFBQIAX8jx0gAAAABJRU5ErkJggg=="
+)
+
+
+def encode_file(path: str) -> dict[str, Any]:
+    """Return an OpenAI ``file`` content part for *path*."""
+    mime, _ = mimetypes.guess_type(path)
+    mime = mime or "application/octet-stream"
+    with open(path, "rb") as fh:
+        data = fh.read()
+    return file_part(filename=os.path.basename(path), data=data, media_type=mime)
+
+
+async def image_part(source: str, provider: str | None) -> dict[str, Any]:
+    """Return an ``image_url`` content part for a local path or a remote URL.
+
+    Local files are always inlined as base64. Remote URLs are passed through
+    when the provider can fetch them and downloaded and inlined otherwise.
+    """
+    is_remote = source.startswith(("http://", "https://"))
+    if is_remote and provider in URL_IMAGE_PROVIDERS:
+        url = source
+    elif is_remote:
+        async with httpx.AsyncClient(follow_redirects=True) as http:
+            resp = await http.get(source)
+            resp.raise_for_status()
+        mime = resp.headers.get("content-type", "").split(";")[0] or mimetypes.guess_type(source)[0] or "image/png"
+        url = f"data:{mime};base64,{base64.b64encode(resp.content).decode('ascii')}"
+    else:
+        mime = mimetypes.guess_type(source)[0] or "image/png"
+        with open(source, "rb") as fh:
+            url = f"data:{mime};base64,{base64.b64encode(fh.read()).decode('ascii')}"
+    return {"type": "image_url", "image_url": {"url": url}}
+
+
+def get_time(_: dict | None = None) -> str:
+    """Return the current UTC time in ISO format."""
+    return datetime.utcnow().isoformat() + "Z"
+
+
+def parse_tool_option(value: str) -> tuple[str, str]:
+    """Split a ``--tool name=command`` option into its name and command."""
+    name, sep, command = value.partition("=")
+    if not sep or not name.strip() or not command.strip():
+        raise argparse.ArgumentTypeError(f"expected name=command, got {value!r}")
+    return name.strip(), command.strip()
+
+
+def parse_price_option(value: str) -> tuple[str, tuple[float, float]]:
+    """Split a ``--price model=in,out`` option into the model and its per-million-token prices."""
+    model, sep, prices = value.partition("=")
+    try:
+        prompt_price, completion_price = (float(p) for p in prices.split(","))
+    except ValueError:
+        prompt_price = completion_price = None
+    if not sep or not model.strip() or prompt_price is None:
+        raise argparse.ArgumentTypeError(f"expected model=in,out, got {value!r}")
+    return model.strip(), (prompt_price, completion_price)
+
+
+def command_tool_spec(name: str, command: str) -> ToolSpec:
+    """Describe an external command as a tool accepting arbitrary JSON arguments."""
+    return ToolSpec(
+        name=name,
+        description=f"Run `{command}` with the call arguments as JSON on stdin and return its stdout",
+        parameters={"type": "object", "properties": {}, "additionalProperties": True},
+    )
+
+
+async def run_command_tool(command: str, arguments: str) -> str:
+    """Execute ``command`` with ``arguments`` (a JSON string) on stdin and return its output.
+
+    A non-zero exit status is reported back as the tool result so the model can react to it.
+    """
+    proc = await asyncio.create_subprocess_exec(
+        *shlex.split(command),
+        stdin=asyncio.subprocess.PIPE,
+        stdout=asyncio.subprocess.PIPE,
+        stderr=asyncio.subprocess.PIPE,
+    )
+    stdout, stderr = await proc.communicate((arguments or "{}").encode())
+    output = stdout.decode(errors="replace")
+    if proc.returncode != 0:
+        return f"Command exited with status {proc.returncode}: {stderr.decode(errors='replace').strip() or output}"
+    return output
+
+
+def setup_observability(port: int = 8000) -> None:
+    """Start Prometheus metrics server and configure console tracing.
+
+    Skipped with a warning when the ``metrics``/``tracing`` extras are missing.
+    """
+    try:
+        from opentelemetry import trace
+        from opentelemetry.sdk.trace import TracerProvider
+        from opentelemetry.sdk.trace.export import BatchSpanProcessor, ConsoleSpanExporter
+        from prometheus_client import start_http_server
+    except ImportError:
+        logging.getLogger(__name__).warning("Metrics and tracing are disabled; install 'prompti[cli]' to enable them")
+        return
+
+    start_http_server(port)
+    provider = TracerProvider()
+    # Spans go to stderr so that stdout only carries the model output.
+    processor = BatchSpanProcessor(ConsoleSpanExporter(out=sys.stderr))
+    provider.add_span_processor(processor)
+    trace.set_tracer_provider(provider)
+
+
+def versioned(document: dict[str, Any], kind: str | None, schema_version: int) -> dict[str, Any]:
+    """Return ``document`` in the JSON output format of ``schema_version``.
+
+    Version 1 is the unversioned format of earlier releases. From version 2
+    each document starts with ``schema_version`` and, unless it is a stream
+    event (typed by ``type``), the ``kind`` of document.
+    """
+    if schema_version < 2:
+        return document
+    header: dict[str, Any] = {"schema_version": schema_version}
+    if kind is not None:
+        header["kind"] = kind
+    return {**header, **document}
+
+
+def print_json(document: dict[str, Any], kind: str, schema_version: int) -> None:
+    """Print a ``--json`` document of ``kind`` on one line."""
+    print(json.dumps(versioned(document, kind, schema_version), ensure_ascii=False))
+
+
+def write_event(event: dict[str, Any], stream_format: str, schema_version: int = SCHEMA_VERSION) -> None:
+    """Write ``event`` to stdout in ``stream_format``.
+
+    In ``text`` format only the answer goes to stdout, so it can be piped;
+    reasoning and errors go to stderr.
+    """
+    if stream_format != "text":
+        event = versioned(event, None, schema_version)
+    if stream_format == "text":
+        if event["type"] == "content_delta":
+            sys.stdout.write(event["content"])
+        elif event["type"] == "reasoning_delta":
+            sys.stderr.write(event["content"])
+            sys.stderr.flush()
+        elif event["type"] == "message":
+            content = event["message"].get("content")
+            if isinstance(content, str):
+                sys.stdout.write(content)
+            for tool_call in event["message"].get("tool_calls") or []:
+                sys.stdout.write(f"\n[tool call] {json.dumps(tool_call, ensure_ascii=False)}")
+        elif event["type"] == "error":
+            sys.stderr.write(f"error: {event['error'].get('message', event['error'])}\n")
+            if event.get("hint"):
+                sys.stderr.write(f"hint: {event['hint']}\n")
+        elif event["type"] == "done":
+            sys.stdout.write("\n")
+    elif stream_format == "ndjson":
+        sys.stdout.write(json.dumps(event, ensure_ascii=False) + "\n")
+    else:
+        sys.stdout.write(sse_event(event))
+    sys.stdout.flush()
+
+
+class UsageSummary:
+    """Token and latency totals accumulated across the requests of one CLI run."""
+
+    def __init__(self, model: str | None = None, provider: str | None = None) -> None:
+        """Start with empty totals; ``model`` and ``provider`` look up the price for the cost estimate."""
+        self.model = model
+        self.provider = provider
+        self.requests = 0
+        self.prompt_tokens = 0
+        self.completion_tokens = 0
+        self.latency = 0.0
+        self.first_token_latency: float | None = None
+        # USD, ``None`` while no request could be priced
+        self.cost: float | None = None
+
+    def add(self, usage: Usage | None, perf_metrics: dict[str, float]) -> None:
+        """Record one request given its final usage and ``trace_context["perf_metrics"]``."""
+        self.requests += 1
+        if usage:
+            self.prompt_tokens += usage.prompt_tokens
+            self.completion_tokens += usage.completion_tokens
+            if self.model:
+                cost = get_price_table().cost(self.model, usage, self.provider)
+                if cost is not None:
+                    self.cost = (self.cost or 0.0) + cost
+        self.latency += perf_metrics.get("total_latency", 0.0)
+        if self.first_token_latency is None and "first_package_latency" in perf_metrics:
+            self.first_token_latency = perf_metrics["first_package_latency"]
+
+    def format(self) -> str:
+        """Return a human readable summary, with an estimated cost line when the model has a price."""
+        first = f"{self.first_token_latency * 1000:.0f} ms" if self.first_token_latency is not None else "n/a"
+        summary = (
+            f"usage: {self.requests} request(s), "
+            f"{self.prompt_tokens} prompt + {self.completion_tokens} completion = "
+            f"{self.prompt_tokens + self.completion_tokens} tokens, "
+            f"latency {self.latency * 1000:.0f} ms, first token {first}"
+        )
+        if self.cost is not None:
+            summary += f"\nestimated cost: ${self.cost:.6f}"
+        return summary
+
+
+def build_parser() -> argparse.ArgumentParser:
+    """Return the argument parser for all subcommands."""
+    common = argparse.ArgumentParser(add_help=False)
+    common.add_argument(
+        "--provider",
+        default=os.environ.get("PROMPTI_PROVIDER", "litellm"),
+        help="Model provider (default from PROMPTI_PROVIDER)",
+    )
+    common.add_argument(
+        "--model",
+        default="gpt-3.5-turbo",
+        help="Model name (default: gpt-3.5-turbo)",
+    )
+    common.add_argument("--api-url", help="Base URL for the LLM API")
+    common.add_argument("--api-key", help="API key for the provider")
+    common.add_argument(
+        "--max-retries",
+        type=int,
+        default=2,
+        help="Retries for rate limits, server errors and network failures (default: 2)",
+    )
+    common.add_argument(
+        "--retry-backoff-ms",
+        type=int,
+        default=500,
+        help="Initial retry backoff in milliseconds, doubled on each retry (default: 500)",
+    )
+    common.add_argument(
+        "--retry-jitter",
+        type=float,
+        default=0.0,
+        help="Fraction of each retry backoff that is randomized, 0 to 1 (default: 0)",
+    )
+
+    output = argparse.ArgumentParser(add_help=False)
+    output.add_argument(
+        "--schema-version",
+        type=int,
+        choices=SCHEMA_VERSIONS,
+        default=os.environ.get("PROMPTI_SCHEMA_VERSION", SCHEMA_VERSION),
+        help=f"Version of the JSON output format (default: {SCHEMA_VERSION}, or PROMPTI_SCHEMA_VERSION); "
+        "1 is the unversioned format of earlier releases",
+    )
+
+    parser = argparse.ArgumentParser(description="Simple LLM CLI")
+    subparsers = parser.add_subparsers(dest="command", required=True)
+    chat = subparsers.add_parser("chat", parents=[common, output], help="Send a query (default command)")
+    chat.add_argument("-q", "--query", required=True, help="Query text to send")
+    chat.add_argument(
+        "-f",
+        "--file",
+        action="append",
+        help="Path to a file to attach (may repeat)",
+    )
+    chat.add_argument(
+        "--image",
+        action="append",
+        metavar="PATH_OR_URL",
+        help="Attach an image from a local path or URL (may repeat)",
+    )
+    chat.add_argument(
+        "--time-tool",
+        action="store_true",
+        help="Enable built-in get_time tool",
+    )
+    chat.add_argument(
+        "--tool",
+        action="append",
+        type=parse_tool_option,
+        metavar="NAME=COMMAND",
+        help="Register a tool executed as a subprocess; arguments are passed as JSON on stdin (may repeat)",
+    )
+    chat.add_argument(
+        "--max-tool-rounds",
+        type=int,
+        default=8,
+        help="Maximum number of tool-calling round trips (default: 8)",
+    )
+    chat.add_argument(
+        "--reasoning",
+        action="store_true",
+        help="Request reasoning messages if supported",
+    )
+    chat.add_argument(
+        "--stream",
+        dest="stream",
+        action="store_true",
+        default=True,
+        help="Stream responses (default)",
+    )
+    chat.add_argument(
+        "--no-stream",
+        dest="stream",
+        action="store_false",
+        help="Disable streaming",
+    )
+    chat.add_argument(
+        "--stream-format",
+        choices=STREAM_FORMATS,
+        default="text",
+        help="Output format: plain text, one JSON event per line (ndjson) or server-sent events (sse)",
+    )
+    chat.add_argument(
+        "--usage",
+        action="store_true",
+        help="Print token usage and latency to stderr when the run finishes",
+    )
+
+    doctor = subparsers.add_parser(
+        "doctor",
+        parents=[common, output],
+        help="Run live capability checks against a provider",
+    )
+    doctor.add_argument("--no-vision", dest="vision", action="store_false", help="Skip the vision check")
+    doctor.add_argument("--json", action="store_true", help="Print the report as JSON")
+
+    compare = subparsers.add_parser(
+        "compare",
+        parents=[common, output],
+        help="Run a query on several models concurrently and show the answers side by side",
+    )
+    compare.add_argument("-q", "--query", "--prompt", required=True, help="Query text to send to every model")
+    compare.add_argument(
+        "--models",
+        metavar="MODEL,MODEL,...",
+        help="Comma separated models, each optionally as provider:model (default provider: --provider)",
+    )
+    compare.add_argument("--against", metavar="MODEL", help="Model to compare with --model, instead of --models")
+    compare.add_argument("--against-provider", help="Provider of the --against model (default: --provider)")
+    compare.add_argument(
+        "--price",
+        action="append",
+        type=parse_price_option,
+        metavar="MODEL=IN,OUT",
+        help="USD per million prompt and completion tokens of MODEL; overrides the bundled price table (may repeat)",
+    )
+    compare.add_argument(
+        "--width", type=int, default=60, help="Truncate responses in the table to this many characters (0: no limit)"
+    )
+    compare.add_argument("--json", action="store_true", help="Print the comparison as JSON")
+
+    tui = subparsers.add_parser(
+        "tui",
+        parents=[common],
+        help="Chat interactively in the terminal, switching between models (needs the 'tui' extra)",
+    )
+    tui.add_argument(
+        "--models",
+        metavar="MODEL,MODEL,...",
+        help="Comma separated models to switch between, each optionally as provider:model (default: --model)",
+    )
+    tui.add_argument("--models-file", metavar="PATH", help="Models file whose models to switch between")
+
+    embed = subparsers.add_parser(
+        "embed",
+        parents=[common],
+        help="Embed the texts of a JSON Lines file with an embedding model (--model)",
+    )
+    embed.add_argument(
+        "--file", required=True, help="JSON Lines file of strings or objects with a 'text' field, '-' for stdin"
+    )
+    embed.add_argument("--out", required=True, help="JSON Lines file to write the records with their embedding to")
+    embed.add_argument("--batch-size", type=int, default=64, help="Texts per embeddings request (default: 64)")
+
+    similar = subparsers.add_parser(
+        "similar",
+        parents=[common],
+        help="Find the texts of an 'embed' output file closest to a query",
+    )
+    similar.add_argument("-q", "--query", required=True, help="Text to search for")
+    similar.add_argument("--index", required=True, help="Output file of 'prompti embed'")
+    similar.add_argument("--top-k", type=int, default=5, help="Number of results (default: 5)")
+    similar.add_argument("--json", action="store_true", help="Print the results as JSON")
+
+    config = subparsers.add_parser("config", help="Work with configuration files")
+    config_commands = config.add_subparsers(dest="config_command", required=True)
+    validate = config_commands.add_parser("validate", help="Check a settings or models file against its schema")
+    validate.add_argument("path", help="YAML or JSON configuration file")
+    validate.add_argument(
+        "--kind",
+        choices=("auto", "setting", "models"),
+        default="auto",
+        help="Schema to validate against; 'auto' picks 'models' when the file has a top-level 'models' key",
+    )
+    validate.add_argument("--json", action="store_true", help="Print the result and every problem as JSON")
+    return parser
+
+
+def build_config(args: argparse.Namespace, *, provider: str | None = None, model: str | None = None) -> ModelConfig:
+    """Return the model config of the common connection options, optionally for another provider/model."""
+    return ModelConfig(
+        provider=provider or args.provider,
+        model=model or args.model,
+        api_key=args.api_key,
+        api_url=args.api_url,
+        retry=RetryConfig(
+            max_attempts=args.max_retries + 1, initial_backoff_ms=args.retry_backoff_ms, jitter=args.retry_jitter
+        ),
+    )
+
+
+def build_client(args: argparse.Namespace, *, provider: str | None = None, model: str | None = None):
+    """Create a model client from the common connection options, optionally for another provider/model."""
+    return create_client(build_config(args, provider=provider, model=model))
+
+
+async def run_chat(args: argparse.Namespace) -> int:  # noqa: C901 - command-line interface complexity
+    """Send the query, handling tool calls until the model produces a final answer."""
+    logging.basicConfig(level=logging.INFO, format="%(asctime)s %(levelname)s: %(message)s")
+
+    setup_observability()
+
+    client = build_client(args)
+
+    content: list[dict[str, Any]] = [encode_file(path) for path in args.file or []]
+    for source in args.image or []:
+        content.append(await image_part(source, args.provider))
+    content.append({"type": "text", "text": args.query})
+    messages: list[Message] = [Message.create_user(content if len(content) > 1 else args.query)]
+
+    tools: list[ToolSpec] = []
+    commands: dict[str, str] = {}
+    if args.time_tool:
+        tools.append(
+            ToolSpec(
+                name="get_time",
+                description="Return the current UTC time",
+                parameters={"type": "object", "properties": {}, "required": []},
+            )
+        )
+    for name, command in args.tool or []:
+        tools.append(command_tool_spec(name, command))
+        commands[name] = command
+    tool_params = ToolParams(tools=tools) if tools else None
+
+    stream = args.stream
+    extra_params = {}
+    if args.reasoning:
+        extra_params["enable_reasoning"] = True
+
+    summary = UsageSummary(client.cfg.model, client.cfg.provider)
+    try:
+        for _ in range(args.max_tool_rounds + 1):
+            logging.info("=== Response ===")
+            params = RunParams(
+                messages=messages,
+                tool_params=tool_params,
+                stream=stream,
+                extra_params=extra_params,
+            )
+            text = ""
+            tool_calls: dict[int, dict[str, Any]] = {}
+            usage = None
+            async for response in client.arun(params):
+                for event in stream_events(response):
+                    # schema version 1 predates hints
+                    if event["type"] == "error" and (args.stream_format == "text" or args.schema_version >= 2):
+                        event["hint"] = remediation(event["error"], cfg=client.cfg, params=params).hint
+                    write_event(event, args.stream_format, args.schema_version)
+                text += response.get_text_content() or ""
+                merge_tool_call_deltas(tool_calls, response.get_tool_calls() or [])
+                usage = response.usage or usage
+            summary.add(usage, params.trace_context.get("perf_metrics", {}))
+            write_event({"type": "done"}, args.stream_format, args.schema_version)
+
+            if not tool_calls:
+                break
+
+            calls = [tool_calls[i] for i in sorted(tool_calls)]
+            messages.append(Message(role="assistant", content=text or None, tool_calls=calls))
+            for call in calls:
+                name = call["function"]["name"]
+                if name in commands:
+                    result = await run_command_tool(commands[name], call["function"]["arguments"])
+                elif name == "get_time":
+                    result = get_time(json.loads(call["function"]["arguments"] or "{}"))
+                else:
+                    result = f"No handler for tool {name}"
+                messages.append(Message.create_tool_result(result, call["id"]))
+        else:
+            logging.warning("Stopped after %d tool rounds", args.max_tool_rounds)
+    finally:
+        await client.aclose()
+        if args.usage:
+            sys.stderr.write(summary.format() + "\n")
+    return 0
+
+
+async def probe(client, params: RunParams) -> dict[str, Any]:
+    """Run ``params`` against ``client`` and summarise what came back."""
+    result: dict[str, Any] = {"text": "", "tool_calls": [], "error": None, "chunks": 0, "usage": None}
+    start = perf_counter()
+    try:
+        async for response in client.arun(params):
+            result["chunks"] += 1
+            if response.error:
+                result["error"] = response.error.get("message", str(response.error))
+            result["text"] += response.get_text_content() or ""
+            result["tool_calls"].extend(response.get_tool_calls() or [])
+            result["usage"] = response.usage or result["usage"]
+    except Exception as exc:  # noqa: BLE001 - any failure is part of the report
+        result["error"] = str(exc)
+    result["latency_ms"] = round((perf_counter() - start) * 1000)
+    return result
+
+
+def is_auth_error(message: str) -> bool:
+    """Return whether an error message looks like rejected credentials."""
+    lowered = message.lower()
+    return any(marker in lowered for marker in ("401", "403", "unauthorized", "forbidden", "api key", "api_key"))
+
+
+async def doctor_checks(client, vision: bool = True) -> list[dict[str, Any]]:  # noqa: C901 - one branch per check
+    """Run the conformance checks and return one report row per check.
+
+    ``auth``, ``chat`` and ``stream`` are required; a failure there is reported
+    as ``fail``. Tool calling, JSON mode and vision are optional features, so a
+    provider rejecting them is reported as ``unsupported``.
+    """
+    report: list[dict[str, Any]] = []
+
+    def add(check: str, status: str, detail: str = "", latency_ms: int | None = None) -> None:
+        report.append({"check": check, "status": status, "detail": detail, "latency_ms": latency_ms})
+
+    ping = [Message.create_user("Reply with the single word: pong")]
+    result = await probe(client, RunParams(messages=ping, stream=False, max_tokens=16))
+    if result["error"] and is_auth_error(result["error"]):
+        add("auth", "fail", result["error"], result["latency_ms"])
+        for check in ("chat", "stream", "tools", "json_mode", "vision"):
+            add(check, "skipped", "authentication failed")
+        return report
+    add("auth", "pass")
+    if result["error"]:
+        add("chat", "fail", result["error"], result["latency_ms"])
+    elif not result["text"].strip():
+        add("chat", "fail", "empty response", result["latency_ms"])
+    else:
+        add("chat", "pass", result["text"].strip()[:40], result["latency_ms"])
+
+    result = await probe(client, RunParams(messages=ping, stream=True, max_tokens=16))
+    if result["error"]:
+        add("stream", "fail", result["error"], result["latency_ms"])
+    elif not result["text"].strip():
+        add("stream", "fail", "no content in stream", result["latency_ms"])
+    else:
+        add("stream", "pass", f"{result['chunks']} chunks", result["latency_ms"])
+
+    tool = ToolSpec(
+        name="get_time",
+        description="Return the current UTC time",
+        parameters={"type": "object", "properties": {}, "required": []},
+    )
+    result = await probe(
+        client,
+        RunParams(
+            messages=[Message.create_user("What time is it? Use the get_time tool.")],
+            tool_params=ToolParams(tools=[tool], choice=ToolChoice.REQUIRED),
+            stream=False,
+        ),
+    )
+    if result["error"]:
+        add("tools", "unsupported", result["error"], result["latency_ms"])
+    elif not any((call.get("function") or {}).get("name") == "get_time" for call in result["tool_calls"]):
+        add("tools", "unsupported", "model did not call the tool", result["latency_ms"])
+    else:
+        add("tools", "pass", "", result["latency_ms"])
+
+    result = await probe(
+        client,
+        RunParams(
+            messages=[Message.create_user('Return a JSON object of the form {"answer": "pong"}.')],
+            response_format="json_object",
+            stream=False,
+        ),
+    )
+    if result["error"]:
+        add("json_mode", "unsupported", result["error"], result["latency_ms"])
+    else:
+        try:
+            json.loads(result["text"])
+        except json.JSONDecodeError:
+            add("json_mode", "unsupported", "response is not valid JSON", result["latency_ms"])
+        else:
+            add("json_mode", "pass", "", result["latency_ms"])
+
+    if not vision:
+        add("vision", "skipped", "disabled with --no-vision")
+    else:
+        content = [
+            {"type": "image_url", "image_url": {"url": PROBE_IMAGE}},
+            {"type": "text", "text": "What colour is this image? Answer in one word."},
+        ]
+        result = await probe(client, RunParams(messages=[Message.create_user(content)], stream=False, max_tokens=16))
+        if result["error"]:
+            add("vision", "unsupported", result["error"], result["latency_ms"])
+        else:
+            add("vision", "pass", result["text"].strip()[:40], result["latency_ms"])
+    return report
+
+
+async def run_doctor(args: argparse.Namespace) -> int:
+    """Print a capability report for the configured provider.
+
+    Returns a non-zero exit status when a required check fails.
+    """
+    logging.basicConfig(level=logging.WARNING, format="%(asctime)s %(levelname)s: %(message)s")
+    client = build_client(args)
+    try:
+        report = await doctor_checks(client, vision=args.vision)
+    finally:
+        await client.aclose()
+
+    if args.json:
+        print_json({"provider": args.provider, "model": args.model, "checks": report}, "doctor", args.schema_version)
+    else:
+        print(f"provider: {args.provider}  model: {args.model}")
+        for row in report:
+            latency = f"{row['latency_ms']} ms" if row["latency_ms"] is not None else ""
+            print(f"  {row['check']:<10} {row['status']:<12} {latency:>8}  {row['detail']}")
+    return 1 if any(row["status"] == "fail" for row in report) else 0
+
+
+def compare_targets(args: argparse.Namespace) -> list[tuple[str, str]]:
+    """Return the ``(provider, model)`` pairs to compare.
+
+    ``--models`` entries are ``model`` or ``provider:model``; without it the
+    comparison is ``--model`` against ``--against``.
+    """
+    if args.models:
+        targets = []
+        for entry in args.models.split(","):
+            provider, sep, model = entry.strip().rpartition(":")
+            targets.append((provider if sep else args.provider, model))
+    elif args.against:
+        targets = [(args.provider, args.model), (args.against_provider or args.provider, args.against)]
+    else:
+        targets = []
+    if len(targets) < 2 or not all(model for _, model in targets):
+        raise SystemExit("compare: give at least two models with --models a,b or --model a --against b")
+    return targets
+
+
+def cell(text: str, width: int) -> str:
+    """Fit ``text`` into one markdown table cell of at most ``width`` characters (0 for no limit)."""
+    text = " ".join(text.split()).replace("|", "\\|")
+    if width and len(text) > width:
+        text = text[: width - 1] + "…"
+    return text
+
+
+def compare_table(rows: list[dict[str, Any]], width: int) -> str:
+    """Render the compared models side by side as a markdown table."""
+    lines = [
+        "| model | latency | tokens in/out | cost | similarity | response |",
+        "|---|---:|---:|---:|---:|---|",
+    ]
+    for row in rows:
+        usage = row["usage"]
+        tokens = f"{usage['prompt_tokens']}/{usage['completion_tokens']}" if usage else "-"
+        cost = f"${row['cost']:.6f}" if row["cost"] is not None else "-"
+        similarity = f"{row['similarity']:.2f}" if row["similarity"] is not None else "-"
+        response = f"error: {row['error']}" if row["error"] else row["text"]
+        lines.append(
+            f"| {row['model']} | {row['latency_ms']} ms | {tokens} | {cost} | {similarity} | {cell(response, width)} |"
+        )
+    return "\n".join(lines)
+
+
+async def run_compare(args: argparse.Namespace) -> int:
+    """Run the query on all models concurrently and print them side by side.
+
+    The similarity column compares each answer with the first model's. With
+    exactly two models the markdown diff of the answers follows the table.
+    Returns a non-zero exit status when any model fails.
+    """
+    logging.basicConfig(level=logging.WARNING, format="%(asctime)s %(levelname)s: %(message)s")
+    targets = compare_targets(args)
+    prices = dict(args.price or [])
+    clients = [build_client(args, provider=provider, model=model) for provider, model in targets]
+    params = RunParams(messages=[Message.create_user(args.query)], stream=False)
+    try:
+        results = await asyncio.gather(*(probe(client, params) for client in clients))
+    finally:
+        for client in clients:
+            await client.aclose()
+
+    rows = []
+    for (provider, model), result in zip(targets, results):
+        usage, price = result["usage"], prices.get(model)
+        if usage and price:
+            cost = (usage.prompt_tokens * price[0] + usage.completion_tokens * price[1]) / 1_000_000
+        else:
+            cost = get_price_table().cost(model, usage, provider)
+        similarity = None
+        if rows and not result["error"] and not results[0]["error"]:
+            similarity = diff_responses(results[0]["text"], result["text"]).token_similarity
+        rows.append(
+            {
+                "model": model,
+                "latency_ms": result["latency_ms"],
+                "usage": usage.model_dump() if usage else None,
+                "cost": cost,
+                "similarity": similarity,
+                "text": result["text"],
+                "error": result["error"],
+            }
+        )
+
+    if args.json:
+        print_json({"models": rows}, "compare", args.schema_version)
+    else:
+        print(compare_table(rows, args.width))
+        if len(rows) == 2:
+            print()
+            labels = (rows[0]["model"], rows[1]["model"])
+            print(diff_responses(results[0]["text"], results[1]["text"], labels=labels).markdown, end="")
+    return 1 if any(r["error"] for r in results) else 0
+
+
+def read_records(path: str) -> list[dict[str, Any]]:
+    """Return the records of a JSON Lines file; a line holding a JSON string becomes ``{"text": ...}``."""
+    f = sys.stdin if path == "-" else open(path, encoding="utf-8")
+    try:
+        records = []
+        for number, line in enumerate(f, 1):
+            if not line.strip():
+                continue
+            record = json.loads(line)
+            if isinstance(record, str):
+                record = {"text": record}
+            if not isinstance(record, dict) or not isinstance(record.get("text"), str):
+                raise ValueError(f"{path}:{number}: expected a string or an object with a 'text' string")
+            records.append(record)
+        return records
+    finally:
+        if f is not sys.stdin:
+            f.close()
+
+
+async def embed_texts(client, texts: list[str], batch_size: int = 64) -> list[list[float]]:
+    """Return the embeddings of ``texts`` from ``client.aembeddings``, ``batch_size`` texts per request."""
+    vectors: list[list[float]] = []
+    for start in range(0, len(texts), batch_size):
+        data = await client.aembeddings({"input": texts[start : start + batch_size]})
+        vectors.extend(item["embedding"] for item in sorted(data["data"], key=lambda item: item["index"]))
+    return vectors
+
+
+async def run_embed(args: argparse.Namespace) -> int:
+    """Write every record of ``--file`` with its embedding to ``--out``."""
+    logging.basicConfig(level=logging.WARNING, format="%(asctime)s %(levelname)s: %(message)s")
+    records = read_records(args.file)
+    client = build_client(args)
+    try:
+        vectors = await embed_texts(client, [r["text"] for r in records], max(args.batch_size, 1))
+    except (NotImplementedError, httpx.HTTPError) as e:
+        print(f"embed: {e}", file=sys.stderr)
+        return 1
+    finally:
+        await client.aclose()
+    with open(args.out, "w", encoding="utf-8") as f:
+        for record, vector in zip(records, vectors):
+            f.write(json.dumps({**record, "embedding": vector}, ensure_ascii=False) + "\n")
+    print(f"embedded {len(records)} text(s) into {args.out}", file=sys.stderr)
+    return 0
+
+
+async def run_similar(args: argparse.Namespace) -> int:
+    """Print the ``--top-k`` records of ``--index`` most similar to the query by cosine similarity."""
+    logging.basicConfig(level=logging.WARNING, format="%(asctime)s %(levelname)s: %(message)s")
+    records = read_records(args.index)
+    client = build_client(args)
+    try:
+        [query] = await embed_texts(client, [args.query])
+    except (NotImplementedError, httpx.HTTPError) as e:
+        print(f"similar: {e}", file=sys.stderr)
+        return 1
+    finally:
+        await client.aclose()
+    scored = sorted(
+        ((_cosine(query, r["embedding"]), r) for r in records if r.get("embedding")), key=lambda item: -item[0]
+    )[: args.top_k]
+    if args.json:
+        results = [{"score": score, **{k: v for k, v in r.items() if k != "embedding"}} for score, r in scored]
+        # ``similar --json`` is newer than schema version 1, so it is always versioned
+        print_json({"results": results}, "similar", SCHEMA_VERSION)
+    else:
+        for score, record in scored:
+            print(f"{score:.4f}\t{record['text']}")
+    return 0
+
+
+def tui_models(args: argparse.Namespace) -> list[ModelConfig]:
+    """Return the models the chat interface switches between: ``--models-file``, ``--models`` or ``--model``."""
+    if args.models_file:
+        with open(args.models_file, encoding="utf-8") as f:
+            models = ModelConfigFile.model_validate(yaml.safe_load(f) or {}).models
+        if not models:
+            raise SystemExit(f"tui: no models in {args.models_file}")
+        return models
+    if args.models:
+        targets = []
+        for entry in args.models.split(","):
+            provider, sep, model = entry.strip().rpartition(":")
+            targets.append(build_config(args, provider=provider if sep else None, model=model))
+        return targets
+    return [build_config(args)]
+
+
+async def run_tui(args: argparse.Namespace) -> int:
+    """Open the interactive chat interface."""
+    from .tui import ChatSession, arun_app
+
+    # 日志会打乱界面，只保留错误
+    logging.basicConfig(level=logging.ERROR, format="%(asctime)s %(levelname)s: %(message)s")
+    session = ChatSession(tui_models(args))
+    try:
+        await arun_app(session)
+    except ImportError as e:
+        raise SystemExit(f"tui: {e}") from e
+    finally:
+        await session.aclose()
+    return 0
+
+
+def run_config_validate(args: argparse.Namespace) -> int:
+    """Validate a configuration file and print every problem found."""
+    kind = args.kind
+    if kind == "auto":
+        try:
+            with open(args.path) as f:
+                data = yaml.safe_load(f)
+        except (OSError, yaml.YAMLError):
+            data = None
+        kind = "models" if isinstance(data, dict) and "models" in data else "setting"
+    schema = ModelConfigFile if kind == "models" else Setting
+    # ``config validate --json`` is newer than schema version 1, so it is always versioned
+    result: dict[str, Any] = {"path": args.path, "schema": kind, "ok": False, "issues": []}
+    status = 0
+    try:
+        validate_config_file(args.path, schema)
+    except FileNotFoundError as e:
+        print(f"{args.path}: {e.strerror}", file=sys.stderr)
+        result["error"] = e.strerror
+        status = 2
+    except ConfigValidationError as e:
+        for issue in e.issues:
+            print(issue.format(e.source), file=sys.stderr)
+        result["issues"] = [
+            {"path": list(i.path), "message": i.message, "line": i.line, "column": i.column} for i in e.issues
+        ]
+        status = 1
+    else:
+        result["ok"] = True
+        if not args.json:
+            print(f"{args.path}: ok ({kind})")
+    if args.json:
+        print_json(result, "config_validate", SCHEMA_VERSION)
+    return status
+
+
+async def main(argv: list[str] | None = None) -> int:
+    """Run the command-line interface."""
+    argv = list(sys.argv[1:] if argv is None else argv)
+    # ``chat`` is the default so existing ``-q ...`` invocations keep working.
+    if not argv or argv[0] not in (*SUBCOMMANDS, "-h", "--help"):
+        argv.insert(0, "chat")
+    parser = build_parser()
+    args = parser.parse_args(argv)
+    # argparse does not check defaults against ``choices``, so PROMPTI_SCHEMA_VERSION is checked here
+    if getattr(args, "schema_version", SCHEMA_VERSION) not in SCHEMA_VERSIONS:
+        parser.error(
+            f"PROMPTI_SCHEMA_VERSION must be one of {', '.join(map(str, SCHEMA_VERSIONS))}, "
+            f"got {args.schema_version}"
+        )
+    if args.command == "doctor":
+        return await run_doctor(args)
+    if args.command == "config":
+        return run_config_validate(args)
+    if args.command == "compare":
+        return await run_compare(args)
+    if args.command == "tui":
+        return await run_tui(args)
+    if args.command == "embed":
+        return await run_embed(args)
+    if args.command == "similar":
+        return await run_similar(args)
+    return await run_chat(args)
+
+
+def run() -> None:
+    """Console script entry point."""
+    sys.exit(asyncio.run(main()))
+
+
+if __name__ == "__main__":
+    run()
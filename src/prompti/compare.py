@@ -0,0 +1,108 @@
+"""Compare the outputs of two models.
+
+:func:`diff_responses` scores how much two responses differ and renders the
+difference as markdown. It is used by :class:`~prompti.shadow.ShadowClient`
+and ``prompti compare``::
+
+    diff = diff_responses(old_response, new_response, labels=("gpt-4o", "qwen-max"))
+    diff.token_similarity     # 0..1, token-level overlap
+    diff.semantic_similarity  # 0..1 cosine similarity, only with ``embed``
+    print(diff.markdown)
+
+Tokens are words, punctuation marks and single CJK characters, so the score
+behaves the same for Chinese and English text. The semantic score needs an
+``embed`` function mapping a text to a vector (an embedding model of your
+choice); prompti ships none.
+"""
+
+from __future__ import annotations
+
+import difflib
+import math
+import re
+from collections.abc import Callable, Sequence
+from typing import Union
+
+from pydantic import BaseModel
+
+from .message import ModelResponse, StreamingModelResponse
+
+__all__ = ["ResponseDiff", "diff_responses", "tokenize"]
+
+Comparable = Union[str, ModelResponse, StreamingModelResponse, None]
+
+_CJK = "\u3040-\u30ff\u3400-\u4dbf\u4e00-\u9fff\uac00-\ud7af\uf900-\ufaff"
+_TOKEN = re.compile(rf"[{_CJK}]|[^\W{_CJK}]+|[^\w\s]")
+
+
+def tokenize(text: str) -> list[str]:
+    """Split ``text`` into words, punctuation marks and single CJK characters."""
+    return _TOKEN.findall(text)
+
+
+class ResponseDiff(BaseModel):
+    """Difference between two responses."""
+
+    token_similarity: float
+    tokens_added: int
+    tokens_removed: int
+    semantic_similarity: float | None = None
+    markdown: str
+
+
+def _text(response: Comparable) -> str:
+    if response is None or isinstance(response, str):
+        return response or ""
+    return response.get_text_content() or ""
+
+
+def _cosine(a: Sequence[float], b: Sequence[float]) -> float:
+    dot = sum(x * y for x, y in zip(a, b))
+    norm = math.sqrt(sum(x * x for x in a)) * math.sqrt(sum(y * y for y in b))
+    return dot / norm if norm else 0.0
+
+
+def _markdown(a: str, b: str, labels: tuple[str, str], scores: str) -> str:
+    lines = list(
+        difflib.unified_diff(a.splitlines(), b.splitlines(), fromfile=labels[0], tofile=labels[1], lineterm="")
+    )
+    body = "\n".join(lines) if lines else "(identical)"
+    return f"**{labels[0]}** vs **{labels[1]}**: {scores}\n\n```diff\n{body}\n```\n"
+
+
+def diff_responses(
+    a: Comparable,
+    b: Comparable,
+    *,
+    embed: Callable[[str], Sequence[float]] | None = None,
+    labels: tuple[str, str] = ("a", "b"),
+) -> ResponseDiff:
+    """Return token-level and, with ``embed``, semantic difference scores of ``a`` and ``b``.
+
+    ``a`` and ``b`` are texts or complete model responses. Two empty texts are
+    identical; an empty and a non-empty one have similarity 0.
+    """
+    text_a, text_b = _text(a), _text(b)
+    tokens_a, tokens_b = tokenize(text_a), tokenize(text_b)
+    matcher = difflib.SequenceMatcher(None, tokens_a, tokens_b, autojunk=False)
+    added = removed = 0
+    for op, i1, i2, j1, j2 in matcher.get_opcodes():
+        if op != "equal":
+            removed += i2 - i1
+            added += j2 - j1
+    token_similarity = matcher.ratio() if tokens_a or tokens_b else 1.0
+
+    semantic = None
+    if embed is not None:
+        semantic = 1.0 if text_a == text_b else _cosine(embed(text_a), embed(text_b))
+
+    scores = f"token similarity {token_similarity:.2f} (+{added} / -{removed} tokens)"
+    if semantic is not None:
+        scores += f", semantic similarity {semantic:.2f}"
+    return ResponseDiff(
+        token_similarity=token_similarity,
+        tokens_added=added,
+        tokens_removed=removed,
+        semantic_similarity=semantic,
+        markdown=_markdown(text_a, text_b, labels, scores),
+    )
@@ -0,0 +1,179 @@
+"""Validate configuration files with file/line context in error messages."""
+
+from __future__ import annotations
+
+import difflib
+import types
+from dataclasses import dataclass
+from pathlib import Path
+from typing import Any, Union, get_args, get_origin
+
+import yaml
+from pydantic import BaseModel, ValidationError
+
+Position = tuple[int, int]
+
+
+@dataclass(kw_only=True)
+class ConfigIssue:
+    """A single problem found in a configuration file."""
+
+    path: tuple[Any, ...]
+    message: str
+    line: int | None = None
+    column: int | None = None
+
+    def format(self, source: str | None = None) -> str:
+        """Return ``file:line:column: key.path: message``."""
+        location = source or "<config>"
+        if self.line is not None:
+            location += f":{self.line}:{self.column}"
+        key = ".".join(str(p) for p in self.path) or "<root>"
+        return f"{location}: {key}: {self.message}"
+
+
+class ConfigValidationError(ValueError):
+    """Raised when a configuration file does not match its schema."""
+
+    def __init__(self, issues: list[ConfigIssue], source: str | None = None) -> None:
+        self.issues = issues
+        self.source = source
+        lines = [issue.format(source) for issue in issues]
+        super().__init__(f"Invalid configuration ({len(issues)} problem(s)):\n" + "\n".join(lines))
+
+
+def load_yaml_with_positions(text: str) -> tuple[Any, dict[tuple[Any, ...], Position]]:
+    """Parse YAML ``text`` and return the data plus the 1-based position of every key path."""
+    data = yaml.safe_load(text)
+    positions: dict[tuple[Any, ...], Position] = {}
+    node = yaml.compose(text, Loader=yaml.SafeLoader)
+
+    def walk(current: yaml.Node | None, path: tuple[Any, ...]) -> None:
+        if isinstance(current, yaml.MappingNode):
+            for key_node, value_node in current.value:
+                child = (*path, key_node.value)
+                positions[child] = (key_node.start_mark.line + 1, key_node.start_mark.column + 1)
+                walk(value_node, child)
+        elif isinstance(current, yaml.SequenceNode):
+            for index, item in enumerate(current.value):
+                child = (*path, index)
+                positions[child] = (item.start_mark.line + 1, item.start_mark.column + 1)
+                walk(item, child)
+
+    walk(node, ())
+    return data, positions
+
+
+def _nested_models(annotation: Any) -> list[tuple[str, type[BaseModel]]]:
+    """Return ``(container, model)`` pairs for BaseModel types inside ``annotation``.
+
+    ``container`` is ``"value"``, ``"list"`` or ``"dict"`` and tells how to reach
+    the nested model from the field's raw data.
+    """
+    origin = get_origin(annotation)
+    if isinstance(annotation, type) and issubclass(annotation, BaseModel):
+        return [("value", annotation)]
+    if origin in (Union, types.UnionType):
+        return [pair for arg in get_args(annotation) for pair in _nested_models(arg)]
+    if origin in (list, tuple, set):
+        return [("list", m) for _, m in _nested_models(get_args(annotation)[0])] if get_args(annotation) else []
+    if origin is dict and len(get_args(annotation)) == 2:
+        return [("dict", m) for _, m in _nested_models(get_args(annotation)[1])]
+    return []
+
+
+def _check_keys(data: Any, model_cls: type[BaseModel], path: tuple[Any, ...], issues: list[ConfigIssue]) -> None:
+    """Report unknown and mutually exclusive keys of ``data`` against ``model_cls``, recursively."""
+    if not isinstance(data, dict):
+        return
+    fields = model_cls.model_fields
+    known = set(fields) | {f.alias for f in fields.values() if f.alias}
+    for key in data:
+        if key not in known:
+            message = f"unknown key '{key}'"
+            suggestion = difflib.get_close_matches(str(key), sorted(known), n=1)
+            if suggestion:
+                message += f"; did you mean '{suggestion[0]}'?"
+            issues.append(ConfigIssue(path=(*path, key), message=message))
+
+    for group in getattr(model_cls, "exclusive_fields", ()):
+        present = [name for name in group if data.get(name) is not None]
+        if len(present) > 1:
+            message = f"'{present[0]}' and '{present[1]}' are mutually exclusive"
+            issues.append(ConfigIssue(path=(*path, present[1]), message=message))
+
+    for name, field in fields.items():
+        value = data.get(name)
+        if value is None:
+            continue
+        for container, nested in _nested_models(field.annotation):
+            if container == "value" and isinstance(value, dict):
+                _check_keys(value, nested, (*path, name), issues)
+            elif container == "list" and isinstance(value, list):
+                for index, item in enumerate(value):
+                    _check_keys(item, nested, (*path, name, index), issues)
+            elif container == "dict" and isinstance(value, dict):
+                for key, item in value.items():
+                    _check_keys(item, nested, (*path, name, key), issues)
+
+
+def _locate(path: tuple[Any, ...], positions: dict[tuple[Any, ...], Position]) -> Position | None:
+    """Return the position of ``path`` or of its closest ancestor."""
+    while path:
+        if path in positions:
+            return positions[path]
+        path = path[:-1]
+    return None
+
+
+def validate_config(
+    data: Any,
+    model_cls: type[BaseModel],
+    *,
+    source: str | None = None,
+    positions: dict[tuple[Any, ...], Position] | None = None,
+) -> BaseModel:
+    """Validate ``data`` against ``model_cls`` and return the parsed model.
+
+    Unknown keys (with a close-match suggestion), type mismatches and
+    violations of a model's ``exclusive_fields`` groups are all collected and
+    raised together as a :class:`ConfigValidationError`.
+    """
+    issues: list[ConfigIssue] = []
+    if not isinstance(data, dict):
+        issues.append(ConfigIssue(path=(), message=f"expected a mapping, got {type(data).__name__}"))
+        raise ConfigValidationError(issues, source)
+
+    _check_keys(data, model_cls, (), issues)
+    result = None
+    try:
+        result = model_cls.model_validate(data)
+    except ValidationError as e:
+        for err in e.errors():
+            message = err["msg"]
+            if err["type"] != "missing":
+                message += f" (got {err['input']!r})"
+            issues.append(ConfigIssue(path=tuple(err["loc"]), message=message))
+
+    if issues:
+        for issue in issues:
+            position = _locate(issue.path, positions or {})
+            if position:
+                issue.line, issue.column = position
+        issues.sort(key=lambda i: (i.line or 0, i.column or 0))
+        raise ConfigValidationError(issues, source)
+    return result
+
+
+def validate_config_file(path: str | Path, model_cls: type[BaseModel]) -> BaseModel:
+    """Load the YAML/JSON file at ``path`` and validate it against ``model_cls``."""
+    path = Path(path)
+    try:
+        data, positions = load_yaml_with_positions(path.read_text())
+    except yaml.YAMLError as e:
+        mark = getattr(e, "problem_mark", None)
+        issue = ConfigIssue(path=(), message=f"invalid YAML: {getattr(e, 'problem', None) or e}")
+        if mark is not None:
+            issue.line, issue.column = mark.line + 1, mark.column + 1
+        raise ConfigValidationError([issue], str(path)) from e
+    return validate_config(data, model_cls, source=str(path), positions=positions)
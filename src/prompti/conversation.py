@@ -0,0 +1,109 @@
+"""Conversations that fork cheaply at any message.
+
+Agent search and tree-of-thought experiments continue one history in many
+directions. :class:`Conversation` stores messages as a chain of immutable
+links, so a fork shares the messages before the branch point with its
+parent instead of copying them::
+
+    root = Conversation([Message.create_system("You are helpful."), Message.create_user("Plan a trip.")])
+    for idea in ideas:
+        branch = root.fork()
+        branch.append(Message.create_user(idea))
+        async for r in client.arun(RunParams(messages=branch.messages)):
+            ...
+
+Appending to one branch never changes another. The messages themselves are
+shared, so treat them as read-only.
+"""
+
+from __future__ import annotations
+
+from collections.abc import Iterable, Iterator
+from dataclasses import dataclass
+
+from .message import Message
+
+__all__ = ["Conversation"]
+
+
+@dataclass(frozen=True, slots=True)
+class _Link:
+    message: Message
+    parent: _Link | None
+    # messages up to and including this one
+    length: int
+
+
+class Conversation:
+    """A message history whose forks share their common prefix."""
+
+    def __init__(self, messages: Iterable[Message] = ()) -> None:
+        self._last: _Link | None = None
+        self.extend(messages)
+
+    def append(self, message: Message) -> None:
+        """Add ``message`` to the end of this branch."""
+        self._last = _Link(message, self._last, len(self) + 1)
+
+    def extend(self, messages: Iterable[Message]) -> None:
+        """Add ``messages`` to the end of this branch, in order."""
+        for message in messages:
+            self.append(message)
+
+    def _link_at(self, length: int) -> _Link | None:
+        """Return the link that ends the first ``length`` messages."""
+        link = self._last
+        while link is not None and link.length > length:
+            link = link.parent
+        return link
+
+    def fork(self, at: int | None = None) -> Conversation:
+        """Return a new branch holding the first ``at`` messages (all when ``None``, negative counts from the end).
+
+        Raises:
+            IndexError: If ``at`` is outside the conversation.
+        """
+        size = len(self)
+        if at is None:
+            at = size
+        elif at < 0:
+            at += size
+        if not 0 <= at <= size:
+            raise IndexError(f"cannot fork a conversation of {size} messages at {at}")
+        branch = Conversation()
+        branch._last = self._link_at(at)
+        return branch
+
+    def common_prefix(self, other: Conversation) -> int:
+        """Return how many leading messages this branch shares with ``other``."""
+        mine = self._link_at(len(other))
+        theirs = other._link_at(len(self))
+        while mine is not theirs:
+            mine, theirs = mine.parent, theirs.parent  # type: ignore[union-attr]
+        return 0 if mine is None else mine.length
+
+    @property
+    def messages(self) -> list[Message]:
+        """The messages of this branch as a new list, oldest first."""
+        return list(self)
+
+    def __len__(self) -> int:
+        return 0 if self._last is None else self._last.length
+
+    def __iter__(self) -> Iterator[Message]:
+        messages = []
+        link = self._last
+        while link is not None:
+            messages.append(link.message)
+            link = link.parent
+        return reversed(messages)
+
+    def __getitem__(self, index: int) -> Message:
+        size = len(self)
+        position = index + size if index < 0 else index
+        if not 0 <= position < size:
+            raise IndexError(f"message index {index} out of range")
+        return self._link_at(position + 1).message  # type: ignore[union-attr]
+
+    def __repr__(self) -> str:
+        return f"Conversation({len(self)} messages)"
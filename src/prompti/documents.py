@@ -0,0 +1,215 @@
+"""Document (PDF) input and citations.
+
+A user message can carry a document content part in Anthropic's
+``document`` block shape, built with :func:`document_part`::
+
+    with open("contract.pdf", "rb") as f:
+        doc = document_part(f.read(), title="Contract", citations=True)
+    message = Message.create_user([doc, {"type": "text", "text": "When does the contract end?"}])
+
+The part is sent unchanged to OpenAI-compatible gateways in front of
+Anthropic (LiteLLM accepts it as is); :func:`to_anthropic_content` converts a
+whole message content for the Messages API. With ``citations=True`` the
+model returns the passages its answer is based on. They are parsed by
+:func:`extract_citations` and available as ``message.citations`` /
+:meth:`ModelResponse.get_citations`.
+
+OpenAI models take files as ``file`` parts instead, either inline
+(``filename`` + base64 ``file_data``) or by the ``file_id`` of an uploaded
+file, see :func:`file_part`. The OpenAI and Azure clients convert base64
+``document`` parts with :func:`to_openai_file_parts`, and their
+``aprepare_file``/``prepare_file`` inline small files and upload large ones.
+"""
+
+from __future__ import annotations
+
+import base64
+import json
+from typing import Any
+
+from .message import Citation
+from .tool_content import _anthropic_image, _image_url
+
+__all__ = [
+    "DEFAULT_MAX_INLINE_FILE_BYTES",
+    "document_part",
+    "extract_citations",
+    "file_part",
+    "parse_anthropic_content",
+    "text_document_part",
+    "to_anthropic_content",
+    "to_openai_file_parts",
+]
+
+# files up to this size are sent inline, larger ones are uploaded first
+DEFAULT_MAX_INLINE_FILE_BYTES = 8 * 1024 * 1024
+
+
+def document_part(
+    source: bytes | str,
+    *,
+    media_type: str = "application/pdf",
+    title: str | None = None,
+    context: str | None = None,
+    citations: bool = False,
+) -> dict[str, Any]:
+    """Return a ``document`` content part.
+
+    Args:
+        source: Raw file bytes, a ``data:`` URL or an ``http(s)`` URL the
+            provider fetches itself.
+        media_type: MIME type of raw bytes.
+        title: Document title, returned with citations.
+        context: Extra information about the document for the model; not cited.
+        citations: Ask the model to cite passages of the document.
+    """
+    if isinstance(source, bytes):
+        block_source = {"type": "base64", "media_type": media_type, "data": base64.b64encode(source).decode("ascii")}
+    elif source.startswith("data:") and ";base64," in source:
+        header, data = source[5:].split(";base64,", 1)
+        block_source = {"type": "base64", "media_type": header, "data": data}
+    elif source.startswith(("http://", "https://")):
+        block_source = {"type": "url", "url": source}
+    else:
+        raise ValueError("document source must be bytes, a base64 data URL or an http(s) URL")
+    return _document(block_source, title, context, citations)
+
+
+def text_document_part(
+    text: str, *, title: str | None = None, context: str | None = None, citations: bool = False
+) -> dict[str, Any]:
+    """Return a plain-text ``document`` content part, see :func:`document_part`.
+
+    Anthropic cites plain-text documents by character range; Cohere takes
+    them as its ``documents`` for grounded answers.
+    """
+    return _document({"type": "text", "media_type": "text/plain", "data": text}, title, context, citations)
+
+
+def _document(source: dict[str, Any], title: str | None, context: str | None, citations: bool) -> dict[str, Any]:
+    part: dict[str, Any] = {"type": "document", "source": source}
+    if title is not None:
+        part["title"] = title
+    if context is not None:
+        part["context"] = context
+    if citations:
+        part["citations"] = {"enabled": True}
+    return part
+
+
+def file_part(
+    *,
+    file_id: str | None = None,
+    filename: str | None = None,
+    data: bytes | None = None,
+    media_type: str = "application/pdf",
+) -> dict[str, Any]:
+    """Return an OpenAI ``file`` content part for an uploaded ``file_id`` or inline ``filename`` + ``data``."""
+    if file_id is not None:
+        return {"type": "file", "file": {"file_id": file_id}}
+    if filename is None or data is None:
+        raise ValueError("file_part needs file_id, or filename and data")
+    encoded = base64.b64encode(data).decode("ascii")
+    return {"type": "file", "file": {"filename": filename, "file_data": f"data:{media_type};base64,{encoded}"}}
+
+
+def to_openai_file_parts(messages: list[dict[str, Any]]) -> list[dict[str, Any]]:
+    """Replace base64 ``document`` parts in OpenAI-format ``messages`` with inline ``file`` parts.
+
+    URL documents are left unchanged: OpenAI does not fetch files by URL.
+    """
+    result = []
+    for message in messages:
+        content = message.get("content")
+        if isinstance(content, list) and any(_is_base64_document(part) for part in content):
+            content = [_document_to_file(part) if _is_base64_document(part) else part for part in content]
+            message = {**message, "content": content}
+        result.append(message)
+    return result
+
+
+def _is_base64_document(part: Any) -> bool:
+    return isinstance(part, dict) and part.get("type") == "document" and part["source"].get("type") == "base64"
+
+
+def _document_to_file(part: dict[str, Any]) -> dict[str, Any]:
+    source = part["source"]
+    file_data = f"data:{source['media_type']};base64,{source['data']}"
+    return {"type": "file", "file": {"filename": part.get("title") or "document.pdf", "file_data": file_data}}
+
+
+def to_anthropic_content(content: str | list[dict[str, Any]] | None) -> str | list[dict[str, Any]]:
+    """Convert message ``content`` into Anthropic Messages API content blocks.
+
+    Text, JSON, ``image_url`` and ``document`` parts are supported; other
+    parts are passed through unchanged.
+    """
+    if content is None or isinstance(content, str):
+        return content or ""
+    blocks: list[dict[str, Any]] = []
+    for part in content:
+        url = _image_url(part)
+        if part.get("type") == "text":
+            blocks.append({"type": "text", "text": part.get("text", "")})
+        elif part.get("type") == "json":
+            blocks.append({"type": "text", "text": json.dumps(part.get("json"), ensure_ascii=False)})
+        elif url:
+            blocks.append(_anthropic_image(url))
+        else:
+            blocks.append(part)
+    return blocks
+
+
+_RANGES = {
+    "char_location": ("start_char_index", "end_char_index"),
+    "page_location": ("start_page_number", "end_page_number"),
+    "content_block_location": ("start_block_index", "end_block_index"),
+}
+
+
+def _citation(data: dict[str, Any], text: str | None) -> Citation:
+    start_key, end_key = _RANGES.get(data.get("type", ""), ("start", "end"))
+    return Citation(
+        type=data.get("type", "unknown"),
+        cited_text=data.get("cited_text", ""),
+        document_index=data.get("document_index"),
+        document_title=data.get("document_title"),
+        start=data.get(start_key),
+        end=data.get(end_key),
+        text=text,
+    )
+
+
+def parse_anthropic_content(blocks: list[dict[str, Any]]) -> tuple[str, list[Citation]]:
+    """Return the text of Anthropic response ``blocks`` and the citations of its text blocks."""
+    parts: list[str] = []
+    citations: list[Citation] = []
+    for block in blocks:
+        if block.get("type") != "text":
+            continue
+        text = block.get("text", "")
+        parts.append(text)
+        citations.extend(_citation(c, text) for c in block.get("citations") or [])
+    return "".join(parts), citations
+
+
+def extract_citations(message: dict[str, Any]) -> list[Citation] | None:
+    """Return the citations in an OpenAI-format response ``message`` or delta, ``None`` if it has none.
+
+    Gateways return them as Anthropic text blocks in ``content``, as a
+    ``citations`` list or in LiteLLM's ``provider_specific_fields``
+    (``citations``, grouped per text block, or ``citation`` in stream deltas).
+    """
+    content = message.get("content")
+    if isinstance(content, list):
+        _, citations = parse_anthropic_content(content)
+        if citations:
+            return citations
+    provider_fields = message.get("provider_specific_fields") or {}
+    raw = message.get("citations") or provider_fields.get("citations")
+    if raw is None and provider_fields.get("citation"):
+        raw = [provider_fields["citation"]]
+    if not raw:
+        return None
+    flat = [c for item in raw for c in (item if isinstance(item, list) else [item]) if isinstance(c, dict)]
+    return [_citation(c, None) for c in flat] or None
@@ -7,7 +7,6 @@ import json
 from functools import lru_cache
 from abc import ABC, abstractmethod
 
-import yaml
 from collections.abc import AsyncGenerator, Generator, Callable, Awaitable
 from typing import Union
 from pathlib import Path
@@ -15,9 +14,12 @@ from typing import Any, cast, ClassVar, Protocol
 import time
 
 from async_lru import alru_cache
-from opentelemetry import trace
 from pydantic import BaseModel, ConfigDict
 
+from ._otel import trace
+from .config_validation import validate_config_file
+from .fragments import PromptLibrary
+from .langdetect import detect_language
 from .loader import (
     FileSystemLoader,
     MemoryLoader,
@@ -26,11 +28,12 @@ from .loader import (
     TemplateNotFoundError,
 )
 from .message import Message, ModelResponse, StreamingModelResponse
-from .trace import TraceService, TraceEvent
-from .model_client import ModelConfig, RunParams, ToolParams, ToolSpec
+from .trace import LangfuseTraceService, TraceService, TraceEvent
+from .model_client import EventHook, ModelConfig, RetryConfig, RunParams, ToolParams, ToolSpec
 from .model_client.factory import create_client
 from .model_client.config_loader import ModelConfigLoader, FileModelConfigLoader, \
     HTTPModelConfigLoader, ModelConfigNotFoundError, MemoryModelConfigLoader
+from .telemetry import get_metrics
 from .template import PromptTemplate
 
 _tracer = trace.get_tracer(__name__)
@@ -86,8 +89,23 @@ class PromptEngine:
         trace_service: TraceService | None = None,
         before_run_hooks: list[BeforeRunHook] | None = None,
         after_run_hooks: list[AfterRunHook] | None = None,
+        retry_overrides: dict[str, RetryConfig] | None = None,
+        event_hooks: list[EventHook] | None = None,
+        model_overrides: dict[str, ModelConfig] | None = None,
+        language_detector: Callable[[str], str | None] | None = None,
+        fragments: PromptLibrary | None = None,
     ) -> None:
-        """Initialize the engine with prompt loaders, model loaders and optional global config."""
+        """Initialize the engine with prompt loaders, model loaders and optional global config.
+
+        ``retry_overrides`` maps a provider name to the :class:`RetryConfig` used
+        when the resolved model configuration does not set ``retry`` itself.
+        ``event_hooks`` are registered on every model client the engine creates.
+        ``model_overrides`` maps a model name to defaults (temperature, max_tokens, ...)
+        applied to fields the resolved configuration leaves unset.
+        ``language_detector`` (e.g. :func:`prompti.langdetect.detect_language`) detects
+        the language of the user's text; see :meth:`_detect_language`.
+        ``fragments`` is the :class:`~prompti.fragments.PromptLibrary` templates include from.
+        """
         self._prompt_loaders = prompt_loaders
         self._model_loaders = model_loaders or []
         self._cache_ttl = cache_ttl
@@ -95,6 +113,11 @@ class PromptEngine:
         self._trace_service = trace_service
         self._before_run_hooks = before_run_hooks or []
         self._after_run_hooks = after_run_hooks or []
+        self._retry_overrides = retry_overrides or {}
+        self._event_hooks = event_hooks or []
+        self._model_overrides = model_overrides or {}
+        self._language_detector = language_detector
+        self._fragments = fragments
         self._resolve = alru_cache(maxsize=128, ttl=cache_ttl)(self._resolve_impl)
         self._sync_resolve = lru_cache(maxsize=128)(self._sync_resolve_impl)
 
@@ -190,11 +213,22 @@ class PromptEngine:
             registry_cfg = self.get_model_config(merged_cfg.model)
             if registry_cfg is not None:
                 # 只补充API相关字段（如果缺失）
-                api_fields = ["api_key", "api_url"]
+                api_fields = ["api_key", "api_url", "organization", "project", "extra_headers"]
                 for field in api_fields:
                     if getattr(merged_cfg, field) is None and getattr(registry_cfg, field) is not None:
                         setattr(merged_cfg, field, getattr(registry_cfg, field))
 
+        # 按模型补充默认参数（仅填充未设置的字段）
+        override_cfg = self._model_overrides.get(merged_cfg.model) if merged_cfg.model else None
+        if override_cfg is not None:
+            for field_name in override_cfg.model_fields_set - {"model"}:
+                if getattr(merged_cfg, field_name) is None:
+                    setattr(merged_cfg, field_name, getattr(override_cfg, field_name))
+
+        # 按 provider 覆盖重试策略（如果配置中未指定）
+        if merged_cfg.retry is None and merged_cfg.provider in self._retry_overrides:
+            merged_cfg.retry = self._retry_overrides[merged_cfg.provider]
+
         # 确保配置完整性
         if not merged_cfg.provider:
             raise ValueError("Provider is required in model configuration")
@@ -226,11 +260,7 @@ class PromptEngine:
     ) -> list[Message] | list[dict]:
         """Return formatted messages for ``template_name`` in ``format``."""
         tmpl = await self._resolve(template_name, version)
-        msgs, _ = tmpl.format(
-            variables,
-            variant=variant,
-            selector=selector
-        )
+        msgs, _, _ = self._format_template(tmpl, variables, variant=variant, selector=selector)
         return msgs
 
     async def acompletion(
@@ -280,17 +310,16 @@ class PromptEngine:
         else:
             converted_messages = None
 
+        ctx, lang = self._detect_language(ctx, variables, converted_messages)
+
         # 如果直接提供了messages，则使用提供的messages，否则使用模板解析
         tmpl_name = template_name
         tmpl = await self._resolve(template_name, version) if template is None else template
+        used_fragments: dict[str, str] = {}
         if converted_messages is not None:
             # 直接使用提供的messages
             params = RunParams(messages=converted_messages, tool_params=converted_tool_params, **run_params)
-            _, var = tmpl.format(
-                variables,
-                variant=variant,
-                selector=ctx,
-            )
+            _, var, _ = self._format_template(tmpl, variables, variant=variant, selector=ctx)
         else:
             # 使用模板解析
             ctx = ctx or variables
@@ -298,11 +327,7 @@ class PromptEngine:
             if variant is None:
                 variant = tmpl.choose_variant(ctx) or next(iter(tmpl.variants))
 
-            messages, var = tmpl.format(
-                variables,
-                variant=variant,
-                selector=ctx,
-            )
+            messages, var, used_fragments = self._format_template(tmpl, variables, variant=variant, selector=ctx)
             # Filter out empty assistant messages from template-generated messages
             filtered_template_messages = self._filter_empty_assistant_messages(messages)
             params = RunParams(messages=cast(list[Message], filtered_template_messages),
@@ -317,6 +342,11 @@ class PromptEngine:
             # 只有使用模板时才有这些属性
             span_attrs["template.version"] = getattr(var, "version", None) or ""
             span_attrs["variant"] = variant or ""
+        if lang:
+            span_attrs["request.lang"] = lang
+        if used_fragments:
+            params.trace_context["fragments"] = used_fragments
+            span_attrs["template.fragments"] = ",".join(f"{n}@{v}" for n, v in sorted(used_fragments.items()))
 
         with _tracer.start_as_current_span(
             "prompt.run",
@@ -326,8 +356,10 @@ class PromptEngine:
             template_cfg = var.model_cfg if var is not None else None
 
             cfg = self._merge_model_configs(input_cfg=converted_model_cfg, template_cfg=template_cfg)
+            if lang:
+                get_metrics().language_requests.labels(cfg.provider, cfg.model, lang).inc()
             # 创建model client
-            model_client = create_client(cfg)
+            model_client = create_client(cfg, event_hooks=self._event_hooks)
 
             # 记录开始时间用于计算请求持续时间
             start_time = time.time()
@@ -530,16 +562,15 @@ class PromptEngine:
         else:
             converted_messages = None
 
+        ctx, lang = self._detect_language(ctx, variables, converted_messages)
+
         # Resolve template synchronously
         tmpl_name = template_name
         tmpl = self._sync_resolve(template_name, version) if template is None else template
+        used_fragments: dict[str, str] = {}
         if converted_messages is not None:
             params = RunParams(messages=converted_messages, tool_params=converted_tool_params, **run_params)
-            _, var = tmpl.format(
-                variables,
-                variant=variant,
-                selector=ctx,
-            )
+            _, var, _ = self._format_template(tmpl, variables, variant=variant, selector=ctx)
         else:
             ctx = ctx or variables
 
@@ -549,11 +580,7 @@ class PromptEngine:
                 except StopIteration:
                     raise ValueError(f"Template {template_name} has no variants")
 
-            messages, var = tmpl.format(
-                variables,
-                variant=variant,
-                selector=ctx,
-            )
+            messages, var, used_fragments = self._format_template(tmpl, variables, variant=variant, selector=ctx)
             # Filter out empty assistant messages from template-generated messages
             filtered_template_messages = self._filter_empty_assistant_messages(messages)
             params = RunParams(messages=cast(list[Message], filtered_template_messages),
@@ -567,6 +594,11 @@ class PromptEngine:
         if var is not None:
             span_attrs["template.version"] = getattr(var, "version", None) or ""
             span_attrs["variant"] = variant or ""
+        if lang:
+            span_attrs["request.lang"] = lang
+        if used_fragments:
+            params.trace_context["fragments"] = used_fragments
+            span_attrs["template.fragments"] = ",".join(f"{n}@{v}" for n, v in sorted(used_fragments.items()))
 
         with _tracer.start_as_current_span(
             "prompt.run",
@@ -575,10 +607,12 @@ class PromptEngine:
             # Merge configurations
             template_cfg = var.model_cfg if var is not None else None
             cfg = self._merge_model_configs(input_cfg=converted_model_cfg, template_cfg=template_cfg)
+            if lang:
+                get_metrics().language_requests.labels(cfg.provider, cfg.model, lang).inc()
 
             # Create sync model client
             from .model_client.factory import create_sync_client
-            model_client = create_sync_client(cfg)
+            model_client = create_sync_client(cfg, event_hooks=self._event_hooks)
 
             # Record start time for request duration calculation
             start_time = time.time()
@@ -733,6 +767,42 @@ class PromptEngine:
                 # Close client connection
                 model_client.close()
 
+    def _format_template(
+        self, tmpl: PromptTemplate, variables: dict[str, Any], *, variant: str | None, selector: dict[str, Any] | None
+    ) -> tuple[list[dict], Any, dict[str, str]]:
+        """Format ``tmpl`` with the engine's fragment library and return the fragments it used."""
+        with PromptLibrary.track() as used:
+            messages, var = tmpl.format(variables, variant=variant, selector=selector, fragments=self._fragments)
+        return messages, var, used
+
+    def _detect_language(
+        self, ctx: dict[str, Any] | None, variables: dict[str, Any], messages: list[Message] | None
+    ) -> tuple[dict[str, Any] | None, str | None]:
+        """Add the detected language of the user's text to the variant selector context.
+
+        The text is the last user message when ``messages`` are given, otherwise
+        the string variables. The language is added as ``lang`` so variants can
+        route on it (``selector: [zh]``) unless the context already has one.
+        """
+        base = ctx or variables
+        if "lang" in base:
+            return ctx, base["lang"]
+        if self._language_detector is None:
+            return ctx, None
+        user_messages = [m for m in messages or [] if m.role == "user"]
+        if user_messages:
+            content = user_messages[-1].content or ""
+            if isinstance(content, list):
+                parts = [p for p in content if isinstance(p, dict) and p.get("type") == "text"]
+                content = "\n".join(p.get("text", "") for p in parts)
+            text = content
+        else:
+            text = "\n".join(v for v in variables.values() if isinstance(v, str))
+        lang = self._language_detector(text)
+        if lang is None:
+            return ctx, None
+        return {**base, "lang": lang}, lang
+
     def _convert_model_cfg(self, model_cfg: ModelConfig | dict[str, Any]) -> ModelConfig:
         """Convert dict to ModelConfig object if needed."""
         if isinstance(model_cfg, dict):
@@ -881,7 +951,13 @@ class PromptEngine:
 
         # 创建trace服务（如果配置了）
         trace_service = None
-        if getattr(setting, "registry_url", None):
+        if setting.langfuse_public_key and setting.langfuse_secret_key:
+            trace_service = LangfuseTraceService(
+                public_key=setting.langfuse_public_key,
+                secret_key=setting.langfuse_secret_key,
+                host=setting.langfuse_host,
+            )
+        elif getattr(setting, "registry_url", None):
             trace_service = TraceService(
                 endpoint_url=setting.registry_url,
             )
@@ -898,7 +974,12 @@ class PromptEngine:
             global_model_config=global_cfg,
             trace_service=trace_service,
             before_run_hooks=before_hooks,
-            after_run_hooks=after_hooks
+            after_run_hooks=after_hooks,
+            retry_overrides=setting.retry_overrides,
+            event_hooks=setting.event_hooks,
+            model_overrides=setting.model_overrides,
+            language_detector=detect_language if setting.detect_language else None,
+            fragments=PromptLibrary.from_dict(setting.fragments) if setting.fragments else None,
         )
 
         # 加载所有模型配置
@@ -929,6 +1010,19 @@ class Setting(BaseModel):
     model_config_loaders: list[ModelConfigLoader] | None = None
     before_run_hooks: list[BeforeRunHook] | None = None
     after_run_hooks: list[AfterRunHook] | None = None
+    retry_overrides: dict[str, RetryConfig] | None = None
+    event_hooks: list[EventHook] | None = None
+    model_overrides: dict[str, ModelConfig] | None = None
+    # detect the user's language for variant routing and metrics, see :mod:`prompti.langdetect`
+    detect_language: bool = False
+    # prompt fragments as {name: text} or {name: {version: text}}, see :mod:`prompti.fragments`
+    fragments: dict[str, str | dict[str, str]] | None = None
+    langfuse_public_key: str | None = None
+    langfuse_secret_key: str | None = None
+    langfuse_host: str = "https://cloud.langfuse.com"
+
+    # Groups of options that cannot be set together in a configuration file.
+    exclusive_fields: ClassVar[tuple[tuple[str, ...], ...]] = (("default_model_config", "global_config_loader"),)
 
     @classmethod
     def from_file(cls, file_path: str | None = None) -> "Setting":
@@ -942,24 +1036,11 @@ class Setting(BaseModel):
             
         Raises:
             FileNotFoundError: If no configuration file could be found
+            ConfigValidationError: If the file has unknown keys, wrong types or conflicting options
         """
         # 如果未指定文件路径，尝试默认路径
         if file_path is None:
             raise FileNotFoundError(f"No configuration file found: {file_path}")
 
-        # 从文件加载配置
-        with open(file_path, "r") as f:
-            config_data = yaml.safe_load(f)
-
-        # 处理Path类型字段
-        if "template_paths" in config_data and isinstance(config_data["template_paths"], list):
-            config_data["template_paths"] = [Path(p) for p in config_data["template_paths"]]
-
-        if "model_config_path" in config_data and isinstance(config_data["model_config_path"], str):
-            config_data["model_config_path"] = Path(config_data["model_config_path"])
-
-        # 处理ModelConfig字段
-        if "default_model_config" in config_data and config_data["default_model_config"]:
-            config_data["default_model_config"] = ModelConfig(**config_data["default_model_config"])
-
-        return cls(**config_data)
+        # 从文件加载并校验配置，错误信息带有文件和行号
+        return cast(Setting, validate_config_file(file_path, cls))
@@ -0,0 +1,124 @@
+"""Named, versioned prompt fragments shared between templates.
+
+A :class:`PromptLibrary` stores reusable pieces of prompt text (tone
+guidelines, output format instructions, ...) under a name and a version.
+Templates include them by reference with the ``fragment`` function, pinned
+to a version with ``name@version`` or following the latest one::
+
+    library = PromptLibrary.from_dict({"tone": {"1": "Be concise.", "2": "Be concise and friendly."}})
+    engine = PromptEngine(loaders, fragments=library)
+
+    # in a template message
+    content: "{{ fragment('tone@1') }} Answer the question: {{ question }}"
+
+Fragments are rendered with the template's variables and may include other
+fragments. Code that builds messages directly uses :meth:`PromptLibrary.render`.
+The engine records the resolved ``name@version`` of every fragment a request
+used in ``params.trace_context["fragments"]`` and the ``template.fragments``
+span attribute, so prompt changes can be traced per request.
+"""
+
+from __future__ import annotations
+
+import re
+from collections.abc import Iterable, Iterator
+from contextlib import contextmanager
+from contextvars import ContextVar
+from typing import Any
+
+from pydantic import BaseModel
+
+__all__ = ["Fragment", "FragmentNotFoundError", "PromptLibrary"]
+
+_used: ContextVar[dict[str, str] | None] = ContextVar("prompti_fragments_used", default=None)
+
+
+class FragmentNotFoundError(KeyError):
+    """Raised when a fragment name or version is not in the library."""
+
+
+class Fragment(BaseModel):
+    """One version of a named prompt fragment."""
+
+    name: str
+    version: str
+    text: str
+    description: str = ""
+
+
+def _version_key(version: str) -> tuple[tuple[int, ...], str]:
+    """Order "2" < "10" and "1.2" < "1.10"; non-numeric parts fall back to text order."""
+    numbers = tuple(int(part) for part in re.findall(r"\d+", version))
+    return numbers, version
+
+
+class PromptLibrary:
+    """Named prompt fragments with versions."""
+
+    def __init__(self, fragments: Iterable[Fragment] = ()) -> None:
+        self._fragments: dict[str, dict[str, Fragment]] = {}
+        for fragment in fragments:
+            self._fragments.setdefault(fragment.name, {})[fragment.version] = fragment
+
+    @classmethod
+    def from_dict(cls, data: dict[str, str | dict[str, str]]) -> PromptLibrary:
+        """Build a library from ``{name: text}`` or ``{name: {version: text}}``."""
+        library = cls()
+        for name, value in data.items():
+            versions = value if isinstance(value, dict) else {"1": value}
+            for version, text in versions.items():
+                library.add(name, text, version=str(version))
+        return library
+
+    def add(self, name: str, text: str, *, version: str = "1", description: str = "") -> Fragment:
+        """Store ``text`` as ``name@version`` and return the fragment."""
+        fragment = Fragment(name=name, version=version, text=text, description=description)
+        self._fragments.setdefault(name, {})[version] = fragment
+        return fragment
+
+    def names(self) -> list[str]:
+        """Return the names of all fragments."""
+        return sorted(self._fragments)
+
+    def versions(self, name: str) -> list[str]:
+        """Return the versions of ``name``, oldest first."""
+        return sorted(self._fragments.get(name, {}), key=_version_key)
+
+    def get(self, ref: str, version: str | None = None) -> Fragment:
+        """Return the fragment for ``ref`` (``name`` or ``name@version``), the latest version if unpinned.
+
+        Raises:
+            FragmentNotFoundError: If the name or version is unknown.
+        """
+        name, _, pinned = ref.partition("@")
+        version = version or pinned or None
+        versions = self._fragments.get(name)
+        if not versions:
+            raise FragmentNotFoundError(f"Unknown prompt fragment {name!r}")
+        if version is None:
+            version = max(versions, key=_version_key)
+        elif version not in versions:
+            raise FragmentNotFoundError(f"Prompt fragment {name!r} has no version {version!r}: {self.versions(name)}")
+        fragment = versions[version]
+        used = _used.get()
+        if used is not None:
+            used[name] = fragment.version
+        return fragment
+
+    def render(self, ref: str, variables: dict[str, Any] | None = None) -> str:
+        """Render ``ref`` with ``variables`` for messages built in code."""
+        from .template import render_text
+
+        fragment = self.get(ref)
+        return render_text(fragment.text, variables or {}, fragments=self, _stack=(fragment.name,))
+
+    @staticmethod
+    @contextmanager
+    def track() -> Iterator[dict[str, str]]:
+        """Collect ``{name: version}`` of the fragments resolved inside the block."""
+        used: dict[str, str] = {}
+        token = _used.set(used)
+        try:
+            yield used
+        finally:
+            _used.reset(token)
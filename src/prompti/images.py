@@ -0,0 +1,141 @@
+"""Fit attached images to provider limits before they are sent.
+
+Providers reject images above a size (e.g. 5 MB per image) or silently
+downscale large ones server-side after the full upload. With
+``ModelConfig(image_limits=ImageLimits(max_bytes=..., max_dimension=...))``
+base64 ``data:`` images in the messages are downscaled and re-encoded to
+fit before the request is built; remote image URLs are left alone. Use
+:func:`image_data_url` to apply the same limits when attaching files::
+
+    part = {"type": "image_url", "image_url": {"url": image_data_url(raw, "image/png", limits)}}
+
+Resizing needs Pillow (``pip install pillow``); images that are already
+within ``max_bytes`` and have no ``max_dimension`` to check pass through
+without it.
+"""
+
+from __future__ import annotations
+
+import base64
+import io
+from collections.abc import Callable
+from typing import Any
+
+from pydantic import BaseModel, Field
+
+from .message import Message
+
+_DATA_URL_PREFIX = "data:"
+# formats that keep transparency when re-encoded
+_ALPHA_MODES = ("RGBA", "LA", "P")
+_QUALITY_STEPS = (0, 15, 30, 45)
+_SCALE_STEP = 0.75
+_MAX_ROUNDS = 12
+
+
+class ImageLimits(BaseModel):
+    """Size limits an attached image must satisfy."""
+
+    max_bytes: int | None = Field(5 * 1024 * 1024, ge=1)  # encoded image size, before base64
+    max_dimension: int | None = Field(None, ge=1)  # longest side in pixels
+    quality: int = Field(85, ge=1, le=100)  # starting JPEG quality when re-encoding
+
+
+class ImageTooLargeError(ValueError):
+    """Raised when an image cannot be brought within :class:`ImageLimits`."""
+
+
+FitImage = Callable[[bytes, str, ImageLimits], tuple[bytes, str]]
+
+
+def _load_pil() -> Any:
+    try:
+        from PIL import Image
+    except ImportError as e:
+        raise ImportError("Pillow is required to resize images. Install with: pip install pillow") from e
+    return Image
+
+
+def fit_image(data: bytes, mime: str, limits: ImageLimits) -> tuple[bytes, str]:
+    """Return ``(data, mime)`` downscaled and re-encoded to satisfy ``limits``.
+
+    Images already within the limits are returned unchanged. Images with
+    transparency are re-encoded as PNG, everything else as JPEG with falling
+    quality, shrinking the image further until it fits.
+
+    Raises:
+        ImageTooLargeError: If the image cannot be made small enough.
+    """
+    if limits.max_dimension is None and (limits.max_bytes is None or len(data) <= limits.max_bytes):
+        return data, mime
+    Image = _load_pil()
+    image = Image.open(io.BytesIO(data))
+    width, height = image.size
+    scale = 1.0
+    if limits.max_dimension is not None and max(width, height) > limits.max_dimension:
+        scale = limits.max_dimension / max(width, height)
+    elif limits.max_bytes is None or len(data) <= limits.max_bytes:
+        return data, mime
+
+    keep_alpha = image.mode in _ALPHA_MODES
+    image = image.convert("RGBA" if keep_alpha else "RGB")
+    fmt, out_mime = ("PNG", "image/png") if keep_alpha else ("JPEG", "image/jpeg")
+    size = len(data)
+    for _ in range(_MAX_ROUNDS):
+        resized = image.resize((max(1, round(width * scale)), max(1, round(height * scale))), Image.LANCZOS)
+        for step in _QUALITY_STEPS if fmt == "JPEG" else (0,):
+            buffer = io.BytesIO()
+            resized.save(buffer, format=fmt, quality=max(1, limits.quality - step), optimize=True)
+            size = buffer.tell()
+            if limits.max_bytes is None or size <= limits.max_bytes:
+                return buffer.getvalue(), out_mime
+        scale *= _SCALE_STEP
+    raise ImageTooLargeError(f"Image is still {size} bytes after downscaling, over the {limits.max_bytes} byte limit")
+
+
+def image_data_url(data: bytes, mime: str, limits: ImageLimits | None = None, fit: FitImage = fit_image) -> str:
+    """Return a base64 ``data:`` URL for ``data``, fitted to ``limits`` first."""
+    if limits is not None:
+        data, mime = fit(data, mime, limits)
+    return f"data:{mime};base64,{base64.b64encode(data).decode('ascii')}"
+
+
+def _fit_data_url(url: str, limits: ImageLimits, fit: FitImage) -> str:
+    header, sep, payload = url.partition(",")
+    if not sep or not header.endswith(";base64"):
+        return url
+    mime = header[len(_DATA_URL_PREFIX) : -len(";base64")] or "application/octet-stream"
+    data = base64.b64decode(payload)
+    fitted, fitted_mime = fit(data, mime, limits)
+    if fitted is data:
+        return url
+    return image_data_url(fitted, fitted_mime)
+
+
+def prepare_images(messages: list[Message], limits: ImageLimits, fit: FitImage = fit_image) -> list[Message]:
+    """Fit every base64 image in ``messages`` to ``limits``.
+
+    Returns ``messages`` itself when nothing changed, otherwise a new list
+    in which only the affected messages are copied.
+    """
+    result: list[Message] = []
+    changed = False
+    for message in messages:
+        if not isinstance(message.content, list):
+            result.append(message)
+            continue
+        parts: list[dict[str, Any]] = []
+        for part in message.content:
+            image = part.get("image_url") if part.get("type") == "image_url" else None
+            url = image.get("url") if isinstance(image, dict) else image
+            if isinstance(url, str) and url.startswith(_DATA_URL_PREFIX):
+                fitted = _fit_data_url(url, limits, fit)
+                if fitted != url:
+                    new_image = {**image, "url": fitted} if isinstance(image, dict) else fitted
+                    part = {**part, "image_url": new_image}
+            parts.append(part)
+        if parts != message.content:
+            changed = True
+            message = message.model_copy(update={"content": parts})
+        result.append(message)
+    return result if changed else messages
@@ -0,0 +1,158 @@
+"""Write-ahead journal for long offline jobs.
+
+:class:`RequestJournal` records every request of a job in a SQLite file
+before it is sent, keyed by :meth:`RunParams.canonical_hash`, and stores the
+response once it completed. After a crash the same job is simply started
+again: requests whose hash is already complete are answered from the
+journal without calling the provider, so the job resumes exactly where it
+stopped::
+
+    with RequestJournal("translate-job.sqlite") as journal:
+        async for entry in journal.arun(client, requests):
+            write_output(entry.key, entry.response)
+
+Requests that failed (an error response or an exception) stay in the
+journal as ``failed`` and are sent again on the next run; :meth:`pending`
+lists everything accepted but not completed. Identical requests share one
+hash, so they are sent once and answered with the same response.
+"""
+
+from __future__ import annotations
+
+import os
+import sqlite3
+import threading
+import time
+from collections.abc import AsyncGenerator, Iterable
+from typing import Any, Literal
+
+from pydantic import BaseModel
+
+from .message import ModelResponse
+from .model_client import ModelClient, RunParams
+
+__all__ = ["JournalEntry", "RequestJournal"]
+
+JournalStatus = Literal["accepted", "completed", "failed"]
+
+_SCHEMA = """
+CREATE TABLE IF NOT EXISTS requests (
+    seq INTEGER PRIMARY KEY AUTOINCREMENT,
+    hash TEXT NOT NULL UNIQUE,
+    request TEXT NOT NULL,
+    status TEXT NOT NULL,
+    response TEXT,
+    error TEXT,
+    accepted_at REAL NOT NULL,
+    completed_at REAL
+)
+"""
+
+
+class JournalEntry(BaseModel):
+    """The outcome of one request run through :meth:`RequestJournal.arun`."""
+
+    key: str
+    response: ModelResponse
+    # True when the response came from the journal instead of the provider
+    resumed: bool = False
+
+
+class RequestJournal:
+    """SQLite journal of accepted and completed requests.
+
+    Args:
+        path: The SQLite file; created if missing. ``":memory:"`` works for tests.
+    """
+
+    def __init__(self, path: str | os.PathLike[str]) -> None:
+        self.path = path
+        # every statement commits on its own, so a crash loses at most the request in flight
+        self._db = sqlite3.connect(path, isolation_level=None, check_same_thread=False)
+        self._db.execute("PRAGMA journal_mode=WAL")
+        self._db.execute(_SCHEMA)
+        self._lock = threading.Lock()
+
+    def _execute(self, sql: str, args: tuple[Any, ...] = ()) -> list[tuple[Any, ...]]:
+        with self._lock:
+            return self._db.execute(sql, args).fetchall()
+
+    def accept(self, params: RunParams, model: str | None = None) -> str:
+        """Record ``params`` before it is sent and return its key; known requests are kept as they are."""
+        key = params.canonical_hash(model)
+        self._execute(
+            "INSERT OR IGNORE INTO requests (hash, request, status, accepted_at) VALUES (?, ?, 'accepted', ?)",
+            (key, params.model_dump_json(), time.time()),
+        )
+        return key
+
+    def complete(self, key: str, response: ModelResponse) -> None:
+        """Store the final response of request ``key``."""
+        self._execute(
+            "UPDATE requests SET status = 'completed', response = ?, error = NULL, completed_at = ? WHERE hash = ?",
+            (response.model_dump_json(), time.time(), key),
+        )
+
+    def fail(self, key: str, error: str) -> None:
+        """Mark request ``key`` as failed; it is sent again by the next :meth:`arun`."""
+        self._execute("UPDATE requests SET status = 'failed', error = ? WHERE hash = ?", (error, key))
+
+    def status(self, key: str) -> JournalStatus | None:
+        """Return the status of request ``key``, ``None`` if it was never accepted."""
+        rows = self._execute("SELECT status FROM requests WHERE hash = ?", (key,))
+        return rows[0][0] if rows else None
+
+    def response(self, key: str) -> ModelResponse | None:
+        """Return the stored response of request ``key``, ``None`` unless it completed."""
+        rows = self._execute("SELECT response FROM requests WHERE hash = ? AND status = 'completed'", (key,))
+        return ModelResponse.model_validate_json(rows[0][0]) if rows else None
+
+    def pending(self) -> list[RunParams]:
+        """Return the requests accepted but not completed, in the order they were accepted."""
+        rows = self._execute("SELECT request FROM requests WHERE status != 'completed' ORDER BY seq")
+        return [RunParams.model_validate_json(request) for (request,) in rows]
+
+    def counts(self) -> dict[str, int]:
+        """Return the number of requests per status."""
+        return dict(self._execute("SELECT status, COUNT(*) FROM requests GROUP BY status"))
+
+    async def arun(self, client: ModelClient, requests: Iterable[RunParams]) -> AsyncGenerator[JournalEntry, None]:
+        """Send ``requests`` through ``client`` in order, skipping those the journal completed.
+
+        Requests are sent with ``stream=False``. A request that fails is
+        yielded with its error response and marked ``failed``; exceptions
+        mark it ``failed`` and propagate.
+        """
+        for params in requests:
+            key = self.accept(params, client.cfg.model)
+            stored = self.response(key)
+            if stored is not None:
+                yield JournalEntry(key=key, response=stored, resumed=True)
+                continue
+            response: ModelResponse | None = None
+            try:
+                async for item in client.arun(params.model_copy(update={"stream": False})):
+                    if isinstance(item, ModelResponse):
+                        response = item
+            except Exception as exc:
+                self.fail(key, f"{type(exc).__name__}: {exc}")
+                raise
+            if response is None:
+                self.fail(key, "No response")
+                raise RuntimeError(f"Client returned no response for request {key}")
+            if response.error:
+                self.fail(key, str(response.error.get("message") or response.error))
+            else:
+                self.complete(key, response)
+            yield JournalEntry(key=key, response=response)
+
+    def close(self) -> None:
+        """Close the database."""
+        with self._lock:
+            self._db.close()
+
+    def __enter__(self) -> RequestJournal:
+        return self
+
+    def __exit__(self, *exc: Any) -> None:
+        self.close()
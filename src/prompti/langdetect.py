@@ -0,0 +1,85 @@
+"""Lightweight language detection for routing and metrics.
+
+:func:`detect_language` tells the major scripts apart by their Unicode
+ranges and common Latin-script languages by frequent short words. It needs
+no model or extra dependency and is meant for routing hints on chat
+messages, not for linguistic accuracy::
+
+    detect_language("你好，请帮我查一下订单")  # "zh"
+    detect_language("Where is my order?")     # "en"
+
+Returns ISO 639-1 codes, or ``None`` when the text is too short or ambiguous.
+"""
+
+from __future__ import annotations
+
+import re
+import unicodedata
+
+__all__ = ["detect_language"]
+
+# (first, last) code point ranges per script, checked in order
+_SCRIPTS: tuple[tuple[str, tuple[tuple[int, int], ...]], ...] = (
+    ("kana", ((0x3040, 0x30FF), (0x31F0, 0x31FF), (0xFF66, 0xFF9F))),
+    ("ko", ((0xAC00, 0xD7AF), (0x1100, 0x11FF), (0x3130, 0x318F))),
+    ("han", ((0x4E00, 0x9FFF), (0x3400, 0x4DBF), (0xF900, 0xFAFF))),
+    ("ru", ((0x0400, 0x04FF),)),
+    ("el", ((0x0370, 0x03FF),)),
+    ("he", ((0x0590, 0x05FF),)),
+    ("ar", ((0x0600, 0x06FF), (0x0750, 0x077F))),
+    ("hi", ((0x0900, 0x097F),)),
+    ("th", ((0x0E00, 0x0E7F),)),
+)
+# a CJK character carries about as much text as a short word in other scripts
+_WIDE_SCRIPTS = {"kana", "ko", "han"}
+_WIDE_WEIGHT = 3
+
+_STOPWORDS: dict[str, frozenset[str]] = {
+    "en": frozenset("the and is are was to of in that it for you my with on this what how can not be".split()),
+    "es": frozenset("el la los las es de que y en por para con una un mi no se qué cómo está".split()),
+    "fr": frozenset("le la les est de et que en des un une je pas pour avec mon ne vous qui".split()),
+    "de": frozenset("der die das und ist nicht ich ein eine zu mit für auf den dem sie wie mein".split()),
+    "pt": frozenset("o a os as é de que e em não um uma para com meu por você está como".split()),
+    "it": frozenset("il la le è di che e non un una per con mio sono come questo ho gli".split()),
+    "nl": frozenset("de het een is en van niet ik dat met voor op zijn mijn hoe wat".split()),
+}
+_WORD = re.compile(r"[^\W\d_]+")
+_MIN_LETTERS = 2
+
+
+def _script(ch: str) -> str | None:
+    code = ord(ch)
+    for name, ranges in _SCRIPTS:
+        if any(first <= code <= last for first, last in ranges):
+            return name
+    if ch.isalpha() and "LATIN" in unicodedata.name(ch, ""):
+        return "latin"
+    return None
+
+
+def _latin_language(text: str) -> str | None:
+    words = _WORD.findall(text.lower())
+    scores = {lang: sum(word in stopwords for word in words) for lang, stopwords in _STOPWORDS.items()}
+    best = max(scores.values())
+    winners = [lang for lang, score in scores.items() if score == best]
+    return winners[0] if best and len(winners) == 1 else None
+
+
+def detect_language(text: str) -> str | None:
+    """Return the ISO 639-1 code of the dominant language of ``text``, or ``None``."""
+    counts: dict[str, int] = {}
+    for ch in text:
+        script = _script(ch)
+        if script is not None:
+            counts[script] = counts.get(script, 0) + (_WIDE_WEIGHT if script in _WIDE_SCRIPTS else 1)
+    if sum(counts.values()) < _MIN_LETTERS:
+        return None
+    # Japanese mixes kana and kanji; any notable amount of kana decides it
+    if counts.get("kana", 0) * 4 >= counts.get("han", 0) and counts.get("kana"):
+        counts["ja"] = counts.pop("kana") + counts.pop("han", 0)
+    if "han" in counts or "kana" in counts:
+        counts["zh"] = counts.pop("han", 0) + counts.pop("kana", 0)
+    script = max(counts, key=counts.__getitem__)
+    if script == "latin":
+        return _latin_language(text)
+    return script
@@ -0,0 +1,146 @@
+"""Summarizing memory for long conversations.
+
+When a conversation outgrows its token budget, :class:`SummarizingMemory`
+asks a (usually cheaper) model to summarize the oldest turns and replaces
+them with a single system "memory" message, keeping the leading system
+messages and the most recent turns verbatim::
+
+    memory = SummarizingMemory(create_client(cheap_cfg), max_tokens=6000, keep_recent=4)
+    messages = await memory.acompact(messages)
+    async for r in client.arun(RunParams(messages=messages)):
+        ...
+
+A turn starts at a user message and includes the assistant replies and tool
+results that follow it, so tool calls are never separated from their
+results. Compacting again later folds the previous memory message into the
+new summary.
+"""
+
+from __future__ import annotations
+
+from collections.abc import Callable
+from typing import Union
+
+from .message import Message
+from .model_client.base import ModelClient, SyncModelClient
+from .model_client.types import RunParams
+from .postprocess import _message_text, estimate_message_tokens
+
+__all__ = ["MEMORY_PREFIX", "SummarizationError", "SummarizingMemory", "estimate_message_tokens"]
+
+# marks the system message holding the summary
+MEMORY_PREFIX = "Summary of the earlier conversation:\n"
+
+DEFAULT_SUMMARY_PROMPT = (
+    "Summarize the conversation below for the assistant that continues it. Keep facts, names, numbers, "
+    "decisions, open questions and the user's preferences; drop small talk. Write in the language of the "
+    "conversation and answer with the summary only."
+)
+
+
+class SummarizationError(RuntimeError):
+    """Raised when the summarizing model returns an error or no text."""
+
+
+def _is_memory(message: Message) -> bool:
+    return message.role == "system" and isinstance(message.content, str) and message.content.startswith(MEMORY_PREFIX)
+
+
+def _is_instruction(message: Message) -> bool:
+    return message.role in ("system", "developer") and not _is_memory(message)
+
+
+class SummarizingMemory:
+    """Keep a conversation under ``max_tokens`` by summarizing its oldest turns.
+
+    Args:
+        client: Client of the model that writes the summaries; a
+            :class:`ModelClient` for :meth:`acompact`, a
+            :class:`SyncModelClient` for :meth:`compact`.
+        max_tokens: Budget for the whole conversation, as counted by ``count_tokens``.
+        keep_recent: Number of most recent turns always kept verbatim.
+        summary_prompt: Instructions for the summarizing model.
+        count_tokens: Token counter; defaults to :func:`estimate_message_tokens`.
+    """
+
+    def __init__(
+        self,
+        client: Union[ModelClient, SyncModelClient],
+        *,
+        max_tokens: int,
+        keep_recent: int = 4,
+        summary_prompt: str = DEFAULT_SUMMARY_PROMPT,
+        count_tokens: Callable[[list[Message]], int] = estimate_message_tokens,
+    ) -> None:
+        if keep_recent < 1:
+            raise ValueError("keep_recent must be at least 1")
+        self.client = client
+        self.max_tokens = max_tokens
+        self.keep_recent = keep_recent
+        self.summary_prompt = summary_prompt
+        self.count_tokens = count_tokens
+
+    def _split(self, messages: list[Message]) -> tuple[list[Message], list[Message], list[Message]] | None:
+        """Return ``(leading system, turns to summarize, recent turns)`` or ``None`` if nothing to do."""
+        if self.count_tokens(messages) <= self.max_tokens:
+            return None
+        start = 0
+        while start < len(messages) and _is_instruction(messages[start]):
+            start += 1
+        head, rest = messages[:start], messages[start:]
+        turn_starts = [i for i, m in enumerate(rest) if m.role == "user"]
+        if len(turn_starts) <= self.keep_recent:
+            return None
+        cut = turn_starts[-self.keep_recent]
+        old, recent = rest[:cut], rest[cut:]
+        if not any(not _is_memory(m) for m in old):
+            return None
+        return head, old, recent
+
+    def _summary_params(self, old: list[Message]) -> RunParams:
+        lines = []
+        for message in old:
+            if _is_memory(message):
+                lines.append(f"(earlier summary) {message.content[len(MEMORY_PREFIX):]}")
+            else:
+                lines.append(f"{message.role}: {_message_text(message)}")
+        return RunParams(
+            messages=[Message.create_system(self.summary_prompt), Message.create_user("\n".join(lines))],
+            stream=False,
+        )
+
+    @staticmethod
+    def _compose(head: list[Message], summary: str, recent: list[Message]) -> list[Message]:
+        if not summary.strip():
+            raise SummarizationError("The summarizing model returned no text")
+        return [*head, Message.create_system(MEMORY_PREFIX + summary.strip()), *recent]
+
+    async def acompact(self, messages: list[Message]) -> list[Message]:
+        """Return ``messages``, with the oldest turns summarized if over budget.
+
+        Raises:
+            SummarizationError: If the summarizing model fails.
+        """
+        split = self._split(messages)
+        if split is None:
+            return messages
+        head, old, recent = split
+        parts = []
+        async for response in self.client.arun(self._summary_params(old)):
+            if response.error:
+                raise SummarizationError(f"Summarizing the conversation failed: {response.error}")
+            parts.append(response.get_text_content() or "")
+        return self._compose(head, "".join(parts), recent)
+
+    def compact(self, messages: list[Message]) -> list[Message]:
+        """Sync variant of :meth:`acompact` for a :class:`SyncModelClient`."""
+        split = self._split(messages)
+        if split is None:
+            return messages
+        head, old, recent = split
+        parts = []
+        for response in self.client.run(self._summary_params(old)):
+            if response.error:
+                raise SummarizationError(f"Summarizing the conversation failed: {response.error}")
+            parts.append(response.get_text_content() or "")
+        return self._compose(head, "".join(parts), recent)
@@ -2,10 +2,52 @@
 
 from __future__ import annotations
 
+import json
 import time
-from typing import Any, Dict, List, Optional, Union
+from datetime import datetime, timedelta, timezone
+from typing import Annotated, Any, Dict, List, Optional, Union
 
-from pydantic import BaseModel, Field
+from pydantic import BaseModel, BeforeValidator, ConfigDict, Field
+
+
+def _epoch_seconds(value: Any) -> Any:
+    """Accept a ``datetime`` for a Unix timestamp field; naive values are taken as UTC."""
+    if isinstance(value, datetime):
+        if value.tzinfo is None:
+            value = value.replace(tzinfo=timezone.utc)
+        return int(value.timestamp())
+    return value
+
+
+# Unix seconds on the wire and in ``model_dump``; ``datetime`` values are converted on input.
+EpochSeconds = Annotated[int, BeforeValidator(_epoch_seconds)]
+
+
+def _utc(timestamp: Optional[int]) -> Optional[datetime]:
+    # 0 is what clients report when the provider sent no timestamp
+    return datetime.fromtimestamp(timestamp, timezone.utc) if timestamp else None
+
+
+def _seconds(value: Optional[float]) -> Optional[timedelta]:
+    return timedelta(seconds=value) if value is not None else None
+
+
+class Citation(BaseModel):
+    """A passage of an input document that supports part of the answer.
+
+    ``start``/``end`` are character offsets for ``char_location``, page
+    numbers for ``page_location`` and content block indices for
+    ``content_block_location`` (end exclusive, as Anthropic returns them).
+    For Cohere's ``text_content`` they are character offsets into the answer.
+    """
+
+    type: str = Field(..., description="Location kind, e.g. char_location or page_location")
+    cited_text: str = Field("", description="The quoted document text")
+    document_index: Optional[int] = Field(None, description="Index of the document in the request")
+    document_title: Optional[str] = Field(None, description="Title of the document")
+    start: Optional[int] = Field(None, description="Start of the cited range")
+    end: Optional[int] = Field(None, description="End of the cited range")
+    text: Optional[str] = Field(None, description="The answer text the citation supports")
 
 
 class Message(BaseModel):
@@ -24,6 +66,9 @@ class Message(BaseModel):
     reasoning_content: Optional[str] = Field(None, description="The content of the message for reasoning")
     tool_calls: Optional[List[Dict[str, Any]]] = Field(None, description="Tool calls made by the assistant")
     tool_call_id: Optional[str] = Field(None, description="ID of the tool call this message is responding to")
+    citations: Optional[List[Citation]] = Field(
+        None, description="Document passages cited by the answer, see :mod:`prompti.documents`"
+    )
 
     def to_openai(self) -> Dict[str, Any]:
         """Convert to OpenAI format dictionary."""
@@ -105,8 +150,13 @@ class Message(BaseModel):
         return cls(role="system", content=content)
 
     @classmethod
-    def create_tool_result(cls, content: str, tool_call_id: str) -> 'Message':
-        """Create a tool result message."""
+    def create_developer(cls, content: str) -> 'Message':
+        """Create a developer message (instructions for newer OpenAI models)."""
+        return cls(role="developer", content=content)
+
+    @classmethod
+    def create_tool_result(cls, content: Union[str, List[Dict[str, Any]]], tool_call_id: str) -> 'Message':
+        """Create a tool result message with text or structured content parts (text/json/image_url)."""
         return cls(role="tool", content=content, tool_call_id=tool_call_id)
 
     @classmethod
@@ -134,6 +184,77 @@ class Message(BaseModel):
         return None
 
 
+class FunctionCall(BaseModel):
+    """The function of a tool call, with its arguments as a JSON string."""
+
+    name: str = Field("", description="Name of the called function")
+    arguments: str = Field("", description="JSON encoded arguments")
+
+
+class ToolCall(BaseModel):
+    """A complete tool call in OpenAI format.
+
+    Provider specific keys, such as Gemini's ``extra_content`` with the
+    thought signature, are kept so the call can be sent back unchanged.
+    """
+
+    model_config = ConfigDict(extra="allow")
+
+    id: str = Field("", description="ID the tool result refers to")
+    type: str = Field("function", description="Tool type")
+    function: FunctionCall = Field(default_factory=FunctionCall, description="The called function")
+
+    def parse_arguments(self) -> Any:
+        """The arguments decoded from JSON; an empty string is no arguments."""
+        return json.loads(self.function.arguments or "{}")
+
+    def merge(self, delta: ToolCallDelta) -> None:
+        """Add the next streamed fragment of this call; name and arguments pieces are appended."""
+        if delta.id:
+            self.id = delta.id
+        if delta.type:
+            self.type = delta.type
+        if delta.function is not None:
+            if delta.function.name:
+                self.function.name += delta.function.name
+            if delta.function.arguments:
+                self.function.arguments += delta.function.arguments
+        # provider keys such as Gemini's ``extra_content``
+        for key, value in (delta.model_extra or {}).items():
+            setattr(self, key, value)
+
+    def to_openai(self) -> Dict[str, Any]:
+        """Convert to the dict stored in :attr:`Message.tool_calls`."""
+        return self.model_dump()
+
+
+class FunctionCallDelta(BaseModel):
+    """A fragment of the function of a streamed tool call."""
+
+    name: Optional[str] = Field(None, description="Part of the function name, usually sent whole in the first fragment")
+    arguments: Optional[str] = Field(None, description="Next piece of the JSON encoded arguments")
+
+
+class ToolCallDelta(BaseModel):
+    """A fragment of a streamed tool call.
+
+    The first fragment of a call carries its ``id``; the following ones only
+    its ``index`` and the next piece of the arguments.
+    """
+
+    model_config = ConfigDict(extra="allow")
+
+    index: int = Field(..., description="Position of the call in the message")
+    id: Optional[str] = Field(None, description="ID of the call, in its first fragment")
+    type: Optional[str] = Field(None, description="Tool type, in its first fragment")
+    function: Optional[FunctionCallDelta] = Field(None, description="Function name and argument fragments")
+
+    @classmethod
+    def from_openai(cls, deltas: List[Dict[str, Any]]) -> List['ToolCallDelta']:
+        """Parse the ``tool_calls`` of a delta; fragments without ``index`` take their position."""
+        return [cls.model_validate({"index": pos, **delta}) for pos, delta in enumerate(deltas)]
+
+
 class Usage(BaseModel):
     """Token usage information following OpenAI format."""
 
@@ -142,6 +263,59 @@ class Usage(BaseModel):
     total_tokens: int = Field(..., description="Total number of tokens used")
 
 
+class Timing(BaseModel):
+    """Client-side timing measured for a model call, in seconds."""
+
+    first_token_latency: Optional[float] = Field(None, description="Seconds until the first response arrived")
+    total_duration: Optional[float] = Field(None, description="Seconds from request start to this response")
+    output_tokens_per_sec: Optional[float] = Field(
+        None, description="Completion tokens per second after the first token, once usage is known"
+    )
+
+    @property
+    def first_token_timedelta(self) -> Optional[timedelta]:
+        """``first_token_latency`` as a ``timedelta``."""
+        return _seconds(self.first_token_latency)
+
+    @property
+    def total_timedelta(self) -> Optional[timedelta]:
+        """``total_duration`` as a ``timedelta``."""
+        return _seconds(self.total_duration)
+
+    @classmethod
+    def measure(cls, first_token_latency: float, total_duration: float, usage: Optional[Usage] = None) -> "Timing":
+        """Build timing values, deriving throughput from ``usage`` when available."""
+        tokens_per_sec = None
+        if usage and usage.completion_tokens:
+            generation = total_duration - first_token_latency
+            if generation <= 0:
+                generation = total_duration
+            if generation > 0:
+                tokens_per_sec = usage.completion_tokens / generation
+        return cls(
+            first_token_latency=first_token_latency,
+            total_duration=total_duration,
+            output_tokens_per_sec=tokens_per_sec,
+        )
+
+
+class AttemptInfo(BaseModel):
+    """One try of a model call that was retried."""
+
+    attempt: int = Field(..., description="1-based attempt number")
+    provider: Optional[str] = Field(None, description="Provider the attempt was sent to")
+    model: Optional[str] = Field(None, description="Model the attempt was sent to")
+    status: str = Field(..., description="'retried', 'error' or 'success'")
+    status_code: Optional[int] = Field(None, description="HTTP status of a failed attempt, if any")
+    latency: float = Field(..., description="Seconds from the start of the attempt to its outcome")
+    error: Optional[str] = Field(None, description="Error message of a failed attempt")
+
+    @property
+    def latency_timedelta(self) -> timedelta:
+        """``latency`` as a ``timedelta``."""
+        return timedelta(seconds=self.latency)
+
+
 class Choice(BaseModel):
     """A single choice from the model response following OpenAI format."""
 
@@ -160,7 +334,7 @@ class ModelResponse(BaseModel):
 
     id: Optional[str] = Field(None, description="Unique identifier for the response")
     object: Optional[str] = Field(None, description="Object type")
-    created: Optional[int] = Field(None, description="Unix timestamp of creation")
+    created: Optional[EpochSeconds] = Field(None, description="Unix timestamp of creation")
     model: Optional[str] = Field(None, description="Model name used for generation")
     choices: Optional[List[Choice]] = Field(None, description="List of generated choices")
     usage: Optional[Usage] = Field(None, description="Token usage information")
@@ -168,6 +342,20 @@ class ModelResponse(BaseModel):
     # Additional fields that may be present
     system_fingerprint: Optional[str] = Field(None, description="System fingerprint")
     error: Optional[Dict[str, Any]] = Field(None, description="Error object if the request failed")
+    timing: Optional[Timing] = Field(
+        None, description="Client-side latency, set on the first and final responses of a call"
+    )
+    attempts: Optional[List[AttemptInfo]] = Field(
+        None, description="Every attempt of the call when it was retried, set together with timing"
+    )
+    extra: Dict[str, Any] = Field(
+        default_factory=dict, description="Provider response fields not mapped above, keyed by dotted path"
+    )
+
+    @property
+    def created_at(self) -> Optional[datetime]:
+        """``created`` as a UTC ``datetime``, ``None`` when the provider sent no timestamp."""
+        return _utc(self.created)
 
     def get_content(self) -> Optional[Union[str, List[Dict[str, Any]]]]:
         """Get the content from the first choice."""
@@ -211,6 +399,12 @@ class ModelResponse(BaseModel):
             return self.choices[0].message
         return None
 
+    def get_citations(self) -> List[Citation]:
+        """Get the document citations of the first choice."""
+        if self.choices and self.choices[0].message.citations:
+            return self.choices[0].message.citations
+        return []
+
     prompt_filter_results: Optional[List[Dict[str, Any]]] = Field(None, description="Prompt filter results")
 
 
@@ -232,7 +426,7 @@ class StreamingModelResponse(BaseModel):
 
     id: Optional[str] = Field(None, description="Unique identifier for the response")
     object: Optional[str] = Field(None, description="Object type")
-    created: Optional[int] = Field(None, description="Unix timestamp of creation")
+    created: Optional[EpochSeconds] = Field(None, description="Unix timestamp of creation")
     model: Optional[str] = Field(None, description="Model name used for generation")
     choices: Optional[List[StreamingChoice]] = Field(None, description="List of streaming choices")
     usage: Optional[Usage] = Field(None, description="Token usage information")
@@ -240,6 +434,20 @@ class StreamingModelResponse(BaseModel):
     # Additional fields that may be present
     system_fingerprint: Optional[str] = Field(None, description="System fingerprint")
     error: Optional[Dict[str, Any]] = Field(None, description="Error object if the request failed")
+    timing: Optional[Timing] = Field(
+        None, description="Client-side latency, set on the first and final responses of a call"
+    )
+    attempts: Optional[List[AttemptInfo]] = Field(
+        None, description="Every attempt of the call when it was retried, set together with timing"
+    )
+    extra: Dict[str, Any] = Field(
+        default_factory=dict, description="Provider response fields not mapped above, keyed by dotted path"
+    )
+
+    @property
+    def created_at(self) -> Optional[datetime]:
+        """``created`` as a UTC ``datetime``, ``None`` when the provider sent no timestamp."""
+        return _utc(self.created)
 
     def get_content(self) -> Optional[Union[str, List[Dict[str, Any]]]]:
         """Get the content from the first choice delta."""
@@ -271,6 +479,10 @@ class StreamingModelResponse(BaseModel):
             return self.choices[0].delta.tool_calls
         return None
 
+    def get_tool_call_deltas(self) -> List[ToolCallDelta]:
+        """Get the tool call fragments of the first choice delta."""
+        return ToolCallDelta.from_openai(self.get_tool_calls() or [])
+
     def get_finish_reason(self) -> Optional[str]:
         """Get finish reason from the first choice."""
         if self.choices:
@@ -287,7 +499,13 @@ class StreamingModelResponse(BaseModel):
 # 为了向后兼容，保留原有的 Message 类作为主要接口
 __all__ = [
     "Message",
+    "FunctionCall",
+    "ToolCall",
+    "FunctionCallDelta",
+    "ToolCallDelta",
     "Usage",
+    "Timing",
+    "AttemptInfo",
     "Choice",
     "ModelResponse",
     "StreamingChoice",
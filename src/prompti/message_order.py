@@ -0,0 +1,217 @@
+"""Validate and normalize message role ordering before it reaches a provider.
+
+Providers reject conversations that break their ordering rules, usually with
+an opaque 400. :func:`normalize_messages` checks a conversation against the
+:class:`RoleRules` of the target provider and either raises a
+:class:`MessageOrderError` listing every problem (``mode="error"``) or repairs
+what can be repaired safely (``mode="fix"``):
+
+* system messages after the start are moved to the front;
+* consecutive plain user or assistant messages are merged;
+* tool results that answer no preceding tool call are dropped;
+* tool calls that are never answered are removed from their assistant message;
+* assistant messages left empty (no content, no tool calls) are dropped.
+
+Problems that cannot be fixed without inventing content (an unknown role, a
+conversation starting with an assistant turn where the provider forbids it, a
+non-empty trailing assistant without prefill) raise in both modes.
+"""
+
+from __future__ import annotations
+
+from dataclasses import dataclass
+from typing import Any, Literal
+
+from pydantic import BaseModel
+
+from .message import Message
+
+NormalizationMode = Literal["error", "fix"]
+
+KNOWN_ROLES = {"system", "developer", "user", "assistant", "tool"}
+
+
+class RoleRules(BaseModel):
+    """Ordering constraints of one provider."""
+
+    # system/developer messages may only appear before the first other message
+    system_first: bool = False
+    # plain user and assistant messages must alternate
+    alternate: bool = False
+    # the first non-system message must be from the user
+    user_first: bool = False
+    # the conversation may end with an assistant message (response prefill)
+    allow_trailing_assistant: bool = True
+
+
+# Providers not listed here only get the tool call/result checks.
+PROVIDER_RULES: dict[str, RoleRules] = {
+    "anthropic": RoleRules(system_first=True, alternate=True, user_first=True, allow_trailing_assistant=False),
+    # Anthropic models on Bedrock; Titan prompts have the same shape
+    "bedrock": RoleRules(system_first=True, alternate=True, user_first=True, allow_trailing_assistant=False),
+    # system messages are sent as the separate systemInstruction
+    "gemini": RoleRules(system_first=True),
+}
+
+
+def rules_for(provider: str | None) -> RoleRules:
+    """Return the ordering rules for ``provider``."""
+    return PROVIDER_RULES.get(provider or "", RoleRules())
+
+
+@dataclass(kw_only=True)
+class OrderIssue:
+    """One ordering problem, ``index`` refers to the input message list."""
+
+    index: int
+    message: str
+    fixable: bool = True
+
+    def __str__(self) -> str:
+        return f"message {self.index}: {self.message}"
+
+
+class MessageOrderError(ValueError):
+    """Raised when a conversation violates the provider's ordering rules."""
+
+    def __init__(self, issues: list[OrderIssue]) -> None:
+        self.issues = issues
+        super().__init__("Invalid message sequence:\n" + "\n".join(f"  {issue}" for issue in issues))
+
+
+def _is_system(msg: Message) -> bool:
+    return msg.role in ("system", "developer")
+
+
+def _is_plain(msg: Message) -> bool:
+    return msg.role in ("user", "assistant") and not msg.tool_calls
+
+
+def _is_empty(msg: Message) -> bool:
+    return not msg.content and not msg.tool_calls
+
+
+def _call_ids(msg: Message) -> list[str]:
+    return [call.get("id") for call in msg.tool_calls or [] if call.get("id")]
+
+
+def _merge_content(first: Any, second: Any) -> Any:
+    if first is None or first == "":
+        return second
+    if second is None or second == "":
+        return first
+    if isinstance(first, str) and isinstance(second, str):
+        return f"{first}\n\n{second}"
+    return _as_parts(first) + _as_parts(second)
+
+
+def _as_parts(content: Any) -> list[dict[str, Any]]:
+    return [{"type": "text", "text": content}] if isinstance(content, str) else list(content)
+
+
+def _check_tools(messages: list[tuple[int, Message]], issues: list[OrderIssue]) -> list[tuple[int, Message]]:
+    """Pair tool results with tool calls; drop orphans and unanswered calls."""
+    result: list[tuple[int, Message]] = []
+    pending: dict[str, int] = {}  # unanswered tool_call_id -> position in ``result``
+    for index, msg in messages:
+        if msg.role == "tool":
+            if msg.tool_call_id in pending:
+                pending.pop(msg.tool_call_id)
+                result.append((index, msg))
+            else:
+                issues.append(
+                    OrderIssue(
+                        index=index,
+                        message=f"tool result '{msg.tool_call_id}' does not follow a matching tool call",
+                    )
+                )
+            continue
+        _drop_unanswered(result, pending, issues)
+        if msg.tool_calls:
+            for call_id in _call_ids(msg):
+                pending[call_id] = len(result)
+        result.append((index, msg))
+    _drop_unanswered(result, pending, issues)
+    return result
+
+
+def _drop_unanswered(result: list[tuple[int, Message]], pending: dict[str, int], issues: list[OrderIssue]) -> None:
+    for call_id, position in list(pending.items()):
+        index, msg = result[position]
+        issues.append(OrderIssue(index=index, message=f"tool call '{call_id}' has no tool result"))
+        calls = [call for call in msg.tool_calls or [] if call.get("id") != call_id]
+        result[position] = (index, msg.model_copy(update={"tool_calls": calls or None}))
+    pending.clear()
+
+
+def normalize_messages(
+    messages: list[Message],
+    rules: RoleRules | None = None,
+    *,
+    mode: NormalizationMode = "error",
+    prefill: bool = False,
+) -> list[Message]:
+    """Check ``messages`` against ``rules`` and return the normalized list.
+
+    ``prefill`` allows a trailing assistant message even when the rules forbid
+    it, for providers that continue a partially written answer.
+
+    Raises:
+        MessageOrderError: In ``"error"`` mode for any problem, in ``"fix"`` mode
+            only for problems that cannot be repaired.
+    """
+    rules = rules or RoleRules()
+    issues: list[OrderIssue] = []
+    items = list(enumerate(messages))
+
+    for index, msg in items:
+        if msg.role not in KNOWN_ROLES:
+            issues.append(OrderIssue(index=index, message=f"unknown role '{msg.role}'", fixable=False))
+
+    if rules.system_first:
+        leading = 0
+        while leading < len(items) and _is_system(items[leading][1]):
+            leading += 1
+        late = [item for item in items[leading:] if _is_system(item[1])]
+        for index, _ in late:
+            issues.append(OrderIssue(index=index, message="system message must come before the conversation"))
+        items = items[:leading] + late + [item for item in items[leading:] if not _is_system(item[1])]
+
+    items = _check_tools(items, issues)
+    kept = []
+    for index, msg in items:
+        if msg.role == "assistant" and _is_empty(msg):
+            issues.append(OrderIssue(index=index, message="assistant message is empty"))
+        else:
+            kept.append((index, msg))
+    items = kept
+
+    if rules.alternate:
+        merged: list[tuple[int, Message]] = []
+        for index, msg in items:
+            if merged and _is_plain(msg) and _is_plain(merged[-1][1]) and merged[-1][1].role == msg.role:
+                issues.append(OrderIssue(index=index, message=f"consecutive '{msg.role}' messages must alternate"))
+                prev_index, prev = merged[-1]
+                content = _merge_content(prev.content, msg.content)
+                merged[-1] = (prev_index, prev.model_copy(update={"content": content}))
+            else:
+                merged.append((index, msg))
+        items = merged
+
+    conversation = [(index, msg) for index, msg in items if not _is_system(msg)]
+    if rules.user_first and conversation and conversation[0][1].role != "user":
+        issues.append(
+            OrderIssue(index=conversation[0][0], message="conversation must start with a user message", fixable=False)
+        )
+    if not rules.allow_trailing_assistant and not prefill and conversation and conversation[-1][1].role == "assistant":
+        issues.append(
+            OrderIssue(
+                index=conversation[-1][0],
+                message="conversation must not end with an assistant message",
+                fixable=False,
+            )
+        )
+
+    if issues and (mode == "error" or not all(issue.fixable for issue in issues)):
+        raise MessageOrderError(sorted(issues, key=lambda issue: issue.index))
+    return [msg for _, msg in items]
@@ -1,57 +1,134 @@
-"""Model clients for various providers."""
+"""Model clients for various providers.
+
+The configuration and request models are imported eagerly; clients,
+loaders and the factory are imported on first access so that
+``from prompti.model_client import RunParams`` does not load the HTTP stack.
+"""
 
 from __future__ import annotations
 
+import importlib
+from typing import Any
+
 from ..message import Message
-from .base import (
-    ModelClient,
+from .types import (
+    AzureADConfig,
+    ErrorClass,
+    KeepAliveConfig,
+    ModelCapabilities,
     ModelConfig,
+    OpenAICompatibleConfig,
+    RateLimitConfig,
+    RateLimitHeadroom,
+    RetryConfig,
     RunParams,
+    TokenBudgetError,
+    TimeoutConfig,
+    TokenCounts,
     ToolChoice,
     ToolParams,
     ToolSpec,
+    UsageReport,
 )
-from .config_loader import (
-    ModelConfigLoader,
-    FileModelConfigLoader,
-    HTTPModelConfigLoader,
-    ModelConfigNotFoundError,
-)
-from .factory import create_client
+
+# public name -> submodule that defines it
+_LAZY_ATTRS = {
+    "EventHook": ".base",
+    "ModelClient": ".base",
+    "ModelConfigLoader": ".config_loader",
+    "FileModelConfigLoader": ".config_loader",
+    "HTTPModelConfigLoader": ".config_loader",
+    "ModelConfigNotFoundError": ".config_loader",
+    "create_client": ".factory",
+    "FallbackClient": ".fallback",
+    "ClientManager": ".manager",
+    "RequestRequirements": ".routing",
+    "NoMatchingModelError": ".routing",
+    "select_model": ".routing",
+    "Remediation": ".hints",
+    "remediation": ".hints",
+    "HealthTracker": ".health",
+    "ProviderHealth": ".health",
+    "RateLimitWaitError": ".ratelimit",
+    "GatewayLimits": ".tenants",
+    "TenantAccessError": ".tenants",
+    "TenantConfig": ".tenants",
+    "TenantGateway": ".tenants",
+    "LiteLLMClient": ".litellm",
+    "OpenAIClient": ".openai_client",
+    "AzureOpenAIClient": ".azure_client",
+    "BedrockClient": ".bedrock_client",
+    "GeminiClient": ".gemini_client",
+    "MistralClient": ".mistral_client",
+    "CohereClient": ".cohere_client",
+    "OllamaClient": ".ollama_client",
+    "OpenAICompatibleClient": ".compatible_client",
+    "QianfanClient": ".qianfan_client",
+    "VertexClient": ".vertex_client",
+}
 
 __all__ = [
     "ModelConfig",
+    "ModelCapabilities",
     "ModelClient",
+    "FallbackClient",
+    "RetryConfig",
+    "RateLimitConfig",
+    "RateLimitHeadroom",
+    "RateLimitWaitError",
+    "KeepAliveConfig",
+    "TimeoutConfig",
+    "AzureADConfig",
+    "OpenAICompatibleConfig",
     "RunParams",
+    "TokenBudgetError",
+    "TokenCounts",
     "ToolSpec",
+    "UsageReport",
     "ToolParams",
     "ToolChoice",
+    "ErrorClass",
+    "EventHook",
     "create_client",
+    "ClientManager",
+    "RequestRequirements",
+    "NoMatchingModelError",
+    "HealthTracker",
+    "ProviderHealth",
+    "GatewayLimits",
+    "TenantAccessError",
+    "TenantConfig",
+    "TenantGateway",
+    "select_model",
+    "Remediation",
+    "remediation",
     "Message",
     "ModelConfigLoader",
-    "FileModelConfigLoader", 
+    "FileModelConfigLoader",
     "HTTPModelConfigLoader",
     "ModelConfigNotFoundError",
+    "LiteLLMClient",
+    "OpenAIClient",
+    "AzureOpenAIClient",
+    "BedrockClient",
+    "GeminiClient",
+    "MistralClient",
+    "CohereClient",
+    "OllamaClient",
+    "OpenAICompatibleClient",
+    "QianfanClient",
+    "VertexClient",
 ]
 
-# Optional import for LiteLLMClient
-try:
-    from .litellm import LiteLLMClient  # noqa: F401
-
-    __all__.append("LiteLLMClient")
-except ImportError:
-    pass
 
-# OpenAI clients
-try:
-    from .openai_client import OpenAIClient  # noqa: F401
+def __getattr__(name: str) -> Any:
+    module = _LAZY_ATTRS.get(name)
+    if module is None:
+        raise AttributeError(f"module {__name__!r} has no attribute {name!r}")
+    value = getattr(importlib.import_module(module, __name__), name)
+    globals()[name] = value
+    return value
 
-    __all__.extend(["OpenAIClient"])
-except ImportError:
-    pass
 
-try:
-    from .qianfan_client import QianFanClient  # noqa: F401
-    __all__.extend(["QianFanClient"])
-except ImportError:
-    pass
+def __dir__() -> list[str]:
+    return sorted(set(globals()) | set(_LAZY_ATTRS))
@@ -0,0 +1,159 @@
+"""Translate Anthropic Messages API stream events into OpenAI-style chunks.
+
+Anthropic streams ``message_start``, ``content_block_start``,
+``content_block_delta``, ``content_block_stop``, ``message_delta`` and
+``message_stop`` events instead of ``chat.completion.chunk`` objects.
+Gateways that relay Claude natively send these on an OpenAI-compatible
+route, so :class:`OpenAIWireMixin` feeds every payload with a known event
+``type`` through an :class:`AnthropicStreamDecoder` and the caller sees the
+same :class:`StreamingModelResponse` deltas as for any other provider:
+
+* text and ``thinking`` deltas become ``content`` and ``reasoning_content``;
+* ``tool_use`` blocks become ``tool_calls`` deltas, numbered from 0 in the
+  order they start, with ``input_json_delta`` as the ``arguments`` fragments;
+* ``citations_delta`` becomes ``citations``;
+* ``message_delta`` carries the mapped ``finish_reason`` and the usage;
+* an ``error`` event becomes an error response with the matching HTTP
+  ``status_code``.
+"""
+
+from __future__ import annotations
+
+from typing import Any
+
+from ..documents import _citation
+from ..message import Message, StreamingChoice, StreamingModelResponse, Usage
+
+__all__ = ["ANTHROPIC_EVENT_TYPES", "ERROR_STATUS", "FINISH_REASONS", "AnthropicStreamDecoder"]
+
+ANTHROPIC_EVENT_TYPES = frozenset(
+    {
+        "message_start",
+        "content_block_start",
+        "content_block_delta",
+        "content_block_stop",
+        "message_delta",
+        "message_stop",
+        "ping",
+        "error",
+    }
+)
+
+# Anthropic stop_reason -> OpenAI finish_reason
+FINISH_REASONS = {
+    "end_turn": "stop",
+    "stop_sequence": "stop",
+    "pause_turn": "stop",
+    "max_tokens": "length",
+    "tool_use": "tool_calls",
+    "refusal": "content_filter",
+}
+
+# Anthropic error type -> HTTP status, so mid-stream errors classify like HTTP ones
+ERROR_STATUS = {
+    "invalid_request_error": 400,
+    "authentication_error": 401,
+    "permission_error": 403,
+    "not_found_error": 404,
+    "request_too_large": 413,
+    "rate_limit_error": 429,
+    "api_error": 500,
+    "overloaded_error": 529,
+}
+
+
+class AnthropicStreamDecoder:
+    """Stateful decoder for the events of one Anthropic stream.
+
+    Args:
+        model: Reported as the chunk model until ``message_start`` names one.
+    """
+
+    def __init__(self, model: str | None = None) -> None:
+        self.id = ""
+        self.model = model
+        self._input_tokens = 0
+        # content block index -> tool_calls index
+        self._tool_calls: dict[int, int] = {}
+
+    def decode(self, event: dict[str, Any]) -> StreamingModelResponse | None:
+        """Return the chunk for ``event``, ``None`` for events without content (ping, block stop)."""
+        kind = event["type"]
+        if kind == "message_start":
+            message = event["message"]
+            self.id = message.get("id", self.id)
+            self.model = message.get("model", self.model)
+            usage = message.get("usage") or {}
+            # cached prompt tokens are reported apart from input_tokens
+            self._input_tokens = (
+                usage.get("input_tokens", 0)
+                + (usage.get("cache_creation_input_tokens") or 0)
+                + (usage.get("cache_read_input_tokens") or 0)
+            )
+            return self._chunk(Message(role="assistant"))
+        if kind == "content_block_start":
+            return self._block_start(event["index"], event["content_block"])
+        if kind == "content_block_delta":
+            return self._block_delta(event["index"], event["delta"])
+        if kind == "message_delta":
+            stop_reason = event["delta"].get("stop_reason")
+            output_tokens = (event.get("usage") or {}).get("output_tokens", 0)
+            usage = Usage(
+                prompt_tokens=self._input_tokens,
+                completion_tokens=output_tokens,
+                total_tokens=self._input_tokens + output_tokens,
+            )
+            finish_reason = FINISH_REASONS.get(stop_reason, stop_reason)
+            return self._chunk(Message(role="assistant"), finish_reason=finish_reason, usage=usage)
+        if kind == "error":
+            error = event.get("error") or {}
+            error_type = error.get("type", "api_error")
+            return StreamingModelResponse(
+                error={
+                    "message": error.get("message", error_type),
+                    "type": error_type,
+                    "code": error_type,
+                    "status_code": ERROR_STATUS.get(error_type, 500),
+                }
+            )
+        return None
+
+    def _block_start(self, index: int, block: dict[str, Any]) -> StreamingModelResponse | None:
+        if block["type"] == "tool_use":
+            position = self._tool_calls.setdefault(index, len(self._tool_calls))
+            tool_call = {
+                "index": position,
+                "id": block["id"],
+                "type": "function",
+                "function": {"name": block["name"], "arguments": ""},
+            }
+            return self._chunk(Message(role="assistant", tool_calls=[tool_call]))
+        if block["type"] == "text" and block.get("text"):
+            return self._chunk(Message(role="assistant", content=block["text"]))
+        return None
+
+    def _block_delta(self, index: int, delta: dict[str, Any]) -> StreamingModelResponse | None:
+        kind = delta["type"]
+        if kind == "text_delta":
+            return self._chunk(Message(role="assistant", content=delta["text"]))
+        if kind == "thinking_delta":
+            return self._chunk(Message(role="assistant", reasoning_content=delta["thinking"]))
+        if kind == "input_json_delta":
+            tool_call = {"index": self._tool_calls[index], "function": {"arguments": delta["partial_json"]}}
+            return self._chunk(Message(role="assistant", tool_calls=[tool_call]))
+        if kind == "citations_delta":
+            return self._chunk(Message(role="assistant", citations=[_citation(delta["citation"], None)]))
+        # signature_delta 只用于回传 thinking 块，不映射
+        return None
+
+    def _chunk(
+        self, delta: Message, finish_reason: str | None = None, usage: Usage | None = None
+    ) -> StreamingModelResponse:
+        return StreamingModelResponse(
+            id=self.id,
+            object="chat.completion.chunk",
+            created=0,
+            model=self.model,
+            choices=[StreamingChoice(index=0, delta=delta, finish_reason=finish_reason)],
+            usage=usage,
+        )
@@ -0,0 +1,215 @@
+"""Microsoft Entra ID (Azure AD) tokens for Azure OpenAI.
+
+Many Azure OpenAI resources have API keys disabled. With ``cfg.azure_ad``
+the Azure clients send ``Authorization: Bearer <token>`` instead of
+``api-key``, with tokens acquired the way azure-identity's
+``DefaultAzureCredential`` does, without depending on it::
+
+    ModelConfig(
+        provider="azure",
+        model="gpt-4o",
+        api_url="https://my-resource.openai.azure.com",
+        azure_ad=AzureADConfig(),  # AZURE_TENANT_ID/AZURE_CLIENT_ID/... or managed identity
+    )
+
+``AzureADConfig.mode`` selects the credential:
+
+- ``client_secret``: OAuth client credentials of an app registration
+- ``workload_identity``: the federated token in ``AZURE_FEDERATED_TOKEN_FILE``
+  (AKS workload identity) as client assertion
+- ``managed_identity``: the App Service/Functions identity endpoint, or the
+  VM instance metadata service
+- ``default``: the first of the above that is configured, managed identity last
+
+Tokens are cached and refreshed :data:`REFRESH_MARGIN` seconds before they
+expire. Token requests use their own HTTP client, so the request logging of
+the model client never records a client secret.
+"""
+
+from __future__ import annotations
+
+import asyncio
+import os
+import threading
+import time
+from collections.abc import Callable
+from dataclasses import dataclass
+from pathlib import Path
+
+import httpx
+
+from .types import AzureADConfig
+
+__all__ = ["AccessToken", "AzureADCredential", "AzureADError", "REFRESH_MARGIN"]
+
+DEFAULT_AUTHORITY_HOST = "https://login.microsoftonline.com"
+IMDS_URL = "http://169.254.169.254/metadata/identity/oauth2/token"
+# refresh tokens this many seconds before they expire
+REFRESH_MARGIN = 300.0
+TOKEN_TIMEOUT = 10.0
+
+_JWT_BEARER = "urn:ietf:params:oauth:client-assertion-type:jwt-bearer"
+
+
+class AzureADError(ValueError):
+    """No Entra ID credential is configured, or the token request failed."""
+
+
+@dataclass(frozen=True)
+class AccessToken:
+    """A bearer token and when it expires (unix time)."""
+
+    token: str
+    expires_on: float
+
+
+class AzureADCredential:
+    """Acquires and caches Entra ID access tokens for one :class:`AzureADConfig`."""
+
+    def __init__(
+        self,
+        cfg: AzureADConfig,
+        *,
+        client: httpx.Client | None = None,
+        async_client: httpx.AsyncClient | None = None,
+        clock: Callable[[], float] = time.time,
+    ) -> None:
+        self.cfg = cfg
+        self._client = client
+        self._async_client = async_client
+        self._owns_clients = client is None and async_client is None
+        self._clock = clock
+        self._token: AccessToken | None = None
+        self._lock = threading.Lock()
+        self._async_lock: asyncio.Lock | None = None
+
+    def _setting(self, value: str | None, env: str) -> str | None:
+        return value or os.environ.get(env) or None
+
+    def mode(self) -> str:
+        """Return the credential in use, resolving ``"default"``."""
+        if self.cfg.mode != "default":
+            return self.cfg.mode
+        tenant_id = self._setting(self.cfg.tenant_id, "AZURE_TENANT_ID")
+        client_id = self._setting(self.cfg.client_id, "AZURE_CLIENT_ID")
+        if tenant_id and client_id:
+            if self._setting(self.cfg.client_secret, "AZURE_CLIENT_SECRET"):
+                return "client_secret"
+            if os.environ.get("AZURE_FEDERATED_TOKEN_FILE"):
+                return "workload_identity"
+        return "managed_identity"
+
+    def _token_request(self) -> httpx.Request:
+        """Return the token request of :meth:`mode`.
+
+        Raises:
+            AzureADError: If the settings of that credential are missing.
+        """
+        mode = self.mode()
+        client_id = self._setting(self.cfg.client_id, "AZURE_CLIENT_ID")
+        if mode == "managed_identity":
+            return self._managed_identity_request(client_id)
+
+        tenant_id = self._setting(self.cfg.tenant_id, "AZURE_TENANT_ID")
+        if not tenant_id or not client_id:
+            raise AzureADError(f"Entra ID {mode} needs tenant_id and client_id (AZURE_TENANT_ID, AZURE_CLIENT_ID)")
+        form = {"grant_type": "client_credentials", "client_id": client_id, "scope": self.cfg.scope}
+        if mode == "client_secret":
+            secret = self._setting(self.cfg.client_secret, "AZURE_CLIENT_SECRET")
+            if not secret:
+                raise AzureADError("Entra ID client_secret needs client_secret (AZURE_CLIENT_SECRET)")
+            form["client_secret"] = secret
+        else:
+            token_file = os.environ.get("AZURE_FEDERATED_TOKEN_FILE")
+            if not token_file:
+                raise AzureADError("Entra ID workload_identity needs AZURE_FEDERATED_TOKEN_FILE")
+            form["client_assertion_type"] = _JWT_BEARER
+            form["client_assertion"] = Path(token_file).read_text().strip()
+        authority = self._setting(self.cfg.authority_host, "AZURE_AUTHORITY_HOST") or DEFAULT_AUTHORITY_HOST
+        authority = authority.rstrip("/")
+        return httpx.Request("POST", f"{authority}/{tenant_id}/oauth2/v2.0/token", data=form)
+
+    def _managed_identity_request(self, client_id: str | None) -> httpx.Request:
+        query = {"resource": self.cfg.scope.removesuffix("/.default")}
+        if client_id:
+            query["client_id"] = client_id
+        endpoint = os.environ.get("IDENTITY_ENDPOINT")
+        header = os.environ.get("IDENTITY_HEADER")
+        if endpoint and header:
+            # App Service, Functions, Container Apps
+            query["api-version"] = "2019-08-01"
+            return httpx.Request("GET", endpoint, params=query, headers={"X-IDENTITY-HEADER": header})
+        query["api-version"] = "2018-02-01"
+        return httpx.Request("GET", IMDS_URL, params=query, headers={"Metadata": "true"})
+
+    def _cached(self) -> str | None:
+        if self._token is not None and self._token.expires_on - REFRESH_MARGIN > self._clock():
+            return self._token.token
+        return None
+
+    def _store(self, response: httpx.Response) -> str:
+        """Cache the token of ``response``.
+
+        Raises:
+            AzureADError: If the response is an error or carries no token.
+        """
+        try:
+            data = response.json()
+        except ValueError:
+            data = None
+        if not isinstance(data, dict):
+            data = {}
+        if response.is_error or not data.get("access_token"):
+            detail = data.get("error_description") or data.get("message") or data.get("error")
+            raise AzureADError(
+                f"Entra ID token request failed (HTTP {response.status_code}): {detail or response.text}"
+            )
+        if data.get("expires_on"):
+            expires_on = float(data["expires_on"])
+        else:
+            expires_on = self._clock() + float(data.get("expires_in", 3600))
+        self._token = AccessToken(token=data["access_token"], expires_on=expires_on)
+        return self._token.token
+
+    def get_token(self) -> str:
+        """Return a valid access token, requesting a new one when the cached one is about to expire."""
+        with self._lock:
+            token = self._cached()
+            if token is not None:
+                return token
+            request = self._token_request()
+            if self._client is None:
+                self._client = httpx.Client(timeout=TOKEN_TIMEOUT)
+            try:
+                response = self._client.send(request)
+            except httpx.TransportError as e:
+                raise AzureADError(f"Entra ID {self.mode()} token request to {request.url.host} failed: {e}") from e
+            return self._store(response)
+
+    async def aget_token(self) -> str:
+        """Async variant of :meth:`get_token`."""
+        if self._async_lock is None:
+            self._async_lock = asyncio.Lock()
+        async with self._async_lock:
+            token = self._cached()
+            if token is not None:
+                return token
+            request = self._token_request()
+            if self._async_client is None:
+                self._async_client = httpx.AsyncClient(timeout=TOKEN_TIMEOUT)
+            try:
+                response = await self._async_client.send(request)
+            except httpx.TransportError as e:
+                raise AzureADError(f"Entra ID {self.mode()} token request to {request.url.host} failed: {e}") from e
+            return self._store(response)
+
+    def close(self) -> None:
+        """Close the HTTP client created for token requests."""
+        if self._owns_clients and self._client is not None:
+            self._client.close()
+
+    async def aclose(self) -> None:
+        """Close the HTTP clients created for token requests."""
+        if self._owns_clients and self._async_client is not None:
+            await self._async_client.aclose()
+        self.close()
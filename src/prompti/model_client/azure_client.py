@@ -0,0 +1,106 @@
+"""Azure OpenAI client built on the shared OpenAI wire format."""
+
+from __future__ import annotations
+
+from typing import Any
+
+import httpx
+
+from .azure_ad import AzureADCredential
+from .openai_wire import OpenAIWireClient, OpenAIWireMixin, SyncOpenAIWireClient
+from .types import ModelConfig, RunParams
+
+DEFAULT_API_VERSION = "2024-06-01"
+
+
+class AzureWireMixin(OpenAIWireMixin):
+    """Azure differences: ``api-key`` auth and per-deployment URLs.
+
+    ``cfg.api_url`` is the resource endpoint (``https://<name>.openai.azure.com``),
+    ``cfg.deployment`` the deployment name (``cfg.model`` when unset) and
+    ``cfg.api_version`` the API version (``cfg.extra_params["api_version"]``,
+    then :data:`DEFAULT_API_VERSION`, when unset). With ``cfg.azure_ad`` calls
+    authenticate with Entra ID tokens instead of ``cfg.api_key``, see
+    :mod:`.azure_ad`.
+    """
+
+    auth_header = "api-key"
+    auth_scheme = None
+    error_label = "Azure OpenAI API"
+    document_blocks = False
+
+    def __init__(self, cfg: ModelConfig, *args: Any, **kwargs: Any) -> None:
+        super().__init__(cfg, *args, **kwargs)
+        self._credential = AzureADCredential(cfg.azure_ad) if cfg.azure_ad else None
+
+    def _build_headers(self, params: RunParams | None = None) -> dict[str, str]:
+        headers = super()._build_headers(params)
+        if self._credential is not None:
+            # Bearer token is added when sending, see _asend/_send
+            headers.pop(self.auth_header, None)
+        return headers
+
+    def _api_version(self) -> str:
+        return self.cfg.api_version or self.cfg.extra_params.get("api_version", DEFAULT_API_VERSION)
+
+    def _request_url(self, endpoint: str | None = None) -> str:
+        endpoint = (endpoint or self._endpoint() or "").rstrip("/")
+        deployment = self.cfg.deployment or self.cfg.model
+        return f"{endpoint}/openai/deployments/{deployment}/chat/completions?api-version={self._api_version()}"
+
+    def _embeddings_url(self) -> str:
+        return self._request_url().replace("/chat/completions?", "/embeddings?", 1)
+
+    def _files_url(self) -> str:
+        endpoint = (self._endpoint() or "").rstrip("/")
+        return f"{endpoint}/openai/files?api-version={self._api_version()}"
+
+
+class AzureOpenAIClient(AzureWireMixin, OpenAIWireClient):
+    """Azure OpenAI API client."""
+
+    provider = "azure"
+
+    async def _aauthorize(self, request: httpx.Request) -> httpx.Request:
+        if self._credential is not None:
+            request.headers["Authorization"] = f"Bearer {await self._credential.aget_token()}"
+        return request
+
+    async def _asend(self, request: httpx.Request, stream: bool = False) -> httpx.Response:
+        return await super()._asend(await self._aauthorize(request), stream=stream)
+
+    async def aupload_file(
+        self, filename: str, data: bytes, *, media_type: str = "application/pdf", purpose: str = "user_data"
+    ) -> str:
+        request = await self._aauthorize(self._upload_request(filename, data, media_type, purpose))
+        return self._uploaded_file_id(await self._client.send(request))
+
+    async def aclose(self) -> None:
+        if self._credential is not None:
+            await self._credential.aclose()
+        await super().aclose()
+
+
+class SyncAzureOpenAIClient(AzureWireMixin, SyncOpenAIWireClient):
+    """Synchronous Azure OpenAI API client."""
+
+    provider = "azure"
+
+    def _authorize(self, request: httpx.Request) -> httpx.Request:
+        if self._credential is not None:
+            request.headers["Authorization"] = f"Bearer {self._credential.get_token()}"
+        return request
+
+    def _send(self, request: httpx.Request, stream: bool = False) -> httpx.Response:
+        return super()._send(self._authorize(request), stream=stream)
+
+    def upload_file(
+        self, filename: str, data: bytes, *, media_type: str = "application/pdf", purpose: str = "user_data"
+    ) -> str:
+        request = self._authorize(self._upload_request(filename, data, media_type, purpose))
+        return self._uploaded_file_id(self._client.send(request))
+
+    def close(self) -> None:
+        if self._credential is not None:
+            self._credential.close()
+        super().close()
@@ -1,171 +1,248 @@
-"""Base classes and data models for model clients."""
+"""Base classes for model clients; the data models live in :mod:`.types`."""
 
 from __future__ import annotations
 
+import asyncio
 import json
 import logging
+import random
+import time
 from collections.abc import AsyncGenerator
-from datetime import datetime, timezone
-from enum import Enum
+from contextlib import aclosing, closing
+from datetime import datetime, timedelta, timezone
 from time import perf_counter
-from typing import Any, Union
+from typing import TYPE_CHECKING, Any, Union
 
 import httpx
-from opentelemetry import trace
-from opentelemetry.baggage import set_baggage
-from prometheus_client import Counter, Gauge, Histogram
-from pydantic import BaseModel, Field, model_validator
-from tenacity import retry, stop_after_attempt, wait_exponential_jitter
 from collections.abc import Generator
 
-from ..message import Message, ModelResponse, StreamingModelResponse
-from typing import Optional
-
-
-class ModelConfig(BaseModel):
-    """Static connection and default generation parameters."""
-
-    provider: Optional[str] | None = None
-    model: Optional[str] | None = None
-    api_key: Optional[str] | None = None
-    api_url: Optional[str] | None = None
-
-    # generation defaults (may be overridden per call)
-    temperature: Optional[float] = None
-    top_p: Optional[float] | None = None
-    max_tokens: Optional[int] | None = None
-    
-    # extra parameters for client construction
-    extra_params: dict[str, Any] = {}
-
-
-class ToolSpec(BaseModel):
-    """Specification for a single tool."""
-
-    name: str
-    description: str
-    parameters: dict[str, Any]
-
-
-class ToolChoice(str, Enum):
-    """Allowed tool invocation policies."""
-
-    AUTO = "auto"
-    BLOCK = "none"
-    REQUIRED = "required"
-    FORCE = "force"
-
+from .._otel import set_baggage, trace
+from ..images import prepare_images
+from ..message import AttemptInfo, Message, ModelResponse, StreamingModelResponse, Timing, Usage
+from ..message_order import normalize_messages, rules_for
+from ..postprocess import ResponsePostprocessor
+from ..roles import default_role_map, map_roles
+from ..telemetry import ClientMetrics, get_metrics
+from ..textnorm import normalize_message_text
+from .deprecations import fallback_model
+from .json_mode import emulate_json_mode, needs_json_emulation
+from .ratelimit import RateLimitWaitError, limiter_for, request_tokens
+from .transport import build_async_http_client, build_sync_http_client, decoded_request_body, http_timeout
+from .types import (  # noqa: F401 - re-exported, existing code imports these from base
+    ErrorClass,
+    KeepAliveConfig,
+    ModelConfig,
+    RateLimitHeadroom,
+    RetryConfig,
+    RunParams,
+    TokenCounts,
+    ToolChoice,
+    ToolParams,
+    ToolSpec,
+    UsageReport,
+    parse_retry_after,
+)
+
+if TYPE_CHECKING:
+    from .best_of import BestOf, Scorer
+
+
+def classify_error(
+    error: dict[str, Any] | None = None,
+    exc: BaseException | None = None,
+    streaming_started: bool = False,
+) -> ErrorClass:
+    """Classify a failed call from its error object or raised exception.
+
+    Failures after the first response was yielded are always ``stream``.
+    ``auth``/``client`` point at our own configuration, while ``timeout``,
+    ``rate_limit`` and ``server`` point at the provider.
+    """
+    if streaming_started:
+        return ErrorClass.STREAM
+    error = error or {}
+    status = error.get("status_code")
+    code = error.get("code")
+    if isinstance(exc, httpx.HTTPStatusError):
+        status = exc.response.status_code
+    if code in ("timeout", "deadline_exceeded") or status == 408 or isinstance(exc, httpx.TimeoutException):
+        return ErrorClass.TIMEOUT
+    if status == 429 or code == "rate_limit_exceeded" or isinstance(exc, RateLimitWaitError):
+        return ErrorClass.RATE_LIMIT
+    if status in (401, 403) or code == "invalid_api_key":
+        return ErrorClass.AUTH
+    if (status is not None and status >= 500) or code == "network_error" or isinstance(exc, httpx.TransportError):
+        return ErrorClass.SERVER
+    return ErrorClass.CLIENT
+
+
+_request_sample_logger = logging.getLogger("model_client.request_sample")
+_SENSITIVE_REQUEST_FIELDS = {"api_key", "authorization", "token", "secret", "password"}
+
+
+def log_sampled_request(cfg: ModelConfig, request_data: dict[str, Any]) -> None:
+    """Log the serialized provider request body for a sample of calls.
+
+    Sampling is controlled by ``cfg.request_log_sample_rate`` and the record is
+    emitted at DEBUG on the ``model_client.request_sample`` logger, so it is
+    opt-in twice: a non-zero rate and a logger configured for DEBUG.
+    """
+    rate = cfg.request_log_sample_rate or 0.0
+    if rate <= 0 or not _request_sample_logger.isEnabledFor(logging.DEBUG):
+        return
+    if random.random() >= rate:  # noqa: S311 - sampling, not security
+        return
+    body = {k: "[REDACTED]" if k in _SENSITIVE_REQUEST_FIELDS else v for k, v in request_data.items()}
+    _request_sample_logger.debug(
+        json.dumps(
+            {"provider": cfg.provider, "model": cfg.model, "request": body},
+            ensure_ascii=False,
+            default=str,
+            separators=(",", ":"),
+        )
+    )
 
-class ToolParams(BaseModel):
-    """Tool catalogue and invocation configuration."""
 
-    tools: list[ToolSpec]
-    choice: ToolChoice | dict[str, Any] = ToolChoice.AUTO
-    force_tool: str | None = None
-    parallel_allowed: bool = True
-    max_calls: int | None = None
+def build_extra_headers(cfg: ModelConfig, params: RunParams | None = None) -> dict[str, str]:
+    """Return the provider-independent headers for one request.
+
+    Covers the project and beta-feature headers, custom headers from the
+    config and the call (the call wins) and the idempotency key. Clients call
+    this from their single request builder so streaming and non-streaming
+    requests carry the same headers.
+    """
+    headers: dict[str, str] = {}
+    if cfg.project:
+        headers["OpenAI-Project"] = cfg.project
+    if cfg.beta_features:
+        # OpenAI-compatible gateways in front of Anthropic forward this header
+        headers["anthropic-beta"] = ",".join(cfg.beta_features)
+    headers.update(cfg.extra_headers or {})
+    if params is not None:
+        headers.update(params.extra_headers)
+        if params.idempotency_key:
+            headers["Idempotency-Key"] = params.idempotency_key
+    return headers
+
+
+class EventHook:
+    """Lifecycle callbacks for a single model call.
+
+    Subclass and override the methods you need, then register the hook with
+    :meth:`ModelClient.add_event_hook` or ``create_client(..., event_hooks=[...])``.
+    Hooks are called synchronously from the request flow and exceptions they
+    raise are logged and ignored.
+    """
+
+    def on_request_start(self, cfg: ModelConfig, params: RunParams) -> None:
+        """Called before the first attempt is sent."""
+
+    def on_retry(
+        self, cfg: ModelConfig, params: RunParams, attempt: int, delay: float, error: dict[str, Any] | BaseException
+    ) -> None:
+        """Called when attempt number ``attempt`` failed and is retried after ``delay`` seconds."""
 
+    def on_first_token(self, cfg: ModelConfig, params: RunParams, latency: float) -> None:
+        """Called when the first response arrives, ``latency`` seconds after the start."""
 
-class RunParams(BaseModel):
-    """Per-call parameters for :class:`ModelClient.run`."""
+    def on_complete(self, cfg: ModelConfig, params: RunParams, usage: Usage | None, duration: float) -> None:
+        """Called when the call finished without error."""
 
-    messages: list[Message]
-    tool_params: ToolParams | list[ToolSpec] | list[dict] | None = None
+    def on_error(
+        self, cfg: ModelConfig, params: RunParams, error_class: str, error: dict[str, Any] | BaseException
+    ) -> None:
+        """Called when the call failed with an error response or an exception."""
 
-    # sampling & length
-    temperature: float | None = None
-    top_p: float | None = None
-    top_k: int | None = None
-    max_tokens: int | None = None
-    stop: str | list[str] | None = None
 
-    # control & reproducibility
-    stream: bool = True
-    n: int | None = None
-    seed: int | None = None
-    logit_bias: dict[int, float] | None = None
-    response_format: str | None = None
+def _emit_event(hooks: list[EventHook], logger: logging.Logger, name: str, *args: Any) -> None:
+    """Invoke ``name`` on every hook, logging instead of propagating hook failures."""
+    for hook in hooks:
+        try:
+            getattr(hook, name)(*args)
+        except Exception:
+            logger.exception("Event hook %s.%s failed", type(hook).__name__, name)
+
+
+def _attempt_info(
+    cfg: ModelConfig, attempt: int, latency: float, status: str, error: dict[str, Any] | BaseException | None = None
+) -> AttemptInfo:
+    """Describe one try of a call for :attr:`ModelResponse.attempts`."""
+    status_code = message = None
+    if isinstance(error, BaseException):
+        message = f"{type(error).__name__}: {error}"
+    elif error:
+        status_code = error.get("status_code")
+        message = str(error.get("message") or error)
+    return AttemptInfo(
+        attempt=attempt,
+        provider=cfg.provider,
+        model=cfg.model,
+        status=status,
+        status_code=status_code,
+        latency=latency,
+        error=message,
+    )
 
-    # misc
-    user_id: str | None = None
-    request_id: str | None = None
-    session_id: str | None = None  # Deprecated: use conversation_id instead
-    conversation_id: str | None = None
-    span_id : str | None = None
-    parent_span_id : str | None = None
-    source: str | None = None
-    extra_params: dict[str, Any] = {}
 
-    
-    # trace data capture - used to pass data between engine and model client
-    trace_context: dict[str, Any] = {}
-    
-    @model_validator(mode='before')
-    @classmethod
-    def handle_session_conversation_compatibility(cls, data):
-        """Handle backward compatibility between session_id and conversation_id."""
-        if isinstance(data, dict):
-            # If only session_id is provided, copy to conversation_id
-            if 'session_id' in data and 'conversation_id' not in data:
-                data['conversation_id'] = data['session_id']
-            # If only conversation_id is provided, copy to session_id for backward compatibility
-            elif 'conversation_id' in data and 'session_id' not in data:
-                data['session_id'] = data['conversation_id']
-            # If both are provided, conversation_id takes precedence
-            elif 'conversation_id' in data and 'session_id' in data:
-                data['session_id'] = data['conversation_id']
-        
-        return data
+def _start_deadline(cfg: ModelConfig, params: RunParams, start: float) -> float | None:
+    """Record the ``perf_counter`` time by which the call must end in ``params.trace_context["deadline"]``."""
+    if cfg.timeout is None or cfg.timeout.deadline_s is None:
+        params.trace_context.pop("deadline", None)
+        return None
+    deadline = params.trace_context["deadline"] = start + cfg.timeout.deadline_s
+    return deadline
+
+
+def _record_rate_limit_wait(cfg: ModelConfig, params: RunParams, metrics: ClientMetrics, waited: float) -> None:
+    """Account the seconds one attempt waited for the client-side rate limit."""
+    metrics.rate_limit_wait.labels(cfg.provider, cfg.model).observe(waited)
+    perf = params.trace_context["perf_metrics"]
+    perf["rate_limit_wait"] = perf.get("rate_limit_wait", 0.0) + waited
+    if waited:
+        trace.get_current_span().add_event("rate_limit_wait", {"wait_s": waited})
+
+
+def _response_postprocessor(cfg: ModelConfig, params: RunParams) -> ResponsePostprocessor | None:
+    """Build the response post-processing of one call, or ``None`` when there is nothing to do."""
+    stop = None
+    if cfg.emulate_stop and params.stop:
+        stop = [params.stop] if isinstance(params.stop, str) else list(params.stop)
+    limited = cfg.max_output_chars is not None or cfg.max_output_tokens is not None
+    if not params.postprocessors and not stop and not limited:
+        return None
+    return ResponsePostprocessor(
+        params.postprocessors,
+        stop=stop,
+        choices=params.n or 1,
+        max_chars=cfg.max_output_chars,
+        max_tokens=cfg.max_output_tokens,
+    )
 
 
 class ModelClient:
     """Base class for model clients."""
 
     provider: str = "generic"
+    # the provider accepts ``response_format``; otherwise JSON mode is emulated, see :mod:`.json_mode`
+    native_json_mode: bool = True
 
-    _counter = Counter("llm_tokens_total", "Tokens in/out", labelnames=["direction"])
-    _histogram = Histogram("llm_request_latency_seconds", "LLM latency", labelnames=["provider"])
-    _inflight = Gauge(
-        "llm_inflight_requests",
-        "Inflight LLM requests",
-        labelnames=["provider", "is_error"],
-    )
-    _request_counter = Counter(
-        "llm_requests_total",
-        "LLM request results",
-        labelnames=["provider", "result", "is_error"],
-    )
-    _first_token = Histogram(
-        "llm_first_token_latency_seconds",
-        "Time to first token",
-        labelnames=["provider", "model"],
-        buckets=(0.1, 0.25, 0.5, 1, 2, 5, 10),
-    )
-    _token_gap = Histogram(
-        "llm_stream_intertoken_gap_seconds",
-        "Gap between streamed tokens",
-        labelnames=["provider", "model"],
-    )
-    _prompt_tokens = Counter(
-        "llm_prompt_tokens_total",
-        "Prompt tokens sent to the provider",
-        labelnames=["provider", "model"],
-    )
-    _completion_tokens = Counter(
-        "llm_completion_tokens_total",
-        "Completion tokens received from the provider",
-        labelnames=["provider", "model"],
-    )
+    @property
+    def _metrics(self) -> ClientMetrics:
+        """Metrics shared by all clients, see :func:`prompti.telemetry.configure_telemetry`."""
+        return get_metrics()
 
     def __init__(
         self, cfg: ModelConfig, client: httpx.AsyncClient | None = None, is_debug: bool = False, **_: Any
     ) -> None:
         """Create the client with static :class:`ModelConfig` and optional HTTP client."""
         self.cfg = cfg
-        self._client = client or httpx.AsyncClient(http2=True, timeout=httpx.Timeout(600))
+        self._client = client or build_async_http_client(cfg, timeout=http_timeout(cfg))
         self._tracer = trace.get_tracer(__name__)
         self._logger = logging.getLogger("model_client")
         self._is_debug = is_debug
+        self.event_hooks: list[EventHook] = []
+        # latest rate-limit headers of the provider, if it sends them
+        self.rate_limit: RateLimitHeadroom | None = None
 
         if self._is_debug:
             self._client.event_hooks.setdefault("request", []).append(self._log_request)
@@ -182,7 +259,7 @@ class ModelClient:
         for k, v in request.headers.items():
             command += f" \\\n  -H '{k}: {v}'"
 
-        body_bytes = request.content
+        body_bytes = decoded_request_body(request)
         if body_bytes:
             body_str = ""
             try:
@@ -242,8 +319,8 @@ class ModelClient:
                         sanitized[field] = "[REDACTED]"
                 return sanitized
             return data
-        except (json.JSONDecodeError, UnicodeDecodeError):
-            # If not JSON or can't decode, return truncated string
+        except (json.JSONDecodeError, UnicodeDecodeError, RecursionError):
+            # If not JSON, too deeply nested or can't decode, return truncated string
             return body[:1000] + "..." if len(body) > 1000 else body
 
     async def _log_request_jsonl(self, request: httpx.Request) -> None:
@@ -251,7 +328,7 @@ class ModelClient:
         body_str = ""
         if request.content:
             try:
-                body_str = request.content.decode()
+                body_str = decoded_request_body(request).decode()
             except UnicodeDecodeError:
                 body_str = "<binary data>"
 
@@ -293,7 +370,6 @@ class ModelClient:
 
         self._logger.info(json.dumps(log_data, separators=(",", ":")))
 
-    @retry(wait=wait_exponential_jitter(), stop=stop_after_attempt(3))
     async def arun(self, params: RunParams) -> AsyncGenerator[Union[ModelResponse, StreamingModelResponse], None]:
         """Execute the LLM call with dynamic ``params``.
         
@@ -301,9 +377,14 @@ class ModelClient:
             AsyncGenerator yielding ModelResponse for non-streaming calls or 
             StreamingResponse for streaming calls.
         """
+        params = self._normalize_messages(self._prepare_json_mode(params))
+        postprocess = _response_postprocessor(self.cfg, params)
         is_error = False
-        self._inflight.labels(self.cfg.provider, "false").inc()
+        metrics = self._metrics
+        metrics.inflight.labels(self.cfg.provider, "false").inc()
         result = "success"
+        error_class = ""
+        usage = None
         start = perf_counter()
         first = True
         last = start
@@ -332,6 +413,9 @@ class ModelClient:
             attrs["user.conversation_id"] = params.conversation_id
         if params.user_id:
             attrs["user.id"] = params.user_id
+        request_hash = params.canonical_hash(self.cfg.model)
+        attrs["llm.request_hash"] = request_hash
+        params.trace_context["request_hash"] = request_hash
 
         for key, val in (
             ("request_id", params.request_id),
@@ -344,30 +428,128 @@ class ModelClient:
 
         with (
             self._tracer.start_as_current_span("llm.call", attributes=attrs),
-            self._histogram.labels(self.cfg.provider).time(),
+            metrics.request_latency.labels(self.cfg.provider).time(),
         ):
             params.trace_context["perf_metrics"] = {}
+            policy = self.cfg.retry or RetryConfig()
+            deadline = _start_deadline(self.cfg, params, start)
+            attempt = 0
+            retry_error = last_error = None
+            attempts: list[AttemptInfo] = []
+            substituted = False
+            limiter = limiter_for(self.cfg)
+            tokens = request_tokens(self.cfg, params) if limiter is not None else 0
+            reservation = None
+            self._emit("on_request_start", params)
             try:
-                async for response in self._run(params):
-                    now = perf_counter()
-                    if first:
-                        self._first_token.labels(self.cfg.provider, self.cfg.model).observe(now - start)
-                        params.trace_context["perf_metrics"]["first_package_latency"] = now - start
-                        params.trace_context["perf_metrics"]["total_latency"] = now - start
-                        first = False
-                    else:
-                        self._token_gap.labels(self.cfg.provider, self.cfg.model).observe(now - last)
-                        params.trace_context["perf_metrics"]["total_latency"] = now - start
-                    last = now
-                    yield response
-
+                while True:
+                    attempt += 1
+                    if limiter is not None:
+                        reservation, waited = await limiter.acquire(tokens)
+                        _record_rate_limit_wait(self.cfg, params, metrics, waited)
+                    attempt_start = perf_counter()
+                    delay = None
+                    try:
+                        async with aclosing(self._run(params)) as responses:
+                            async for response in responses:
+                                if first and not substituted:
+                                    replacement = fallback_model(self.cfg, response.error)
+                                    if replacement:
+                                        self._substitute_model(replacement)
+                                        substituted = True
+                                        delay, retry_error = 0.0, response.error
+                                        break
+                                if first and policy.should_retry(response.error, attempt):
+                                    delay = policy.backoff(attempt, response.error.get("retry_after"))
+                                    retry_error = response.error
+                                    break
+                                if response.error:
+                                    is_error = True
+                                    result = "error"
+                                    if first and policy.is_retryable(response.error):
+                                        response.error["retries_exhausted"] = True
+                                    error_class = classify_error(response.error, streaming_started=not first).value
+                                    response.error.setdefault("error_class", error_class)
+                                    last_error = response.error
+                                now = perf_counter()
+                                is_first = first
+                                if first:
+                                    metrics.first_token.labels(self.cfg.provider, self.cfg.model).observe(now - start)
+                                    params.trace_context["perf_metrics"]["first_package_latency"] = now - start
+                                    params.trace_context["perf_metrics"]["total_latency"] = now - start
+                                    first = False
+                                    self._emit("on_first_token", params, now - start)
+                                else:
+                                    metrics.token_gap.labels(self.cfg.provider, self.cfg.model).observe(now - last)
+                                    params.trace_context["perf_metrics"]["total_latency"] = now - start
+                                last = now
+                                if response.usage:
+                                    usage = response.usage
+                                if is_first or response.usage or response.get_finish_reason():
+                                    response.timing = Timing.measure(
+                                        params.trace_context["perf_metrics"]["first_package_latency"],
+                                        now - start,
+                                        response.usage,
+                                    )
+                                    if response.timing.output_tokens_per_sec is not None:
+                                        params.trace_context["perf_metrics"]["output_tokens_per_sec"] = (
+                                            response.timing.output_tokens_per_sec
+                                        )
+                                    if attempts:
+                                        status = "error" if response.error else "success"
+                                        current = _attempt_info(
+                                            self.cfg, attempt, now - attempt_start, status, response.error
+                                        )
+                                        response.attempts = [*attempts, current]
+                                if postprocess is not None:
+                                    postprocess.apply(response)
+                                yield response
+                                if postprocess is not None and postprocess.stopped:
+                                    # leaving the loop closes the stream and the upstream request
+                                    break
+                    except httpx.TransportError as e:
+                        code = "timeout" if isinstance(e, httpx.TimeoutException) else "network_error"
+                        if not first or not policy.should_retry({"code": code}, attempt):
+                            raise
+                        delay = policy.backoff(attempt)
+                        retry_error = e
+                    if delay is None:
+                        break
+                    if deadline is not None:
+                        # the next attempt ends with ``deadline_exceeded`` when no time is left
+                        delay = min(delay, max(deadline - perf_counter(), 0.0))
+                    latency = perf_counter() - attempt_start
+                    attempts.append(_attempt_info(self.cfg, attempt, latency, "retried", retry_error))
+                    self._emit("on_retry", params, attempt, delay, retry_error)
+                    self._logger.warning(
+                        "Retrying %s request (attempt %d/%d) in %.2fs", self.cfg.provider, attempt + 1,
+                        policy.max_attempts, delay,
+                    )
+                    await asyncio.sleep(delay)
+
+                if postprocess is not None and not is_error:
+                    tail = postprocess.finish()
+                    if tail is not None:
+                        yield tail
+                if is_error:
+                    self._emit("on_error", params, error_class, last_error)
+                else:
+                    self._emit("on_complete", params, usage, perf_counter() - start)
             except Exception as e:
                 is_error = True
                 result = "error"
+                error_class = classify_error(exc=e, streaming_started=not first).value
+                self._emit("on_error", params, error_class, e)
                 raise
             finally:
-                self._inflight.labels(self.cfg.provider, "false").dec()
-                self._request_counter.labels(self.cfg.provider, result, str(is_error).lower()).inc()
+                metrics.inflight.labels(self.cfg.provider, "false").dec()
+                metrics.requests.labels(self.cfg.provider, result, str(is_error).lower(), error_class).inc()
+                if usage is not None:
+                    metrics.record_usage(
+                        self.cfg.provider, self.cfg.model, usage.prompt_tokens, usage.completion_tokens
+                    )
+                    if reservation is not None:
+                        limiter.settle(reservation, usage.total_tokens)
 
     async def _run(self, params: RunParams) -> AsyncGenerator[Union[ModelResponse, StreamingModelResponse], None]:
         """Internal method to be implemented by subclasses.
@@ -382,6 +564,77 @@ class ModelClient:
         raise NotImplementedError
         yield  # pragma: no cover - satisfies generator type
 
+    def _emit(self, name: str, params: RunParams, *args: Any) -> None:
+        """Dispatch a lifecycle event to the registered :class:`EventHook` objects."""
+        if self.event_hooks:
+            _emit_event(self.event_hooks, self._logger, name, self.cfg, params, *args)
+
+    def _substitute_model(self, replacement: str) -> None:
+        """Switch to ``replacement`` after the provider reported ``cfg.model`` as not found."""
+        self._logger.warning("%s model %s not found, substituting %s", self.cfg.provider, self.cfg.model, replacement)
+        self.cfg = self.cfg.model_copy(update={"model": replacement})
+
+    def _prepare_json_mode(self, params: RunParams) -> RunParams:
+        """Emulate the JSON mode of ``params`` when the provider or ``cfg.emulate_json_mode`` asks for it."""
+        if not needs_json_emulation(self.cfg, params, self.native_json_mode):
+            return params
+        return emulate_json_mode(params)
+
+    def _normalize_messages(self, params: RunParams) -> RunParams:
+        """Map roles, fit images to ``cfg.image_limits``, normalize text and apply ``cfg.message_normalization``."""
+        role_map = self.cfg.role_map if self.cfg.role_map is not None else default_role_map(self.cfg.model)
+        messages = map_roles(params.messages, role_map)
+        if self.cfg.image_limits is not None:
+            messages = prepare_images(messages, self.cfg.image_limits)
+        if self.cfg.normalize_prompt_text:
+            messages = normalize_message_text(messages)
+        if self.cfg.message_normalization:
+            messages = normalize_messages(messages, rules_for(self.cfg.provider), mode=self.cfg.message_normalization)
+        if messages is params.messages:
+            return params
+        return params.model_copy(update={"messages": messages})
+
+    def add_event_hook(self, hook: EventHook) -> None:
+        """Register ``hook`` for lifecycle events of every call made by this client."""
+        self.event_hooks.append(hook)
+
+    def annotate_tokens(
+        self, messages: list[Message], tools: ToolParams | list[ToolSpec] | list[dict] | None = None
+    ) -> TokenCounts:
+        """Return the estimated prompt tokens of each of ``messages`` and of ``tools``, see :class:`TokenCounts`."""
+        return TokenCounts.estimate(messages, tools)
+
+    async def abest_of(self, params: RunParams, n: int, scorer: Scorer, *, use_n: bool = False) -> BestOf:
+        """Sample ``n`` completions of ``params`` concurrently and return them all with the best scored one.
+
+        ``scorer(params, response)`` returns a number, higher is better, see
+        :mod:`.best_of`. Failed completions and scorer errors are kept in the
+        result with ``score=None``. ``use_n=True`` asks for all completions
+        in one request with ``params.n``.
+        """
+        from .best_of import abest_of
+
+        return await abest_of(self, params, n, scorer, use_n=use_n)
+
+    async def ausage_report(self, period: timedelta | tuple[datetime, datetime]) -> UsageReport:
+        """Return the provider-side usage and spend for ``period`` (a window ending now or ``(start, end)``).
+
+        Raises:
+            NotImplementedError: If the provider has no usage API.
+        """
+        raise NotImplementedError(f"{self.provider} does not expose a usage API")
+
+    async def aembeddings(self, body: dict[str, Any]) -> dict[str, Any]:
+        """Send an OpenAI ``/v1/embeddings`` request ``body`` and return the provider's JSON response.
+
+        ``body["model"]`` is replaced by ``cfg.model``.
+
+        Raises:
+            NotImplementedError: If the provider has no embeddings endpoint.
+            httpx.HTTPStatusError: If the provider rejects the request.
+        """
+        raise NotImplementedError(f"{self.provider} does not expose an embeddings API")
+
     async def aclose(self) -> None:
         """Close the underlying HTTP client."""
         await self._client.aclose()
@@ -406,26 +659,24 @@ class SyncModelClient:
     """Synchronous base class for model clients."""
 
     provider: str = "generic"
+    # the provider accepts ``response_format``; otherwise JSON mode is emulated, see :mod:`.json_mode`
+    native_json_mode: bool = True
 
-    # Reuse the same metrics from async version to avoid duplication
-    _counter = ModelClient._counter
-    _histogram = ModelClient._histogram
-    _inflight = ModelClient._inflight
-    _request_counter = ModelClient._request_counter
-    _first_token = ModelClient._first_token
-    _token_gap = ModelClient._token_gap
-    _prompt_tokens = ModelClient._prompt_tokens
-    _completion_tokens = ModelClient._completion_tokens
+    # Share the metrics with the async clients
+    _metrics = ModelClient._metrics
 
     def __init__(
         self, cfg: ModelConfig, client: httpx.Client | None = None, is_debug: bool = False, **_: Any
     ) -> None:
         """Create the client with static :class:`ModelConfig` and optional HTTP client."""
         self.cfg = cfg
-        self._client = client or httpx.Client(http2=True, timeout=httpx.Timeout(600))
+        self._client = client or build_sync_http_client(cfg, timeout=http_timeout(cfg))
         self._tracer = trace.get_tracer(__name__)
         self._logger = logging.getLogger("model_client")
         self._is_debug = is_debug
+        self.event_hooks: list[EventHook] = []
+        # latest rate-limit headers of the provider, if it sends them
+        self.rate_limit: RateLimitHeadroom | None = None
 
         if self._is_debug:
             self._client.event_hooks.setdefault("request", []).append(self._log_request)
@@ -442,7 +693,7 @@ class SyncModelClient:
         for k, v in request.headers.items():
             command += f" \\\n  -H '{k}: {v}'"
 
-        body_bytes = request.content
+        body_bytes = decoded_request_body(request)
         if body_bytes:
             body_str = ""
             try:
@@ -479,7 +730,7 @@ class SyncModelClient:
         body_str = ""
         if request.content:
             try:
-                body_str = request.content.decode()
+                body_str = decoded_request_body(request).decode()
             except UnicodeDecodeError:
                 body_str = "<binary data>"
 
@@ -543,10 +794,9 @@ class SyncModelClient:
                         sanitized[field] = "[REDACTED]"
                 return sanitized
             return data
-        except (json.JSONDecodeError, UnicodeDecodeError):
+        except (json.JSONDecodeError, UnicodeDecodeError, RecursionError):
             return body[:1000] + "..." if len(body) > 1000 else body
 
-    @retry(wait=wait_exponential_jitter(), stop=stop_after_attempt(3))
     def run(self, params: RunParams) -> Generator[Union[ModelResponse, StreamingModelResponse], None, None]:
         """Execute the LLM call with dynamic ``params``.
         
@@ -554,9 +804,14 @@ class SyncModelClient:
             Generator yielding ModelResponse for non-streaming calls or 
             StreamingResponse for streaming calls.
         """
+        params = self._normalize_messages(self._prepare_json_mode(params))
+        postprocess = _response_postprocessor(self.cfg, params)
         is_error = False
-        self._inflight.labels(self.cfg.provider, "false").inc()
+        metrics = self._metrics
+        metrics.inflight.labels(self.cfg.provider, "false").inc()
         result = "success"
+        error_class = ""
+        usage = None
         start = perf_counter()
         first = True
         last = start
@@ -583,6 +838,9 @@ class SyncModelClient:
             attrs["user.conversation_id"] = params.conversation_id
         if params.user_id:
             attrs["user.id"] = params.user_id
+        request_hash = params.canonical_hash(self.cfg.model)
+        attrs["llm.request_hash"] = request_hash
+        params.trace_context["request_hash"] = request_hash
 
         for key, val in (
             ("request_id", params.request_id),
@@ -595,30 +853,128 @@ class SyncModelClient:
 
         with (
             self._tracer.start_as_current_span("llm.call", attributes=attrs),
-            self._histogram.labels(self.cfg.provider).time(),
+            metrics.request_latency.labels(self.cfg.provider).time(),
         ):
             params.trace_context["perf_metrics"] = {}
+            policy = self.cfg.retry or RetryConfig()
+            deadline = _start_deadline(self.cfg, params, start)
+            attempt = 0
+            retry_error = last_error = None
+            attempts: list[AttemptInfo] = []
+            substituted = False
+            limiter = limiter_for(self.cfg)
+            tokens = request_tokens(self.cfg, params) if limiter is not None else 0
+            reservation = None
+            self._emit("on_request_start", params)
             try:
-                for response in self._run(params):
-                    now = perf_counter()
-                    if first:
-                        self._first_token.labels(self.cfg.provider, self.cfg.model).observe(now - start)
-                        params.trace_context["perf_metrics"]["first_package_latency"] = now - start
-                        params.trace_context["perf_metrics"]["total_latency"] = now - start
-                        first = False
-                    else:
-                        self._token_gap.labels(self.cfg.provider, self.cfg.model).observe(now - last)
-                        params.trace_context["perf_metrics"]["total_latency"] = now - start
-                    last = now
-                    yield response
-
+                while True:
+                    attempt += 1
+                    if limiter is not None:
+                        reservation, waited = limiter.acquire_sync(tokens)
+                        _record_rate_limit_wait(self.cfg, params, metrics, waited)
+                    attempt_start = perf_counter()
+                    delay = None
+                    try:
+                        with closing(self._run(params)) as responses:
+                            for response in responses:
+                                if first and not substituted:
+                                    replacement = fallback_model(self.cfg, response.error)
+                                    if replacement:
+                                        self._substitute_model(replacement)
+                                        substituted = True
+                                        delay, retry_error = 0.0, response.error
+                                        break
+                                if first and policy.should_retry(response.error, attempt):
+                                    delay = policy.backoff(attempt, response.error.get("retry_after"))
+                                    retry_error = response.error
+                                    break
+                                if response.error:
+                                    is_error = True
+                                    result = "error"
+                                    if first and policy.is_retryable(response.error):
+                                        response.error["retries_exhausted"] = True
+                                    error_class = classify_error(response.error, streaming_started=not first).value
+                                    response.error.setdefault("error_class", error_class)
+                                    last_error = response.error
+                                now = perf_counter()
+                                is_first = first
+                                if first:
+                                    metrics.first_token.labels(self.cfg.provider, self.cfg.model).observe(now - start)
+                                    params.trace_context["perf_metrics"]["first_package_latency"] = now - start
+                                    params.trace_context["perf_metrics"]["total_latency"] = now - start
+                                    first = False
+                                    self._emit("on_first_token", params, now - start)
+                                else:
+                                    metrics.token_gap.labels(self.cfg.provider, self.cfg.model).observe(now - last)
+                                    params.trace_context["perf_metrics"]["total_latency"] = now - start
+                                last = now
+                                if response.usage:
+                                    usage = response.usage
+                                if is_first or response.usage or response.get_finish_reason():
+                                    response.timing = Timing.measure(
+                                        params.trace_context["perf_metrics"]["first_package_latency"],
+                                        now - start,
+                                        response.usage,
+                                    )
+                                    if response.timing.output_tokens_per_sec is not None:
+                                        params.trace_context["perf_metrics"]["output_tokens_per_sec"] = (
+                                            response.timing.output_tokens_per_sec
+                                        )
+                                    if attempts:
+                                        status = "error" if response.error else "success"
+                                        current = _attempt_info(
+                                            self.cfg, attempt, now - attempt_start, status, response.error
+                                        )
+                                        response.attempts = [*attempts, current]
+                                if postprocess is not None:
+                                    postprocess.apply(response)
+                                yield response
+                                if postprocess is not None and postprocess.stopped:
+                                    # leaving the loop closes the stream and the upstream request
+                                    break
+                    except httpx.TransportError as e:
+                        code = "timeout" if isinstance(e, httpx.TimeoutException) else "network_error"
+                        if not first or not policy.should_retry({"code": code}, attempt):
+                            raise
+                        delay = policy.backoff(attempt)
+                        retry_error = e
+                    if delay is None:
+                        break
+                    if deadline is not None:
+                        # the next attempt ends with ``deadline_exceeded`` when no time is left
+                        delay = min(delay, max(deadline - perf_counter(), 0.0))
+                    latency = perf_counter() - attempt_start
+                    attempts.append(_attempt_info(self.cfg, attempt, latency, "retried", retry_error))
+                    self._emit("on_retry", params, attempt, delay, retry_error)
+                    self._logger.warning(
+                        "Retrying %s request (attempt %d/%d) in %.2fs", self.cfg.provider, attempt + 1,
+                        policy.max_attempts, delay,
+                    )
+                    time.sleep(delay)
+
+                if postprocess is not None and not is_error:
+                    tail = postprocess.finish()
+                    if tail is not None:
+                        yield tail
+                if is_error:
+                    self._emit("on_error", params, error_class, last_error)
+                else:
+                    self._emit("on_complete", params, usage, perf_counter() - start)
             except Exception as e:
                 is_error = True
                 result = "error"
+                error_class = classify_error(exc=e, streaming_started=not first).value
+                self._emit("on_error", params, error_class, e)
                 raise
             finally:
-                self._inflight.labels(self.cfg.provider, "false").dec()
-                self._request_counter.labels(self.cfg.provider, result, str(is_error).lower()).inc()
+                metrics.inflight.labels(self.cfg.provider, "false").dec()
+                metrics.requests.labels(self.cfg.provider, result, str(is_error).lower(), error_class).inc()
+                if usage is not None:
+                    metrics.record_usage(
+                        self.cfg.provider, self.cfg.model, usage.prompt_tokens, usage.completion_tokens
+                    )
+                    if reservation is not None:
+                        limiter.settle(reservation, usage.total_tokens)
 
     def _run(self, params: RunParams) -> Generator[Union[ModelResponse, StreamingModelResponse], None, None]:
         """Internal method to be implemented by subclasses.
@@ -633,6 +989,60 @@ class SyncModelClient:
         raise NotImplementedError
         yield  # pragma: no cover - satisfies generator type
 
+    def _emit(self, name: str, params: RunParams, *args: Any) -> None:
+        """Dispatch a lifecycle event to the registered :class:`EventHook` objects."""
+        if self.event_hooks:
+            _emit_event(self.event_hooks, self._logger, name, self.cfg, params, *args)
+
+    def _substitute_model(self, replacement: str) -> None:
+        """Switch to ``replacement`` after the provider reported ``cfg.model`` as not found."""
+        self._logger.warning("%s model %s not found, substituting %s", self.cfg.provider, self.cfg.model, replacement)
+        self.cfg = self.cfg.model_copy(update={"model": replacement})
+
+    def _prepare_json_mode(self, params: RunParams) -> RunParams:
+        """Emulate the JSON mode of ``params`` when the provider or ``cfg.emulate_json_mode`` asks for it."""
+        if not needs_json_emulation(self.cfg, params, self.native_json_mode):
+            return params
+        return emulate_json_mode(params)
+
+    def _normalize_messages(self, params: RunParams) -> RunParams:
+        """Map roles, fit images to ``cfg.image_limits``, normalize text and apply ``cfg.message_normalization``."""
+        role_map = self.cfg.role_map if self.cfg.role_map is not None else default_role_map(self.cfg.model)
+        messages = map_roles(params.messages, role_map)
+        if self.cfg.image_limits is not None:
+            messages = prepare_images(messages, self.cfg.image_limits)
+        if self.cfg.normalize_prompt_text:
+            messages = normalize_message_text(messages)
+        if self.cfg.message_normalization:
+            messages = normalize_messages(messages, rules_for(self.cfg.provider), mode=self.cfg.message_normalization)
+        if messages is params.messages:
+            return params
+        return params.model_copy(update={"messages": messages})
+
+    def add_event_hook(self, hook: EventHook) -> None:
+        """Register ``hook`` for lifecycle events of every call made by this client."""
+        self.event_hooks.append(hook)
+
+    def annotate_tokens(
+        self, messages: list[Message], tools: ToolParams | list[ToolSpec] | list[dict] | None = None
+    ) -> TokenCounts:
+        """See :meth:`ModelClient.annotate_tokens`."""
+        return TokenCounts.estimate(messages, tools)
+
+    def best_of(self, params: RunParams, n: int, scorer: Scorer, *, use_n: bool = False) -> BestOf:
+        """Sync variant of :meth:`ModelClient.abest_of`; ``scorer`` must be synchronous."""
+        from .best_of import best_of
+
+        return best_of(self, params, n, scorer, use_n=use_n)
+
+    def usage_report(self, period: timedelta | tuple[datetime, datetime]) -> UsageReport:
+        """Sync variant of :meth:`ModelClient.ausage_report`."""
+        raise NotImplementedError(f"{self.provider} does not expose a usage API")
+
+    def embeddings(self, body: dict[str, Any]) -> dict[str, Any]:
+        """Sync variant of :meth:`ModelClient.aembeddings`."""
+        raise NotImplementedError(f"{self.provider} does not expose an embeddings API")
+
     def close(self) -> None:
         """Close the underlying HTTP client."""
         self._client.close()
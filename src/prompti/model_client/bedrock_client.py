@@ -0,0 +1,544 @@
+"""AWS Bedrock client for the ``InvokeModel`` API.
+
+Two model families are supported, picked from the model id:
+
+* Anthropic Claude (``anthropic.*``, also as the ``us.anthropic.*`` inference
+  profiles): the Messages API body, with tools, images and thinking, and its
+  stream events decoded by :class:`AnthropicStreamDecoder`;
+* Amazon Titan Text (``amazon.titan-text-*``): the conversation is sent as a
+  ``User:``/``Bot:`` prompt, without tools.
+
+Requests are signed with AWS SigV4 from ``AWS_ACCESS_KEY_ID``,
+``AWS_SECRET_ACCESS_KEY`` and ``AWS_SESSION_TOKEN``; with ``cfg.api_key`` (or
+``AWS_BEARER_TOKEN_BEDROCK``) a Bedrock API key is sent instead. The region is
+``cfg.region``, else ``AWS_REGION``/``AWS_DEFAULT_REGION``::
+
+    cfg = ModelConfig(provider="bedrock", model="anthropic.claude-3-5-sonnet-20240620-v1:0", region="us-west-2")
+
+Streams use ``invoke-with-response-stream``, whose body is an AWS event
+stream (binary frames, see :class:`EventStreamDecoder`) rather than SSE.
+"""
+
+from __future__ import annotations
+
+import base64
+import json
+import os
+import re
+import struct
+import zlib
+from collections.abc import AsyncGenerator, Generator
+from dataclasses import dataclass
+from typing import Any
+from urllib.parse import quote
+
+import httpx
+
+from ..documents import parse_anthropic_content, to_anthropic_content
+from ..message import Choice, Message, ModelResponse, StreamingChoice, StreamingModelResponse, Usage
+from ..tool_content import _call_arguments, _part_text, to_anthropic_tool_result
+from .anthropic_stream import FINISH_REASONS, AnthropicStreamDecoder
+from .base import RunParams, log_sampled_request
+from .openai_wire import (
+    _MALFORMED_DATA_ERRORS,
+    MalformedResponseError,
+    OpenAIWireClient,
+    OpenAIWireMixin,
+    SyncOpenAIWireClient,
+)
+from .sigv4 import AwsCredentials, sign
+from .transport import encode_json_body
+from .types import ToolChoice
+
+ANTHROPIC_VERSION = "bedrock-2023-05-31"
+# Anthropic requires max_tokens
+DEFAULT_MAX_TOKENS = 4096
+
+# Titan completionReason -> OpenAI finish_reason
+TITAN_FINISH_REASONS = {
+    "FINISH": "stop",
+    "STOP_CRITERIA_MET": "stop",
+    "LENGTH": "length",
+    "CONTENT_FILTERED": "content_filter",
+}
+
+# Bedrock stream exception -> HTTP status, so mid-stream errors classify like HTTP ones
+EXCEPTION_STATUS = {
+    "validationException": 400,
+    "accessDeniedException": 403,
+    "resourceNotFoundException": 404,
+    "modelTimeoutException": 408,
+    "modelStreamErrorException": 424,
+    "throttlingException": 429,
+    "internalServerException": 500,
+    "serviceUnavailableException": 503,
+}
+
+_REGION = re.compile(r"bedrock-runtime(?:-fips)?\.([a-z0-9-]+)\.amazonaws\.com")
+
+
+def model_family(model: str) -> str:
+    """Return ``"anthropic"`` or ``"titan"`` for a Bedrock model id.
+
+    Raises:
+        ValueError: For model families this client does not support.
+    """
+    if "anthropic." in model:
+        return "anthropic"
+    if "amazon.titan-text" in model:
+        return "titan"
+    raise ValueError(f"Unsupported Bedrock model {model!r}: only Anthropic Claude and Amazon Titan Text are supported")
+
+
+def _text(content: str | list[dict[str, Any]] | None) -> str:
+    if not content or isinstance(content, str):
+        return content or ""
+    return "\n".join(text for part in content if (text := _part_text(part)) is not None)
+
+
+def to_anthropic_messages(messages: list[Message]) -> tuple[list[dict[str, Any]], str | None]:
+    """Return Anthropic Messages API ``messages`` and ``system`` for OpenAI-format ``messages``.
+
+    Tool calls become ``tool_use`` blocks and tool results ``tool_result``
+    blocks of a user turn; consecutive turns of the same role are merged, so
+    parallel tool results answer their calls together.
+    """
+    system: list[str] = []
+    result: list[dict[str, Any]] = []
+    for message in messages:
+        if message.role in ("system", "developer"):
+            system.append(_text(message.content))
+            continue
+        if message.role == "tool":
+            role, blocks = "user", [to_anthropic_tool_result(message)]
+        else:
+            role = "assistant" if message.role == "assistant" else "user"
+            content = to_anthropic_content(message.content)
+            blocks = [{"type": "text", "text": content}] if isinstance(content, str) else content
+            blocks = [b for b in blocks if b.get("type") != "text" or b.get("text")]
+            for call in message.tool_calls or []:
+                function = call["function"]
+                blocks.append(
+                    {
+                        "type": "tool_use",
+                        "id": call["id"],
+                        "name": function["name"],
+                        "input": _call_arguments(call),
+                    }
+                )
+        if result and result[-1]["role"] == role:
+            result[-1]["content"].extend(blocks)
+        else:
+            result.append({"role": role, "content": blocks})
+    return result, "\n\n".join(system) or None
+
+
+def to_titan_prompt(messages: list[Message]) -> str:
+    """Render ``messages`` as the ``User:``/``Bot:`` prompt Titan Text models are tuned for."""
+    lines = []
+    for message in messages:
+        text = _text(message.content)
+        if message.role in ("system", "developer"):
+            lines.append(text)
+        elif message.role == "assistant":
+            lines.append(f"Bot: {text}")
+        else:
+            lines.append(f"User: {text}")
+    return "\n".join(lines) + "\nBot:"
+
+
+@dataclass(kw_only=True)
+class EventStreamMessage:
+    """One frame of an AWS event stream: its headers and payload."""
+
+    headers: dict[str, Any]
+    payload: bytes
+
+
+class EventStreamDecoder:
+    """Split an ``application/vnd.amazon.eventstream`` body into messages.
+
+    Each frame is ``total length | headers length | prelude CRC | headers |
+    payload | message CRC`` (big-endian ``uint32`` lengths, CRC32 checksums).
+    Frames may span network chunks; a failed checksum raises
+    :class:`MalformedResponseError`.
+    """
+
+    # header value type -> struct format of fixed-size values
+    _FIXED = {2: ">b", 3: ">h", 4: ">i", 5: ">q", 8: ">q"}
+
+    def __init__(self) -> None:
+        self._buffer = b""
+
+    def feed(self, chunk: bytes) -> list[EventStreamMessage]:
+        """Add ``chunk`` and return the complete messages it finished."""
+        self._buffer += chunk
+        messages = []
+        while len(self._buffer) >= 12:
+            total, headers_length, prelude_crc = struct.unpack(">III", self._buffer[:12])
+            if zlib.crc32(self._buffer[:8]) != prelude_crc:
+                raise MalformedResponseError("stream", "Event stream prelude checksum mismatch")
+            if len(self._buffer) < total:
+                break
+            frame, self._buffer = self._buffer[:total], self._buffer[total:]
+            if zlib.crc32(frame[:-4]) != struct.unpack(">I", frame[-4:])[0]:
+                raise MalformedResponseError("stream", "Event stream message checksum mismatch")
+            headers = self._headers(frame[12 : 12 + headers_length])
+            messages.append(EventStreamMessage(headers=headers, payload=frame[12 + headers_length : -4]))
+        return messages
+
+    def _headers(self, data: bytes) -> dict[str, Any]:
+        headers: dict[str, Any] = {}
+        i = 0
+        while i < len(data):
+            name_length = data[i]
+            name = data[i + 1 : i + 1 + name_length].decode()
+            kind = data[i + 1 + name_length]
+            i += 2 + name_length
+            if kind in (0, 1):
+                value: Any = kind == 0
+            elif kind in self._FIXED:
+                fmt = self._FIXED[kind]
+                value = struct.unpack(fmt, data[i : i + struct.calcsize(fmt)])[0]
+                i += struct.calcsize(fmt)
+            elif kind in (6, 7):
+                (length,) = struct.unpack(">H", data[i : i + 2])
+                value = data[i + 2 : i + 2 + length]
+                value = value.decode() if kind == 7 else value
+                i += 2 + length
+            elif kind == 9:
+                value = data[i : i + 16]
+                i += 16
+            else:
+                raise MalformedResponseError("stream", f"Unknown event stream header type {kind}")
+            headers[name] = value
+        return headers
+
+
+class TitanStreamDecoder:
+    """Stateful decoder for the chunks of one Titan Text stream."""
+
+    def __init__(self, model: str | None = None) -> None:
+        self.model = model
+
+    def decode(self, event: dict[str, Any]) -> StreamingModelResponse | None:
+        """Return the chunk for ``event``, ``None`` if it carries nothing."""
+        reason = event.get("completionReason")
+        finish_reason = TITAN_FINISH_REASONS.get(reason, reason.lower()) if reason else None
+        metrics = event.get("amazon-bedrock-invocationMetrics")
+        usage = None
+        if metrics:
+            prompt, completion = metrics.get("inputTokenCount", 0), metrics.get("outputTokenCount", 0)
+            usage = Usage(prompt_tokens=prompt, completion_tokens=completion, total_tokens=prompt + completion)
+        text = event.get("outputText") or None
+        if text is None and finish_reason is None and usage is None:
+            return None
+        delta = Message(role="assistant", content=text)
+        return StreamingModelResponse(
+            id="",
+            object="chat.completion.chunk",
+            created=0,
+            model=self.model,
+            choices=[StreamingChoice(index=0, delta=delta, finish_reason=finish_reason)],
+            usage=usage,
+        )
+
+
+class BedrockWireMixin(OpenAIWireMixin):
+    """Bedrock differences: SigV4 signing, per-model URLs and the Anthropic/Titan bodies.
+
+    ``cfg.api_url`` is the runtime endpoint,
+    ``https://bedrock-runtime.{region}.amazonaws.com`` by default.
+    """
+
+    error_label = "Bedrock API"
+    # neither the Anthropic nor the Titan body has a JSON mode
+    native_json_mode = False
+
+    def _region(self) -> str:
+        if self.cfg.region:
+            return self.cfg.region
+        match = _REGION.search(self.cfg.api_url or "")
+        if match:
+            return match.group(1)
+        return os.environ.get("AWS_REGION") or os.environ.get("AWS_DEFAULT_REGION") or "us-east-1"
+
+    def _request_url(self, endpoint: str | None = None, stream: bool = False) -> str:
+        base = (endpoint or self._endpoint() or f"https://bedrock-runtime.{self._region()}.amazonaws.com").rstrip("/")
+        method = "invoke-with-response-stream" if stream else "invoke"
+        # 模型 ID 中的 ":" 需要编码，SigV4 对路径再编码一次
+        return f"{base}/model/{quote(self.cfg.model, safe='')}/{method}"
+
+    def _signed_request(
+        self, url: str, headers: dict[str, str], body: bytes, extensions: dict[str, Any] | None = None
+    ) -> httpx.Request:
+        """Return the ``POST`` of ``body`` to ``url``, authenticated with an API key or SigV4.
+
+        Raises:
+            ValueError: If neither an API key nor AWS credentials are configured.
+        """
+        api_key = self.cfg.api_key or os.environ.get("AWS_BEARER_TOKEN_BEDROCK")
+        headers = {k: v for k, v in headers.items() if k != self.auth_header}
+        if api_key:
+            headers[self.auth_header] = f"Bearer {api_key}"
+        else:
+            credentials = AwsCredentials.from_env()
+            if credentials is None:
+                raise ValueError(
+                    "No AWS credentials: set AWS_ACCESS_KEY_ID and AWS_SECRET_ACCESS_KEY, or a Bedrock API key"
+                )
+            headers.update(sign(credentials, "POST", url, headers, body, region=self._region(), service="bedrock"))
+        return self._client.build_request("POST", url, headers=headers, content=body, extensions=extensions)
+
+    def _retarget(self, request: httpx.Request, endpoint: str) -> httpx.Request:
+        url = self._retargeted_url(request, endpoint)
+        if str(request.url) == url:
+            return request
+        # 签名包含 Host，换端点后需要重新签名
+        headers = {k: v for k, v in request.headers.items() if k.lower() not in ("host", "authorization")}
+        headers = {k: v for k, v in headers.items() if not k.lower().startswith("x-amz-")}
+        return self._signed_request(url, headers, request.content, request.extensions)
+
+    def _build_request(self, params: RunParams) -> httpx.Request:
+        request_data = self._build_request_data(params)
+        self._logger.info(request_data)
+        log_sampled_request(self.cfg, request_data)
+        body, body_headers = encode_json_body(self.cfg, request_data)
+        headers = {**self._build_headers(params), **body_headers}
+        # 流式时 Accept 描述事件流中每个块的格式
+        headers["X-Amzn-Bedrock-Accept" if params.stream else "Accept"] = "application/json"
+        return self._signed_request(self._request_url(stream=params.stream), headers, body)
+
+    def _embeddings_request(self, body: dict[str, Any]) -> httpx.Request:
+        raise NotImplementedError("Embeddings are not supported for Bedrock")
+
+    def _upload_request(self, filename: str, data: bytes, media_type: str, purpose: str) -> httpx.Request:
+        raise NotImplementedError("File uploads are not supported for Bedrock; send files inline")
+
+    @staticmethod
+    def _parse_error_body(response: httpx.Response) -> str:
+        """Bedrock 错误体为 ``{"message": ...}``。"""
+        if not response.content:
+            return f"HTTP {response.status_code}"
+        try:
+            data = response.json()
+        except Exception:
+            return f"HTTP {response.status_code}: {response.text}"
+        if isinstance(data, dict) and data.get("message"):
+            return str(data["message"])
+        return str(data)
+
+    def _build_request_data(self, params: RunParams) -> dict[str, Any]:
+        """构建 Bedrock InvokeModel 请求数据。"""
+        if model_family(self.cfg.model) == "titan":
+            request_data = self._titan_request(params)
+        else:
+            request_data = self._anthropic_request(params)
+        request_data.update(params.extra_params)
+        params.trace_context["llm_request"] = request_data
+        return request_data
+
+    def _anthropic_request(self, params: RunParams) -> dict[str, Any]:
+        messages, system = to_anthropic_messages(params.messages)
+        max_tokens = params.max_tokens if params.max_tokens is not None else self.cfg.max_tokens
+        request_data: dict[str, Any] = {
+            "anthropic_version": ANTHROPIC_VERSION,
+            "max_tokens": max_tokens if max_tokens is not None else DEFAULT_MAX_TOKENS,
+            "messages": messages,
+        }
+        if system:
+            request_data["system"] = system
+        temperature = params.temperature if params.temperature is not None else self.cfg.temperature
+        if temperature is not None:
+            request_data["temperature"] = temperature
+        top_p = params.top_p if params.top_p is not None else self.cfg.top_p
+        if top_p is not None:
+            request_data["top_p"] = top_p
+        if params.top_k is not None:
+            request_data["top_k"] = params.top_k
+        if params.stop:
+            request_data["stop_sequences"] = [params.stop] if isinstance(params.stop, str) else params.stop
+        if self.cfg.beta_features:
+            request_data["anthropic_beta"] = self.cfg.beta_features
+        if params.tool_params:
+            self._add_tool_params(request_data, params.tool_params)
+        return request_data
+
+    def _titan_request(self, params: RunParams) -> dict[str, Any]:
+        if params.tool_params and params.tool_params.tools:
+            raise ValueError("Amazon Titan Text models do not support tools")
+        config: dict[str, Any] = {}
+        max_tokens = params.max_tokens if params.max_tokens is not None else self.cfg.max_tokens
+        if max_tokens is not None:
+            config["maxTokenCount"] = max_tokens
+        temperature = params.temperature if params.temperature is not None else self.cfg.temperature
+        if temperature is not None:
+            config["temperature"] = temperature
+        top_p = params.top_p if params.top_p is not None else self.cfg.top_p
+        if top_p is not None:
+            config["topP"] = top_p
+        if params.stop:
+            config["stopSequences"] = [params.stop] if isinstance(params.stop, str) else params.stop
+        request_data: dict[str, Any] = {"inputText": to_titan_prompt(params.messages)}
+        if config:
+            request_data["textGenerationConfig"] = config
+        return request_data
+
+    def _add_tool_params(self, request_data: dict[str, Any], tool_params) -> None:
+        """添加 Anthropic tools 与 tool_choice。"""
+        if not tool_params or not tool_params.tools:
+            return
+        request_data["tools"] = [
+            {"name": tool.name, "description": tool.description, "input_schema": tool.parameters}
+            for tool in tool_params.tools
+        ]
+        choice = tool_params.choice
+        if isinstance(choice, dict):
+            name = (choice.get("function") or {}).get("name") or choice.get("name")
+            request_data["tool_choice"] = {"type": "tool", "name": name}
+        elif choice == ToolChoice.FORCE and tool_params.force_tool:
+            request_data["tool_choice"] = {"type": "tool", "name": tool_params.force_tool}
+        elif choice in (ToolChoice.REQUIRED, ToolChoice.FORCE):
+            request_data["tool_choice"] = {"type": "any"}
+        elif choice == ToolChoice.BLOCK:
+            request_data["tool_choice"] = {"type": "none"}
+        else:
+            request_data["tool_choice"] = {"type": "auto"}
+
+    def _native_stream_decoder(self) -> AnthropicStreamDecoder | TitanStreamDecoder:
+        if model_family(self.cfg.model) == "titan":
+            return TitanStreamDecoder(self.cfg.model)
+        return AnthropicStreamDecoder(self.cfg.model)
+
+    def _stream_event(
+        self, message: EventStreamMessage, native: AnthropicStreamDecoder | TitanStreamDecoder
+    ) -> StreamingModelResponse | None:
+        """把一个事件流消息解析为流式响应。
+
+        Raises:
+            MalformedResponseError: If a chunk is not a valid event of the model family.
+        """
+        if message.headers.get(":message-type") == "exception":
+            kind = message.headers.get(":exception-type", "internalServerException")
+            try:
+                text = json.loads(message.payload).get("message", kind)
+            except (ValueError, AttributeError):
+                text = message.payload.decode(errors="replace") or kind
+            return self._create_error_response(
+                text, is_streaming=True, status_code=EXCEPTION_STATUS.get(kind, 500), code=kind
+            )
+        if message.headers.get(":event-type") != "chunk":
+            return None
+        try:
+            event = json.loads(base64.b64decode(json.loads(message.payload)["bytes"]))
+            return native.decode(event)
+        except (ValueError, RecursionError, *_MALFORMED_DATA_ERRORS) as e:
+            raise MalformedResponseError("stream", f"Malformed stream chunk from {self.error_label}: {e}") from e
+
+    def _process_non_streaming_response(self, response: httpx.Response) -> ModelResponse:
+        """处理 InvokeModel 响应。
+
+        Raises:
+            MalformedResponseError: If the body is not a valid response of the model family.
+        """
+        try:
+            data = response.json()
+        except (ValueError, RecursionError) as e:
+            raise MalformedResponseError("json", f"{self.error_label} returned invalid JSON: {e}") from e
+        if not isinstance(data, dict):
+            raise MalformedResponseError("json", f"Unexpected response format: {str(data)[:500]}")
+        try:
+            if model_family(self.cfg.model) == "titan":
+                return self._titan_response(data, response)
+            return self._anthropic_response(data)
+        except _MALFORMED_DATA_ERRORS as e:
+            raise MalformedResponseError("json", f"Malformed response from {self.error_label}: {e}") from e
+
+    def _anthropic_response(self, data: dict[str, Any]) -> ModelResponse:
+        blocks = data["content"]
+        text, citations = parse_anthropic_content(blocks)
+        thinking = "".join(b.get("thinking", "") for b in blocks if b.get("type") == "thinking")
+        tool_calls = [
+            {
+                "id": b["id"],
+                "type": "function",
+                "function": {"name": b["name"], "arguments": json.dumps(b.get("input") or {}, ensure_ascii=False)},
+            }
+            for b in blocks
+            if b.get("type") == "tool_use"
+        ]
+        usage = data.get("usage") or {}
+        prompt = (
+            usage.get("input_tokens", 0)
+            + (usage.get("cache_creation_input_tokens") or 0)
+            + (usage.get("cache_read_input_tokens") or 0)
+        )
+        completion = usage.get("output_tokens", 0)
+        message = Message(
+            role="assistant",
+            content=text or None,
+            reasoning_content=thinking or None,
+            tool_calls=tool_calls or None,
+            citations=citations or None,
+        )
+        stop_reason = data.get("stop_reason")
+        return ModelResponse(
+            id=data.get("id", ""),
+            object="chat.completion",
+            created=0,
+            model=data.get("model", self.cfg.model),
+            choices=[Choice(index=0, message=message, finish_reason=FINISH_REASONS.get(stop_reason, stop_reason))],
+            usage=Usage(prompt_tokens=prompt, completion_tokens=completion, total_tokens=prompt + completion),
+        )
+
+    def _titan_response(self, data: dict[str, Any], response: httpx.Response) -> ModelResponse:
+        [result] = data["results"]
+        reason = result.get("completionReason")
+        prompt = data.get("inputTextTokenCount", 0)
+        completion = result.get("tokenCount", 0)
+        return ModelResponse(
+            id=response.headers.get("x-amzn-requestid", ""),
+            object="chat.completion",
+            created=0,
+            model=self.cfg.model,
+            choices=[
+                Choice(
+                    index=0,
+                    message=Message(role="assistant", content=result["outputText"]),
+                    finish_reason=TITAN_FINISH_REASONS.get(reason, reason.lower()) if reason else None,
+                )
+            ],
+            usage=Usage(prompt_tokens=prompt, completion_tokens=completion, total_tokens=prompt + completion),
+        )
+
+
+class BedrockClient(BedrockWireMixin, OpenAIWireClient):
+    """AWS Bedrock client."""
+
+    provider = "bedrock"
+
+    async def _aprocess_streaming_response(self, response) -> AsyncGenerator[StreamingModelResponse, None]:
+        """处理事件流响应。"""
+        decoder = EventStreamDecoder()
+        native = self._native_stream_decoder()
+        async for chunk in response.aiter_bytes():
+            for message in decoder.feed(chunk):
+                result = self._stream_event(message, native)
+                if result is not None:
+                    yield result
+
+
+class SyncBedrockClient(BedrockWireMixin, SyncOpenAIWireClient):
+    """Synchronous AWS Bedrock client."""
+
+    provider = "bedrock"
+
+    def _process_streaming_response(self, response) -> Generator[StreamingModelResponse, None, None]:
+        """处理事件流响应。"""
+        decoder = EventStreamDecoder()
+        native = self._native_stream_decoder()
+        for chunk in response.iter_bytes():
+            for message in decoder.feed(chunk):
+                result = self._stream_event(message, native)
+                if result is not None:
+                    yield result
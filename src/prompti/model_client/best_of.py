@@ -0,0 +1,212 @@
+"""Best-of-N sampling.
+
+:meth:`ModelClient.abest_of` samples ``n`` completions of one request
+concurrently, scores each with a scorer and returns them all with the best
+one::
+
+    result = await client.abest_of(params, 4, LLMJudge(create_client(judge_cfg)).ascore)
+    print(result.best.response.get_text_content(), [c.score for c in result.candidates])
+
+A scorer takes the request and a completion and returns a number, higher is
+better; async scorers are awaited. :class:`LLMJudge` asks a model to rate
+completions from 0 to 10, heuristics such as ``lambda params, r:
+-len(r.get_text_content() or "")`` work as well. With ``use_n=True`` the
+completions come from one request with ``params.n``, for providers that
+support it; completions missing from that response are sampled concurrently.
+Sample with a temperature above 0, or the completions are likely identical.
+"""
+
+from __future__ import annotations
+
+import asyncio
+import inspect
+import re
+from collections.abc import Awaitable, Callable
+from concurrent.futures import ThreadPoolExecutor
+from typing import TYPE_CHECKING, Any, Union
+
+from pydantic import BaseModel
+
+from ..message import Message, ModelResponse
+from .types import RunParams
+
+if TYPE_CHECKING:
+    from .base import ModelClient, SyncModelClient
+
+__all__ = ["BestOf", "Candidate", "LLMJudge", "Scorer", "abest_of", "asample", "best_of", "sample"]
+
+Scorer = Callable[[RunParams, ModelResponse], Union[float, Awaitable[float]]]
+
+DEFAULT_JUDGE_PROMPT = (
+    "You grade answers. Rate how well the answer below responds to the conversation, considering correctness, "
+    "completeness and clarity, from 0 (useless) to 10 (excellent). Answer with the number only."
+)
+
+
+class Candidate(BaseModel):
+    """One sampled completion and its score."""
+
+    index: int
+    response: ModelResponse
+    # ``None`` when the completion failed or could not be scored
+    score: float | None = None
+    error: str | None = None
+
+
+class BestOf(BaseModel):
+    """All candidates of a best-of-N call, in sampling order, and the highest scored one."""
+
+    candidates: list[Candidate]
+    # ``None`` when no candidate could be scored
+    best: Candidate | None = None
+
+
+def _sample_params(params: RunParams, use_n: bool, n: int) -> RunParams:
+    # concurrent samples must not write into one trace context
+    update = {"stream": False, "n": n if use_n else None, "trace_context": dict(params.trace_context)}
+    return params.model_copy(update=update)
+
+
+def _final(responses: list[ModelResponse]) -> ModelResponse:
+    if not responses:
+        return ModelResponse(error={"message": "the model returned no response", "code": "empty_response"})
+    return responses[-1]
+
+
+def _split_choices(response: ModelResponse, n: int) -> list[ModelResponse]:
+    """Turn a response with up to ``n`` choices into one response per choice."""
+    if response.error or not response.choices:
+        return [response] * n
+    return [
+        response.model_copy(update={"choices": [choice.model_copy(update={"index": 0})]})
+        for choice in sorted(response.choices, key=lambda c: c.index)[:n]
+    ]
+
+
+async def asample(client: ModelClient, params: RunParams, n: int, *, use_n: bool = False) -> list[ModelResponse]:
+    """Return ``n`` non-streaming completions of ``params``, sampled concurrently or with ``params.n``."""
+    if n < 1:
+        raise ValueError("n must be at least 1")
+
+    async def one(sample_params: RunParams) -> ModelResponse:
+        return _final([r async for r in client.arun(sample_params)])  # type: ignore[misc]
+
+    samples: list[ModelResponse] = []
+    if use_n:
+        samples = _split_choices(await one(_sample_params(params, True, n)), n)
+    # providers that ignore n return fewer choices; sample the rest concurrently
+    missing = n - len(samples)
+    return samples + list(await asyncio.gather(*(one(_sample_params(params, False, n)) for _ in range(missing))))
+
+
+def sample(client: SyncModelClient, params: RunParams, n: int, *, use_n: bool = False) -> list[ModelResponse]:
+    """Sync variant of :func:`asample`; samples run in threads."""
+    if n < 1:
+        raise ValueError("n must be at least 1")
+
+    def one(sample_params: RunParams) -> ModelResponse:
+        return _final(list(client.run(sample_params)))  # type: ignore[arg-type]
+
+    samples: list[ModelResponse] = []
+    if use_n:
+        samples = _split_choices(one(_sample_params(params, True, n)), n)
+    missing = n - len(samples)
+    if not missing:
+        return samples
+    with ThreadPoolExecutor(max_workers=missing) as pool:
+        return samples + list(pool.map(one, [_sample_params(params, False, n) for _ in range(missing)]))
+
+
+def _candidate(index: int, response: ModelResponse) -> Candidate:
+    error = str(response.error.get("message") or response.error) if response.error else None
+    return Candidate(index=index, response=response, error=error)
+
+
+def _result(candidates: list[Candidate]) -> BestOf:
+    scored = [c for c in candidates if c.score is not None]
+    # ties go to the earlier candidate
+    best = max(scored, key=lambda c: c.score, default=None)  # type: ignore[arg-type,return-value]
+    return BestOf(candidates=candidates, best=best)
+
+
+async def abest_of(client: ModelClient, params: RunParams, n: int, scorer: Scorer, *, use_n: bool = False) -> BestOf:
+    """Sample ``n`` completions of ``params`` with ``client`` and score them with ``scorer``.
+
+    See :meth:`ModelClient.abest_of`.
+    """
+    candidates = [_candidate(i, r) for i, r in enumerate(await asample(client, params, n, use_n=use_n))]
+
+    async def score(candidate: Candidate) -> None:
+        if candidate.error is not None:
+            return
+        try:
+            value = scorer(params, candidate.response)
+            if inspect.isawaitable(value):
+                value = await value
+            candidate.score = float(value)
+        except Exception as e:  # one failed judgement must not lose the other candidates
+            candidate.error = f"scoring failed: {type(e).__name__}: {e}"
+
+    await asyncio.gather(*(score(c) for c in candidates))
+    return _result(candidates)
+
+
+def best_of(client: SyncModelClient, params: RunParams, n: int, scorer: Scorer, *, use_n: bool = False) -> BestOf:
+    """Sync variant of :func:`abest_of`; samples run in threads and ``scorer`` must be synchronous."""
+    candidates = [_candidate(i, r) for i, r in enumerate(sample(client, params, n, use_n=use_n))]
+    for candidate in candidates:
+        if candidate.error is not None:
+            continue
+        try:
+            candidate.score = float(scorer(params, candidate.response))  # type: ignore[arg-type]
+        except Exception as e:
+            candidate.error = f"scoring failed: {type(e).__name__}: {e}"
+    return _result(candidates)
+
+
+class LLMJudge:
+    """Scores completions by asking a model to rate them from 0 to 10.
+
+    Args:
+        client: Client of the judging model; a :class:`ModelClient` for
+            :meth:`ascore`, a :class:`SyncModelClient` for :meth:`score`.
+        prompt: Instructions for the judge. It must answer with a number.
+    """
+
+    def __init__(self, client: Union[ModelClient, SyncModelClient], *, prompt: str = DEFAULT_JUDGE_PROMPT) -> None:
+        self.client = client
+        self.prompt = prompt
+
+    def _judge_params(self, params: RunParams, response: ModelResponse) -> RunParams:
+        from ..postprocess import _message_text
+
+        conversation = "\n".join(f"{m.role}: {_message_text(m)}" for m in params.messages)
+        answer = response.get_text_content() or ""
+        return RunParams(
+            messages=[
+                Message.create_system(self.prompt),
+                Message.create_user(f"Conversation:\n{conversation}\n\nAnswer:\n{answer}"),
+            ],
+            stream=False,
+            temperature=0,
+        )
+
+    @staticmethod
+    def _parse(responses: list[Any]) -> float:
+        text = "".join(r.get_text_content() or "" for r in responses if not r.error)
+        errors = [r.error for r in responses if r.error]
+        if errors:
+            raise ValueError(f"the judge failed: {errors[0].get('message') or errors[0]}")
+        match = re.search(r"-?\d+(?:\.\d+)?", text)
+        if match is None:
+            raise ValueError(f"the judge gave no score: {text!r}")
+        return float(match.group())
+
+    async def ascore(self, params: RunParams, response: ModelResponse) -> float:
+        """Return the judge's rating of ``response`` to ``params``."""
+        judge_params = self._judge_params(params, response)
+        return self._parse([r async for r in self.client.arun(judge_params)])  # type: ignore[union-attr]
+
+    def score(self, params: RunParams, response: ModelResponse) -> float:
+        """Sync variant of :meth:`ascore` for a :class:`SyncModelClient`."""
+        return self._parse(list(self.client.run(self._judge_params(params, response))))  # type: ignore[union-attr]
@@ -0,0 +1,351 @@
+"""Cohere client for the v2 Chat API (``/v2/chat``).
+
+Messages and tools keep the OpenAI shape; sampling parameters are renamed
+(``top_p`` → ``p``, ``top_k`` → ``k``, ``stop`` → ``stop_sequences``) and
+``tool_choice`` is sent as ``REQUIRED``/``NONE``. Plain-text ``document``
+parts (see :func:`prompti.documents.text_document_part`) are lifted out of
+the messages into Cohere's ``documents`` for grounded answers; the
+citations of the answer come back as ``message.citations``, with
+``start``/``end`` the cited range of the answer and ``document_index`` the
+position of the document in the request::
+
+    doc = text_document_part("The warranty lasts two years.", title="Warranty")
+    params = RunParams(messages=[Message.create_user([doc, {"type": "text", "text": "How long is the warranty?"}])])
+    client = create_client(ModelConfig(provider="cohere", model="command-r-plus-08-2024"))
+
+The API key is ``cfg.api_key``, or the ``CO_API_KEY`` environment variable
+when unset. Streams are decoded from Cohere's typed events
+(``content-delta``, ``tool-call-start``, ``citation-start``, ...) into the
+same deltas as for any other provider.
+"""
+
+from __future__ import annotations
+
+import json
+import os
+import re
+from typing import Any
+
+import httpx
+
+from ..message import Choice, Citation, Message, ModelResponse, StreamingChoice, StreamingModelResponse, Usage
+from ..tool_content import to_openai_tool_messages
+from .base import RunParams, ToolChoice
+from .openai_wire import (
+    _MALFORMED_DATA_ERRORS,
+    DeltaTextAssembler,
+    MalformedResponseError,
+    OpenAIWireClient,
+    OpenAIWireMixin,
+    SyncOpenAIWireClient,
+)
+
+DEFAULT_API_URL = "https://api.cohere.com/v2/chat"
+
+# Cohere finish reason -> OpenAI finish reason
+FINISH_REASONS = {
+    "COMPLETE": "stop",
+    "STOP_SEQUENCE": "stop",
+    "MAX_TOKENS": "length",
+    "TOOL_CALL": "tool_calls",
+    "ERROR_TOXIC": "content_filter",
+    "ERROR": "error",
+}
+
+# ids of the documents sent by :func:`to_cohere_messages`
+_DOCUMENT_ID = re.compile(r"doc[_:]?(\d+)")
+
+
+def to_cohere_messages(messages: list[dict[str, Any]]) -> tuple[list[dict[str, Any]], list[dict[str, Any]]]:
+    """Convert OpenAI-format ``messages`` into v2 Chat messages and ``documents``.
+
+    Raises:
+        ValueError: For PDF and other non-text documents, which Cohere does not accept.
+    """
+    documents: list[dict[str, Any]] = []
+    result = []
+    for message in to_openai_tool_messages(messages):
+        item: dict[str, Any] = {"role": message["role"]}
+        content = message.get("content")
+        if isinstance(content, list):
+            parts = []
+            for part in content:
+                if part.get("type") == "document":
+                    documents.append(_cohere_document(part, len(documents)))
+                elif part.get("type") == "json":
+                    parts.append({"type": "text", "text": json.dumps(part.get("json"), ensure_ascii=False)})
+                else:
+                    parts.append(part)
+            content = parts
+        if content or message["role"] != "assistant":
+            item["content"] = content or ""
+        if message.get("tool_calls"):
+            item["tool_calls"] = message["tool_calls"]
+        if message.get("tool_call_id"):
+            item["tool_call_id"] = message["tool_call_id"]
+        result.append(item)
+    return result, documents
+
+
+def _cohere_document(part: dict[str, Any], index: int) -> dict[str, Any]:
+    source = part.get("source") or {}
+    if source.get("type") != "text":
+        raise ValueError("Cohere only accepts plain-text documents, see prompti.documents.text_document_part")
+    data = {"text": source["data"]}
+    if part.get("title"):
+        data["title"] = part["title"]
+    return {"id": f"doc_{index}", "data": data}
+
+
+def parse_cohere_citations(raw: list[dict[str, Any]]) -> list[Citation]:
+    """Return one :class:`Citation` per source of Cohere ``citations``."""
+    citations = []
+    for citation in raw:
+        for source in citation.get("sources") or [{}]:
+            document = source.get("document") or {}
+            match = _DOCUMENT_ID.fullmatch(str(source.get("id") or document.get("id") or ""))
+            citations.append(
+                Citation(
+                    type=str(citation.get("type") or "text_content").lower(),
+                    cited_text=document.get("snippet") or "",
+                    document_index=int(match[1]) if match else None,
+                    document_title=document.get("title"),
+                    start=citation.get("start"),
+                    end=citation.get("end"),
+                    text=citation.get("text"),
+                )
+            )
+    return citations
+
+
+class CohereWireMixin(OpenAIWireMixin):
+    """Request building and response decoding for the Cohere v2 Chat API."""
+
+    default_api_url = DEFAULT_API_URL
+    error_label = "Cohere API"
+
+    def _build_headers(self, params: RunParams | None = None) -> dict[str, str]:
+        headers = super()._build_headers(params)
+        api_key = self.cfg.api_key or os.environ.get("CO_API_KEY")
+        if api_key:
+            headers[self.auth_header] = f"Bearer {api_key}"
+        return headers
+
+    def _embeddings_request(self, body: dict[str, Any]) -> httpx.Request:
+        raise NotImplementedError("Embeddings are not supported for Cohere")
+
+    def _upload_request(self, filename: str, data: bytes, media_type: str, purpose: str) -> httpx.Request:
+        raise NotImplementedError("File uploads are not supported for Cohere; send documents inline")
+
+    @staticmethod
+    def _parse_error_body(response: httpx.Response) -> str:
+        """Cohere 错误体为 ``{"message": ...}``。"""
+        if not response.content:
+            return f"HTTP {response.status_code}"
+        try:
+            data = response.json()
+        except Exception:
+            return f"HTTP {response.status_code}: {response.text}"
+        if isinstance(data, dict) and data.get("message"):
+            return str(data["message"])
+        return str(data)
+
+    def _build_request_data(self, params: RunParams) -> dict[str, Any]:
+        """构建 Cohere v2 Chat 请求数据。"""
+        messages = []
+        for msg in params.messages:
+            item: dict[str, Any] = {"role": msg.role, "content": msg.content}
+            if msg.tool_calls:
+                item["tool_calls"] = msg.tool_calls
+            if msg.tool_call_id:
+                item["tool_call_id"] = msg.tool_call_id
+            messages.append(item)
+        messages, documents = to_cohere_messages(messages)
+        request_data: dict[str, Any] = {"model": self.cfg.model, "messages": messages, "stream": params.stream}
+        if documents:
+            request_data["documents"] = documents
+
+        temperature = params.temperature if params.temperature is not None else self.cfg.temperature
+        top_p = params.top_p if params.top_p is not None else self.cfg.top_p
+        max_tokens = params.max_tokens if params.max_tokens is not None else self.cfg.max_tokens
+        for name, value in (
+            ("temperature", temperature),
+            ("p", top_p),
+            ("k", params.top_k),
+            ("max_tokens", max_tokens),
+            ("seed", params.seed),
+        ):
+            if value is not None:
+                request_data[name] = value
+        if params.stop:
+            request_data["stop_sequences"] = [params.stop] if isinstance(params.stop, str) else params.stop
+        if params.response_format in ("json_object", "json_schema"):
+            request_data["response_format"] = {"type": "json_object"}
+
+        if params.tool_params:
+            self._add_tool_params(request_data, params.tool_params)
+
+        request_data.update(params.extra_params)
+        params.trace_context["llm_request"] = request_data
+        return request_data
+
+    def _add_tool_params(self, request_data: dict[str, Any], tool_params) -> None:
+        """添加 tools 与 tool_choice；Cohere 不支持指定函数，改为只发送该函数并要求调用。"""
+        if not tool_params or not tool_params.tools:
+            return
+        choice = tool_params.choice
+        name = None
+        if isinstance(choice, dict):
+            name = (choice.get("function") or {}).get("name") or choice.get("name")
+        elif choice == ToolChoice.FORCE:
+            name = tool_params.force_tool
+        request_data["tools"] = [
+            {
+                "type": "function",
+                "function": {"name": tool.name, "description": tool.description, "parameters": tool.parameters},
+            }
+            for tool in tool_params.tools
+            if name is None or tool.name == name
+        ]
+        if name or choice in (ToolChoice.REQUIRED, ToolChoice.FORCE):
+            request_data["tool_choice"] = "REQUIRED"
+        elif choice == ToolChoice.BLOCK:
+            request_data["tool_choice"] = "NONE"
+
+    @staticmethod
+    def _parse_usage(data: dict[str, Any]) -> Usage:
+        usage = data.get("usage") or {}
+        tokens = usage.get("tokens") or usage.get("billed_units") or {}
+        prompt = int(tokens.get("input_tokens") or 0)
+        completion = int(tokens.get("output_tokens") or 0)
+        return Usage(prompt_tokens=prompt, completion_tokens=completion, total_tokens=prompt + completion)
+
+    @staticmethod
+    def _message_from(message_data: dict[str, Any]) -> Message:
+        texts, thinking = [], []
+        for part in message_data.get("content") or []:
+            if part.get("type") == "text":
+                texts.append(part.get("text", ""))
+            elif part.get("type") == "thinking":
+                thinking.append(part.get("thinking", ""))
+        citations = parse_cohere_citations(message_data.get("citations") or [])
+        return Message(
+            role=message_data.get("role", "assistant"),
+            content="".join(texts) or None,
+            reasoning_content="".join(thinking) or message_data.get("tool_plan") or None,
+            tool_calls=message_data.get("tool_calls") or None,
+            citations=citations or None,
+        )
+
+    def _process_non_streaming_response(self, response: httpx.Response) -> ModelResponse:
+        """处理 Cohere 非流式响应。
+
+        Raises:
+            MalformedResponseError: If the body is not a valid response.
+        """
+        try:
+            data = response.json()
+        except (ValueError, RecursionError) as e:
+            raise MalformedResponseError("json", f"{self.error_label} returned invalid JSON: {e}") from e
+        if not isinstance(data, dict) or not isinstance(data.get("message"), dict):
+            raise MalformedResponseError("json", f"Unexpected response format: {str(data)[:500]}")
+        try:
+            finish_reason = data.get("finish_reason")
+            choice = Choice(
+                index=0,
+                message=self._message_from(data["message"]),
+                finish_reason=FINISH_REASONS.get(finish_reason, finish_reason),
+            )
+            return ModelResponse(
+                id=data.get("id", ""),
+                object="chat.completion",
+                created=0,
+                model=self.cfg.model,
+                choices=[choice],
+                usage=self._parse_usage(data) if "usage" in data else None,
+            )
+        except _MALFORMED_DATA_ERRORS as e:
+            raise MalformedResponseError("json", f"Malformed response from {self.error_label}: {e}") from e
+
+    def _native_stream_decoder(self) -> None:
+        return None
+
+    def _parse_stream_chunk(
+        self,
+        data_str: str,
+        assembler: DeltaTextAssembler | None = None,
+        native: Any = None,
+    ) -> StreamingModelResponse | None:
+        """把一个 Cohere 流式事件解析为流式响应，无内容时返回 ``None``。"""
+        try:
+            data = json.loads(data_str)
+        except (json.JSONDecodeError, RecursionError):
+            return None
+        if not isinstance(data, dict):
+            return None
+        try:
+            return self._stream_event(data, assembler)
+        except _MALFORMED_DATA_ERRORS as e:
+            raise MalformedResponseError("stream", f"Malformed stream event from {self.error_label}: {e}") from e
+
+    def _stream_event(
+        self, data: dict[str, Any], assembler: DeltaTextAssembler | None
+    ) -> StreamingModelResponse | None:
+        event = data.get("type")
+        delta = (data.get("delta") or {}).get("message") or {}
+        index = data.get("index", 0)
+        message = Message(role="assistant")
+        finish_reason = usage = None
+        if event == "message-start":
+            pass
+        elif event == "content-delta":
+            content = delta["content"]
+            if "thinking" in content:
+                message.reasoning_content = content["thinking"]
+            else:
+                text = content.get("text", "")
+                message.content = assembler.push(("content", 0), text) if assembler is not None else text
+        elif event == "tool-plan-delta":
+            message.reasoning_content = delta.get("tool_plan")
+        elif event == "tool-call-start":
+            call = delta["tool_calls"]
+            function = call.get("function") or {}
+            message.tool_calls = [
+                {
+                    "index": index,
+                    "id": call.get("id"),
+                    "type": "function",
+                    "function": {"name": function.get("name"), "arguments": function.get("arguments") or ""},
+                }
+            ]
+        elif event == "tool-call-delta":
+            arguments = delta["tool_calls"]["function"].get("arguments") or ""
+            message.tool_calls = [{"index": index, "function": {"arguments": arguments}}]
+        elif event == "citation-start":
+            message.citations = parse_cohere_citations([delta["citations"]])
+        elif event == "message-end":
+            end = data.get("delta") or {}
+            finish_reason = FINISH_REASONS.get(end.get("finish_reason"), end.get("finish_reason"))
+            usage = self._parse_usage(end) if "usage" in end else None
+        else:
+            return None
+        return StreamingModelResponse(
+            id=data.get("id", ""),
+            object="chat.completion.chunk",
+            created=0,
+            model=self.cfg.model,
+            choices=[StreamingChoice(index=0, delta=message, finish_reason=finish_reason)],
+            usage=usage,
+        )
+
+
+class CohereClient(CohereWireMixin, OpenAIWireClient):
+    """Cohere v2 Chat API client."""
+
+    provider = "cohere"
+
+
+class SyncCohereClient(CohereWireMixin, SyncOpenAIWireClient):
+    """Synchronous Cohere v2 Chat API client."""
+
+    provider = "cohere"
@@ -0,0 +1,71 @@
+"""Client for servers that speak the OpenAI chat completions protocol with quirks.
+
+vLLM, LM Studio, llama.cpp's server, Together, Groq and many gateways accept
+``/chat/completions`` requests but differ in what else they support. Instead
+of one client per server, ``cfg.compat`` describes the differences::
+
+    ModelConfig(
+        provider="openai_compatible",
+        model="meta-llama/Llama-3.1-8B-Instruct",
+        api_url="http://localhost:8000",
+        compat=OpenAICompatibleConfig(supports_stream_options=False),
+    )
+
+``cfg.api_url`` is the server's base URL; requests go to
+``{api_url}{path_prefix}/chat/completions``, or to ``api_url`` itself when it
+already ends with ``/chat/completions``.
+"""
+
+from __future__ import annotations
+
+from typing import Any
+
+from .base import RunParams
+from .openai_wire import OpenAIWireClient, OpenAIWireMixin, SyncOpenAIWireClient
+from .types import OpenAICompatibleConfig
+
+CHAT_COMPLETIONS_PATH = "/chat/completions"
+
+
+class CompatibleWireMixin(OpenAIWireMixin):
+    """OpenAI wire format adjusted to the capabilities in ``cfg.compat``."""
+
+    error_label = "OpenAI-compatible API"
+    document_blocks = False
+
+    def _compat(self) -> OpenAICompatibleConfig:
+        return self.cfg.compat or OpenAICompatibleConfig()
+
+    @property
+    def native_json_mode(self) -> bool:  # type: ignore[override]
+        return self._compat().supports_json_mode
+
+    def _request_url(self, endpoint: str | None = None) -> str:
+        base = (endpoint or self._endpoint() or "").rstrip("/")
+        if not base:
+            raise ValueError("provider 'openai_compatible' needs api_url, the base URL of the server")
+        if base.endswith(CHAT_COMPLETIONS_PATH):
+            return base
+        prefix = self._compat().path_prefix.strip("/")
+        return f"{base}/{prefix}{CHAT_COMPLETIONS_PATH}" if prefix else f"{base}{CHAT_COMPLETIONS_PATH}"
+
+    def _build_request_data(self, params: RunParams) -> dict[str, Any]:
+        compat = self._compat()
+        if params.tool_params and params.tool_params.tools and not compat.supports_tools:
+            raise ValueError(f"{self.cfg.model} does not support tools (compat.supports_tools is False)")
+        request_data = super()._build_request_data(params)
+        if not compat.supports_stream_options:
+            request_data.pop("stream_options", None)
+        return request_data
+
+
+class OpenAICompatibleClient(CompatibleWireMixin, OpenAIWireClient):
+    """Client for any OpenAI-compatible server, see :mod:`.compatible_client`."""
+
+    provider = "openai_compatible"
+
+
+class SyncOpenAICompatibleClient(CompatibleWireMixin, SyncOpenAIWireClient):
+    """Synchronous client for any OpenAI-compatible server."""
+
+    provider = "openai_compatible"
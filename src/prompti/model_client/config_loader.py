@@ -6,11 +6,12 @@ import threading
 import time
 from abc import ABC, abstractmethod
 from pathlib import Path
-from typing import List
+from typing import List, cast
 
 import httpx
-import yaml
+from pydantic import BaseModel
 
+from ..config_validation import load_yaml_with_positions, validate_config
 from .base import ModelConfig
 
 
@@ -22,6 +23,12 @@ class ModelConfigNotFoundError(Exception):
         super().__init__(f"Model configuration '{model_name}' not found")
 
 
+class ModelConfigFile(BaseModel):
+    """Schema of a models file loaded by :class:`FileModelConfigLoader`."""
+
+    models: List[ModelConfig] = []
+
+
 class ModelConfigLoader(ABC):
     """Base class for loaders that return a :class:`ModelConfig`."""
 
@@ -67,24 +74,9 @@ class FileModelConfigLoader(ModelConfigLoader):
             raise FileNotFoundError(f"Config file not found: {self.path}")
 
         text = self.path.read_text()
-        data = yaml.safe_load(text)
-
-        if not isinstance(data, dict):
-            raise ValueError("Config file must contain a mapping")
-
-        # 支持两种格式：单个模型配置或多个模型配置
-        if "models" in data:
-            # 多模型配置格式
-            models_data = data["models"]
-            if not isinstance(models_data, list):
-                raise ValueError("'models' field must be a list")
-
-            self.models = []
-            for model_data in models_data:
-                if isinstance(model_data, dict):
-                    self.models.append(ModelConfig(**model_data))
-        else:
-            self.models = []
+        data, positions = load_yaml_with_positions(text)
+        config = validate_config(data, ModelConfigFile, source=str(self.path), positions=positions)
+        self.models = list(cast(ModelConfigFile, config).models)
 
     def get_model_config(self, model: str, provider: str=None) -> ModelConfig:
         """Get model config from memory by model name."""
@@ -0,0 +1,95 @@
+"""Replacements for deprecated and retired models.
+
+Providers retire model names and answer calls to them with "model not
+found". :data:`DEPRECATED_MODELS` maps such names to their successor;
+``ModelConfig.model_aliases`` adds to or overrides it per profile. With
+``ModelConfig(model_fallback="substitute")`` a client that gets a
+model-not-found error before any output logs a warning, switches to the
+replacement and retries the call; it keeps using the replacement for later
+calls. The default ``"error"`` returns the error unchanged::
+
+    cfg = ModelConfig(provider="openai", model="gpt-4-turbo-preview", model_fallback="substitute")
+"""
+
+from __future__ import annotations
+
+import re
+from typing import Any
+
+from .types import ModelConfig
+
+__all__ = ["DEPRECATED_MODELS", "fallback_model", "is_model_not_found", "replacement_model"]
+
+DEPRECATED_MODELS = {
+    # OpenAI
+    "gpt-4-turbo-preview": "gpt-4o",
+    "gpt-4-0125-preview": "gpt-4o",
+    "gpt-4-1106-preview": "gpt-4o",
+    "gpt-4-vision-preview": "gpt-4o",
+    "gpt-4-32k": "gpt-4o",
+    "gpt-3.5-turbo-0613": "gpt-4o-mini",
+    "gpt-3.5-turbo-16k": "gpt-4o-mini",
+    # Anthropic
+    "claude-instant-1.2": "claude-3-5-haiku-latest",
+    "claude-2.0": "claude-3-5-sonnet-latest",
+    "claude-2.1": "claude-3-5-sonnet-latest",
+    "claude-3-sonnet-20240229": "claude-3-5-sonnet-latest",
+    # Google
+    "gemini-pro": "gemini-1.5-flash",
+    "gemini-pro-vision": "gemini-1.5-flash",
+    "gemini-1.0-pro": "gemini-1.5-flash",
+}
+
+# error codes some providers send for unknown or retired models
+_NOT_FOUND_CODES = {"model_not_found", "model_decommissioned"}
+# "model", optionally its name, then what happened to it, within one clause, so that
+# e.g. a deprecated parameter mentioned next to the model does not count
+_NOT_FOUND_MESSAGES = re.compile(
+    r"\bmodels?\b\S*"
+    r"(?:\s+(?:`[^`]*`|'[^']*'|\"[^\"]*\"|[\w.:/@-]*[\d.:/@-][\w.:/@-]*|identifier))?"
+    r"\s+(?:(?:is|was|has been|have been|has)\s+)?"
+    r"(?:not found|does not exist|decommissioned|deprecated|retired|invalid"
+    r"|(?:reached|at)\s+(?:the\s+)?end of its life)",
+    re.IGNORECASE,
+)
+
+
+def replacement_model(model: str | None, aliases: dict[str, str] | None = None) -> str | None:
+    """Return the successor of ``model``, ``None`` if it is not known to be deprecated.
+
+    ``aliases`` are looked up before :data:`DEPRECATED_MODELS`; chains are
+    followed to the newest name. Provider prefixes such as
+    ``openai/gpt-4-turbo-preview`` are kept.
+    """
+    if not model:
+        return None
+    prefix, _, name = model.rpartition("/")
+    table = {**DEPRECATED_MODELS, **(aliases or {})}
+    seen = {name}
+    while name in table and table[name] not in seen:
+        name = table[name]
+        seen.add(name)
+    if len(seen) == 1:
+        return None
+    return f"{prefix}/{name}" if prefix else name
+
+
+def is_model_not_found(error: dict[str, Any] | None, model: str | None = None) -> bool:
+    """Return whether ``error`` says the requested model does not exist or was retired."""
+    if not error:
+        return False
+    if error.get("code") in _NOT_FOUND_CODES:
+        return True
+    if error.get("status_code") not in (400, 404, 410):
+        return False
+    message = str(error.get("message") or "")
+    if error.get("status_code") == 404 and model and model in message:
+        return True
+    return bool(_NOT_FOUND_MESSAGES.search(message))
+
+
+def fallback_model(cfg: ModelConfig, error: dict[str, Any] | None) -> str | None:
+    """Return the model to retry with after ``error``, ``None`` to keep the error."""
+    if cfg.model_fallback != "substitute" or not is_model_not_found(error, cfg.model):
+        return None
+    return replacement_model(cfg.model, cfg.model_aliases)
@@ -0,0 +1,89 @@
+"""Endpoint failover on DNS and connect errors.
+
+With ``cfg.failover_api_urls`` set, a client sends each request to the
+endpoint in use and, when the connection cannot be established (the host
+does not resolve, refuses or times out connecting), immediately resends it
+to the next endpoint. Nothing has reached the provider at that point, so
+this is safe for every request and independent of the HTTP-level retries
+in :class:`RetryConfig`::
+
+    ModelConfig(
+        provider="openai",
+        model="gpt-4o",
+        api_url="https://gateway-a.internal/v1/chat/completions",
+        failover_api_urls=["https://gateway-b.internal/v1/chat/completions"],
+        failback_probe_s=60,
+    )
+
+Once failed over, the next request after ``failback_probe_s`` seconds tries
+the primary endpoint first and switches back if it connects. The probe
+rides on a regular request instead of a background task, so it only happens
+while the client is in use.
+"""
+
+from __future__ import annotations
+
+import logging
+import threading
+import time
+from collections.abc import Callable
+
+__all__ = ["EndpointFailover"]
+
+_logger = logging.getLogger("model_client.endpoints")
+
+
+class EndpointFailover:
+    """The endpoint in use among a primary and its alternatives."""
+
+    def __init__(
+        self,
+        primary: str,
+        alternates: list[str],
+        probe_interval_s: float,
+        clock: Callable[[], float] = time.monotonic,
+    ) -> None:
+        self.urls = list(dict.fromkeys([primary, *alternates]))
+        self.probe_interval_s = probe_interval_s
+        self._clock = clock
+        self._active = 0
+        # when we last switched away from the primary or failed to reach it again
+        self._since = 0.0
+        self._lock = threading.Lock()
+
+    @property
+    def active(self) -> str:
+        """The endpoint requests currently go to."""
+        return self.urls[self._active]
+
+    def order(self) -> list[str]:
+        """Return the endpoints to try for the next request, in order.
+
+        The one in use comes first, unless a probe of the primary is due.
+        """
+        with self._lock:
+            rotated = self.urls[self._active :] + self.urls[: self._active]
+            if self._active and self._clock() - self._since >= self.probe_interval_s:
+                rotated.remove(self.urls[0])
+                rotated.insert(0, self.urls[0])
+            return rotated
+
+    def failed(self, url: str, error: BaseException) -> None:
+        """Record that connecting to ``url`` failed."""
+        _logger.warning("Cannot connect to endpoint %s: %s", url, error)
+        with self._lock:
+            if url == self.urls[0] and self._active:
+                # the probe failed; wait another interval before the next one
+                self._since = self._clock()
+
+    def succeeded(self, url: str) -> None:
+        """Record that ``url`` connected; it becomes the endpoint in use."""
+        with self._lock:
+            index = self.urls.index(url)
+            if index == self._active:
+                return
+            previous, self._active, self._since = self.urls[self._active], index, self._clock()
+        if index == 0:
+            _logger.info("Primary endpoint %s reachable again, failing back from %s", url, previous)
+        else:
+            _logger.warning("Failing over from endpoint %s to %s", previous, url)
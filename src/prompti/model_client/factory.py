@@ -4,7 +4,8 @@ import pkgutil
 import importlib
 import inspect
 from typing import Type, Dict, Any
-from .base import ModelClient, SyncModelClient
+from .base import EventHook, ModelClient, SyncModelClient
+from .transport import build_async_http_client, build_sync_http_client
 import httpx
 
 _CLIENT_CLASS_REGISTRY: Dict[str, Type[ModelClient]] = {}
@@ -23,31 +24,48 @@ def _initialize_client_registry():
     for finder, module_name, _ in pkgutil.iter_modules(model_client_pkg_path):
         module = importlib.import_module(f"{model_client_pkg_name}.{module_name}")
         for _, obj in inspect.getmembers(module, inspect.isclass):
+            # 只注册自身声明了 provider 的类，共享基类（如 OpenAIWireClient）不注册
+            name = obj.__dict__.get("provider")
+            if not name:
+                continue
             if issubclass(obj, ModelClient) and obj is not ModelClient:
-                name = getattr(obj, "provider", None)
-                if name:
-                    _CLIENT_CLASS_REGISTRY[name] = obj
+                _CLIENT_CLASS_REGISTRY[name] = obj
             elif issubclass(obj, SyncModelClient) and obj is not SyncModelClient:
-                name = getattr(obj, "provider", None)
-                if name:
-                    _SYNC_CLIENT_CLASS_REGISTRY[name] = obj
+                _SYNC_CLIENT_CLASS_REGISTRY[name] = obj
 
     _IS_REGISTRY_INITIALIZED = True
 
 
-def create_client(cfg, *, is_debug: bool = False, **httpx_kw: Any):
-    """基于 cfg.provider 从注册表中创建 ModelClient 实例"""
+def create_client(
+    cfg,
+    *,
+    is_debug: bool = False,
+    event_hooks: list[EventHook] | None = None,
+    http_client: httpx.AsyncClient | None = None,
+    **httpx_kw: Any,
+):
+    """基于 cfg.provider 从注册表中创建 ModelClient 实例
+
+    传入 ``http_client`` 时复用该连接池，``httpx_kw`` 将被忽略。
+    """
     _initialize_client_registry()
 
     cls = _CLIENT_CLASS_REGISTRY.get(cfg.provider)
     if not cls:
         raise ValueError(f"Unsupported provider: {cfg.provider}")
 
-    client = httpx.AsyncClient(http2=True, **httpx_kw) if httpx_kw else None
-    return cls(cfg, client=client, is_debug=is_debug)
+    client = http_client
+    if client is None and httpx_kw:
+        client = build_async_http_client(cfg, **httpx_kw)
+    model_client = cls(cfg, client=client, is_debug=is_debug)
+    for hook in event_hooks or []:
+        model_client.add_event_hook(hook)
+    return model_client
 
 
-def create_sync_client(cfg, *, is_debug: bool = False, **httpx_kw: Any):
+def create_sync_client(
+    cfg, *, is_debug: bool = False, event_hooks: list[EventHook] | None = None, **httpx_kw: Any
+):
     """基于 cfg.provider 从注册表中创建 SyncModelClient 实例"""
     _initialize_client_registry()
 
@@ -55,5 +73,8 @@ def create_sync_client(cfg, *, is_debug: bool = False, **httpx_kw: Any):
     if not cls:
         raise ValueError(f"Unsupported sync provider: {cfg.provider}")
 
-    client = httpx.Client(http2=True, **httpx_kw) if httpx_kw else None
-    return cls(cfg, client=client, is_debug=is_debug)
+    client = build_sync_http_client(cfg, **httpx_kw) if httpx_kw else None
+    model_client = cls(cfg, client=client, is_debug=is_debug)
+    for hook in event_hooks or []:
+        model_client.add_event_hook(hook)
+    return model_client
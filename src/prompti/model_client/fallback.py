@@ -0,0 +1,125 @@
+"""Ordered fallback across providers and models.
+
+:class:`FallbackClient` sends a call to the first of its clients and moves
+on to the next one when it fails before any output with a transient error
+(timeout, network, 5xx), a rate limit or an unknown model::
+
+    client = FallbackClient.from_configs([
+        ModelConfig(provider="openai", model="gpt-4o"),
+        ModelConfig(provider="azure", model="gpt-4o", api_url="https://res.openai.azure.com"),
+        ModelConfig(provider="anthropic", model="claude-3-5-sonnet-latest"),
+    ])
+
+Each client still retries on its own first, see :class:`RetryConfig`; lower
+``max_attempts`` to fall back sooner. Errors after output was streamed and
+errors of the last client reach the caller unchanged. When a call fell back,
+:attr:`ModelResponse.attempts` lists every target tried, and the last entry
+names the provider and model that served it.
+"""
+
+from __future__ import annotations
+
+import logging
+from collections.abc import AsyncGenerator, Callable, Sequence
+from contextlib import aclosing
+from time import perf_counter
+from typing import Any, Union
+
+from ..message import AttemptInfo, ModelResponse, StreamingModelResponse
+from .base import ModelClient, _attempt_info, classify_error
+from .deprecations import is_model_not_found
+from .types import ErrorClass, ModelConfig, RetryConfig, RunParams
+
+__all__ = ["FallbackClient", "should_fall_back"]
+
+_logger = logging.getLogger("model_client.fallback")
+
+_TRANSIENT = (ErrorClass.TIMEOUT, ErrorClass.RATE_LIMIT, ErrorClass.SERVER)
+
+
+def should_fall_back(cfg: ModelConfig, error: dict[str, Any] | BaseException) -> bool:
+    """Return whether ``error`` of the client configured by ``cfg`` moves the call to the next client."""
+    if isinstance(error, BaseException):
+        return classify_error(exc=error) in _TRANSIENT
+    policy = cfg.retry or RetryConfig()
+    return policy.is_retryable(error) or classify_error(error) in _TRANSIENT or is_model_not_found(error, cfg.model)
+
+
+def _renumbered(attempts: list[AttemptInfo], offset: int) -> list[AttemptInfo]:
+    return [a.model_copy(update={"attempt": offset + i}) for i, a in enumerate(attempts, 1)]
+
+
+class FallbackClient:
+    """Serve each call from the first of ``clients`` that does not fail with a fallback error.
+
+    Args:
+        clients: Clients in order of preference.
+        should_fall_back: Decides whether an error response or exception of a
+            client moves the call to the next one.
+    """
+
+    def __init__(
+        self,
+        clients: Sequence[ModelClient],
+        *,
+        should_fall_back: Callable[[ModelConfig, dict[str, Any] | BaseException], bool] = should_fall_back,
+    ) -> None:
+        if not clients:
+            raise ValueError("FallbackClient needs at least one client")
+        self.clients = list(clients)
+        self.should_fall_back = should_fall_back
+
+    @classmethod
+    def from_configs(cls, cfgs: Sequence[ModelConfig], **kwargs: Any) -> FallbackClient:
+        """Create a client per configuration with :func:`create_client`."""
+        from .factory import create_client
+
+        return cls([create_client(cfg) for cfg in cfgs], **kwargs)
+
+    @property
+    def cfg(self) -> ModelConfig:
+        """The configuration of the preferred client."""
+        return self.clients[0].cfg
+
+    def _fell_back(self, cfg: ModelConfig, error: dict[str, Any] | BaseException, target: ModelConfig) -> None:
+        reason = f"{type(error).__name__}: {error}" if isinstance(error, BaseException) else error.get("message")
+        _logger.warning(
+            "%s/%s failed (%s), falling back to %s/%s", cfg.provider, cfg.model, reason, target.provider, target.model
+        )
+
+    async def arun(self, params: RunParams) -> AsyncGenerator[Union[ModelResponse, StreamingModelResponse], None]:
+        """Yield the responses of the first client that serves the call."""
+        tried: list[AttemptInfo] = []
+        for index, client in enumerate(self.clients):
+            cfg = client.cfg
+            last = index == len(self.clients) - 1
+            start = perf_counter()
+            started = False
+            failed: dict[str, Any] | BaseException | None = None
+            try:
+                async with aclosing(client.arun(params)) as responses:
+                    async for response in responses:
+                        if not started and not last and response.error and self.should_fall_back(cfg, response.error):
+                            failed = response.error
+                            own = response.attempts or [_attempt_info(cfg, 1, perf_counter() - start, "error", failed)]
+                            tried += _renumbered(own, len(tried))
+                            break
+                        started = True
+                        if tried and response.timing is not None:
+                            status = "error" if response.error else "success"
+                            own = response.attempts or [_attempt_info(cfg, 1, perf_counter() - start, status)]
+                            response.attempts = [*tried, *_renumbered(own, len(tried))]
+                        yield response
+            except Exception as e:
+                if started or last or not self.should_fall_back(cfg, e):
+                    raise
+                failed = e
+                tried.append(_attempt_info(cfg, len(tried) + 1, perf_counter() - start, "error", e))
+            if failed is None:
+                return
+            self._fell_back(cfg, failed, self.clients[index + 1].cfg)
+
+    async def aclose(self) -> None:
+        """Close every client."""
+        for client in self.clients:
+            await client.aclose()
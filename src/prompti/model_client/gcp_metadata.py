@@ -0,0 +1,137 @@
+"""Access tokens from the Google Cloud metadata server.
+
+Workloads on GCE, GKE (with Workload Identity), Cloud Run and Cloud
+Functions get tokens for their attached service account from the metadata
+server, so no service-account JSON has to be mounted::
+
+    credential = MetadataCredential()
+    headers["Authorization"] = f"Bearer {credential.get_token()}"
+
+``GCE_METADATA_HOST`` overrides the server address, as with the Google
+client libraries. Tokens are cached and refreshed :data:`REFRESH_MARGIN`
+seconds before they expire.
+"""
+
+from __future__ import annotations
+
+import asyncio
+import os
+import threading
+import time
+from collections.abc import Callable
+
+import httpx
+
+__all__ = ["GCPMetadataError", "MetadataCredential", "REFRESH_MARGIN"]
+
+METADATA_HOST = "metadata.google.internal"
+CLOUD_PLATFORM_SCOPE = "https://www.googleapis.com/auth/cloud-platform"
+# refresh tokens this many seconds before they expire
+REFRESH_MARGIN = 300.0
+TOKEN_TIMEOUT = 5.0
+
+
+class GCPMetadataError(ValueError):
+    """The metadata server is unreachable or returned no token."""
+
+
+class MetadataCredential:
+    """Fetches and caches access tokens of a service account from the metadata server."""
+
+    def __init__(
+        self,
+        service_account: str = "default",
+        *,
+        scopes: tuple[str, ...] = (CLOUD_PLATFORM_SCOPE,),
+        client: httpx.Client | None = None,
+        async_client: httpx.AsyncClient | None = None,
+        clock: Callable[[], float] = time.time,
+    ) -> None:
+        self.service_account = service_account
+        self.scopes = scopes
+        self._client = client
+        self._async_client = async_client
+        self._owns_clients = client is None and async_client is None
+        self._clock = clock
+        self._token: str | None = None
+        self._expires_on = 0.0
+        self._lock = threading.Lock()
+        self._async_lock: asyncio.Lock | None = None
+
+    def _token_request(self) -> httpx.Request:
+        host = os.environ.get("GCE_METADATA_HOST") or METADATA_HOST
+        url = f"http://{host}/computeMetadata/v1/instance/service-accounts/{self.service_account}/token"
+        params = {"scopes": ",".join(self.scopes)}
+        return httpx.Request("GET", url, params=params, headers={"Metadata-Flavor": "Google"})
+
+    def _cached(self) -> str | None:
+        if self._token is not None and self._expires_on - REFRESH_MARGIN > self._clock():
+            return self._token
+        return None
+
+    def _store(self, response: httpx.Response) -> str:
+        """Cache the token of ``response``.
+
+        Raises:
+            GCPMetadataError: If the response is an error or carries no token.
+        """
+        try:
+            data = response.json()
+        except ValueError:
+            data = None
+        if response.is_error or not isinstance(data, dict) or not data.get("access_token"):
+            raise GCPMetadataError(
+                f"metadata server token request failed (HTTP {response.status_code}): {response.text}"
+            )
+        self._token = data["access_token"]
+        self._expires_on = self._clock() + float(data.get("expires_in", 3600))
+        return self._token
+
+    def _unreachable(self, request: httpx.Request, exc: Exception) -> GCPMetadataError:
+        return GCPMetadataError(
+            f"metadata server {request.url.host} is unreachable ({exc}); "
+            "run on Google Cloud or set GCE_METADATA_HOST"
+        )
+
+    def get_token(self) -> str:
+        """Return a valid access token, fetching a new one when the cached one is about to expire."""
+        with self._lock:
+            token = self._cached()
+            if token is not None:
+                return token
+            request = self._token_request()
+            if self._client is None:
+                self._client = httpx.Client(timeout=TOKEN_TIMEOUT)
+            try:
+                response = self._client.send(request)
+            except httpx.TransportError as e:
+                raise self._unreachable(request, e) from e
+            return self._store(response)
+
+    async def aget_token(self) -> str:
+        """Async variant of :meth:`get_token`."""
+        if self._async_lock is None:
+            self._async_lock = asyncio.Lock()
+        async with self._async_lock:
+            token = self._cached()
+            if token is not None:
+                return token
+            request = self._token_request()
+            if self._async_client is None:
+                self._async_client = httpx.AsyncClient(timeout=TOKEN_TIMEOUT)
+            try:
+                response = await self._async_client.send(request)
+            except httpx.TransportError as e:
+                raise self._unreachable(request, e) from e
+            return self._store(response)
+
+    def close(self) -> None:
+        """Close the HTTP client created for token requests."""
+        if self._owns_clients and self._client is not None:
+            self._client.close()
+
+    async def aclose(self) -> None:
+        """Close the HTTP clients created for token requests."""
+        if self._owns_clients and self._async_client is not None:
+            await self._async_client.aclose()
+        self.close()
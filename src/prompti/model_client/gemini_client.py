@@ -0,0 +1,411 @@
+"""Google Gemini client for the ``generateContent`` API.
+
+Requests are translated from the OpenAI message format used by the rest of
+the package into Gemini ``contents``, and responses back into
+:class:`ModelResponse` and streaming deltas, so callers see the same output
+as for any other provider. Streams use ``streamGenerateContent?alt=sse``.
+The API key is ``cfg.api_key``, or the ``GEMINI_API_KEY`` environment
+variable when unset::
+
+    client = create_client(ModelConfig(provider="gemini", model="gemini-2.5-flash"))
+
+Thought signatures of function calls are kept in the tool call as
+``extra_content.google.thought_signature`` (the shape of Gemini's OpenAI
+compatible API) and sent back with the call on the next turn, which
+thinking models require.
+"""
+
+from __future__ import annotations
+
+import json
+import mimetypes
+import os
+from typing import Any, Union
+
+import httpx
+
+from ..documents import to_openai_file_parts
+from ..message import Choice, Message, ModelResponse, StreamingChoice, StreamingModelResponse, Usage
+from ..tool_content import _call_arguments, _image_url, _part_text, to_openai_tool_messages
+from .base import RunParams, log_sampled_request
+from .openai_wire import (
+    _MALFORMED_DATA_ERRORS,
+    DeltaTextAssembler,
+    MalformedResponseError,
+    OpenAIWireClient,
+    OpenAIWireMixin,
+    SyncOpenAIWireClient,
+    ToolCallIndexer,
+)
+from .transport import encode_json_body
+from .types import ToolChoice
+
+DEFAULT_API_URL = "https://generativelanguage.googleapis.com/v1beta"
+
+# Gemini finishReason -> OpenAI finish_reason; others are lower-cased
+FINISH_REASONS = {
+    "STOP": "stop",
+    "MAX_TOKENS": "length",
+    "SAFETY": "content_filter",
+    "RECITATION": "content_filter",
+    "BLOCKLIST": "content_filter",
+    "PROHIBITED_CONTENT": "content_filter",
+    "SPII": "content_filter",
+    "IMAGE_SAFETY": "content_filter",
+}
+
+TOOL_CHOICE_MODES = {ToolChoice.AUTO: "AUTO", ToolChoice.BLOCK: "NONE", ToolChoice.REQUIRED: "ANY"}
+
+
+def _inline_or_file(url: str, default_mime: str) -> dict[str, Any]:
+    """Return the ``inlineData`` part of a base64 data URL, or a ``fileData`` part for other URLs."""
+    if url.startswith("data:") and ";base64," in url:
+        mime, data = url[5:].split(";base64,", 1)
+        return {"inlineData": {"mimeType": mime, "data": data}}
+    return {"fileData": {"mimeType": mimetypes.guess_type(url)[0] or default_mime, "fileUri": url}}
+
+
+def _parts(content: str | list[dict[str, Any]] | None) -> list[dict[str, Any]]:
+    """Convert OpenAI message ``content`` into Gemini parts."""
+    if not content:
+        return []
+    if isinstance(content, str):
+        return [{"text": content}]
+    parts = []
+    for part in content:
+        if (url := _image_url(part)) is not None:
+            parts.append(_inline_or_file(url, "image/jpeg"))
+        elif part.get("type") == "file":
+            file = part["file"]
+            parts.append(_inline_or_file(file.get("file_data") or file["file_id"], "application/pdf"))
+        elif (text := _part_text(part)) is not None:
+            parts.append({"text": text})
+    return parts
+
+
+def _function_call_part(call: dict[str, Any]) -> dict[str, Any]:
+    function = call["function"]
+    function_call = {"name": function["name"], "args": _call_arguments(call)}
+    if call.get("id"):
+        function_call["id"] = call["id"]
+    part: dict[str, Any] = {"functionCall": function_call}
+    signature = ((call.get("extra_content") or {}).get("google") or {}).get("thought_signature")
+    if signature:
+        part["thoughtSignature"] = signature
+    return part
+
+
+def _function_response_part(message: dict[str, Any], names: dict[str, str]) -> dict[str, Any]:
+    text = message.get("content") or ""
+    try:
+        response = json.loads(text)
+    except ValueError:
+        response = None
+    if not isinstance(response, dict):
+        response = {"content": text}
+    call_id = message.get("tool_call_id")
+    function_response = {"name": names.get(call_id, ""), "response": response}
+    if call_id:
+        function_response["id"] = call_id
+    return {"functionResponse": function_response}
+
+
+def to_gemini_contents(messages: list[dict[str, Any]]) -> tuple[list[dict[str, Any]], dict[str, Any] | None]:
+    """Return Gemini ``contents`` and ``systemInstruction`` for OpenAI-format ``messages``.
+
+    System and developer messages become the system instruction, tool results
+    ``functionResponse`` parts of a user turn. Consecutive messages of the
+    same role are merged into one turn, so parallel tool results answer their
+    calls together.
+    """
+    messages = to_openai_file_parts(to_openai_tool_messages(messages))
+    names = {
+        call["id"]: call["function"]["name"]
+        for message in messages
+        for call in message.get("tool_calls") or []
+        if call.get("id")
+    }
+    system: list[dict[str, Any]] = []
+    contents: list[dict[str, Any]] = []
+    for message in messages:
+        role = message.get("role")
+        if role in ("system", "developer"):
+            system.extend(_parts(message.get("content")))
+            continue
+        if role == "tool":
+            role, parts = "user", [_function_response_part(message, names)]
+        elif role == "assistant":
+            role = "model"
+            parts = _parts(message.get("content"))
+            parts += [_function_call_part(call) for call in message.get("tool_calls") or []]
+        else:
+            role, parts = "user", _parts(message.get("content"))
+        if contents and contents[-1]["role"] == role:
+            contents[-1]["parts"].extend(parts)
+        else:
+            contents.append({"role": role, "parts": parts})
+    return contents, ({"parts": system} if system else None)
+
+
+class GeminiWireMixin(OpenAIWireMixin):
+    """Gemini differences: ``x-goog-api-key`` auth, per-model URLs and the request/response shapes.
+
+    ``cfg.api_url`` is the API base, ``https://generativelanguage.googleapis.com/v1beta``
+    by default.
+    """
+
+    default_api_url = DEFAULT_API_URL
+    auth_header = "x-goog-api-key"
+    auth_scheme = None
+    error_label = "Gemini API"
+
+    def _request_url(self, endpoint: str | None = None, stream: bool = False) -> str:
+        base = (endpoint or self._endpoint() or self.default_api_url).rstrip("/")
+        method = "streamGenerateContent?alt=sse" if stream else "generateContent"
+        return f"{base}/models/{self.cfg.model}:{method}"
+
+    def _build_request(self, params: RunParams) -> httpx.Request:
+        request_data = self._build_request_data(params)
+        self._logger.info(request_data)
+        log_sampled_request(self.cfg, request_data)
+        body, body_headers = encode_json_body(self.cfg, request_data)
+        return self._client.build_request(
+            "POST",
+            self._request_url(stream=params.stream),
+            headers={**self._build_headers(params), **body_headers},
+            content=body,
+        )
+
+    def _build_headers(self, params: RunParams | None = None) -> dict[str, str]:
+        headers = super()._build_headers(params)
+        api_key = self.cfg.api_key or os.environ.get("GEMINI_API_KEY")
+        if api_key:
+            headers[self.auth_header] = api_key
+        return headers
+
+    def _embeddings_request(self, body: dict[str, Any]) -> httpx.Request:
+        raise NotImplementedError("Embeddings are not supported for Gemini")
+
+    def _upload_request(self, filename: str, data: bytes, media_type: str, purpose: str) -> httpx.Request:
+        raise NotImplementedError("File uploads are not supported for Gemini; send files inline")
+
+    def _build_request_data(self, params: RunParams) -> dict[str, Any]:
+        """构建 Gemini generateContent 请求数据。"""
+        messages = []
+        for msg in params.messages:
+            item: dict[str, Any] = {"role": msg.role, "content": msg.content}
+            if msg.tool_calls:
+                item["tool_calls"] = msg.tool_calls
+            if msg.tool_call_id:
+                item["tool_call_id"] = msg.tool_call_id
+            messages.append(item)
+        contents, system = to_gemini_contents(messages)
+        request_data: dict[str, Any] = {"contents": contents}
+        if system:
+            request_data["systemInstruction"] = system
+
+        config: dict[str, Any] = {}
+        temperature = params.temperature if params.temperature is not None else self.cfg.temperature
+        if temperature is not None:
+            config["temperature"] = temperature
+        top_p = params.top_p if params.top_p is not None else self.cfg.top_p
+        if top_p is not None:
+            config["topP"] = top_p
+        if params.top_k is not None:
+            config["topK"] = params.top_k
+        max_tokens = params.max_tokens if params.max_tokens is not None else self.cfg.max_tokens
+        if max_tokens is not None:
+            config["maxOutputTokens"] = max_tokens
+        if params.stop:
+            config["stopSequences"] = [params.stop] if isinstance(params.stop, str) else params.stop
+        if params.n is not None:
+            config["candidateCount"] = params.n
+        if params.seed is not None:
+            config["seed"] = params.seed
+        if params.response_format in ("json_object", "json_schema"):
+            config["responseMimeType"] = "application/json"
+        if config:
+            request_data["generationConfig"] = config
+
+        if params.tool_params:
+            self._add_tool_params(request_data, params.tool_params)
+
+        request_data.update(params.extra_params)
+        params.trace_context["llm_request"] = request_data
+        return request_data
+
+    def _add_tool_params(self, request_data: dict[str, Any], tool_params) -> None:
+        """添加 functionDeclarations 与 toolConfig。"""
+        if not tool_params or not tool_params.tools:
+            return
+        request_data["tools"] = [
+            {
+                "functionDeclarations": [
+                    {"name": tool.name, "description": tool.description, "parameters": tool.parameters}
+                    for tool in tool_params.tools
+                ]
+            }
+        ]
+        choice = tool_params.choice
+        calling: dict[str, Any] = {"mode": "ANY"}
+        if isinstance(choice, dict):
+            name = (choice.get("function") or {}).get("name") or choice.get("name")
+            calling["allowedFunctionNames"] = [name]
+        elif choice == ToolChoice.FORCE:
+            if tool_params.force_tool:
+                calling["allowedFunctionNames"] = [tool_params.force_tool]
+        else:
+            calling["mode"] = TOOL_CHOICE_MODES[choice]
+        request_data["toolConfig"] = {"functionCallingConfig": calling}
+
+    @staticmethod
+    def _parse_usage(data: dict[str, Any]) -> Usage:
+        usage = data.get("usageMetadata") or {}
+        prompt = usage.get("promptTokenCount", 0)
+        # thinking tokens are billed as output
+        completion = usage.get("candidatesTokenCount", 0) + usage.get("thoughtsTokenCount", 0)
+        return Usage(
+            prompt_tokens=prompt,
+            completion_tokens=completion,
+            total_tokens=usage.get("totalTokenCount", prompt + completion),
+        )
+
+    @staticmethod
+    def _candidate_message(candidate: dict[str, Any], tool_index: Any = None) -> tuple[Message, str | None]:
+        """Return the OpenAI message and finish reason of a Gemini ``candidate``.
+
+        ``tool_index`` numbers the tool calls of a stream; without it they
+        are complete calls of a response.
+        """
+        texts, thoughts, tool_calls = [], [], []
+        for part in (candidate.get("content") or {}).get("parts") or []:
+            if "functionCall" in part:
+                function_call = part["functionCall"]
+                index = tool_index() if tool_index is not None else len(tool_calls)
+                call: dict[str, Any] = {
+                    "id": function_call.get("id") or f"call_{index}",
+                    "type": "function",
+                    "function": {
+                        "name": function_call["name"],
+                        "arguments": json.dumps(function_call.get("args") or {}, ensure_ascii=False),
+                    },
+                }
+                if tool_index is not None:
+                    call = {"index": index, **call}
+                if part.get("thoughtSignature"):
+                    call["extra_content"] = {"google": {"thought_signature": part["thoughtSignature"]}}
+                tool_calls.append(call)
+            elif "text" in part:
+                (thoughts if part.get("thought") else texts).append(part["text"])
+        reason = candidate.get("finishReason")
+        finish_reason = FINISH_REASONS.get(reason, reason.lower()) if reason else None
+        if tool_calls and finish_reason == "stop":
+            finish_reason = "tool_calls"
+        message = Message(
+            role="assistant",
+            content="".join(texts) or None,
+            reasoning_content="".join(thoughts) or None,
+            tool_calls=tool_calls or None,
+        )
+        return message, finish_reason
+
+    def _blocked(self, data: dict[str, Any], is_streaming: bool) -> Union[ModelResponse, StreamingModelResponse]:
+        reason = (data.get("promptFeedback") or {}).get("blockReason", "unknown")
+        return self._create_error_response(
+            f"{self.error_label} blocked the prompt: {reason}", is_streaming=is_streaming, code="content_filter"
+        )
+
+    def _native_stream_decoder(self) -> ToolCallIndexer:
+        return ToolCallIndexer()
+
+    def _parse_stream_chunk(
+        self,
+        data_str: str,
+        assembler: DeltaTextAssembler | None = None,
+        native: ToolCallIndexer | None = None,
+    ) -> StreamingModelResponse | None:
+        """把一个 streamGenerateContent SSE 负载解析为流式响应。"""
+        try:
+            data = json.loads(data_str)
+        except (json.JSONDecodeError, RecursionError):
+            return None
+        if not isinstance(data, dict):
+            return None
+        try:
+            if not data.get("candidates"):
+                if (data.get("promptFeedback") or {}).get("blockReason"):
+                    return self._blocked(data, is_streaming=True)
+                # usage-only chunk at the end of some streams
+                return self._stream_response(data, []) if "usageMetadata" in data else None
+            native = native or ToolCallIndexer()
+            choices = []
+            finished = False
+            for position, candidate in enumerate(data["candidates"]):
+                message, finish_reason = self._candidate_message(candidate, native.next_index)
+                if assembler is not None:
+                    index = candidate.get("index", position)
+                    message.content = assembler.push(("content", index), message.content)
+                    message.reasoning_content = assembler.push(("reasoning", index), message.reasoning_content)
+                choices.append(
+                    StreamingChoice(index=candidate.get("index", position), delta=message, finish_reason=finish_reason)
+                )
+                finished = finished or finish_reason is not None
+            # usageMetadata is repeated on every chunk; report it once, with the finish reason
+            return self._stream_response(data if finished else {**data, "usageMetadata": None}, choices)
+        except _MALFORMED_DATA_ERRORS as e:
+            raise MalformedResponseError("stream", f"Malformed stream chunk from {self.error_label}: {e}") from e
+
+    def _stream_response(self, data: dict[str, Any], choices: list[StreamingChoice]) -> StreamingModelResponse:
+        return StreamingModelResponse(
+            id=data.get("responseId", ""),
+            object="chat.completion.chunk",
+            created=0,
+            model=data.get("modelVersion", self.cfg.model),
+            choices=choices,
+            usage=self._parse_usage(data) if data.get("usageMetadata") else None,
+        )
+
+    def _process_non_streaming_response(self, response: httpx.Response) -> ModelResponse:
+        """处理 generateContent 响应。
+
+        Raises:
+            MalformedResponseError: If the body is not a valid response.
+        """
+        try:
+            data = response.json()
+        except (ValueError, RecursionError) as e:
+            raise MalformedResponseError("json", f"{self.error_label} returned invalid JSON: {e}") from e
+        if isinstance(data, dict) and not data.get("candidates") and "promptFeedback" in data:
+            return self._blocked(data, is_streaming=False)
+        if not isinstance(data, dict) or not data.get("candidates"):
+            raise MalformedResponseError("json", f"Unexpected response format: {str(data)[:500]}")
+        try:
+            return self._response_from(data)
+        except _MALFORMED_DATA_ERRORS as e:
+            raise MalformedResponseError("json", f"Malformed response from {self.error_label}: {e}") from e
+
+    def _response_from(self, data: dict[str, Any]) -> ModelResponse:
+        choices = []
+        for position, candidate in enumerate(data["candidates"]):
+            message, finish_reason = self._candidate_message(candidate)
+            choices.append(Choice(index=candidate.get("index", position), message=message, finish_reason=finish_reason))
+        return ModelResponse(
+            id=data.get("responseId", ""),
+            object="chat.completion",
+            created=0,
+            model=data.get("modelVersion", self.cfg.model),
+            choices=choices,
+            usage=self._parse_usage(data) if "usageMetadata" in data else None,
+        )
+
+
+class GeminiClient(GeminiWireMixin, OpenAIWireClient):
+    """Google Gemini API client."""
+
+    provider = "gemini"
+
+
+class SyncGeminiClient(GeminiWireMixin, SyncOpenAIWireClient):
+    """Synchronous Google Gemini API client."""
+
+    provider = "gemini"
@@ -0,0 +1,123 @@
+"""Rolling per-provider health for status pages.
+
+:class:`HealthTracker` is an :class:`EventHook` that remembers the outcome
+and duration of recent calls per provider. :class:`ClientManager` installs
+one on every client it creates, so a snapshot is one call away::
+
+    for provider, health in manager.provider_health().items():
+        print(provider, health.success_rate, health.p95_latency, health.circuit_state)
+
+The circuit state is derived from consecutive failures: ``open`` after
+``failure_threshold`` failures in a row, ``half_open`` once ``cooldown_s``
+passed since the last of them, and ``closed`` again after a success. It
+describes the provider for dashboards; requests are not blocked by it.
+"""
+
+from __future__ import annotations
+
+import math
+import threading
+import time
+from collections import deque
+from typing import Any, Literal
+
+from pydantic import BaseModel
+
+from ..message import Usage
+from .base import EventHook
+from .types import ModelConfig, RateLimitHeadroom, RunParams
+
+__all__ = ["HealthTracker", "ProviderHealth"]
+
+CircuitState = Literal["closed", "open", "half_open"]
+
+
+class ProviderHealth(BaseModel):
+    """Snapshot of one provider over the tracker's window."""
+
+    provider: str
+    requests: int = 0
+    # ``None`` without requests in the window
+    success_rate: float | None = None
+    # seconds, over successful calls
+    p95_latency: float | None = None
+    circuit_state: CircuitState = "closed"
+    consecutive_failures: int = 0
+    # the provider's latest ``x-ratelimit-*`` headers, ``None`` if it sends none
+    rate_limit: RateLimitHeadroom | None = None
+
+
+class _ProviderStats:
+    def __init__(self) -> None:
+        # (monotonic time, succeeded, duration or None)
+        self.calls: deque[tuple[float, bool, float | None]] = deque()
+        self.consecutive_failures = 0
+        self.last_failure = 0.0
+
+
+class HealthTracker(EventHook):
+    """Track success rate, latency and circuit state per provider.
+
+    Args:
+        window_s: Only calls of the last ``window_s`` seconds count.
+        failure_threshold: Consecutive failures that open the circuit.
+        cooldown_s: Seconds after the last failure before an open circuit is half open.
+    """
+
+    def __init__(self, window_s: float = 300.0, failure_threshold: int = 5, cooldown_s: float = 30.0) -> None:
+        self.window_s = window_s
+        self.failure_threshold = failure_threshold
+        self.cooldown_s = cooldown_s
+        self._stats: dict[str, _ProviderStats] = {}
+        self._lock = threading.Lock()
+
+    def _record(self, provider: str | None, ok: bool, duration: float | None) -> None:
+        now = time.monotonic()
+        with self._lock:
+            stats = self._stats.setdefault(provider or "unknown", _ProviderStats())
+            stats.calls.append((now, ok, duration))
+            self._prune(stats, now)
+            if ok:
+                stats.consecutive_failures = 0
+            else:
+                stats.consecutive_failures += 1
+                stats.last_failure = now
+
+    def _prune(self, stats: _ProviderStats, now: float) -> None:
+        while stats.calls and stats.calls[0][0] < now - self.window_s:
+            stats.calls.popleft()
+
+    def on_complete(self, cfg: ModelConfig, params: RunParams, usage: Usage | None, duration: float) -> None:
+        self._record(cfg.provider, True, duration)
+
+    def on_error(
+        self, cfg: ModelConfig, params: RunParams, error_class: str, error: dict[str, Any] | BaseException
+    ) -> None:
+        self._record(cfg.provider, False, None)
+
+    def _circuit_state(self, stats: _ProviderStats, now: float) -> CircuitState:
+        if stats.consecutive_failures < self.failure_threshold:
+            return "closed"
+        return "half_open" if now - stats.last_failure >= self.cooldown_s else "open"
+
+    def snapshot(self, provider: str) -> ProviderHealth:
+        """Return the health of ``provider``; a provider without calls is reported healthy and idle."""
+        now = time.monotonic()
+        with self._lock:
+            stats = self._stats.get(provider)
+            if stats is None:
+                return ProviderHealth(provider=provider)
+            self._prune(stats, now)
+            calls = list(stats.calls)
+            state = self._circuit_state(stats, now)
+            failures = stats.consecutive_failures
+        latencies = sorted(duration for _, ok, duration in calls if ok and duration is not None)
+        p95 = latencies[math.ceil(0.95 * len(latencies)) - 1] if latencies else None
+        return ProviderHealth(
+            provider=provider,
+            requests=len(calls),
+            success_rate=sum(ok for _, ok, _ in calls) / len(calls) if calls else None,
+            p95_latency=p95,
+            circuit_state=state,
+            consecutive_failures=failures,
+        )
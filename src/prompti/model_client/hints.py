@@ -0,0 +1,139 @@
+"""Short, actionable hints for failed model calls.
+
+:func:`remediation` explains an error object (``response.error``) or a raised
+exception in one sentence an end user can act on, on top of the coarse
+:class:`ErrorClass` of :func:`classify_error`::
+
+    async for response in client.arun(params):
+        if response.error:
+            print(remediation(response.error, cfg=client.cfg, params=params).hint)
+
+Context window overflows and content filter blocks are recognised from the
+provider's code and message; for overflows the prompt size and the context
+window are reported, taken from the provider's message when it states them
+and estimated from ``params`` and ``cfg.capabilities`` otherwise.
+"""
+
+from __future__ import annotations
+
+import re
+from typing import Any
+
+import httpx
+from pydantic import BaseModel
+
+from ..postprocess import estimate_message_tokens
+from .base import classify_error
+from .deprecations import is_model_not_found, replacement_model
+from .types import ErrorClass, ModelConfig, RunParams
+
+__all__ = ["Remediation", "remediation"]
+
+_CONTEXT_CODES = {"context_length_exceeded", "string_above_max_length", "request_too_large"}
+_CONTEXT_MESSAGES = re.compile(
+    r"maximum context length|context window|context length|prompt is too long|input is too long"
+    r"|too many (input )?tokens|exceeds the (maximum|max) (number of )?tokens",
+    re.IGNORECASE,
+)
+# "maximum context length is 8192 tokens. However, you requested 9013 tokens"
+# "prompt is too long: 210000 tokens > 200000 maximum"
+_CONTEXT_NUMBERS = (
+    re.compile(r"maximum context length is (?P<window>\d+) tokens.*?(?:requested|resulted in) (?P<prompt>\d+)", re.S),
+    re.compile(r"(?P<prompt>\d+) tokens > (?P<window>\d+) maximum"),
+)
+
+
+class Remediation(BaseModel):
+    """What went wrong and what to do about it."""
+
+    error_class: ErrorClass
+    # finer than ``error_class``: "context_exceeded", "content_filter", "model_deprecated",
+    # "not_found", "malformed_response" or the error class value
+    reason: str
+    hint: str
+    # for "context_exceeded": prompt tokens and context window, when known
+    prompt_tokens: int | None = None
+    context_window: int | None = None
+
+
+def _context_numbers(message: str, cfg: ModelConfig | None, params: RunParams | None) -> tuple[int | None, int | None]:
+    for pattern in _CONTEXT_NUMBERS:
+        match = pattern.search(message)
+        if match:
+            return int(match["prompt"]), int(match["window"])
+    prompt = estimate_message_tokens(params.messages) if params is not None else None
+    window = cfg.capabilities.context_window if cfg is not None and cfg.capabilities is not None else None
+    return prompt, window
+
+
+def remediation(
+    error: dict[str, Any] | None = None,
+    exc: BaseException | None = None,
+    *,
+    streaming_started: bool = False,
+    cfg: ModelConfig | None = None,
+    params: RunParams | None = None,
+) -> Remediation:
+    """Return the hint for a failed call, given its error object or raised exception.
+
+    ``cfg`` and ``params`` make the hint more specific (provider, model,
+    prompt size) but are optional.
+    """
+    error = error or {}
+    error_class = classify_error(error, exc, streaming_started)
+    message = str(error.get("message") or exc or "")
+    code = error.get("code")
+    status = error.get("status_code")
+    if isinstance(exc, httpx.HTTPStatusError):
+        status = exc.response.status_code
+    provider = (cfg.provider if cfg is not None else None) or "the provider"
+    model = cfg.model if cfg is not None and cfg.model else "the model"
+
+    if error_class is ErrorClass.RATE_LIMIT:
+        hint = f"Rate limited by {provider}: lower the request rate or concurrency, or request a quota increase."
+        if error.get("retry_after"):
+            hint += f" Retry after {error['retry_after']}s."
+        return Remediation(error_class=error_class, reason="rate_limit", hint=hint)
+    if error_class is ErrorClass.AUTH:
+        hint = f"Credentials rejected by {provider}: check the API key and that it may use {model}."
+        return Remediation(error_class=error_class, reason="auth", hint=hint)
+    if error_class is ErrorClass.TIMEOUT:
+        if code == "deadline_exceeded":
+            hint = "The call ran past timeout.deadline_s, retries included: raise it or lower max_tokens."
+        else:
+            hint = "The request timed out: stream the response, lower max_tokens or raise timeout.read_s."
+        return Remediation(error_class=error_class, reason="timeout", hint=hint)
+    if error_class is ErrorClass.STREAM:
+        hint = "The stream failed after output had started: retry the request; the partial answer is incomplete."
+        return Remediation(error_class=error_class, reason="stream", hint=hint)
+    if error_class is ErrorClass.SERVER:
+        hint = f"Server error at {provider}: retry later, or configure failover_api_urls or another model."
+        return Remediation(error_class=error_class, reason="server", hint=hint)
+
+    if code in _CONTEXT_CODES or status == 413 or _CONTEXT_MESSAGES.search(message):
+        prompt, window = _context_numbers(message, cfg, params)
+        if prompt is not None and window is not None:
+            size = f"The prompt (~{prompt} tokens) exceeds the {window}-token context window of {model}"
+        elif prompt is not None:
+            size = f"The prompt (~{prompt} tokens) is too long for {model}"
+        else:
+            size = f"The prompt is too long for {model}"
+        hint = f"{size}: truncate or summarize the conversation, lower max_tokens, or use a larger-context model."
+        return Remediation(
+            error_class=error_class, reason="context_exceeded", hint=hint, prompt_tokens=prompt, context_window=window
+        )
+    if code == "content_filter":
+        hint = f"Blocked by the content filter of {provider}: rephrase the prompt or review the input."
+        return Remediation(error_class=error_class, reason="content_filter", hint=hint)
+    if code in ("malformed_response", "malformed_stream", "unknown_response_field"):
+        hint = f"Unparseable response from {provider}: check that api_url is a compatible endpoint."
+        return Remediation(error_class=error_class, reason="malformed_response", hint=hint)
+    replacement = replacement_model(cfg.model, cfg.model_aliases) if cfg is not None else None
+    if replacement and is_model_not_found(error, cfg.model):
+        hint = f'{model} is deprecated or retired: use {replacement}, or set model_fallback="substitute".'
+        return Remediation(error_class=error_class, reason="model_deprecated", hint=hint)
+    if status == 404:
+        hint = f"Not found: check the model name ({model}) and api_url."
+        return Remediation(error_class=error_class, reason="not_found", hint=hint)
+    hint = f"Request rejected by {provider}: check the model name ({model}) and the request parameters."
+    return Remediation(error_class=error_class, reason="client", hint=hint)
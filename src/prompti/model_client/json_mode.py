@@ -0,0 +1,61 @@
+"""JSON mode for providers and models that do not support ``response_format``.
+
+``RunParams(response_format="json_object")`` is sent as is to providers with
+a native JSON mode. For the others, e.g. Bedrock or an OpenAI-compatible
+server with ``compat.supports_json_mode=False``, the client emulates it:
+
+* the request is sent without ``response_format`` and with
+  :data:`JSON_INSTRUCTION` added to the system message, and
+* the ``repair_json`` postprocessor (:class:`prompti.postprocess.RepairJSON`)
+  keeps only the JSON value of the answer, removes trailing commas and closes
+  a value cut off by ``max_tokens``.
+
+Callers get the same JSON text either way. ``ModelConfig.emulate_json_mode``
+forces emulation, e.g. for older models of a provider that otherwise has
+JSON mode, or turns it off.
+"""
+
+from __future__ import annotations
+
+from ..message import Message
+from .types import ModelConfig, RunParams
+
+__all__ = ["JSON_FORMATS", "JSON_INSTRUCTION", "emulate_json_mode", "needs_json_emulation"]
+
+JSON_FORMATS = ("json_object", "json_schema")
+
+JSON_INSTRUCTION = (
+    "Respond with a single valid JSON value and nothing else. "
+    "Do not wrap it in Markdown code fences and do not add any text before or after it."
+)
+
+
+def needs_json_emulation(cfg: ModelConfig, params: RunParams, native: bool) -> bool:
+    """Whether ``params`` asks for JSON mode that has to be emulated for ``cfg``.
+
+    Args:
+        cfg: Model configuration; ``emulate_json_mode`` overrides ``native``.
+        params: The call.
+        native: Whether the provider accepts ``response_format``.
+    """
+    if params.response_format not in JSON_FORMATS:
+        return False
+    if cfg.emulate_json_mode is not None:
+        return cfg.emulate_json_mode
+    return not native
+
+
+def emulate_json_mode(params: RunParams) -> RunParams:
+    """Return a copy of ``params`` that asks for JSON in the prompt instead of ``response_format``."""
+    messages = list(params.messages)
+    first = messages[0] if messages else None
+    if first is not None and first.role == "system" and isinstance(first.content, str):
+        messages[0] = first.model_copy(update={"content": f"{first.content}\n\n{JSON_INSTRUCTION}"})
+    else:
+        messages.insert(0, Message.create_system(JSON_INSTRUCTION))
+    postprocessors = list(params.postprocessors)
+    if "repair_json" not in postprocessors:
+        postprocessors.append("repair_json")
+    return params.model_copy(
+        update={"messages": messages, "response_format": None, "postprocessors": postprocessors}
+    )
@@ -10,8 +10,21 @@ from typing import Any, Dict, List, Union
 
 import httpx
 
+from ..documents import extract_citations
 from ..message import Message, ModelResponse, StreamingModelResponse, Usage, Choice, StreamingChoice
-from .base import ModelClient, SyncModelClient, ModelConfig, RunParams, ToolChoice, ToolParams, ToolSpec
+from ..tool_content import to_openai_tool_messages
+from .base import (
+    ModelClient,
+    ModelConfig,
+    RunParams,
+    SyncModelClient,
+    ToolChoice,
+    ToolParams,
+    ToolSpec,
+    build_extra_headers,
+    log_sampled_request,
+)
+from .transport import check_request_size
 
 
 class LiteLLMClient(ModelClient):
@@ -36,7 +49,7 @@ class LiteLLMClient(ModelClient):
     def _build_request_data(self, params: RunParams) -> Dict[str, Any]:
         """构建LiteLLM API请求数据。"""
         # 转换消息格式
-        messages = [m.to_openai() for m in params.messages]
+        messages = to_openai_tool_messages([m.to_openai() for m in params.messages])
 
         # 基础请求数据
         request_data = {
@@ -52,6 +65,11 @@ class LiteLLMClient(ModelClient):
             request_data["api_key"] = self.api_key
         if self.api_url:
             request_data["api_base"] = self.api_url  # litellm 使用 api_base 参数
+        if self.cfg.organization:
+            request_data["organization"] = self.cfg.organization
+        extra_headers = build_extra_headers(self.cfg, params)
+        if extra_headers:
+            request_data["extra_headers"] = extra_headers
 
         # 添加可选参数
         if params.temperature is not None:
@@ -199,7 +217,11 @@ class LiteLLMClient(ModelClient):
                 message=Message(
                     role="assistant",
                     content=message.content if hasattr(message, "content") else None,
-                    tool_calls=tool_calls
+                    tool_calls=tool_calls,
+                    # Anthropic 文档引用由 LiteLLM 放在 provider_specific_fields 中
+                    citations=extract_citations(
+                        {"provider_specific_fields": getattr(message, "provider_specific_fields", None)}
+                    ),
                 ),
                 finish_reason=choice.finish_reason if hasattr(choice, "finish_reason") else None
             )
@@ -234,14 +256,21 @@ class LiteLLMClient(ModelClient):
                 )]
             )
 
-    def _create_error_response(self, error_message: str, is_streaming: bool = False) -> Union[
-        ModelResponse, StreamingModelResponse]:
+    def _create_error_response(
+        self,
+        error_message: str,
+        is_streaming: bool = False,
+        status_code: int | None = None,
+        code: str = "request_error",
+    ) -> Union[ModelResponse, StreamingModelResponse]:
         """创建错误响应。"""
         error_object = {
             "message": error_message,
             "type": "litellm_error",
-            "code": "request_error"
+            "code": code
         }
+        if status_code is not None:
+            error_object["status_code"] = status_code
 
         if is_streaming:
             return StreamingModelResponse(error=error_object)
@@ -261,6 +290,8 @@ class LiteLLMClient(ModelClient):
         # 构建请求数据
         request_data = self._build_request_data(params)
         self._logger.info(f"litellm request data: {request_data}")
+        log_sampled_request(self.cfg, request_data)
+        check_request_size(self.cfg, request_data)
         try:
             if params.stream:
                 # 处理流式响应
@@ -292,14 +323,16 @@ class LiteLLMClient(ModelClient):
             # 网络连接错误
             error_msg = f"Network error: {str(e)}"
             self._logger.error(error_msg)
-            yield self._create_error_response(error_msg, is_streaming=params.stream)
+            yield self._create_error_response(error_msg, is_streaming=params.stream, code="network_error")
 
         except Exception as e:
             # 其他错误
             error_msg = f"Unexpected error: {str(e)}"
             self._logger.error(error_msg)
             traceback.print_exc()
-            yield self._create_error_response(error_msg, is_streaming=params.stream)
+            yield self._create_error_response(
+                error_msg, is_streaming=params.stream, status_code=getattr(e, "status_code", None)
+            )
 
     async def aclose(self) -> None:
         """Close the underlying HTTP client and clean up LiteLLM resources."""
@@ -349,7 +382,7 @@ class SyncLiteLLMClient(SyncModelClient):
 
     def _build_request_data(self, params: RunParams) -> Dict[str, Any]:
         """构建LiteLLM API请求数据。"""
-        messages = [m.to_openai() for m in params.messages]
+        messages = to_openai_tool_messages([m.to_openai() for m in params.messages])
 
         request_data = {
             "model": self.cfg.model,
@@ -363,6 +396,11 @@ class SyncLiteLLMClient(SyncModelClient):
             request_data["api_key"] = self.api_key
         if self.api_url:
             request_data["api_base"] = self.api_url
+        if self.cfg.organization:
+            request_data["organization"] = self.cfg.organization
+        extra_headers = build_extra_headers(self.cfg, params)
+        if extra_headers:
+            request_data["extra_headers"] = extra_headers
 
         if params.temperature is not None:
             request_data["temperature"] = params.temperature
@@ -498,7 +536,11 @@ class SyncLiteLLMClient(SyncModelClient):
                 message=Message(
                     role="assistant",
                     content=message.content if hasattr(message, "content") else None,
-                    tool_calls=tool_calls
+                    tool_calls=tool_calls,
+                    # Anthropic 文档引用由 LiteLLM 放在 provider_specific_fields 中
+                    citations=extract_citations(
+                        {"provider_specific_fields": getattr(message, "provider_specific_fields", None)}
+                    ),
                 ),
                 finish_reason=choice.finish_reason if hasattr(choice, "finish_reason") else None
             )
@@ -530,14 +572,21 @@ class SyncLiteLLMClient(SyncModelClient):
                 )]
             )
 
-    def _create_error_response(self, error_message: str, is_streaming: bool = False) -> Union[
-        ModelResponse, StreamingModelResponse]:
+    def _create_error_response(
+        self,
+        error_message: str,
+        is_streaming: bool = False,
+        status_code: int | None = None,
+        code: str = "request_error",
+    ) -> Union[ModelResponse, StreamingModelResponse]:
         """创建错误响应。"""
         error_object = {
             "message": error_message,
             "type": "litellm_error",
-            "code": "request_error"
+            "code": code
         }
+        if status_code is not None:
+            error_object["status_code"] = status_code
 
         if is_streaming:
             return StreamingModelResponse(error=error_object)
@@ -555,6 +604,8 @@ class SyncLiteLLMClient(SyncModelClient):
 
         request_data = self._build_request_data(params)
         self._logger.info(f"litellm request data: {request_data}")
+        log_sampled_request(self.cfg, request_data)
+        check_request_size(self.cfg, request_data)
         try:
             if params.stream:
                 response = litellm.completion(
@@ -587,9 +638,11 @@ class SyncLiteLLMClient(SyncModelClient):
             if isinstance(e, httpx.RequestError):
                 error_msg = f"Network error: {str(e)}"
                 self._logger.error(error_msg)
-                yield self._create_error_response(error_msg, is_streaming=params.stream)
+                yield self._create_error_response(error_msg, is_streaming=params.stream, code="network_error")
             else:
                 error_msg = f"Unexpected error: {str(e)}"
                 self._logger.error(error_msg)
                 traceback.print_exc()
-                yield self._create_error_response(error_msg, is_streaming=params.stream)
+                yield self._create_error_response(
+                    error_msg, is_streaming=params.stream, status_code=getattr(e, "status_code", None)
+                )
@@ -0,0 +1,122 @@
+"""Named, cached model clients that share one connection pool."""
+
+from __future__ import annotations
+
+import threading
+from typing import Any
+
+import httpx
+
+from .base import EventHook, ModelClient, ModelConfig
+from .config_loader import ModelConfigLoader, ModelConfigNotFoundError
+from .factory import create_client
+from .health import HealthTracker, ProviderHealth
+from .routing import RequestRequirements, select_model
+
+
+class ClientManager:
+    """Construct :class:`ModelClient` instances per profile on demand and cache them.
+
+    A profile is either a name registered with :meth:`register` or a model
+    name resolved through ``loaders``. All clients share a single
+    ``httpx.AsyncClient`` so connections are pooled across profiles; the
+    manager owns that pool, so close the manager rather than the clients::
+
+        async with ClientManager({"fast": ModelConfig(provider="openai", model="gpt-4o-mini")}) as manager:
+            async for response in manager.get("fast").arun(params):
+                ...
+    """
+
+    def __init__(
+        self,
+        profiles: dict[str, ModelConfig] | None = None,
+        *,
+        loaders: list[ModelConfigLoader] | None = None,
+        event_hooks: list[EventHook] | None = None,
+        http_client: httpx.AsyncClient | None = None,
+    ) -> None:
+        """Create the manager with static ``profiles`` and optional fallback ``loaders``."""
+        self._profiles: dict[str, ModelConfig] = dict(profiles or {})
+        self._loaders = list(loaders or [])
+        self._event_hooks = list(event_hooks or [])
+        self._http_client = http_client or httpx.AsyncClient(http2=True, timeout=httpx.Timeout(600))
+        self._clients: dict[str, ModelClient] = {}
+        self._lock = threading.Lock()
+        # outcome of every call made through the manager's clients, see :meth:`provider_health`
+        self.health = HealthTracker()
+
+    def register(self, name: str, cfg: ModelConfig) -> None:
+        """Add or replace the profile ``name``; a cached client for it is dropped."""
+        with self._lock:
+            self._profiles[name] = cfg
+            self._clients.pop(name, None)
+
+    def profiles(self) -> list[str]:
+        """Return the names of the registered profiles."""
+        return list(self._profiles)
+
+    def resolve(self, name: str) -> ModelConfig:
+        """Return the configuration for profile or model ``name``.
+
+        Raises:
+            ModelConfigNotFoundError: If neither a profile nor any loader knows ``name``.
+        """
+        if name in self._profiles:
+            return self._profiles[name]
+        for loader in self._loaders:
+            try:
+                return loader.get_model_config(name)
+            except ModelConfigNotFoundError:
+                continue
+        raise ModelConfigNotFoundError(name)
+
+    def get(self, name: str) -> ModelClient:
+        """Return the cached client for ``name``, creating it on first use."""
+        with self._lock:
+            client = self._clients.get(name)
+            if client is None:
+                client = create_client(
+                    self.resolve(name), event_hooks=[*self._event_hooks, self.health], http_client=self._http_client
+                )
+                self._clients[name] = client
+            return client
+
+    def select(self, requirements: RequestRequirements) -> str:
+        """Return the registered profile best satisfying ``requirements``, see :func:`.routing.select_model`.
+
+        Raises:
+            NoMatchingModelError: If no profile qualifies.
+        """
+        with self._lock:
+            profiles = dict(self._profiles)
+        return select_model(profiles, requirements)
+
+    def provider_health(self) -> dict[str, ProviderHealth]:
+        """Return the rolling health of every provider with a profile or a cached client.
+
+        The rate-limit headroom is the latest one reported to any of the
+        provider's clients.
+        """
+        with self._lock:
+            providers = [cfg.provider for cfg in self._profiles.values()]
+            clients = list(self._clients.values())
+        providers.extend(client.cfg.provider for client in clients)
+        result = {}
+        for provider in dict.fromkeys(p for p in providers if p):
+            health = self.health.snapshot(provider)
+            limits = [c.rate_limit for c in clients if c.cfg.provider == provider and c.rate_limit is not None]
+            health.rate_limit = max(limits, key=lambda limit: limit.updated_at, default=None)
+            result[provider] = health
+        return result
+
+    async def aclose(self) -> None:
+        """Close the shared connection pool and forget all cached clients."""
+        with self._lock:
+            self._clients.clear()
+        await self._http_client.aclose()
+
+    async def __aenter__(self) -> ClientManager:
+        return self
+
+    async def __aexit__(self, *exc: Any) -> None:
+        await self.aclose()
@@ -0,0 +1,130 @@
+"""Mistral AI client for ``api.mistral.ai`` chat completions.
+
+The API follows the OpenAI format with a few differences handled here:
+``seed`` is sent as ``random_seed``, ``tool_choice="required"`` as
+``"any"``, ``stream_options``, ``user`` and ``logit_bias`` are left out, and
+tool call ids, which Mistral requires to be 9 letters or digits, are mapped
+onto such ids. Mistral's guardrail prompt is enabled per profile with
+``cfg.extra_params["safe_prompt"]`` or per call with
+``RunParams(extra_params={"safe_prompt": True})``::
+
+    client = create_client(ModelConfig(provider="mistral", model="mistral-large-latest"))
+
+The API key is ``cfg.api_key``, or the ``MISTRAL_API_KEY`` environment
+variable when unset.
+"""
+
+from __future__ import annotations
+
+import hashlib
+import os
+import re
+from typing import Any
+
+import httpx
+
+from ..message import ModelResponse, StreamingModelResponse
+from .base import RunParams
+from .openai_wire import DeltaTextAssembler, OpenAIWireClient, OpenAIWireMixin, SyncOpenAIWireClient
+
+DEFAULT_API_URL = "https://api.mistral.ai/v1/chat/completions"
+
+# request fields the API rejects
+UNSUPPORTED_FIELDS = ("stream_options", "user", "logit_bias")
+
+# Mistral finish reason -> OpenAI finish reason
+FINISH_REASONS = {"model_length": "length"}
+
+_TOOL_CALL_ID = re.compile(r"[A-Za-z0-9]{9}")
+
+
+def mistral_tool_call_id(call_id: str) -> str:
+    """Return ``call_id`` as the 9 letters or digits Mistral accepts, the same for the same id."""
+    if _TOOL_CALL_ID.fullmatch(call_id):
+        return call_id
+    return hashlib.sha256(call_id.encode()).hexdigest()[:9]
+
+
+def _with_mistral_tool_call_ids(messages: list[dict[str, Any]]) -> list[dict[str, Any]]:
+    result = []
+    for message in messages:
+        if message.get("tool_calls"):
+            calls = [
+                {**call, "id": mistral_tool_call_id(call["id"])} if call.get("id") else call
+                for call in message["tool_calls"]
+            ]
+            message = {**message, "tool_calls": calls}
+        if message.get("tool_call_id"):
+            message = {**message, "tool_call_id": mistral_tool_call_id(message["tool_call_id"])}
+        result.append(message)
+    return result
+
+
+class MistralWireMixin(OpenAIWireMixin):
+    """Mistral differences from the OpenAI chat completions API."""
+
+    default_api_url = DEFAULT_API_URL
+    error_label = "Mistral API"
+    document_blocks = False
+
+    def _build_headers(self, params: RunParams | None = None) -> dict[str, str]:
+        headers = super()._build_headers(params)
+        api_key = self.cfg.api_key or os.environ.get("MISTRAL_API_KEY")
+        if api_key:
+            headers[self.auth_header] = f"Bearer {api_key}"
+        return headers
+
+    def _build_request_data(self, params: RunParams) -> dict[str, Any]:
+        request_data = super()._build_request_data(params)
+        for field in UNSUPPORTED_FIELDS:
+            request_data.pop(field, None)
+        if "seed" in request_data:
+            request_data["random_seed"] = request_data.pop("seed")
+        if request_data.get("tool_choice") == "required":
+            request_data["tool_choice"] = "any"
+        if self.cfg.extra_params.get("safe_prompt"):
+            request_data.setdefault("safe_prompt", True)
+        request_data["messages"] = _with_mistral_tool_call_ids(request_data["messages"])
+        return request_data
+
+    @staticmethod
+    def _parse_error_body(response: httpx.Response) -> str:
+        """Mistral 错误体为 ``{"message": ...}``，参数校验错误为 ``{"detail": [...]}``。"""
+        if not response.content:
+            return f"HTTP {response.status_code}"
+        try:
+            data = response.json()
+        except Exception:
+            return f"HTTP {response.status_code}: {response.text}"
+        if isinstance(data, dict):
+            if data.get("message"):
+                return str(data["message"])
+            if data.get("detail"):
+                return str(data["detail"])
+        return str(data)
+
+    def _stream_chunk_from(
+        self, data: dict[str, Any], assembler: DeltaTextAssembler | None
+    ) -> StreamingModelResponse:
+        response = super()._stream_chunk_from(data, assembler)
+        for choice in response.choices:
+            choice.finish_reason = FINISH_REASONS.get(choice.finish_reason, choice.finish_reason)
+        return response
+
+    def _response_from(self, data: dict[str, Any]) -> ModelResponse:
+        response = super()._response_from(data)
+        for choice in response.choices:
+            choice.finish_reason = FINISH_REASONS.get(choice.finish_reason, choice.finish_reason)
+        return response
+
+
+class MistralClient(MistralWireMixin, OpenAIWireClient):
+    """Mistral AI API client."""
+
+    provider = "mistral"
+
+
+class SyncMistralClient(MistralWireMixin, SyncOpenAIWireClient):
+    """Synchronous Mistral AI API client."""
+
+    provider = "mistral"
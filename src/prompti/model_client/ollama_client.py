@@ -0,0 +1,279 @@
+"""Ollama client for local models through the native ``/api/chat`` API.
+
+Messages and options are translated from the OpenAI format used by the rest
+of the package: ``max_tokens`` becomes ``options.num_predict``, sampling
+parameters go to ``options``, images are sent as base64 ``images`` and
+``response_format="json_object"`` as ``format: "json"``. Streams are NDJSON,
+one response object per line, and are decoded into the same streaming
+deltas as for any other provider::
+
+    client = create_client(ModelConfig(provider="ollama", model="llama3.1"))
+
+No API key is needed; ``cfg.api_key`` is sent as a bearer token for Ollama
+servers behind an authenticating proxy.
+"""
+
+from __future__ import annotations
+
+import json
+from collections.abc import AsyncGenerator, Generator
+from datetime import datetime, timezone
+from typing import Any
+
+import httpx
+
+from ..documents import to_openai_file_parts
+from ..message import Choice, Message, ModelResponse, StreamingChoice, StreamingModelResponse, Usage
+from ..tool_content import _call_arguments, _image_url, _part_text, to_openai_tool_messages
+from .base import RunParams
+from .openai_wire import (
+    _MALFORMED_DATA_ERRORS,
+    DeltaTextAssembler,
+    MalformedResponseError,
+    OpenAIWireClient,
+    OpenAIWireMixin,
+    SyncOpenAIWireClient,
+    ToolCallIndexer,
+)
+
+DEFAULT_API_URL = "http://localhost:11434/api/chat"
+
+# RunParams field -> Ollama option
+OPTION_NAMES = {"temperature": "temperature", "top_p": "top_p", "top_k": "top_k", "seed": "seed"}
+
+
+def _created(value: Any) -> int:
+    """Return Ollama's ``created_at`` (RFC 3339 with nanoseconds) as Unix seconds, 0 if missing."""
+    try:
+        return int(datetime.strptime(value[:19], "%Y-%m-%dT%H:%M:%S").replace(tzinfo=timezone.utc).timestamp())
+    except (TypeError, ValueError):
+        return 0
+
+
+def to_ollama_messages(messages: list[dict[str, Any]]) -> list[dict[str, Any]]:
+    """Convert OpenAI-format ``messages`` into ``/api/chat`` messages.
+
+    Content parts are flattened into ``content`` text and base64 ``images``;
+    tool call arguments are sent as objects and tool results name their tool.
+
+    Raises:
+        ValueError: For image URLs, which Ollama cannot fetch.
+    """
+    messages = to_openai_file_parts(to_openai_tool_messages(messages))
+    names = {
+        call["id"]: call["function"]["name"]
+        for message in messages
+        for call in message.get("tool_calls") or []
+        if call.get("id")
+    }
+    result = []
+    for message in messages:
+        content = message.get("content")
+        item: dict[str, Any] = {"role": message["role"], "content": content if isinstance(content, str) else ""}
+        if isinstance(content, list):
+            texts, images = [], []
+            for part in content:
+                if (url := _image_url(part)) is not None:
+                    if not (url.startswith("data:") and ";base64," in url):
+                        raise ValueError(f"Ollama only accepts base64 images, got {url[:100]}")
+                    images.append(url.split(";base64,", 1)[1])
+                elif (text := _part_text(part)) is not None:
+                    texts.append(text)
+            item["content"] = "\n".join(texts)
+            if images:
+                item["images"] = images
+        if message.get("tool_calls"):
+            item["tool_calls"] = [
+                {
+                    "function": {
+                        "name": call["function"]["name"],
+                        "arguments": _call_arguments(call),
+                    }
+                }
+                for call in message["tool_calls"]
+            ]
+        if message["role"] == "tool" and message.get("tool_call_id") in names:
+            item["tool_name"] = names[message["tool_call_id"]]
+        result.append(item)
+    return result
+
+
+class OllamaWireMixin(OpenAIWireMixin):
+    """Ollama differences: ``/api/chat`` request/response shapes and NDJSON streams.
+
+    ``cfg.api_url`` is the ``/api/chat`` endpoint, ``http://localhost:11434/api/chat``
+    by default.
+    """
+
+    default_api_url = DEFAULT_API_URL
+    error_label = "Ollama API"
+
+    def _embeddings_request(self, body: dict[str, Any]) -> httpx.Request:
+        raise NotImplementedError("Embeddings are not supported for Ollama")
+
+    def _upload_request(self, filename: str, data: bytes, media_type: str, purpose: str) -> httpx.Request:
+        raise NotImplementedError("File uploads are not supported for Ollama")
+
+    def _build_request_data(self, params: RunParams) -> dict[str, Any]:
+        """构建 Ollama /api/chat 请求数据。"""
+        messages = []
+        for msg in params.messages:
+            item: dict[str, Any] = {"role": msg.role, "content": msg.content}
+            if msg.tool_calls:
+                item["tool_calls"] = msg.tool_calls
+            if msg.tool_call_id:
+                item["tool_call_id"] = msg.tool_call_id
+            messages.append(item)
+        request_data: dict[str, Any] = {
+            "model": self.cfg.model,
+            "messages": to_ollama_messages(messages),
+            "stream": params.stream,
+        }
+
+        options: dict[str, Any] = {}
+        defaults = {"temperature": self.cfg.temperature, "top_p": self.cfg.top_p}
+        for field, option in OPTION_NAMES.items():
+            value = getattr(params, field)
+            value = value if value is not None else defaults.get(field)
+            if value is not None:
+                options[option] = value
+        max_tokens = params.max_tokens if params.max_tokens is not None else self.cfg.max_tokens
+        if max_tokens is not None:
+            options["num_predict"] = max_tokens
+        if params.stop:
+            options["stop"] = [params.stop] if isinstance(params.stop, str) else params.stop
+        if options:
+            request_data["options"] = options
+        if params.response_format in ("json_object", "json_schema"):
+            request_data["format"] = "json"
+
+        if params.tool_params:
+            self._add_tool_params(request_data, params.tool_params)
+            # /api/chat 不支持 tool_choice
+            request_data.pop("tool_choice", None)
+
+        request_data.update(params.extra_params)
+        params.trace_context["llm_request"] = request_data
+        return request_data
+
+    @staticmethod
+    def _parse_usage(data: dict[str, Any]) -> Usage:
+        prompt = data.get("prompt_eval_count", 0)
+        completion = data.get("eval_count", 0)
+        return Usage(prompt_tokens=prompt, completion_tokens=completion, total_tokens=prompt + completion)
+
+    @staticmethod
+    def _message_from(data: dict[str, Any], tool_index: Any = None) -> tuple[Message, str | None]:
+        """Return the OpenAI message and finish reason of an ``/api/chat`` response object."""
+        message_data = data.get("message") or {}
+        tool_calls = []
+        for call in message_data.get("tool_calls") or []:
+            function = call["function"]
+            index = tool_index() if tool_index is not None else len(tool_calls)
+            tool_call: dict[str, Any] = {
+                "id": call.get("id") or f"call_{index}",
+                "type": "function",
+                "function": {
+                    "name": function["name"],
+                    "arguments": json.dumps(function.get("arguments") or {}, ensure_ascii=False),
+                },
+            }
+            tool_calls.append({"index": index, **tool_call} if tool_index is not None else tool_call)
+        finish_reason = None
+        if data.get("done"):
+            finish_reason = "tool_calls" if tool_calls else data.get("done_reason") or "stop"
+        message = Message(
+            role=message_data.get("role", "assistant"),
+            content=message_data.get("content") or None,
+            reasoning_content=message_data.get("thinking") or None,
+            tool_calls=tool_calls or None,
+        )
+        return message, finish_reason
+
+    def _parse_stream_chunk(
+        self,
+        data_str: str,
+        assembler: DeltaTextAssembler | None = None,
+        native: ToolCallIndexer | None = None,
+    ) -> StreamingModelResponse | None:
+        """把一行 NDJSON 解析为流式响应。"""
+        try:
+            data = json.loads(data_str)
+        except (json.JSONDecodeError, RecursionError):
+            return None
+        if not isinstance(data, dict):
+            return None
+        if "error" in data:
+            return self._create_error_response(str(data["error"]), is_streaming=True)
+        native = native or ToolCallIndexer()
+        try:
+            message, finish_reason = self._message_from(data, native.next_index)
+            if finish_reason == "stop" and native.count:
+                # 工具调用在此前的行中已发出
+                finish_reason = "tool_calls"
+            return StreamingModelResponse(
+                id="",
+                object="chat.completion.chunk",
+                created=_created(data.get("created_at")),
+                model=data.get("model", self.cfg.model),
+                choices=[StreamingChoice(index=0, delta=message, finish_reason=finish_reason)],
+                usage=self._parse_usage(data) if data.get("done") else None,
+            )
+        except _MALFORMED_DATA_ERRORS as e:
+            raise MalformedResponseError("stream", f"Malformed stream chunk from {self.error_label}: {e}") from e
+
+    def _native_stream_decoder(self) -> ToolCallIndexer:
+        return ToolCallIndexer()
+
+    def _process_non_streaming_response(self, response: httpx.Response) -> ModelResponse:
+        """处理 /api/chat 非流式响应。
+
+        Raises:
+            MalformedResponseError: If the body is not a valid response.
+        """
+        try:
+            data = response.json()
+        except (ValueError, RecursionError) as e:
+            raise MalformedResponseError("json", f"{self.error_label} returned invalid JSON: {e}") from e
+        if not isinstance(data, dict) or not isinstance(data.get("message"), dict):
+            raise MalformedResponseError("json", f"Unexpected response format: {str(data)[:500]}")
+        try:
+            message, finish_reason = self._message_from(data)
+            return ModelResponse(
+                id="",
+                object="chat.completion",
+                created=_created(data.get("created_at")),
+                model=data.get("model", self.cfg.model),
+                choices=[Choice(index=0, message=message, finish_reason=finish_reason)],
+                usage=self._parse_usage(data),
+            )
+        except _MALFORMED_DATA_ERRORS as e:
+            raise MalformedResponseError("json", f"Malformed response from {self.error_label}: {e}") from e
+
+
+class OllamaClient(OllamaWireMixin, OpenAIWireClient):
+    """Ollama API client."""
+
+    provider = "ollama"
+
+    async def _aprocess_streaming_response(self, response) -> AsyncGenerator[StreamingModelResponse, None]:
+        """处理 NDJSON 流式响应。"""
+        native = self._native_stream_decoder()
+        async for line in response.aiter_lines():
+            message = self._parse_stream_chunk(line, native=native)
+            if message is not None:
+                yield message
+
+
+class SyncOllamaClient(OllamaWireMixin, SyncOpenAIWireClient):
+    """Synchronous Ollama API client."""
+
+    provider = "ollama"
+
+    def _process_streaming_response(self, response) -> Generator[StreamingModelResponse, None, None]:
+        """处理 NDJSON 流式响应。"""
+        native = self._native_stream_decoder()
+        for line in response.iter_lines():
+            message = self._parse_stream_chunk(line, native=native)
+            if message is not None:
+                yield message
@@ -0,0 +1,843 @@
+"""Wire format shared by OpenAI-compatible chat completion clients.
+
+:class:`OpenAIWireMixin` builds requests, parses errors and decodes SSE
+streams for the ``/chat/completions`` protocol. Concrete clients only pick
+the transport (sync or async) and, where the provider differs, the auth
+header and URL shape::
+
+    class MyGatewayClient(OpenAIWireClient):
+        provider = "my-gateway"
+        default_api_url = "https://gateway.example.com/v1/chat/completions"
+        auth_header = "X-Api-Key"
+        auth_scheme = None
+"""
+
+from __future__ import annotations
+
+import codecs
+import json
+import time
+import traceback
+from collections.abc import AsyncGenerator, Generator
+from typing import Any, Literal, Union
+
+import httpx
+from pydantic import ValidationError
+
+from ..documents import DEFAULT_MAX_INLINE_FILE_BYTES, extract_citations, file_part, to_openai_file_parts
+from ..message import Choice, Message, ModelResponse, StreamingChoice, StreamingModelResponse, Usage
+from ..tool_content import to_openai_tool_messages
+from .anthropic_stream import ANTHROPIC_EVENT_TYPES, AnthropicStreamDecoder
+from .base import ModelClient, RateLimitHeadroom, RunParams, SyncModelClient, build_extra_headers, log_sampled_request
+from .endpoints import EndpointFailover
+from .strictness import UnknownResponseFieldError, check_unknown_fields, unknown_fields
+from .transport import encode_json_body, http_timeout
+
+# Models that take ``max_completion_tokens`` and reject ``top_p``.
+REASONING_MODELS = ["o4-mini", "gpt-5", "gpt-5-mini", "gpt-5-nano"]
+
+SSE_DONE = "[DONE]"
+
+# Response fields mapped onto ModelResponse/StreamingModelResponse; anything
+# else ends up in ``response.extra`` (see :mod:`.strictness`).
+RESPONSE_FIELDS = {"id", "object", "created", "model", "choices", "usage", "system_fingerprint"}
+CHOICE_FIELDS = {"index", "message", "delta", "finish_reason", "logprobs"}
+MESSAGE_FIELDS = {"role", "content", "reasoning_content", "tool_calls", "citations"}
+
+
+class DeadlineExceededError(TimeoutError):
+    """Raised when a call runs past ``cfg.timeout.deadline_s``, see :class:`TimeoutConfig`."""
+
+    def __init__(self, deadline_s: float) -> None:
+        super().__init__(f"deadline of {deadline_s:g}s exceeded")
+
+
+class MalformedResponseError(ValueError):
+    """Raised when a provider response or stream chunk is not valid chat completions data.
+
+    ``kind`` is ``"stream"`` for an SSE chunk and ``"json"`` for a complete
+    response body. Clients turn it into an error response with code
+    ``malformed_stream`` or ``malformed_response``.
+    """
+
+    def __init__(self, kind: Literal["stream", "json"], message: str) -> None:
+        self.kind = kind
+        super().__init__(message)
+
+    @property
+    def code(self) -> str:
+        return "malformed_stream" if self.kind == "stream" else "malformed_response"
+
+
+# what malformed provider data raises while it is mapped onto our models
+_MALFORMED_DATA_ERRORS = (KeyError, IndexError, TypeError, AttributeError, ValidationError, RecursionError)
+
+
+class SSEDecoder:
+    """Split a streamed body into the payloads of SSE ``data:`` lines.
+
+    Chunks may be ``bytes``; they are decoded incrementally so a multi-byte
+    UTF-8 character split across network chunks is never emitted broken.
+    Lines may end in ``\n``, ``\r\n`` or ``\r``. Comment lines (``: ping``)
+    and ``event:``/``id:``/``retry:`` fields, which gateways send as
+    keep-alives during long generations, are skipped.
+    """
+
+    def __init__(self) -> None:
+        self._buffer = ""
+        self._utf8 = codecs.getincrementaldecoder("utf-8")(errors="replace")
+
+    def feed(self, chunk: bytes | str) -> list[str]:
+        """Add ``chunk`` and return the complete ``data:`` payloads it finished."""
+        if isinstance(chunk, bytes):
+            chunk = self._utf8.decode(chunk)
+        self._buffer += chunk
+        # a trailing "\r" may be the first half of "\r\n"; wait for the next chunk
+        complete, held = (self._buffer[:-1], "\r") if self._buffer.endswith("\r") else (self._buffer, "")
+        lines = complete.replace("\r\n", "\n").replace("\r", "\n").split("\n")
+        self._buffer = lines[-1] + held  # 保留可能不完整的最后一行
+        payloads = []
+        for line in lines[:-1]:
+            line = line.strip()
+            if line.startswith("data:"):
+                payloads.append(line[5:].strip())
+        return payloads
+
+
+class DeltaTextAssembler:
+    """Keep UTF-16 surrogate pairs together across streamed text deltas.
+
+    Some providers split an emoji's ``\\ud83d\\ude00`` escape pair over two
+    events, which decodes to two lone surrogates. A trailing high surrogate is
+    held back until the next delta of the same ``key`` completes the pair.
+    """
+
+    def __init__(self) -> None:
+        self._pending: dict[Any, str] = {}
+
+    def push(self, key: Any, text: str | None) -> str | None:
+        """Return the printable part of ``text`` for the stream ``key``."""
+        if not text:
+            return text
+        text = self._pending.pop(key, "") + text
+        if "\ud800" <= text[-1] <= "\udbff":
+            self._pending[key] = text[-1]
+            text = text[:-1]
+        if any("\ud800" <= ch <= "\udfff" for ch in text):
+            text = text.encode("utf-16", "surrogatepass").decode("utf-16", "replace")
+        return text
+
+
+class ToolCallIndexer:
+    """Numbers the tool calls of one stream for providers that send each call whole."""
+
+    def __init__(self) -> None:
+        self.count = 0
+
+    def next_index(self) -> int:
+        self.count += 1
+        return self.count - 1
+
+
+class OpenAIWireMixin:
+    """Request building, error parsing and stream decoding for OpenAI-compatible APIs.
+
+    ``default_api_url`` is used when ``cfg.api_url`` is unset; override
+    :meth:`_request_url` for URL shapes that depend on the model. The API key is
+    sent as ``{auth_header}: {auth_scheme} {key}``, or the bare key when
+    ``auth_scheme`` is ``None``.
+    """
+
+    default_api_url = "https://api.openai.com/v1/chat/completions"
+    auth_header = "Authorization"
+    auth_scheme: str | None = "Bearer"
+    error_label = "OpenAI API"
+    # send ``document`` content parts as is (gateways in front of Anthropic);
+    # ``False`` converts base64 documents into OpenAI ``file`` parts
+    document_blocks = True
+
+    def _request_url(self, endpoint: str | None = None) -> str:
+        """Return the chat completions endpoint, at ``endpoint`` instead of the one in use if given."""
+        return endpoint or self._endpoint() or self.default_api_url
+
+    def _endpoint_failover(self) -> EndpointFailover | None:
+        """Return the failover state of ``cfg.failover_api_urls``, ``None`` without alternatives."""
+        failover = getattr(self, "_failover", None)
+        if failover is None and self.cfg.failover_api_urls:
+            primary = self.cfg.api_url or self.default_api_url
+            failover = self._failover = EndpointFailover(
+                primary, self.cfg.failover_api_urls, self.cfg.failback_probe_s
+            )
+        return failover
+
+    def _endpoint(self) -> str | None:
+        """Return ``cfg.api_url``, or the failover endpoint in use."""
+        failover = self._endpoint_failover()
+        return failover.active if failover is not None else self.cfg.api_url
+
+    def _files_url(self) -> str:
+        """Return the Files API endpoint next to the chat completions endpoint."""
+        url = self._request_url()
+        base, sep, query = url.partition("?")
+        return base.removesuffix("/chat/completions") + "/files" + sep + query
+
+    def _embeddings_url(self) -> str:
+        """Return the embeddings endpoint next to the chat completions endpoint."""
+        url = self._request_url()
+        base, sep, query = url.partition("?")
+        return base.removesuffix("/chat/completions") + "/embeddings" + sep + query
+
+    def _embeddings_request(self, body: dict[str, Any]) -> httpx.Request:
+        return self._client.build_request(
+            "POST", self._embeddings_url(), headers=self._build_headers(), json={**body, "model": self.cfg.model}
+        )
+
+    def _upload_request(self, filename: str, data: bytes, media_type: str, purpose: str) -> httpx.Request:
+        headers = self._build_headers()
+        headers.pop("Content-Type")  # multipart boundary is set by httpx
+        request = self._client.build_request(
+            "POST",
+            self._files_url(),
+            headers=headers,
+            data={"purpose": purpose},
+            files={"file": (filename, data, media_type)},
+        )
+        # 请求日志钩子需要读取 body，multipart 流在此一次性编码
+        request.read()
+        return request
+
+    def _inline_limit(self) -> int:
+        limit = self.cfg.max_inline_file_bytes
+        return DEFAULT_MAX_INLINE_FILE_BYTES if limit is None else limit
+
+    @staticmethod
+    def _uploaded_file_id(response: httpx.Response) -> str:
+        response.raise_for_status()
+        return response.json()["id"]
+
+    def _url_bases(self, endpoint: str) -> list[str]:
+        """Return the URL prefixes of requests to ``endpoint``: the chat completions base and the endpoint itself."""
+        chat = self._request_url(endpoint).partition("?")[0].removesuffix("/chat/completions")
+        return [chat, endpoint.rstrip("/")]
+
+    def _retargeted_url(self, request: httpx.Request, endpoint: str) -> str:
+        """Return the URL of ``request`` with its endpoint replaced by ``endpoint``.
+
+        Only the base is swapped; the path after it and the query are kept, so
+        chat, embeddings and file requests each keep their own endpoint.
+        """
+        url = str(request.url)
+        failover = self._endpoint_failover()
+        sources = failover.urls if failover is not None else [self._request_url()]
+        best: tuple[str, str] | None = None
+        for source in sources:
+            for old, new in zip(self._url_bases(source), self._url_bases(endpoint)):
+                at_boundary = url[len(old) : len(old) + 1] in ("", "/", "?", ":")
+                if url.startswith(old) and at_boundary and (best is None or len(old) > len(best[0])):
+                    best = (old, new)
+        return url if best is None else best[1] + url[len(best[0]) :]
+
+    def _retarget(self, request: httpx.Request, endpoint: str) -> httpx.Request:
+        """Return ``request`` sent to ``endpoint`` instead, see :meth:`_endpoint_failover`."""
+        url = self._retargeted_url(request, endpoint)
+        if str(request.url) == url:
+            return request
+        headers = [(k, v) for k, v in request.headers.multi_items() if k.lower() != "host"]
+        # extensions carry the per-request timeout of ``_apply_timeout``
+        return self._client.build_request(
+            request.method, url, headers=headers, content=request.content, extensions=request.extensions
+        )
+
+    def _build_request(self, params: RunParams) -> httpx.Request:
+        """构建请求，流式与非流式调用共用，保证请求头一致。"""
+        request_data = self._build_request_data(params)
+        self._logger.info(request_data)
+        log_sampled_request(self.cfg, request_data)
+        body, body_headers = encode_json_body(self.cfg, request_data)
+        return self._client.build_request(
+            "POST",
+            self._request_url(),
+            headers={**self._build_headers(params), **body_headers},
+            content=body,
+        )
+
+    def _note_rate_limit(self, response: httpx.Response) -> None:
+        """记录响应头中的限流余量。"""
+        self.rate_limit = RateLimitHeadroom.from_headers(response.headers) or self.rate_limit
+
+    def _build_headers(self, params: RunParams | None = None) -> dict[str, str]:
+        """构建请求头。"""
+        headers = {
+            "Content-Type": "application/json",
+        }
+
+        if self.cfg.api_key:
+            scheme = f"{self.auth_scheme} " if self.auth_scheme else ""
+            headers[self.auth_header] = f"{scheme}{self.cfg.api_key}"
+        if self.cfg.organization:
+            headers["OpenAI-Organization"] = self.cfg.organization
+        headers.update(build_extra_headers(self.cfg, params))
+
+        return headers
+
+    def _build_request_data(self, params: RunParams) -> dict[str, Any]:
+        """构建OpenAI API请求数据。"""
+        # 转换消息格式
+        messages = []
+        for msg in params.messages:
+            openai_msg = {
+                "role": msg.role,
+                "content": msg.content
+            }
+
+            # 添加工具调用字段
+            if msg.tool_calls:
+                openai_msg["tool_calls"] = msg.tool_calls
+
+            # 添加工具调用ID字段（用于工具结果消息）
+            if msg.tool_call_id:
+                openai_msg["tool_call_id"] = msg.tool_call_id
+
+            messages.append(openai_msg)
+
+        for item in messages:
+            if item.get("role") == "assistant" and "tool_calls" in item and item.get("content") == "":
+                item["content"] = None
+        # tool 消息只能包含文本，结构化内容（json、图片）在此转换
+        messages = to_openai_tool_messages(messages)
+        if not self.document_blocks:
+            messages = to_openai_file_parts(messages)
+        # 基础请求数据
+        request_data = {
+            "model": self.cfg.model,
+            "messages": messages,
+            "stream": params.stream,
+        }
+
+        if params.stream:
+            request_data["stream_options"] = {
+                "include_usage": True,
+            }
+
+        # 添加可选参数
+        if params.temperature is not None:
+            request_data["temperature"] = params.temperature
+        elif self.cfg.temperature is not None:
+            request_data["temperature"] = self.cfg.temperature
+
+        if params.top_p is not None:
+            request_data["top_p"] = params.top_p
+        elif self.cfg.top_p is not None:
+            request_data["top_p"] = self.cfg.top_p
+
+        max_tokens = params.max_tokens if params.max_tokens is not None else self.cfg.max_tokens
+        if max_tokens is not None:
+            if request_data.get("model", "") in REASONING_MODELS:
+                request_data["max_completion_tokens"] = max_tokens
+            else:
+                request_data["max_tokens"] = max_tokens
+
+        if params.stop:
+            request_data["stop"] = params.stop
+
+        if params.n is not None:
+            request_data["n"] = params.n
+
+        if params.seed is not None:
+            request_data["seed"] = params.seed
+
+        if params.logit_bias:
+            request_data["logit_bias"] = params.logit_bias
+
+        if params.response_format:
+            request_data["response_format"] = {"type": params.response_format}
+
+        if params.user_id:
+            request_data["user"] = params.user_id
+
+        # 处理工具参数
+        if params.tool_params:
+            self._add_tool_params(request_data, params.tool_params)
+
+        # 添加额外参数
+        request_data.update(params.extra_params)
+        if self.cfg.model in REASONING_MODELS:
+            request_data.pop("top_p", None)
+        params.trace_context["llm_request"] = request_data
+        return request_data
+
+    def _add_tool_params(self, request_data: dict[str, Any], tool_params) -> None:
+        """添加工具参数到请求数据。"""
+        if not tool_params or not tool_params.tools:
+            return
+
+        # 转换工具规范为OpenAI格式
+        request_data["tools"] = [
+            {
+                "type": "function",
+                "function": {
+                    "name": tool.name,
+                    "description": tool.description,
+                    "parameters": tool.parameters
+                }
+            }
+            for tool in tool_params.tools
+        ]
+
+        # 设置工具选择策略
+        if tool_params.choice == "auto":
+            request_data["tool_choice"] = "auto"
+        elif tool_params.choice == "none":
+            request_data["tool_choice"] = "none"
+        elif tool_params.choice == "required":
+            request_data["tool_choice"] = "required"
+        elif isinstance(tool_params.choice, str) and tool_params.choice.startswith("function:"):
+            # 指定特定函数
+            function_name = tool_params.choice[9:]  # 移除 "function:" 前缀
+            request_data["tool_choice"] = {
+                "type": "function",
+                "function": {"name": function_name}
+            }
+        elif tool_params.choice:
+            # 如果choice是具体的工具名称
+            request_data["tool_choice"] = {
+                "type": "function",
+                "function": {"name": tool_params.choice}
+            }
+
+    def _create_error_response(
+        self,
+        error_message: str,
+        is_streaming: bool = False,
+        status_code: int | None = None,
+        retry_after: str | None = None,
+        code: str | None = None,
+    ) -> Union[ModelResponse, StreamingModelResponse]:
+        """创建错误响应"""
+        # 尝试解析OpenAI标准错误格式
+        error_object = None
+        try:
+            if error_message.strip().startswith('{'):
+                parsed_error = json.loads(error_message)
+                if "error" in parsed_error:
+                    error_object = parsed_error["error"]
+        except json.JSONDecodeError:
+            pass
+        if error_object is None:
+            # 包装成标准格式
+            error_object = {
+                "message": error_message,
+                "type": "api_error",
+                "code": "unknown_error"
+            }
+
+        # 附加重试判断所需的信息
+        if code:
+            error_object["code"] = code
+        if status_code is not None:
+            error_object["status_code"] = status_code
+        if retry_after:
+            error_object["retry_after"] = retry_after
+
+        # 根据请求类型创建相应的错误响应
+        if is_streaming:
+            return StreamingModelResponse(error=error_object)
+        else:
+            return ModelResponse(error=error_object)
+
+    def _apply_timeout(self, request: httpx.Request, params: RunParams) -> None:
+        """Apply ``cfg.timeout`` to ``request``, capped by the time left until the call's deadline.
+
+        Raises:
+            DeadlineExceededError: If the deadline has already passed.
+        """
+        if self.cfg.timeout is None:
+            return
+        self._check_deadline(params)
+        deadline = params.trace_context.get("deadline")
+        remaining = deadline - time.perf_counter() if deadline is not None else None
+        # 按请求设置，共享的 HTTP 客户端也生效
+        request.extensions["timeout"] = http_timeout(self.cfg, remaining).as_dict()
+
+    def _check_deadline(self, params: RunParams) -> None:
+        deadline = params.trace_context.get("deadline")
+        if deadline is not None and time.perf_counter() >= deadline:
+            raise DeadlineExceededError(self.cfg.timeout.deadline_s)
+
+    def _timed_error(
+        self, exc: Exception, is_streaming: bool, started: float
+    ) -> Union[ModelResponse, StreamingModelResponse]:
+        """Return the error response of ``exc``, with ``elapsed_s`` for timeouts."""
+        response = self._error_from_exception(exc, is_streaming)
+        if response.error and response.error.get("code") in ("timeout", "deadline_exceeded"):
+            response.error["elapsed_s"] = round(time.perf_counter() - started, 3)
+        return response
+
+    def _error_from_exception(
+        self, exc: Exception, is_streaming: bool
+    ) -> Union[ModelResponse, StreamingModelResponse]:
+        """把请求过程中的异常转换为错误响应。"""
+        if isinstance(exc, httpx.HTTPStatusError):
+            # HTTP错误（4xx, 5xx）
+            error_detail = self._parse_error_body(exc.response)
+            self._logger.error(f"{self.error_label} HTTP error: {error_detail}")
+            return self._create_error_response(
+                error_detail,
+                is_streaming=is_streaming,
+                status_code=exc.response.status_code,
+                retry_after=exc.response.headers.get("retry-after"),
+            )
+
+        if isinstance(exc, MalformedResponseError):
+            self._logger.error(str(exc))
+            return self._create_error_response(str(exc), is_streaming=is_streaming, code=exc.code)
+
+        if isinstance(exc, UnknownResponseFieldError):
+            self._logger.error(str(exc))
+            return self._create_error_response(str(exc), is_streaming=is_streaming, code="unknown_response_field")
+
+        if isinstance(exc, DeadlineExceededError):
+            self._logger.error(f"{self.error_label} call: {exc}")
+            return self._create_error_response(str(exc), is_streaming=is_streaming, code="deadline_exceeded")
+
+        traceback.print_exc()
+        if isinstance(exc, httpx.RequestError):
+            # 网络连接错误
+            error_msg = f"Network error: {str(exc)}"
+            self._logger.error(error_msg)
+            return self._create_error_response(
+                error_msg,
+                is_streaming=is_streaming,
+                code="timeout" if isinstance(exc, httpx.TimeoutException) else "network_error",
+            )
+
+        # 其他错误
+        error_msg = f"Unexpected error: {str(exc)}"
+        self._logger.error(error_msg)
+        return self._create_error_response(error_msg, is_streaming=is_streaming)
+
+    @staticmethod
+    def _parse_error_body(response: httpx.Response) -> str:
+        """从错误响应体中提取错误信息。"""
+        try:
+            if not response.content:
+                return f"HTTP {response.status_code}"
+            error_data = response.json()
+            if "error" in error_data:
+                return error_data["error"].get("message", str(error_data["error"]))
+            return str(error_data)
+        except Exception:
+            return f"HTTP {response.status_code}: {response.text}"
+
+    def _parse_stream_chunk(
+        self,
+        data_str: str,
+        assembler: DeltaTextAssembler | None = None,
+        native: AnthropicStreamDecoder | None = None,
+    ) -> StreamingModelResponse | None:
+        """把一个 SSE ``data:`` 负载解析为流式响应，无内容时返回 ``None``。
+
+        Anthropic stream events are translated by ``native`` when given, see
+        :meth:`_native_stream_decoder`.
+
+        Raises:
+            MalformedResponseError: If the payload is JSON but not a valid chunk.
+        """
+        try:
+            data = json.loads(data_str)
+        except (json.JSONDecodeError, RecursionError):
+            # 忽略无效的JSON行
+            return None
+        if native is not None and isinstance(data, dict) and data.get("type") in ANTHROPIC_EVENT_TYPES:
+            try:
+                return native.decode(data)
+            except _MALFORMED_DATA_ERRORS as e:
+                raise MalformedResponseError("stream", f"Malformed stream event from {self.error_label}: {e}") from e
+        # 网关保活事件（如 {"type": "ping"} 或非对象负载）不含 choices
+        if not isinstance(data, dict) or not data.get("choices"):
+            return None
+        try:
+            return self._stream_chunk_from(data, assembler)
+        except _MALFORMED_DATA_ERRORS as e:
+            raise MalformedResponseError("stream", f"Malformed stream chunk from {self.error_label}: {e}") from e
+
+    def _native_stream_decoder(self) -> Any:
+        """Return the state kept across the chunks of one stream for :meth:`_parse_stream_chunk`."""
+        return AnthropicStreamDecoder(self.cfg.model)
+
+    def _stream_chunk_from(
+        self, data: dict[str, Any], assembler: DeltaTextAssembler | None
+    ) -> StreamingModelResponse:
+        # n > 1 时每个 chunk 可能带多个 choice，按 index 区分
+        choices = [self._streaming_choice_from(choice_data, assembler) for choice_data in data["choices"]]
+
+        return StreamingModelResponse(
+            id=data.get("id", ""),
+            object=data.get("object", "chat.completion.chunk"),
+            created=data.get("created", 0),
+            model=data.get("model", self.cfg.model),
+            choices=choices,
+            system_fingerprint=data.get("system_fingerprint"),
+            usage=self._parse_usage(data) if "usage" in data else None,
+            extra=self._unknown_fields(data, "delta"),
+        )
+
+    @staticmethod
+    def _streaming_choice_from(
+        choice_data: dict[str, Any], assembler: DeltaTextAssembler | None
+    ) -> StreamingChoice:
+        delta_data = choice_data.get("delta", {})
+        content = delta_data.get("content", "")
+        reasoning_content = delta_data.get("reasoning_content")
+        index = choice_data.get("index", 0)
+        if assembler is not None:
+            content = assembler.push(("content", index), content)
+            reasoning_content = assembler.push(("reasoning", index), reasoning_content)
+
+        # 创建Message对象作为delta
+        delta_message = Message(
+            role=delta_data.get("role", "assistant"),
+            content=content if content else None,
+            reasoning_content=reasoning_content,
+            tool_calls=delta_data.get("tool_calls"),
+            citations=extract_citations(delta_data),
+        )
+
+        return StreamingChoice(
+            index=index,
+            delta=delta_message,
+            finish_reason=choice_data.get("finish_reason"),
+            logprobs=choice_data.get("logprobs"),
+        )
+
+    def _unknown_fields(self, data: dict[str, Any], message_key: str) -> dict[str, Any]:
+        """收集未映射的响应字段，并按 ``cfg.response_strictness`` 处理。"""
+        choice_data = data["choices"][0]
+        extra = unknown_fields(data, RESPONSE_FIELDS)
+        extra.update(unknown_fields(choice_data, CHOICE_FIELDS, "choices.0."))
+        extra.update(unknown_fields(choice_data.get(message_key) or {}, MESSAGE_FIELDS, f"choices.0.{message_key}."))
+        return check_unknown_fields(self.cfg.provider, extra, self.cfg.response_strictness)
+
+    @staticmethod
+    def _parse_usage(data: dict[str, Any]) -> Usage:
+        usage_data = data.get("usage") or {}
+        return Usage(
+            prompt_tokens=usage_data.get("prompt_tokens", 0),
+            completion_tokens=usage_data.get("completion_tokens", 0),
+            total_tokens=usage_data.get("total_tokens", 0)
+        )
+
+    def _process_non_streaming_response(self, response: httpx.Response) -> ModelResponse:
+        """处理非流式响应。
+
+        Raises:
+            MalformedResponseError: If the body is not a valid chat completion.
+        """
+        try:
+            data = response.json()
+        except (ValueError, RecursionError) as e:
+            raise MalformedResponseError("json", f"{self.error_label} returned invalid JSON: {e}") from e
+        if not isinstance(data, dict) or not data.get("choices"):
+            raise MalformedResponseError("json", f"Unexpected response format: {str(data)[:500]}")
+        try:
+            return self._response_from(data)
+        except _MALFORMED_DATA_ERRORS as e:
+            raise MalformedResponseError("json", f"Malformed response from {self.error_label}: {e}") from e
+
+    def _response_from(self, data: dict[str, Any]) -> ModelResponse:
+        # 请求带 n 时会返回多个 choice，全部保留
+        choices = [self._choice_from(choice_data) for choice_data in data["choices"]]
+
+        return ModelResponse(
+            id=data.get("id", ""),
+            object=data.get("object", "chat.completion"),
+            created=data.get("created", 0),
+            model=data.get("model", self.cfg.model),
+            choices=choices,
+            usage=self._parse_usage(data) if "usage" in data else None,
+            system_fingerprint=data.get("system_fingerprint"),
+            extra=self._unknown_fields(data, "message"),
+        )
+
+    @staticmethod
+    def _choice_from(choice_data: dict[str, Any]) -> Choice:
+        message_data = choice_data["message"]
+
+        message = Message(
+            role=message_data["role"],
+            content=message_data.get("content"),
+            reasoning_content=message_data.get("reasoning_content"),
+            tool_calls=message_data.get("tool_calls"),
+            citations=extract_citations(message_data),
+        )
+
+        return Choice(
+            index=choice_data.get("index", 0),
+            message=message,
+            finish_reason=choice_data.get("finish_reason"),
+            logprobs=choice_data.get("logprobs"),
+        )
+
+
+class OpenAIWireClient(OpenAIWireMixin, ModelClient):
+    """Async client for any OpenAI-compatible ``/chat/completions`` endpoint."""
+
+    async def _run(self, params: RunParams) -> AsyncGenerator[Union[ModelResponse, StreamingModelResponse], None]:
+        """Execute the chat completions call."""
+        # 流式与非流式请求共用同一个请求构建逻辑
+        request = self._build_request(params)
+        started = time.perf_counter()
+        try:
+            self._apply_timeout(request, params)
+            if params.stream:
+                response = await self._asend(request, stream=True)
+                self._note_rate_limit(response)
+                try:
+                    if response.is_error:
+                        # 读取错误响应体，便于解析错误信息
+                        await response.aread()
+                    response.raise_for_status()
+                    async for message in self._aprocess_streaming_response(response):
+                        self._check_deadline(params)
+                        yield message
+                finally:
+                    await response.aclose()
+            else:
+                response = await self._asend(request)
+                self._note_rate_limit(response)
+                response.raise_for_status()
+                yield self._process_non_streaming_response(response)
+        except Exception as e:
+            yield self._timed_error(e, params.stream, started)
+
+    async def _asend(self, request: httpx.Request, stream: bool = False) -> httpx.Response:
+        """发送请求；DNS/建连失败时切换到 ``cfg.failover_api_urls`` 中的下一个端点。"""
+        failover = self._endpoint_failover()
+        if failover is None:
+            return await self._client.send(request, stream=stream)
+        endpoints = failover.order()
+        for i, endpoint in enumerate(endpoints):
+            try:
+                response = await self._client.send(self._retarget(request, endpoint), stream=stream)
+            except (httpx.ConnectError, httpx.ConnectTimeout) as e:
+                failover.failed(endpoint, e)
+                if i == len(endpoints) - 1:
+                    raise
+                continue
+            failover.succeeded(endpoint)
+            return response
+        raise AssertionError("unreachable")
+
+    async def aembeddings(self, body: dict[str, Any]) -> dict[str, Any]:
+        """Send an OpenAI embeddings request, see :meth:`ModelClient.aembeddings`."""
+        response = await self._asend(self._embeddings_request(body))
+        self._note_rate_limit(response)
+        response.raise_for_status()
+        return response.json()
+
+    async def aupload_file(
+        self, filename: str, data: bytes, *, media_type: str = "application/pdf", purpose: str = "user_data"
+    ) -> str:
+        """Upload ``data`` with the Files API and return the file id."""
+        response = await self._client.send(self._upload_request(filename, data, media_type, purpose))
+        return self._uploaded_file_id(response)
+
+    async def aprepare_file(self, filename: str, data: bytes, *, media_type: str = "application/pdf") -> dict[str, Any]:
+        """Return a ``file`` content part: inline up to ``cfg.max_inline_file_bytes``, uploaded beyond."""
+        if len(data) <= self._inline_limit():
+            return file_part(filename=filename, data=data, media_type=media_type)
+        return file_part(file_id=await self.aupload_file(filename, data, media_type=media_type))
+
+    async def _aprocess_streaming_response(self, response) -> AsyncGenerator[StreamingModelResponse, None]:
+        """处理流式响应。"""
+        decoder = SSEDecoder()
+        assembler = DeltaTextAssembler()
+        native = self._native_stream_decoder()
+        async for chunk in response.aiter_bytes():
+            for payload in decoder.feed(chunk):
+                if payload == SSE_DONE:
+                    return
+                message = self._parse_stream_chunk(payload, assembler, native)
+                if message is not None:
+                    yield message
+
+
+class SyncOpenAIWireClient(OpenAIWireMixin, SyncModelClient):
+    """Sync client for any OpenAI-compatible ``/chat/completions`` endpoint."""
+
+    def _run(self, params: RunParams) -> Generator[Union[ModelResponse, StreamingModelResponse], None, None]:
+        """Execute the chat completions call."""
+        request = self._build_request(params)
+        started = time.perf_counter()
+        try:
+            self._apply_timeout(request, params)
+            if params.stream:
+                response = self._send(request, stream=True)
+                self._note_rate_limit(response)
+                try:
+                    if response.is_error:
+                        response.read()
+                    response.raise_for_status()
+                    for message in self._process_streaming_response(response):
+                        self._check_deadline(params)
+                        yield message
+                finally:
+                    response.close()
+            else:
+                response = self._send(request)
+                self._note_rate_limit(response)
+                response.raise_for_status()
+                yield self._process_non_streaming_response(response)
+        except Exception as e:
+            yield self._timed_error(e, params.stream, started)
+
+    def _send(self, request: httpx.Request, stream: bool = False) -> httpx.Response:
+        """Sync variant of :meth:`OpenAIWireClient._asend`."""
+        failover = self._endpoint_failover()
+        if failover is None:
+            return self._client.send(request, stream=stream)
+        endpoints = failover.order()
+        for i, endpoint in enumerate(endpoints):
+            try:
+                response = self._client.send(self._retarget(request, endpoint), stream=stream)
+            except (httpx.ConnectError, httpx.ConnectTimeout) as e:
+                failover.failed(endpoint, e)
+                if i == len(endpoints) - 1:
+                    raise
+                continue
+            failover.succeeded(endpoint)
+            return response
+        raise AssertionError("unreachable")
+
+    def embeddings(self, body: dict[str, Any]) -> dict[str, Any]:
+        """Sync variant of :meth:`OpenAIWireClient.aembeddings`."""
+        response = self._send(self._embeddings_request(body))
+        self._note_rate_limit(response)
+        response.raise_for_status()
+        return response.json()
+
+    def upload_file(
+        self, filename: str, data: bytes, *, media_type: str = "application/pdf", purpose: str = "user_data"
+    ) -> str:
+        """Upload ``data`` with the Files API and return the file id."""
+        response = self._client.send(self._upload_request(filename, data, media_type, purpose))
+        return self._uploaded_file_id(response)
+
+    def prepare_file(self, filename: str, data: bytes, *, media_type: str = "application/pdf") -> dict[str, Any]:
+        """Return a ``file`` content part: inline up to ``cfg.max_inline_file_bytes``, uploaded beyond."""
+        if len(data) <= self._inline_limit():
+            return file_part(filename=filename, data=data, media_type=media_type)
+        return file_part(file_id=self.upload_file(filename, data, media_type=media_type))
+
+    def _process_streaming_response(self, response) -> Generator[StreamingModelResponse, None, None]:
+        """处理流式响应。"""
+        decoder = SSEDecoder()
+        assembler = DeltaTextAssembler()
+        native = self._native_stream_decoder()
+        for chunk in response.iter_bytes():
+            for payload in decoder.feed(chunk):
+                if payload == SSE_DONE:
+                    return
+                message = self._parse_stream_chunk(payload, assembler, native)
+                if message is not None:
+                    yield message
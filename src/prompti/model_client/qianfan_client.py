@@ -8,8 +8,10 @@ class QianfanClient(OpenAIClient):
     """OpenAI-compatible API client."""
 
     provider = "qianfan"
+    usage_api = False
 
 
 class SyncQianfanClient(SyncOpenAIClient):
     """Synchronous OpenAI-compatible API client."""
-    provider = "qianfan"
\ No newline at end of file
+    provider = "qianfan"
+    usage_api = False
\ No newline at end of file
@@ -0,0 +1,169 @@
+"""Client-side requests and tokens per minute, see :class:`RateLimitConfig`.
+
+Bursts wait locally for the budget instead of running into 429s::
+
+    ModelConfig(
+        provider="openai",
+        model="gpt-4o",
+        rate_limit=RateLimitConfig(requests_per_minute=500, tokens_per_minute=30_000),
+    )
+
+Budgets are sliding one-minute windows kept in memory, shared by all
+clients of the same provider and model in the process. The time spent
+waiting is recorded in ``llm_rate_limit_wait_seconds``, as a
+``rate_limit_wait`` event on the ``llm.call`` span and in
+``params.trace_context["perf_metrics"]["rate_limit_wait"]``.
+"""
+
+from __future__ import annotations
+
+import asyncio
+import threading
+import time
+from collections import deque
+from collections.abc import Callable
+from dataclasses import dataclass
+
+from .types import ModelConfig, RateLimitConfig, RunParams, TokenCounts
+
+__all__ = ["RateLimitWaitError", "RateLimiter", "Reservation", "limiter_for", "request_tokens", "reset_rate_limits"]
+
+WINDOW_S = 60.0
+
+
+class RateLimitWaitError(RuntimeError):
+    """Waiting for the client-side budget would take longer than ``max_wait_s``."""
+
+    def __init__(self, name: str, wait_s: float, max_wait_s: float) -> None:
+        self.retry_after = wait_s
+        super().__init__(f"rate limit of {name} needs a wait of {wait_s:.2f}s, more than max_wait_s={max_wait_s:g}")
+
+
+@dataclass
+class Reservation:
+    """One request counted against the budgets: when it started and its tokens."""
+
+    time: float
+    tokens: int
+
+
+class RateLimiter:
+    """Sliding-window requests and tokens per minute of one provider/model."""
+
+    def __init__(self, config: RateLimitConfig, name: str, *, clock: Callable[[], float] = time.monotonic) -> None:
+        self.config = config
+        self.name = name
+        self._clock = clock
+        self._reservations: deque[Reservation] = deque()
+        self._lock = threading.Lock()
+
+    def delay(self, tokens: int = 0) -> float:
+        """Return the seconds until a request of ``tokens`` fits into the budgets, ``0`` when it fits now."""
+        with self._lock:
+            return self._delay(tokens, self._clock())
+
+    def _delay(self, tokens: int, now: float) -> float:
+        window = self._reservations
+        while window and window[0].time <= now - WINDOW_S:
+            window.popleft()
+        delay = 0.0
+        rpm = self.config.requests_per_minute
+        if rpm is not None and len(window) >= rpm:
+            # a slot frees when the request ``rpm`` places from the newest leaves the window
+            delay = window[len(window) - rpm].time + WINDOW_S - now
+        tpm = self.config.tokens_per_minute
+        excess = sum(r.tokens for r in window) + tokens - tpm if tpm is not None else 0
+        if excess > 0 and window:
+            freed = 0
+            for reservation in window:
+                freed += reservation.tokens
+                if freed >= excess:
+                    break
+            # a request larger than the whole budget waits for an empty window and then runs alone
+            delay = max(delay, reservation.time + WINDOW_S - now)
+        return max(delay, 0.0)
+
+    def _try_acquire(self, tokens: int) -> tuple[Reservation | None, float]:
+        with self._lock:
+            now = self._clock()
+            delay = self._delay(tokens, now)
+            if delay > 0:
+                return None, delay
+            reservation = Reservation(now, tokens)
+            self._reservations.append(reservation)
+            return reservation, 0.0
+
+    def _check_wait(self, waited: float, delay: float) -> None:
+        max_wait = self.config.max_wait_s
+        if max_wait is not None and waited + delay > max_wait:
+            raise RateLimitWaitError(self.name, waited + delay, max_wait)
+
+    async def acquire(self, tokens: int = 0) -> tuple[Reservation, float]:
+        """Wait until a request of ``tokens`` fits, then reserve it.
+
+        Returns:
+            The reservation, to :meth:`settle` with the reported usage, and the seconds waited.
+
+        Raises:
+            RateLimitWaitError: If the wait would exceed ``max_wait_s``.
+        """
+        start = self._clock()
+        waited = 0.0
+        while True:
+            reservation, delay = self._try_acquire(tokens)
+            if reservation is not None:
+                return reservation, waited
+            self._check_wait(waited, delay)
+            await asyncio.sleep(delay)
+            waited = self._clock() - start
+
+    def acquire_sync(self, tokens: int = 0) -> tuple[Reservation, float]:
+        """Blocking variant of :meth:`acquire`."""
+        start = self._clock()
+        waited = 0.0
+        while True:
+            reservation, delay = self._try_acquire(tokens)
+            if reservation is not None:
+                return reservation, waited
+            self._check_wait(waited, delay)
+            time.sleep(delay)
+            waited = self._clock() - start
+
+    def settle(self, reservation: Reservation, tokens: int) -> None:
+        """Replace the estimated tokens of ``reservation`` with the ``tokens`` actually used."""
+        with self._lock:
+            reservation.tokens = tokens
+
+
+_limiters: dict[str, RateLimiter] = {}
+_limiters_lock = threading.Lock()
+
+
+def limiter_for(cfg: ModelConfig) -> RateLimiter | None:
+    """Return the shared limiter of ``cfg``, or ``None`` when it has no budgets."""
+    config = cfg.rate_limit
+    if config is None or (config.requests_per_minute is None and config.tokens_per_minute is None):
+        return None
+    name = cfg.provider if config.scope == "provider" else f"{cfg.provider}/{cfg.model}"
+    with _limiters_lock:
+        limiter = _limiters.get(name)
+        if limiter is None:
+            limiter = _limiters[name] = RateLimiter(config, name)
+        else:
+            # the latest configuration wins, e.g. after a config reload
+            limiter.config = config
+    return limiter
+
+
+def reset_rate_limits() -> None:
+    """Forget all budgets and their windows."""
+    with _limiters_lock:
+        _limiters.clear()
+
+
+def request_tokens(cfg: ModelConfig, params: RunParams) -> int:
+    """Return what a call counts against ``tokens_per_minute``: the estimated prompt plus ``max_tokens``."""
+    if cfg.rate_limit is None or cfg.rate_limit.tokens_per_minute is None:
+        return 0
+    prompt = TokenCounts.estimate(params.messages, params.tool_params).total
+    return prompt + (params.max_tokens or cfg.max_tokens or 0)
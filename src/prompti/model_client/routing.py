@@ -0,0 +1,135 @@
+"""Automatic model selection by capability requirements.
+
+Each candidate :class:`ModelConfig` describes what it supports and costs in
+``capabilities``. :func:`select_model` picks the cheapest (or fastest) one
+that satisfies a request's :class:`RequestRequirements`::
+
+    profiles = {
+        "mini": ModelConfig(provider="openai", model="gpt-4o-mini", capabilities=ModelCapabilities(
+            vision=True, tools=True, json_mode=True, context_window=128000,
+            input_cost_per_1k=0.00015, output_cost_per_1k=0.0006)),
+        "qwen": ModelConfig(provider="litellm", model="qwen-max", capabilities=...),
+    }
+    name = select_model(profiles, RequestRequirements.from_params(params, max_cost_per_1k=0.01))
+
+:meth:`ClientManager.select` does the same over the manager's profiles.
+Every decision is logged on the ``model_client.routing`` logger with the
+reason each rejected candidate did not qualify.
+"""
+
+from __future__ import annotations
+
+import json
+import logging
+from typing import Literal
+
+from pydantic import BaseModel, Field
+
+from ..postprocess import estimate_tokens
+from .types import ModelConfig, RunParams, ToolParams
+
+__all__ = ["NoMatchingModelError", "RequestRequirements", "select_model"]
+
+_logger = logging.getLogger("model_client.routing")
+
+
+class NoMatchingModelError(LookupError):
+    """Raised when no candidate model satisfies the requirements."""
+
+    def __init__(self, rejected: dict[str, str]) -> None:
+        self.rejected = rejected
+        reasons = "; ".join(f"{name}: {reason}" for name, reason in rejected.items()) or "no candidates"
+        super().__init__(f"No model satisfies the request requirements ({reasons})")
+
+
+class RequestRequirements(BaseModel):
+    """What a request needs from the model that serves it."""
+
+    needs_vision: bool = False
+    needs_tools: bool = False
+    needs_json_mode: bool = False
+    # prompt plus completion tokens the context window must hold
+    min_context: int | None = Field(None, ge=1)
+    # upper bound of the blended price, see :attr:`ModelCapabilities.cost_per_1k`
+    max_cost_per_1k: float | None = Field(None, ge=0)
+    # rank qualifying models by price or by ``capabilities.latency_ms``
+    prefer: Literal["cost", "latency"] = "cost"
+
+    @classmethod
+    def from_params(cls, params: RunParams, **overrides) -> RequestRequirements:
+        """Derive the requirements of ``params``: images, tools, JSON mode and an estimated context size."""
+        needs_vision = any(
+            isinstance(m.content, list) and any(p.get("type") == "image_url" for p in m.content)
+            for m in params.messages
+        )
+        tools = params.tool_params.tools if isinstance(params.tool_params, ToolParams) else params.tool_params
+        prompt = "".join(
+            m.content if isinstance(m.content, str) else json.dumps(m.content or "", ensure_ascii=False)
+            for m in params.messages
+        )
+        values = {
+            "needs_vision": needs_vision,
+            "needs_tools": bool(tools),
+            "needs_json_mode": params.response_format in ("json_object", "json_schema"),
+            "min_context": estimate_tokens(prompt) + (params.max_tokens or 0) or None,
+        }
+        values.update(overrides)
+        return cls(**values)
+
+
+def _rejection(cfg: ModelConfig, req: RequestRequirements) -> str | None:
+    caps = cfg.capabilities
+    if caps is None:
+        return "no capabilities configured"
+    if req.needs_vision and not caps.vision:
+        return "no vision"
+    if req.needs_tools and not caps.tools:
+        return "no tool calling"
+    if req.needs_json_mode and not caps.json_mode:
+        return "no JSON mode"
+    if req.min_context is not None and (caps.context_window or 0) < req.min_context:
+        return f"context window {caps.context_window} < {req.min_context}"
+    if req.max_cost_per_1k is not None and (caps.cost_per_1k is None or caps.cost_per_1k > req.max_cost_per_1k):
+        return f"cost per 1k {caps.cost_per_1k} > {req.max_cost_per_1k}"
+    return None
+
+
+def select_model(candidates: dict[str, ModelConfig], requirements: RequestRequirements) -> str:
+    """Return the name of the best candidate satisfying ``requirements``.
+
+    Qualifying candidates are ranked by ``requirements.prefer`` with the other
+    criterion breaking ties; unknown prices or latencies rank last, then the
+    candidates' order decides.
+
+    Raises:
+        NoMatchingModelError: If no candidate qualifies.
+    """
+    rejected: dict[str, str] = {}
+    qualifying: list[str] = []
+    for name, cfg in candidates.items():
+        reason = _rejection(cfg, requirements)
+        if reason is None:
+            qualifying.append(name)
+        else:
+            rejected[name] = reason
+    if not qualifying:
+        _logger.warning("no model for %s: %s", requirements.model_dump(exclude_defaults=True), rejected)
+        raise NoMatchingModelError(rejected)
+
+    def rank(name: str) -> tuple[float, float]:
+        caps = candidates[name].capabilities
+        cost = caps.cost_per_1k if caps.cost_per_1k is not None else float("inf")
+        latency = caps.latency_ms if caps.latency_ms is not None else float("inf")
+        return (cost, latency) if requirements.prefer == "cost" else (latency, cost)
+
+    chosen = min(qualifying, key=rank)
+    _logger.info(
+        "selected model %s (%s) by %s for %s; qualifying: %s; rejected: %s",
+        chosen,
+        candidates[chosen].model,
+        requirements.prefer,
+        requirements.model_dump(exclude_defaults=True),
+        qualifying,
+        rejected,
+    )
+    return chosen
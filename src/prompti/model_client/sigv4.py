@@ -0,0 +1,109 @@
+"""AWS Signature Version 4 request signing.
+
+Only what signing a JSON ``POST`` to an AWS API needs, implemented on the
+standard library so AWS providers work without ``botocore``::
+
+    credentials = AwsCredentials.from_env()
+    headers.update(sign(credentials, "POST", url, headers, body, region="us-east-1", service="bedrock"))
+
+See https://docs.aws.amazon.com/IAM/latest/UserGuide/reference_sigv-create-signed-request.html.
+"""
+
+from __future__ import annotations
+
+import hashlib
+import hmac
+import os
+from dataclasses import dataclass
+from datetime import datetime, timezone
+from urllib.parse import quote, urlsplit
+
+__all__ = ["ALGORITHM", "AwsCredentials", "sign"]
+
+ALGORITHM = "AWS4-HMAC-SHA256"
+
+
+@dataclass(frozen=True, kw_only=True)
+class AwsCredentials:
+    """AWS access key, with the session token of temporary credentials."""
+
+    access_key: str
+    secret_key: str
+    session_token: str | None = None
+
+    @classmethod
+    def from_env(cls) -> AwsCredentials | None:
+        """Return the credentials in ``AWS_ACCESS_KEY_ID``/``AWS_SECRET_ACCESS_KEY``, ``None`` if unset."""
+        access_key = os.environ.get("AWS_ACCESS_KEY_ID")
+        secret_key = os.environ.get("AWS_SECRET_ACCESS_KEY")
+        if not access_key or not secret_key:
+            return None
+        return cls(access_key=access_key, secret_key=secret_key, session_token=os.environ.get("AWS_SESSION_TOKEN"))
+
+
+def _hmac(key: bytes, msg: str) -> bytes:
+    return hmac.new(key, msg.encode(), hashlib.sha256).digest()
+
+
+def _canonical_query(query: str) -> str:
+    pairs = []
+    for item in query.split("&") if query else []:
+        name, _, value = item.partition("=")
+        pairs.append((quote(name, safe="-_.~"), quote(value, safe="-_.~")))
+    return "&".join(f"{name}={value}" for name, value in sorted(pairs))
+
+
+def sign(
+    credentials: AwsCredentials,
+    method: str,
+    url: str,
+    headers: dict[str, str],
+    body: bytes,
+    *,
+    region: str,
+    service: str,
+    now: datetime | None = None,
+) -> dict[str, str]:
+    """Return the headers that sign a request: ``Authorization``, ``X-Amz-Date`` and the session token.
+
+    ``headers`` are the headers the request is sent with; all of them are
+    signed, along with ``Host``. ``url`` must already be percent-encoded as
+    it is sent; its path is encoded once more for the canonical request, as
+    every service but S3 expects.
+    """
+    now = now or datetime.now(timezone.utc)
+    amz_date = now.strftime("%Y%m%dT%H%M%SZ")
+    date = amz_date[:8]
+    parts = urlsplit(url)
+    signed = {name.lower(): " ".join(str(value).split()) for name, value in headers.items()}
+    signed["host"] = parts.netloc
+    signed["x-amz-date"] = amz_date
+    if credentials.session_token:
+        signed["x-amz-security-token"] = credentials.session_token
+    names = sorted(signed)
+    canonical_request = "\n".join(
+        [
+            method.upper(),
+            quote(parts.path or "/", safe="/-_.~"),
+            _canonical_query(parts.query),
+            "".join(f"{name}:{signed[name]}\n" for name in names),
+            ";".join(names),
+            hashlib.sha256(body).hexdigest(),
+        ]
+    )
+    scope = f"{date}/{region}/{service}/aws4_request"
+    string_to_sign = "\n".join([ALGORITHM, amz_date, scope, hashlib.sha256(canonical_request.encode()).hexdigest()])
+    key = _hmac(f"AWS4{credentials.secret_key}".encode(), date)
+    for part in (region, service, "aws4_request"):
+        key = _hmac(key, part)
+    signature = hmac.new(key, string_to_sign.encode(), hashlib.sha256).hexdigest()
+    result = {
+        "Authorization": (
+            f"{ALGORITHM} Credential={credentials.access_key}/{scope}, "
+            f"SignedHeaders={';'.join(names)}, Signature={signature}"
+        ),
+        "X-Amz-Date": amz_date,
+    }
+    if credentials.session_token:
+        result["X-Amz-Security-Token"] = credentials.session_token
+    return result
@@ -0,0 +1,54 @@
+"""Handling of response fields a client does not know about.
+
+Providers add response fields all the time. In ``lenient`` mode (the default)
+unknown fields are kept in the response's ``extra`` map and logged once per
+provider and field; in ``strict`` mode they raise
+:class:`UnknownResponseFieldError`. Fields that are ``null`` or empty carry no
+data and are ignored in both modes.
+"""
+
+from __future__ import annotations
+
+import logging
+import threading
+from typing import Any, Literal
+
+ResponseStrictness = Literal["lenient", "strict"]
+
+_logger = logging.getLogger("model_client.response_fields")
+_logged: set[tuple[str | None, str]] = set()
+_logged_lock = threading.Lock()
+
+
+class UnknownResponseFieldError(ValueError):
+    """Raised in strict mode when a provider response has unexpected fields."""
+
+    def __init__(self, provider: str | None, fields: list[str]) -> None:
+        self.provider = provider
+        self.fields = fields
+        super().__init__(f"Unknown fields in {provider} response: {', '.join(fields)}")
+
+
+def unknown_fields(data: dict[str, Any], known: set[str], prefix: str = "") -> dict[str, Any]:
+    """Return the non-empty entries of ``data`` not in ``known``, keyed by dotted path."""
+    return {
+        f"{prefix}{key}": value
+        for key, value in data.items()
+        if key not in known and value not in (None, "", [], {})
+    }
+
+
+def check_unknown_fields(
+    provider: str | None, extra: dict[str, Any], strictness: ResponseStrictness | None
+) -> dict[str, Any]:
+    """Apply ``strictness`` to the unknown fields ``extra`` and return what to keep."""
+    if not extra:
+        return extra
+    if strictness == "strict":
+        raise UnknownResponseFieldError(provider, sorted(extra))
+    with _logged_lock:
+        new = [path for path in extra if (provider, path) not in _logged]
+        _logged.update((provider, path) for path in new)
+    for path in new:
+        _logger.warning("Unknown field %r in %s response, kept in response.extra", path, provider)
+    return extra
@@ -0,0 +1,362 @@
+"""Per-tenant access control for services that expose prompti as a gateway.
+
+A gateway in front of the upstream providers maps each caller's API key to
+a :class:`TenantConfig` with the models it may use, a request rate limit
+and a spend budget. :class:`TenantGateway` enforces them before a request
+is routed to a :class:`ClientManager` profile and labels metrics with the
+tenant::
+
+    gateway = TenantGateway(manager, [
+        TenantConfig(name="search", api_keys=["sk-search-1"], allowed_models=["gpt-4o*"],
+                     requests_per_minute=600, budget_usd=500),
+    ])
+    try:
+        async for response in gateway.arun(request_api_key, "gpt-4o-mini", params):
+            ...
+    except TenantAccessError as e:
+        return error_response(e.status_code, str(e), retry_after=e.retry_after)
+
+:meth:`TenantGateway.list_models` and :meth:`TenantGateway.aembeddings`
+return the bodies of OpenAI's ``GET /v1/models`` and ``POST /v1/embeddings``,
+so a gateway can serve OpenAI SDK clients unmodified.
+
+:class:`GatewayLimits` bounds what any caller may send: the request body
+size (rejected with 413), the number of messages and ``max_tokens`` (400).
+Requests without ``max_tokens`` are capped at ``max_output_tokens`` so a
+single call cannot run up unbounded output.
+
+With ``max_concurrent_per_provider`` each upstream provider gets its own
+pool of in-flight requests, so a provider that stalls during an incident
+fills only its own pool: its callers wait up to ``queue_timeout_s`` for a
+slot and are then rejected with 503, while traffic to other providers is
+unaffected.
+
+Rate limits and spend are kept in memory per process; spend is reset with
+:meth:`TenantGateway.reset_spend`, e.g. at the start of a billing period.
+The cost of a request is only known once it finished, so concurrent
+requests can overshoot a budget by their own cost.
+"""
+
+from __future__ import annotations
+
+import asyncio
+import fnmatch
+import threading
+import time
+from collections import deque
+from collections.abc import AsyncGenerator, AsyncIterator
+from contextlib import asynccontextmanager
+from typing import Any, Literal, Union
+
+from pydantic import BaseModel, Field
+
+from ..message import ModelResponse, StreamingModelResponse, Usage
+from ..pricing import get_price_table
+from ..telemetry import get_metrics
+from .manager import ClientManager
+from .types import ModelConfig, RunParams
+
+__all__ = ["GatewayLimits", "TenantAccessError", "TenantConfig", "TenantGateway"]
+
+RejectReason = Literal[
+    "unauthorized",
+    "model_not_allowed",
+    "rate_limited",
+    "budget_exceeded",
+    "request_too_large",
+    "too_many_messages",
+    "max_tokens_exceeded",
+    "provider_busy",
+]
+
+_STATUS_CODES = {
+    "unauthorized": 401,
+    "model_not_allowed": 403,
+    "rate_limited": 429,
+    "budget_exceeded": 402,
+    "request_too_large": 413,
+    "too_many_messages": 400,
+    "max_tokens_exceeded": 400,
+    "provider_busy": 503,
+}
+
+
+class TenantAccessError(PermissionError):
+    """Raised when a caller may not send a request."""
+
+    def __init__(self, reason: RejectReason, message: str, retry_after: float | None = None) -> None:
+        self.reason = reason
+        # seconds until a rate-limited tenant may send again
+        self.retry_after = retry_after
+        super().__init__(message)
+
+    @property
+    def status_code(self) -> int:
+        """The HTTP status a gateway should answer with."""
+        return _STATUS_CODES[self.reason]
+
+
+class TenantConfig(BaseModel):
+    """A workspace sharing limits across its API keys."""
+
+    name: str
+    api_keys: list[str]
+    # profile names or shell-style patterns ("gpt-4o*"); ``None`` allows every profile
+    allowed_models: list[str] | None = None
+    requests_per_minute: int | None = Field(None, ge=1)
+    # USD, see :meth:`TenantGateway.reset_spend`
+    budget_usd: float | None = Field(None, ge=0)
+
+
+class GatewayLimits(BaseModel):
+    """Bounds on every request, regardless of tenant; ``None`` disables a check."""
+
+    # size of the caller's request body in bytes
+    max_request_bytes: int | None = Field(None, ge=1)
+    max_messages: int | None = Field(None, ge=1)
+    # upper bound for ``max_tokens``, and its value when the caller sets none
+    max_output_tokens: int | None = Field(None, ge=1)
+    # in-flight requests per upstream provider, ``provider_concurrency`` overrides it per provider
+    max_concurrent_per_provider: int | None = Field(None, ge=1)
+    provider_concurrency: dict[str, int] = {}
+    # seconds to wait for a free slot before answering 503
+    queue_timeout_s: float = Field(5.0, ge=0)
+
+
+class _TenantState:
+    def __init__(self) -> None:
+        self.requests: deque[float] = deque()
+        self.spent = 0.0
+
+
+class TenantGateway:
+    """Authorize callers by API key and route their requests through ``manager``."""
+
+    def __init__(
+        self, manager: ClientManager, tenants: list[TenantConfig], limits: GatewayLimits | None = None
+    ) -> None:
+        self.manager = manager
+        self.limits = limits or GatewayLimits()
+        self.tenants = {tenant.name: tenant for tenant in tenants}
+        self._by_key: dict[str, TenantConfig] = {}
+        for tenant in tenants:
+            for key in tenant.api_keys:
+                other = self._by_key.setdefault(key, tenant)
+                if other is not tenant:
+                    raise ValueError(f"An API key of tenant {tenant.name!r} is already used by {other.name!r}")
+        self._state = {name: _TenantState() for name in self.tenants}
+        self._lock = threading.Lock()
+        self._pools: dict[str, asyncio.Semaphore] = {}
+
+    def _reject(self, tenant: str, model: str, error: TenantAccessError) -> TenantAccessError:
+        get_metrics().tenant_requests.labels(tenant, model, error.reason).inc()
+        return error
+
+    def _check_limits(self, params: RunParams | None, body_size: int | None) -> TenantAccessError | None:
+        limits = self.limits
+        if limits.max_request_bytes is not None and body_size is not None and body_size > limits.max_request_bytes:
+            return TenantAccessError(
+                "request_too_large",
+                f"Request body is {body_size} bytes, over the limit of {limits.max_request_bytes}",
+            )
+        if params is None:
+            return None
+        if limits.max_messages is not None and len(params.messages) > limits.max_messages:
+            return TenantAccessError(
+                "too_many_messages",
+                f"Request has {len(params.messages)} messages, over the limit of {limits.max_messages}",
+            )
+        if (
+            limits.max_output_tokens is not None
+            and params.max_tokens is not None
+            and params.max_tokens > limits.max_output_tokens
+        ):
+            return TenantAccessError(
+                "max_tokens_exceeded",
+                f"max_tokens is {params.max_tokens}, over the limit of {limits.max_output_tokens}",
+            )
+        return None
+
+    def authorize(
+        self,
+        api_key: str | None,
+        model: str,
+        params: RunParams | None = None,
+        body_size: int | None = None,
+    ) -> TenantConfig:
+        """Return the tenant of ``api_key`` and count the request against its rate limit.
+
+        ``params`` and ``body_size`` (bytes of the caller's request body) are
+        checked against :attr:`limits` when given.
+
+        Raises:
+            TenantAccessError: If the key is unknown, the tenant may not use
+                ``model``, the request exceeds :attr:`limits`, or the tenant's
+                rate limit or budget is exhausted.
+        """
+        tenant = self._by_key.get(api_key or "")
+        # ``model`` comes from the caller; only profile names become metric labels,
+        # so unknown names cannot create unbounded label series
+        if tenant is None:
+            raise self._reject("unknown", "unknown", TenantAccessError("unauthorized", "Invalid API key"))
+        if not self._allows(tenant, model):
+            error = TenantAccessError("model_not_allowed", f"Tenant {tenant.name!r} may not use model {model!r}")
+            raise self._reject(tenant.name, "unknown", error)
+        label = model if model in self.manager.profiles() else "unknown"
+        error = self._check_limits(params, body_size)
+        if error is not None:
+            raise self._reject(tenant.name, label, error)
+        now = time.monotonic()
+        with self._lock:
+            state = self._state[tenant.name]
+            if tenant.budget_usd is not None and state.spent >= tenant.budget_usd:
+                error = TenantAccessError(
+                    "budget_exceeded", f"Tenant {tenant.name!r} spent its budget of ${tenant.budget_usd:g}"
+                )
+                raise self._reject(tenant.name, label, error)
+            if tenant.requests_per_minute is not None:
+                while state.requests and state.requests[0] <= now - 60:
+                    state.requests.popleft()
+                if len(state.requests) >= tenant.requests_per_minute:
+                    retry_after = state.requests[0] + 60 - now
+                    error = TenantAccessError(
+                        "rate_limited",
+                        f"Tenant {tenant.name!r} exceeded {tenant.requests_per_minute} requests per minute",
+                        retry_after=retry_after,
+                    )
+                    raise self._reject(tenant.name, label, error)
+                state.requests.append(now)
+        return tenant
+
+    def _allows(self, tenant: TenantConfig, model: str) -> bool:
+        return tenant.allowed_models is None or any(
+            fnmatch.fnmatchcase(model, pattern) for pattern in tenant.allowed_models
+        )
+
+    def list_models(self, api_key: str | None) -> dict[str, Any]:
+        """Return the profiles the caller may use as an OpenAI ``/v1/models`` response.
+
+        Raises:
+            TenantAccessError: If the key is unknown.
+        """
+        tenant = self._by_key.get(api_key or "")
+        if tenant is None:
+            raise TenantAccessError("unauthorized", "Invalid API key")
+        models = []
+        for name in self.manager.profiles():
+            if self._allows(tenant, name):
+                cfg = self.manager.resolve(name)
+                models.append({"id": name, "object": "model", "created": 0, "owned_by": cfg.provider})
+        return {"object": "list", "data": models}
+
+    def _record(self, tenant: TenantConfig, model: str, cfg: ModelConfig, usage: Usage | None, result: str) -> None:
+        metrics = get_metrics()
+        metrics.tenant_requests.labels(tenant.name, model, result).inc()
+        if usage is None:
+            return
+        metrics.tenant_tokens.labels(tenant.name, "in").inc(usage.prompt_tokens)
+        metrics.tenant_tokens.labels(tenant.name, "out").inc(usage.completion_tokens)
+        cost = get_price_table().cost(cfg.model or model, usage, cfg.provider)
+        if cost:
+            metrics.tenant_cost.labels(tenant.name).inc(cost)
+            with self._lock:
+                self._state[tenant.name].spent += cost
+
+    def _pool(self, provider: str) -> asyncio.Semaphore | None:
+        size = self.limits.provider_concurrency.get(provider, self.limits.max_concurrent_per_provider)
+        if size is None:
+            return None
+        pool = self._pools.get(provider)
+        if pool is None:
+            pool = self._pools[provider] = asyncio.Semaphore(size)
+        return pool
+
+    @asynccontextmanager
+    async def _slot(self, tenant: TenantConfig, model: str, cfg: ModelConfig) -> AsyncIterator[None]:
+        """Hold a slot of the pool of ``cfg.provider`` for the duration of the block."""
+        provider = cfg.provider or "unknown"
+        pool = self._pool(provider)
+        if pool is None:
+            yield
+            return
+        try:
+            if pool.locked():
+                await asyncio.wait_for(pool.acquire(), self.limits.queue_timeout_s)
+            else:
+                await pool.acquire()
+        except asyncio.TimeoutError:
+            error = TenantAccessError(
+                "provider_busy",
+                f"Provider {provider!r} has no free capacity, try again later",
+                retry_after=self.limits.queue_timeout_s or None,
+            )
+            raise self._reject(tenant.name, model, error) from None
+        try:
+            yield
+        finally:
+            pool.release()
+
+    async def arun(
+        self, api_key: str | None, model: str, params: RunParams, *, body_size: int | None = None
+    ) -> AsyncGenerator[Union[ModelResponse, StreamingModelResponse], None]:
+        """Authorize the caller, then yield the responses of profile ``model`` for ``params``.
+
+        ``body_size`` is the size of the caller's request body; without it the
+        size of ``params`` serialized as JSON is checked against the limit.
+
+        Raises:
+            TenantAccessError: Before anything is sent, see :meth:`authorize`;
+                ``provider_busy`` if the provider's pool stays full.
+        """
+        if body_size is None and self.limits.max_request_bytes is not None:
+            body_size = len(params.model_dump_json(exclude_none=True).encode("utf-8"))
+        tenant = self.authorize(api_key, model, params, body_size)
+        if params.max_tokens is None and self.limits.max_output_tokens is not None:
+            params.max_tokens = self.limits.max_output_tokens
+        params.trace_context["tenant"] = tenant.name
+        client = self.manager.get(model)
+        async with self._slot(tenant, model, client.cfg):
+            usage = None
+            failed = completed = False
+            try:
+                async for response in client.arun(params):
+                    usage = response.usage or usage
+                    failed = failed or bool(response.error)
+                    yield response
+                completed = True
+            finally:
+                self._record(tenant, model, client.cfg, usage, "success" if completed and not failed else "error")
+
+    async def aembeddings(self, api_key: str | None, body: dict[str, Any]) -> dict[str, Any]:
+        """Authorize the caller and pass an OpenAI embeddings request to profile ``body["model"]``.
+
+        Returns the provider's response with ``model`` set to the profile name.
+
+        Raises:
+            TenantAccessError: Before anything is sent, see :meth:`authorize` and :meth:`arun`.
+            httpx.HTTPStatusError: If the provider rejects the request.
+        """
+        model = body.get("model") or ""
+        tenant = self.authorize(api_key, model)
+        client = self.manager.get(model)
+        async with self._slot(tenant, model, client.cfg):
+            try:
+                data = await client.aembeddings(body)
+            except Exception:
+                self._record(tenant, model, client.cfg, None, "error")
+                raise
+        prompt_tokens = (data.get("usage") or {}).get("prompt_tokens") or 0
+        usage = Usage(prompt_tokens=prompt_tokens, completion_tokens=0, total_tokens=prompt_tokens)
+        self._record(tenant, model, client.cfg, usage, "success")
+        return {**data, "model": model}
+
+    def spent(self, tenant: str) -> float:
+        """Return the USD spent by ``tenant`` since the last :meth:`reset_spend`."""
+        with self._lock:
+            return self._state[tenant].spent
+
+    def reset_spend(self, tenant: str | None = None) -> None:
+        """Reset the spend of ``tenant``, or of every tenant."""
+        with self._lock:
+            for name in [tenant] if tenant else list(self._state):
+                self._state[name].spent = 0.0
@@ -0,0 +1,304 @@
+"""HTTP connection pools and request bodies for model clients.
+
+Clients that are not given an ``httpx`` client build one here, so connection
+settings derived from :class:`ModelConfig` (TCP keep-alive, pinned host
+addresses, IP family) apply to every provider. Pass the result of
+:func:`build_async_http_client` to :class:`ClientManager` to share one such
+pool between profiles.
+
+Compressed responses are negotiated and decoded by ``httpx``: gzip and
+deflate always, brotli and zstd once the ``brotli``/``zstandard`` packages
+are installed. Request bodies are compressed by :func:`encode_json_body`
+when ``cfg.request_compression`` is set.
+"""
+
+from __future__ import annotations
+
+import gzip
+import ipaddress
+import json
+import socket
+from collections.abc import Callable, Sequence
+from typing import Any
+
+import anyio
+import httpcore
+import httpx
+
+from .types import KeepAliveConfig, ModelConfig
+
+# generations can take minutes; used when a client builds its own pool
+DEFAULT_TIMEOUT = httpx.Timeout(600)
+
+
+def http_timeout(cfg: ModelConfig | None, remaining: float | None = None) -> httpx.Timeout:
+    """Return the ``httpx`` timeouts of ``cfg.timeout``, each at most ``remaining`` seconds."""
+    config = cfg.timeout if cfg is not None else None
+    if config is None and remaining is None:
+        return DEFAULT_TIMEOUT
+    default = config.request_s if config is not None else DEFAULT_TIMEOUT.read
+    phases = {
+        "connect": config.connect_s if config is not None and config.connect_s is not None else default,
+        "read": config.read_s if config is not None and config.read_s is not None else default,
+        "write": default,
+        "pool": default,
+    }
+    if remaining is not None:
+        remaining = max(remaining, 0.001)
+        phases = {name: remaining if value is None else min(value, remaining) for name, value in phases.items()}
+    return httpx.Timeout(**phases)
+
+# maps a host name to the addresses to connect to, or ``None`` to use DNS
+Resolver = Callable[[str], Sequence[str] | None]
+
+
+def keepalive_socket_options(config: KeepAliveConfig) -> list[tuple[int, int, int]]:
+    """Return the socket options enabling TCP keep-alive with ``config``'s timings."""
+    options = [(socket.SOL_SOCKET, socket.SO_KEEPALIVE, 1)]
+    # TCP_KEEPIDLE on Linux, TCP_KEEPALIVE on macOS
+    idle = getattr(socket, "TCP_KEEPIDLE", None) or getattr(socket, "TCP_KEEPALIVE", None)
+    for name, value in ((idle, config.idle_s), (getattr(socket, "TCP_KEEPINTVL", None), config.interval_s)):
+        if name is not None:
+            options.append((socket.IPPROTO_TCP, name, value))
+    if hasattr(socket, "TCP_KEEPCNT"):
+        options.append((socket.IPPROTO_TCP, socket.TCP_KEEPCNT, config.count))
+    return options
+
+
+def static_resolver(hosts: dict[str, str | list[str]]) -> Resolver:
+    """Return a :data:`Resolver` answering from ``hosts`` (see ``ModelConfig.resolve``)."""
+    table = {
+        host.lower(): [addresses] if isinstance(addresses, str) else list(addresses)
+        for host, addresses in hosts.items()
+    }
+    return lambda host: table.get(host.lower())
+
+
+def _chain(*resolvers: Resolver | None) -> Resolver | None:
+    active = [r for r in resolvers if r is not None]
+    if not active:
+        return None
+    if len(active) == 1:
+        return active[0]
+
+    def resolve(host: str) -> Sequence[str] | None:
+        for resolver in active:
+            addresses = resolver(host)
+            if addresses:
+                return addresses
+        return None
+
+    return resolve
+
+
+def _ip_version(address: str) -> int | None:
+    try:
+        return ipaddress.ip_address(address).version
+    except ValueError:
+        return None  # a host name, resolved by the underlying backend
+
+
+class _ConnectPolicy:
+    """Which addresses a new connection tries, in which order and for how long each."""
+
+    def __init__(self, resolve: Resolver | None, family: str | None, fallback_delay_s: float | None) -> None:
+        self.resolve = resolve
+        self.family = family
+        self.fallback_delay_s = fallback_delay_s
+
+    def pinned(self, host: str) -> list[str] | None:
+        addresses = self.resolve(host) if self.resolve is not None else None
+        return list(addresses) if addresses else None
+
+    def needs_lookup(self, addresses: list[str] | None) -> bool:
+        # DNS results are only needed here to filter or reorder them by family
+        return addresses is None and self.family is not None
+
+    def candidates(self, host: str, addresses: list[str] | None) -> list[str]:
+        addresses = list(dict.fromkeys(addresses or [host]))
+        if self.family is None:
+            return addresses
+        wanted = 6 if self.family.endswith("ipv6") else 4
+        if self.family.startswith("prefer_"):
+            return sorted(addresses, key=lambda a: _ip_version(a) not in (wanted, None))
+        addresses = [a for a in addresses if _ip_version(a) in (wanted, None)]
+        if not addresses:
+            raise httpcore.ConnectError(f"No IPv{wanted} address for {host} (ip_family={self.family!r})")
+        return addresses
+
+    def attempt_timeout(self, timeout: float | None, last: bool) -> float | None:
+        if last or self.fallback_delay_s is None:
+            return timeout
+        return self.fallback_delay_s if timeout is None else min(timeout, self.fallback_delay_s)
+
+
+def _lookup_results(infos: list[Any]) -> list[str]:
+    return [str(info[4][0]) for info in infos]
+
+
+class _AsyncPolicyBackend(httpcore.AsyncNetworkBackend):
+    """Connect to the addresses a :class:`_ConnectPolicy` picks for a host, one after another."""
+
+    def __init__(self, inner: httpcore.AsyncNetworkBackend, policy: _ConnectPolicy) -> None:
+        self._inner = inner
+        self._policy = policy
+
+    async def connect_tcp(
+        self, host: str, port: int, timeout: float | None = None, **kwargs: Any
+    ) -> httpcore.AsyncNetworkStream:
+        # TLS runs on the returned stream with the original host name for SNI and verification
+        addresses = self._policy.pinned(host)
+        if self._policy.needs_lookup(addresses):
+            try:
+                addresses = _lookup_results(await anyio.getaddrinfo(host, port, type=socket.SOCK_STREAM))
+            except OSError as e:
+                raise httpcore.ConnectError(str(e)) from e
+        candidates = self._policy.candidates(host, addresses)
+        for i, address in enumerate(candidates):
+            last = i == len(candidates) - 1
+            try:
+                attempt_timeout = self._policy.attempt_timeout(timeout, last)
+                return await self._inner.connect_tcp(address, port, timeout=attempt_timeout, **kwargs)
+            except (httpcore.ConnectError, httpcore.ConnectTimeout):
+                if last:
+                    raise
+        raise AssertionError("unreachable")
+
+    async def connect_unix_socket(self, path: str, **kwargs: Any) -> httpcore.AsyncNetworkStream:
+        return await self._inner.connect_unix_socket(path, **kwargs)
+
+    async def sleep(self, seconds: float) -> None:
+        await self._inner.sleep(seconds)
+
+
+class _PolicyBackend(httpcore.NetworkBackend):
+    """Sync variant of :class:`_AsyncPolicyBackend`."""
+
+    def __init__(self, inner: httpcore.NetworkBackend, policy: _ConnectPolicy) -> None:
+        self._inner = inner
+        self._policy = policy
+
+    def connect_tcp(self, host: str, port: int, timeout: float | None = None, **kwargs: Any) -> httpcore.NetworkStream:
+        addresses = self._policy.pinned(host)
+        if self._policy.needs_lookup(addresses):
+            try:
+                addresses = _lookup_results(socket.getaddrinfo(host, port, type=socket.SOCK_STREAM))
+            except OSError as e:
+                raise httpcore.ConnectError(str(e)) from e
+        candidates = self._policy.candidates(host, addresses)
+        for i, address in enumerate(candidates):
+            last = i == len(candidates) - 1
+            try:
+                attempt_timeout = self._policy.attempt_timeout(timeout, last)
+                return self._inner.connect_tcp(address, port, timeout=attempt_timeout, **kwargs)
+            except (httpcore.ConnectError, httpcore.ConnectTimeout):
+                if last:
+                    raise
+        raise AssertionError("unreachable")
+
+    def connect_unix_socket(self, path: str, **kwargs: Any) -> httpcore.NetworkStream:
+        return self._inner.connect_unix_socket(path, **kwargs)
+
+    def sleep(self, seconds: float) -> None:
+        self._inner.sleep(seconds)
+
+
+def _transport_kwargs(cfg: ModelConfig | None) -> dict[str, Any]:
+    if cfg is None or cfg.keepalive is None:
+        return {}
+    return {"socket_options": keepalive_socket_options(cfg.keepalive)}
+
+
+def _connect_policy(cfg: ModelConfig | None, resolver: Resolver | None) -> _ConnectPolicy | None:
+    pinned = static_resolver(cfg.resolve) if cfg is not None and cfg.resolve else None
+    resolve = _chain(pinned, resolver)
+    family = cfg.ip_family if cfg is not None else None
+    fallback_delay_s = cfg.connect_fallback_delay_s if cfg is not None else None
+    if resolve is None and family is None and fallback_delay_s is None:
+        return None
+    return _ConnectPolicy(resolve, family, fallback_delay_s)
+
+
+def build_async_http_client(
+    cfg: ModelConfig | None = None, *, resolver: Resolver | None = None, **kwargs: Any
+) -> httpx.AsyncClient:
+    """Create an HTTP/2 ``httpx.AsyncClient`` with the connection settings of ``cfg``.
+
+    ``resolver`` is consulted for hosts not pinned by ``cfg.resolve``, e.g. to
+    look provider hosts up in an internal DNS; it runs on every new connection
+    and should not block.
+    """
+    transport_kwargs = _transport_kwargs(cfg)
+    policy = _connect_policy(cfg, resolver)
+    if (transport_kwargs or policy) and "transport" not in kwargs:
+        transport = httpx.AsyncHTTPTransport(http2=True, **transport_kwargs)
+        if policy is not None:
+            pool = transport._pool
+            pool._network_backend = _AsyncPolicyBackend(pool._network_backend, policy)
+        kwargs["transport"] = transport
+    return httpx.AsyncClient(http2=True, **kwargs)
+
+
+def build_sync_http_client(
+    cfg: ModelConfig | None = None, *, resolver: Resolver | None = None, **kwargs: Any
+) -> httpx.Client:
+    """Create an HTTP/2 ``httpx.Client`` with the connection settings of ``cfg``."""
+    transport_kwargs = _transport_kwargs(cfg)
+    policy = _connect_policy(cfg, resolver)
+    if (transport_kwargs or policy) and "transport" not in kwargs:
+        transport = httpx.HTTPTransport(http2=True, **transport_kwargs)
+        if policy is not None:
+            pool = transport._pool
+            pool._network_backend = _PolicyBackend(pool._network_backend, policy)
+        kwargs["transport"] = transport
+    return httpx.Client(http2=True, **kwargs)
+
+
+class RequestTooLargeError(ValueError):
+    """Raised before sending a request body larger than ``cfg.max_request_bytes``."""
+
+    def __init__(self, size: int, limit: int) -> None:
+        self.size = size
+        self.limit = limit
+        super().__init__(
+            f"Request body is {size} bytes, over the max_request_bytes limit of {limit}; "
+            "send fewer or smaller attachments, or set image_limits to downscale images"
+        )
+
+
+def _check_size(cfg: ModelConfig, body: bytes) -> None:
+    if cfg.max_request_bytes is not None and len(body) > cfg.max_request_bytes:
+        raise RequestTooLargeError(len(body), cfg.max_request_bytes)
+
+
+def check_request_size(cfg: ModelConfig, data: Any) -> None:
+    """Raise :class:`RequestTooLargeError` if ``data`` serializes over ``cfg.max_request_bytes``.
+
+    For clients that hand the request to an SDK instead of sending the body themselves.
+    """
+    if cfg.max_request_bytes is not None:
+        _check_size(cfg, json.dumps(data, ensure_ascii=False, separators=(",", ":"), default=str).encode("utf-8"))
+
+
+def encode_json_body(cfg: ModelConfig, data: Any) -> tuple[bytes, dict[str, str]]:
+    """Serialize ``data`` as a JSON request body and return it with its extra headers.
+
+    The body is gzip-compressed when ``cfg.request_compression`` is set and it
+    is at least ``cfg.request_compression_min_bytes`` long.
+
+    Raises:
+        RequestTooLargeError: If the body is larger than ``cfg.max_request_bytes``.
+    """
+    body = json.dumps(data, ensure_ascii=False, separators=(",", ":"), allow_nan=False).encode("utf-8")
+    _check_size(cfg, body)
+    if cfg.request_compression == "gzip" and len(body) >= cfg.request_compression_min_bytes:
+        return gzip.compress(body, compresslevel=6), {"Content-Encoding": "gzip"}
+    return body, {}
+
+
+def decoded_request_body(request: httpx.Request) -> bytes:
+    """Return the body of ``request``, decompressed if it was sent gzip-encoded."""
+    body = request.content
+    if body and request.headers.get("Content-Encoding") == "gzip":
+        return gzip.decompress(body)
+    return body
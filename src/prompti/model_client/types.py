@@ -0,0 +1,583 @@
+"""Configuration and request models shared by all model clients.
+
+This module only depends on pydantic and :mod:`prompti.message`, so services
+that just serialize requests or configs can import it without the HTTP stack::
+
+    from prompti.model_client.types import ModelConfig, RunParams
+"""
+
+from __future__ import annotations
+
+import hashlib
+import json
+import math
+import random
+from collections.abc import Mapping
+from datetime import datetime, timedelta, timezone
+from email.utils import parsedate_to_datetime
+from enum import Enum
+from typing import Any, Literal, Optional
+
+from pydantic import BaseModel, Field, model_validator
+
+from ..images import ImageLimits
+from ..message import Message
+
+
+def parse_retry_after(value: str | None) -> float | None:
+    """Return the delay in seconds encoded in a ``Retry-After`` header value."""
+    if not value:
+        return None
+    try:
+        return max(0.0, float(value))
+    except ValueError:
+        pass
+    try:
+        when = parsedate_to_datetime(value)
+    except (TypeError, ValueError):
+        return None
+    if when.tzinfo is None:
+        when = when.replace(tzinfo=timezone.utc)
+    return max(0.0, (when - datetime.now(timezone.utc)).total_seconds())
+
+
+class RetryConfig(BaseModel):
+    """Retry policy for transient provider failures.
+
+    Only failures that happen before the first response is yielded are
+    retried, so a partially streamed answer is never replayed. When the last
+    attempt fails with a retryable error, the error carries
+    ``"retries_exhausted": True``.
+    """
+
+    max_attempts: int = Field(3, ge=1)
+    initial_backoff_ms: int = Field(500, ge=0)
+    max_backoff_ms: int = Field(8000, ge=0)
+    # growth of the backoff per attempt
+    backoff_multiplier: float = Field(2.0, ge=1)
+    # fraction of the backoff that is randomized, so that clients failing together
+    # do not retry together: 0.5 waits between half and all of it
+    jitter: float = Field(0.0, ge=0, le=1)
+    retry_on_status: list[int] = [408, 409, 429, 500, 502, 503, 504]
+    respect_retry_after: bool = True
+
+    def is_retryable(self, error: dict[str, Any] | None) -> bool:
+        """Return whether ``error`` is transient: a status in ``retry_on_status``, a timeout or a network error."""
+        if not error:
+            return False
+        return error.get("status_code") in self.retry_on_status or error.get("code") in ("network_error", "timeout")
+
+    def should_retry(self, error: dict[str, Any] | None, attempt: int) -> bool:
+        """Return whether ``error`` from attempt number ``attempt`` warrants another try."""
+        return attempt < self.max_attempts and self.is_retryable(error)
+
+    def backoff(self, attempt: int, retry_after: str | None = None) -> float:
+        """Return the delay in seconds before retrying after attempt number ``attempt``.
+
+        A ``Retry-After`` value is used as is; the exponential backoff is capped
+        at ``max_backoff_ms`` and then jittered.
+        """
+        if self.respect_retry_after:
+            delay = parse_retry_after(retry_after)
+            if delay is not None:
+                return delay
+        delay = min(self.max_backoff_ms, self.initial_backoff_ms * self.backoff_multiplier ** (attempt - 1)) / 1000
+        if self.jitter:
+            delay *= 1 - self.jitter * random.random()
+        return delay
+
+
+class TimeoutConfig(BaseModel):
+    """Time limits of a call, in seconds; ``None`` means no limit.
+
+    ``request_s`` applies to each phase without a limit of its own: connecting,
+    each read, each write and waiting for a pooled connection. ``read_s`` is
+    the longest wait for the next bytes, e.g. between two stream chunks.
+    ``deadline_s`` bounds the whole call, retries and backoff included; a call
+    running past it ends with an error of code ``deadline_exceeded``. Timeout
+    errors carry ``elapsed_s``, the time the attempt took.
+    """
+
+    request_s: float | None = Field(600, gt=0)
+    connect_s: float | None = Field(None, gt=0)
+    read_s: float | None = Field(None, gt=0)
+    deadline_s: float | None = Field(None, gt=0)
+
+
+class RateLimitConfig(BaseModel):
+    """Client-side requests and tokens per minute, so bursts queue locally instead of running into 429s.
+
+    Budgets are shared by every client of the same provider and model in the
+    process (of the provider with ``scope="provider"``). Each attempt counts
+    as a request; its tokens are the estimated prompt plus ``max_tokens``,
+    corrected to the reported usage once the call finishes.
+    """
+
+    requests_per_minute: int | None = Field(None, ge=1)
+    tokens_per_minute: int | None = Field(None, ge=1)
+    scope: Literal["model", "provider"] = "model"
+    # longest wait for the budget before the call fails with ``RateLimitWaitError``; ``None`` waits as needed
+    max_wait_s: float | None = Field(None, ge=0)
+
+
+class KeepAliveConfig(BaseModel):
+    """TCP keep-alive probes for connections that stay idle during long generations.
+
+    Load balancers and NAT gateways drop connections that carry no packets for
+    a while (often 60s), which kills slow non-streaming calls and streams that
+    pause before the first token. Probes keep those connections alive. Options
+    missing on the platform are skipped.
+    """
+
+    idle_s: int = Field(30, ge=1)  # idle time before the first probe (TCP_KEEPIDLE)
+    interval_s: int = Field(10, ge=1)  # time between probes (TCP_KEEPINTVL)
+    count: int = Field(6, ge=1)  # unanswered probes before the connection is dropped (TCP_KEEPCNT)
+
+
+class OpenAICompatibleConfig(BaseModel):
+    """What a server speaking the OpenAI chat completions protocol supports.
+
+    Used by ``provider="openai_compatible"``, see
+    :class:`prompti.model_client.compatible_client.OpenAICompatibleClient`.
+    """
+
+    # accepts ``tools``/``tool_choice``; without, requests with tools raise ``ValueError``
+    supports_tools: bool = True
+    # accepts ``stream_options`` (usage in the last stream chunk); without, streams report no usage
+    supports_stream_options: bool = True
+    # accepts ``response_format``; without, JSON mode is emulated, see :mod:`prompti.model_client.json_mode`
+    supports_json_mode: bool = True
+    # path between ``api_url`` and ``/chat/completions``
+    path_prefix: str = "/v1"
+
+
+class AzureADConfig(BaseModel):
+    """Microsoft Entra ID (Azure AD) tokens instead of an API key for ``provider="azure"``.
+
+    Unset fields fall back to the ``AZURE_TENANT_ID``, ``AZURE_CLIENT_ID``,
+    ``AZURE_CLIENT_SECRET``, ``AZURE_FEDERATED_TOKEN_FILE`` and
+    ``AZURE_AUTHORITY_HOST`` environment variables, as with azure-identity.
+    See :mod:`prompti.model_client.azure_ad`.
+    """
+
+    # "default" tries a client secret, then workload identity, then managed identity
+    mode: Literal["default", "client_secret", "workload_identity", "managed_identity"] = "default"
+    tenant_id: str | None = None
+    # app registration, or the user-assigned managed identity (system-assigned when unset)
+    client_id: str | None = None
+    client_secret: str | None = None
+    scope: str = "https://cognitiveservices.azure.com/.default"
+    authority_host: str | None = None
+
+
+class ErrorClass(str, Enum):
+    """Coarse failure categories used for the ``error_class`` metric label."""
+
+    TIMEOUT = "timeout"
+    RATE_LIMIT = "rate_limit"
+    AUTH = "auth"
+    SERVER = "server"
+    CLIENT = "client"
+    STREAM = "stream"
+
+
+class ModelCapabilities(BaseModel):
+    """What a configured model supports and costs, used for automatic model selection.
+
+    See :func:`prompti.model_client.routing.select_model`.
+    """
+
+    vision: bool = False
+    tools: bool = False
+    json_mode: bool = False
+    context_window: int | None = Field(None, ge=1)
+    # USD per 1k prompt / completion tokens
+    input_cost_per_1k: float | None = Field(None, ge=0)
+    output_cost_per_1k: float | None = Field(None, ge=0)
+    # typical latency of a request, for ranking by speed
+    latency_ms: float | None = Field(None, ge=0)
+
+    @property
+    def cost_per_1k(self) -> float | None:
+        """Blended price of 1k tokens, assuming as many prompt as completion tokens."""
+        if self.input_cost_per_1k is None or self.output_cost_per_1k is None:
+            return None
+        return (self.input_cost_per_1k + self.output_cost_per_1k) / 2
+
+
+class ModelConfig(BaseModel):
+    """Static connection and default generation parameters."""
+
+    provider: Optional[str] | None = None
+    model: Optional[str] | None = None
+    api_key: Optional[str] | None = None
+    api_url: Optional[str] | None = None
+
+    # billing attribution for multi-org accounts (OpenAI-Organization / OpenAI-Project headers);
+    # ``project`` is the Google Cloud project for provider "vertex"
+    organization: Optional[str] = None
+    project: Optional[str] = None
+
+    # Azure OpenAI deployment and API version; ``deployment`` defaults to ``model``, so ``model``
+    # can name the underlying model (pricing, capabilities, reasoning-model handling)
+    deployment: str | None = None
+    api_version: str | None = None
+    # Entra ID tokens instead of ``api_key`` for Azure OpenAI, see AzureADConfig
+    azure_ad: AzureADConfig | None = None
+
+    # AWS region of Bedrock models, see BedrockClient; Google Cloud location of Vertex models
+    region: str | None = None
+
+    # quirks of the server for provider "openai_compatible"; ``None`` assumes full OpenAI support
+    compat: OpenAICompatibleConfig | None = None
+
+    # what to do when the provider no longer knows ``model``: "error" returns the error,
+    # "substitute" retries with the successor from ``model_aliases`` or
+    # :data:`prompti.model_client.deprecations.DEPRECATED_MODELS` and logs a warning
+    model_fallback: Literal["error", "substitute"] = "error"
+    model_aliases: dict[str, str] | None = None
+
+    # Anthropic beta features sent as the ``anthropic-beta`` header, e.g. ["prompt-caching-2024-07-31"]
+    beta_features: list[str] | None = None
+
+    # custom headers sent with every request, e.g. for a gateway in front of the provider
+    extra_headers: dict[str, str] | None = None
+
+    # check message ordering against the provider's rules before sending:
+    # "error" raises MessageOrderError, "fix" repairs what it safely can
+    message_normalization: Literal["error", "fix"] | None = None
+
+    # rename message roles before sending, e.g. {"system": "developer"};
+    # ``None`` uses :func:`prompti.roles.default_role_map` for the model
+    role_map: dict[str, str] | None = None
+
+    # generation defaults (may be overridden per call)
+    temperature: Optional[float] = None
+    top_p: Optional[float] | None = None
+    max_tokens: Optional[int] | None = None
+
+    # enforce ``RunParams.stop`` client-side for providers and models that ignore it:
+    # output is cut at the first stop sequence and the stream is closed
+    emulate_stop: bool = False
+
+    # emulate ``RunParams.response_format`` JSON mode with a system instruction and the
+    # "repair_json" postprocessor; ``None`` emulates it only for providers without JSON mode,
+    # see :mod:`prompti.model_client.json_mode`
+    emulate_json_mode: bool | None = None
+
+    # client-side cap on the generated text for providers where ``max_tokens`` is advisory:
+    # output past the limit is cut, the choice finishes with "length" and the stream is closed;
+    # tokens are estimated, see :func:`prompti.postprocess.estimate_tokens`
+    max_output_chars: int | None = Field(None, ge=1)
+    max_output_tokens: int | None = Field(None, ge=1)
+
+    # OpenAI file inputs larger than this are uploaded via the Files API instead of sent inline,
+    # see ``OpenAIClient.aprepare_file``; ``None`` uses ``prompti.documents.DEFAULT_MAX_INLINE_FILE_BYTES``
+    max_inline_file_bytes: int | None = Field(None, ge=0)
+
+    # unknown provider response fields: "lenient" (default) keeps them in
+    # ``response.extra`` and logs once per field, "strict" turns them into an error
+    response_strictness: Literal["lenient", "strict"] | None = None
+
+    # retry policy; ``None`` uses the :class:`RetryConfig` defaults
+    retry: RetryConfig | None = None
+
+    # connect/read timeouts and the deadline of a call; ``None`` allows 600s per phase and no deadline
+    timeout: TimeoutConfig | None = None
+
+    # client-side requests/tokens per minute, see RateLimitConfig
+    rate_limit: RateLimitConfig | None = None
+
+    # TCP keep-alive for the connections the client opens itself; ``None`` disables it
+    keepalive: KeepAliveConfig | None = None
+
+    # pin provider hosts to fixed addresses instead of DNS, like curl's ``--resolve``:
+    # {"api.openai.com": "203.0.113.7"} or a list tried in order; TLS still verifies the host name
+    resolve: dict[str, str | list[str]] | None = None
+
+    # address family for new connections: "ipv4"/"ipv6" use only that family,
+    # "prefer_ipv4"/"prefer_ipv6" try it first; ``None`` keeps the system's order
+    ip_family: Literal["ipv4", "ipv6", "prefer_ipv4", "prefer_ipv6"] | None = None
+
+    # when a host has several addresses, give up on one after this many seconds and
+    # try the next instead of waiting out the whole connect timeout
+    connect_fallback_delay_s: float | None = Field(None, gt=0)
+
+    # alternatives to ``api_url``, tried in order when the endpoint in use cannot be
+    # connected to (DNS or connect errors); see :mod:`prompti.model_client.endpoints`
+    failover_api_urls: list[str] = []
+    # after failing over, try the primary ``api_url`` again on the first request this many seconds later
+    failback_probe_s: float = Field(60.0, gt=0)
+
+    # compress request bodies of at least ``request_compression_min_bytes`` (large
+    # base64 images); enable only for endpoints that accept ``Content-Encoding: gzip``
+    request_compression: Literal["gzip"] | None = None
+    request_compression_min_bytes: int = Field(16384, ge=0)
+
+    # reject request bodies (serialized JSON, before compression) larger than this
+    max_request_bytes: int | None = Field(None, ge=1)
+
+    # downscale/re-encode base64 images to these limits before sending, see :mod:`prompti.images`
+    image_limits: ImageLimits | None = None
+
+    # replace smart quotes and unusual spaces and drop zero-width characters in the
+    # text of the messages sent, see :mod:`prompti.textnorm`
+    normalize_prompt_text: bool = False
+
+    # fraction of requests (0.0-1.0) whose provider request body is logged at DEBUG
+    request_log_sample_rate: float | None = Field(None, ge=0.0, le=1.0)
+
+    # features, context window and price of the model, for :mod:`prompti.model_client.routing`
+    capabilities: ModelCapabilities | None = None
+    
+    # extra parameters for client construction
+    extra_params: dict[str, Any] = {}
+
+
+class UsageReport(BaseModel):
+    """Token usage and spend for a period as reported by the provider's billing API.
+
+    Used to reconcile locally tracked usage and cost with what the provider bills.
+    """
+
+    provider: str | None = None
+    start: datetime
+    end: datetime
+    requests: int = 0
+    input_tokens: int = 0
+    output_tokens: int = 0
+    cached_input_tokens: int = 0
+    # ``None`` when the provider reports no spend
+    cost: float | None = None
+    currency: str = "usd"
+
+
+def usage_period(period: timedelta | tuple[datetime, datetime]) -> tuple[datetime, datetime]:
+    """Return ``(start, end)`` for a period given as a window ending now or as explicit bounds."""
+    if isinstance(period, timedelta):
+        end = datetime.now(timezone.utc)
+        return end - period, end
+    start, end = period
+    return start, end
+
+
+class RateLimitHeadroom(BaseModel):
+    """Rate-limit budget left as reported by the provider's ``x-ratelimit-*`` response headers."""
+
+    limit_requests: int | None = None
+    remaining_requests: int | None = None
+    limit_tokens: int | None = None
+    remaining_tokens: int | None = None
+    # when the headers were read
+    updated_at: datetime
+
+    @classmethod
+    def from_headers(cls, headers: Mapping[str, str]) -> RateLimitHeadroom | None:
+        """Parse OpenAI-style rate-limit headers; ``None`` if the response carries none."""
+        values: dict[str, int] = {}
+        for kind in ("limit", "remaining"):
+            for unit in ("requests", "tokens"):
+                raw = headers.get(f"x-ratelimit-{kind}-{unit}")
+                if raw is not None and raw.strip().isdigit():
+                    values[f"{kind}_{unit}"] = int(raw)
+        if not values:
+            return None
+        return cls(updated_at=datetime.now(timezone.utc), **values)
+
+
+class ToolSpec(BaseModel):
+    """Specification for a single tool."""
+
+    name: str
+    description: str
+    parameters: dict[str, Any]
+
+
+class ToolChoice(str, Enum):
+    """Allowed tool invocation policies."""
+
+    AUTO = "auto"
+    BLOCK = "none"
+    REQUIRED = "required"
+    FORCE = "force"
+
+
+class ToolParams(BaseModel):
+    """Tool catalogue and invocation configuration."""
+
+    tools: list[ToolSpec]
+    choice: ToolChoice | dict[str, Any] = ToolChoice.AUTO
+    force_tool: str | None = None
+    parallel_allowed: bool = True
+    max_calls: int | None = None
+
+
+class TokenCounts(BaseModel):
+    """Estimated prompt tokens of each message of a conversation, in order.
+
+    Returned by :meth:`ModelClient.annotate_tokens`; ``messages[i]`` belongs
+    to the i-th message, so truncation strategies and UIs can see which
+    messages cost what. Counts are estimates, see
+    :func:`prompti.postprocess.estimate_message_tokens`.
+    """
+
+    messages: list[int] = []
+    # tool definitions sent with the conversation
+    tools: int = 0
+
+    @classmethod
+    def estimate(
+        cls, messages: list[Message], tools: ToolParams | list[ToolSpec] | list[dict] | None = None
+    ) -> TokenCounts:
+        """Estimate the tokens of ``messages`` and ``tools`` without a tokenizer."""
+        from ..postprocess import estimate_message_tokens, estimate_tokens
+
+        counts = cls(messages=[estimate_message_tokens([m]) for m in messages])
+        if tools:
+            dumped = RunParams(messages=[], tool_params=tools).model_dump(mode="json", include={"tool_params"})
+            counts.tools = estimate_tokens(json.dumps(dumped["tool_params"], ensure_ascii=False))
+        return counts
+
+    @property
+    def total(self) -> int:
+        return sum(self.messages) + self.tools
+
+    def keep_from(self, budget: int) -> int:
+        """Return the index of the oldest message kept when the newest messages must fit into ``budget``.
+
+        Tool definitions count against ``budget``; ``len(messages)`` means
+        not even the last message fits.
+        """
+        remaining = budget - self.tools
+        index = len(self.messages)
+        while index > 0 and self.messages[index - 1] <= remaining:
+            index -= 1
+            remaining -= self.messages[index]
+        return index
+
+
+class TokenBudgetError(ValueError):
+    """Raised by :meth:`RunParams.with_token_budget` when the prompt leaves no room for a completion."""
+
+    def __init__(self, prompt_tokens: int, total: int) -> None:
+        self.prompt_tokens = prompt_tokens
+        self.total = total
+        super().__init__(f"The prompt (~{prompt_tokens} tokens) leaves no room for a completion in {total} tokens")
+
+
+# Fields that identify or trace one call rather than describe what is asked of the model.
+_HASH_EXCLUDED_FIELDS = {
+    "stream",
+    "user_id",
+    "request_id",
+    "session_id",
+    "conversation_id",
+    "span_id",
+    "parent_span_id",
+    "source",
+    "extra_headers",
+    "idempotency_key",
+    "trace_context",
+}
+
+
+class RunParams(BaseModel):
+    """Per-call parameters for :class:`ModelClient.run`."""
+
+    messages: list[Message]
+    tool_params: ToolParams | list[ToolSpec] | list[dict] | None = None
+
+    # sampling & length
+    temperature: float | None = None
+    top_p: float | None = None
+    top_k: int | None = None
+    max_tokens: int | None = None
+    stop: str | list[str] | None = None
+
+    # control & reproducibility
+    stream: bool = True
+    n: int | None = None
+    seed: int | None = None
+    logit_bias: dict[int, float] | None = None
+    response_format: str | None = None
+
+    # misc
+    user_id: str | None = None
+    request_id: str | None = None
+    session_id: str | None = None  # Deprecated: use conversation_id instead
+    conversation_id: str | None = None
+    span_id : str | None = None
+    parent_span_id : str | None = None
+    source: str | None = None
+    extra_params: dict[str, Any] = {}
+    # per-call headers, merged over ``ModelConfig.extra_headers``
+    extra_headers: dict[str, str] = {}
+    # sent as ``Idempotency-Key`` and reused across retries of this call
+    idempotency_key: str | None = None
+    # response text post-processors applied in order, e.g. ["strip_thinking", "trim"];
+    # see :mod:`prompti.postprocess`
+    postprocessors: list[str] = []
+
+    
+    # trace data capture - used to pass data between engine and model client
+    trace_context: dict[str, Any] = {}
+    
+    @model_validator(mode='before')
+    @classmethod
+    def handle_session_conversation_compatibility(cls, data):
+        """Handle backward compatibility between session_id and conversation_id."""
+        if isinstance(data, dict):
+            # If only session_id is provided, copy to conversation_id
+            if 'session_id' in data and 'conversation_id' not in data:
+                data['conversation_id'] = data['session_id']
+            # If only conversation_id is provided, copy to session_id for backward compatibility
+            elif 'conversation_id' in data and 'session_id' not in data:
+                data['session_id'] = data['conversation_id']
+            # If both are provided, conversation_id takes precedence
+            elif 'conversation_id' in data and 'session_id' in data:
+                data['session_id'] = data['conversation_id']
+
+        return data
+
+    def with_token_budget(self, total: int, *, prompt_tokens: int | None = None, margin: float = 0.1) -> RunParams:
+        """Return a copy whose ``max_tokens`` fits the completion into ``total`` tokens.
+
+        ``total`` is the model's context window, or a smaller budget for prompt
+        and completion together. ``prompt_tokens`` counted with the model's
+        tokenizer is used as is; otherwise messages and tools are estimated
+        (see :meth:`TokenCounts.estimate`) and the estimate padded by
+        ``margin``. A smaller ``max_tokens`` already set is kept.
+
+        Raises:
+            TokenBudgetError: When the prompt alone uses up ``total``.
+        """
+        if prompt_tokens is None:
+            prompt_tokens = math.ceil(TokenCounts.estimate(self.messages, self.tool_params).total * (1 + margin))
+        available = total - prompt_tokens
+        if available < 1:
+            raise TokenBudgetError(prompt_tokens, total)
+        max_tokens = available if self.max_tokens is None else min(self.max_tokens, available)
+        return self.model_copy(update={"max_tokens": max_tokens})
+
+    def canonical_hash(self, model: str | None = None) -> str:
+        """Return a SHA-256 hex digest identifying the logical request.
+
+        The digest covers the messages, tools, sampling parameters and
+        ``extra_params`` (plus ``model`` when given). Fields left at their
+        default are dropped and keys are sorted, so field order and spelling
+        out a default do not change it; per-call identifiers, tracing data,
+        headers and ``stream`` are not part of it. Use it as the key for
+        caching, deduplication, log correlation or a deterministic
+        ``idempotency_key``.
+        """
+        canonical = json.dumps(
+            self.canonical_request(model), sort_keys=True, separators=(",", ":"), ensure_ascii=False, default=str
+        )
+        return hashlib.sha256(canonical.encode()).hexdigest()
+
+    def canonical_request(self, model: str | None = None) -> dict[str, Any]:
+        """Return the JSON data :meth:`canonical_hash` digests."""
+        body = self.model_dump(mode="json", exclude=_HASH_EXCLUDED_FIELDS, exclude_defaults=True)
+        if model is not None:
+            body["model"] = model
+        return body
@@ -0,0 +1,92 @@
+"""Gemini models on Google Cloud Vertex AI.
+
+Requests and responses have the ``generateContent`` shape of
+:mod:`.gemini_client`; the differences are the regional, per-project URL
+and OAuth bearer tokens instead of an API key. Tokens come from the
+metadata server of the workload's service account (see
+:mod:`.gcp_metadata`), so GCE, GKE, Cloud Run and Cloud Functions need no
+service-account JSON::
+
+    client = create_client(ModelConfig(provider="vertex", model="gemini-2.5-flash", project="my-project"))
+
+``cfg.project`` is the Google Cloud project (``GOOGLE_CLOUD_PROJECT`` when
+unset) and ``cfg.region`` the location (``GOOGLE_CLOUD_LOCATION``, then
+:data:`DEFAULT_LOCATION`); ``cfg.api_url`` replaces the
+``.../publishers/google`` base URL, e.g. for Private Service Connect.
+"""
+
+from __future__ import annotations
+
+import os
+from typing import Any
+
+import httpx
+
+from .base import RunParams
+from .gcp_metadata import MetadataCredential
+from .gemini_client import GeminiWireMixin
+from .openai_wire import OpenAIWireClient, SyncOpenAIWireClient
+from .types import ModelConfig
+
+DEFAULT_LOCATION = "us-central1"
+
+
+class VertexWireMixin(GeminiWireMixin):
+    """Vertex AI differences from the Gemini API: URLs and metadata-server tokens."""
+
+    error_label = "Vertex AI API"
+
+    def __init__(self, cfg: ModelConfig, *args: Any, **kwargs: Any) -> None:
+        super().__init__(cfg, *args, **kwargs)
+        self._credential = MetadataCredential()
+
+    def _location(self) -> str:
+        return self.cfg.region or os.environ.get("GOOGLE_CLOUD_LOCATION") or DEFAULT_LOCATION
+
+    def _vertex_base(self) -> str:
+        project = self.cfg.project or os.environ.get("GOOGLE_CLOUD_PROJECT")
+        if not project:
+            raise ValueError("provider 'vertex' needs project, or GOOGLE_CLOUD_PROJECT")
+        location = self._location()
+        host = "aiplatform.googleapis.com" if location == "global" else f"{location}-aiplatform.googleapis.com"
+        return f"https://{host}/v1/projects/{project}/locations/{location}/publishers/google"
+
+    def _request_url(self, endpoint: str | None = None, stream: bool = False) -> str:
+        base = (endpoint or self._endpoint() or self._vertex_base()).rstrip("/")
+        method = "streamGenerateContent?alt=sse" if stream else "generateContent"
+        return f"{base}/models/{self.cfg.model}:{method}"
+
+    def _build_headers(self, params: RunParams | None = None) -> dict[str, str]:
+        headers = super()._build_headers(params)
+        # the bearer token is added when sending; cfg.project names the GCP project here
+        for name in (self.auth_header, "OpenAI-Project"):
+            headers.pop(name, None)
+        return headers
+
+
+class VertexClient(VertexWireMixin, OpenAIWireClient):
+    """Google Cloud Vertex AI client for Gemini models."""
+
+    provider = "vertex"
+
+    async def _asend(self, request: httpx.Request, stream: bool = False) -> httpx.Response:
+        request.headers["Authorization"] = f"Bearer {await self._credential.aget_token()}"
+        return await super()._asend(request, stream=stream)
+
+    async def aclose(self) -> None:
+        await self._credential.aclose()
+        await super().aclose()
+
+
+class SyncVertexClient(VertexWireMixin, SyncOpenAIWireClient):
+    """Synchronous Google Cloud Vertex AI client for Gemini models."""
+
+    provider = "vertex"
+
+    def _send(self, request: httpx.Request, stream: bool = False) -> httpx.Response:
+        request.headers["Authorization"] = f"Bearer {self._credential.get_token()}"
+        return super()._send(request, stream=stream)
+
+    def close(self) -> None:
+        self._credential.close()
+        super().close()
@@ -0,0 +1,141 @@
+"""Self-consistency voting for structured extraction and classification.
+
+:func:`avote` runs ``k`` samples of one request and returns the answer most
+samples agree on, with how strongly they agree::
+
+    result = await avote(client, params, 5, key=lambda answer: answer["label"])
+    if result.agreement < 0.8:
+        escalate(result.tally)
+
+Each completion is parsed with ``parse`` (JSON by default, a fence around
+it is removed); completions that fail or do not parse do not vote.
+``key`` picks what is compared, e.g. one field of the extracted object;
+answers are otherwise compared whole, with dict keys in any order. Sample
+with a temperature above 0, or the votes are likely identical.
+"""
+
+from __future__ import annotations
+
+import json
+from collections.abc import Callable, Hashable
+from typing import TYPE_CHECKING, Any
+
+from pydantic import BaseModel
+
+from ..message import ModelResponse
+from ..postprocess import StripMarkdownFences
+from .best_of import asample, sample
+from .types import RunParams
+
+if TYPE_CHECKING:
+    from .base import ModelClient, SyncModelClient
+
+__all__ = ["VoteCount", "VoteResult", "avote", "parse_json_answer", "vote"]
+
+
+class VoteCount(BaseModel):
+    """One distinct answer and the indexes of the samples that gave it."""
+
+    answer: Any
+    samples: list[int]
+
+    @property
+    def count(self) -> int:
+        return len(self.samples)
+
+
+class VoteResult(BaseModel):
+    """The majority answer of a vote and its agreement statistics."""
+
+    # the value of the winning answer; ``None`` when no sample gave a valid answer
+    answer: Any = None
+    # samples that gave the winning answer / samples with a valid answer
+    agreement: float = 0.0
+    votes: int = 0
+    valid: int = 0
+    samples: int
+    # another answer got as many votes; the earliest of them wins
+    tied: bool = False
+    # distinct answers, most votes first
+    tally: list[VoteCount] = []
+    # why samples did not vote, by sample index
+    errors: dict[int, str] = {}
+
+
+def parse_json_answer(text: str) -> Any:
+    """Parse a JSON answer, removing a Markdown fence around it."""
+    strip = StripMarkdownFences()
+    return json.loads(strip.feed(text) + strip.flush())
+
+
+def _canonical(value: Any) -> Hashable:
+    if isinstance(value, BaseModel):
+        value = value.model_dump(mode="json")
+    if isinstance(value, (dict, list)):
+        return json.dumps(value, sort_keys=True, ensure_ascii=False)
+    return value if isinstance(value, Hashable) else repr(value)
+
+
+def _count(
+    responses: list[ModelResponse], parse: Callable[[str], Any], key: Callable[[Any], Any] | None
+) -> VoteResult:
+    groups: dict[Hashable, VoteCount] = {}
+    errors: dict[int, str] = {}
+    for index, response in enumerate(responses):
+        if response.error:
+            errors[index] = str(response.error.get("message") or response.error)
+            continue
+        try:
+            answer = parse(response.get_text_content() or "")
+            value = key(answer) if key is not None else answer
+        except Exception as e:
+            errors[index] = f"{type(e).__name__}: {e}"
+            continue
+        group = groups.setdefault(_canonical(value), VoteCount(answer=value, samples=[]))
+        group.samples.append(index)
+
+    # sorted() is stable, so among equal counts the answer seen first stays first
+    tally = sorted(groups.values(), key=lambda g: g.count, reverse=True)
+    result = VoteResult(samples=len(responses), valid=len(responses) - len(errors), tally=tally, errors=errors)
+    if tally:
+        result.answer = tally[0].answer
+        result.votes = tally[0].count
+        result.agreement = result.votes / result.valid
+        result.tied = len(tally) > 1 and tally[1].count == result.votes
+    return result
+
+
+async def avote(
+    client: ModelClient,
+    params: RunParams,
+    k: int,
+    *,
+    parse: Callable[[str], Any] = parse_json_answer,
+    key: Callable[[Any], Any] | None = None,
+    use_n: bool = False,
+) -> VoteResult:
+    """Sample ``params`` ``k`` times and return the majority answer.
+
+    Args:
+        client: Client that samples the answers.
+        params: The extraction or classification request.
+        k: Number of samples.
+        parse: Turns the text of a completion into an answer.
+        key: Picks the part of an answer that is voted on.
+        use_n: Request all samples in one call with ``params.n``; samples
+            missing from the response are sampled separately.
+    """
+    return _count(await asample(client, params, k, use_n=use_n), parse, key)
+
+
+def vote(
+    client: SyncModelClient,
+    params: RunParams,
+    k: int,
+    *,
+    parse: Callable[[str], Any] = parse_json_answer,
+    key: Callable[[Any], Any] | None = None,
+    use_n: bool = False,
+) -> VoteResult:
+    """Sync variant of :func:`avote`; samples run in threads."""
+    return _count(sample(client, params, k, use_n=use_n), parse, key)
@@ -0,0 +1,376 @@
+"""Incremental parsing of JSON streamed by a model in JSON mode.
+
+:class:`PartialJSONParser` accepts text deltas and after each one returns the
+best value that can be read from the text so far: open objects and arrays
+are closed, strings that are still being written are included as-is, and
+numbers or literals that may still grow are left out. It also reports which
+fields completed with the latest delta, so a UI can render them as they
+arrive::
+
+    async for snapshot in astream_json(client.arun(params), model=Answer):
+        render(snapshot.value)
+    answer = snapshot.parsed
+
+Tool call arguments stream the same way. :func:`astream_tool_calls`
+accumulates the tool call deltas and yields the arguments of the call that
+changed, also as a partially filled instance of the tool's argument model,
+so e.g. a search query can be shown while it is being generated::
+
+    async for call in astream_tool_calls(client.arun(params), models={"search": SearchArgs}):
+        if call.partial is not None and call.partial.query:
+            show_query(call.partial.query)
+"""
+
+from __future__ import annotations
+
+import functools
+import json
+import types
+from collections.abc import AsyncIterable, AsyncGenerator
+from dataclasses import dataclass, field
+from typing import Any, Optional, Union, get_args, get_origin
+
+from pydantic import BaseModel, Field, ValidationError, create_model
+
+from .message import ModelResponse, StreamingModelResponse, ToolCall, ToolCallDelta
+
+Path = tuple[Union[str, int], ...]
+
+_MISSING = object()
+_WHITESPACE = " \t\r\n"
+_LITERALS = {"true": True, "false": False, "null": None}
+_ESCAPES = {'"': '"', "\\": "\\", "/": "/", "b": "\b", "f": "\f", "n": "\n", "r": "\r", "t": "\t"}
+
+
+@dataclass(kw_only=True)
+class PartialJSON:
+    """Snapshot of a JSON value being streamed."""
+
+    value: Any
+    complete: bool
+    # paths of the values that completed since the previous snapshot, e.g. ("items", 0, "name")
+    completed: list[Path] = field(default_factory=list)
+    # the value validated against the target model, once complete
+    parsed: BaseModel | None = None
+
+
+class _Parser:
+    def __init__(self, text: str, final: bool = False) -> None:
+        self.text = text
+        # the stream has ended, so a number at the end of the text is complete
+        self.final = final
+        self.completed: list[Path] = []
+
+    def skip_ws(self, i: int) -> int:
+        while i < len(self.text) and self.text[i] in _WHITESPACE:
+            i += 1
+        return i
+
+    def value(self, i: int, path: Path) -> tuple[Any, int, bool]:
+        i = self.skip_ws(i)
+        if i >= len(self.text):
+            return _MISSING, i, False
+        ch = self.text[i]
+        if ch == "{":
+            result = self.object(i + 1, path)
+        elif ch == "[":
+            result = self.array(i + 1, path)
+        elif ch == '"':
+            result = self.string(i + 1)
+        elif ch in "-0123456789":
+            result = self.number(i)
+        else:
+            result = self.literal(i)
+        if result[2]:
+            self.completed.append(path)
+        return result
+
+    def object(self, i: int, path: Path) -> tuple[Any, int, bool]:
+        obj: dict[str, Any] = {}
+        while True:
+            i = self.skip_ws(i)
+            if i >= len(self.text):
+                return obj, i, False
+            if self.text[i] == "}":
+                return obj, i + 1, True
+            if self.text[i] == ",":
+                i += 1
+                continue
+            if self.text[i] != '"':
+                raise ValueError(f"Expected object key at offset {i}")
+            key, i, key_done = self.string(i + 1)
+            i = self.skip_ws(i)
+            if not key_done or i >= len(self.text):
+                return obj, i, False
+            if self.text[i] != ":":
+                raise ValueError(f"Expected ':' at offset {i}")
+            value, i, done = self.value(i + 1, (*path, key))
+            if value is not _MISSING:
+                obj[key] = value
+            if not done:
+                return obj, i, False
+
+    def array(self, i: int, path: Path) -> tuple[Any, int, bool]:
+        items: list[Any] = []
+        while True:
+            i = self.skip_ws(i)
+            if i >= len(self.text):
+                return items, i, False
+            if self.text[i] == "]":
+                return items, i + 1, True
+            if self.text[i] == ",":
+                i += 1
+                continue
+            value, i, done = self.value(i, (*path, len(items)))
+            if value is not _MISSING:
+                items.append(value)
+            if not done:
+                return items, i, False
+
+    def string(self, i: int) -> tuple[str, int, bool]:
+        chars: list[str] = []
+        while i < len(self.text):
+            ch = self.text[i]
+            if ch == '"':
+                return "".join(chars), i + 1, True
+            if ch != "\\":
+                chars.append(ch)
+                i += 1
+                continue
+            if i + 1 >= len(self.text):
+                break
+            escape = self.text[i + 1]
+            if escape == "u":
+                digits = self.text[i + 2 : i + 6]
+                if len(digits) < 4:
+                    break
+                code = int(digits, 16)
+                i += 6
+                if 0xD800 <= code < 0xDC00:
+                    # a high surrogate combines with the low surrogate escape that follows it
+                    low = self.text[i : i + 6]
+                    if len(low) < 6 and "\\u".startswith(low[:2]):
+                        break
+                    if low.startswith("\\u") and 0xDC00 <= (low_code := int(low[2:], 16)) < 0xE000:
+                        code = 0x10000 + ((code - 0xD800) << 10) + (low_code - 0xDC00)
+                        i += 6
+                chars.append(chr(code))
+            else:
+                chars.append(_ESCAPES.get(escape, escape))
+                i += 2
+        # an unterminated string is still useful for progressive rendering
+        return "".join(chars), len(self.text), False
+
+    def number(self, i: int) -> tuple[Any, int, bool]:
+        end = i
+        while end < len(self.text) and self.text[end] in "-+.eE0123456789":
+            end += 1
+        if end >= len(self.text) and not self.final:
+            # more digits may follow
+            return _MISSING, end, False
+        return json.loads(self.text[i:end]), end, True
+
+    def literal(self, i: int) -> tuple[Any, int, bool]:
+        for word, value in _LITERALS.items():
+            if self.text.startswith(word, i):
+                return value, i + len(word), True
+            if word.startswith(self.text[i:]) and not self.final:
+                return _MISSING, len(self.text), False
+        raise ValueError(f"Unexpected character {self.text[i]!r} at offset {i}")
+
+
+def parse_partial_json(text: str) -> tuple[Any, bool]:
+    """Parse a possibly truncated JSON document.
+
+    Returns ``(value, complete)``; ``value`` is ``None`` when nothing can be read yet.
+
+    Raises:
+        ValueError: If ``text`` is not a prefix of valid JSON.
+    """
+    value, _, complete = _Parser(text).value(0, ())
+    return (None if value is _MISSING else value), complete
+
+
+class PartialJSONParser:
+    """Feed text deltas and get :class:`PartialJSON` snapshots back."""
+
+    def __init__(self, model: type[BaseModel] | None = None) -> None:
+        """Create a parser; ``model`` validates the value once it is complete."""
+        self.model = model
+        self.text = ""
+        self._reported: set[Path] = set()
+
+    def feed(self, delta: str) -> PartialJSON:
+        """Append ``delta`` and return the current snapshot."""
+        self.text += delta
+        return self._snapshot(_Parser(self.text))
+
+    def finish(self) -> PartialJSON:
+        """Return the snapshot of the text once the stream has ended.
+
+        A number or literal at the end of the text, e.g. the whole document
+        ``42``, is complete now that no more characters can follow.
+
+        Raises:
+            ValueError: If the text ends in the middle of a literal.
+        """
+        return self._snapshot(_Parser(self.text, final=True))
+
+    def _snapshot(self, parser: _Parser) -> PartialJSON:
+        value, _, complete = parser.value(0, ())
+        completed = [path for path in parser.completed if path not in self._reported]
+        self._reported.update(completed)
+        snapshot = PartialJSON(value=None if value is _MISSING else value, complete=complete, completed=completed)
+        if complete and self.model is not None:
+            snapshot.parsed = self.model.model_validate(snapshot.value)
+        return snapshot
+
+
+async def astream_json(
+    responses: AsyncIterable[Union[ModelResponse, StreamingModelResponse]],
+    model: type[BaseModel] | None = None,
+) -> AsyncGenerator[PartialJSON, None]:
+    """Yield a snapshot for every response of ``responses`` that adds text.
+
+    When the stream ends with a value that only the end of the stream completes,
+    e.g. a trailing number, one more snapshot from :meth:`PartialJSONParser.finish` follows.
+    """
+    parser = PartialJSONParser(model)
+    snapshot = None
+    async for response in responses:
+        if response.error:
+            raise ValueError(f"Model returned an error: {response.error}")
+        delta = response.get_text_content()
+        if delta:
+            snapshot = parser.feed(delta)
+            yield snapshot
+    if snapshot is not None and not snapshot.complete:
+        final = parser.finish()
+        if final.complete or final.completed:
+            yield final
+
+
+def merge_tool_call_deltas(calls: dict[int, dict[str, Any]], deltas: list[dict[str, Any]]) -> None:
+    """Merge streamed tool call fragments into ``calls`` keyed by index, see :meth:`ToolCall.merge`."""
+    for delta in ToolCallDelta.from_openai(deltas):
+        call = ToolCall.model_validate(calls[delta.index]) if delta.index in calls else ToolCall()
+        call.merge(delta)
+        calls[delta.index] = call.to_openai()
+
+
+def _partial_annotation(annotation: Any) -> Any:
+    if isinstance(annotation, type) and issubclass(annotation, BaseModel):
+        return partial_model(annotation)
+    origin, args = get_origin(annotation), get_args(annotation)
+    if origin is list and args:
+        return list[_partial_annotation(args[0])]
+    if origin is dict and len(args) == 2:
+        return dict[args[0], _partial_annotation(args[1])]
+    if origin in (Union, types.UnionType):
+        return Union[tuple(_partial_annotation(arg) for arg in args)]
+    return annotation
+
+
+@functools.lru_cache(maxsize=None)
+def partial_model(model: type[BaseModel]) -> type[BaseModel]:
+    """Return a variant of ``model`` whose fields are all optional and default to ``None``.
+
+    Nested models, also inside lists, dicts and unions, are made partial too.
+    """
+    fields: dict[str, Any] = {
+        name: (Optional[_partial_annotation(info.annotation)], Field(None, alias=info.alias))
+        for name, info in model.model_fields.items()
+    }
+    return create_model(f"Partial{model.__name__}", **fields)
+
+
+def validate_partial(model: type[BaseModel], value: Any) -> BaseModel | None:
+    """Validate a partially streamed ``value`` against :func:`partial_model` of ``model``.
+
+    Top-level fields that do not validate yet, such as a string that will
+    become an enum value, are left out. Returns ``None`` if ``value`` is not an
+    object.
+    """
+    if not isinstance(value, dict):
+        return None
+    partial = partial_model(model)
+    data = dict(value)
+    while True:
+        try:
+            return partial.model_validate(data)
+        except ValidationError as e:
+            invalid = {error["loc"][0] for error in e.errors() if error["loc"] and error["loc"][0] in data}
+            if not invalid:
+                return None
+            for key in invalid:
+                del data[key]
+
+
+@dataclass(kw_only=True)
+class PartialToolCall:
+    """Snapshot of a tool call being streamed."""
+
+    index: int
+    id: str
+    name: str
+    # the arguments parsed so far, ``None`` before any can be read
+    arguments: Any
+    complete: bool
+    # argument paths that completed since the previous snapshot of this call
+    completed: list[Path] = field(default_factory=list)
+    # the arguments as a :func:`partial_model` instance, for tools with a model
+    partial: BaseModel | None = None
+    # the arguments validated against the tool's model, once complete
+    parsed: BaseModel | None = None
+
+
+class ToolCallStream:
+    """Feed tool call deltas and get :class:`PartialToolCall` snapshots of the calls they changed.
+
+    ``models`` maps tool names to the models of their arguments; the
+    arguments of other tools are only available as parsed JSON.
+    """
+
+    def __init__(self, models: dict[str, type[BaseModel]] | None = None) -> None:
+        self.models = dict(models or {})
+        # the accumulated calls in OpenAI format, keyed by index
+        self.calls: dict[int, dict[str, Any]] = {}
+        self._parsers: dict[int, PartialJSONParser] = {}
+
+    def feed(self, deltas: list[dict[str, Any]]) -> list[PartialToolCall]:
+        """Merge ``deltas`` and return a snapshot of every call they touched."""
+        merge_tool_call_deltas(self.calls, deltas)
+        snapshots = []
+        for index in dict.fromkeys(delta.get("index", pos) for pos, delta in enumerate(deltas)):
+            call = self.calls[index]
+            name, arguments = call["function"]["name"], call["function"]["arguments"]
+            parser = self._parsers.setdefault(index, PartialJSONParser())
+            snapshot = parser.feed(arguments[len(parser.text) :])
+            result = PartialToolCall(
+                index=index,
+                id=call["id"],
+                name=name,
+                arguments=snapshot.value,
+                complete=snapshot.complete,
+                completed=snapshot.completed,
+            )
+            model = self.models.get(name)
+            if model is not None:
+                result.partial = validate_partial(model, snapshot.value)
+                if snapshot.complete:
+                    result.parsed = model.model_validate(snapshot.value)
+            snapshots.append(result)
+        return snapshots
+
+
+async def astream_tool_calls(
+    responses: AsyncIterable[Union[ModelResponse, StreamingModelResponse]],
+    models: dict[str, type[BaseModel]] | None = None,
+) -> AsyncGenerator[PartialToolCall, None]:
+    """Yield a snapshot of each tool call whenever a response of ``responses`` extends it."""
+    stream = ToolCallStream(models)
+    async for response in responses:
+        if response.error:
+            raise ValueError(f"Model returned an error: {response.error}")
+        for snapshot in stream.feed(response.get_tool_calls() or []):
+            yield snapshot
@@ -0,0 +1,523 @@
+"""Post-processing of response text, applied the same way to streamed and complete responses.
+
+Name the processors to run, in order, with ``RunParams(postprocessors=[...])``::
+
+    params = RunParams(messages=msgs, postprocessors=["strip_thinking", "strip_fences", "trim"])
+
+Every processor works incrementally: it receives the text delta by delta and
+holds back only what it cannot decide on yet (a possibly split ``</think>``
+tag, trailing whitespace, ...), which it releases with the next delta or at
+the end of the response. A complete response is processed as a single delta,
+so both modes produce the same text.
+
+Built-in processors:
+
+* ``normalize_newlines`` - ``\\r\\n`` and ``\\r`` become ``\\n``
+* ``strip_thinking`` - drops ``<think>...</think>`` blocks
+* ``strip_fences`` - removes a markdown code fence wrapping the whole response
+* ``trim`` - strips leading and trailing whitespace
+* ``normalize_text`` - plain quotes and spaces, no zero-width characters (see :mod:`prompti.textnorm`)
+* ``repair_json`` - keeps only the first JSON value and repairs trailing commas and truncation
+
+Register more with :func:`register_postprocessor`.
+"""
+
+from __future__ import annotations
+
+import json
+import logging
+import math
+import re
+from collections.abc import Callable
+from typing import Union
+
+from .message import Message, ModelResponse, StreamingChoice, StreamingModelResponse
+from .textnorm import normalize_text
+
+__all__ = [
+    "Postprocessor",
+    "PostprocessChain",
+    "LengthLimit",
+    "RepairJSON",
+    "ResponsePostprocessor",
+    "StopAt",
+    "estimate_message_tokens",
+    "estimate_tokens",
+    "register_postprocessor",
+]
+
+logger = logging.getLogger(__name__)
+
+_FENCE = "```"
+# whitespace, up to a full fence and whitespace at the end of the text
+_FENCE_TAIL = re.compile(r"\s*`{0,3}\s*\Z")
+
+
+class Postprocessor:
+    """Incremental text transformation; subclasses override :meth:`feed` and :meth:`flush`."""
+
+    # set by processors that end the text early, e.g. "stop" or "length"
+    finish_reason: str | None = None
+
+    def feed(self, text: str) -> str:
+        """Consume the next delta and return the text that can be emitted now."""
+        return text
+
+    def flush(self) -> str:
+        """Return whatever is still held back at the end of the response."""
+        return ""
+
+
+class NormalizeNewlines(Postprocessor):
+    """Convert ``\\r\\n`` and lone ``\\r`` line endings to ``\\n``."""
+
+    def __init__(self) -> None:
+        self._cr = False
+
+    def feed(self, text: str) -> str:
+        if self._cr:
+            text = "\r" + text
+            self._cr = False
+        # a trailing \r may be the first half of \r\n
+        if text.endswith("\r"):
+            self._cr = True
+            text = text[:-1]
+        return text.replace("\r\n", "\n").replace("\r", "\n")
+
+    def flush(self) -> str:
+        held, self._cr = self._cr, False
+        return "\n" if held else ""
+
+
+def _partial_suffix(text: str, tag: str) -> int:
+    """Length of the longest suffix of ``text`` that is a proper prefix of ``tag``."""
+    for size in range(min(len(text), len(tag) - 1), 0, -1):
+        if text.endswith(tag[:size]):
+            return size
+    return 0
+
+
+class StripThinking(Postprocessor):
+    """Remove ``open ... close`` blocks (``<think>...</think>`` by default); an unclosed block is dropped."""
+
+    def __init__(self, open_tag: str = "<think>", close_tag: str = "</think>") -> None:
+        self.open_tag = open_tag
+        self.close_tag = close_tag
+        self._inside = False
+        self._held = ""
+
+    def feed(self, text: str) -> str:
+        buffer = self._held + text
+        self._held = ""
+        out: list[str] = []
+        while buffer:
+            tag = self.close_tag if self._inside else self.open_tag
+            index = buffer.find(tag)
+            if index >= 0:
+                if not self._inside:
+                    out.append(buffer[:index])
+                buffer = buffer[index + len(tag) :]
+                self._inside = not self._inside
+                continue
+            keep = _partial_suffix(buffer, tag)
+            if not self._inside:
+                out.append(buffer[: len(buffer) - keep])
+            self._held = buffer[len(buffer) - keep :]
+            break
+        return "".join(out)
+
+    def flush(self) -> str:
+        held = "" if self._inside else self._held
+        self._inside = False
+        self._held = ""
+        return held
+
+
+class StripMarkdownFences(Postprocessor):
+    """Remove a ```` ```lang ```` fence wrapping the whole response, e.g. around JSON output.
+
+    Only the opening fence line and a closing fence at the very end are
+    removed; text without an opening fence passes through unchanged.
+    """
+
+    def __init__(self) -> None:
+        self._state = "start"  # "start" until the first line is known, then "fenced" or "plain"
+        self._held = ""
+
+    def feed(self, text: str) -> str:
+        if self._state == "plain":
+            return text
+        buffer = self._held + text
+        self._held = ""
+        if self._state == "start":
+            head = buffer.lstrip()
+            if (len(head) < len(_FENCE) and _FENCE.startswith(head)) or (head.startswith(_FENCE) and "\n" not in head):
+                # not enough text yet to tell whether this is a fence line
+                self._held = buffer
+                return ""
+            if not head.startswith(_FENCE):
+                self._state = "plain"
+                return buffer
+            self._state = "fenced"
+            buffer = head[head.index("\n") + 1 :]
+        # hold back a possible closing fence until more text or the end arrives
+        tail = _FENCE_TAIL.search(buffer)
+        cut = tail.start() if tail else len(buffer)
+        self._held = buffer[cut:]
+        return buffer[:cut]
+
+    def flush(self) -> str:
+        held = self._held
+        fenced = self._state == "fenced"
+        self._state = "start"
+        self._held = ""
+        if fenced and held.strip() == _FENCE:
+            return ""
+        return held
+
+
+class TrimWhitespace(Postprocessor):
+    """Strip leading and trailing whitespace from the response."""
+
+    def __init__(self) -> None:
+        self._started = False
+        self._held = ""
+
+    def feed(self, text: str) -> str:
+        if not self._started:
+            text = text.lstrip()
+            if not text:
+                return ""
+            self._started = True
+        text = self._held + text
+        kept = text.rstrip()
+        # trailing whitespace is only emitted once more text follows it
+        self._held = text[len(kept) :]
+        return kept
+
+    def flush(self) -> str:
+        self._started = False
+        self._held = ""
+        return ""
+
+
+class NormalizeText(Postprocessor):
+    """Apply :func:`prompti.textnorm.normalize_text`; it works per character, so nothing is held back."""
+
+    def feed(self, text: str) -> str:
+        return normalize_text(text)
+
+
+_CLOSERS = {"{": "}", "[": "]"}
+
+
+class RepairJSON(Postprocessor):
+    """Keep only the first JSON object or array of the response and repair what a model commonly gets wrong.
+
+    Text before the value (prose, an opening fence) and after it is dropped,
+    trailing commas are removed and a value cut off by the length limit is
+    closed. The value streams through as it is written. A response without
+    JSON passes unchanged; output that is still not valid JSON is logged.
+    Used to emulate JSON mode, see :mod:`prompti.model_client.json_mode`.
+    """
+
+    def __init__(self) -> None:
+        self._reset()
+
+    def _reset(self) -> None:
+        self._prefix = ""  # text before the value, returned if no value starts
+        self._text = ""  # the value emitted so far
+        self._held = ""  # a comma and the whitespace after it
+        self._stack: list[str] = []
+        self._in_string = False
+        self._escape = False
+        self._last = ""  # last character outside of strings
+        self._done = False
+
+    def feed(self, text: str) -> str:
+        out: list[str] = []
+        for ch in text:
+            if self._done:
+                break
+            if not self._stack and ch not in _CLOSERS:
+                self._prefix += ch
+                continue
+            if self._in_string:
+                if self._escape:
+                    self._escape = False
+                elif ch == "\\":
+                    self._escape = True
+                elif ch == '"':
+                    self._in_string = False
+                out.append(ch)
+                continue
+            if ch.isspace():
+                if self._held:
+                    self._held += ch
+                else:
+                    out.append(ch)
+                continue
+            if self._held:
+                # a comma directly before a closing bracket is dropped
+                if ch not in "}]":
+                    out.append(self._held)
+                self._held = ""
+            self._last = ch
+            if ch == ",":
+                self._held = ch
+                continue
+            if ch in _CLOSERS:
+                self._stack.append(_CLOSERS[ch])
+            elif ch == self._stack[-1]:
+                self._stack.pop()
+                self._done = not self._stack
+            elif ch == '"':
+                self._in_string = True
+            out.append(ch)
+        emitted = "".join(out)
+        self._text += emitted
+        return emitted
+
+    def flush(self) -> str:
+        if not self._text:
+            text = self._prefix
+        else:
+            text = ""
+            if self._in_string:
+                text += '\\"' if self._escape else '"'
+            elif self._last == ":":
+                text += "null"
+            text += "".join(reversed(self._stack))
+            try:
+                json.loads(self._text + text)
+            except ValueError as e:
+                logger.warning("Response is not valid JSON after repair: %s", e)
+        self._reset()
+        return text
+
+
+_REGISTRY: dict[str, Callable[[], Postprocessor]] = {
+    "normalize_newlines": NormalizeNewlines,
+    "strip_thinking": StripThinking,
+    "strip_fences": StripMarkdownFences,
+    "trim": TrimWhitespace,
+    "normalize_text": NormalizeText,
+    "repair_json": RepairJSON,
+}
+
+
+def register_postprocessor(name: str, factory: Callable[[], Postprocessor]) -> None:
+    """Make ``factory`` available as ``name`` in ``RunParams.postprocessors``.
+
+    ``factory`` is called once per response choice, so processors may keep state.
+    """
+    _REGISTRY[name] = factory
+
+
+class StopAt(Postprocessor):
+    """Cut the text at the first of ``stops`` and drop everything after it.
+
+    Emulates the ``stop`` parameter for providers and models that ignore it.
+    """
+
+    def __init__(self, stops: list[str]) -> None:
+        self.stops = [stop for stop in stops if stop]
+        self._held = ""
+
+    def feed(self, text: str) -> str:
+        if self.finish_reason:
+            return ""
+        buffer = self._held + text
+        self._held = ""
+        hits = [index for index in (buffer.find(stop) for stop in self.stops) if index >= 0]
+        if hits:
+            self.finish_reason = "stop"
+            return buffer[: min(hits)]
+        # hold back a possible start of a stop sequence
+        keep = max((_partial_suffix(buffer, stop) for stop in self.stops), default=0)
+        self._held = buffer[len(buffer) - keep :]
+        return buffer[: len(buffer) - keep]
+
+    def flush(self) -> str:
+        held, self._held = self._held, ""
+        return held
+
+
+# CJK characters are about one token each, other text about four characters per token
+_CJK = re.compile(r"[\u3040-\u30ff\u3400-\u4dbf\u4e00-\u9fff\uac00-\ud7af\uf900-\ufaff\uff00-\uffef]")
+
+
+def estimate_tokens(text: str) -> int:
+    """Rough output token count of ``text`` without a tokenizer."""
+    cjk = len(_CJK.findall(text))
+    return cjk + math.ceil((len(text) - cjk) / 4)
+
+
+# per-message overhead of the chat format (role, separators)
+_MESSAGE_OVERHEAD = 4
+
+
+def _message_text(message: Message) -> str:
+    content = message.content
+    if isinstance(content, list):
+        content = "\n".join(p.get("text", "") for p in content if isinstance(p, dict) and p.get("type") == "text")
+    text = content or ""
+    if message.tool_calls:
+        text += json.dumps(message.tool_calls, ensure_ascii=False)
+    return text
+
+
+def estimate_message_tokens(messages: list[Message]) -> int:
+    """Rough prompt token count of ``messages``, see :func:`estimate_tokens`."""
+    return sum(estimate_tokens(_message_text(m)) + _MESSAGE_OVERHEAD for m in messages)
+
+
+class LengthLimit(Postprocessor):
+    """Cut the text once it exceeds ``max_chars`` or an estimated ``max_tokens``.
+
+    Guards against runaway generations on providers that treat ``max_tokens``
+    as advisory; tokens are estimated as in :func:`estimate_tokens`.
+    """
+
+    def __init__(self, max_chars: int | None = None, max_tokens: int | None = None) -> None:
+        self.max_chars = max_chars
+        self.max_tokens = max_tokens
+        self._chars = 0
+        self._tokens = 0.0
+
+    def feed(self, text: str) -> str:
+        if self.finish_reason:
+            return ""
+        for index, ch in enumerate(text):
+            chars = self._chars + 1
+            tokens = self._tokens + (1.0 if _CJK.match(ch) else 0.25)
+            if (self.max_chars is not None and chars > self.max_chars) or (
+                self.max_tokens is not None and tokens > self.max_tokens
+            ):
+                self.finish_reason = "length"
+                return text[:index]
+            self._chars, self._tokens = chars, tokens
+        return text
+
+
+class PostprocessChain(Postprocessor):
+    """Run several processors in order, each one fed with the output of the previous.
+
+    The raw text is first cut at the first of the ``stop`` sequences and at
+    the ``max_chars``/``max_tokens`` limits, when given.
+    """
+
+    def __init__(
+        self,
+        names: list[str],
+        stop: list[str] | None = None,
+        max_chars: int | None = None,
+        max_tokens: int | None = None,
+    ) -> None:
+        unknown = [name for name in names if name not in _REGISTRY]
+        if unknown:
+            raise ValueError(f"Unknown postprocessors: {', '.join(unknown)}; available: {', '.join(sorted(_REGISTRY))}")
+        self.processors: list[Postprocessor] = []
+        if stop:
+            self.processors.append(StopAt(stop))
+        if max_chars is not None or max_tokens is not None:
+            self.processors.append(LengthLimit(max_chars, max_tokens))
+        self.processors.extend(_REGISTRY[name]() for name in names)
+
+    @property
+    def finish_reason(self) -> str | None:  # type: ignore[override]
+        """Why the text was ended early, if a processor did."""
+        return next((p.finish_reason for p in self.processors if p.finish_reason), None)
+
+    def feed(self, text: str) -> str:
+        for processor in self.processors:
+            text = processor.feed(text)
+        return text
+
+    def flush(self) -> str:
+        text = ""
+        for processor in self.processors:
+            text = processor.feed(text) + processor.flush()
+        return text
+
+    def process(self, text: str) -> str:
+        """Process a complete text."""
+        return self.feed(text) + self.flush()
+
+
+class ResponsePostprocessor:
+    """Apply a :class:`PostprocessChain` to the text of every choice in a response stream.
+
+    Choices whose text is ended early (a stop sequence, a length limit) get
+    that ``finish_reason`` and no further text; :attr:`stopped` tells the
+    caller when all ``choices`` are done so it can abort the stream.
+    """
+
+    def __init__(
+        self,
+        names: list[str],
+        stop: list[str] | None = None,
+        choices: int = 1,
+        max_chars: int | None = None,
+        max_tokens: int | None = None,
+    ) -> None:
+        self.names = names
+        self.choices = choices
+        self._options = {"stop": stop, "max_chars": max_chars, "max_tokens": max_tokens}
+        self._chains: dict[int, PostprocessChain] = {}
+        self._stopped: set[int] = set()
+        # validate the names before the call is made
+        self._new_chain()
+
+    @property
+    def stopped(self) -> bool:
+        """Whether the text of every choice has been ended early."""
+        return len(self._stopped) >= self.choices
+
+    def _new_chain(self) -> PostprocessChain:
+        return PostprocessChain(self.names, **self._options)
+
+    def _chain(self, index: int) -> PostprocessChain:
+        if index not in self._chains:
+            self._chains[index] = self._new_chain()
+        return self._chains[index]
+
+    def apply(self, response: Union[ModelResponse, StreamingModelResponse]) -> None:
+        """Rewrite the text of ``response`` in place."""
+        if response.error or not response.choices:
+            return
+        if isinstance(response, ModelResponse):
+            for choice in response.choices:
+                if isinstance(choice.message.content, str):
+                    chain = self._new_chain()
+                    choice.message.content = chain.process(choice.message.content)
+                    if chain.finish_reason:
+                        choice.finish_reason = chain.finish_reason
+            return
+        for choice in response.choices:
+            content = choice.delta.content
+            if content is not None and not isinstance(content, str):
+                continue
+            if choice.index in self._stopped:
+                if content is not None:
+                    choice.delta.content = ""
+                continue
+            chain = self._chain(choice.index)
+            text = chain.feed(content) if content else ""
+            if chain.finish_reason:
+                self._stopped.add(choice.index)
+                choice.finish_reason = chain.finish_reason
+            if choice.finish_reason:
+                text += self._chains.pop(choice.index).flush()
+            if content is not None or text:
+                choice.delta.content = text
+
+    def finish(self) -> StreamingModelResponse | None:
+        """Return a final chunk with text still held back when a stream ended without a finish reason."""
+        choices = []
+        for index, chain in sorted(self._chains.items()):
+            text = chain.flush()
+            if text:
+                choices.append(StreamingChoice(index=index, delta=Message(role="assistant", content=text)))
+        self._chains.clear()
+        return StreamingModelResponse(choices=choices) if choices else None
@@ -0,0 +1,166 @@
+"""Model prices for cost accounting.
+
+A :class:`PriceTable` maps models to their USD price per million prompt and
+completion tokens. The package ships a table (``prompti/data/prices.json``);
+:func:`arefresh_prices` replaces the active one with a newer table from a
+URL, so cost numbers stay current between releases::
+
+    await arefresh_prices("https://example.com/prices.json", public_key=PRICES_PUBLIC_KEY)
+    cost = get_price_table().cost("gpt-4o-2024-08-06", response.usage, provider="openai")
+
+A remote table must come with a detached Ed25519 signature of its exact
+bytes, base64 encoded, at ``<url>.sig`` (or ``signature_url``). It is checked
+against ``public_key`` (raw 32-byte key, base64) before the table is used;
+this needs the ``signing`` extra (``cryptography``). Remote entries are
+merged over the bundled ones, and on any failure the active table is kept.
+
+Models are looked up as ``provider/model``, then by model name, then by the
+longest known name the model starts with, so dated snapshots such as
+``claude-3-5-sonnet-20241022`` use the price of ``claude-3-5-sonnet``.
+"""
+
+from __future__ import annotations
+
+import base64
+import binascii
+from importlib import resources
+
+import httpx
+from pydantic import BaseModel, ValidationError
+
+from .message import Usage
+
+__all__ = [
+    "ModelPrice",
+    "PriceTable",
+    "PriceTableError",
+    "arefresh_prices",
+    "get_price_table",
+    "set_price_table",
+    "verify_signature",
+]
+
+
+class PriceTableError(Exception):
+    """Raised when a price table cannot be loaded or its signature is invalid."""
+
+
+class ModelPrice(BaseModel):
+    """USD per million tokens."""
+
+    input: float
+    output: float
+
+
+class PriceTable(BaseModel):
+    """Prices keyed by ``provider/model``."""
+
+    updated: str | None = None
+    models: dict[str, ModelPrice] = {}
+
+    @classmethod
+    def from_json(cls, data: bytes | str) -> PriceTable:
+        """Parse a price table document.
+
+        Raises:
+            PriceTableError: If ``data`` is not a valid price table.
+        """
+        try:
+            return cls.model_validate_json(data)
+        except ValidationError as e:
+            raise PriceTableError(f"Invalid price table: {e}") from e
+
+    @classmethod
+    def bundled(cls) -> PriceTable:
+        """Return the table shipped with the package."""
+        return cls.from_json(resources.files("prompti").joinpath("data/prices.json").read_bytes())
+
+    def merged(self, other: PriceTable) -> PriceTable:
+        """Return this table with the entries of ``other`` added or replacing ours."""
+        return PriceTable(updated=other.updated or self.updated, models={**self.models, **other.models})
+
+    def get(self, model: str, provider: str | None = None) -> ModelPrice | None:
+        """Return the price of ``model``, ``None`` if unknown."""
+        for key in (f"{provider}/{model}", model):
+            if key in self.models:
+                return self.models[key]
+        name = model.rsplit("/", 1)[-1]
+        best: tuple[int, ModelPrice] | None = None
+        for key, price in self.models.items():
+            known = key.rsplit("/", 1)[-1]
+            if name == known or name.startswith(known + "-"):
+                if best is None or len(known) > best[0]:
+                    best = (len(known), price)
+        return best[1] if best else None
+
+    def cost(self, model: str, usage: Usage | None, provider: str | None = None) -> float | None:
+        """Return the USD cost of ``usage`` on ``model``, ``None`` if the price or usage is unknown."""
+        price = self.get(model, provider)
+        if price is None or usage is None:
+            return None
+        return (usage.prompt_tokens * price.input + usage.completion_tokens * price.output) / 1_000_000
+
+
+def verify_signature(data: bytes, signature: bytes, public_key: str | bytes) -> None:
+    """Check the base64 Ed25519 ``signature`` of ``data``.
+
+    Raises:
+        PriceTableError: If the signature does not match or cannot be checked.
+    """
+    try:
+        from cryptography.exceptions import InvalidSignature
+        from cryptography.hazmat.primitives.asymmetric.ed25519 import Ed25519PublicKey
+    except ImportError as e:
+        raise PriceTableError("Verifying price table signatures needs the 'signing' extra (cryptography)") from e
+    try:
+        key = Ed25519PublicKey.from_public_bytes(base64.b64decode(public_key))
+        key.verify(base64.b64decode(signature.strip()), data)
+    except (InvalidSignature, ValueError, binascii.Error) as e:
+        raise PriceTableError("Price table signature is invalid") from e
+
+
+_table: PriceTable | None = None
+
+
+def get_price_table() -> PriceTable:
+    """Return the active price table, the bundled one unless refreshed or replaced."""
+    global _table
+    if _table is None:
+        _table = PriceTable.bundled()
+    return _table
+
+
+def set_price_table(table: PriceTable | None) -> None:
+    """Replace the active price table; ``None`` restores the bundled one."""
+    global _table
+    _table = table
+
+
+async def arefresh_prices(
+    url: str,
+    *,
+    public_key: str | bytes,
+    signature_url: str | None = None,
+    client: httpx.AsyncClient | None = None,
+) -> PriceTable:
+    """Download a signed price table, merge it over the bundled one and make it active.
+
+    Raises:
+        PriceTableError: If the download fails, the signature is invalid or
+            the document is not a price table. The active table is unchanged.
+    """
+    http = client or httpx.AsyncClient(timeout=httpx.Timeout(30))
+    try:
+        data = await http.get(url)
+        signature = await http.get(signature_url or f"{url}.sig")
+        data.raise_for_status()
+        signature.raise_for_status()
+    except httpx.HTTPError as e:
+        raise PriceTableError(f"Downloading the price table failed: {e}") from e
+    finally:
+        if client is None:
+            await http.aclose()
+    verify_signature(data.content, signature.content, public_key)
+    table = PriceTable.bundled().merged(PriceTable.from_json(data.content))
+    set_price_table(table)
+    return table
@@ -1,25 +1,53 @@
-"""Replay and recording utilities."""
+"""Replay and recording utilities.
+
+:class:`ModelClientRecorder` writes every request and response to a JSONL
+session file, including the request's :meth:`RunParams.canonical_hash`.
+:class:`ReplayClient` serves such a recording in tests: each call must match
+the next recorded request, otherwise :class:`ReplayMismatchError` shows how
+the prompt drifted instead of silently answering with the wrong fixture::
+
+    rows = [json.loads(line) for line in Path("fixtures/chat.jsonl").read_text().splitlines()]
+    client = ReplayClient(rows)
+    responses = [r async for r in client.arun(params)]
+"""
 
 from __future__ import annotations
 
+import difflib
 import json
 from collections.abc import AsyncGenerator, Callable, Iterable
-from typing import Union
+from typing import Any, Union
 from datetime import datetime, timezone
 from pathlib import Path
 from uuid import uuid4
 
 import aiofiles
-from prometheus_client import Counter
 
 from .message import Message, ModelResponse, StreamingModelResponse
 from .model_client import ModelClient, ModelConfig, RunParams
+from .telemetry import Counter
 
 
 class ReplayError(Exception):
     """Raised when replay encounters an error event."""
 
 
+class ReplayMismatchError(ReplayError):
+    """Raised when a request does not match the recorded one it is replayed for."""
+
+    def __init__(self, index: int, recorded_hash: str, incoming_hash: str, diff: str) -> None:
+        # position of the recorded request among those of the recording, from 0
+        self.index = index
+        self.recorded_hash = recorded_hash
+        self.incoming_hash = incoming_hash
+        # unified diff from the recorded to the incoming canonical request
+        self.diff = diff
+        super().__init__(
+            f"Request does not match recorded request #{index} "
+            f"(recorded {recorded_hash[:12]}, got {incoming_hash[:12]}):\n{diff}"
+        )
+
+
 _replay_counter = Counter("trace_replay_total", "replay summary", labelnames=["status"])
 _diff_tokens = Counter("trace_diff_tokens_total", "token diff")
 
@@ -58,7 +86,12 @@ class ModelClientRecorder(ModelClient):
     ) -> AsyncGenerator[Union[ModelResponse, StreamingModelResponse], None]:
         self._trace_id = str(uuid4())
         step = 0
-        meta = {"provider": self.cfg.provider, "model": self.cfg.model}
+        meta = {
+            "provider": self.cfg.provider,
+            "model": self.cfg.model,
+            "request_hash": params.canonical_hash(self.cfg.model),
+            "request": params.canonical_request(self.cfg.model),
+        }
         log_file = self.output_dir / f"rollout-{datetime.now(timezone.utc).date().isoformat()}-{self.session_id}.jsonl"
         async with aiofiles.open(log_file, "a") as f:
             await self._write_row(
@@ -156,3 +189,75 @@ class ReplayEngine:
                     raise ReplayError(row.get("payload"))
         finally:
             _replay_counter.labels(status).inc()
+
+
+def _request_diff(recorded: Any, incoming: Any) -> str:
+    def lines(value: Any) -> list[str]:
+        return json.dumps(value, indent=2, sort_keys=True, ensure_ascii=False, default=str).splitlines()
+
+    diff = difflib.unified_diff(lines(recorded), lines(incoming), "recorded", "incoming", lineterm="")
+    return "\n".join(diff)
+
+
+def verify_request(row: dict[str, Any], params: RunParams, model: str | None = None, index: int = 0) -> None:
+    """Check ``params`` against the recorded request ``row``, number ``index`` of its recording.
+
+    Rows recorded before request hashes were stored are not checked.
+
+    Raises:
+        ReplayMismatchError: If the canonical hashes differ.
+    """
+    meta = row.get("meta") or {}
+    recorded_hash = meta.get("request_hash")
+    if recorded_hash is None:
+        return
+    incoming_hash = params.canonical_hash(model)
+    if incoming_hash == recorded_hash:
+        return
+    if "request" in meta:
+        diff = _request_diff(meta["request"], params.canonical_request(model))
+    else:
+        incoming = [m.model_dump() for m in params.messages]
+        diff = _request_diff(row["payload"], incoming) or "(messages are equal; other request fields differ)"
+    raise ReplayMismatchError(index, recorded_hash, incoming_hash, diff)
+
+
+class ReplayClient(ModelClient):
+    """Serve the responses of a recorded session instead of calling a provider.
+
+    Each call is matched against the next recorded request with
+    :func:`verify_request` and answered with the responses recorded after
+    it. ``cfg`` defaults to the provider and model of the first request.
+    """
+
+    provider = "replay"
+
+    def __init__(self, rows: Iterable[dict], cfg: ModelConfig | None = None, **kwargs: Any) -> None:
+        # file order: every recorded call numbers its steps from 0
+        rows = list(rows)
+        requests = [row for row in rows if row["direction"] == "req"]
+        if cfg is None:
+            meta = requests[0].get("meta", {}) if requests else {}
+            cfg = ModelConfig(provider=meta.get("provider"), model=meta.get("model"))
+        super().__init__(cfg, **kwargs)
+        self._exchanges: list[tuple[dict, list[dict]]] = []
+        self._replayed = 0
+        for row in rows:
+            if row["direction"] == "req":
+                self._exchanges.append((row, []))
+            elif self._exchanges:
+                self._exchanges[-1][1].append(row)
+
+    async def _run(self, params: RunParams) -> AsyncGenerator[Union[ModelResponse, StreamingModelResponse], None]:
+        if not self._exchanges:
+            raise ReplayError("No recorded request left to replay")
+        request, responses = self._exchanges.pop(0)
+        index, self._replayed = self._replayed, self._replayed + 1
+        verify_request(request, params, self.cfg.model, index)
+        for row in responses:
+            if row["direction"] == "error":
+                raise ReplayError(row.get("payload"))
+            if row["direction"] == "delta":
+                yield StreamingModelResponse.model_validate(row["payload"])
+            elif row["direction"] == "res":
+                yield ModelResponse.model_validate(row["payload"])
@@ -0,0 +1,34 @@
+"""Translate message roles to what each model accepts.
+
+Newer OpenAI reasoning models take their instructions in a ``developer``
+message and treat ``system`` as a legacy alias, while most other models only
+know ``system``. Prompts can use either role; :func:`map_roles` rewrites them
+for the target model so the same template works everywhere.
+"""
+
+from __future__ import annotations
+
+from .message import Message
+
+# Model name prefixes that expect ``developer`` instead of ``system``.
+DEVELOPER_ROLE_MODEL_PREFIXES = ("o1", "o3", "o4", "gpt-5")
+
+
+def default_role_map(model: str | None) -> dict[str, str]:
+    """Return the built-in role mapping for ``model``.
+
+    Provider prefixes such as ``openai/o3-mini`` are ignored when matching.
+    """
+    name = (model or "").rsplit("/", 1)[-1]
+    if name.startswith(DEVELOPER_ROLE_MODEL_PREFIXES):
+        return {"system": "developer"}
+    return {"developer": "system"}
+
+
+def map_roles(messages: list[Message], role_map: dict[str, str]) -> list[Message]:
+    """Return ``messages`` with roles renamed according to ``role_map``."""
+    if not any(msg.role in role_map for msg in messages):
+        return messages
+    return [
+        msg.model_copy(update={"role": role_map[msg.role]}) if msg.role in role_map else msg for msg in messages
+    ]
@@ -0,0 +1,218 @@
+"""Shadow traffic for model migrations.
+
+:class:`ShadowClient` serves every request from the primary client and, for
+a sample of requests, sends the same request to a secondary ("shadow")
+client at the same time. The shadow response is never returned and its
+errors never reach the caller; once both have finished, latency, token
+usage, cost and output similarity are compared and recorded::
+
+    client = ShadowClient(
+        create_client(current_cfg),
+        create_client(candidate_cfg),
+        sample_rate=0.05,
+        prices={"gpt-4o": (2.5, 10.0), "qwen-max": (0.4, 1.2)},
+    )
+    async for r in client.arun(params):  # primary responses only
+        ...
+    await client.aclose()  # waits for the shadow requests still running
+
+The shadow request runs in an asyncio task owned by the wrapper, so the
+primary response is not slowed down by it. :meth:`ShadowClient.aclose`
+waits for those tasks; :meth:`ShadowClient.drain` does so without closing.
+"""
+
+from __future__ import annotations
+
+import asyncio
+import random
+from collections.abc import AsyncGenerator, Callable, Sequence
+from time import perf_counter
+from typing import Any, Union
+
+from pydantic import BaseModel
+
+from .compare import diff_responses
+from .message import ModelResponse, StreamingModelResponse, Usage
+from .model_client import ModelClient, ModelConfig, RunParams
+from .pricing import get_price_table
+from .telemetry import get_metrics
+
+__all__ = ["ShadowClient", "ShadowComparison", "ShadowOutcome"]
+
+
+class ShadowOutcome(BaseModel):
+    """What one side of a shadowed request produced."""
+
+    provider: str | None = None
+    model: str | None = None
+    latency_s: float
+    text: str = ""
+    usage: Usage | None = None
+    cost: float | None = None
+    error: str | None = None
+
+
+class ShadowComparison(BaseModel):
+    """Primary and shadow results of one sampled request."""
+
+    primary: ShadowOutcome
+    shadow: ShadowOutcome
+    # 0..1 token-level similarity of the two output texts, None if either side failed
+    similarity: float | None = None
+    # 0..1 embedding similarity, only with ``embed``
+    semantic_similarity: float | None = None
+    # markdown diff of the two outputs, see :func:`prompti.compare.diff_responses`
+    diff: str | None = None
+
+
+class _Collector:
+    """Accumulates the text, usage and error of a response stream."""
+
+    def __init__(self, cfg: ModelConfig, prices: dict[str, tuple[float, float]]) -> None:
+        self.cfg = cfg
+        self.prices = prices
+        self.start = perf_counter()
+        self.parts: list[str] = []
+        self.usage: Usage | None = None
+        self.error: str | None = None
+
+    def add(self, response: Union[ModelResponse, StreamingModelResponse]) -> None:
+        if response.error:
+            self.error = str(response.error.get("message") or response.error)
+        self.parts.append(response.get_text_content() or "")
+        self.usage = response.usage or self.usage
+
+    def outcome(self) -> ShadowOutcome:
+        cost = None
+        price = self.prices.get(self.cfg.model or "")
+        if price and self.usage:
+            cost = (self.usage.prompt_tokens * price[0] + self.usage.completion_tokens * price[1]) / 1_000_000
+        elif self.cfg.model:
+            cost = get_price_table().cost(self.cfg.model, self.usage, self.cfg.provider)
+        return ShadowOutcome(
+            provider=self.cfg.provider,
+            model=self.cfg.model,
+            latency_s=perf_counter() - self.start,
+            text="".join(self.parts),
+            usage=self.usage,
+            cost=cost,
+            error=self.error,
+        )
+
+
+class ShadowClient:
+    """Serve requests from ``primary`` and mirror a sample of them to ``shadow``.
+
+    Args:
+        primary: Client whose responses are returned.
+        shadow: Client that receives the mirrored requests.
+        sample_rate: Fraction of requests mirrored, from 0 to 1.
+        prices: USD per million ``(prompt, completion)`` tokens by model name,
+            used for the cost comparison; other models are priced with
+            :func:`prompti.pricing.get_price_table`.
+        on_compare: Called with each :class:`ShadowComparison`, e.g. to log it.
+        embed: Embedding function for the semantic similarity, see
+            :func:`prompti.compare.diff_responses`.
+    """
+
+    def __init__(
+        self,
+        primary: ModelClient,
+        shadow: ModelClient,
+        *,
+        sample_rate: float = 0.0,
+        prices: dict[str, tuple[float, float]] | None = None,
+        on_compare: Callable[[ShadowComparison], Any] | None = None,
+        embed: Callable[[str], Sequence[float]] | None = None,
+    ) -> None:
+        if not 0 <= sample_rate <= 1:
+            raise ValueError("sample_rate must be between 0 and 1")
+        self.primary = primary
+        self.shadow = shadow
+        self.sample_rate = sample_rate
+        self.prices = prices or {}
+        self.on_compare = on_compare
+        self.embed = embed
+        self._tasks: set[asyncio.Task[ShadowOutcome]] = set()
+
+    @property
+    def cfg(self) -> ModelConfig:
+        """The primary client's configuration."""
+        return self.primary.cfg
+
+    async def _run_shadow(self, params: RunParams) -> ShadowOutcome:
+        collector = _Collector(self.shadow.cfg, self.prices)
+        try:
+            async for response in self.shadow.arun(params):
+                collector.add(response)
+        except Exception as exc:  # the shadow must never affect the caller
+            collector.error = f"{type(exc).__name__}: {exc}"
+        return collector.outcome()
+
+    def _record(self, primary: ShadowOutcome, task: asyncio.Task[ShadowOutcome]) -> None:
+        self._tasks.discard(task)
+        if task.cancelled():
+            return
+        shadow = task.result()
+        comparison = ShadowComparison(primary=primary, shadow=shadow)
+        if primary.error is None and shadow.error is None:
+            labels = (primary.model or "primary", shadow.model or "shadow")
+            diff = diff_responses(primary.text, shadow.text, embed=self.embed, labels=labels)
+            comparison.similarity = diff.token_similarity
+            comparison.semantic_similarity = diff.semantic_similarity
+            comparison.diff = diff.markdown
+
+        metrics = get_metrics()
+        pair = (primary.model, shadow.model)
+        metrics.shadow_requests.labels(*pair, "error" if shadow.error else "success").inc()
+        for role, outcome in (("primary", primary), ("shadow", shadow)):
+            metrics.shadow_latency.labels(*pair, role).observe(outcome.latency_s)
+            if outcome.usage:
+                metrics.shadow_completion_tokens.labels(*pair, role).inc(outcome.usage.completion_tokens)
+            if outcome.cost is not None:
+                metrics.shadow_cost.labels(*pair, role).inc(outcome.cost)
+        if comparison.similarity is not None:
+            metrics.shadow_similarity.labels(*pair).observe(comparison.similarity)
+        if self.on_compare is not None:
+            self.on_compare(comparison)
+
+    async def arun(self, params: RunParams) -> AsyncGenerator[Union[ModelResponse, StreamingModelResponse], None]:
+        """Yield the primary responses; mirror the request to the shadow client if sampled."""
+        if self.sample_rate == 0 or random.random() >= self.sample_rate:
+            async for response in self.primary.arun(params):
+                yield response
+            return
+
+        task = asyncio.create_task(self._run_shadow(params.model_copy(deep=True)))
+        self._tasks.add(task)
+        collector = _Collector(self.primary.cfg, self.prices)
+        completed = False
+        try:
+            async for response in self.primary.arun(params):
+                collector.add(response)
+                yield response
+            completed = True
+        except Exception as exc:
+            collector.error = f"{type(exc).__name__}: {exc}"
+            raise
+        finally:
+            if completed or collector.error:
+                primary = collector.outcome()
+                task.add_done_callback(lambda t: self._record(primary, t))
+            else:
+                # the caller abandoned the stream; there is nothing to compare against
+                task.cancel()
+                self._tasks.discard(task)
+
+    async def drain(self) -> None:
+        """Wait for the shadow requests still running."""
+        while self._tasks:
+            await asyncio.gather(*self._tasks, return_exceptions=True)
+            # let the done callbacks record the comparisons
+            await asyncio.sleep(0)
+
+    async def aclose(self) -> None:
+        """Wait for pending shadow requests, then close both clients."""
+        await self.drain()
+        await self.primary.aclose()
+        await self.shadow.aclose()
@@ -0,0 +1,615 @@
+"""Helpers for consuming the response stream of :meth:`ModelClient.arun`."""
+
+from __future__ import annotations
+
+import asyncio
+import inspect
+import json
+from collections import deque
+from collections.abc import AsyncGenerator, AsyncIterable, Iterable
+from typing import Any, Union
+
+from .message import Choice, Message, ModelResponse, StreamingModelResponse, ToolCall, ToolCallDelta
+
+Response = Union[ModelResponse, StreamingModelResponse]
+
+# SSE comment sent while the upstream is silent; EventSource clients ignore it
+HEARTBEAT = ": keep-alive\n\n"
+
+
+class StreamError(RuntimeError):
+    """Raised when the model returns an error in the middle of a piped stream."""
+
+    def __init__(self, error: dict[str, Any], text: str) -> None:
+        self.error = error
+        # what was written before the error
+        self.text = text
+        super().__init__(f"Model returned an error: {error.get('message', error)}")
+
+
+def stream_events(response: ModelResponse | StreamingModelResponse) -> list[dict[str, Any]]:
+    """Translate a model response into typed stream events.
+
+    Streaming chunks produce ``content_delta``, ``reasoning_delta``,
+    ``tool_call_delta`` and ``finish`` events per choice; complete responses
+    produce a single ``message`` event per choice. Usage and errors are
+    reported as ``usage`` and ``error`` events.
+    """
+    base = {"id": response.id, "model": response.model}
+    events: list[dict[str, Any]] = []
+
+    if response.error:
+        events.append({**base, "type": "error", "error": response.error})
+
+    if isinstance(response, StreamingModelResponse):
+        for choice in response.choices or []:
+            delta = choice.delta
+            if delta.reasoning_content:
+                events.append(
+                    {**base, "type": "reasoning_delta", "index": choice.index, "content": delta.reasoning_content}
+                )
+            if delta.content:
+                events.append({**base, "type": "content_delta", "index": choice.index, "content": delta.content})
+            for tool_call in delta.tool_calls or []:
+                events.append({**base, "type": "tool_call_delta", "index": choice.index, "tool_call": tool_call})
+            if choice.finish_reason:
+                events.append(
+                    {**base, "type": "finish", "index": choice.index, "finish_reason": choice.finish_reason}
+                )
+    else:
+        for choice in response.choices or []:
+            events.append(
+                {
+                    **base,
+                    "type": "message",
+                    "index": choice.index,
+                    "message": choice.message.model_dump(exclude_none=True),
+                    "finish_reason": choice.finish_reason,
+                }
+            )
+
+    if response.usage:
+        events.append({**base, "type": "usage", "usage": response.usage.model_dump()})
+    return events
+
+
+def sse_event(event: dict[str, Any]) -> str:
+    """Format a :func:`stream_events` event as a server-sent event."""
+    return f"event: {event['type']}\ndata: {json.dumps(event, ensure_ascii=False)}\n\n"
+
+
+async def asse_stream(
+    responses: AsyncIterable[Response], *, heartbeat_s: float | None = 15.0
+) -> AsyncGenerator[str, None]:
+    """Yield ``responses`` as server-sent events for a proxied stream.
+
+    Every response becomes the events of :func:`stream_events`, followed by a
+    final ``done`` event. While the upstream sends nothing for ``heartbeat_s``
+    seconds an SSE comment is sent so proxies and browsers keep the
+    connection open. If the upstream stream breaks (the connection drops or
+    the client raises), an ``error`` event with type ``upstream_disconnected``
+    ends the stream instead, so ``EventSource`` consumers see a clean end
+    rather than a hanging or reset connection::
+
+        async def handler(request):
+            body = asse_stream(client.arun(params))
+            return StreamingResponse(body, media_type="text/event-stream")
+    """
+    iterator = aiter(responses)
+    pending: asyncio.Future[Response] | None = None
+    try:
+        while True:
+            if pending is None:
+                pending = asyncio.ensure_future(anext(iterator))
+            done, _ = await asyncio.wait({pending}, timeout=heartbeat_s)
+            if not done:
+                yield HEARTBEAT
+                continue
+            task, pending = pending, None
+            try:
+                response = task.result()
+            except StopAsyncIteration:
+                break
+            except Exception as exc:
+                error = {"message": f"Upstream stream ended: {exc}", "type": "upstream_disconnected"}
+                yield sse_event({"type": "error", "error": error})
+                break
+            for event in stream_events(response):
+                yield sse_event(event)
+        yield sse_event({"type": "done"})
+    finally:
+        if pending is not None:
+            pending.cancel()
+            # the generator cannot be closed while the cancelled ``anext`` still runs it
+            await asyncio.wait({pending})
+        if hasattr(iterator, "aclose"):
+            await iterator.aclose()
+
+
+def _paced_text(response: Response) -> tuple[str, str] | None:
+    """Return the ``(reasoning, content)`` text :func:`apaced` spreads out, ``None`` to pass ``response`` on."""
+    if not isinstance(response, StreamingModelResponse) or response.error or len(response.choices or []) != 1:
+        return None
+    delta = response.choices[0].delta
+    content = delta.content if isinstance(delta.content, str) else ""
+    if isinstance(delta.content, list) or not (delta.reasoning_content or content):
+        return None
+    return delta.reasoning_content or "", content
+
+
+def _piece(response: StreamingModelResponse, reasoning: str, content: str, last: bool) -> StreamingModelResponse:
+    """Return a copy of ``response`` with only the given text; tool calls, finish and usage go on the ``last`` piece."""
+    choice = response.choices[0]
+    delta = choice.delta.model_copy(
+        update={
+            "reasoning_content": reasoning or None,
+            "content": content or None,
+            **({} if last else {"tool_calls": None, "citations": None}),
+        }
+    )
+    if last:
+        return response.model_copy(update={"choices": [choice.model_copy(update={"delta": delta})]})
+    choice = choice.model_copy(update={"delta": delta, "finish_reason": None})
+    return response.model_copy(update={"choices": [choice], "usage": None, "timing": None, "attempts": None})
+
+
+async def apaced(
+    responses: AsyncIterable[Response],
+    chars_per_sec: float = 60.0,
+    *,
+    max_lag_s: float = 2.0,
+    catch_up_s: float = 0.5,
+    tick_s: float = 0.02,
+) -> AsyncGenerator[Response, None]:
+    """Re-chunk ``responses`` into an even typing cadence for display.
+
+    Providers deliver text in bursts; this yields the same stream with the
+    text of each chunk spread out at ``chars_per_sec``, split into pieces
+    every ``tick_s`` seconds. Text that arrived more than ``max_lag_s``
+    seconds of typing ahead speeds the cadence up so the display never
+    falls further behind, and once the upstream ends whatever is left is
+    typed out within ``catch_up_s``. Reasoning text is paced like content.
+    Chunks without text (tool calls, usage, errors) and complete responses
+    are passed on unchanged, in order, once the text before them is shown::
+
+        async for chunk in apaced(client.arun(params), chars_per_sec=80):
+            render(chunk.get_text_content() or "")
+
+    Exceptions of the upstream are raised after the text received before
+    them has been typed out.
+    """
+    loop = asyncio.get_running_loop()
+    iterator = aiter(responses)
+    pending: asyncio.Future[Response] | None = None
+    queue: deque[Response] = deque()
+    # characters of the head of ``queue`` already yielded
+    offset = 0
+    backlog = 0
+    allowance = 0.0
+    last_tick = loop.time()
+    failure: BaseException | None = None
+    finished = False
+    try:
+        while True:
+            while queue and _paced_text(queue[0]) is None:
+                yield queue.popleft()
+            if not queue and finished:
+                break
+            if pending is None and not finished:
+                pending = asyncio.ensure_future(anext(iterator))
+            if pending is not None:
+                await asyncio.wait({pending}, timeout=tick_s if queue else None)
+            else:
+                await asyncio.sleep(tick_s)
+            if pending is not None and pending.done():
+                task, pending = pending, None
+                try:
+                    response = task.result()
+                except StopAsyncIteration:
+                    finished = True
+                except Exception as exc:
+                    failure, finished = exc, True
+                else:
+                    if not queue:
+                        # nothing was being typed: don't let the idle time turn into a burst
+                        allowance, last_tick = 0.0, loop.time()
+                    queue.append(response)
+                    text = _paced_text(response)
+                    backlog += len(text[0]) + len(text[1]) if text else 0
+
+            now = loop.time()
+            rate = max(chars_per_sec, backlog / (catch_up_s if finished else max_lag_s))
+            allowance += rate * (now - last_tick)
+            last_tick = now
+            while queue and allowance >= 1:
+                head = queue[0]
+                text = _paced_text(head)
+                if text is None:
+                    break
+                reasoning, content = text
+                size = min(int(allowance), len(reasoning) + len(content) - offset)
+                end = offset + size
+                last = end == len(reasoning) + len(content)
+                piece = _piece(
+                    head,
+                    reasoning[offset:end],
+                    content[max(offset - len(reasoning), 0) : max(end - len(reasoning), 0)],
+                    last,
+                )
+                offset, backlog, allowance = end, backlog - size, allowance - size
+                if last:
+                    queue.popleft()
+                    offset = 0
+                yield piece
+        if failure is not None:
+            raise failure
+    finally:
+        if pending is not None:
+            pending.cancel()
+            await asyncio.wait({pending})
+        if hasattr(iterator, "aclose"):
+            await iterator.aclose()
+
+
+class SharedChatStream:
+    """Fan one in-flight response stream out to several consumers.
+
+    Each :meth:`subscribe` returns an independent async iterator over the
+    same responses, e.g. for a UI, a logger and a safety scanner. Chunks are
+    kept for the life of the stream, so a late subscriber first gets
+    everything sent so far (``replay=False`` starts at the live edge).
+    Consumers read at their own pace; the upstream is only paused while a
+    subscriber is ``max_lag`` chunks behind (``None`` never pauses it). A
+    consumer that stops iterating or closes its iterator no longer holds
+    the stream back. Upstream exceptions are raised in every subscriber
+    after the chunks before them::
+
+        async with SharedChatStream(client.arun(params)) as shared:
+            ui, audit = shared.subscribe(), shared.subscribe()
+            await asyncio.gather(render(ui), scan(audit))
+
+    The upstream is consumed from the first iteration of a subscriber until
+    it ends or :meth:`aclose` is called. A subscriber that is never iterated
+    or closed counts as lagging.
+    """
+
+    def __init__(self, responses: AsyncIterable[Response], *, max_lag: int | None = 64) -> None:
+        if max_lag is not None and max_lag < 1:
+            raise ValueError("max_lag must be at least 1")
+        self._responses = responses
+        self.max_lag = max_lag
+        self._history: list[Response] = []
+        # subscriber -> index of its next chunk in ``_history``
+        self._positions: dict[int, int] = {}
+        self._next_id = 0
+        self._changed = asyncio.Event()
+        self._task: asyncio.Task[None] | None = None
+        self._error: BaseException | None = None
+        self.done = False
+
+    @property
+    def history(self) -> list[Response]:
+        """The responses received so far."""
+        return list(self._history)
+
+    def subscribe(self, *, replay: bool = True) -> AsyncGenerator[Response, None]:
+        """Return a new consumer, from the first response with ``replay`` or from the next one without."""
+        subscriber = self._next_id
+        self._next_id += 1
+        self._positions[subscriber] = 0 if replay else len(self._history)
+        return self._consume(subscriber)
+
+    def _notify(self) -> None:
+        self._changed.set()
+        self._changed = asyncio.Event()
+
+    async def _wait(self, ready: Any) -> None:
+        while True:
+            changed = self._changed
+            if ready():
+                return
+            await changed.wait()
+
+    def _lagging(self) -> bool:
+        if self.max_lag is None:
+            return False
+        return any(len(self._history) - position >= self.max_lag for position in self._positions.values())
+
+    async def _pump(self) -> None:
+        iterator = aiter(self._responses)
+        try:
+            async for response in iterator:
+                self._history.append(response)
+                self._notify()
+                await self._wait(lambda: not self._lagging())
+        except Exception as exc:
+            self._error = exc
+        finally:
+            self.done = True
+            self._notify()
+            if hasattr(iterator, "aclose"):
+                await iterator.aclose()
+
+    async def _consume(self, subscriber: int) -> AsyncGenerator[Response, None]:
+        try:
+            if self._task is None:
+                self._task = asyncio.ensure_future(self._pump())
+            while True:
+                await self._wait(lambda: self._positions[subscriber] < len(self._history) or self.done)
+                position = self._positions[subscriber]
+                if position == len(self._history):
+                    if self._error is not None:
+                        raise self._error
+                    return
+                self._positions[subscriber] = position + 1
+                self._notify()
+                yield self._history[position]
+        finally:
+            self._positions.pop(subscriber, None)
+            self._notify()
+
+    async def aclose(self) -> None:
+        """Stop consuming the upstream; subscribers end after the chunks already received."""
+        if self._task is not None and not self._task.done():
+            self._task.cancel()
+            await asyncio.wait({self._task})
+        elif self._task is None and hasattr(self._responses, "aclose"):
+            await self._responses.aclose()
+        self.done = True
+        self._notify()
+
+    async def __aenter__(self) -> SharedChatStream:
+        return self
+
+    async def __aexit__(self, *exc_info: Any) -> None:
+        await self.aclose()
+
+
+class ToolCallAccumulator:
+    """Reassemble complete tool calls from the fragments of a response stream.
+
+    OpenAI streams each tool call as fragments with the same ``index``: the
+    first carries the id and name, the following ones pieces of the JSON
+    arguments. :meth:`add` returns the calls a response completed, so a tool
+    can start while the model is still writing the next call::
+
+        calls = ToolCallAccumulator()
+        async for response in client.arun(params):
+            for call in calls.add(response):
+                tasks.append(asyncio.create_task(run_tool(call.function.name, call.parse_arguments())))
+
+    A call is complete once a fragment of a later call arrives or the choice
+    finishes; :meth:`finish` completes the rest when the stream ended without
+    a finish reason. Complete responses, e.g. of ``stream=False``, add their
+    tool calls whole. Only the choice with index ``choice`` is read.
+    """
+
+    def __init__(self, choice: int = 0) -> None:
+        self.choice = choice
+        self._calls: dict[int, ToolCall] = {}
+        self._completed: set[int] = set()
+
+    @property
+    def calls(self) -> list[ToolCall]:
+        """Every call so far, complete or not, by index."""
+        return [self._calls[index] for index in sorted(self._calls)]
+
+    def _complete(self, indexes: Iterable[int]) -> list[ToolCall]:
+        completed = [index for index in sorted(indexes) if index not in self._completed]
+        self._completed.update(completed)
+        return [self._calls[index] for index in completed]
+
+    def add(self, response: Response) -> list[ToolCall]:
+        """Merge the tool calls of ``response`` and return the calls it completed."""
+        choice = next((c for c in response.choices or [] if c.index == self.choice), None)
+        if choice is None:
+            return []
+        if isinstance(response, StreamingModelResponse):
+            deltas = ToolCallDelta.from_openai(choice.delta.tool_calls or [])
+            finished = choice.finish_reason is not None
+        else:
+            deltas = ToolCallDelta.from_openai(choice.message.tool_calls or [])
+            finished = True
+        for delta in deltas:
+            self._calls.setdefault(delta.index, ToolCall()).merge(delta)
+        if finished:
+            return self.finish()
+        if not deltas:
+            return []
+        # calls are streamed one after another, so a later call completes the earlier ones
+        last = max(delta.index for delta in deltas)
+        return self._complete(index for index in self._calls if index < last)
+
+    def finish(self) -> list[ToolCall]:
+        """Complete and return the calls that are not complete yet."""
+        return self._complete(list(self._calls))
+
+
+class _ChoiceBuilder:
+    def __init__(self, index: int) -> None:
+        self.index = index
+        self.role = "assistant"
+        self.text: list[str] = []
+        # multimodal content parts, kept when a provider streams content as a list
+        self.parts: list[dict[str, Any]] = []
+        self.reasoning: list[str] = []
+        self.citations: list[Any] = []
+        self.tool_calls = ToolCallAccumulator(index)
+        self.finish_reason: str | None = None
+        self.logprobs: dict[str, Any] | None = None
+
+    def add(self, message: Message, finish_reason: str | None, logprobs: dict[str, Any] | None) -> None:
+        if message.role:
+            self.role = message.role
+        if isinstance(message.content, str):
+            self.text.append(message.content)
+        elif message.content:
+            self.parts.extend(message.content)
+        if message.reasoning_content:
+            self.reasoning.append(message.reasoning_content)
+        self.citations.extend(message.citations or [])
+        if finish_reason:
+            self.finish_reason = finish_reason
+        if logprobs:
+            if self.logprobs is None:
+                self.logprobs = {}
+            for key, value in logprobs.items():
+                # token logprobs arrive as one list per chunk
+                if isinstance(value, list) and isinstance(self.logprobs.get(key), list):
+                    self.logprobs[key] = self.logprobs[key] + value
+                else:
+                    self.logprobs[key] = value
+
+    def build(self) -> Choice:
+        text = "".join(self.text)
+        content: str | list[dict[str, Any]] | None = text or None
+        if self.parts:
+            content = [{"type": "text", "text": text}, *self.parts] if text else self.parts
+        tool_calls = [call.to_openai() for call in self.tool_calls.calls]
+        message = Message(
+            role=self.role,
+            content=content,
+            reasoning_content="".join(self.reasoning) or None,
+            tool_calls=tool_calls or None,
+            citations=self.citations or None,
+        )
+        return Choice(index=self.index, message=message, finish_reason=self.finish_reason, logprobs=self.logprobs)
+
+
+class ModelResponseBuilder:
+    """Aggregate the responses of a call into one complete :class:`ModelResponse`.
+
+    Text and reasoning deltas are concatenated and tool call fragments merged
+    (see :class:`ToolCallAccumulator`), per choice. The last finish reason,
+    usage, timing and attempts are kept, as is an error. Identifiers such
+    as ``id`` and ``model`` are taken from the first response that has them.
+    Complete responses are added like a single delta, so the result is the
+    same with and without streaming.
+    """
+
+    def __init__(self) -> None:
+        self._choices: dict[int, _ChoiceBuilder] = {}
+        self._fields: dict[str, Any] = {}
+        self._extra: dict[str, Any] = {}
+
+    def add(self, response: Response) -> None:
+        """Merge the next response of the call."""
+        for name in ("id", "created", "model", "system_fingerprint", "prompt_filter_results"):
+            value = getattr(response, name, None)
+            if value is not None:
+                self._fields.setdefault(name, value)
+        for name in ("usage", "error", "timing", "attempts"):
+            value = getattr(response, name)
+            if value is not None:
+                self._fields[name] = value
+        self._extra.update(response.extra)
+        for choice in response.choices or []:
+            builder = self._choices.get(choice.index)
+            if builder is None:
+                builder = self._choices[choice.index] = _ChoiceBuilder(choice.index)
+            message = choice.delta if isinstance(response, StreamingModelResponse) else choice.message
+            builder.add(message, choice.finish_reason, choice.logprobs)
+            builder.tool_calls.add(response)
+
+    def build(self) -> ModelResponse:
+        """Return the complete response of the responses added so far."""
+        choices = [self._choices[index].build() for index in sorted(self._choices)]
+        return ModelResponse(object="chat.completion", choices=choices, extra=dict(self._extra), **self._fields)
+
+
+async def acollect(responses: AsyncIterable[Response]) -> ModelResponse:
+    """Consume a response stream and return it as one complete :class:`ModelResponse`.
+
+    Use it to stream a call, e.g. for first-token latency, and still work
+    with the complete answer::
+
+        response = await acollect(client.arun(params))
+        response.get_text_content(), response.get_tool_calls(), response.usage, response.timing
+
+    An error ends up in ``response.error`` instead of being raised.
+    """
+    builder = ModelResponseBuilder()
+    async for response in responses:
+        builder.add(response)
+    return builder.build()
+
+
+def collect(responses: Iterable[Response]) -> ModelResponse:
+    """Sync variant of :func:`acollect`, e.g. for :meth:`SyncModelClient.run`."""
+    builder = ModelResponseBuilder()
+    for response in responses:
+        builder.add(response)
+    return builder.build()
+
+
+async def _maybe_await(value: Any) -> None:
+    if inspect.isawaitable(value):
+        await value
+
+
+async def _flush(writer: Any) -> None:
+    if hasattr(writer, "drain"):
+        await writer.drain()
+    elif hasattr(writer, "flush"):
+        await _maybe_await(writer.flush())
+
+
+async def apipe_to(
+    responses: AsyncIterable[Response],
+    writer: Any,
+    *,
+    flush: bool = False,
+    encoding: str | None = None,
+) -> str:
+    """Write the text deltas of ``responses`` to ``writer`` as they arrive.
+
+    ``writer`` may be a text file, an ``aiofiles`` file, an
+    ``asyncio.StreamWriter`` or anything with a sync or async ``write``. Text is
+    encoded with ``encoding`` when given; ``asyncio.StreamWriter`` defaults to
+    UTF-8 since it only accepts bytes. With ``flush=True`` the writer is
+    flushed (or drained) after every chunk, otherwise once at the end::
+
+        reader, writer = await asyncio.open_connection(host, port)
+        text = await apipe_to(client.arun(params), writer, flush=True)
+
+    Returns:
+        The complete text written.
+
+    Raises:
+        StreamError: If a response carries an error; text received before it
+            has already been written.
+    """
+    if encoding is None and isinstance(writer, asyncio.StreamWriter):
+        encoding = "utf-8"
+    written: list[str] = []
+    async for response in responses:
+        if response.error:
+            raise StreamError(response.error, "".join(written))
+        text = response.get_text_content()
+        if not text:
+            continue
+        written.append(text)
+        await _maybe_await(writer.write(text.encode(encoding) if encoding else text))
+        if flush:
+            await _flush(writer)
+    if not flush:
+        await _flush(writer)
+    return "".join(written)
+
+
+def pipe_to(responses: Iterable[Response], writer: Any, *, flush: bool = False, encoding: str | None = None) -> str:
+    """Sync variant of :func:`apipe_to` for :meth:`SyncModelClient.run` and sync writers."""
+    written: list[str] = []
+    for response in responses:
+        if response.error:
+            raise StreamError(response.error, "".join(written))
+        text = response.get_text_content()
+        if not text:
+            continue
+        written.append(text)
+        writer.write(text.encode(encoding) if encoding else text)
+        if flush and hasattr(writer, "flush"):
+            writer.flush()
+    if not flush and hasattr(writer, "flush"):
+        writer.flush()
+    return "".join(written)
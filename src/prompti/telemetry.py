@@ -0,0 +1,296 @@
+"""Prometheus metrics for model clients with configurable names and buckets.
+
+``prometheus-client`` comes with the ``metrics`` extra. Without it the
+collectors below are no-ops, so the library works the same and simply
+records nothing.
+"""
+
+from __future__ import annotations
+
+import threading
+from typing import Any
+
+from pydantic import BaseModel
+
+try:
+    from prometheus_client import REGISTRY, CollectorRegistry, Counter, Gauge, Histogram
+
+    METRICS_ENABLED = True
+except ImportError:  # installed without the ``metrics`` extra
+    METRICS_ENABLED = False
+
+    class _NoopMetric:
+        """Accepts the collector calls used in prompti and records nothing."""
+
+        # same as prometheus_client.Histogram.DEFAULT_BUCKETS
+        DEFAULT_BUCKETS = (
+            0.005, 0.01, 0.025, 0.05, 0.075, 0.1, 0.25, 0.5, 0.75, 1.0, 2.5, 5.0, 7.5, 10.0, float("inf")
+        )
+
+        def __init__(self, *args: Any, **kwargs: Any) -> None:
+            pass
+
+        def labels(self, *args: Any, **kwargs: Any) -> _NoopMetric:
+            return self
+
+        def inc(self, amount: float = 1) -> None:
+            pass
+
+        def dec(self, amount: float = 1) -> None:
+            pass
+
+        def set(self, value: float) -> None:
+            pass
+
+        def observe(self, amount: float) -> None:
+            pass
+
+        def time(self) -> _NoopMetric:
+            return self
+
+        def __enter__(self) -> _NoopMetric:
+            return self
+
+        def __exit__(self, *exc: Any) -> None:
+            pass
+
+    class CollectorRegistry:  # type: ignore[no-redef]
+        """Stand-in registry; collectors are never registered."""
+
+        def register(self, collector: Any) -> None:
+            pass
+
+        def unregister(self, collector: Any) -> None:
+            pass
+
+    Counter = Gauge = Histogram = _NoopMetric  # type: ignore[misc,assignment]
+    REGISTRY = CollectorRegistry()
+
+
+class TelemetryConfig(BaseModel):
+    """Naming and bucket configuration for the model client metrics.
+
+    ``namespace`` is prepended to every metric name (``myapp_llm_requests_total``),
+    which avoids collisions with other libraries exporting ``llm_*`` metrics.
+    """
+
+    namespace: str = ""
+    latency_buckets: list[float] = list(Histogram.DEFAULT_BUCKETS)
+    first_token_buckets: list[float] = [0.1, 0.25, 0.5, 1, 2, 5, 10]
+    token_gap_buckets: list[float] = list(Histogram.DEFAULT_BUCKETS)
+    token_buckets: list[float] = [16, 64, 256, 1024, 4096, 16384, 65536]
+
+
+class ClientMetrics:
+    """The collectors used by :class:`~prompti.model_client.ModelClient`."""
+
+    def __init__(self, config: TelemetryConfig, registry: CollectorRegistry | None = REGISTRY) -> None:
+        """Create and register all collectors according to ``config``."""
+        self.config = config
+        self.registry = registry
+        ns = config.namespace
+        self.tokens = Counter(
+            "llm_tokens_total", "Tokens in/out", labelnames=["direction"], namespace=ns, registry=registry
+        )
+        self.request_latency = Histogram(
+            "llm_request_latency_seconds",
+            "LLM latency",
+            labelnames=["provider"],
+            buckets=config.latency_buckets,
+            namespace=ns,
+            registry=registry,
+        )
+        self.inflight = Gauge(
+            "llm_inflight_requests",
+            "Inflight LLM requests",
+            labelnames=["provider", "is_error"],
+            namespace=ns,
+            registry=registry,
+        )
+        self.requests = Counter(
+            "llm_requests_total",
+            "LLM request results",
+            labelnames=["provider", "result", "is_error", "error_class"],
+            namespace=ns,
+            registry=registry,
+        )
+        self.first_token = Histogram(
+            "llm_first_token_latency_seconds",
+            "Time to first token",
+            labelnames=["provider", "model"],
+            buckets=config.first_token_buckets,
+            namespace=ns,
+            registry=registry,
+        )
+        self.token_gap = Histogram(
+            "llm_stream_intertoken_gap_seconds",
+            "Gap between streamed tokens",
+            labelnames=["provider", "model"],
+            buckets=config.token_gap_buckets,
+            namespace=ns,
+            registry=registry,
+        )
+        self.rate_limit_wait = Histogram(
+            "llm_rate_limit_wait_seconds",
+            "Time calls waited for the client-side rate limit",
+            labelnames=["provider", "model"],
+            buckets=[0.1, 0.5, 1, 5, 15, 30, 60],
+            namespace=ns,
+            registry=registry,
+        )
+        self.prompt_tokens = Counter(
+            "llm_prompt_tokens_total",
+            "Prompt tokens sent to the provider",
+            labelnames=["provider", "model"],
+            namespace=ns,
+            registry=registry,
+        )
+        self.completion_tokens = Counter(
+            "llm_completion_tokens_total",
+            "Completion tokens received from the provider",
+            labelnames=["provider", "model"],
+            namespace=ns,
+            registry=registry,
+        )
+        self.completion_tokens_per_request = Histogram(
+            "llm_completion_tokens_per_request",
+            "Completion tokens per request",
+            labelnames=["provider", "model"],
+            buckets=config.token_buckets,
+            namespace=ns,
+            registry=registry,
+        )
+
+        self.language_requests = Counter(
+            "llm_requests_by_language_total",
+            "Requests by detected user language",
+            labelnames=["provider", "model", "lang"],
+            namespace=ns,
+            registry=registry,
+        )
+
+        self.shadow_requests = Counter(
+            "llm_shadow_requests_total",
+            "Requests mirrored to a shadow model",
+            labelnames=["primary_model", "shadow_model", "result"],
+            namespace=ns,
+            registry=registry,
+        )
+        self.shadow_latency = Histogram(
+            "llm_shadow_latency_seconds",
+            "Latency of shadowed requests by side",
+            labelnames=["primary_model", "shadow_model", "role"],
+            buckets=config.latency_buckets,
+            namespace=ns,
+            registry=registry,
+        )
+        self.shadow_completion_tokens = Counter(
+            "llm_shadow_completion_tokens_total",
+            "Completion tokens of shadowed requests by side",
+            labelnames=["primary_model", "shadow_model", "role"],
+            namespace=ns,
+            registry=registry,
+        )
+        self.shadow_cost = Counter(
+            "llm_shadow_cost_usd_total",
+            "Cost of shadowed requests by side",
+            labelnames=["primary_model", "shadow_model", "role"],
+            namespace=ns,
+            registry=registry,
+        )
+        self.shadow_similarity = Histogram(
+            "llm_shadow_output_similarity",
+            "Similarity of primary and shadow output",
+            labelnames=["primary_model", "shadow_model"],
+            buckets=[0.1, 0.25, 0.5, 0.75, 0.9, 0.95, 0.99, 1.0],
+            namespace=ns,
+            registry=registry,
+        )
+
+        self.tenant_requests = Counter(
+            "llm_tenant_requests_total",
+            "Gateway requests by tenant and result",
+            labelnames=["tenant", "model", "result"],
+            namespace=ns,
+            registry=registry,
+        )
+        self.tenant_tokens = Counter(
+            "llm_tenant_tokens_total",
+            "Tokens used by tenant",
+            labelnames=["tenant", "direction"],
+            namespace=ns,
+            registry=registry,
+        )
+        self.tenant_cost = Counter(
+            "llm_tenant_cost_usd_total",
+            "Cost of gateway requests by tenant",
+            labelnames=["tenant"],
+            namespace=ns,
+            registry=registry,
+        )
+
+    def record_usage(self, provider: str | None, model: str | None, prompt_tokens: int, completion_tokens: int) -> None:
+        """Account the token usage reported for one request."""
+        self.tokens.labels("in").inc(prompt_tokens)
+        self.tokens.labels("out").inc(completion_tokens)
+        self.prompt_tokens.labels(provider, model).inc(prompt_tokens)
+        self.completion_tokens.labels(provider, model).inc(completion_tokens)
+        self.completion_tokens_per_request.labels(provider, model).observe(completion_tokens)
+
+    def unregister(self) -> None:
+        """Remove the collectors from their registry."""
+        if self.registry is None:
+            return
+        for collector in (
+            self.tokens,
+            self.request_latency,
+            self.inflight,
+            self.requests,
+            self.first_token,
+            self.token_gap,
+            self.rate_limit_wait,
+            self.prompt_tokens,
+            self.completion_tokens,
+            self.completion_tokens_per_request,
+            self.language_requests,
+            self.shadow_requests,
+            self.shadow_latency,
+            self.shadow_completion_tokens,
+            self.shadow_cost,
+            self.shadow_similarity,
+            self.tenant_requests,
+            self.tenant_tokens,
+            self.tenant_cost,
+        ):
+            self.registry.unregister(collector)
+
+
+_metrics: ClientMetrics | None = None
+_lock = threading.Lock()
+
+
+def configure_telemetry(
+    config: TelemetryConfig | None = None, registry: CollectorRegistry | None = REGISTRY
+) -> ClientMetrics:
+    """Create the model client metrics from ``config``.
+
+    Metrics are created lazily on first use with the default configuration;
+    call this at startup to change names or buckets. Calling it again replaces
+    the previously registered collectors.
+    """
+    global _metrics
+    with _lock:
+        if _metrics is not None:
+            _metrics.unregister()
+        _metrics = ClientMetrics(config or TelemetryConfig(), registry)
+        return _metrics
+
+
+def get_metrics() -> ClientMetrics:
+    """Return the active metrics, creating them with defaults if needed."""
+    global _metrics
+    if _metrics is None:
+        with _lock:
+            if _metrics is None:
+                _metrics = ClientMetrics(TelemetryConfig())
+    return _metrics
@@ -6,14 +6,17 @@ import json
 import re
 import ast
 from time import perf_counter
-from typing import Any
+from typing import TYPE_CHECKING, Any
 
 from jinja2 import StrictUndefined
 from jinja2.sandbox import SandboxedEnvironment
-from prometheus_client import Histogram
 from pydantic import BaseModel, Field
 
 from .model_client import ModelConfig
+from .telemetry import Histogram
+
+if TYPE_CHECKING:
+    from .fragments import PromptLibrary
 
 _env = SandboxedEnvironment(undefined=StrictUndefined)
 
@@ -27,6 +30,22 @@ _format_latency = Histogram(
 SNAKE = re.compile(r"^[a-z][a-z0-9_]*$")
 
 
+def render_text(
+    text: str, variables: dict[str, Any], fragments: PromptLibrary | None = None, _stack: tuple[str, ...] = ()
+) -> str:
+    """Render ``text`` with Jinja; with ``fragments`` it can include them via ``fragment('name@version')``."""
+    if fragments is None:
+        return _env.from_string(text).render(**variables)
+
+    def fragment(ref: str, version: str | None = None) -> str:
+        item = fragments.get(ref, version)
+        if item.name in _stack:
+            raise ValueError(f"Prompt fragment {item.name!r} includes itself: {' -> '.join((*_stack, item.name))}")
+        return render_text(item.text, variables, fragments, (*_stack, item.name))
+
+    return _env.from_string(text).render(**variables, fragment=fragment)
+
+
 def _selector_to_flat(selector: dict[str, Any]) -> str:
     """Flatten selector to a lowercase JSON string for token matching."""
     return json.dumps(selector, separators=(",", ":")).lower()
@@ -206,8 +225,13 @@ class PromptTemplate(BaseModel):
         *,
         variant: str | None = None,
         selector: dict[str, Any] | None = None,
+        fragments: PromptLibrary | None = None,
     ) -> tuple[list[dict], Variant]:
-        """Render the template and return messages in OpenAI format."""
+        """Render the template and return messages in OpenAI format.
+
+        ``fragments`` makes the library's fragments available to the messages
+        as ``fragment('name@version')``, see :mod:`prompti.fragments`.
+        """
         start = perf_counter()
         try:
             selector = selector or variables
@@ -236,7 +260,7 @@ class PromptTemplate(BaseModel):
                             item_type = item.get("type")
                             if item_type == "text":
                                 text = item.get("text", "")
-                                rendered = _env.from_string(text).render(**variables)
+                                rendered = render_text(text, variables, fragments)
                                 # 只有当渲染后的文本不为空时才添加
                                 if rendered.strip():
                                     rendered_content.append({"type": "text", "text": rendered})
@@ -244,7 +268,7 @@ class PromptTemplate(BaseModel):
                                 # Render image_url if it contains template variables
                                 other_key = [k for k in item.keys() if k != "type"][0]
                                 image_url = item.get(other_key, "")
-                                rendered_url = _env.from_string(image_url).render(**variables)
+                                rendered_url = render_text(image_url, variables, fragments)
                                 parsed_rendered_url = _parse_list_or_return_string(rendered_url)
                                 if isinstance(parsed_rendered_url, str):
                                     if parsed_rendered_url:
@@ -260,14 +284,14 @@ class PromptTemplate(BaseModel):
                         else:
                             # Handle string content
                             text = str(item)
-                            rendered = _env.from_string(text).render(**variables)
+                            rendered = render_text(text, variables, fragments)
                             # 只有当渲染后的文本不为空时才添加
                             if rendered.strip():
                                 rendered_content.append({"type": "text", "text": rendered})
                 else:
                     # Handle single string content
                     text = str(content)
-                    rendered = _env.from_string(text).render(**variables)
+                    rendered = render_text(text, variables, fragments)
                     rendered_content = rendered
 
                 # 只有当消息内容不为空时才添加到最终结果中
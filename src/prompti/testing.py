@@ -0,0 +1,246 @@
+"""Helpers for testing code built on prompti without calling a provider.
+
+:func:`snapshot_request` returns the request body a client would send for a
+:class:`RunParams`, after the same role mapping and message normalization a
+real call applies. Compare it against a stored snapshot to catch changes in
+provider translation::
+
+    body = snapshot_request("openai", params, model="gpt-4o")
+    assert body == json.loads(Path("snapshots/chat.json").read_text())
+
+:func:`check_provider_invariants` is a property-based check of a provider's
+translation: it generates random conversations (system prompts, multi-turn
+chats, tool calls and results, sampling and token parameters) and asserts
+that no message is lost, roles keep their order, token parameters stay
+within the requested bounds and OpenAI-format bodies translate back to the
+same request. A failing example is shrunk before it is reported::
+
+    def test_my_gateway_translation():
+        check_provider_invariants("my-gateway", examples=500)
+"""
+
+from __future__ import annotations
+
+import json
+import random
+from typing import Any
+
+from .message import Message
+from .model_client.base import ModelConfig, RunParams
+from .model_client.factory import create_sync_client
+from .model_client.transport import decoded_request_body
+
+__all__ = ["InvariantViolation", "check_provider_invariants", "snapshot_request"]
+
+_REDACTED = "[REDACTED]"
+# request body fields that carry credentials (litellm passes them as arguments)
+_SECRET_FIELDS = {"api_key"}
+
+
+def snapshot_request(provider: str | ModelConfig, params: RunParams, *, model: str = "test-model") -> dict[str, Any]:
+    """Return the JSON body ``provider`` would send for ``params``.
+
+    Args:
+        provider: A provider name, or a full :class:`ModelConfig` when the
+            config affects translation (``role_map``, ``max_tokens``, ...).
+        params: The call to translate.
+        model: Model name used when ``provider`` is a name.
+
+    Returns:
+        The request body as plain JSON data; credentials are redacted so the
+        result can be stored as a snapshot.
+    """
+    cfg = provider if isinstance(provider, ModelConfig) else ModelConfig(provider=provider, model=model)
+    client = create_sync_client(cfg)
+    try:
+        params = client._normalize_messages(client._prepare_json_mode(params))
+        build_request = getattr(client, "_build_request", None)
+        if build_request is not None:
+            # HTTP clients: decode the exact bytes that would go on the wire
+            body = json.loads(decoded_request_body(build_request(params)))
+        else:
+            body = json.loads(json.dumps(client._build_request_data(params), default=str))
+    finally:
+        client.close()
+    return {key: _REDACTED if key in _SECRET_FIELDS else value for key, value in body.items()}
+
+
+class InvariantViolation(AssertionError):
+    """Raised by :func:`check_provider_invariants` with the smallest failing request found."""
+
+    def __init__(self, provider: str, invariant: str, detail: str, params: RunParams) -> None:
+        self.provider = provider
+        self.invariant = invariant
+        self.params = params
+        request = params.model_dump_json(exclude_defaults=True, exclude={"trace_context"})
+        super().__init__(f"{provider}: {invariant} violated: {detail}\nrequest: {request}")
+
+
+# text fragments for generated messages: quoting, escapes, newlines and non-ASCII
+_FRAGMENTS = [
+    "hello",
+    "What's the weather?",
+    'say "hi"',
+    "line\nbreak",
+    "\\path",
+    "你好",
+    "🙂",
+    "{json}",
+    " spaced ",
+]
+# request body keys a provider may use for the output token limit
+_MAX_TOKEN_KEYS = ("max_tokens", "max_completion_tokens", "max_output_tokens")
+
+
+def _random_text(rng: random.Random) -> str:
+    return " ".join(rng.choice(_FRAGMENTS) for _ in range(rng.randint(1, 4)))
+
+
+def _random_params(rng: random.Random) -> RunParams:
+    messages = [Message.create_system(_random_text(rng))] if rng.random() < 0.5 else []
+    for turn in range(rng.randint(1, 5)):
+        messages.append(Message.create_user(_random_text(rng)))
+        if rng.random() < 0.3:
+            calls = [
+                {
+                    "id": f"call_{turn}_{i}",
+                    "type": "function",
+                    "function": {"name": "lookup", "arguments": json.dumps({"q": _random_text(rng)})},
+                }
+                for i in range(rng.randint(1, 2))
+            ]
+            messages.append(Message(role="assistant", content=None, tool_calls=calls))
+            for call in calls:
+                messages.append(Message(role="tool", content=_random_text(rng), tool_call_id=call["id"]))
+        if turn < 4 or rng.random() < 0.5:
+            messages.append(Message(role="assistant", content=_random_text(rng)))
+    if messages[-1].role == "assistant":
+        messages.append(Message.create_user(_random_text(rng)))
+    return RunParams(
+        messages=messages,
+        stream=rng.random() < 0.5,
+        temperature=rng.choice([None, 0.0, round(rng.uniform(0, 2), 2)]),
+        top_p=rng.choice([None, 1.0, round(rng.uniform(0.01, 1), 2)]),
+        max_tokens=rng.choice([None, 1, rng.randint(1, 32_000)]),
+    )
+
+
+def _content_text(content: Any) -> str:
+    if isinstance(content, str):
+        return content
+    if isinstance(content, list):
+        return "".join(part.get("text") or "" for part in content if isinstance(part, dict))
+    return ""
+
+
+def _wire_messages(body: dict[str, Any]) -> list[tuple[str, str, list[str]]]:
+    """Return ``(role, text, tool call ids)`` per message of a request body."""
+    messages = []
+    if "system" in body:  # APIs that take the system prompt outside the messages
+        messages.append(("system", _content_text(body["system"]), []))
+    for message in body.get("messages") or []:
+        ids = [call.get("id") for call in message.get("tool_calls") or []]
+        if message.get("tool_call_id"):
+            ids.append(message["tool_call_id"])
+        messages.append((message.get("role"), _content_text(message.get("content")), ids))
+    return messages
+
+
+def _violation(cfg: ModelConfig, params: RunParams) -> tuple[str, str] | None:
+    """Return ``(invariant, detail)`` for the first invariant ``params`` breaks on ``cfg``."""
+    client = create_sync_client(cfg)
+    try:
+        expected_messages = client._normalize_messages(params).messages
+    finally:
+        client.close()
+    body = snapshot_request(cfg, params.model_copy(deep=True))
+    expected = [
+        (m.role, _content_text(m.content), [c.get("id") for c in m.tool_calls or []] + [m.tool_call_id or ""])
+        for m in expected_messages
+    ]
+    expected = [(role, text, [i for i in ids if i]) for role, text, ids in expected]
+    actual = _wire_messages(body)
+
+    if len(actual) != len(expected):
+        return "no message loss", f"{len(expected)} messages became {len(actual)}"
+    for index, ((role, text, ids), (wire_role, wire_text, wire_ids)) in enumerate(zip(expected, actual)):
+        if text not in wire_text:
+            return "no message loss", f"text of message {index} is {wire_text!r}, expected {text!r}"
+        if ids != wire_ids:
+            return "no message loss", f"tool call ids of message {index} are {wire_ids}, expected {ids}"
+    roles, wire_roles = [m[0] for m in expected], [m[0] for m in actual]
+    if roles != wire_roles:
+        return "role order preserved", f"roles {wire_roles}, expected {roles}"
+
+    requested = params.max_tokens if params.max_tokens is not None else cfg.max_tokens
+    limits = [body[key] for key in _MAX_TOKEN_KEYS if body.get(key) is not None]
+    if len(limits) > 1:
+        return "token params within bounds", f"several output token limits {limits}"
+    if limits and not (isinstance(limits[0], int) and 1 <= limits[0] <= (requested or limits[0])):
+        return "token params within bounds", f"output token limit {limits[0]}, requested {requested}"
+    if requested is not None and not limits:
+        return "token params within bounds", f"requested output token limit {requested} was dropped"
+    temperature = body.get("temperature")
+    requested_temperature = params.temperature if params.temperature is not None else cfg.temperature
+    if temperature is not None and temperature != requested_temperature:
+        return "token params within bounds", f"temperature {temperature}, requested {requested_temperature}"
+
+    wire_messages = body.get("messages") or []
+    if "system" not in body and all(isinstance(m.get("content"), (str, type(None))) for m in wire_messages):
+        # OpenAI format: the body's messages read back into the same request
+        again = params.model_copy(update={"messages": [Message.model_validate(m) for m in body["messages"]]})
+        again_body = snapshot_request(cfg, again)
+        if again_body["messages"] != body["messages"]:
+            return "round trip", "translating the request's own messages again changed them"
+    return None
+
+
+def _shrink(cfg: ModelConfig, params: RunParams, invariant: str) -> RunParams:
+    """Drop messages and parameters while ``invariant`` still fails."""
+    changed = True
+    while changed:
+        changed = False
+        candidates = [
+            params.model_copy(update={"messages": params.messages[:i] + params.messages[i + 1 :]})
+            for i in range(len(params.messages))
+            if len(params.messages) > 1
+        ]
+        candidates += [
+            params.model_copy(update={field: None})
+            for field in ("temperature", "top_p", "max_tokens")
+            if getattr(params, field) is not None
+        ]
+        for candidate in candidates:
+            found = _violation(cfg, candidate)
+            if found is not None and found[0] == invariant:
+                params, changed = candidate, True
+                break
+    return params
+
+
+def check_provider_invariants(
+    provider: str | ModelConfig, *, examples: int = 200, seed: int = 0, model: str = "test-model"
+) -> None:
+    """Check ``provider``'s request translation on ``examples`` random requests.
+
+    Args:
+        provider: A provider name, or a full :class:`ModelConfig` to check a
+            configuration (``role_map``, ``max_tokens``, ...).
+        examples: Number of random requests to translate.
+        seed: Seed of the generator; the same seed checks the same requests.
+        model: Model name used when ``provider`` is a name.
+
+    Raises:
+        InvariantViolation: For the first broken invariant, with the smallest
+            request that still breaks it.
+    """
+    cfg = provider if isinstance(provider, ModelConfig) else ModelConfig(provider=provider, model=model)
+    rng = random.Random(seed)
+    for _ in range(examples):
+        params = _random_params(rng)
+        found = _violation(cfg, params)
+        if found is not None:
+            invariant = found[0]
+            params = _shrink(cfg, params, invariant)
+            detail = (_violation(cfg, params) or found)[1]
+            raise InvariantViolation(cfg.provider, invariant, detail, params)
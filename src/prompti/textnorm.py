@@ -0,0 +1,73 @@
+"""Normalization of look-alike characters that break downstream parsers.
+
+Models (and text pasted into prompts) produce typographic quotes, unusual
+spaces and invisible characters where parsers expect plain ASCII.
+:func:`normalize_text` replaces them:
+
+* curly single and double quotes become ``'`` and ``"``
+* no-break, narrow, thin, figure and other fixed-width spaces become ``" "``
+* zero-width spaces, word joiners and byte-order marks are removed
+
+The zero-width joiner and non-joiner are kept: emoji sequences and several
+scripts need them. The ideographic space (U+3000) is kept as well, since it
+is ordinary typesetting in CJK text.
+
+Use ``RunParams(postprocessors=["normalize_text"])`` for model output and
+``ModelConfig(normalize_prompt_text=True)`` for the messages sent.
+"""
+
+from __future__ import annotations
+
+from .message import Message
+
+__all__ = ["normalize_message_text", "normalize_text"]
+
+_QUOTES = {
+    "‘": "'",  # left single quotation mark
+    "’": "'",  # right single quotation mark
+    "‚": "'",  # single low-9 quotation mark
+    "‛": "'",  # single high-reversed-9 quotation mark
+    "“": '"',  # left double quotation mark
+    "”": '"',  # right double quotation mark
+    "„": '"',  # double low-9 quotation mark
+    "‟": '"',  # double high-reversed-9 quotation mark
+}
+# no-break space, en quad .. hair space, narrow no-break space, medium mathematical space
+_SPACES = {chr(code): " " for code in (0x00A0, *range(0x2000, 0x200B), 0x202F, 0x205F)}
+# zero-width space, word joiner, zero-width no-break space (BOM), mongolian vowel separator
+_INVISIBLE = {chr(code): None for code in (0x200B, 0x2060, 0xFEFF, 0x180E)}
+
+_TABLE = str.maketrans({**_QUOTES, **_SPACES, **_INVISIBLE})
+
+
+def normalize_text(text: str) -> str:
+    """Replace typographic quotes and unusual spaces and drop invisible characters."""
+    return text.translate(_TABLE)
+
+
+def _normalize_content(message: Message) -> Message:
+    content = message.content
+    if isinstance(content, str):
+        normalized = normalize_text(content)
+        return message if normalized == content else message.model_copy(update={"content": normalized})
+    if isinstance(content, list):
+        parts = [
+            {**part, "text": normalize_text(part["text"])}
+            if part.get("type") == "text" and isinstance(part.get("text"), str)
+            else part
+            for part in content
+        ]
+        return message if parts == content else message.model_copy(update={"content": parts})
+    return message
+
+
+def normalize_message_text(messages: list[Message]) -> list[Message]:
+    """Apply :func:`normalize_text` to the text content of ``messages``.
+
+    Returns ``messages`` itself when nothing changed, otherwise a new list in
+    which only the affected messages are copied.
+    """
+    result = [_normalize_content(message) for message in messages]
+    if all(new is old for new, old in zip(result, messages)):
+        return messages
+    return result
@@ -0,0 +1,115 @@
+"""Structured tool result content and its translation per provider.
+
+A tool message may carry a list of content parts instead of a string:
+
+* ``{"type": "text", "text": "..."}``
+* ``{"type": "json", "json": {...}}`` for machine-readable results
+* ``{"type": "image_url", "image_url": {"url": "https://... or data:..."}}``
+
+OpenAI tool messages only accept text, so :func:`to_openai_tool_messages`
+renders text and JSON parts into the tool message and moves images into a
+user message after the tool results. Anthropic ``tool_result`` blocks accept
+text and images directly, see :func:`to_anthropic_tool_result`.
+"""
+
+from __future__ import annotations
+
+import json
+from typing import Any
+
+from .message import Message
+
+
+def json_part(value: Any) -> dict[str, Any]:
+    """Return a JSON content part for a tool result."""
+    return {"type": "json", "json": value}
+
+
+def _part_text(part: dict[str, Any]) -> str | None:
+    if part.get("type") == "text":
+        return part.get("text", "")
+    if part.get("type") == "json":
+        return json.dumps(part.get("json"), ensure_ascii=False)
+    return None
+
+
+def _call_arguments(call: dict[str, Any]) -> dict[str, Any]:
+    """Parse the arguments of an OpenAI tool call for providers that take an object.
+
+    Arguments that are not a JSON object, e.g. cut off by ``max_tokens`` in an
+    earlier turn, are sent as ``{"_raw": arguments}`` instead of failing the request.
+    """
+    arguments = call["function"].get("arguments") or "{}"
+    try:
+        value = json.loads(arguments)
+    except json.JSONDecodeError:
+        value = None
+    return value if isinstance(value, dict) else {"_raw": arguments}
+
+
+def _image_url(part: dict[str, Any]) -> str | None:
+    if part.get("type") != "image_url":
+        return None
+    image = part.get("image_url")
+    return image.get("url") if isinstance(image, dict) else image
+
+
+def to_openai_tool_messages(messages: list[dict[str, Any]]) -> list[dict[str, Any]]:
+    """Rewrite OpenAI-format ``messages`` so tool messages only contain text.
+
+    Images returned by tools are collected and sent in one user message right
+    after the run of tool messages they came from, since the API requires all
+    tool results to directly follow the assistant's tool calls.
+    """
+    result: list[dict[str, Any]] = []
+    images: list[dict[str, Any]] = []
+
+    def flush() -> None:
+        if images:
+            result.append({"role": "user", "content": list(images)})
+            images.clear()
+
+    for message in messages:
+        if message.get("role") != "tool":
+            flush()
+            result.append(message)
+            continue
+        content = message.get("content")
+        if isinstance(content, list):
+            texts = [text for part in content if isinstance(part, dict) and (text := _part_text(part)) is not None]
+            tool_images = [part for part in content if isinstance(part, dict) and _image_url(part)]
+            if tool_images:
+                images.append({"type": "text", "text": f"Images returned by tool call {message.get('tool_call_id')}:"})
+                images.extend(tool_images)
+                texts = texts or ["(see the images below)"]
+            message = {**message, "content": "\n".join(texts)}
+        result.append(message)
+    flush()
+    return result
+
+
+def _anthropic_image(url: str) -> dict[str, Any]:
+    if url.startswith("data:") and ";base64," in url:
+        header, data = url[5:].split(";base64,", 1)
+        return {"type": "image", "source": {"type": "base64", "media_type": header, "data": data}}
+    return {"type": "image", "source": {"type": "url", "url": url}}
+
+
+def to_anthropic_tool_result(message: Message, is_error: bool = False) -> dict[str, Any]:
+    """Return the Anthropic ``tool_result`` block for a tool ``message``."""
+    content = message.content
+    if content is None or isinstance(content, str):
+        blocks: Any = content or ""
+    else:
+        blocks = []
+        for part in content:
+            text = _part_text(part)
+            url = _image_url(part)
+            if text is not None:
+                blocks.append({"type": "text", "text": text})
+            elif url:
+                blocks.append(_anthropic_image(url))
+    block = {"type": "tool_result", "tool_use_id": message.tool_call_id, "content": blocks}
+    if is_error:
+        block["is_error"] = True
+    return block
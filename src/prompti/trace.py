@@ -3,12 +3,16 @@ Trace service for reporting model calls to external services.
 """
 from typing import Any, Dict, List, Optional, Union
 import asyncio
+import base64
 import logging
 import time
+import uuid
 from dataclasses import dataclass, field
+from datetime import datetime, timezone
 
 import httpx
-from opentelemetry import trace
+
+from ._otel import trace
 
 
 # Setup logger
@@ -17,7 +21,7 @@ logger = logging.getLogger(__name__)
 _tracer = trace.get_tracer(__name__)
 
 
-@dataclass
+@dataclass(kw_only=True)
 class TraceEvent:
     """Event data structure for model call trace."""
     
@@ -83,29 +87,57 @@ class TraceService:
         self._http_client = None
         self._sync_http_client = None
         
+    def _headers(self) -> Dict[str, str]:
+        """Return the headers sent with every report."""
+        headers = {}
+        if self.api_key:
+            headers["Authorization"] = f"Bearer {self.api_key}"
+        return headers
+
+    def _report_url(self) -> str:
+        """Return the URL trace events are posted to."""
+        return self.endpoint_url + "/trace/llm-message/dump"
+
+    def _build_payload(self, event: TraceEvent) -> Dict[str, Any]:
+        """Convert ``event`` into the JSON body of a report."""
+        return {
+            "template_name": event.template_name,
+            "template_id": event.template_id,
+            "template_version": event.template_version,
+            "variant": event.variant,
+            "model": event.model,
+            "messages_template": event.messages_template,
+            "variables": event.variables,
+            "llm_request_body": event.llm_request_body,
+            "llm_response_body": event.llm_response_body,
+            "request_id": event.request_id,
+            "user_id": event.user_id,
+            "timestamp": event.timestamp,
+            "conversation_id": event.conversation_id,
+            "token_usage": event.token_usage,
+            "error": event.error,
+            "source": event.source,
+            "span_id": event.span_id,
+            "parent_span_id": event.parent_span_id,
+            "ext": event.ext,
+            "perf_metrics": event.perf_metrics
+        }
+
     async def _get_client(self) -> httpx.AsyncClient:
         """Get or create the HTTP client."""
         if self._http_client is None:
-            headers = {}
-            if self.api_key:
-                headers["Authorization"] = f"Bearer {self.api_key}"
-                
             self._http_client = httpx.AsyncClient(
                 timeout=self.timeout,
-                headers=headers
+                headers=self._headers()
             )
         return self._http_client
     
     def _get_sync_client(self) -> httpx.Client:
         """Get or create the synchronous HTTP client."""
         if self._sync_http_client is None:
-            headers = {}
-            if self.api_key:
-                headers["Authorization"] = f"Bearer {self.api_key}"
-                
             self._sync_http_client = httpx.Client(
                 timeout=self.timeout,
-                headers=headers
+                headers=self._headers()
             )
         return self._sync_http_client
 
@@ -139,29 +171,8 @@ class TraceService:
             client = await self._get_client()
             
             # Convert event to serializable dict
-            payload = {
-                "template_name": event.template_name,
-                "template_id": event.template_id,
-                "template_version": event.template_version,
-                "variant": event.variant,
-                "model": event.model,
-                "messages_template": event.messages_template,
-                "variables": event.variables,
-                "llm_request_body": event.llm_request_body,
-                "llm_response_body": event.llm_response_body,
-                "request_id": event.request_id,
-                "user_id": event.user_id,
-                "timestamp": event.timestamp,
-                "conversation_id": event.conversation_id,
-                "token_usage": event.token_usage,
-                "error": event.error,
-                "source": event.source,
-                "span_id": event.span_id,
-                "parent_span_id": event.parent_span_id,
-                "ext": event.ext,
-                "perf_metrics": event.perf_metrics
-            }
-            url = self.endpoint_url + "/trace/llm-message/dump"
+            payload = self._build_payload(event)
+            url = self._report_url()
 
             # Try to send the report with retries
             for attempt in range(self.max_retries):
@@ -215,29 +226,8 @@ class TraceService:
         client = self._get_sync_client()
         
         # Convert event to serializable dict
-        payload = {
-            "template_name": event.template_name,
-            "template_id": event.template_id,
-            "template_version": event.template_version,
-            "variant": event.variant,
-            "model": event.model,
-            "messages_template": event.messages_template,
-            "variables": event.variables,
-            "llm_request_body": event.llm_request_body,
-            "llm_response_body": event.llm_response_body,
-            "request_id": event.request_id,
-            "user_id": event.user_id,
-            "timestamp": event.timestamp,
-            "conversation_id": event.conversation_id,
-            "token_usage": event.token_usage,
-            "error": event.error,
-            "source": event.source,
-            "span_id": event.span_id,
-            "parent_span_id": event.parent_span_id,
-            "ext": event.ext,
-            "perf_metrics": event.perf_metrics
-        }
-        url = self.endpoint_url + "/trace/llm-message/dump"
+        payload = self._build_payload(event)
+        url = self._report_url()
 
         # Try to send the report with retries
         for attempt in range(self.max_retries):
@@ -275,3 +265,141 @@ class TraceService:
         # All attempts failed
         logger.error(f"Trace report failed after {self.max_retries} attempts")
         return False
+
+
+class LangfuseTraceService(TraceService):
+    """
+    Trace service that exports generations to Langfuse's public ingestion API.
+
+    Each :class:`TraceEvent` becomes a ``trace-create`` and a
+    ``generation-create`` event carrying input, output, usage, latency, model
+    and tags, so prompt runs show up in Langfuse (or any service speaking the
+    same ingestion schema).
+    """
+
+    def __init__(
+        self,
+        public_key: str,
+        secret_key: str,
+        host: str = "https://cloud.langfuse.com",
+        tags: Optional[List[str]] = None,
+        timeout: float = 10.0,
+        max_retries: int = 3,
+        enabled: bool = True
+    ):
+        """
+        Initialize the Langfuse exporter.
+
+        Args:
+            public_key: Langfuse public key (``pk-lf-...``)
+            secret_key: Langfuse secret key (``sk-lf-...``)
+            host: Langfuse base URL
+            tags: Tags added to every exported trace
+            timeout: Request timeout in seconds
+            max_retries: Maximum number of retry attempts for failed reports
+            enabled: Whether trace reporting is enabled
+        """
+        super().__init__(
+            endpoint_url=host.rstrip("/"),
+            timeout=timeout,
+            max_retries=max_retries,
+            enabled=enabled
+        )
+        self.public_key = public_key
+        self.secret_key = secret_key
+        self.tags = list(tags or [])
+
+    def _headers(self) -> Dict[str, str]:
+        """Langfuse authenticates with HTTP basic auth using the key pair."""
+        token = base64.b64encode(f"{self.public_key}:{self.secret_key}".encode()).decode()
+        return {"Authorization": f"Basic {token}"}
+
+    def _report_url(self) -> str:
+        return self.endpoint_url + "/api/public/ingestion"
+
+    @staticmethod
+    def _iso(ts: float) -> str:
+        return datetime.fromtimestamp(ts, tz=timezone.utc).isoformat().replace("+00:00", "Z")
+
+    @staticmethod
+    def _output(event: TraceEvent) -> Dict[str, Any]:
+        """Reassemble the assistant message from the (possibly streamed) responses."""
+        responses = event.llm_response_body.get("final_responses") or event.llm_response_body.get("responses") or []
+        content = ""
+        tool_calls: List[Dict[str, Any]] = []
+        for response in responses:
+            for choice in response.get("choices") or []:
+                message = choice.get("message") or choice.get("delta") or {}
+                if isinstance(message.get("content"), str):
+                    content += message["content"]
+                tool_calls.extend(message.get("tool_calls") or [])
+        output: Dict[str, Any] = {"role": "assistant", "content": content}
+        if tool_calls:
+            output["tool_calls"] = tool_calls
+        return output
+
+    def _build_payload(self, event: TraceEvent) -> Dict[str, Any]:
+        trace_id = event.request_id or str(uuid.uuid4())
+        perf = event.perf_metrics or {}
+        start = event.timestamp
+        if event.duration_ms is not None:
+            end = start + event.duration_ms / 1000
+        else:
+            end = start + perf.get("total_latency", 0.0)
+        tags = [*self.tags]
+        if event.template_name:
+            tags.append(f"template:{event.template_name}")
+        if event.variant:
+            tags.append(f"variant:{event.variant}")
+        usage = event.token_usage or {}
+
+        generation: Dict[str, Any] = {
+            "id": str(uuid.uuid4()),
+            "traceId": trace_id,
+            "name": event.template_name or "llm.call",
+            "startTime": self._iso(start),
+            "endTime": self._iso(end),
+            "model": event.model,
+            "input": event.llm_request_body.get("messages", event.llm_request_body),
+            "output": self._output(event),
+            "usage": {
+                "input": usage.get("prompt_tokens", 0),
+                "output": usage.get("completion_tokens", 0),
+                "total": usage.get("total_tokens", 0),
+                "unit": "TOKENS",
+            },
+            "metadata": {
+                "template_id": event.template_id,
+                "template_version": event.template_version,
+                "variant": event.variant,
+                "source": event.source,
+                "perf_metrics": perf,
+                **(event.ext or {}),
+            },
+            "level": "ERROR" if event.error else "DEFAULT",
+        }
+        if "first_package_latency" in perf:
+            generation["completionStartTime"] = self._iso(start + perf["first_package_latency"])
+        if event.error:
+            generation["statusMessage"] = event.error
+        if event.parent_span_id:
+            generation["parentObservationId"] = event.parent_span_id
+
+        trace_body: Dict[str, Any] = {
+            "id": trace_id,
+            "name": event.template_name or "llm.call",
+            "timestamp": self._iso(start),
+            "userId": event.user_id or None,
+            "sessionId": event.conversation_id or None,
+            "tags": tags,
+            "version": event.template_version,
+            "input": generation["input"],
+            "output": generation["output"],
+        }
+        now = self._iso(time.time())
+        return {
+            "batch": [
+                {"id": str(uuid.uuid4()), "timestamp": now, "type": "trace-create", "body": trace_body},
+                {"id": str(uuid.uuid4()), "timestamp": now, "type": "generation-create", "body": generation},
+            ]
+        }
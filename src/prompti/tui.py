@@ -0,0 +1,232 @@
+"""Terminal chat interface, ``prompti tui`` with the ``tui`` extra.
+
+A quick way to try a configured provider interactively: answers stream into
+the scrollback as they arrive, ``ctrl+n`` (or ``/model NAME``) switches
+between the configured models in the middle of a conversation and the footer
+shows the tokens and the estimated cost of the session so far.
+
+:class:`ChatSession` holds the conversation and does not depend on the UI
+library, so it can drive other front ends too; :func:`arun_app` runs the
+`Textual <https://textual.textualize.io>`_ app on top of it.
+"""
+
+from __future__ import annotations
+
+from collections.abc import AsyncGenerator, Callable
+from dataclasses import dataclass
+from typing import Any
+
+from .message import Message
+from .model_client import ModelConfig, RunParams, create_client
+from .pricing import get_price_table
+
+__all__ = ["ChatEntry", "ChatSession", "arun_app"]
+
+HELP = "/model [NAME] switches model (next without NAME), /models lists them, /clear starts over"
+
+
+def _label(cfg: ModelConfig) -> str:
+    return f"{cfg.provider}:{cfg.model}"
+
+
+@dataclass(kw_only=True)
+class ChatEntry:
+    """One message of the scrollback."""
+
+    role: str
+    text: str = ""
+    model: str | None = None
+    error: str | None = None
+
+
+class ChatSession:
+    """Conversation state of the chat interface.
+
+    Args:
+        models: The models to switch between, the first one is used first.
+        client_factory: Creates the client of a model, :func:`create_client` by default.
+            Clients are created on first use and reused afterwards.
+    """
+
+    def __init__(self, models: list[ModelConfig], client_factory: Callable[[ModelConfig], Any] = create_client) -> None:
+        if not models:
+            raise ValueError("ChatSession needs at least one model")
+        self.models = models
+        self.current = 0
+        self.messages: list[Message] = []
+        self.entries: list[ChatEntry] = []
+        self.prompt_tokens = 0
+        self.completion_tokens = 0
+        self.cost = 0.0
+        # 价格表里没有的模型不计入费用
+        self.cost_known = True
+        self._client_factory = client_factory
+        self._clients: dict[int, Any] = {}
+
+    @property
+    def model(self) -> ModelConfig:
+        """The model the next message is sent to."""
+        return self.models[self.current]
+
+    def switch(self, name: str | None = None) -> ModelConfig:
+        """Select the model named ``name`` (``model`` or ``provider:model``), or the next one.
+
+        Raises:
+            ValueError: If no configured model has that name.
+        """
+        if name is None:
+            self.current = (self.current + 1) % len(self.models)
+            return self.model
+        for i, cfg in enumerate(self.models):
+            if name in (cfg.model, _label(cfg)):
+                self.current = i
+                return cfg
+        raise ValueError(f"Unknown model {name!r}, configured: {', '.join(map(_label, self.models))}")
+
+    def command(self, line: str) -> str | None:
+        """Run a ``/`` command and return its status text, ``None`` if ``line`` is a message."""
+        if not line.startswith("/"):
+            return None
+        name, _, argument = line[1:].strip().partition(" ")
+        argument = argument.strip()
+        if name == "model":
+            try:
+                return f"Switched to {_label(self.switch(argument or None))}"
+            except ValueError as e:
+                return str(e)
+        if name == "models":
+            return "\n".join(("* " if i == self.current else "  ") + _label(m) for i, m in enumerate(self.models))
+        if name == "clear":
+            self.messages.clear()
+            self.entries.clear()
+            return "Conversation cleared"
+        return f"Unknown command /{name}. {HELP}"
+
+    async def asend(self, text: str) -> AsyncGenerator[ChatEntry, None]:
+        """Send ``text`` to the current model and yield the answer entry after each streamed delta.
+
+        A failed call ends with the entry's ``error`` set and leaves the
+        message out of the history, so it can simply be sent again.
+        """
+        cfg = self.model
+        client = self._clients.get(self.current)
+        if client is None:
+            client = self._clients[self.current] = self._client_factory(cfg)
+        self.entries.append(ChatEntry(role="user", text=text))
+        entry = ChatEntry(role="assistant", model=_label(cfg))
+        self.entries.append(entry)
+        messages = [*self.messages, Message.create_user(text)]
+        usage = None
+        async for response in client.arun(RunParams(messages=messages, stream=True)):
+            if response.error:
+                entry.error = response.error.get("message", str(response.error))
+                yield entry
+                return
+            delta = response.get_text_content()
+            usage = response.usage or usage
+            if delta:
+                entry.text += delta
+                yield entry
+        self.messages = [*messages, Message(role="assistant", content=entry.text)]
+        if usage is not None:
+            self.prompt_tokens += usage.prompt_tokens
+            self.completion_tokens += usage.completion_tokens
+            cost = get_price_table().cost(cfg.model, usage, cfg.provider)
+            if cost is None:
+                self.cost_known = False
+            else:
+                self.cost += cost
+        yield entry
+
+    def footer(self) -> str:
+        """Return the status line: current model, session tokens and estimated cost."""
+        if self.cost_known:
+            cost = f"${self.cost:.4f}"
+        else:
+            # 部分模型没有价格，只能给出下限
+            cost = f"${self.cost:.4f}+" if self.cost else "n/a"
+        return f"{_label(self.model)} | tokens {self.prompt_tokens} in / {self.completion_tokens} out | cost {cost}"
+
+    async def aclose(self) -> None:
+        """Close the clients created by the session."""
+        for client in self._clients.values():
+            await client.aclose()
+        self._clients.clear()
+
+
+async def arun_app(session: ChatSession) -> None:
+    """Run the chat interface for ``session`` until the user quits.
+
+    Raises:
+        ImportError: If the ``tui`` extra (``textual``) is not installed.
+    """
+    try:
+        from rich.text import Text
+        from textual.app import App, ComposeResult
+        from textual.binding import Binding
+        from textual.containers import VerticalScroll
+        from textual.widgets import Header, Input, Static
+    except ImportError as e:
+        raise ImportError("The chat interface needs textual. Install with: pip install 'prompti[tui]'") from e
+
+    class ChatApp(App):
+        CSS = """
+        #scrollback { height: 1fr; }
+        .user { color: $accent; margin-top: 1; }
+        .error { color: $error; }
+        #footer { height: 1; background: $panel; }
+        """
+        BINDINGS = [Binding("ctrl+n", "next_model", "Next model"), Binding("ctrl+c", "quit", "Quit")]
+
+        def compose(self) -> ComposeResult:
+            yield Header()
+            yield VerticalScroll(Static(HELP, classes="status"), id="scrollback")
+            yield Input(placeholder="Message, or /model, /models, /clear", id="prompt")
+            yield Static(session.footer(), id="footer")
+
+        def on_mount(self) -> None:
+            self.title = "prompti"
+            self.query_one("#prompt", Input).focus()
+
+        def _append(self, text: str, classes: str = "") -> Static:
+            # 模型输出按纯文本显示，不解析 markup
+            widget = Static(Text(text), classes=classes)
+            scrollback = self.query_one("#scrollback", VerticalScroll)
+            scrollback.mount(widget)
+            scrollback.scroll_end(animate=False)
+            return widget
+
+        def _status(self, text: str) -> None:
+            self._append(text, "status")
+            self.query_one("#footer", Static).update(session.footer())
+
+        def action_next_model(self) -> None:
+            self._status(f"Switched to {_label(session.switch())}")
+
+        async def on_input_submitted(self, event: Input.Submitted) -> None:
+            line = event.value.strip()
+            event.input.value = ""
+            if not line:
+                return
+            status = session.command(line)
+            if status is not None:
+                if line.startswith("/clear"):
+                    await self.query_one("#scrollback", VerticalScroll).remove_children()
+                self._status(status)
+                return
+            self.run_worker(self._send(line), exclusive=True)
+
+        async def _send(self, line: str) -> None:
+            self._append(f"> {line}", "user")
+            answer = self._append("…")
+            scrollback = self.query_one("#scrollback", VerticalScroll)
+            async for entry in session.asend(line):
+                if entry.error:
+                    answer.update(Text(f"error: {entry.error}"))
+                    answer.add_class("error")
+                else:
+                    answer.update(Text(entry.text))
+                scrollback.scroll_end(animate=False)
+            self.query_one("#footer", Static).update(session.footer())
+
+    await ChatApp().run_async()
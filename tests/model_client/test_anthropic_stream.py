@@ -0,0 +1,90 @@
+import json
+
+import httpx
+import pytest
+
+from prompti.message import Message
+from prompti.model_client.base import ModelConfig, RunParams, classify_error
+from prompti.model_client.openai_client import OpenAIClient, SyncOpenAIClient
+from prompti.model_client.types import ErrorClass
+from prompti.partial_json import merge_tool_call_deltas
+
+EVENTS = [
+    {
+        "type": "message_start",
+        "message": {
+            "id": "msg_1",
+            "model": "claude-sonnet",
+            "role": "assistant",
+            "usage": {"input_tokens": 20, "cache_read_input_tokens": 5, "output_tokens": 1},
+        },
+    },
+    {"type": "ping"},
+    {"type": "content_block_start", "index": 0, "content_block": {"type": "thinking", "thinking": ""}},
+    {"type": "content_block_delta", "index": 0, "delta": {"type": "thinking_delta", "thinking": "Check weather."}},
+    {"type": "content_block_delta", "index": 0, "delta": {"type": "signature_delta", "signature": "sig"}},
+    {"type": "content_block_stop", "index": 0},
+    {"type": "content_block_start", "index": 1, "content_block": {"type": "text", "text": ""}},
+    {"type": "content_block_delta", "index": 1, "delta": {"type": "text_delta", "text": "Let me "}},
+    {"type": "content_block_delta", "index": 1, "delta": {"type": "text_delta", "text": "look."}},
+    {"type": "content_block_stop", "index": 1},
+    {
+        "type": "content_block_start",
+        "index": 2,
+        "content_block": {"type": "tool_use", "id": "toolu_1", "name": "weather", "input": {}},
+    },
+    {"type": "content_block_delta", "index": 2, "delta": {"type": "input_json_delta", "partial_json": '{"city": '}},
+    {"type": "content_block_delta", "index": 2, "delta": {"type": "input_json_delta", "partial_json": '"Paris"}'}},
+    {"type": "content_block_stop", "index": 2},
+    {"type": "message_delta", "delta": {"stop_reason": "tool_use"}, "usage": {"output_tokens": 30}},
+    {"type": "message_stop"},
+]
+
+
+def _body(events):
+    return "".join(f"event: {e['type']}\ndata: {json.dumps(e)}\n\n" for e in events).encode()
+
+
+def _params():
+    return RunParams(messages=[Message.create_user("Weather in Paris?")], stream=True)
+
+
+def _check(chunks):
+    assert all(c.id == "msg_1" and c.model == "claude-sonnet" for c in chunks)
+    deltas = [c.choices[0].delta for c in chunks]
+    assert "".join(d.reasoning_content or "" for d in deltas) == "Check weather."
+    assert "".join(d.content or "" for d in deltas) == "Let me look."
+    calls = {}
+    for d in deltas:
+        merge_tool_call_deltas(calls, d.tool_calls or [])
+    assert calls == {
+        0: {"id": "toolu_1", "type": "function", "function": {"name": "weather", "arguments": '{"city": "Paris"}'}}
+    }
+    assert chunks[-1].choices[0].finish_reason == "tool_calls"
+    assert chunks[-1].usage.model_dump() == {"prompt_tokens": 25, "completion_tokens": 30, "total_tokens": 55}
+
+
+@pytest.mark.asyncio
+async def test_anthropic_events_stream_as_openai_chunks():
+    transport = httpx.MockTransport(lambda request: httpx.Response(200, content=_body(EVENTS)))
+    client = OpenAIClient(ModelConfig(provider="openai", model="m"), client=httpx.AsyncClient(transport=transport))
+    _check([c async for c in client._run(_params())])
+    await client.aclose()
+
+
+def test_sync_client_decodes_anthropic_events():
+    transport = httpx.MockTransport(lambda request: httpx.Response(200, content=_body(EVENTS)))
+    client = SyncOpenAIClient(ModelConfig(provider="openai", model="m"), client=httpx.Client(transport=transport))
+    _check(list(client._run(_params())))
+    client.close()
+
+
+@pytest.mark.asyncio
+async def test_anthropic_error_event_becomes_an_error_response():
+    events = EVENTS[:1] + [{"type": "error", "error": {"type": "overloaded_error", "message": "Overloaded"}}]
+    transport = httpx.MockTransport(lambda request: httpx.Response(200, content=_body(events)))
+    client = OpenAIClient(ModelConfig(provider="openai", model="m"), client=httpx.AsyncClient(transport=transport))
+    chunks = [c async for c in client._run(_params())]
+    assert chunks[-1].error["message"] == "Overloaded"
+    assert classify_error(chunks[-1].error) is ErrorClass.SERVER
+    await client.aclose()
@@ -0,0 +1,142 @@
+import json
+from urllib.parse import parse_qs
+
+import httpx
+import pytest
+
+from prompti.message import Message
+from prompti.model_client.azure_ad import AzureADCredential, AzureADError
+from prompti.model_client.azure_client import AzureOpenAIClient, SyncAzureOpenAIClient
+from prompti.model_client.base import ModelConfig, RunParams
+from prompti.model_client.types import AzureADConfig
+
+AZURE_ENV = (
+    "AZURE_TENANT_ID",
+    "AZURE_CLIENT_ID",
+    "AZURE_CLIENT_SECRET",
+    "AZURE_FEDERATED_TOKEN_FILE",
+    "AZURE_AUTHORITY_HOST",
+    "IDENTITY_ENDPOINT",
+    "IDENTITY_HEADER",
+)
+CHAT = {
+    "id": "1",
+    "choices": [{"index": 0, "message": {"role": "assistant", "content": "Hi"}, "finish_reason": "stop"}],
+}
+
+
+@pytest.fixture
+def azure_env(monkeypatch):
+    for name in AZURE_ENV:
+        monkeypatch.delenv(name, raising=False)
+    return monkeypatch
+
+
+def _token_transport(seen, expires_in=3600):
+    def handler(request):
+        seen.append(request)
+        return httpx.Response(200, json={"access_token": f"token-{len(seen)}", "expires_in": expires_in})
+
+    return httpx.MockTransport(handler)
+
+
+def _cfg(**azure_ad):
+    return ModelConfig(
+        provider="azure", model="gpt-4o", api_url="https://res.openai.azure.com", azure_ad=AzureADConfig(**azure_ad)
+    )
+
+
+def test_client_secret_token_is_cached_until_refresh_margin(azure_env):
+    azure_env.setenv("AZURE_CLIENT_SECRET", "s3cret")
+    seen = []
+    now = [1000.0]
+    credential = AzureADCredential(
+        AzureADConfig(tenant_id="contoso", client_id="app"),
+        client=httpx.Client(transport=_token_transport(seen)),
+        clock=lambda: now[0],
+    )
+    assert credential.mode() == "client_secret"
+    assert credential.get_token() == "token-1"
+    now[0] += 3000
+    assert credential.get_token() == "token-1"
+    now[0] += 400  # within five minutes of expiry
+    assert credential.get_token() == "token-2"
+
+    assert str(seen[0].url) == "https://login.microsoftonline.com/contoso/oauth2/v2.0/token"
+    form = parse_qs(seen[0].content.decode())
+    assert form["grant_type"] == ["client_credentials"]
+    assert form["client_secret"] == ["s3cret"]
+    assert form["scope"] == ["https://cognitiveservices.azure.com/.default"]
+
+
+def test_workload_identity_sends_federated_token(azure_env, tmp_path):
+    token_file = tmp_path / "token"
+    token_file.write_text("federated.jwt\n")
+    azure_env.setenv("AZURE_TENANT_ID", "contoso")
+    azure_env.setenv("AZURE_CLIENT_ID", "app")
+    azure_env.setenv("AZURE_FEDERATED_TOKEN_FILE", str(token_file))
+    seen = []
+    credential = AzureADCredential(AzureADConfig(), client=httpx.Client(transport=_token_transport(seen)))
+    assert credential.mode() == "workload_identity"
+    credential.get_token()
+    form = parse_qs(seen[0].content.decode())
+    assert form["client_assertion"] == ["federated.jwt"]
+    assert "client_secret" not in form
+
+
+def test_managed_identity_endpoints(azure_env):
+    seen = []
+    client = httpx.Client(transport=_token_transport(seen))
+    credential = AzureADCredential(AzureADConfig(client_id="uami"), client=client)
+    assert credential.mode() == "managed_identity"
+    credential.get_token()
+    assert seen[0].url.host == "169.254.169.254"
+    assert seen[0].headers["Metadata"] == "true"
+    assert seen[0].url.params["resource"] == "https://cognitiveservices.azure.com"
+    assert seen[0].url.params["client_id"] == "uami"
+
+    azure_env.setenv("IDENTITY_ENDPOINT", "http://localhost:42356/msi/token")
+    azure_env.setenv("IDENTITY_HEADER", "h")
+    AzureADCredential(AzureADConfig(), client=client).get_token()
+    assert seen[1].url.host == "localhost"
+    assert seen[1].headers["X-IDENTITY-HEADER"] == "h"
+    assert seen[1].url.params["api-version"] == "2019-08-01"
+
+
+def test_token_errors(azure_env):
+    with pytest.raises(AzureADError, match="needs tenant_id and client_id"):
+        AzureADCredential(AzureADConfig(mode="client_secret")).get_token()
+
+    transport = httpx.MockTransport(
+        lambda request: httpx.Response(401, json={"error": "invalid_client", "error_description": "AADSTS7000215"})
+    )
+    credential = AzureADCredential(
+        AzureADConfig(tenant_id="t", client_id="c", client_secret="s"), client=httpx.Client(transport=transport)
+    )
+    with pytest.raises(AzureADError, match=r"HTTP 401\): AADSTS7000215"):
+        credential.get_token()
+
+
+@pytest.mark.asyncio
+async def test_async_client_sends_bearer_token(azure_env):
+    seen = []
+
+    def handler(request):
+        seen.append(request)
+        return httpx.Response(200, json=CHAT)
+
+    cfg = _cfg(tenant_id="t", client_id="c", client_secret="s")
+    client = AzureOpenAIClient(cfg, client=httpx.AsyncClient(transport=httpx.MockTransport(handler)))
+    client._credential = AzureADCredential(cfg.azure_ad, async_client=httpx.AsyncClient(transport=_token_transport([])))
+    responses = [r async for r in client._run(RunParams(messages=[Message.create_user("q")], stream=False))]
+    await client.aclose()
+    assert responses[0].choices[0].message.content == "Hi"
+    assert seen[0].headers["Authorization"] == "Bearer token-1"
+    assert "api-key" not in seen[0].headers
+
+
+def test_sync_client_reports_token_errors(azure_env):
+    cfg = _cfg(mode="client_secret")
+    client = SyncAzureOpenAIClient(cfg, client=httpx.Client(transport=httpx.MockTransport(lambda r: None)))
+    response = next(client._run(RunParams(messages=[Message.create_user("q")], stream=False)))
+    assert "needs tenant_id and client_id" in json.dumps(response.model_dump(), default=str)
@@ -0,0 +1,216 @@
+import base64
+import json
+import struct
+import zlib
+
+import httpx
+import pytest
+
+from prompti.message import Message
+from prompti.model_client.base import ModelConfig, RunParams, ToolParams, ToolSpec, classify_error
+from prompti.model_client.bedrock_client import BedrockClient, EventStreamDecoder, SyncBedrockClient
+from prompti.model_client.factory import create_client, create_sync_client
+from prompti.model_client.openai_wire import MalformedResponseError
+from prompti.model_client.types import ErrorClass
+from prompti.testing import snapshot_request
+
+CLAUDE = "anthropic.claude-3-5-sonnet-20240620-v1:0"
+TITAN = "amazon.titan-text-express-v1"
+EVENTS = [
+    {"type": "message_start", "message": {"id": "msg_1", "model": "claude", "usage": {"input_tokens": 10}}},
+    {"type": "content_block_start", "index": 0, "content_block": {"type": "text", "text": ""}},
+    {"type": "content_block_delta", "index": 0, "delta": {"type": "text_delta", "text": "Sunny "}},
+    {"type": "content_block_delta", "index": 0, "delta": {"type": "text_delta", "text": "today."}},
+    {"type": "content_block_stop", "index": 0},
+    {"type": "message_delta", "delta": {"stop_reason": "end_turn"}, "usage": {"output_tokens": 3}},
+    {"type": "message_stop", "amazon-bedrock-invocationMetrics": {"inputTokenCount": 10, "outputTokenCount": 3}},
+]
+WEATHER = ToolSpec(name="weather", description="Weather of a city", parameters={"type": "object", "properties": {}})
+
+
+def frame(headers, payload):
+    """Encode one AWS event stream message."""
+    encoded = b""
+    for name, value in headers.items():
+        encoded += bytes([len(name)]) + name.encode() + b"\x07" + struct.pack(">H", len(value)) + value.encode()
+    prelude = struct.pack(">II", 16 + len(encoded) + len(payload), len(encoded))
+    prelude += struct.pack(">I", zlib.crc32(prelude))
+    message = prelude + encoded + payload
+    return message + struct.pack(">I", zlib.crc32(message))
+
+
+def chunk(event):
+    payload = json.dumps({"bytes": base64.b64encode(json.dumps(event).encode()).decode()}).encode()
+    return frame({":event-type": "chunk", ":content-type": "application/json", ":message-type": "event"}, payload)
+
+
+@pytest.fixture
+def aws_env(monkeypatch):
+    monkeypatch.setenv("AWS_ACCESS_KEY_ID", "AKIDEXAMPLE")
+    monkeypatch.setenv("AWS_SECRET_ACCESS_KEY", "secret")
+    monkeypatch.delenv("AWS_SESSION_TOKEN", raising=False)
+    monkeypatch.delenv("AWS_BEARER_TOKEN_BEDROCK", raising=False)
+
+
+def _params(stream=False):
+    return RunParams(messages=[Message.create_user("Weather in Paris?")], stream=stream)
+
+
+def test_anthropic_request_translation(aws_env):
+    call = {"id": "toolu_1", "type": "function", "function": {"name": "weather", "arguments": '{"city": "Paris"}'}}
+    params = RunParams(
+        messages=[
+            Message.create_system("Be brief."),
+            Message.create_user("Weather in Paris?"),
+            Message.create_tool_call([call]),
+            Message.create_tool_result("sunny", "toolu_1"),
+        ],
+        tool_params=ToolParams(tools=[WEATHER], choice="required"),
+        stop="END",
+    )
+    body = snapshot_request(ModelConfig(provider="bedrock", model=CLAUDE), params)
+    assert body["anthropic_version"] == "bedrock-2023-05-31"
+    assert (body["system"], body["max_tokens"], body["stop_sequences"]) == ("Be brief.", 4096, ["END"])
+    assert body["messages"] == [
+        {"role": "user", "content": [{"type": "text", "text": "Weather in Paris?"}]},
+        {
+            "role": "assistant",
+            "content": [{"type": "tool_use", "id": "toolu_1", "name": "weather", "input": {"city": "Paris"}}],
+        },
+        {"role": "user", "content": [{"type": "tool_result", "tool_use_id": "toolu_1", "content": "sunny"}]},
+    ]
+    assert body["tools"][0]["input_schema"] == WEATHER.parameters
+    assert body["tool_choice"] == {"type": "any"}
+
+
+def test_titan_request_translation(aws_env):
+    params = RunParams(
+        messages=[Message.create_system("Be brief."), Message.create_user("Hi"), Message.create_assistant("Hello!")]
+        + [Message.create_user("Weather?")],
+        max_tokens=50,
+        temperature=0.3,
+    )
+    body = snapshot_request(ModelConfig(provider="bedrock", model=TITAN), params)
+    assert body == {
+        "inputText": "Be brief.\nUser: Hi\nBot: Hello!\nUser: Weather?\nBot:",
+        "textGenerationConfig": {"maxTokenCount": 50, "temperature": 0.3},
+    }
+
+
+@pytest.mark.asyncio
+async def test_invoke_anthropic_model_is_signed(aws_env):
+    seen = []
+    response = {
+        "id": "msg_1",
+        "model": "claude",
+        "content": [{"type": "thinking", "thinking": "Hmm."}, {"type": "text", "text": "Sunny."}],
+        "stop_reason": "end_turn",
+        "usage": {"input_tokens": 10, "output_tokens": 3},
+    }
+
+    def handler(request):
+        seen.append(request)
+        return httpx.Response(200, json=response)
+
+    http_client = httpx.AsyncClient(transport=httpx.MockTransport(handler))
+    client = create_client(ModelConfig(provider="bedrock", model=CLAUDE, region="eu-west-1"), http_client=http_client)
+    assert isinstance(client, BedrockClient)
+    [result] = [r async for r in client._run(_params())]
+    await client.aclose()
+
+    request = seen[0]
+    assert request.url.host == "bedrock-runtime.eu-west-1.amazonaws.com"
+    assert request.url.raw_path == b"/model/anthropic.claude-3-5-sonnet-20240620-v1%3A0/invoke"
+    authorization = request.headers["authorization"]
+    assert authorization.startswith("AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/")
+    assert "/eu-west-1/bedrock/aws4_request" in authorization and "x-amz-date" in request.headers
+    message = result.choices[0].message
+    assert (message.content, message.reasoning_content) == ("Sunny.", "Hmm.")
+    assert result.choices[0].finish_reason == "stop"
+    assert result.usage.total_tokens == 13
+
+
+def test_api_key_is_sent_instead_of_a_signature(aws_env):
+    seen = []
+
+    def handler(request):
+        seen.append(request)
+        return httpx.Response(200, json={"inputTextTokenCount": 4, "results": [{"tokenCount": 2, "outputText": "Hi"}]})
+
+    client = SyncBedrockClient(
+        ModelConfig(provider="bedrock", model=TITAN, api_key="bedrock-key"),
+        client=httpx.Client(transport=httpx.MockTransport(handler)),
+    )
+    [result] = list(client._run(_params()))
+    client.close()
+    assert seen[0].headers["authorization"] == "Bearer bedrock-key"
+    assert result.choices[0].message.content == "Hi"
+    assert result.usage.model_dump() == {"prompt_tokens": 4, "completion_tokens": 2, "total_tokens": 6}
+
+
+@pytest.mark.asyncio
+async def test_anthropic_event_stream(aws_env):
+    seen = []
+    body = b"".join(chunk(event) for event in EVENTS)
+
+    def handler(request):
+        seen.append(request)
+        return httpx.Response(200, content=body)
+
+    client = BedrockClient(
+        ModelConfig(provider="bedrock", model=CLAUDE), client=httpx.AsyncClient(transport=httpx.MockTransport(handler))
+    )
+    chunks = [c async for c in client._run(_params(stream=True))]
+    await client.aclose()
+    assert "".join(c.choices[0].delta.content or "" for c in chunks) == "Sunny today."
+    assert (chunks[-1].choices[0].finish_reason, chunks[-1].usage.total_tokens) == ("stop", 13)
+    assert seen[0].url.path.endswith("/invoke-with-response-stream")
+
+
+def test_titan_event_stream(aws_env):
+    events = [
+        {"outputText": "Sunny ", "index": 0, "completionReason": None},
+        {
+            "outputText": "today.",
+            "index": 0,
+            "completionReason": "FINISH",
+            "amazon-bedrock-invocationMetrics": {"inputTokenCount": 5, "outputTokenCount": 3},
+        },
+    ]
+    body = b"".join(chunk(event) for event in events)
+    client = SyncBedrockClient(
+        ModelConfig(provider="bedrock", model=TITAN),
+        client=httpx.Client(transport=httpx.MockTransport(lambda request: httpx.Response(200, content=body))),
+    )
+    chunks = list(client._run(_params(stream=True)))
+    client.close()
+    assert "".join(c.choices[0].delta.content for c in chunks) == "Sunny today."
+    assert chunks[-1].choices[0].finish_reason == "stop"
+    assert chunks[-1].usage.total_tokens == 8
+
+
+@pytest.mark.asyncio
+async def test_stream_exception_becomes_an_error_response(aws_env):
+    headers = {":exception-type": "throttlingException", ":message-type": "exception"}
+    body = chunk(EVENTS[0]) + frame(headers, b'{"message": "Too many requests"}')
+    client = BedrockClient(
+        ModelConfig(provider="bedrock", model=CLAUDE),
+        client=httpx.AsyncClient(transport=httpx.MockTransport(lambda request: httpx.Response(200, content=body))),
+    )
+    chunks = [c async for c in client._run(_params(stream=True))]
+    await client.aclose()
+    assert chunks[-1].error["message"] == "Too many requests"
+    assert classify_error(chunks[-1].error) is ErrorClass.RATE_LIMIT
+
+
+def test_event_stream_frames_may_span_chunks():
+    data = chunk({"outputText": "a"}) + chunk({"outputText": "b"})
+    decoder = EventStreamDecoder()
+    messages = [m for i in range(0, len(data), 7) for m in decoder.feed(data[i : i + 7])]
+    assert [m.headers[":event-type"] for m in messages] == ["chunk", "chunk"]
+    with pytest.raises(MalformedResponseError, match="checksum"):
+        EventStreamDecoder().feed(data[:-1] + b"\x00")
+
+
+def test_sync_factory():
+    assert isinstance(create_sync_client(ModelConfig(provider="bedrock", model=CLAUDE)), SyncBedrockClient)
@@ -0,0 +1,117 @@
+import json
+
+import httpx
+import pytest
+
+from prompti.message import Choice, Message, ModelResponse
+from prompti.model_client.base import ModelClient, ModelConfig, RunParams, SyncModelClient
+from prompti.model_client.best_of import LLMJudge
+from prompti.model_client.openai_client import OpenAIClient, SyncOpenAIClient
+
+
+def reply(*texts):
+    choices = [Choice(index=i, message=Message.create_assistant(t), finish_reason="stop") for i, t in enumerate(texts)]
+    return ModelResponse(choices=choices)
+
+
+class Sampler(ModelClient):
+    def __init__(self, outcomes):
+        super().__init__(ModelConfig(provider="dummy", model="m"))
+        self.outcomes = list(outcomes)
+        self.calls = []
+
+    async def _run(self, params):
+        self.calls.append(params)
+        yield self.outcomes.pop(0)
+
+
+class SyncSampler(SyncModelClient):
+    def __init__(self, outcomes):
+        super().__init__(ModelConfig(provider="dummy", model="m"))
+        self.outcomes = list(outcomes)
+
+    def _run(self, params):
+        yield self.outcomes.pop(0)
+
+
+def completions(seen, supports_n=True):
+    """Handler of an OpenAI endpoint that answers ``ans0``, ``ans1``, ... in the order they are sampled."""
+
+    def handler(request):
+        body = json.loads(request.content)
+        seen.append(body)
+        n = (body.get("n") or 1) if supports_n else 1
+        start = sum(len(c) for c in answered)
+        texts = [f"ans{start + i}" for i in range(n)]
+        answered.append(texts)
+        choices = [
+            {"index": i, "message": {"role": "assistant", "content": t}, "finish_reason": "stop"}
+            for i, t in enumerate(texts)
+        ]
+        return httpx.Response(200, json={"id": "r", "model": "gpt-4o", "choices": choices})
+
+    answered = []
+    return handler
+
+
+def openai(handler, sync=False):
+    cfg = ModelConfig(provider="openai", model="gpt-4o", api_key="k")
+    if sync:
+        return SyncOpenAIClient(cfg, client=httpx.Client(transport=httpx.MockTransport(handler)))
+    return OpenAIClient(cfg, client=httpx.AsyncClient(transport=httpx.MockTransport(handler)))
+
+
+def params():
+    return RunParams(messages=[Message.create_user("Name a fruit.")], temperature=1, stream=True)
+
+
+def by_length(params, response):
+    return len(response.get_text_content())
+
+
+@pytest.mark.asyncio
+async def test_best_of_scores_concurrent_samples():
+    failed = ModelResponse(error={"message": "invalid request", "status_code": 400})
+    client = Sampler([reply("fig"), reply("banana"), failed])
+    result = await client.abest_of(params(), 3, by_length)
+
+    assert result.best.response.get_text_content() == "banana"
+    assert [(c.index, c.score) for c in result.candidates] == [(0, 3), (1, 6), (2, None)]
+    assert result.candidates[2].error == "invalid request"
+    assert all(p.stream is False and p.n is None for p in client.calls)
+
+
+@pytest.mark.asyncio
+async def test_use_n_and_llm_judge():
+    seen = []
+    client = openai(completions(seen))
+    judge = Sampler([reply("Score: 4"), reply("9"), reply("2")])
+    result = await client.abest_of(params(), 3, LLMJudge(judge).ascore, use_n=True)
+    await client.aclose()
+
+    assert [body.get("n") for body in seen] == [3]
+    assert [c.response.get_text_content() for c in result.candidates] == ["ans0", "ans1", "ans2"]
+    assert [c.score for c in result.candidates] == [4, 9, 2]
+    assert result.best.index == 1
+    assert "Name a fruit." in judge.calls[0].messages[1].content
+
+
+def test_use_n_samples_the_choices_a_provider_left_out():
+    seen = []
+    client = openai(completions(seen, supports_n=False), sync=True)
+    result = client.best_of(params(), 3, by_length, use_n=True)
+    client.close()
+
+    assert [body.get("n") for body in seen] == [3, None, None]
+    assert sorted(c.response.get_text_content() for c in result.candidates) == ["ans0", "ans1", "ans2"]
+
+
+def test_sync_best_of_keeps_scorer_errors():
+    def scorer(params, response):
+        if response.get_text_content() == "plum":
+            raise ValueError("no plums")
+        return 1
+
+    result = SyncSampler([reply("plum"), reply("plum")]).best_of(params(), 2, scorer)
+    assert result.best is None
+    assert result.candidates[0].error == "scoring failed: ValueError: no plums"
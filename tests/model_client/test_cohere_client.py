@@ -0,0 +1,168 @@
+import json
+
+import httpx
+import pytest
+
+from prompti.documents import document_part, text_document_part
+from prompti.message import Message
+from prompti.model_client.base import ModelConfig, RunParams, ToolChoice, ToolParams, ToolSpec
+from prompti.model_client.cohere_client import CohereClient, SyncCohereClient
+from prompti.model_client.factory import create_client, create_sync_client
+from prompti.partial_json import merge_tool_call_deltas
+from prompti.testing import snapshot_request
+
+WEATHER = ToolSpec(name="weather", description="Weather of a city", parameters={"type": "object", "properties": {}})
+CLOCK = ToolSpec(name="clock", description="Local time of a city", parameters={"type": "object", "properties": {}})
+CFG = ModelConfig(provider="cohere", model="command-r-plus-08-2024")
+SOURCE = {"type": "document", "id": "doc_0", "document": {"id": "doc_0", "title": "Warranty", "snippet": "two years"}}
+CITATION = {"start": 22, "end": 31, "text": "two years", "type": "TEXT_CONTENT", "sources": [SOURCE]}
+
+
+def _params(stream=False):
+    doc = text_document_part("The warranty lasts two years.", title="Warranty")
+    return RunParams(
+        messages=[Message.create_user([doc, {"type": "text", "text": "How long is the warranty?"}])],
+        stream=stream,
+    )
+
+
+def test_request_translation():
+    call = {"id": "call_1", "type": "function", "function": {"name": "weather", "arguments": '{"city": "Paris"}'}}
+    params = _params()
+    params.messages += [Message.create_tool_call([call]), Message.create_tool_result("sunny", "call_1")]
+    params.tool_params = ToolParams(tools=[WEATHER, CLOCK], choice=ToolChoice.FORCE, force_tool="clock")
+    params.top_p, params.top_k, params.stop = 0.9, 40, "END"
+    body = snapshot_request(CFG, params)
+    document = {"text": "The warranty lasts two years.", "title": "Warranty"}
+    assert body["documents"] == [{"id": "doc_0", "data": document}]
+    assert body["messages"] == [
+        {"role": "user", "content": [{"type": "text", "text": "How long is the warranty?"}]},
+        {"role": "assistant", "tool_calls": [call]},
+        {"role": "tool", "content": "sunny", "tool_call_id": "call_1"},
+    ]
+    assert (body["p"], body["k"], body["stop_sequences"]) == (0.9, 40, ["END"])
+    assert [t["function"]["name"] for t in body["tools"]] == ["clock"]
+    assert body["tool_choice"] == "REQUIRED"
+
+    with pytest.raises(ValueError, match="plain-text documents"):
+        snapshot_request(CFG, RunParams(messages=[Message.create_user([document_part(b"%PDF-1.4")])]))
+
+
+@pytest.mark.asyncio
+async def test_chat_with_citations():
+    seen = []
+    response = {
+        "id": "c1",
+        "finish_reason": "COMPLETE",
+        "message": {
+            "role": "assistant",
+            "content": [{"type": "text", "text": "The warranty lasts two years."}],
+            "citations": [CITATION],
+        },
+        "usage": {
+            "billed_units": {"input_tokens": 9, "output_tokens": 6},
+            "tokens": {"input_tokens": 70, "output_tokens": 6},
+        },
+    }
+
+    def handler(request):
+        seen.append(request)
+        return httpx.Response(200, json=response)
+
+    http_client = httpx.AsyncClient(transport=httpx.MockTransport(handler))
+    client = create_client(CFG.model_copy(update={"api_key": "key"}), http_client=http_client)
+    assert isinstance(client, CohereClient)
+    [result] = [r async for r in client._run(_params())]
+    await client.aclose()
+
+    assert str(seen[0].url) == "https://api.cohere.com/v2/chat"
+    assert seen[0].headers["authorization"] == "Bearer key"
+    assert result.get_text_content() == "The warranty lasts two years."
+    [citation] = result.get_citations()
+    assert (citation.start, citation.end, citation.text) == (22, 31, "two years")
+    assert (citation.document_index, citation.document_title, citation.cited_text) == (0, "Warranty", "two years")
+    assert (result.choices[0].finish_reason, result.usage.total_tokens) == ("stop", 76)
+
+
+def test_stream(monkeypatch):
+    monkeypatch.setenv("CO_API_KEY", "env-key")
+    events = [
+        {"type": "message-start", "id": "c1", "delta": {"message": {"role": "assistant"}}},
+        {"type": "content-start", "index": 0, "delta": {"message": {"content": {"type": "text", "text": ""}}}},
+        {"type": "content-delta", "index": 0, "delta": {"message": {"content": {"text": "Two "}}}},
+        {"type": "content-delta", "index": 0, "delta": {"message": {"content": {"text": "years."}}}},
+        {"type": "citation-start", "index": 0, "delta": {"message": {"citations": CITATION}}},
+        {"type": "citation-end", "index": 0},
+        {"type": "tool-plan-delta", "delta": {"message": {"tool_plan": "Check the time."}}},
+        {
+            "type": "tool-call-start",
+            "index": 0,
+            "delta": {
+                "message": {
+                    "tool_calls": {"id": "clock_1", "type": "function", "function": {"name": "clock", "arguments": ""}}
+                }
+            },
+        },
+        {
+            "type": "tool-call-delta",
+            "index": 0,
+            "delta": {"message": {"tool_calls": {"function": {"arguments": "{}"}}}},
+        },
+        {"type": "tool-call-end", "index": 0},
+        {
+            "type": "message-end",
+            "delta": {"finish_reason": "TOOL_CALL", "usage": {"tokens": {"input_tokens": 5, "output_tokens": 3}}},
+        },
+    ]
+    body = "".join(f"event: {e['type']}\ndata: {json.dumps(e)}\n\n" for e in events).encode()
+    seen = []
+
+    def handler(request):
+        seen.append(request)
+        return httpx.Response(200, content=body)
+
+    client = SyncCohereClient(CFG, client=httpx.Client(transport=httpx.MockTransport(handler)))
+    chunks = list(client._run(_params(stream=True)))
+    client.close()
+
+    assert seen[0].headers["authorization"] == "Bearer env-key"
+    assert json.loads(seen[0].content)["stream"] is True
+    deltas = [c.choices[0].delta for c in chunks]
+    assert "".join(d.content or "" for d in deltas) == "Two years."
+    assert "".join(d.reasoning_content or "" for d in deltas) == "Check the time."
+    assert [c.document_title for d in deltas for c in d.citations or []] == ["Warranty"]
+    calls = {}
+    for delta in deltas:
+        merge_tool_call_deltas(calls, delta.tool_calls or [])
+    assert [(c["id"], c["function"]["name"], c["function"]["arguments"]) for c in calls.values()] == [
+        ("clock_1", "clock", "{}")
+    ]
+    assert (chunks[-1].choices[0].finish_reason, chunks[-1].usage.total_tokens) == ("tool_calls", 8)
+
+
+def test_error_message():
+    client = SyncCohereClient(
+        CFG,
+        client=httpx.Client(
+            transport=httpx.MockTransport(lambda request: httpx.Response(400, json={"message": "invalid model"}))
+        ),
+    )
+    [response] = list(client._run(_params()))
+    client.close()
+    assert (response.error["message"], response.error["status_code"]) == ("invalid model", 400)
+
+
+def test_sync_factory():
+    assert isinstance(create_sync_client(CFG), SyncCohereClient)
+
+
+@pytest.mark.asyncio
+async def test_embeddings_and_uploads_are_not_sent_to_the_chat_api():
+    sent = []
+    transport = httpx.MockTransport(lambda request: sent.append(request) or httpx.Response(200, json={}))
+    client = CohereClient(CFG, client=httpx.AsyncClient(transport=transport))
+    with pytest.raises(NotImplementedError, match="Cohere"):
+        await client.aembeddings({"input": "hi"})
+    with pytest.raises(NotImplementedError, match="Cohere"):
+        await client.aupload_file("a.pdf", b"%PDF")
+    assert sent == []
@@ -0,0 +1,91 @@
+import httpx
+import pytest
+
+from prompti.message import Message
+from prompti.model_client.base import ModelConfig, RunParams, ToolParams, ToolSpec
+from prompti.model_client.compatible_client import OpenAICompatibleClient, SyncOpenAICompatibleClient
+from prompti.model_client.factory import create_client, create_sync_client
+from prompti.model_client.types import OpenAICompatibleConfig
+from prompti.testing import snapshot_request
+
+WEATHER = ToolSpec(name="weather", description="Weather of a city", parameters={"type": "object", "properties": {}})
+SSE = (
+    b'data: {"id": "1", "choices": [{"index": 0, "delta": {"content": "Hi"}}]}\n\n'
+    b'data: {"id": "1", "choices": [{"index": 0, "delta": {}, "finish_reason": "stop"}]}\n\n'
+    b"data: [DONE]\n\n"
+)
+
+
+def _cfg(api_url="http://localhost:8000", **compat):
+    return ModelConfig(
+        provider="openai_compatible", model="llama", api_url=api_url, compat=OpenAICompatibleConfig(**compat)
+    )
+
+
+def _params(**kw):
+    return RunParams(messages=[Message.create_user("Weather in Paris?")], **kw)
+
+
+@pytest.mark.parametrize(
+    "api_url,path_prefix,expected",
+    [
+        ("http://localhost:8000", "/v1", "http://localhost:8000/v1/chat/completions"),
+        ("http://localhost:8080/", "", "http://localhost:8080/chat/completions"),
+        ("https://api.groq.com/openai", "v1/", "https://api.groq.com/openai/v1/chat/completions"),
+        ("https://gateway.example.com/llm/chat/completions", "/v1", "https://gateway.example.com/llm/chat/completions"),
+    ],
+)
+def test_request_url(api_url, path_prefix, expected):
+    client = SyncOpenAICompatibleClient(_cfg(api_url, path_prefix=path_prefix))
+    assert client._request_url() == expected
+    client.close()
+
+
+def test_api_url_is_required():
+    client = SyncOpenAICompatibleClient(ModelConfig(provider="openai_compatible", model="llama"))
+    with pytest.raises(ValueError, match="needs api_url"):
+        client._request_url()
+    client.close()
+
+
+def test_capabilities_shape_the_request():
+    params = _params(stream=True, tool_params=ToolParams(tools=[WEATHER]))
+    body = snapshot_request(_cfg(), params)
+    assert body["stream_options"] == {"include_usage": True}
+    assert body["tools"][0]["function"]["name"] == "weather"
+
+    assert "stream_options" not in snapshot_request(_cfg(supports_stream_options=False), _params(stream=True))
+    with pytest.raises(ValueError, match="does not support tools"):
+        snapshot_request(_cfg(supports_tools=False), params)
+
+
+def test_json_mode_is_emulated_without_server_support():
+    body = snapshot_request(_cfg(), _params(response_format="json_object"))
+    assert body["response_format"] == {"type": "json_object"}
+    body = snapshot_request(_cfg(supports_json_mode=False), _params(response_format="json_object"))
+    assert "response_format" not in body
+    assert body["messages"][0]["role"] == "system"
+    assert "JSON" in body["messages"][0]["content"]
+
+
+@pytest.mark.asyncio
+async def test_stream_without_stream_options():
+    seen = []
+
+    def handler(request):
+        seen.append(request)
+        return httpx.Response(200, content=SSE)
+
+    http_client = httpx.AsyncClient(transport=httpx.MockTransport(handler))
+    client = create_client(_cfg(supports_stream_options=False), http_client=http_client)
+    assert isinstance(client, OpenAICompatibleClient)
+    chunks = [c async for c in client._run(_params(stream=True))]
+    await client.aclose()
+    assert str(seen[0].url) == "http://localhost:8000/v1/chat/completions"
+    assert b"stream_options" not in seen[0].content
+    assert "".join(c.choices[0].delta.content or "" for c in chunks) == "Hi"
+    assert chunks[-1].choices[0].finish_reason == "stop"
+
+
+def test_sync_factory():
+    assert isinstance(create_sync_client(_cfg()), SyncOpenAICompatibleClient)
@@ -0,0 +1,47 @@
+import gzip
+import json
+
+import httpx
+
+from prompti.message import Message
+from prompti.model_client.base import ModelConfig, RunParams
+from prompti.model_client.openai_client import SyncOpenAIClient
+from prompti.testing import snapshot_request
+
+IMAGE = "data:image/png;base64," + "A" * 40000
+
+
+def image_params():
+    content = [{"type": "text", "text": "describe"}, {"type": "image_url", "image_url": {"url": IMAGE}}]
+    return RunParams(messages=[Message.create_user(content)], stream=False)
+
+
+def test_large_bodies_are_gzipped_when_enabled():
+    seen = {}
+
+    def handler(request):
+        seen["encoding"] = request.headers.get("Content-Encoding")
+        seen["body"] = json.loads(gzip.decompress(request.content))
+        body = json.dumps({"choices": [{"index": 0, "message": {"role": "assistant", "content": "ok"}}]}).encode()
+        # the response is compressed too; httpx decodes it transparently
+        return httpx.Response(200, content=gzip.compress(body), headers={"Content-Encoding": "gzip"})
+
+    cfg = ModelConfig(provider="openai", model="m", request_compression="gzip")
+    client = SyncOpenAIClient(cfg, client=httpx.Client(transport=httpx.MockTransport(handler)))
+    (response,) = list(client._run(image_params()))
+    assert response.get_text_content() == "ok"
+    assert seen["encoding"] == "gzip"
+    assert seen["body"]["messages"][0]["content"][1]["image_url"]["url"] == IMAGE
+
+
+def test_small_bodies_and_default_config_are_sent_plain():
+    client = SyncOpenAIClient(ModelConfig(provider="openai", model="m", request_compression="gzip"))
+    small = client._build_request(RunParams(messages=[Message.create_user("hi")]))
+    assert "Content-Encoding" not in small.headers
+    plain = SyncOpenAIClient(ModelConfig(provider="openai", model="m"))._build_request(image_params())
+    assert "Content-Encoding" not in plain.headers
+
+
+def test_snapshot_request_decodes_compressed_bodies():
+    cfg = ModelConfig(provider="openai", model="m", request_compression="gzip")
+    assert snapshot_request(cfg, image_params())["messages"][0]["role"] == "user"
@@ -0,0 +1,85 @@
+import json
+import logging
+
+import httpx
+import pytest
+
+from prompti.message import Message
+from prompti.model_client.base import ModelConfig, RunParams
+from prompti.model_client.deprecations import is_model_not_found, replacement_model
+from prompti.model_client.openai_client import OpenAIClient, SyncOpenAIClient
+
+NOT_FOUND = {
+    "error": {
+        "message": "The model `gpt-4-turbo-preview` does not exist or you do not have access to it.",
+        "type": "invalid_request_error",
+        "code": "model_not_found",
+    }
+}
+OK = {"id": "1", "model": "gpt-4o", "choices": [{"index": 0, "message": {"role": "assistant", "content": "hi"}}]}
+
+
+def _handler(seen):
+    def handler(request):
+        model = json.loads(request.content)["model"]
+        seen.append(model)
+        return httpx.Response(404, json=NOT_FOUND) if model == "gpt-4-turbo-preview" else httpx.Response(200, json=OK)
+
+    return handler
+
+
+def _params():
+    return RunParams(messages=[Message.create_user("hi")], stream=False)
+
+
+def test_replacement_model():
+    assert replacement_model("gpt-4-turbo-preview") == "gpt-4o"
+    assert replacement_model("openai/claude-2.1") == "openai/claude-3-5-sonnet-latest"
+    assert replacement_model("gpt-4o") is None
+    # 别名优先，链式映射取最新名称，循环不会死循环
+    assert replacement_model("gpt-4-turbo-preview", {"gpt-4o": "gpt-4.1"}) == "gpt-4.1"
+    assert replacement_model("a", {"a": "b", "b": "a"}) == "b"
+
+
+@pytest.mark.parametrize(
+    "error,expected",
+    [
+        ({"code": "model_decommissioned", "message": "x"}, True),
+        ({"status_code": 404, "message": "model: claude-2.0"}, True),
+        ({"status_code": 404, "message": "models/gemini-pro is not found for API version v1beta"}, True),
+        ({"status_code": 400, "message": "The provided model identifier is invalid."}, True),
+        ({"status_code": 404, "message": "Not Found"}, False),
+        ({"status_code": 400, "message": "The model `gpt-4-0314` has been deprecated"}, True),
+        ({"status_code": 400, "message": "model gpt-3.5-turbo-0301 has reached the end of its life"}, True),
+        ({"status_code": 400, "message": "The parameter `functions` is deprecated for this model, use tools"}, False),
+        ({"status_code": 400, "message": "The model gpt-4o rejected the deprecated parameter `functions`"}, False),
+        ({"status_code": 500, "message": "model not found"}, False),
+    ],
+)
+def test_is_model_not_found(error, expected):
+    assert is_model_not_found(error, "claude-2.0") is expected
+
+
+@pytest.mark.asyncio
+async def test_substitutes_the_replacement_with_a_warning(caplog):
+    seen = []
+    cfg = ModelConfig(provider="openai", model="gpt-4-turbo-preview", model_fallback="substitute")
+    client = OpenAIClient(cfg, client=httpx.AsyncClient(transport=httpx.MockTransport(_handler(seen))))
+    with caplog.at_level(logging.WARNING):
+        responses = [r async for r in client.arun(_params())]
+    assert [r.get_text_content() for r in responses] == ["hi"]
+    assert "gpt-4-turbo-preview not found, substituting gpt-4o" in caplog.text
+    [r async for r in client.arun(_params())]
+    await client.aclose()
+    assert seen == ["gpt-4-turbo-preview", "gpt-4o", "gpt-4o"]
+    assert (client.cfg.model, cfg.model) == ("gpt-4o", "gpt-4-turbo-preview")
+
+
+def test_hard_fail_by_default():
+    seen = []
+    cfg = ModelConfig(provider="openai", model="gpt-4-turbo-preview")
+    client = SyncOpenAIClient(cfg, client=httpx.Client(transport=httpx.MockTransport(_handler(seen))))
+    [response] = list(client.run(_params()))
+    client.close()
+    assert seen == ["gpt-4-turbo-preview"]
+    assert response.error["status_code"] == 404
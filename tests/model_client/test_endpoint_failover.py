@@ -0,0 +1,126 @@
+import httpx
+import pytest
+
+from prompti.message import Message
+from prompti.model_client import ModelConfig, RunParams
+from prompti.model_client.azure_client import AzureOpenAIClient
+from prompti.model_client.endpoints import EndpointFailover
+from prompti.model_client.openai_client import OpenAIClient, SyncOpenAIClient
+
+PRIMARY = "https://a.example/v1/chat/completions"
+BACKUP = "https://b.example/v1/chat/completions"
+BODY = {"choices": [{"index": 0, "message": {"role": "assistant", "content": "hi"}}]}
+
+
+def _transport(seen, down, urls=None):
+    def handler(request):
+        seen.append(request.url.host)
+        if urls is not None:
+            urls.append(str(request.url))
+        if request.url.host in down:
+            raise httpx.ConnectError(f"cannot resolve {request.url.host}")
+        if request.url.path.endswith("/embeddings"):
+            return httpx.Response(200, json={"data": [{"index": 0, "embedding": [0.5]}]})
+        return httpx.Response(200, json=BODY)
+
+    return httpx.MockTransport(handler)
+
+
+def _cfg(**kwargs):
+    return ModelConfig(provider="openai", model="m", api_url=PRIMARY, failover_api_urls=[BACKUP], **kwargs)
+
+
+def _params():
+    return RunParams(messages=[Message.create_user("q")], stream=False)
+
+
+@pytest.mark.asyncio
+async def test_fails_over_on_connect_error_and_stays():
+    seen, down = [], {"a.example"}
+    client = OpenAIClient(_cfg(), client=httpx.AsyncClient(transport=_transport(seen, down)))
+    [response] = [r async for r in client.arun(_params())]
+    assert response.get_text_content() == "hi"
+    assert seen == ["a.example", "b.example"]
+    [r async for r in client.arun(_params())]
+    assert seen[2:] == ["b.example"]
+
+
+@pytest.mark.asyncio
+async def test_all_endpoints_down_is_a_network_error():
+    seen = []
+    cfg = _cfg(retry={"max_attempts": 1})
+    client = OpenAIClient(cfg, client=httpx.AsyncClient(transport=_transport(seen, {"a.example", "b.example"})))
+    [response] = [r async for r in client.arun(_params())]
+    assert response.error["code"] == "network_error"
+    assert seen == ["a.example", "b.example"]
+
+
+@pytest.mark.asyncio
+async def test_embeddings_fail_over_to_the_embeddings_endpoint():
+    seen, urls = [], []
+    client = OpenAIClient(_cfg(), client=httpx.AsyncClient(transport=_transport(seen, {"a.example"}, urls)))
+    result = await client.aembeddings({"input": "hi"})
+    assert result["data"][0]["embedding"] == [0.5]
+    assert urls == ["https://a.example/v1/embeddings", "https://b.example/v1/embeddings"]
+
+
+@pytest.mark.asyncio
+async def test_failover_keeps_the_request_timeout():
+    timeouts = []
+
+    def handler(request):
+        timeouts.append(request.extensions["timeout"])
+        if request.url.host == "a.example":
+            raise httpx.ConnectError("cannot resolve a.example")
+        return httpx.Response(200, json=BODY)
+
+    cfg = _cfg(timeout={"connect_s": 1.5, "read_s": 42})
+    client = OpenAIClient(cfg, client=httpx.AsyncClient(transport=httpx.MockTransport(handler), timeout=5))
+    [response] = [r async for r in client.arun(_params())]
+    assert response.get_text_content() == "hi"
+    assert len(timeouts) == 2
+    assert timeouts[1] == timeouts[0]
+    assert (timeouts[1]["connect"], timeouts[1]["read"]) == (1.5, 42)
+
+
+def test_sync_failover_and_failback_probe():
+    now = [0.0]
+    seen, down = [], {"a.example"}
+    client = SyncOpenAIClient(_cfg(failback_probe_s=30), client=httpx.Client(transport=_transport(seen, down)))
+    client._failover = EndpointFailover(PRIMARY, [BACKUP], 30, clock=lambda: now[0])
+    list(client.run(_params()))
+    assert seen == ["a.example", "b.example"]
+    now[0] = 10
+    list(client.run(_params()))
+    assert seen[2:] == ["b.example"]  # no probe before the interval
+    now[0] = 40
+    list(client.run(_params()))
+    assert seen[3:] == ["a.example", "b.example"]  # probe failed, next one in 30s
+    down.clear()
+    now[0] = 50
+    list(client.run(_params()))
+    assert seen[5:] == ["b.example"]
+    now[0] = 71
+    list(client.run(_params()))
+    assert seen[6:] == ["a.example"]
+    assert client._failover.active == PRIMARY
+
+
+def test_azure_fails_over_between_resources():
+    cfg = ModelConfig(
+        provider="azure", model="dep", api_url="https://r1.example", failover_api_urls=["https://r2.example"]
+    )
+    client = AzureOpenAIClient(cfg)
+    assert client._request_url().startswith("https://r1.example/openai/deployments/dep/")
+    client._endpoint_failover().succeeded("https://r2.example")
+    assert client._request_url().startswith("https://r2.example/openai/deployments/dep/")
+
+
+def test_retarget_keeps_the_path_and_query_of_each_request():
+    cfg = ModelConfig(
+        provider="azure", model="dep", api_url="https://r1.example", failover_api_urls=["https://r2.example"]
+    )
+    client = AzureOpenAIClient(cfg)
+    files = client._client.build_request("POST", client._files_url())
+    moved = client._retarget(files, "https://r2.example")
+    assert str(moved.url) == client._files_url().replace("r1.example", "r2.example")
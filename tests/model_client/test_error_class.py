@@ -0,0 +1,43 @@
+import httpx
+import pytest
+
+from prompti.message import Message, ModelResponse
+from prompti.model_client.base import ErrorClass, ModelClient, ModelConfig, RetryConfig, RunParams, classify_error
+
+
+@pytest.mark.parametrize(
+    "error,expected",
+    [
+        ({"status_code": 429}, ErrorClass.RATE_LIMIT),
+        ({"status_code": 401}, ErrorClass.AUTH),
+        ({"status_code": 403}, ErrorClass.AUTH),
+        ({"status_code": 408}, ErrorClass.TIMEOUT),
+        ({"code": "timeout"}, ErrorClass.TIMEOUT),
+        ({"status_code": 502}, ErrorClass.SERVER),
+        ({"code": "network_error"}, ErrorClass.SERVER),
+        ({"status_code": 400}, ErrorClass.CLIENT),
+        ({"message": "Unexpected error"}, ErrorClass.CLIENT),
+    ],
+)
+def test_classify_error_objects(error, expected):
+    assert classify_error(error) is expected
+
+
+def test_classify_exceptions_and_stream_failures():
+    assert classify_error(exc=httpx.ReadTimeout("slow")) is ErrorClass.TIMEOUT
+    assert classify_error(exc=httpx.ConnectError("refused")) is ErrorClass.SERVER
+    assert classify_error({"status_code": 429}, streaming_started=True) is ErrorClass.STREAM
+
+
+class FailingClient(ModelClient):
+    provider = "failing"
+
+    async def _run(self, params):
+        yield ModelResponse(error={"message": "slow down", "status_code": 429})
+
+
+@pytest.mark.asyncio
+async def test_error_responses_are_tagged_with_error_class():
+    client = FailingClient(ModelConfig(provider="failing", model="m", retry=RetryConfig(max_attempts=1)))
+    responses = [r async for r in client.arun(RunParams(messages=[Message.create_user("q")]))]
+    assert responses[0].error["error_class"] == "rate_limit"
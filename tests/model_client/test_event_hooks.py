@@ -0,0 +1,72 @@
+import pytest
+
+from prompti.message import Choice, Message, ModelResponse, Usage
+from prompti.model_client.base import EventHook, ModelClient, ModelConfig, RetryConfig, RunParams
+
+
+class RecordingHook(EventHook):
+    def __init__(self):
+        self.events = []
+
+    def on_request_start(self, cfg, params):
+        self.events.append("start")
+
+    def on_retry(self, cfg, params, attempt, delay, error):
+        self.events.append(("retry", attempt, error["status_code"]))
+
+    def on_first_token(self, cfg, params, latency):
+        self.events.append("first_token")
+
+    def on_complete(self, cfg, params, usage, duration):
+        self.events.append(("complete", usage.total_tokens if usage else None))
+
+    def on_error(self, cfg, params, error_class, error):
+        self.events.append(("error", error_class))
+
+
+class ScriptedClient(ModelClient):
+    provider = "scripted"
+
+    def __init__(self, cfg, outcomes):
+        super().__init__(cfg)
+        self.outcomes = list(outcomes)
+
+    async def _run(self, params):
+        yield self.outcomes.pop(0)
+
+
+def make_client(*outcomes):
+    cfg = ModelConfig(provider="scripted", model="m", retry=RetryConfig(max_attempts=2, initial_backoff_ms=0))
+    client = ScriptedClient(cfg, outcomes)
+    hook = RecordingHook()
+    client.add_event_hook(hook)
+    return client, hook
+
+
+async def drain(client):
+    return [r async for r in client.arun(RunParams(messages=[Message.create_user("q")]))]
+
+
+@pytest.mark.asyncio
+async def test_lifecycle_events_for_retried_success():
+    ok = ModelResponse(
+        choices=[Choice(index=0, message=Message(role="assistant", content="hi"))],
+        usage=Usage(prompt_tokens=1, completion_tokens=2, total_tokens=3),
+    )
+    client, hook = make_client(ModelResponse(error={"message": "busy", "status_code": 503}), ok)
+    await drain(client)
+    assert hook.events == ["start", ("retry", 1, 503), "first_token", ("complete", 3)]
+
+
+@pytest.mark.asyncio
+async def test_error_event_and_failing_hook_is_ignored():
+    client, hook = make_client(ModelResponse(error={"message": "bad key", "status_code": 401}))
+
+    class Broken(EventHook):
+        def on_request_start(self, cfg, params):
+            raise RuntimeError("boom")
+
+    client.add_event_hook(Broken())
+    responses = await drain(client)
+    assert responses[0].error["status_code"] == 401
+    assert hook.events == ["start", "first_token", ("error", "auth")]
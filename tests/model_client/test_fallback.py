@@ -0,0 +1,74 @@
+import httpx
+import pytest
+
+from prompti.message import Choice, Message, ModelResponse
+from prompti.model_client.base import ModelClient, ModelConfig, RetryConfig, RunParams
+from prompti.model_client.fallback import FallbackClient
+
+
+class ScriptedClient(ModelClient):
+    def __init__(self, provider, model, outcome):
+        super().__init__(ModelConfig(provider=provider, model=model, retry=RetryConfig(max_attempts=1)))
+        self.outcome = outcome
+        self.calls = 0
+
+    async def _run(self, params):
+        self.calls += 1
+        if isinstance(self.outcome, Exception):
+            raise self.outcome
+        for item in self.outcome:
+            yield item
+
+
+def ok(text="hi"):
+    return ModelResponse(choices=[Choice(index=0, message=Message(role="assistant", content=text))])
+
+
+def err(status, message="boom", code="unknown_error"):
+    return ModelResponse(error={"message": message, "type": "api_error", "code": code, "status_code": status})
+
+
+async def collect(client):
+    return [r async for r in client.arun(RunParams(messages=[Message.create_user("q")]))]
+
+
+@pytest.mark.asyncio
+async def test_falls_back_and_records_the_serving_target():
+    clients = [
+        ScriptedClient("openai", "gpt-4o", [err(429)]),
+        ScriptedClient("azure", "gpt-4o", httpx.ConnectError("refused")),
+        ScriptedClient("anthropic", "claude-3-5-sonnet-latest", [err(404, "model: gpt-9 not found")]),
+        ScriptedClient("gemini", "gemini-2.5-flash", [ok("from gemini")]),
+    ]
+    [response] = await collect(FallbackClient(clients))
+    assert response.get_text_content() == "from gemini"
+    assert [(a.attempt, a.provider, a.status) for a in response.attempts] == [
+        (1, "openai", "error"),
+        (2, "azure", "error"),
+        (3, "anthropic", "error"),
+        (4, "gemini", "success"),
+    ]
+    assert response.attempts[-1].model == "gemini-2.5-flash"
+    assert response.attempts[1].error == "ConnectError: refused"
+
+
+@pytest.mark.asyncio
+async def test_client_errors_and_the_last_error_are_returned():
+    bad_request = ScriptedClient("openai", "gpt-4o", [err(400, "invalid messages")])
+    backup = ScriptedClient("azure", "gpt-4o", [ok()])
+    [response] = await collect(FallbackClient([bad_request, backup]))
+    assert response.error["status_code"] == 400
+    assert backup.calls == 0
+
+    [response] = await collect(FallbackClient([ScriptedClient("openai", "gpt-4o", [err(503)])]))
+    assert response.error["status_code"] == 503
+    assert response.attempts is None
+
+
+@pytest.mark.asyncio
+async def test_no_fallback_after_output_was_streamed():
+    partial = ScriptedClient("openai", "gpt-4o", [ok("par"), err(503)])
+    backup = ScriptedClient("azure", "gpt-4o", [ok()])
+    responses = await collect(FallbackClient([partial, backup]))
+    assert [r.get_text_content() for r in responses] == ["par", None]
+    assert backup.calls == 0
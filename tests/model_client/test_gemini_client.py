@@ -0,0 +1,177 @@
+import json
+
+import httpx
+import pytest
+
+from prompti.message import Message
+from prompti.model_client.base import ModelConfig, RunParams, ToolParams, ToolSpec
+from prompti.model_client.factory import create_client, create_sync_client
+from prompti.model_client.gemini_client import GeminiClient, SyncGeminiClient
+from prompti.partial_json import merge_tool_call_deltas
+from prompti.testing import snapshot_request
+
+WEATHER = ToolSpec(name="weather", description="Weather of a city", parameters={"type": "object", "properties": {}})
+
+RESPONSE = {
+    "candidates": [
+        {
+            "content": {
+                "role": "model",
+                "parts": [
+                    {"text": "Checking the weather.", "thought": True},
+                    {"text": "Let me look."},
+                    {"functionCall": {"name": "weather", "args": {"city": "Paris"}}, "thoughtSignature": "sig"},
+                ],
+            },
+            "finishReason": "STOP",
+        }
+    ],
+    "usageMetadata": {
+        "promptTokenCount": 10,
+        "candidatesTokenCount": 5,
+        "thoughtsTokenCount": 3,
+        "totalTokenCount": 18,
+    },
+    "modelVersion": "gemini-2.5-flash",
+    "responseId": "r1",
+}
+
+
+def _params(stream=False):
+    return RunParams(
+        messages=[Message.create_system("Be brief."), Message.create_user("Weather in Paris?")],
+        tool_params=ToolParams(tools=[WEATHER]),
+        stream=stream,
+        max_tokens=100,
+    )
+
+
+def test_request_translation():
+    calls = [
+        {"id": "c1", "type": "function", "function": {"name": "weather", "arguments": '{"city": "Paris"}'}},
+        {"id": "c2", "type": "function", "function": {"name": "weather", "arguments": '{"city": "Rome"}'}},
+    ]
+    calls[0]["extra_content"] = {"google": {"thought_signature": "sig"}}
+    params = _params()
+    params.messages += [
+        Message.create_tool_call(calls),
+        Message.create_tool_result('{"temp": 21}', "c1"),
+        Message.create_tool_result("sunny", "c2"),
+    ]
+    body = snapshot_request("gemini", params, model="gemini-2.5-flash")
+    assert body["systemInstruction"] == {"parts": [{"text": "Be brief."}]}
+    assert body["contents"] == [
+        {"role": "user", "parts": [{"text": "Weather in Paris?"}]},
+        {
+            "role": "model",
+            "parts": [
+                {"functionCall": {"name": "weather", "args": {"city": "Paris"}, "id": "c1"}, "thoughtSignature": "sig"},
+                {"functionCall": {"name": "weather", "args": {"city": "Rome"}, "id": "c2"}},
+            ],
+        },
+        {
+            "role": "user",
+            "parts": [
+                {"functionResponse": {"name": "weather", "response": {"temp": 21}, "id": "c1"}},
+                {"functionResponse": {"name": "weather", "response": {"content": "sunny"}, "id": "c2"}},
+            ],
+        },
+    ]
+    assert body["generationConfig"] == {"maxOutputTokens": 100}
+    assert body["tools"][0]["functionDeclarations"][0]["name"] == "weather"
+    assert body["toolConfig"] == {"functionCallingConfig": {"mode": "AUTO"}}
+
+
+def test_images_become_inline_data():
+    content = [
+        {"type": "text", "text": "What is this?"},
+        {"type": "image_url", "image_url": {"url": "data:image/png;base64,AAAA"}},
+        {"type": "image_url", "image_url": {"url": "https://example.com/cat.jpg"}},
+    ]
+    body = snapshot_request("gemini", RunParams(messages=[Message.create_user(content)]))
+    assert body["contents"][0]["parts"][1:] == [
+        {"inlineData": {"mimeType": "image/png", "data": "AAAA"}},
+        {"fileData": {"mimeType": "image/jpeg", "fileUri": "https://example.com/cat.jpg"}},
+    ]
+
+
+@pytest.mark.asyncio
+async def test_generate_content(monkeypatch):
+    monkeypatch.setenv("GEMINI_API_KEY", "env-key")
+    seen = []
+
+    def handler(request):
+        seen.append(request)
+        return httpx.Response(200, json=RESPONSE)
+
+    http_client = httpx.AsyncClient(transport=httpx.MockTransport(handler))
+    client = create_client(ModelConfig(provider="gemini", model="gemini-2.5-flash"), http_client=http_client)
+    assert isinstance(client, GeminiClient)
+    [response] = [r async for r in client._run(_params())]
+    await client.aclose()
+
+    url = "https://generativelanguage.googleapis.com/v1beta/models/gemini-2.5-flash:generateContent"
+    assert str(seen[0].url) == url
+    assert seen[0].headers["x-goog-api-key"] == "env-key"
+    message = response.choices[0].message
+    assert (message.content, message.reasoning_content) == ("Let me look.", "Checking the weather.")
+    assert message.tool_calls == [
+        {
+            "id": "call_0",
+            "type": "function",
+            "function": {"name": "weather", "arguments": '{"city": "Paris"}'},
+            "extra_content": {"google": {"thought_signature": "sig"}},
+        }
+    ]
+    assert response.choices[0].finish_reason == "tool_calls"
+    assert response.usage.model_dump() == {"prompt_tokens": 10, "completion_tokens": 8, "total_tokens": 18}
+    assert (response.id, response.model) == ("r1", "gemini-2.5-flash")
+
+
+def test_stream_generate_content():
+    first = {"candidates": [{"content": {"parts": [{"text": "Let me "}]}}], "usageMetadata": {"promptTokenCount": 10}}
+    second = {
+        "candidates": [{"content": {"parts": [{"text": "look."}, *RESPONSE["candidates"][0]["content"]["parts"][2:]]}}],
+    }
+    body = "".join(f"data: {json.dumps(chunk)}\r\n\r\n" for chunk in (first, second, RESPONSE)).encode()
+    seen = []
+
+    def handler(request):
+        seen.append(request)
+        return httpx.Response(200, content=body)
+
+    client = SyncGeminiClient(
+        ModelConfig(provider="gemini", model="gemini-2.5-flash", api_key="k"),
+        client=httpx.Client(transport=httpx.MockTransport(handler)),
+    )
+    chunks = list(client._run(_params(stream=True)))
+    client.close()
+
+    assert seen[0].url.path.endswith(":streamGenerateContent") and seen[0].url.params["alt"] == "sse"
+    assert seen[0].headers["x-goog-api-key"] == "k"
+    calls = {}
+    for chunk in chunks:
+        merge_tool_call_deltas(calls, chunk.choices[0].delta.tool_calls or [])
+    assert [(i, c["id"], c["function"]["name"]) for i, c in sorted(calls.items())] == [
+        (0, "call_0", "weather"),
+        (1, "call_1", "weather"),
+    ]
+    assert [c.usage is not None for c in chunks] == [False, False, True]
+    assert chunks[-1].choices[0].finish_reason == "tool_calls"
+
+
+@pytest.mark.asyncio
+async def test_blocked_prompt_becomes_an_error_response():
+    blocked = {"promptFeedback": {"blockReason": "SAFETY"}}
+    client = GeminiClient(
+        ModelConfig(provider="gemini", model="m", api_key="k"),
+        client=httpx.AsyncClient(transport=httpx.MockTransport(lambda request: httpx.Response(200, json=blocked))),
+    )
+    [response] = [r async for r in client._run(_params())]
+    assert response.error["code"] == "content_filter"
+    assert "SAFETY" in response.error["message"]
+    await client.aclose()
+
+
+def test_sync_factory():
+    assert isinstance(create_sync_client(ModelConfig(provider="gemini", model="m")), SyncGeminiClient)
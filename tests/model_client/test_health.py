@@ -0,0 +1,69 @@
+import httpx
+import pytest
+
+from prompti.message import Message
+from prompti.model_client import ClientManager, HealthTracker, ModelConfig, RateLimitHeadroom, RunParams
+from prompti.model_client.health import time as health_time
+
+CFG = ModelConfig(provider="openai", model="gpt-4o", api_key="k")
+
+
+def test_success_rate_p95_and_window(monkeypatch):
+    now = [1000.0]
+    monkeypatch.setattr(health_time, "monotonic", lambda: now[0])
+    tracker = HealthTracker(window_s=60)
+    for duration in range(1, 20):
+        tracker.on_complete(CFG, None, None, float(duration))
+    tracker.on_error(CFG, None, "server", {"message": "boom"})
+    health = tracker.snapshot("openai")
+    assert health.requests == 20
+    assert health.success_rate == pytest.approx(0.95)
+    assert health.p95_latency == 19.0
+    now[0] += 61
+    assert tracker.snapshot("openai").requests == 0
+    assert tracker.snapshot("other").success_rate is None
+
+
+def test_circuit_opens_half_opens_and_closes(monkeypatch):
+    now = [1000.0]
+    monkeypatch.setattr(health_time, "monotonic", lambda: now[0])
+    tracker = HealthTracker(failure_threshold=2, cooldown_s=10)
+    tracker.on_error(CFG, None, "server", {"message": "boom"})
+    assert tracker.snapshot("openai").circuit_state == "closed"
+    tracker.on_error(CFG, None, "timeout", TimeoutError())
+    assert tracker.snapshot("openai").circuit_state == "open"
+    now[0] += 10
+    assert tracker.snapshot("openai").circuit_state == "half_open"
+    tracker.on_complete(CFG, None, None, 0.5)
+    health = tracker.snapshot("openai")
+    assert (health.circuit_state, health.consecutive_failures) == ("closed", 0)
+
+
+def test_rate_limit_headers():
+    headers = {"x-ratelimit-limit-requests": "500", "x-ratelimit-remaining-requests": "499"}
+    limit = RateLimitHeadroom.from_headers(headers)
+    assert (limit.limit_requests, limit.remaining_requests, limit.remaining_tokens) == (500, 499, None)
+    assert RateLimitHeadroom.from_headers({}) is None
+
+
+@pytest.mark.asyncio
+async def test_manager_provider_health():
+    def handler(request):
+        if b'"bad"' in request.content:
+            return httpx.Response(400, json={"error": {"message": "bad request"}})
+        body = {"choices": [{"index": 0, "message": {"role": "assistant", "content": "hi"}}]}
+        return httpx.Response(200, json=body, headers={"x-ratelimit-remaining-tokens": "9000"})
+
+    http = httpx.AsyncClient(transport=httpx.MockTransport(handler))
+    profiles = {"fast": CFG, "local": ModelConfig(provider="litellm", model="m")}
+    async with ClientManager(profiles, http_client=http) as manager:
+        client = manager.get("fast")
+        for text in ("ok", "bad", "ok"):
+            params = RunParams(messages=[Message.create_user(text)], stream=False)
+            [r async for r in client.arun(params)]
+        health = manager.provider_health()
+    assert set(health) == {"openai", "litellm"}
+    assert health["openai"].requests == 3
+    assert health["openai"].success_rate == pytest.approx(2 / 3)
+    assert health["openai"].rate_limit.remaining_tokens == 9000
+    assert health["litellm"].requests == 0 and health["litellm"].rate_limit is None
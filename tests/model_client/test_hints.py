@@ -0,0 +1,62 @@
+import httpx
+import pytest
+
+from prompti.message import Message
+from prompti.model_client import ModelCapabilities, ModelConfig, RunParams, remediation
+from prompti.model_client.types import ErrorClass
+
+CFG = ModelConfig(provider="openai", model="gpt-4o")
+
+
+@pytest.mark.parametrize(
+    "error, reason, hint",
+    [
+        ({"status_code": 429, "retry_after": "20"}, "rate_limit", "request a quota increase. Retry after 20s."),
+        ({"status_code": 401}, "auth", "check the API key and that it may use gpt-4o"),
+        ({"code": "timeout"}, "timeout", "The request timed out"),
+        ({"code": "deadline_exceeded"}, "timeout", "ran past timeout.deadline_s"),
+        ({"status_code": 503}, "server", "Server error at openai"),
+        ({"code": "content_filter"}, "content_filter", "Blocked by the content filter of openai"),
+        ({"code": "malformed_stream"}, "malformed_response", "check that api_url is a compatible endpoint"),
+        ({"status_code": 404}, "not_found", "check the model name (gpt-4o)"),
+        ({"status_code": 400, "message": "Invalid value for 'n'"}, "client", "Request rejected by openai"),
+    ],
+)
+def test_hint_per_error(error, reason, hint):
+    result = remediation(error, cfg=CFG)
+    assert result.reason == reason
+    assert hint in result.hint
+
+
+def test_context_exceeded_reports_token_counts():
+    message = (
+        "This model's maximum context length is 8192 tokens. However, you requested 9013 tokens "
+        "(8013 in the messages, 1000 in the completion)."
+    )
+    result = remediation({"status_code": 400, "code": "context_length_exceeded", "message": message}, cfg=CFG)
+    assert (result.error_class, result.reason) == (ErrorClass.CLIENT, "context_exceeded")
+    assert (result.prompt_tokens, result.context_window) == (9013, 8192)
+    assert result.hint.startswith("The prompt (~9013 tokens) exceeds the 8192-token context window of gpt-4o:")
+
+    anthropic = remediation({"message": "prompt is too long: 210000 tokens > 200000 maximum"})
+    assert (anthropic.prompt_tokens, anthropic.context_window) == (210000, 200000)
+
+
+def test_context_exceeded_estimates_from_the_request():
+    cfg = ModelConfig(provider="openai", model="m", capabilities=ModelCapabilities(context_window=100))
+    params = RunParams(messages=[Message.create_user("word " * 400)])
+    result = remediation({"status_code": 413, "message": "Request too large"}, cfg=cfg, params=params)
+    assert result.context_window == 100 and result.prompt_tokens > 100
+
+
+def test_exceptions_and_started_streams():
+    assert remediation(exc=httpx.ConnectError("refused")).reason == "server"
+    assert remediation({"status_code": 429}, streaming_started=True).reason == "stream"
+    assert "the provider" in remediation({"status_code": 400}).hint
+
+
+def test_deprecated_model_names_its_replacement():
+    cfg = ModelConfig(provider="anthropic", model="claude-2.1")
+    result = remediation({"status_code": 404, "message": "model: claude-2.1"}, cfg=cfg)
+    assert result.reason == "model_deprecated"
+    assert result.hint.startswith("claude-2.1 is deprecated or retired: use claude-3-5-sonnet-latest")
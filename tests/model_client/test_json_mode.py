@@ -0,0 +1,62 @@
+import json
+
+import pytest
+
+from prompti.message import Message, StreamingChoice, StreamingModelResponse
+from prompti.model_client.base import ModelClient, ModelConfig, RunParams
+from prompti.model_client.json_mode import JSON_INSTRUCTION
+
+
+def chunk(text, finish_reason=None):
+    delta = Message(role="assistant", content=text)
+    return StreamingModelResponse(choices=[StreamingChoice(index=0, delta=delta, finish_reason=finish_reason)])
+
+
+class NoJSONModeClient(ModelClient):
+    native_json_mode = False
+
+    def __init__(self, cfg=None):
+        super().__init__(cfg or ModelConfig(provider="dummy", model="m"))
+        self.calls = []
+
+    async def _run(self, params):
+        self.calls.append(params)
+        for text in ['Sure:\n```json\n{"label": "bil', 'ling", "tags": ["a",]}\n', "```"]:
+            yield chunk(text)
+        yield chunk("", finish_reason="stop")
+
+
+async def run(client, **kw):
+    params = RunParams(messages=[Message.create_system("Classify."), Message.create_user("refund")], **kw)
+    return "".join([r.get_text_content() or "" async for r in client.arun(params)])
+
+
+@pytest.mark.asyncio
+async def test_json_mode_is_emulated_for_providers_without_it():
+    client = NoJSONModeClient()
+    text = await run(client, response_format="json_object", postprocessors=["trim"])
+
+    assert json.loads(text) == {"label": "billing", "tags": ["a"]}
+    sent = client.calls[0]
+    assert sent.response_format is None
+    assert sent.messages[0].content == f"Classify.\n\n{JSON_INSTRUCTION}"
+    assert sent.postprocessors == ["trim", "repair_json"]
+
+
+@pytest.mark.asyncio
+async def test_emulate_json_mode_overrides_the_provider():
+    client = NoJSONModeClient(ModelConfig(provider="dummy", model="m", emulate_json_mode=False))
+    assert (await run(client, response_format="json_object")).startswith("Sure:")
+    assert client.calls[0].response_format == "json_object"
+
+    client = NoJSONModeClient()
+    client.native_json_mode = True
+    await run(client, response_format="json_object")
+    assert client.calls[0].response_format == "json_object"
+
+    client.cfg = ModelConfig(provider="dummy", model="m", emulate_json_mode=True)
+    await run(client, response_format="json_object")
+    assert client.calls[1].response_format is None
+
+    await run(client)
+    assert client.calls[2].postprocessors == []
@@ -0,0 +1,59 @@
+import json
+import socket
+
+import httpx
+
+from prompti.message import Message
+from prompti.model_client.base import ModelConfig, RunParams
+from prompti.model_client.openai_client import OpenAIClient, SyncOpenAIClient
+from prompti.model_client.openai_wire import SSEDecoder
+from prompti.model_client.transport import keepalive_socket_options
+from prompti.model_client.types import KeepAliveConfig
+
+
+def test_keepalive_socket_options():
+    options = keepalive_socket_options(KeepAliveConfig(idle_s=20, interval_s=5, count=3))
+    assert (socket.SOL_SOCKET, socket.SO_KEEPALIVE, 1) in options
+    if hasattr(socket, "TCP_KEEPIDLE"):
+        assert (socket.IPPROTO_TCP, socket.TCP_KEEPIDLE, 20) in options
+        assert (socket.IPPROTO_TCP, socket.TCP_KEEPINTVL, 5) in options
+        assert (socket.IPPROTO_TCP, socket.TCP_KEEPCNT, 3) in options
+
+
+def test_clients_apply_keepalive_to_their_own_pool():
+    cfg = ModelConfig(provider="openai", model="m", keepalive=KeepAliveConfig())
+    client = OpenAIClient(cfg)
+    pool = client._client._transport._pool
+    assert (socket.SOL_SOCKET, socket.SO_KEEPALIVE, 1) in pool._socket_options
+    assert client._client.timeout.read == 600
+    plain = OpenAIClient(ModelConfig(provider="openai", model="m"))
+    assert not plain._client._transport._pool._socket_options
+
+
+def test_sse_decoder_skips_keepalive_lines_and_handles_cr_endings():
+    decoder = SSEDecoder()
+    body = ': ping\r\rretry: 1000\r\nevent: ping\ndata: {"type": "ping"}\n\ndata: a\rdata: b\r'
+    payloads = [p for ch in body for p in decoder.feed(ch)]
+    assert payloads == ['{"type": "ping"}', "a"]
+    assert decoder.feed("\n") == ["b"]
+
+
+def test_stream_survives_gateway_keepalives():
+    delta = {"choices": [{"index": 0, "delta": {"content": "hi"}}]}
+    body = (
+        ": keep-alive\n\n"
+        "event: ping\ndata: {\"type\": \"ping\"}\n\n"
+        "data: \"ping\"\n\n"
+        f"data: {json.dumps(delta)}\r\n\r\n"
+        "data: [DONE]\r\n\r\n"
+    )
+
+    def handler(request):
+        return httpx.Response(200, content=body.encode())
+
+    client = SyncOpenAIClient(
+        ModelConfig(provider="openai", model="m"), client=httpx.Client(transport=httpx.MockTransport(handler))
+    )
+    responses = list(client._run(RunParams(messages=[Message.create_user("q")])))
+    assert [r.get_text_content() for r in responses] == ["hi"]
+    assert not any(r.error for r in responses)
@@ -0,0 +1,41 @@
+import pytest
+
+from prompti.model_client import ClientManager, ModelConfig, ModelConfigNotFoundError
+from prompti.model_client.config_loader import FileModelConfigLoader
+
+
+def make_manager(**kwargs):
+    return ClientManager({"fast": ModelConfig(provider="openai", model="gpt-4o-mini", api_key="k")}, **kwargs)
+
+
+@pytest.mark.asyncio
+async def test_get_caches_clients_and_shares_pool():
+    manager = make_manager()
+    manager.register("smart", ModelConfig(provider="openai", model="gpt-4o", api_key="k"))
+    fast = manager.get("fast")
+    assert manager.get("fast") is fast
+    smart = manager.get("smart")
+    assert smart is not fast
+    assert smart._client is fast._client
+    assert smart.cfg.model == "gpt-4o"
+    await manager.aclose()
+    assert fast._client.is_closed
+
+
+@pytest.mark.asyncio
+async def test_register_replaces_cached_client():
+    async with make_manager() as manager:
+        first = manager.get("fast")
+        manager.register("fast", ModelConfig(provider="openai", model="other", api_key="k"))
+        assert manager.get("fast") is not first
+        assert manager.get("fast").cfg.model == "other"
+
+
+@pytest.mark.asyncio
+async def test_unknown_names_fall_back_to_loaders(tmp_path):
+    path = tmp_path / "models.yaml"
+    path.write_text("models:\n  - provider: openai\n    model: from-file\n    api_key: k\n")
+    async with make_manager(loaders=[FileModelConfigLoader(path)]) as manager:
+        assert manager.get("from-file").cfg.model == "from-file"
+        with pytest.raises(ModelConfigNotFoundError):
+            manager.get("missing")
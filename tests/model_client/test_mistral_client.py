@@ -0,0 +1,122 @@
+import json
+
+import httpx
+import pytest
+
+from prompti.message import Message
+from prompti.model_client.base import ModelConfig, RunParams, ToolParams, ToolSpec
+from prompti.model_client.factory import create_client, create_sync_client
+from prompti.model_client.mistral_client import MistralClient, SyncMistralClient, mistral_tool_call_id
+from prompti.partial_json import merge_tool_call_deltas
+from prompti.testing import snapshot_request
+
+WEATHER = ToolSpec(name="weather", description="Weather of a city", parameters={"type": "object", "properties": {}})
+CALL = {"id": "D681PevKs", "type": "function", "function": {"name": "weather", "arguments": '{"city": "Paris"}'}}
+
+
+def _params(stream=False):
+    return RunParams(
+        messages=[Message.create_user("Weather in Paris?")],
+        tool_params=ToolParams(tools=[WEATHER], choice="required"),
+        stream=stream,
+        seed=7,
+        user_id="u1",
+    )
+
+
+def test_request_translation():
+    call = {**CALL, "id": "call_abc123"}
+    params = _params(stream=True)
+    params.messages += [Message.create_tool_call([call]), Message.create_tool_result("sunny", "call_abc123")]
+    cfg = ModelConfig(provider="mistral", model="mistral-large-latest", extra_params={"safe_prompt": True})
+    body = snapshot_request(cfg, params)
+    assert (body["random_seed"], body["tool_choice"], body["safe_prompt"]) == (7, "any", True)
+    assert not {"seed", "user", "stream_options"} & set(body)
+    short_id = mistral_tool_call_id("call_abc123")
+    assert len(short_id) == 9 and short_id.isalnum()
+    assert body["messages"][1]["tool_calls"][0]["id"] == body["messages"][2]["tool_call_id"] == short_id
+    # 原消息中的 id 不变
+    assert params.messages[1].tool_calls[0]["id"] == "call_abc123"
+    assert mistral_tool_call_id("D681PevKs") == "D681PevKs"
+
+
+@pytest.mark.asyncio
+async def test_chat_with_tool_call():
+    seen = []
+    response = {
+        "id": "cmpl-1",
+        "object": "chat.completion",
+        "created": 1721680408,
+        "model": "mistral-large-latest",
+        "choices": [
+            {
+                "index": 0,
+                "message": {"role": "assistant", "content": "", "tool_calls": [CALL]},
+                "finish_reason": "tool_calls",
+            }
+        ],
+        "usage": {"prompt_tokens": 12, "completion_tokens": 7, "total_tokens": 19},
+    }
+
+    def handler(request):
+        seen.append(request)
+        return httpx.Response(200, json=response)
+
+    http_client = httpx.AsyncClient(transport=httpx.MockTransport(handler))
+    cfg = ModelConfig(provider="mistral", model="mistral-large-latest", api_key="key")
+    client = create_client(cfg, http_client=http_client)
+    assert isinstance(client, MistralClient)
+    [result] = [r async for r in client._run(_params())]
+    await client.aclose()
+    assert str(seen[0].url) == "https://api.mistral.ai/v1/chat/completions"
+    assert seen[0].headers["authorization"] == "Bearer key"
+    assert result.choices[0].message.tool_calls == [CALL]
+    assert result.usage.total_tokens == 19
+
+
+def test_stream(monkeypatch):
+    monkeypatch.setenv("MISTRAL_API_KEY", "env-key")
+    chunks = [
+        {"id": "1", "choices": [{"index": 0, "delta": {"role": "assistant", "content": "Sunny "}}]},
+        {"id": "1", "choices": [{"index": 0, "delta": {"content": "and", "tool_calls": [{**CALL, "index": 0}]}}]},
+        {
+            "id": "1",
+            "choices": [{"index": 0, "delta": {"content": ""}, "finish_reason": "model_length"}],
+            "usage": {"prompt_tokens": 5, "completion_tokens": 3, "total_tokens": 8},
+        },
+    ]
+    body = "".join(f"data: {json.dumps(c)}\n\n" for c in chunks).encode() + b"data: [DONE]\n\n"
+    seen = []
+
+    def handler(request):
+        seen.append(request)
+        return httpx.Response(200, content=body)
+
+    client = SyncMistralClient(
+        ModelConfig(provider="mistral", model="mistral-small-latest"),
+        client=httpx.Client(transport=httpx.MockTransport(handler)),
+    )
+    responses = list(client._run(_params(stream=True)))
+    client.close()
+    assert seen[0].headers["authorization"] == "Bearer env-key"
+    assert "".join(r.choices[0].delta.content or "" for r in responses) == "Sunny and"
+    calls = {}
+    for r in responses:
+        merge_tool_call_deltas(calls, r.choices[0].delta.tool_calls or [])
+    assert calls[0]["function"]["arguments"] == '{"city": "Paris"}'
+    assert (responses[-1].choices[0].finish_reason, responses[-1].usage.total_tokens) == ("length", 8)
+
+
+def test_error_message():
+    error = {"object": "error", "message": "Invalid model: mistral-huge", "type": "invalid_model", "code": "1500"}
+    client = SyncMistralClient(
+        ModelConfig(provider="mistral", model="mistral-huge"),
+        client=httpx.Client(transport=httpx.MockTransport(lambda request: httpx.Response(400, json=error))),
+    )
+    [response] = list(client._run(_params()))
+    client.close()
+    assert (response.error["message"], response.error["status_code"]) == ("Invalid model: mistral-huge", 400)
+
+
+def test_sync_factory():
+    assert isinstance(create_sync_client(ModelConfig(provider="mistral", model="m")), SyncMistralClient)
@@ -0,0 +1,138 @@
+import json
+
+import httpx
+import pytest
+
+from prompti.message import Message
+from prompti.model_client.base import ModelConfig, RunParams, ToolParams, ToolSpec
+from prompti.model_client.factory import create_client, create_sync_client
+from prompti.model_client.ollama_client import OllamaClient, SyncOllamaClient
+from prompti.partial_json import merge_tool_call_deltas
+from prompti.testing import snapshot_request
+
+WEATHER = ToolSpec(name="weather", description="Weather of a city", parameters={"type": "object", "properties": {}})
+
+RESPONSE = {
+    "model": "llama3.1",
+    "created_at": "2024-07-22T20:33:28.123648Z",
+    "message": {
+        "role": "assistant",
+        "content": "",
+        "thinking": "Check the weather.",
+        "tool_calls": [{"function": {"name": "weather", "arguments": {"city": "Paris"}}}],
+    },
+    "done": True,
+    "done_reason": "stop",
+    "prompt_eval_count": 12,
+    "eval_count": 7,
+}
+
+
+def _params(stream=False):
+    return RunParams(
+        messages=[Message.create_system("Be brief."), Message.create_user("Weather in Paris?")],
+        tool_params=ToolParams(tools=[WEATHER]),
+        stream=stream,
+        temperature=0.2,
+        max_tokens=100,
+        stop="END",
+    )
+
+
+def test_request_translation():
+    call = {"id": "c1", "type": "function", "function": {"name": "weather", "arguments": '{"city": "Paris"}'}}
+    image = {"type": "image_url", "image_url": {"url": "data:image/png;base64,AAAA"}}
+    params = _params()
+    params.messages += [
+        Message.create_tool_call([call]),
+        Message.create_tool_result('{"temp": 21}', "c1"),
+        Message.create_user([{"type": "text", "text": "And this?"}, image]),
+    ]
+    body = snapshot_request("ollama", params, model="llama3.1")
+    assert body["messages"][2:] == [
+        {
+            "role": "assistant",
+            "content": "",
+            "tool_calls": [{"function": {"name": "weather", "arguments": {"city": "Paris"}}}],
+        },
+        {"role": "tool", "content": '{"temp": 21}', "tool_name": "weather"},
+        {"role": "user", "content": "And this?", "images": ["AAAA"]},
+    ]
+    assert body["options"] == {"temperature": 0.2, "num_predict": 100, "stop": ["END"]}
+    assert body["tools"][0]["function"]["name"] == "weather"
+    assert "tool_choice" not in body and "max_tokens" not in body
+
+
+def test_image_urls_are_rejected():
+    image = {"type": "image_url", "image_url": {"url": "https://example.com/cat.jpg"}}
+    with pytest.raises(ValueError, match="base64"):
+        snapshot_request("ollama", RunParams(messages=[Message.create_user([image])]))
+
+
+@pytest.mark.asyncio
+async def test_chat():
+    seen = []
+
+    def handler(request):
+        seen.append(request)
+        return httpx.Response(200, json=RESPONSE)
+
+    http_client = httpx.AsyncClient(transport=httpx.MockTransport(handler))
+    client = create_client(ModelConfig(provider="ollama", model="llama3.1"), http_client=http_client)
+    assert isinstance(client, OllamaClient)
+    [response] = [r async for r in client._run(_params())]
+    await client.aclose()
+
+    assert str(seen[0].url) == "http://localhost:11434/api/chat"
+    assert json.loads(seen[0].content)["stream"] is False
+    message = response.choices[0].message
+    assert (message.content, message.reasoning_content) == (None, "Check the weather.")
+    assert message.tool_calls == [
+        {"id": "call_0", "type": "function", "function": {"name": "weather", "arguments": '{"city": "Paris"}'}}
+    ]
+    assert response.choices[0].finish_reason == "tool_calls"
+    assert response.usage.model_dump() == {"prompt_tokens": 12, "completion_tokens": 7, "total_tokens": 19}
+    assert response.created == 1721680408
+
+
+def test_ndjson_stream():
+    lines = [
+        {"model": "llama3.1", "message": {"role": "assistant", "content": "Let me "}, "done": False},
+        {"model": "llama3.1", "message": {"role": "assistant", "content": "look."}, "done": False},
+        {**RESPONSE, "message": {**RESPONSE["message"], "thinking": ""}, "done": False},
+        {**RESPONSE, "message": {"role": "assistant", "content": ""}},
+    ]
+    body = "".join(json.dumps(line) + "\n" for line in lines).encode()
+    client = SyncOllamaClient(
+        ModelConfig(provider="ollama", model="llama3.1"),
+        client=httpx.Client(transport=httpx.MockTransport(lambda request: httpx.Response(200, content=body))),
+    )
+    chunks = list(client._run(_params(stream=True)))
+    client.close()
+
+    assert "".join(c.choices[0].delta.content or "" for c in chunks) == "Let me look."
+    calls = {}
+    for chunk in chunks:
+        merge_tool_call_deltas(calls, chunk.choices[0].delta.tool_calls or [])
+    assert [(i, c["id"], c["function"]["arguments"]) for i, c in calls.items()] == [(0, "call_0", '{"city": "Paris"}')]
+    assert [c.usage is not None for c in chunks] == [False, False, False, True]
+    assert chunks[-1].choices[0].finish_reason == "tool_calls"
+
+
+@pytest.mark.asyncio
+async def test_stream_error_line_becomes_an_error_response():
+    first = {"model": "m", "message": {"role": "assistant", "content": "Hi"}, "done": False}
+    lines = [first, {"error": "model crashed"}]
+    body = "".join(json.dumps(line) + "\n" for line in lines).encode()
+    client = OllamaClient(
+        ModelConfig(provider="ollama", model="m"),
+        client=httpx.AsyncClient(transport=httpx.MockTransport(lambda request: httpx.Response(200, content=body))),
+    )
+    chunks = [c async for c in client._run(_params(stream=True))]
+    await client.aclose()
+    assert chunks[0].choices[0].delta.content == "Hi"
+    assert chunks[-1].error["message"] == "model crashed"
+
+
+def test_sync_factory():
+    assert isinstance(create_sync_client(ModelConfig(provider="ollama", model="m")), SyncOllamaClient)
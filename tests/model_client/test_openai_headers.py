@@ -0,0 +1,86 @@
+import httpx
+import pytest
+
+from prompti.message import Message
+from prompti.model_client.base import ModelConfig, RunParams
+from prompti.model_client.openai_client import OpenAIClient, SyncOpenAIClient
+
+
+def test_organization_and_project_headers():
+    cfg = ModelConfig(provider="openai", model="gpt-4o", api_key="sk", organization="org-1", project="proj-1")
+    for cls in (OpenAIClient, SyncOpenAIClient):
+        headers = cls(cfg)._build_headers()
+        assert headers["Authorization"] == "Bearer sk"
+        assert headers["OpenAI-Organization"] == "org-1"
+        assert headers["OpenAI-Project"] == "proj-1"
+
+
+def test_headers_omitted_when_unset():
+    headers = OpenAIClient(ModelConfig(provider="openai", model="gpt-4o"))._build_headers()
+    assert "OpenAI-Organization" not in headers
+    assert "OpenAI-Project" not in headers
+
+
+def test_beta_features_header():
+    cfg = ModelConfig(
+        provider="openai", model="claude", beta_features=["prompt-caching-2024-07-31", "context-1m-2025-08-07"]
+    )
+    headers = OpenAIClient(cfg)._build_headers()
+    assert headers["anthropic-beta"] == "prompt-caching-2024-07-31,context-1m-2025-08-07"
+
+
+def _recording_transport(seen):
+    def handler(request):
+        seen.append(request.headers)
+        if b'"stream": true' in request.content or b'"stream":true' in request.content:
+            return httpx.Response(200, text='data: {"choices": []}\n\ndata: [DONE]\n\n')
+        return httpx.Response(200, json={"choices": [{"index": 0, "message": {"role": "assistant", "content": "hi"}}]})
+
+    return httpx.MockTransport(handler)
+
+
+def _params(stream):
+    return RunParams(
+        messages=[Message.create_user("q")],
+        stream=stream,
+        extra_headers={"X-Call": "2"},
+        idempotency_key="idem-1",
+    )
+
+
+def _assert_custom_headers(headers):
+    assert headers["X-Gateway"] == "1"
+    assert headers["X-Call"] == "2"
+    assert headers["Idempotency-Key"] == "idem-1"
+    assert headers["anthropic-beta"] == "b1"
+    assert headers["OpenAI-Organization"] == "org-1"
+
+
+CFG = ModelConfig(
+    provider="openai", model="m", organization="org-1", beta_features=["b1"], extra_headers={"X-Gateway": "1"}
+)
+
+
+@pytest.mark.asyncio
+async def test_stream_and_non_stream_send_same_headers():
+    seen = []
+    client = OpenAIClient(CFG, client=httpx.AsyncClient(transport=_recording_transport(seen)))
+    for stream in (True, False):
+        async for _ in client._run(_params(stream)):
+            pass
+    await client.aclose()
+    assert len(seen) == 2
+    for headers in seen:
+        _assert_custom_headers(headers)
+
+
+def test_sync_stream_and_non_stream_send_same_headers():
+    seen = []
+    client = SyncOpenAIClient(CFG, client=httpx.Client(transport=_recording_transport(seen)))
+    for stream in (True, False):
+        for _ in client._run(_params(stream)):
+            pass
+    client.close()
+    assert len(seen) == 2
+    for headers in seen:
+        _assert_custom_headers(headers)
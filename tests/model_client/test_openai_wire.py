@@ -0,0 +1,142 @@
+import json
+
+import httpx
+import pytest
+
+from prompti.message import Message
+from prompti.model_client.azure_client import AzureOpenAIClient, SyncAzureOpenAIClient
+from prompti.model_client.base import ModelConfig, RunParams
+from prompti.model_client.factory import create_client, create_sync_client
+from prompti.model_client.openai_client import OpenAIClient
+from prompti.model_client.openai_wire import MalformedResponseError, SSEDecoder
+
+
+def test_sse_decoder_handles_split_lines():
+    decoder = SSEDecoder()
+    assert decoder.feed('data: {"a"') == []
+    assert decoder.feed(': 1}\n\ndata:[DONE]\n') == ['{"a": 1}', "[DONE]"]
+    assert decoder.feed(": keep-alive comment\n") == []
+
+
+def test_azure_uses_api_key_header_and_deployment_url():
+    cfg = ModelConfig(
+        provider="azure",
+        model="my-deployment",
+        api_key="secret",
+        api_url="https://res.openai.azure.com/",
+        extra_params={"api_version": "2024-10-21"},
+    )
+    client = SyncAzureOpenAIClient(cfg)
+    request = client._build_request(RunParams(messages=[Message.create_user("q")], stream=False))
+    assert str(request.url) == (
+        "https://res.openai.azure.com/openai/deployments/my-deployment/chat/completions?api-version=2024-10-21"
+    )
+    assert request.headers["api-key"] == "secret"
+    assert "Authorization" not in request.headers
+
+
+def test_azure_deployment_and_api_version_fields():
+    cfg = ModelConfig(
+        provider="azure",
+        model="gpt-5",
+        deployment="prod-gpt5",
+        api_version="2025-04-01-preview",
+        api_url="https://res.openai.azure.com",
+        extra_params={"api_version": "2024-10-21"},
+    )
+    client = SyncAzureOpenAIClient(cfg)
+    request = client._build_request(RunParams(messages=[Message.create_user("q")], stream=False, max_tokens=10))
+    assert str(request.url) == (
+        "https://res.openai.azure.com/openai/deployments/prod-gpt5/chat/completions?api-version=2025-04-01-preview"
+    )
+    # the model name, not the deployment, decides reasoning-model handling
+    assert json.loads(request.content)["max_completion_tokens"] == 10
+
+
+def embeddings_handler(seen):
+    def handler(request):
+        seen.append(request)
+        return httpx.Response(200, json={"object": "list", "data": [{"embedding": [0.1]}]})
+
+    return httpx.MockTransport(handler)
+
+
+@pytest.mark.asyncio
+async def test_embeddings_use_the_endpoint_next_to_chat_completions():
+    seen = []
+    client = OpenAIClient(
+        ModelConfig(provider="openai", model="text-embedding-3-small", api_key="k"),
+        client=httpx.AsyncClient(transport=embeddings_handler(seen)),
+    )
+    data = await client.aembeddings({"model": "alias", "input": "hi"})
+    assert data["data"][0]["embedding"] == [0.1]
+    assert str(seen[0].url) == "https://api.openai.com/v1/embeddings"
+    assert json.loads(seen[0].content) == {"model": "text-embedding-3-small", "input": "hi"}
+
+
+def test_azure_embeddings_use_the_deployment_url():
+    seen = []
+    client = SyncAzureOpenAIClient(
+        ModelConfig(provider="azure", model="embed", api_url="https://res.openai.azure.com"),
+        client=httpx.Client(transport=embeddings_handler(seen)),
+    )
+    client.embeddings({"input": ["a"]})
+    assert str(seen[0].url) == "https://res.openai.azure.com/openai/deployments/embed/embeddings?api-version=2024-06-01"
+
+
+def test_factory_registers_only_concrete_providers():
+    cfg = ModelConfig(provider="azure", model="d", api_url="https://res.openai.azure.com")
+    assert isinstance(create_client(cfg), AzureOpenAIClient)
+    assert isinstance(create_sync_client(cfg), SyncAzureOpenAIClient)
+    with pytest.raises(ValueError):
+        create_client(ModelConfig(provider="generic", model="m"))
+
+
+@pytest.mark.asyncio
+async def test_http_errors_are_parsed_into_error_responses():
+    def handler(request):
+        return httpx.Response(429, headers={"retry-after": "3"}, json={"error": {"message": "slow down"}})
+
+    client = OpenAIClient(
+        ModelConfig(provider="openai", model="m"), client=httpx.AsyncClient(transport=httpx.MockTransport(handler))
+    )
+    for stream in (True, False):
+        responses = [r async for r in client._run(RunParams(messages=[Message.create_user("q")], stream=stream))]
+        error = responses[0].error
+        assert error["message"] == "slow down"
+        assert error["status_code"] == 429
+        assert error["retry_after"] == "3"
+    await client.aclose()
+
+
+# inputs found by the fuzz targets in fuzz/
+MALFORMED_CHUNKS = [
+    '{"id": "x", "choices": -1}',
+    '{"id": "x", "choices": {"a": 1}}',
+    '{"id": true, "choices": [{"index": 0, "delta": {"content": "hi"}}]}',
+    '{"choices": [{"index": 0, "delta": {"content": "hi"}}], "usage": "s"}',
+    '{"choices": [{"index": 0, "delta": []}]}',
+]
+
+
+@pytest.mark.parametrize("payload", MALFORMED_CHUNKS)
+def test_malformed_stream_chunks_raise_a_typed_error(payload):
+    client = OpenAIClient(ModelConfig(provider="openai", model="m"), client=httpx.AsyncClient())
+    with pytest.raises(MalformedResponseError) as info:
+        client._parse_stream_chunk(payload)
+    assert info.value.code == "malformed_stream"
+
+
+@pytest.mark.asyncio
+@pytest.mark.parametrize(
+    "body",
+    [b"not json", b'""', b'{"choices": [-1]}', b'{"choices": [{"index": 0}]}', b"[" * 100_000 + b"]" * 100_000],
+)
+async def test_malformed_responses_become_error_responses(body):
+    client = OpenAIClient(
+        ModelConfig(provider="openai", model="m"),
+        client=httpx.AsyncClient(transport=httpx.MockTransport(lambda request: httpx.Response(200, content=body))),
+    )
+    [response] = [r async for r in client._run(RunParams(messages=[Message.create_user("q")], stream=False))]
+    assert response.error["code"] == "malformed_response"
+    await client.aclose()
@@ -0,0 +1,96 @@
+import httpx
+import pytest
+
+from prompti.message import Message
+from prompti.model_client.base import ModelConfig, RunParams
+from prompti.model_client.openai_client import SyncOpenAIClient
+from prompti.model_client import ratelimit
+from prompti.model_client.ratelimit import RateLimiter, RateLimitWaitError, limiter_for, reset_rate_limits
+from prompti.model_client.types import RateLimitConfig
+
+OK = {
+    "id": "1",
+    "choices": [{"index": 0, "message": {"role": "assistant", "content": "Hi"}, "finish_reason": "stop"}],
+    "usage": {"prompt_tokens": 5, "completion_tokens": 2, "total_tokens": 7},
+}
+
+
+@pytest.fixture(autouse=True)
+def _fresh_limiters():
+    reset_rate_limits()
+    yield
+    reset_rate_limits()
+
+
+@pytest.fixture
+def fake_time(monkeypatch):
+    now = [0.0]
+
+    def sleep(seconds):
+        now[0] += seconds
+
+    monkeypatch.setattr(ratelimit.time, "sleep", sleep)
+    return now
+
+
+def _limiter(now, **config):
+    return RateLimiter(RateLimitConfig(**config), "openai/gpt-4o", clock=lambda: now[0])
+
+
+def test_requests_per_minute(fake_time):
+    limiter = _limiter(fake_time, requests_per_minute=2)
+    assert limiter.acquire_sync()[1] == 0
+    fake_time[0] = 10
+    assert limiter.acquire_sync()[1] == 0
+    # the third request waits until the first leaves the window
+    assert limiter.acquire_sync()[1] == 50
+    assert fake_time[0] == 60
+
+
+def test_tokens_per_minute_are_settled_to_usage(fake_time):
+    limiter = _limiter(fake_time, tokens_per_minute=100)
+    reservation, _ = limiter.acquire_sync(60)
+    assert limiter.delay(70) == 60
+    limiter.settle(reservation, 20)
+    assert limiter.acquire_sync(70)[1] == 0
+    # larger than the whole budget: waits for an empty window, then runs alone
+    assert limiter.acquire_sync(500)[1] == 60
+    assert limiter.delay(1) == 60
+
+
+def test_max_wait(fake_time):
+    limiter = _limiter(fake_time, requests_per_minute=1, max_wait_s=5)
+    limiter.acquire_sync()
+    with pytest.raises(RateLimitWaitError, match="needs a wait of 60.00s") as info:
+        limiter.acquire_sync()
+    assert info.value.retry_after == 60
+
+
+def test_limiters_are_shared_by_scope():
+    config = RateLimitConfig(requests_per_minute=10)
+    gpt = limiter_for(ModelConfig(provider="openai", model="gpt-4o", rate_limit=config))
+    assert limiter_for(ModelConfig(provider="openai", model="gpt-4o", rate_limit=config)) is gpt
+    assert limiter_for(ModelConfig(provider="openai", model="gpt-4o-mini", rate_limit=config)) is not gpt
+    provider = RateLimitConfig(requests_per_minute=10, scope="provider")
+    assert limiter_for(ModelConfig(provider="openai", model="o3", rate_limit=provider)).name == "openai"
+    assert limiter_for(ModelConfig(provider="openai", model="gpt-4o", rate_limit=RateLimitConfig())) is None
+
+
+def test_client_waits_for_the_budget():
+    cfg = ModelConfig(
+        provider="openai",
+        model="gpt-4o",
+        api_key="k",
+        rate_limit=RateLimitConfig(requests_per_minute=1, tokens_per_minute=10_000, max_wait_s=0),
+    )
+    transport = httpx.MockTransport(lambda request: httpx.Response(200, json=OK))
+    client = SyncOpenAIClient(cfg, client=httpx.Client(transport=transport))
+    params = RunParams(messages=[Message.create_user("q")], stream=False)
+    [response] = list(client.run(params))
+    assert response.get_text_content() == "Hi"
+    assert params.trace_context["perf_metrics"]["rate_limit_wait"] == 0
+    # the estimate is replaced by the reported usage
+    assert [r.tokens for r in limiter_for(cfg)._reservations] == [7]
+
+    with pytest.raises(RateLimitWaitError):
+        list(client.run(RunParams(messages=[Message.create_user("q")], stream=False)))
@@ -0,0 +1,37 @@
+import httpx
+
+from prompti.message import Message
+from prompti.model_client.base import ModelConfig, RunParams
+from prompti.model_client.openai_client import SyncOpenAIClient
+
+
+def test_hash_ignores_defaults_ordering_and_call_identity():
+    a = RunParams(messages=[Message.create_user("hi")], temperature=0.2, extra_params={"a": 1, "b": 2})
+    b = RunParams(
+        extra_params={"b": 2, "a": 1},
+        temperature=0.2,
+        messages=[Message(role="user", content="hi", tool_calls=None)],
+        top_p=None,
+        stream=False,
+        request_id="r2",
+        user_id="u",
+        trace_context={"x": 1},
+    )
+    assert a.canonical_hash() == b.canonical_hash()
+    assert len(a.canonical_hash()) == 64
+
+
+def test_hash_changes_with_request_content():
+    base = RunParams(messages=[Message.create_user("hi")])
+    assert base.canonical_hash() != RunParams(messages=[Message.create_user("hi!")]).canonical_hash()
+    assert base.canonical_hash() != base.model_copy(update={"temperature": 0.0}).canonical_hash()
+    assert base.canonical_hash("gpt-4o") != base.canonical_hash("gpt-4o-mini")
+
+
+def test_client_records_hash_in_trace_context():
+    body = {"choices": [{"index": 0, "message": {"role": "assistant", "content": "ok"}}]}
+    http = httpx.Client(transport=httpx.MockTransport(lambda request: httpx.Response(200, json=body)))
+    client = SyncOpenAIClient(ModelConfig(provider="openai", model="m"), client=http)
+    params = RunParams(messages=[Message.create_user("hi")], stream=False)
+    list(client.run(params))
+    assert params.trace_context["request_hash"] == params.canonical_hash("m")
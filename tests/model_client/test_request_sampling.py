@@ -0,0 +1,19 @@
+import logging
+
+from prompti.model_client.base import ModelConfig, log_sampled_request
+
+
+def test_sampled_request_is_logged_redacted(caplog):
+    cfg = ModelConfig(provider="openai", model="gpt-4o", request_log_sample_rate=1.0)
+    with caplog.at_level(logging.DEBUG, logger="model_client.request_sample"):
+        log_sampled_request(cfg, {"model": "gpt-4o", "messages": [], "api_key": "sk-secret"})
+    assert len(caplog.records) == 1
+    assert "sk-secret" not in caplog.text
+    assert '"model":"gpt-4o"' in caplog.text
+
+
+def test_sampling_is_opt_in(caplog):
+    with caplog.at_level(logging.DEBUG, logger="model_client.request_sample"):
+        log_sampled_request(ModelConfig(provider="openai", model="m"), {"model": "m"})
+        log_sampled_request(ModelConfig(provider="openai", model="m", request_log_sample_rate=0.0), {"model": "m"})
+    assert caplog.records == []
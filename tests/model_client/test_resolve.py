@@ -0,0 +1,113 @@
+import http.server
+import threading
+
+import httpcore
+import httpx
+import pytest
+
+from prompti.model_client.base import ModelConfig
+from prompti.model_client.openai_client import OpenAIClient
+from prompti.model_client.transport import (
+    _ConnectPolicy,
+    build_async_http_client,
+    build_sync_http_client,
+    static_resolver,
+)
+
+
+class Handler(http.server.BaseHTTPRequestHandler):
+    def do_GET(self):
+        body = self.headers["Host"].encode()
+        self.send_response(200)
+        self.send_header("Content-Length", str(len(body)))
+        self.end_headers()
+        self.wfile.write(body)
+
+    def log_message(self, *args):
+        pass
+
+
+@pytest.fixture
+def server():
+    httpd = http.server.HTTPServer(("127.0.0.1", 0), Handler)
+    thread = threading.Thread(target=httpd.serve_forever, daemon=True)
+    thread.start()
+    yield httpd.server_address[1]
+    httpd.shutdown()
+    httpd.server_close()
+
+
+def test_static_resolver_is_case_insensitive():
+    resolve = static_resolver({"API.openai.com": "203.0.113.7", "b.example": ["10.0.0.1", "10.0.0.2"]})
+    assert resolve("api.openai.com") == ["203.0.113.7"]
+    assert resolve("b.example") == ["10.0.0.1", "10.0.0.2"]
+    assert resolve("other.example") is None
+
+
+def test_sync_client_connects_to_pinned_address(server):
+    # 127.0.0.2 refuses (the server only listens on 127.0.0.1), so the next address is tried
+    cfg = ModelConfig(resolve={"api.provider.invalid": ["127.0.0.2", "127.0.0.1"]})
+    with build_sync_http_client(cfg) as client:
+        response = client.get(f"http://api.provider.invalid:{server}/")
+    # the request still carries the original host name
+    assert response.text == f"api.provider.invalid:{server}"
+
+
+@pytest.mark.asyncio
+async def test_async_client_uses_custom_resolver(server):
+    looked_up = []
+
+    def resolver(host):
+        looked_up.append(host)
+        return ["127.0.0.1"] if host == "internal.invalid" else None
+
+    async with build_async_http_client(ModelConfig(), resolver=resolver) as client:
+        response = await client.get(f"http://internal.invalid:{server}/")
+    assert response.text == f"internal.invalid:{server}"
+    assert looked_up == ["internal.invalid"]
+
+
+def test_clients_apply_resolve_to_their_own_pool():
+    client = OpenAIClient(ModelConfig(provider="openai", model="m", resolve={"api.openai.com": "203.0.113.7"}))
+    backend = client._client._transport._pool._network_backend
+    assert backend._policy.pinned("api.openai.com") == ["203.0.113.7"]
+    # an explicitly passed client is used as is
+    plain = httpx.AsyncClient()
+    assert OpenAIClient(ModelConfig(provider="openai", model="m", resolve={"a": "b"}), client=plain)._client is plain
+
+
+def test_ip_family_filters_and_orders_addresses():
+    addresses = ["2001:db8::1", "192.0.2.1", "2001:db8::2", "192.0.2.2"]
+    assert _ConnectPolicy(None, "ipv4", None).candidates("h", addresses) == ["192.0.2.1", "192.0.2.2"]
+    assert _ConnectPolicy(None, "ipv6", None).candidates("h", addresses) == ["2001:db8::1", "2001:db8::2"]
+    assert _ConnectPolicy(None, "prefer_ipv4", None).candidates("h", addresses) == [
+        "192.0.2.1",
+        "192.0.2.2",
+        "2001:db8::1",
+        "2001:db8::2",
+    ]
+    with pytest.raises(httpcore.ConnectError, match="No IPv6 address"):
+        _ConnectPolicy(None, "ipv6", None).candidates("h", ["192.0.2.1"])
+
+
+def test_fallback_delay_caps_all_but_the_last_attempt():
+    policy = _ConnectPolicy(None, None, 0.3)
+    assert policy.attempt_timeout(10, last=False) == 0.3
+    assert policy.attempt_timeout(None, last=False) == 0.3
+    assert policy.attempt_timeout(10, last=True) == 10
+
+
+def test_ipv4_only_skips_ipv6_addresses(server):
+    cfg = ModelConfig(ip_family="ipv4", resolve={"dual.invalid": ["::1", "127.0.0.1"]})
+    with build_sync_http_client(cfg) as client:
+        assert client.get(f"http://dual.invalid:{server}/").status_code == 200
+    with build_sync_http_client(cfg.model_copy(update={"ip_family": "ipv6"})) as client:
+        with pytest.raises(httpx.ConnectError):
+            client.get(f"http://dual.invalid:{server}/")
+
+
+@pytest.mark.asyncio
+async def test_ip_family_applies_to_dns_results(server):
+    async with build_async_http_client(ModelConfig(ip_family="ipv4")) as client:
+        response = await client.get(f"http://localhost:{server}/")
+    assert response.status_code == 200
@@ -0,0 +1,127 @@
+import httpx
+import pytest
+
+from prompti.message import Choice, Message, ModelResponse
+from prompti.model_client.base import ModelClient, ModelConfig, RetryConfig, RunParams, parse_retry_after
+
+
+class FlakyClient(ModelClient):
+    provider = "flaky"
+
+    def __init__(self, cfg, outcomes):
+        super().__init__(cfg)
+        self.outcomes = list(outcomes)
+        self.calls = 0
+
+    async def _run(self, params):
+        self.calls += 1
+        outcome = self.outcomes.pop(0)
+        if isinstance(outcome, Exception):
+            raise outcome
+        for item in outcome:
+            yield item
+
+
+def ok(text="hi"):
+    return ModelResponse(choices=[Choice(index=0, message=Message(role="assistant", content=text))])
+
+
+def err(status, retry_after=None):
+    error = {"message": "boom", "type": "api_error", "code": "unknown_error", "status_code": status}
+    if retry_after:
+        error["retry_after"] = retry_after
+    return ModelResponse(error=error)
+
+
+def make_client(outcomes, **retry):
+    retry.setdefault("initial_backoff_ms", 0)
+    cfg = ModelConfig(provider="flaky", model="m", retry=RetryConfig(**retry))
+    return FlakyClient(cfg, outcomes)
+
+
+async def collect(client):
+    return [r async for r in client.arun(RunParams(messages=[Message.create_user("q")]))]
+
+
+@pytest.mark.asyncio
+async def test_retries_retryable_status_then_succeeds():
+    client = make_client([[err(503)], [err(429)], [ok()]])
+    responses = await collect(client)
+    assert client.calls == 3
+    assert [r.get_text_content() for r in responses] == ["hi"]
+
+
+@pytest.mark.asyncio
+async def test_gives_up_after_max_attempts():
+    client = make_client([[err(500)], [err(500)]], max_attempts=2)
+    responses = await collect(client)
+    assert client.calls == 2
+    assert responses[0].error["status_code"] == 500
+    assert responses[0].error["retries_exhausted"] is True
+
+
+@pytest.mark.asyncio
+async def test_non_retryable_status_is_returned_immediately():
+    client = make_client([[err(400)], [ok()]])
+    responses = await collect(client)
+    assert client.calls == 1
+    assert responses[0].error["status_code"] == 400
+    assert "retries_exhausted" not in responses[0].error
+
+
+@pytest.mark.asyncio
+async def test_transport_errors_are_retried():
+    client = make_client([httpx.ConnectError("refused"), [ok()]])
+    responses = await collect(client)
+    assert client.calls == 2
+    assert responses[0].get_text_content() == "hi"
+
+
+@pytest.mark.asyncio
+async def test_no_retry_after_first_chunk():
+    client = make_client([[ok("partial"), err(503)], [ok()]])
+    responses = await collect(client)
+    assert client.calls == 1
+    assert responses[1].error["status_code"] == 503
+
+
+@pytest.mark.asyncio
+async def test_attempts_report_retries():
+    client = make_client([[err(503)], httpx.ConnectError("refused"), [ok()]])
+    [response] = await collect(client)
+    assert [(a.attempt, a.status, a.status_code) for a in response.attempts] == [
+        (1, "retried", 503),
+        (2, "retried", None),
+        (3, "success", None),
+    ]
+    assert response.attempts[0].error == "boom"
+    assert response.attempts[1].error == "ConnectError: refused"
+    assert all(a.provider == "flaky" and a.latency >= 0 for a in response.attempts)
+
+
+@pytest.mark.asyncio
+async def test_attempts_report_final_failure_and_absent_without_retries():
+    [failed] = await collect(make_client([[err(500)], [err(500)]], max_attempts=2))
+    assert [a.status for a in failed.attempts] == ["retried", "error"]
+    [response] = await collect(make_client([[ok()]]))
+    assert response.attempts is None
+
+
+def test_backoff_respects_retry_after_and_cap():
+    policy = RetryConfig(initial_backoff_ms=500, max_backoff_ms=1500)
+    assert policy.backoff(1) == 0.5
+    assert policy.backoff(3) == 1.5
+    assert policy.backoff(1, "7") == 7
+    assert RetryConfig(respect_retry_after=False).backoff(1, "7") == 0.5
+    assert parse_retry_after("not a date") is None
+
+
+def test_backoff_multiplier_and_jitter(monkeypatch):
+    policy = RetryConfig(initial_backoff_ms=1000, max_backoff_ms=60000, backoff_multiplier=3, jitter=0.5)
+    monkeypatch.setattr("random.random", lambda: 1.0)
+    assert policy.backoff(3) == 4.5
+    monkeypatch.setattr("random.random", lambda: 0.0)
+    assert policy.backoff(3) == 9
+    # Retry-After is the provider's instruction and is not jittered
+    monkeypatch.setattr("random.random", lambda: 1.0)
+    assert policy.backoff(3, "2") == 2
@@ -0,0 +1,93 @@
+import logging
+
+import pytest
+
+from prompti.message import Message
+from prompti.model_client import (
+    ClientManager,
+    ModelCapabilities,
+    ModelConfig,
+    NoMatchingModelError,
+    RequestRequirements,
+    RunParams,
+    ToolSpec,
+    select_model,
+)
+
+
+def profiles():
+    return {
+        "big": ModelConfig(
+            provider="openai",
+            model="gpt-4o",
+            capabilities=ModelCapabilities(
+                vision=True,
+                tools=True,
+                json_mode=True,
+                context_window=128000,
+                input_cost_per_1k=0.0025,
+                output_cost_per_1k=0.01,
+                latency_ms=800,
+            ),
+        ),
+        "mini": ModelConfig(
+            provider="openai",
+            model="gpt-4o-mini",
+            capabilities=ModelCapabilities(
+                tools=True,
+                json_mode=True,
+                context_window=16000,
+                input_cost_per_1k=0.00015,
+                output_cost_per_1k=0.0006,
+                latency_ms=900,
+            ),
+        ),
+        "local": ModelConfig(
+            provider="openai",
+            model="qwen-7b",
+            capabilities=ModelCapabilities(context_window=8000, latency_ms=200),
+        ),
+        "unknown": ModelConfig(provider="openai", model="mystery"),
+    }
+
+
+def test_requirements_from_params():
+    image = {"type": "image_url", "image_url": {"url": "https://x/y.png"}}
+    tool = ToolSpec(name="t", description="d", parameters={"type": "object"})
+    params = RunParams(
+        messages=[Message.create_user([image, {"type": "text", "text": "hi"}])],
+        tool_params=[tool],
+        response_format="json_object",
+        max_tokens=1000,
+    )
+    req = RequestRequirements.from_params(params, prefer="latency")
+    assert (req.needs_vision, req.needs_tools, req.needs_json_mode, req.prefer) == (True, True, True, "latency")
+    assert req.min_context > 1000
+    assert RequestRequirements.from_params(RunParams(messages=[Message.create_user("hi")])).needs_tools is False
+
+
+def test_select_cheapest_or_fastest_qualifying_model(caplog):
+    with caplog.at_level(logging.INFO, logger="model_client.routing"):
+        assert select_model(profiles(), RequestRequirements()) == "mini"
+    assert "selected model mini (gpt-4o-mini) by cost" in caplog.text
+    assert "'unknown': 'no capabilities configured'" in caplog.text
+
+    assert select_model(profiles(), RequestRequirements(needs_vision=True)) == "big"
+    assert select_model(profiles(), RequestRequirements(min_context=20000)) == "big"
+    assert select_model(profiles(), RequestRequirements(prefer="latency")) == "local"
+    assert select_model(profiles(), RequestRequirements(prefer="latency", max_cost_per_1k=0.01)) == "big"
+
+
+def test_no_matching_model_lists_reasons():
+    with pytest.raises(NoMatchingModelError) as info:
+        select_model(profiles(), RequestRequirements(needs_vision=True, max_cost_per_1k=0.001))
+    assert info.value.rejected["big"] == "cost per 1k 0.00625 > 0.001"
+    assert info.value.rejected["mini"] == "no vision"
+
+
+@pytest.mark.asyncio
+async def test_client_manager_select():
+    async with ClientManager(profiles()) as manager:
+        name = manager.select(RequestRequirements(needs_tools=True, min_context=10000))
+        assert name == "mini"
+        assert manager.get(name).cfg.model == "gpt-4o-mini"
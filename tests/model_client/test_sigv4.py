@@ -0,0 +1,43 @@
+from datetime import datetime, timezone
+
+import pytest
+
+from prompti.model_client.sigv4 import AwsCredentials, sign
+
+# AWS SigV4 test suite credentials and request time
+CREDENTIALS = AwsCredentials(access_key="AKIDEXAMPLE", secret_key="wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY")
+NOW = datetime(2015, 8, 30, 12, 36, tzinfo=timezone.utc)
+
+
+@pytest.mark.parametrize(
+    "method, url, signature",
+    [
+        ("GET", "https://example.amazonaws.com/", "5fa00fa31553b73ebf1942676e86291e8372ff2a2260956d9b8aae1d763fbf31"),
+        ("POST", "https://example.amazonaws.com/", "5da7c1a2acd57cee7505fc6676e4e544621c30862966e37dddb68e92efbe5d6b"),
+        (
+            "GET",
+            "https://example.amazonaws.com/?Param2=value2&Param1=value1",
+            "b97d918cfa904a5beff61c982a1b6f458b799221646efd99d3219ec94cdf2500",
+        ),
+    ],
+)
+def test_aws_test_suite_signatures(method, url, signature):
+    headers = sign(CREDENTIALS, method, url, {}, b"", region="us-east-1", service="service", now=NOW)
+    assert headers == {
+        "Authorization": "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20150830/us-east-1/service/aws4_request, "
+        f"SignedHeaders=host;x-amz-date, Signature={signature}",
+        "X-Amz-Date": "20150830T123600Z",
+    }
+
+
+def test_session_token_is_signed(monkeypatch):
+    monkeypatch.setenv("AWS_ACCESS_KEY_ID", "AKIDEXAMPLE")
+    monkeypatch.setenv("AWS_SECRET_ACCESS_KEY", "secret")
+    monkeypatch.setenv("AWS_SESSION_TOKEN", "token")
+    credentials = AwsCredentials.from_env()
+    url = "https://example.amazonaws.com/"
+    headers = sign(
+        credentials, "POST", url, {"Content-Type": "application/json"}, b"{}", region="us-east-1", service="s", now=NOW
+    )
+    assert headers["X-Amz-Security-Token"] == "token"
+    assert "SignedHeaders=content-type;host;x-amz-date;x-amz-security-token," in headers["Authorization"]
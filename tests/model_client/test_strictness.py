@@ -0,0 +1,57 @@
+import json
+
+import httpx
+import pytest
+
+from prompti.message import Message
+from prompti.model_client.base import ModelConfig, RunParams
+from prompti.model_client import strictness
+from prompti.model_client.openai_client import SyncOpenAIClient
+
+BODY = {
+    "id": "r1",
+    "choices": [
+        {
+            "index": 0,
+            "message": {"role": "assistant", "content": "hi", "refusal": None, "annotations": [{"type": "url"}]},
+            "finish_reason": "stop",
+        }
+    ],
+    "service_tier": "default",
+    "usage": {"prompt_tokens": 1, "completion_tokens": 1, "total_tokens": 2},
+}
+
+
+def run(strictness, stream=False):
+    def handler(request):
+        if stream:
+            chunk = {**BODY, "choices": [{"index": 0, "delta": {"content": "hi"}, "native_finish_reason": "STOP"}]}
+            return httpx.Response(200, text=f"data: {json.dumps(chunk)}\n\ndata: [DONE]\n\n")
+        return httpx.Response(200, json=BODY)
+
+    cfg = ModelConfig(provider="openai", model="m", response_strictness=strictness)
+    client = SyncOpenAIClient(cfg, client=httpx.Client(transport=httpx.MockTransport(handler)))
+    return list(client._run(RunParams(messages=[Message.create_user("q")], stream=stream)))
+
+
+def test_lenient_keeps_unknown_fields_and_logs_once(caplog):
+    strictness._logged.clear()
+    with caplog.at_level("WARNING", logger="model_client.response_fields"):
+        first = run(None)[0]
+        run("lenient")
+    assert first.get_text_content() == "hi"
+    assert first.extra == {"service_tier": "default", "choices.0.message.annotations": [{"type": "url"}]}
+    logged = [r.getMessage() for r in caplog.records if "service_tier" in r.getMessage()]
+    assert len(logged) == 1
+
+
+def test_lenient_stream_chunk_extra():
+    (chunk,) = run("lenient", stream=True)
+    assert chunk.extra["choices.0.native_finish_reason"] == "STOP"
+
+
+@pytest.mark.parametrize("stream", [False, True])
+def test_strict_turns_unknown_fields_into_error(stream):
+    (response,) = run("strict", stream=stream)
+    assert response.error["code"] == "unknown_response_field"
+    assert "service_tier" in response.error["message"]
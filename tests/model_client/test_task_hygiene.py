@@ -0,0 +1,82 @@
+"""ModelClient.arun/run spawn no tasks or threads; the helpers listed in docs/DESIGN.md clean up theirs."""
+
+import asyncio
+import json
+import threading
+
+import httpx
+import pytest
+
+from prompti.message import Message
+from prompti.model_client.base import ModelConfig, RetryConfig, RunParams
+from prompti.model_client.openai_client import OpenAIClient, SyncOpenAIClient
+from prompti.streaming import SharedChatStream
+
+EVENTS = [{"choices": [{"index": 0, "delta": {"content": c}}]} for c in ("a", "b", "c")]
+BODY = "".join(f"data: {json.dumps(e)}\n\n" for e in EVENTS) + "data: [DONE]\n\n"
+
+
+def handler_with_one_retry():
+    calls = []
+
+    def handler(request):
+        calls.append(request)
+        if len(calls) == 1:
+            return httpx.Response(503, json={"error": {"message": "busy"}})
+        return httpx.Response(200, content=BODY.encode())
+
+    return handler
+
+
+def config():
+    return ModelConfig(provider="openai", model="m", retry=RetryConfig(initial_backoff_ms=1))
+
+
+@pytest.mark.asyncio
+async def test_async_calls_spawn_no_background_tasks():
+    # Stream pumping, retry backoff and hooks all run inside the caller's task.
+    before = asyncio.all_tasks()
+    http = httpx.AsyncClient(transport=httpx.MockTransport(handler_with_one_retry()))
+    client = OpenAIClient(config(), client=http)
+    params = RunParams(messages=[Message.create_user("q")])
+    text = "".join([r.get_text_content() or "" async for r in client.arun(params)])
+    assert text == "abc"
+
+    # abandoning a stream half-way must not leave anything running either
+    stream = client.arun(RunParams(messages=[Message.create_user("q")]))
+    await stream.__anext__()
+    await stream.aclose()
+    await client.aclose()
+    assert asyncio.all_tasks() == before
+
+
+def test_sync_calls_spawn_no_threads():
+    before = threading.active_count()
+    client = SyncOpenAIClient(config(), client=httpx.Client(transport=httpx.MockTransport(handler_with_one_retry())))
+    responses = client.run(RunParams(messages=[Message.create_user("q")]))
+    assert "".join(r.get_text_content() or "" for r in responses) == "abc"
+    client.close()
+    assert threading.active_count() == before
+
+
+@pytest.mark.asyncio
+async def test_shared_stream_task_ends_with_aclose():
+    before = asyncio.all_tasks()
+    client = OpenAIClient(config(), client=httpx.AsyncClient(transport=httpx.MockTransport(handler_with_one_retry())))
+    async with SharedChatStream(client.arun(RunParams(messages=[Message.create_user("q")])), max_lag=1) as shared:
+        subscriber = shared.subscribe()
+        await subscriber.__anext__()
+        await subscriber.aclose()
+    await client.aclose()
+    assert asyncio.all_tasks() == before
+
+
+def test_sync_sampling_threads_end_with_the_call():
+    before = threading.active_count()
+    body = {"choices": [{"index": 0, "message": {"role": "assistant", "content": "a"}}]}
+    transport = httpx.MockTransport(lambda request: httpx.Response(200, json=body))
+    client = SyncOpenAIClient(config(), client=httpx.Client(transport=transport))
+    result = client.best_of(RunParams(messages=[Message.create_user("q")]), 3, lambda params, response: 1)
+    assert len(result.candidates) == 3
+    client.close()
+    assert threading.active_count() == before
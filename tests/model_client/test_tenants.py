@@ -0,0 +1,192 @@
+import asyncio
+import json
+
+import httpx
+import pytest
+
+from prompti.message import Message
+from prompti.model_client import (
+    ClientManager,
+    GatewayLimits,
+    ModelConfig,
+    RunParams,
+    TenantAccessError,
+    TenantConfig,
+    TenantGateway,
+)
+from prompti.model_client.tenants import time as tenants_time
+
+BODY = {
+    "choices": [{"index": 0, "message": {"role": "assistant", "content": "hi"}}],
+    "usage": {"prompt_tokens": 1_000_000, "completion_tokens": 0, "total_tokens": 1_000_000},
+}
+
+
+def make_gateway(seen, limits=None, **tenant):
+    def handler(request):
+        seen.append(request)
+        return httpx.Response(200, json=BODY)
+
+    http = httpx.AsyncClient(transport=httpx.MockTransport(handler))
+    profiles = {
+        "gpt-4o-mini": ModelConfig(provider="openai", model="gpt-4o-mini", api_key="upstream"),
+        "gpt-4o": ModelConfig(provider="openai", model="gpt-4o", api_key="upstream"),
+    }
+    manager = ClientManager(profiles, http_client=http)
+    return TenantGateway(manager, [TenantConfig(name="search", api_keys=["sk-a", "sk-b"], **tenant)], limits)
+
+
+async def call(gateway, key, model="gpt-4o-mini", **kwargs):
+    params = RunParams(messages=[Message.create_user("q")], stream=False, **kwargs)
+    return [r async for r in gateway.arun(key, model, params)], params
+
+
+@pytest.mark.asyncio
+async def test_routes_authorized_requests_and_tracks_spend():
+    seen = []
+    gateway = make_gateway(seen, budget_usd=1.0)
+    [response], params = await call(gateway, "sk-b")
+    assert response.get_text_content() == "hi"
+    assert params.trace_context["tenant"] == "search"
+    assert seen[0].headers["Authorization"] == "Bearer upstream"
+    # 1M prompt tokens of gpt-4o-mini at the bundled price
+    assert gateway.spent("search") == pytest.approx(0.15)
+
+
+@pytest.mark.asyncio
+async def test_rejections_happen_before_routing():
+    seen = []
+    gateway = make_gateway(seen, allowed_models=["gpt-4o-mini*"])
+    with pytest.raises(TenantAccessError) as info:
+        await call(gateway, "sk-unknown")
+    assert (info.value.reason, info.value.status_code) == ("unauthorized", 401)
+    with pytest.raises(TenantAccessError) as info:
+        await call(gateway, "sk-a", model="gpt-4o")
+    assert info.value.status_code == 403
+    assert seen == []
+
+
+def test_rejections_label_metrics_with_known_profiles_only(monkeypatch):
+    labels = []
+    gateway = make_gateway([], allowed_models=["gpt-4o-mini*"], requests_per_minute=1)
+    reject = gateway._reject
+
+    def spy(tenant, model, error):
+        labels.append(model)
+        return reject(tenant, model, error)
+
+    monkeypatch.setattr(gateway, "_reject", spy)
+    gateway.authorize("sk-a", "gpt-4o-mini")
+    for key, model in [("sk-x", "random-1"), ("sk-a", "random-2"), ("sk-a", "gpt-4o-mini-x"), ("sk-a", "gpt-4o-mini")]:
+        with pytest.raises(TenantAccessError):
+            gateway.authorize(key, model)
+    # unauthorized, model_not_allowed, rate limited for a name that is no profile, rate limited
+    assert labels == ["unknown", "unknown", "unknown", "gpt-4o-mini"]
+
+
+@pytest.mark.asyncio
+async def test_budget_exceeded():
+    gateway = make_gateway([], budget_usd=0.1)
+    await call(gateway, "sk-a")
+    with pytest.raises(TenantAccessError) as info:
+        await call(gateway, "sk-a")
+    assert info.value.status_code == 402
+    gateway.reset_spend()
+    await call(gateway, "sk-a")
+
+
+def test_rate_limit_is_shared_by_the_tenant_keys(monkeypatch):
+    now = [100.0]
+    monkeypatch.setattr(tenants_time, "monotonic", lambda: now[0])
+    gateway = make_gateway([], requests_per_minute=2)
+    gateway.authorize("sk-a", "gpt-4o")
+    now[0] += 20
+    gateway.authorize("sk-b", "gpt-4o")
+    with pytest.raises(TenantAccessError) as info:
+        gateway.authorize("sk-a", "gpt-4o")
+    assert info.value.reason == "rate_limited"
+    assert info.value.retry_after == pytest.approx(40)
+    now[0] += 40
+    assert gateway.authorize("sk-a", "gpt-4o").name == "search"
+
+
+def test_duplicate_keys_are_rejected():
+    with pytest.raises(ValueError, match="already used"):
+        TenantGateway(ClientManager(), [TenantConfig(name="a", api_keys=["k"]), TenantConfig(name="b", api_keys=["k"])])
+
+
+def test_list_models_only_shows_allowed_profiles():
+    gateway = make_gateway([], allowed_models=["gpt-4o-mini*"])
+    assert gateway.list_models("sk-a") == {
+        "object": "list",
+        "data": [{"id": "gpt-4o-mini", "object": "model", "created": 0, "owned_by": "openai"}],
+    }
+    with pytest.raises(TenantAccessError):
+        gateway.list_models(None)
+
+
+@pytest.mark.asyncio
+async def test_embeddings_are_passed_through_to_the_profile():
+    seen = []
+    gateway = make_gateway(seen, allowed_models=["gpt-4o-mini"])
+    data = await gateway.aembeddings("sk-a", {"model": "gpt-4o-mini", "input": "q"})
+    assert data["model"] == "gpt-4o-mini"
+    assert str(seen[0].url) == "https://api.openai.com/v1/embeddings"
+    # 1M prompt tokens reported by the mock
+    assert gateway.spent("search") == pytest.approx(0.15)
+    with pytest.raises(TenantAccessError):
+        await gateway.aembeddings("sk-a", {"model": "gpt-4o", "input": "q"})
+
+
+@pytest.mark.asyncio
+async def test_limits_reject_oversized_requests_and_cap_output():
+    seen = []
+    gateway = make_gateway(seen, GatewayLimits(max_request_bytes=1000, max_messages=1, max_output_tokens=256))
+    _, params = await call(gateway, "sk-a")
+    assert params.max_tokens == 256
+    assert json.loads(seen[0].content)["max_tokens"] == 256
+
+    with pytest.raises(TenantAccessError) as info:
+        await call(gateway, "sk-a", max_tokens=1024)
+    assert (info.value.reason, info.value.status_code) == ("max_tokens_exceeded", 400)
+    with pytest.raises(TenantAccessError) as info:
+        await call(gateway, "sk-a", temperature=0.5, stop=["x" * 1000])
+    assert info.value.status_code == 413
+    with pytest.raises(TenantAccessError) as info:
+        gateway.authorize("sk-a", "gpt-4o", body_size=2000)
+    assert info.value.reason == "request_too_large"
+    params = RunParams(messages=[Message.create_user("a"), Message.create_user("b")])
+    with pytest.raises(TenantAccessError) as info:
+        gateway.authorize("sk-a", "gpt-4o", params)
+    assert info.value.reason == "too_many_messages"
+    assert len(seen) == 1
+
+
+@pytest.mark.asyncio
+async def test_a_stalled_provider_only_fills_its_own_pool():
+    release = asyncio.Event()
+
+    async def handler(request):
+        if request.url.host == "stalled.openai.azure.com":
+            await release.wait()
+        return httpx.Response(200, json=BODY)
+
+    profiles = {
+        "azure": ModelConfig(provider="azure", model="d", api_url="https://stalled.openai.azure.com"),
+        "openai": ModelConfig(provider="openai", model="gpt-4o-mini"),
+    }
+    manager = ClientManager(profiles, http_client=httpx.AsyncClient(transport=httpx.MockTransport(handler)))
+    limits = GatewayLimits(max_concurrent_per_provider=1, queue_timeout_s=0.01)
+    gateway = TenantGateway(manager, [TenantConfig(name="t", api_keys=["k"])], limits)
+
+    stalled = asyncio.create_task(call(gateway, "k", model="azure"))
+    await asyncio.sleep(0.01)
+    with pytest.raises(TenantAccessError) as info:
+        await call(gateway, "k", model="azure")
+    assert (info.value.reason, info.value.status_code) == ("provider_busy", 503)
+    [response], _ = await call(gateway, "k", model="openai")
+    assert response.get_text_content() == "hi"
+
+    release.set()
+    await stalled
+    await call(gateway, "k", model="azure")
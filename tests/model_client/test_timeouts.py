@@ -0,0 +1,75 @@
+import httpx
+import pytest
+
+from prompti.message import Message
+from prompti.model_client.base import ModelConfig, RetryConfig, RunParams
+from prompti.model_client.openai_client import OpenAIClient, SyncOpenAIClient
+from prompti.model_client.transport import DEFAULT_TIMEOUT, http_timeout
+from prompti.model_client.types import TimeoutConfig
+
+OK = {
+    "id": "1",
+    "choices": [{"index": 0, "message": {"role": "assistant", "content": "Hi"}, "finish_reason": "stop"}],
+}
+
+
+def _cfg(**timeout):
+    return ModelConfig(provider="openai", model="gpt-4o", api_key="k", timeout=TimeoutConfig(**timeout))
+
+
+def _params(stream=False):
+    return RunParams(messages=[Message.create_user("q")], stream=stream)
+
+
+def test_http_timeout_per_phase():
+    assert http_timeout(ModelConfig(provider="openai")) is DEFAULT_TIMEOUT
+    timeout = http_timeout(_cfg(request_s=60, connect_s=5, read_s=30))
+    assert (timeout.connect, timeout.read, timeout.write, timeout.pool) == (5, 30, 60, 60)
+    # the time left until the deadline caps every phase, also unlimited ones
+    timeout = http_timeout(_cfg(request_s=None, connect_s=5), remaining=2)
+    assert (timeout.connect, timeout.read, timeout.write, timeout.pool) == (2, 2, 2, 2)
+
+
+def test_timeouts_apply_to_shared_clients():
+    seen = []
+
+    def handler(request):
+        seen.append(request)
+        return httpx.Response(200, json=OK)
+
+    shared = httpx.Client(transport=httpx.MockTransport(handler), timeout=600)
+    client = SyncOpenAIClient(_cfg(connect_s=3, read_s=20), client=shared)
+    [response] = list(client.run(_params()))
+    assert response.get_text_content() == "Hi"
+    assert seen[0].extensions["timeout"] == {"connect": 3, "read": 20, "write": 600, "pool": 600}
+
+
+@pytest.mark.asyncio
+async def test_read_timeout_reports_elapsed_time():
+    def handler(request):
+        raise httpx.ReadTimeout("timed out", request=request)
+
+    cfg = _cfg(read_s=1).model_copy(update={"retry": RetryConfig(max_attempts=1)})
+    client = OpenAIClient(cfg, client=httpx.AsyncClient(transport=httpx.MockTransport(handler)))
+    [response] = [r async for r in client.arun(_params())]
+    assert response.error["code"] == "timeout"
+    assert response.error["elapsed_s"] >= 0
+    assert response.error["error_class"] == "timeout"
+
+
+@pytest.mark.asyncio
+async def test_deadline_bounds_retries():
+    calls = []
+
+    def handler(request):
+        calls.append(request)
+        return httpx.Response(503, json={"error": {"message": "overloaded"}}, headers={"retry-after": "30"})
+
+    cfg = _cfg(deadline_s=0.05).model_copy(update={"retry": RetryConfig(max_attempts=5)})
+    client = OpenAIClient(cfg, client=httpx.AsyncClient(transport=httpx.MockTransport(handler)))
+    [response] = [r async for r in client.arun(_params())]
+    # Retry-After asked for 30s; the backoff is cut to the deadline and the next attempt is not sent
+    assert len(calls) == 1
+    assert response.error["code"] == "deadline_exceeded"
+    assert response.error["message"] == "deadline of 0.05s exceeded"
+    assert [a.status for a in response.attempts] == ["retried", "error"]
@@ -0,0 +1,57 @@
+from datetime import datetime, timedelta, timezone
+
+import pytest
+
+from prompti.message import Message, ModelResponse, StreamingChoice, StreamingModelResponse, Timing, Usage
+from prompti.model_client.base import ModelClient, ModelConfig, RunParams
+
+
+def chunk(text=None, finish_reason=None, usage=None):
+    return StreamingModelResponse(
+        choices=[StreamingChoice(index=0, delta=Message(role="assistant", content=text), finish_reason=finish_reason)],
+        usage=usage,
+    )
+
+
+class StreamClient(ModelClient):
+    provider = "stream"
+
+    async def _run(self, params):
+        yield chunk("a")
+        yield chunk("b")
+        yield chunk(finish_reason="stop", usage=Usage(prompt_tokens=1, completion_tokens=10, total_tokens=11))
+
+
+@pytest.mark.asyncio
+async def test_timing_is_set_on_first_and_final_chunks():
+    client = StreamClient(ModelConfig(provider="stream", model="m"))
+    params = RunParams(messages=[Message.create_user("q")])
+    responses = [r async for r in client.arun(params)]
+    first, middle, last = responses
+    assert first.timing.first_token_latency is not None
+    assert middle.timing is None
+    assert last.timing.total_duration >= last.timing.first_token_latency
+    assert last.timing.output_tokens_per_sec > 0
+    assert params.trace_context["perf_metrics"]["output_tokens_per_sec"] == last.timing.output_tokens_per_sec
+
+
+def test_measure_throughput():
+    usage = Usage(prompt_tokens=5, completion_tokens=20, total_tokens=25)
+    assert Timing.measure(0.5, 2.5, usage).output_tokens_per_sec == 10
+    # Non-streaming responses arrive all at once; use the total duration.
+    assert Timing.measure(2.0, 2.0, usage).output_tokens_per_sec == 10
+    assert Timing.measure(0.5, 2.5).output_tokens_per_sec is None
+
+
+def test_typed_timestamps_and_durations():
+    timing = Timing.measure(0.25, 1.5)
+    assert (timing.first_token_timedelta, timing.total_timedelta) == (timedelta(seconds=0.25), timedelta(seconds=1.5))
+    assert Timing().total_timedelta is None
+
+    created = datetime(2024, 5, 1, 12, tzinfo=timezone(timedelta(hours=8)))
+    response = StreamingModelResponse(created=created)
+    assert response.created == 1714536000
+    assert response.created_at == created and response.created_at.tzinfo is timezone.utc
+    # naive datetimes are UTC, and the wire format stays Unix seconds
+    assert ModelResponse(created=datetime(2024, 5, 1, 4)).model_dump()["created"] == 1714536000
+    assert ModelResponse(created=0).created_at is None
@@ -0,0 +1,45 @@
+import pytest
+
+from prompti.message import Message
+from prompti.model_client import ModelConfig, RunParams, TokenBudgetError, ToolParams, ToolSpec
+from prompti.model_client.factory import create_sync_client
+
+TOOL = ToolSpec(name="weather", description="Weather of a city", parameters={"type": "object"})
+
+
+def _params(**kw):
+    return RunParams(messages=[Message.create_system("Be brief."), Message.create_user("a" * 400)], **kw)
+
+
+def test_counted_prompt_tokens_are_used_as_is():
+    params = _params()
+    assert params.with_token_budget(8192, prompt_tokens=1000).max_tokens == 7192
+    assert params.max_tokens is None
+    assert _params(max_tokens=500).with_token_budget(8192, prompt_tokens=1000).max_tokens == 500
+
+
+def test_estimated_prompt_is_padded():
+    # (3 + 4) + (100 + 4) 估算 token，加 10% 余量
+    assert _params().with_token_budget(1000).max_tokens == 1000 - 123
+    assert _params().with_token_budget(1000, margin=0).max_tokens == 1000 - 111
+    assert _params(tool_params=ToolParams(tools=[TOOL])).with_token_budget(1000).max_tokens < 1000 - 123
+
+
+def test_prompt_that_fills_the_budget():
+    with pytest.raises(TokenBudgetError, match="no room") as exc_info:
+        _params().with_token_budget(100)
+    assert (exc_info.value.prompt_tokens, exc_info.value.total) == (123, 100)
+
+
+def test_annotate_tokens_per_message():
+    client = create_sync_client(ModelConfig(provider="openai", model="gpt-4o"))
+    messages = _params().messages
+    counts = client.annotate_tokens(messages)
+    assert (counts.messages, counts.tools, counts.total) == ([7, 104], 0, 111)
+    # 只保留能放进预算的最新消息
+    assert [counts.keep_from(budget) for budget in (111, 110, 104, 50)] == [0, 1, 1, 2]
+
+    with_tools = client.annotate_tokens(messages, [TOOL])
+    client.close()
+    assert with_tools.tools > 0 and with_tools.total == 111 + with_tools.tools
+    assert [with_tools.keep_from(104 + with_tools.tools), with_tools.keep_from(104)] == [1, 2]
@@ -0,0 +1,39 @@
+import os
+import subprocess
+import sys
+import textwrap
+
+
+def test_types_import_without_the_http_stack():
+    # Block the heavy dependencies; importing them would raise ImportError.
+    script = textwrap.dedent(
+        """
+        import sys
+        for name in ("httpx", "jinja2", "yaml", "aiofiles", "prometheus_client", "opentelemetry"):
+            sys.modules[name] = None
+
+        from prompti import Message, ModelResponse, RunParams
+        from prompti.model_client import ErrorClass, ModelConfig, ToolParams, ToolSpec
+
+        params = RunParams(
+            messages=[Message.create_user("hi")],
+            tool_params=ToolParams(tools=[ToolSpec(name="t", description="d", parameters={})]),
+        )
+        assert RunParams.model_validate_json(params.model_dump_json()) == params
+        assert ModelConfig.model_validate({"provider": "openai", "retry": {"max_attempts": 2}}).retry.max_attempts == 2
+        ModelResponse.model_validate({"choices": [{"index": 0, "message": {"role": "assistant", "content": "x"}}]})
+        assert params.with_token_budget(1000).max_tokens < 1000
+        print(ErrorClass("timeout").name, len(params.canonical_hash()))
+        """
+    )
+    env = {**os.environ, "PYTHONPATH": os.pathsep.join(sys.path)}
+    result = subprocess.run([sys.executable, "-c", script], capture_output=True, text=True, env=env)  # noqa: S603
+    assert result.returncode == 0, result.stderr
+    assert result.stdout.split() == ["TIMEOUT", "64"]
+
+
+def test_client_modules_still_export_the_types():
+    from prompti.model_client import base, types
+
+    assert base.RunParams is types.RunParams
+    assert base.ModelConfig is types.ModelConfig
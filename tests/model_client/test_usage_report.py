@@ -0,0 +1,89 @@
+from datetime import datetime, timedelta, timezone
+
+import httpx
+import pytest
+
+from prompti.model_client.base import ModelConfig
+from prompti.model_client.openai_client import OpenAIClient, SyncOpenAIClient
+from prompti.model_client.qianfan_client import QianfanClient, SyncQianfanClient
+
+START = datetime(2024, 6, 1, tzinfo=timezone.utc)
+END = datetime(2024, 6, 3, tzinfo=timezone.utc)
+
+def _usage(tokens_in, tokens_out, cached, requests):
+    result = {"input_tokens": tokens_in, "output_tokens": tokens_out, "input_cached_tokens": cached}
+    return {"results": [{**result, "num_model_requests": requests}]}
+
+
+USAGE_PAGES = {
+    None: {
+        "data": [_usage(100, 20, 10, 3)],
+        "has_more": True,
+        "next_page": "p2",
+    },
+    "p2": {
+        "data": [_usage(50, 5, 0, 1)],
+        "has_more": False,
+        "next_page": None,
+    },
+}
+COSTS = {"data": [{"results": [{"amount": {"value": 0.25, "currency": "usd"}}, {"amount": {"value": 0.5}}]}]}
+
+
+def _transport(seen):
+    def handler(request):
+        seen.append(request)
+        if request.url.path.endswith("/organization/costs"):
+            return httpx.Response(200, json=COSTS)
+        return httpx.Response(200, json=USAGE_PAGES[request.url.params.get("page")])
+
+    return httpx.MockTransport(handler)
+
+
+CFG = ModelConfig(provider="openai", model="gpt-4o", api_key="sk", extra_params={"admin_api_key": "sk-admin"})
+
+
+def _check(report, seen):
+    assert (report.requests, report.input_tokens, report.output_tokens, report.cached_input_tokens) == (4, 150, 25, 10)
+    assert report.cost == pytest.approx(0.75)
+    assert (report.start, report.end) == (START, END)
+    first = seen[0]
+    assert str(first.url).startswith("https://api.openai.com/v1/organization/usage/completions?")
+    assert first.url.params["start_time"] == str(int(START.timestamp()))
+    assert first.url.params["end_time"] == str(int(END.timestamp()))
+    assert first.headers["Authorization"] == "Bearer sk-admin"
+    assert [r.url.path for r in seen].count("/v1/organization/usage/completions") == 2
+
+
+@pytest.mark.asyncio
+async def test_async_usage_report_sums_pages_and_costs():
+    seen = []
+    client = OpenAIClient(CFG, client=httpx.AsyncClient(transport=_transport(seen)))
+    report = await client.ausage_report((START, END))
+    _check(report, seen)
+    assert report.provider == "openai"
+
+
+def test_sync_usage_report():
+    seen = []
+    client = SyncOpenAIClient(CFG, client=httpx.Client(transport=_transport(seen)))
+    _check(client.usage_report((START, END)), seen)
+
+
+@pytest.mark.asyncio
+async def test_usage_report_window_and_api_key_fallback():
+    seen = []
+    cfg = ModelConfig(provider="openai", model="gpt-4o", api_key="sk")
+    client = OpenAIClient(cfg, client=httpx.AsyncClient(transport=_transport(seen)))
+    report = await client.ausage_report(timedelta(days=1))
+    assert report.end - report.start == timedelta(days=1)
+    assert seen[0].headers["Authorization"] == "Bearer sk"
+
+
+@pytest.mark.asyncio
+async def test_usage_report_unsupported():
+    cfg = ModelConfig(provider="qianfan", model="ernie")
+    with pytest.raises(NotImplementedError):
+        await QianfanClient(cfg).ausage_report(timedelta(days=1))
+    with pytest.raises(NotImplementedError):
+        SyncQianfanClient(cfg).usage_report(timedelta(days=1))
@@ -0,0 +1,58 @@
+import json
+
+import httpx
+
+from prompti.message import Message
+from prompti.model_client.base import ModelConfig, RunParams
+from prompti.model_client.openai_client import SyncOpenAIClient
+from prompti.model_client.openai_wire import DeltaTextAssembler, SSEDecoder
+
+TEXT = "héllo 你好 👩‍👩‍👧‍👦 🇯🇵 done"
+
+
+def sse_body(deltas, ensure_ascii=False):
+    events = [
+        "data: " + json.dumps({"choices": [{"index": 0, "delta": {"content": d}}]}, ensure_ascii=ensure_ascii)
+        for d in deltas
+    ]
+    return ("\n\n".join(events) + "\n\ndata: [DONE]\n\n").encode("utf-8")
+
+
+def test_sse_decoder_survives_every_byte_split():
+    body = sse_body([TEXT])
+    for split in range(1, len(body)):
+        decoder = SSEDecoder()
+        payloads = decoder.feed(body[:split]) + decoder.feed(body[split:])
+        assert json.loads(payloads[0])["choices"][0]["delta"]["content"] == TEXT
+
+
+def test_sse_decoder_single_byte_chunks():
+    decoder = SSEDecoder()
+    payloads = [p for byte in sse_body(["你", "好"]) for p in decoder.feed(bytes([byte]))]
+    assert [json.loads(p)["choices"][0]["delta"]["content"] for p in payloads[:2]] == ["你", "好"]
+
+
+def test_assembler_joins_surrogate_pairs_split_across_deltas():
+    utf16 = TEXT.encode("utf-16-le")
+    units = [utf16[i : i + 2].decode("utf-16-le", "surrogatepass") for i in range(0, len(utf16), 2)]
+    assembler = DeltaTextAssembler()
+    pieces = [assembler.push("content", unit) for unit in units]
+    assert "".join(pieces) == TEXT
+    assert all("\ud800" > ch or ch > "\udfff" for piece in pieces for ch in piece)
+
+
+def test_lone_low_surrogate_is_replaced():
+    assert DeltaTextAssembler().push("content", "a\ude00") == "a�"
+
+
+def test_client_stream_with_one_byte_chunks_and_split_escapes():
+    # the emoji escape pair is split across two events and the body arrives one byte at a time
+    body = sse_body(["ok ", "\ud83d", "\ude00", " 你好"], ensure_ascii=True)
+
+    def handler(request):
+        return httpx.Response(200, content=iter([bytes([b]) for b in body]))
+
+    http = httpx.Client(transport=httpx.MockTransport(handler))
+    client = SyncOpenAIClient(ModelConfig(provider="openai", model="m"), client=http)
+    text = "".join(r.get_text_content() or "" for r in client._run(RunParams(messages=[Message.create_user("q")])))
+    assert text == "ok 😀 你好"
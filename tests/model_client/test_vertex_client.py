@@ -0,0 +1,101 @@
+import httpx
+import pytest
+
+from prompti.message import Message
+from prompti.model_client.base import ModelConfig, RunParams
+from prompti.model_client.factory import create_sync_client
+from prompti.model_client.gcp_metadata import GCPMetadataError, MetadataCredential
+from prompti.model_client.vertex_client import SyncVertexClient, VertexClient
+
+RESPONSE = {
+    "candidates": [{"content": {"role": "model", "parts": [{"text": "Hi"}]}, "finishReason": "STOP"}],
+    "usageMetadata": {"promptTokenCount": 2, "candidatesTokenCount": 1, "totalTokenCount": 3},
+}
+
+
+@pytest.fixture
+def gcp_env(monkeypatch):
+    for name in ("GOOGLE_CLOUD_PROJECT", "GOOGLE_CLOUD_LOCATION", "GCE_METADATA_HOST", "GEMINI_API_KEY"):
+        monkeypatch.delenv(name, raising=False)
+    return monkeypatch
+
+
+def _metadata_transport(seen):
+    def handler(request):
+        seen.append(request)
+        token = {"access_token": f"ya29.{len(seen)}", "expires_in": 3599, "token_type": "Bearer"}
+        return httpx.Response(200, json=token)
+
+    return httpx.MockTransport(handler)
+
+
+def _params(stream=False):
+    return RunParams(messages=[Message.create_user("q")], stream=stream)
+
+
+def test_request_url(gcp_env):
+    cfg = ModelConfig(provider="vertex", model="gemini-2.5-flash", project="p", region="europe-west4")
+    client = SyncVertexClient(cfg)
+    assert client._request_url(stream=True) == (
+        "https://europe-west4-aiplatform.googleapis.com/v1/projects/p/locations/europe-west4"
+        "/publishers/google/models/gemini-2.5-flash:streamGenerateContent?alt=sse"
+    )
+    gcp_env.setenv("GOOGLE_CLOUD_PROJECT", "env-project")
+    gcp_env.setenv("GOOGLE_CLOUD_LOCATION", "global")
+    client = SyncVertexClient(ModelConfig(provider="vertex", model="gemini-2.5-pro"))
+    assert client._request_url().startswith(
+        "https://aiplatform.googleapis.com/v1/projects/env-project/locations/global/"
+    )
+
+    gcp_env.delenv("GOOGLE_CLOUD_PROJECT")
+    with pytest.raises(ValueError, match="needs project"):
+        client._request_url()
+
+
+def test_metadata_token_is_cached_until_refresh_margin(gcp_env):
+    gcp_env.setenv("GCE_METADATA_HOST", "169.254.169.254")
+    seen = []
+    now = [0.0]
+    credential = MetadataCredential(client=httpx.Client(transport=_metadata_transport(seen)), clock=lambda: now[0])
+    assert credential.get_token() == "ya29.1"
+    now[0] += 3000
+    assert credential.get_token() == "ya29.1"
+    now[0] += 400
+    assert credential.get_token() == "ya29.2"
+    assert str(seen[0].url).startswith(
+        "http://169.254.169.254/computeMetadata/v1/instance/service-accounts/default/token?scopes="
+    )
+    assert seen[0].headers["Metadata-Flavor"] == "Google"
+
+
+def test_unreachable_metadata_server(gcp_env):
+    def handler(request):
+        raise httpx.ConnectError("Name or service not known", request=request)
+
+    credential = MetadataCredential(client=httpx.Client(transport=httpx.MockTransport(handler)))
+    with pytest.raises(GCPMetadataError, match="metadata.google.internal is unreachable"):
+        credential.get_token()
+
+
+@pytest.mark.asyncio
+async def test_async_client_sends_bearer_token(gcp_env):
+    gcp_env.setenv("GEMINI_API_KEY", "unused")
+    seen = []
+
+    def handler(request):
+        seen.append(request)
+        return httpx.Response(200, json=RESPONSE)
+
+    cfg = ModelConfig(provider="vertex", model="gemini-2.5-flash", project="p")
+    client = VertexClient(cfg, client=httpx.AsyncClient(transport=httpx.MockTransport(handler)))
+    client._credential = MetadataCredential(async_client=httpx.AsyncClient(transport=_metadata_transport([])))
+    responses = [r async for r in client._run(_params())]
+    await client.aclose()
+    assert responses[0].choices[0].message.content == "Hi"
+    assert seen[0].headers["Authorization"] == "Bearer ya29.1"
+    assert "x-goog-api-key" not in seen[0].headers
+    assert "OpenAI-Project" not in seen[0].headers
+
+
+def test_sync_factory(gcp_env):
+    assert isinstance(create_sync_client(ModelConfig(provider="vertex", model="gemini-2.5-flash")), SyncVertexClient)
@@ -0,0 +1,97 @@
+import json
+
+import httpx
+import pytest
+
+from prompti.message import Choice, Message, ModelResponse
+from prompti.model_client.base import ModelClient, ModelConfig, RunParams, SyncModelClient
+from prompti.model_client.openai_client import OpenAIClient
+from prompti.model_client.voting import avote, parse_json_answer, vote
+
+
+def reply(text):
+    return ModelResponse(choices=[Choice(index=0, message=Message.create_assistant(text), finish_reason="stop")])
+
+
+class Sampler(ModelClient):
+    def __init__(self, outcomes):
+        super().__init__(ModelConfig(provider="dummy", model="m"))
+        self.outcomes = list(outcomes)
+
+    async def _run(self, params):
+        yield self.outcomes.pop(0)
+
+
+class SyncSampler(SyncModelClient):
+    def __init__(self, outcomes):
+        super().__init__(ModelConfig(provider="dummy", model="m"))
+        self.outcomes = list(outcomes)
+
+    def _run(self, params):
+        yield self.outcomes.pop(0)
+
+
+def params():
+    return RunParams(messages=[Message.create_user("Classify: 'refund please'")], temperature=0.7)
+
+
+def test_parse_json_answer():
+    assert parse_json_answer('```json\n{"label": "billing"}\n```') == {"label": "billing"}
+
+
+@pytest.mark.asyncio
+async def test_majority_answer_and_agreement():
+    client = Sampler([
+        reply('{"label": "billing", "confidence": 0.9}'),
+        reply('{"confidence": 0.9, "label": "billing"}'),
+        reply('{"label": "shipping", "confidence": 0.6}'),
+        reply("I think it is billing"),
+        ModelResponse(error={"message": "invalid request", "status_code": 400}),
+    ])
+    result = await avote(client, params(), 5)
+    assert result.answer == {"label": "billing", "confidence": 0.9}
+    assert (result.votes, result.valid, result.samples) == (2, 3, 5)
+    assert result.agreement == pytest.approx(2 / 3)
+    assert [(c.answer["label"], c.samples) for c in result.tally] == [("billing", [0, 1]), ("shipping", [2])]
+    assert result.errors[4] == "invalid request"
+    assert result.errors[3].startswith("JSONDecodeError")
+    assert not result.tied
+
+
+@pytest.mark.asyncio
+async def test_vote_on_a_field_reports_ties():
+    answers = ['{"label": "a", "why": "x"}', '{"label": "b"}', '{"label": "a", "why": "y"}', '{"label": "b"}']
+    result = await avote(Sampler([reply(a) for a in answers]), params(), 4, key=lambda answer: answer["label"])
+    assert result.answer == "a"
+    assert result.tied
+    assert result.agreement == 0.5
+
+
+def test_sync_vote():
+    answers = ['{"label": "a"}', '{"label": "a"}', "nope"]
+    result = vote(SyncSampler([reply(a) for a in answers]), params(), 3)
+    assert result.answer == {"label": "a"}
+    assert (result.votes, result.valid, len(result.errors)) == (2, 2, 1)
+
+
+@pytest.mark.asyncio
+async def test_use_n_votes_on_every_choice():
+    answers = ['{"label": "a"}', '{"label": "a"}', '{"label": "b"}', '{"label": "a"}', '{"label": "b"}']
+    seen = []
+
+    def handler(request):
+        seen.append(json.loads(request.content))
+        choices = [
+            {"index": i, "message": {"role": "assistant", "content": a}, "finish_reason": "stop"}
+            for i, a in enumerate(answers)
+        ]
+        return httpx.Response(200, json={"id": "r", "model": "gpt-4o", "choices": choices})
+
+    cfg = ModelConfig(provider="openai", model="gpt-4o", api_key="k")
+    client = OpenAIClient(cfg, client=httpx.AsyncClient(transport=httpx.MockTransport(handler)))
+    result = await avote(client, params(), 5, use_n=True)
+    await client.aclose()
+
+    assert [body["n"] for body in seen] == [5]
+    assert (result.samples, result.valid, result.votes) == (5, 5, 3)
+    assert [(c.answer, c.samples) for c in result.tally] == [({"label": "a"}, [0, 1, 3]), ({"label": "b"}, [2, 4])]
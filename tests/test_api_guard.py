@@ -0,0 +1,137 @@
+"""Guard the public API surface against accidental breaking changes.
+
+Every module of the package with an ``__all__`` is described in
+``tests/data/public_api.txt``: its public names, model and dataclass fields
+(marking the required ones) and callable signatures. A removed name, field
+or parameter, or a new required one, breaks downstream code and fails this
+test. Additions fail too, so the snapshot is reviewed along with them;
+regenerate it with::
+
+    PROMPTI_UPDATE_API_SNAPSHOT=1 pytest tests/test_api_guard.py
+"""
+
+import dataclasses
+import enum
+import importlib
+import inspect
+import os
+import pkgutil
+from pathlib import Path
+
+from pydantic import BaseModel
+
+import prompti
+
+SNAPSHOT = Path(__file__).parent / "data" / "public_api.txt"
+
+
+def _signature(obj):
+    try:
+        signature = inspect.signature(obj)
+    except (TypeError, ValueError):
+        return "(...)"
+    parts = []
+    keyword_only = False
+    for param in signature.parameters.values():
+        if param.kind is param.VAR_POSITIONAL:
+            parts.append(f"*{param.name}")
+            keyword_only = True
+            continue
+        if param.kind is param.VAR_KEYWORD:
+            parts.append(f"**{param.name}")
+            continue
+        if param.kind is param.KEYWORD_ONLY and not keyword_only:
+            parts.append("*")
+            keyword_only = True
+        parts.append(param.name + ("=..." if param.default is not param.empty else ""))
+    return f"({', '.join(parts)})"
+
+
+def _own_members(cls):
+    """Public attributes defined by the package's classes in the MRO of ``cls``."""
+    names = {}
+    for base in reversed(cls.__mro__):
+        if not base.__module__.startswith("prompti"):
+            continue
+        for name, value in vars(base).items():
+            if not name.startswith("_") or name in ("__init__", "__call__"):
+                names[name] = value
+    return names
+
+
+def _describe_class(prefix, cls):
+    lines = [f"{prefix} class"]
+    if issubclass(cls, enum.Enum):
+        return lines + [f"{prefix}.{member.name} member" for member in cls]
+    fields = {}
+    if issubclass(cls, BaseModel):
+        fields = {name: field.is_required() for name, field in cls.model_fields.items()}
+    elif dataclasses.is_dataclass(cls):
+        fields = {
+            f.name: f.default is dataclasses.MISSING and f.default_factory is dataclasses.MISSING
+            for f in dataclasses.fields(cls)
+        }
+    lines += [f"{prefix}.{name} field" + (" (required)" if required else "") for name, required in fields.items()]
+    for name, value in sorted(_own_members(cls).items()):
+        if name in fields or (issubclass(cls, BaseModel) and name in ("model_config", "model_fields")):
+            continue
+        if isinstance(value, property):
+            lines.append(f"{prefix}.{name} property")
+        elif isinstance(value, (staticmethod, classmethod)):
+            lines.append(f"{prefix}.{name}{_signature(getattr(cls, name))}")
+        elif inspect.isfunction(value):
+            if name == "__init__" and issubclass(cls, BaseModel):
+                continue
+            lines.append(f"{prefix}.{name}{_signature(value)}")
+        elif not callable(value):
+            lines.append(f"{prefix}.{name} attribute")
+    return lines
+
+
+def _describe_module(module):
+    lines = []
+    for name in sorted(module.__all__):
+        obj = getattr(module, name)
+        prefix = f"{module.__name__}:{name}"
+        if inspect.isclass(obj):
+            lines += _describe_class(prefix, obj)
+        elif callable(obj):
+            lines.append(f"{prefix}{_signature(obj)}")
+        else:
+            lines.append(f"{prefix} value")
+    return lines
+
+
+def public_api():
+    """Return the description of the public API, one line per item."""
+    lines = _describe_module(prompti)
+    for info in sorted(pkgutil.walk_packages(prompti.__path__, "prompti."), key=lambda info: info.name):
+        if any(part.startswith("_") for part in info.name.split(".")):
+            continue
+        module = importlib.import_module(info.name)
+        if hasattr(module, "__all__"):
+            lines += _describe_module(module)
+    return lines
+
+
+def test_public_api_matches_snapshot():
+    current = public_api()
+    if os.environ.get("PROMPTI_UPDATE_API_SNAPSHOT"):
+        SNAPSHOT.write_text("\n".join(current) + "\n", encoding="utf-8")
+    recorded = SNAPSHOT.read_text(encoding="utf-8").splitlines()
+    removed = sorted(set(recorded) - set(current))
+    added = sorted(set(current) - set(recorded))
+    assert not removed, "Public API removed or changed (breaking):\n" + "\n".join(removed)
+    assert not added, "Public API added; review and update the snapshot:\n" + "\n".join(added)
+
+
+def test_returned_types_are_keyword_only():
+    # fields can be added to these without breaking callers that construct them
+    from prompti.config_validation import ConfigIssue
+    from prompti.message_order import OrderIssue
+    from prompti.partial_json import PartialJSON, PartialToolCall
+
+    types = [prompti.ModelResponse, prompti.StreamingModelResponse, prompti.Choice, prompti.StreamingChoice]
+    for cls in types + [ConfigIssue, OrderIssue, PartialJSON, PartialToolCall]:
+        params = inspect.signature(cls).parameters.values()
+        assert all(p.kind in (p.KEYWORD_ONLY, p.VAR_KEYWORD) for p in params), cls
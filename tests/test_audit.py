@@ -0,0 +1,51 @@
+import json
+
+import pytest
+
+from prompti.audit import AuditIntegrityError, AuditLogger, verify_audit_log
+from prompti.message import Usage
+from prompti.model_client import Message, ModelConfig, RunParams
+
+
+def make_params(**kw):
+    return RunParams(messages=[Message.create_user("hi")], user_id="alice", request_id="r1", **kw)
+
+
+def test_entries_are_chained_and_verifiable(tmp_path):
+    path = tmp_path / "audit.jsonl"
+    cfg = ModelConfig(provider="openai", model="gpt-4o")
+    logger = AuditLogger(path)
+    params = make_params(trace_context={"audit": {"anonymized": True}})
+    logger.on_complete(cfg, params, Usage(prompt_tokens=3, completion_tokens=2, total_tokens=5), 0.25)
+    logger.on_error(cfg, make_params(), "rate_limit", {"message": "slow down"})
+
+    # A new logger continues the existing chain.
+    AuditLogger(path).record("policy", rule="pii", decision="redacted")
+
+    entries = [json.loads(line) for line in path.read_text().splitlines()]
+    assert [e["seq"] for e in entries] == [1, 2, 3]
+    assert entries[0]["user_id"] == "alice"
+    assert entries[0]["policy"] == {"anonymized": True}
+    assert entries[0]["usage"]["total_tokens"] == 5
+    assert entries[0]["request_hash"] == entries[1]["request_hash"] == make_params().canonical_hash("gpt-4o")
+    assert entries[1]["error_class"] == "rate_limit"
+    assert entries[1]["prev_hash"] == entries[0]["hash"]
+    assert verify_audit_log(path) == 3
+
+
+def test_tampering_is_detected(tmp_path):
+    path = tmp_path / "audit.jsonl"
+    logger = AuditLogger(path)
+    logger.record("completion", model="a")
+    logger.record("completion", model="b")
+    lines = path.read_text().splitlines()
+    tampered = json.loads(lines[0])
+    tampered["model"] = "evil"
+    path.write_text(json.dumps(tampered) + "\n" + lines[1] + "\n")
+    with pytest.raises(AuditIntegrityError) as exc:
+        verify_audit_log(path)
+    assert exc.value.line == 1
+
+    path.write_text(lines[1] + "\n")
+    with pytest.raises(AuditIntegrityError):
+        verify_audit_log(path)
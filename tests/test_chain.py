@@ -0,0 +1,105 @@
+import pytest
+from pydantic import BaseModel
+
+import prompti.engine as engine_module
+from prompti.chain import Chain, ChainError
+from prompti.engine import PromptEngine
+from prompti.loader import MemoryLoader
+from prompti.message import Message, ModelResponse
+from prompti.model_client import ModelClient, ModelConfig, RunParams
+
+
+def template(text):
+    return {
+        "yaml": f"""
+name: t
+version: '1'
+variants:
+  base:
+    selector: []
+    model_cfg:
+      provider: dummy
+      model: small
+    messages:
+      - role: user
+        content: "{text}"
+"""
+    }
+
+
+TEMPLATES = {
+    "summarize": template("Summarize: {{ document }}"),
+    "extract": template("Extract from: {{ summary }}"),
+    "classify": template("Classify {{ facts.people | join(',') }} in {{ language }}"),
+}
+
+
+class Facts(BaseModel):
+    people: list[str]
+
+
+class ScriptedClient(ModelClient):
+    def __init__(self, cfg, answers, prompts):
+        super().__init__(cfg)
+        self.answers = answers
+        self.prompts = prompts
+
+    async def _run(self, params: RunParams):
+        self.prompts.append((self.cfg.model, params.messages[-1].content))
+        text = self.answers.pop(0)
+        yield ModelResponse(
+            model=self.cfg.model,
+            choices=[{"index": 0, "message": Message.create_assistant(text), "finish_reason": "stop"}],
+        )
+
+
+@pytest.fixture
+def scripted(monkeypatch):
+    answers, prompts = [], []
+    monkeypatch.setattr(
+        engine_module, "create_client", lambda cfg, **kwargs: ScriptedClient(cfg, answers, prompts)
+    )
+    return answers, prompts
+
+
+def pipeline():
+    engine = PromptEngine([MemoryLoader(TEMPLATES)])
+    return (
+        Chain(engine, name="triage")
+        .step("summary", "summarize")
+        .step("facts", "extract", output=Facts, retries=1, model_cfg=ModelConfig(provider="dummy", model="big"))
+        .step("label", "classify", variables={"language": "en"})
+    )
+
+
+@pytest.mark.asyncio
+async def test_outputs_feed_later_steps(scripted):
+    answers, prompts = scripted
+    answers += ["Ann paid Bob.", "not json", '```json\n{"people": ["Ann", "Bob"]}\n```', " payment "]
+    result = await pipeline().arun({"document": "long text"})
+
+    assert result.outputs["facts"] == Facts(people=["Ann", "Bob"])
+    assert result.output == "payment"
+    assert [(s.name, s.model, s.attempts) for s in result.steps] == [
+        ("summary", "small", 1),
+        ("facts", "big", 2),
+        ("label", "small", 1),
+    ]
+    assert prompts[0] == ("small", "Summarize: long text")
+    assert prompts[1] == ("big", "Extract from: Ann paid Bob.")
+    assert prompts[3] == ("small", "Classify Ann,Bob in en")
+
+
+@pytest.mark.asyncio
+async def test_invalid_output_after_retries(scripted):
+    answers, _ = scripted
+    answers += ["summary", "{}", "[]"]
+    with pytest.raises(ChainError, match="'facts' failed: invalid output after 2 attempts") as info:
+        await pipeline().arun({"document": "x"})
+    assert list(info.value.result.outputs) == ["summary"]
+
+
+def test_step_names_are_unique():
+    chain = Chain(PromptEngine([MemoryLoader(TEMPLATES)])).step("a", "summarize")
+    with pytest.raises(ValueError, match="already has a step 'a'"):
+        chain.step("a", "extract")
@@ -0,0 +1,251 @@
+import argparse
+import json
+
+import pytest
+
+from prompti import cli
+from prompti.message import Message, ModelResponse, StreamingChoice, StreamingModelResponse, Usage
+
+
+def test_parse_tool_option():
+    assert cli.parse_tool_option(" lint = ./lint.sh --fix ") == ("lint", "./lint.sh --fix")
+    with pytest.raises(argparse.ArgumentTypeError):
+        cli.parse_tool_option("lint")
+
+
+def test_stream_events_for_a_chunk():
+    chunk = StreamingModelResponse(
+        id="r1",
+        model="m",
+        choices=[StreamingChoice(index=0, delta=Message(role="assistant", content="hi"), finish_reason="stop")],
+        usage=Usage(prompt_tokens=1, completion_tokens=2, total_tokens=3),
+    )
+    assert [e["type"] for e in cli.stream_events(chunk)] == ["content_delta", "finish", "usage"]
+
+
+def test_merge_tool_call_deltas():
+    calls: dict = {}
+    cli.merge_tool_call_deltas(calls, [{"index": 0, "id": "c1", "function": {"name": "get_", "arguments": '{"a"'}}])
+    cli.merge_tool_call_deltas(calls, [{"index": 0, "function": {"name": "time", "arguments": ": 1}"}}])
+    assert calls[0] == {"id": "c1", "type": "function", "function": {"name": "get_time", "arguments": '{"a": 1}'}}
+
+
+def test_usage_summary():
+    summary = cli.UsageSummary()
+    summary.add(Usage(prompt_tokens=3, completion_tokens=2, total_tokens=5), {"total_latency": 0.5})
+    summary.add(None, {"total_latency": 0.25, "first_package_latency": 0.1})
+    assert summary.format() == (
+        "usage: 2 request(s), 3 prompt + 2 completion = 5 tokens, latency 750 ms, first token 100 ms"
+    )
+
+
+def test_usage_summary_estimates_the_cost_of_priced_models():
+    usage = Usage(prompt_tokens=1000, completion_tokens=100, total_tokens=1100)
+    summary = cli.UsageSummary("gpt-4o", "openai")
+    summary.add(usage, {})
+    summary.add(usage, {})
+    assert summary.format().splitlines()[1] == "estimated cost: $0.007000"
+
+    unpriced = cli.UsageSummary("my-finetune", "openai")
+    unpriced.add(usage, {})
+    assert "cost" not in unpriced.format()
+
+
+@pytest.mark.asyncio
+async def test_config_validate_exit_codes(tmp_path, capsys):
+    good = tmp_path / "models.yaml"
+    good.write_text("models:\n  - provider: openai\n    model: gpt-4o\n")
+    bad = tmp_path / "bad.yaml"
+    bad.write_text("models:\n  - provider: openai\n    modle: gpt-4o\n")
+    assert await cli.main(["config", "validate", str(good)]) == 0
+    assert "ok (models)" in capsys.readouterr().out
+    assert await cli.main(["config", "validate", str(bad)]) == 1
+    assert "did you mean 'model'" in capsys.readouterr().err
+
+
+@pytest.mark.asyncio
+async def test_config_validate_json(tmp_path, capsys):
+    bad = tmp_path / "bad.yaml"
+    bad.write_text("models:\n  - provider: openai\n    modle: gpt-4o\n")
+    assert await cli.main(["config", "validate", "--json", str(bad)]) == 1
+    document = json.loads(capsys.readouterr().out)
+    assert document["schema_version"] == cli.SCHEMA_VERSION
+    assert document["kind"] == "config_validate" and document["schema"] == "models" and not document["ok"]
+    assert document["issues"][0]["path"] == ["models", 0, "modle"] and document["issues"][0]["line"] == 3
+
+
+@pytest.mark.parametrize("version", cli.SCHEMA_VERSIONS)
+def test_stream_events_are_versioned(version, capsys):
+    cli.write_event({"type": "done"}, "ndjson", version)
+    cli.write_event({"type": "done"}, "sse", version)
+    out = capsys.readouterr().out.splitlines()
+    expected = {"type": "done"} if version == 1 else {"schema_version": version, "type": "done"}
+    assert json.loads(out[0]) == expected
+    assert out[1:3] == ["event: done", f"data: {json.dumps(expected)}"]
+
+
+
+def test_text_stream_keeps_reasoning_off_stdout(capsys):
+    cli.write_event({"type": "reasoning_delta", "index": 0, "content": "thinking"}, "text")
+    cli.write_event({"type": "content_delta", "index": 0, "content": "answer"}, "text")
+    captured = capsys.readouterr()
+    assert (captured.out, captured.err) == ("answer", "thinking")
+
+
+def test_parse_price_option():
+    assert cli.parse_price_option("gpt-4o=2.5,10") == ("gpt-4o", (2.5, 10.0))
+    with pytest.raises(argparse.ArgumentTypeError):
+        cli.parse_price_option("gpt-4o=2.5")
+
+
+class CompareClient:
+    answers = {"a": "The cat sat.", "b": "The dog sat.", "c": "A | B"}
+
+    def __init__(self, model):
+        self.model = model
+
+    async def arun(self, params):
+        message = Message.create_assistant(self.answers[self.model])
+        usage = Usage(prompt_tokens=1000, completion_tokens=10, total_tokens=1010)
+        yield ModelResponse(choices=[{"index": 0, "message": message, "finish_reason": "stop"}], usage=usage)
+
+    async def aclose(self):
+        pass
+
+
+@pytest.mark.asyncio
+async def test_chat_usage_summary_includes_the_estimated_cost(monkeypatch, capsys):
+    def create_client(cfg):
+        client = CompareClient("a")
+        client.cfg = cfg
+        return client
+
+    monkeypatch.setattr(cli, "create_client", create_client)
+    monkeypatch.setattr(cli, "setup_observability", lambda: None)
+    argv = ["chat", "--provider", "openai", "--model", "gpt-4o", "-q", "hi", "--no-stream", "--usage"]
+    assert await cli.main(argv) == 0
+    err = capsys.readouterr().err
+    assert "usage: 1 request(s), 1000 prompt + 10 completion = 1010 tokens" in err
+    assert "estimated cost: $0.002600" in err
+
+
+@pytest.mark.asyncio
+async def test_compare_prints_diff(monkeypatch, capsys):
+    monkeypatch.setattr(cli, "create_client", lambda cfg: CompareClient(cfg.model))
+    assert await cli.main(["compare", "--provider", "openai", "--model", "a", "--against", "b", "-q", "hi"]) == 0
+    out = capsys.readouterr().out
+    assert "**a** vs **b**: token similarity 0.75" in out
+    assert "-The cat sat.\n+The dog sat." in out
+
+
+@pytest.mark.asyncio
+async def test_compare_json_schema_versions(monkeypatch, capsys):
+    monkeypatch.setattr(cli, "create_client", lambda cfg: CompareClient(cfg.model))
+    argv = ["compare", "--provider", "openai", "--models", "a,b", "-q", "hi", "--json"]
+    assert await cli.main(argv) == 0
+    document = json.loads(capsys.readouterr().out)
+    assert (document["schema_version"], document["kind"]) == (2, "compare")
+    assert await cli.main(argv + ["--schema-version", "1"]) == 0
+    assert list(json.loads(capsys.readouterr().out)) == ["models"]
+
+
+@pytest.mark.asyncio
+async def test_schema_version_from_the_environment_is_validated(monkeypatch, capsys):
+    monkeypatch.setattr(cli, "create_client", lambda cfg: CompareClient(cfg.model))
+    argv = ["compare", "--provider", "openai", "--models", "a,b", "-q", "hi", "--json"]
+    monkeypatch.setenv("PROMPTI_SCHEMA_VERSION", "1")
+    assert await cli.main(argv) == 0
+    assert list(json.loads(capsys.readouterr().out)) == ["models"]
+    monkeypatch.setenv("PROMPTI_SCHEMA_VERSION", "7")
+    with pytest.raises(SystemExit):
+        await cli.main(argv)
+    assert "PROMPTI_SCHEMA_VERSION must be one of 1, 2, got 7" in capsys.readouterr().err
+
+
+@pytest.mark.asyncio
+async def test_compare_table_for_several_models(monkeypatch, capsys):
+    created = []
+
+    def create_client(cfg):
+        created.append((cfg.provider, cfg.model))
+        return CompareClient(cfg.model)
+
+    monkeypatch.setattr(cli, "create_client", create_client)
+    argv = ["compare", "--provider", "openai", "--models", "a,litellm:b,c", "--prompt", "hi", "--price", "b=1,2"]
+    assert await cli.main(argv) == 0
+    lines = capsys.readouterr().out.splitlines()
+    assert created == [("openai", "a"), ("litellm", "b"), ("openai", "c")]
+    assert lines[0] == "| model | latency | tokens in/out | cost | similarity | response |"
+    assert lines[2].startswith("| a | ") and lines[2].endswith("| 1000/10 | - | - | The cat sat. |")
+    assert lines[3].endswith("| 1000/10 | $0.001020 | 0.75 | The dog sat. |")
+    assert lines[4].endswith("| A \\| B |")
+    assert not any("```diff" in line for line in lines)
+
+
+def test_tui_models(tmp_path):
+    parser = cli.build_parser()
+    args = parser.parse_args(["tui", "--provider", "openai", "--models", "gpt-4o,ollama:llama3.1"])
+    assert [(m.provider, m.model) for m in cli.tui_models(args)] == [("openai", "gpt-4o"), ("ollama", "llama3.1")]
+    path = tmp_path / "models.yaml"
+    path.write_text("models:\n  - {provider: gemini, model: gemini-2.5-flash, api_key: k}\n")
+    [model] = cli.tui_models(parser.parse_args(["tui", "--models-file", str(path)]))
+    assert (model.provider, model.api_key) == ("gemini", "k")
+
+
+class ErrorClient:
+    cfg = cli.ModelConfig(provider="openai", model="gpt-4o")
+
+    async def arun(self, params):
+        yield StreamingModelResponse(error={"message": "Rate limit reached", "status_code": 429})
+
+    async def aclose(self):
+        pass
+
+
+@pytest.mark.asyncio
+async def test_chat_error_prints_a_hint(monkeypatch, capsys):
+    monkeypatch.setattr(cli, "setup_observability", lambda: None)
+    monkeypatch.setattr(cli, "create_client", lambda cfg: ErrorClient())
+    assert await cli.main(["-q", "hi"]) == 0
+    err = capsys.readouterr().err
+    assert "error: Rate limit reached\nhint: Rate limited by openai:" in err
+    assert await cli.main(["-q", "hi", "--stream-format", "ndjson"]) == 0
+    event = json.loads(capsys.readouterr().out.splitlines()[0])
+    assert (event["type"], event["hint"].startswith("Rate limited")) == ("error", True)
+
+
+class EmbedClient:
+    vectors = {"cat": [1.0, 0.0], "dog": [0.8, 0.6], "car": [0.0, 1.0], "kitten": [0.9, 0.1]}
+
+    def __init__(self):
+        self.bodies = []
+
+    async def aembeddings(self, body):
+        self.bodies.append(body)
+        data = [{"index": i, "embedding": self.vectors[text]} for i, text in enumerate(body["input"])]
+        return {"data": data[::-1]}
+
+    async def aclose(self):
+        pass
+
+
+@pytest.mark.asyncio
+async def test_embed_and_similar(monkeypatch, tmp_path, capsys):
+    client = EmbedClient()
+    monkeypatch.setattr(cli, "create_client", lambda cfg: client)
+    texts = tmp_path / "texts.jsonl"
+    texts.write_text('"cat"\n{"id": 7, "text": "dog"}\n\n"car"\n')
+    index = tmp_path / "vectors.jsonl"
+    argv = ["embed", "--provider", "openai", "--model", "text-embedding-3-small", "--file", str(texts)]
+    assert await cli.main(argv + ["--out", str(index), "--batch-size", "2"]) == 0
+    assert [b["input"] for b in client.bodies] == [["cat", "dog"], ["car"]]
+    records = [json.loads(line) for line in index.read_text().splitlines()]
+    assert records[1] == {"id": 7, "text": "dog", "embedding": [0.8, 0.6]}
+
+    argv = ["similar", "--provider", "openai", "--model", "text-embedding-3-small", "--index", str(index)]
+    assert await cli.main(argv + ["-q", "kitten", "--top-k", "2"]) == 0
+    assert [line.split("\t")[1] for line in capsys.readouterr().out.splitlines()] == ["cat", "dog"]
+    assert await cli.main(argv + ["-q", "kitten", "--top-k", "1", "--json"]) == 0
+    document = json.loads(capsys.readouterr().out)
+    assert document["kind"] == "similar"
+    assert [(r["text"], round(r["score"], 3)) for r in document["results"]] == [("cat", 0.994)]
@@ -0,0 +1,30 @@
+from prompti.compare import diff_responses, tokenize
+from prompti.message import Message, ModelResponse
+
+
+def test_tokenize_splits_cjk_characters():
+    assert tokenize("订单order 12, ok!") == ["订", "单", "order", "12", ",", "ok", "!"]
+
+
+def test_diff_scores_and_markdown():
+    diff = diff_responses("The cat sat.\nBye", "The dog sat.\nBye", labels=("old", "new"))
+    assert diff.tokens_added == 1 and diff.tokens_removed == 1
+    assert diff.token_similarity == 0.8
+    assert diff.semantic_similarity is None
+    assert diff.markdown.startswith("**old** vs **new**: token similarity 0.80 (+1 / -1 tokens)")
+    assert "-The cat sat.\n+The dog sat.\n Bye" in diff.markdown
+
+
+def test_diff_of_responses_and_identical_texts():
+    message = Message.create_assistant("你好")
+    response = ModelResponse(choices=[{"index": 0, "message": message, "finish_reason": "stop"}])
+    diff = diff_responses(response, "你好")
+    assert diff.token_similarity == 1.0 and "(identical)" in diff.markdown
+    assert diff_responses("", None).token_similarity == 1.0
+    assert diff_responses("", "hi").token_similarity == 0.0
+
+
+def test_semantic_similarity_uses_embedding():
+    vectors = {"yes": [1.0, 0.0], "sure": [0.6, 0.8], "no": [0.0, 1.0]}
+    assert diff_responses("yes", "sure", embed=vectors.__getitem__).semantic_similarity == 0.6
+    assert diff_responses("yes", "no", embed=vectors.__getitem__).semantic_similarity == 0.0
@@ -0,0 +1,52 @@
+import pytest
+
+from prompti.config_validation import ConfigValidationError, validate_config, validate_config_file
+from prompti.engine import Setting
+from prompti.model_client.config_loader import FileModelConfigLoader
+
+
+def test_valid_setting_file_loads(tmp_path):
+    path = tmp_path / "setting.yaml"
+    path.write_text("template_paths:\n  - ./prompts\ndefault_model_config:\n  provider: openai\n  model: gpt-4o\n")
+    setting = Setting.from_file(str(path))
+    assert setting.default_model_config.model == "gpt-4o"
+    assert str(setting.template_paths[0]) == "prompts"
+
+
+def test_unknown_key_reports_line_and_suggestion(tmp_path):
+    path = tmp_path / "setting.yaml"
+    path.write_text("cache_ttl: 10\ndefault_model_config:\n  provider: openai\n  temprature: 0.2\n")
+    with pytest.raises(ConfigValidationError) as exc:
+        Setting.from_file(str(path))
+    (issue,) = exc.value.issues
+    assert issue.path == ("default_model_config", "temprature")
+    assert issue.line == 4
+    assert "did you mean 'temperature'" in issue.message
+    assert f"{path}:4:3: default_model_config.temprature" in str(exc.value)
+
+
+def test_type_mismatch_is_located(tmp_path):
+    path = tmp_path / "models.yaml"
+    path.write_text("models:\n  - provider: openai\n    model: a\n  - provider: openai\n    max_tokens: lots\n")
+    loader = FileModelConfigLoader(path)
+    with pytest.raises(ConfigValidationError) as exc:
+        loader.load()
+    (issue,) = exc.value.issues
+    assert issue.path == ("models", 1, "max_tokens")
+    assert issue.line == 5
+    assert "'lots'" in issue.message
+
+
+def test_mutually_exclusive_options_are_rejected():
+    data = {"default_model_config": {"model": "a"}, "global_config_loader": object()}
+    with pytest.raises(ConfigValidationError) as exc:
+        validate_config(data, Setting)
+    assert any("mutually exclusive" in issue.message for issue in exc.value.issues)
+
+
+def test_invalid_yaml_is_reported_with_position(tmp_path):
+    path = tmp_path / "broken.yaml"
+    path.write_text("cache_ttl: [1, 2\n")
+    with pytest.raises(ConfigValidationError) as exc:
+        validate_config_file(path, Setting)
+    assert exc.value.issues[0].line is not None
@@ -0,0 +1,36 @@
+import pytest
+
+from prompti.conversation import Conversation
+from prompti.message import Message
+
+
+def root():
+    return Conversation([Message.create_system("You are helpful."), Message.create_user("Plan a trip.")])
+
+
+def test_branches_share_the_prefix():
+    base = root()
+    left, right = base.fork(), base.fork()
+    left.append(Message.create_assistant("Paris"))
+    right.extend([Message.create_assistant("Rome"), Message.create_user("Cheaper?")])
+
+    assert len(base) == 2
+    assert [m.content for m in left] == ["You are helpful.", "Plan a trip.", "Paris"]
+    assert [m.content for m in right.messages][2:] == ["Rome", "Cheaper?"]
+    assert left[0] is right[0] is base[0]
+    assert right[-1].content == "Cheaper?"
+    assert left.common_prefix(right) == 2
+    assert right.common_prefix(right.fork(3)) == 3
+    assert base.common_prefix(Conversation(base.messages)) == 0
+
+
+def test_fork_at_a_message():
+    branch = root()
+    branch.append(Message.create_assistant("Paris"))
+    retry = branch.fork(-1)
+    assert [m.content for m in retry] == ["You are helpful.", "Plan a trip."]
+    assert len(branch.fork(0)) == 0
+    with pytest.raises(IndexError):
+        branch.fork(4)
+    with pytest.raises(IndexError):
+        branch[3]
@@ -0,0 +1,153 @@
+import base64
+import json
+
+import httpx
+import pytest
+
+from prompti.documents import (
+    document_part,
+    extract_citations,
+    file_part,
+    text_document_part,
+    to_anthropic_content,
+    to_openai_file_parts,
+)
+from prompti.message import Message
+from prompti.model_client import ModelConfig, RunParams
+from prompti.model_client.azure_client import SyncAzureOpenAIClient
+from prompti.model_client.openai_client import OpenAIClient
+from prompti.model_client.openai_wire import OpenAIWireClient
+
+
+def test_document_part_sources():
+    part = document_part(b"%PDF-1.4", title="Contract", citations=True)
+    assert part == {
+        "type": "document",
+        "source": {"type": "base64", "media_type": "application/pdf", "data": base64.b64encode(b"%PDF-1.4").decode()},
+        "title": "Contract",
+        "citations": {"enabled": True},
+    }
+    assert document_part("https://example.com/a.pdf")["source"] == {"type": "url", "url": "https://example.com/a.pdf"}
+    assert document_part("data:text/plain;base64,aGk=")["source"]["media_type"] == "text/plain"
+    with pytest.raises(ValueError):
+        document_part("/tmp/a.pdf")
+    assert text_document_part("Term: 2 years.", title="Contract") == {
+        "type": "document",
+        "source": {"type": "text", "media_type": "text/plain", "data": "Term: 2 years."},
+        "title": "Contract",
+    }
+
+
+def test_to_anthropic_content():
+    doc = document_part("https://example.com/a.pdf", citations=True)
+    image = {"type": "image_url", "image_url": {"url": "https://x/y.png"}}
+    content = [doc, {"type": "text", "text": "Summarize"}, image]
+    assert to_anthropic_content(content) == [
+        doc,
+        {"type": "text", "text": "Summarize"},
+        {"type": "image", "source": {"type": "url", "url": "https://x/y.png"}},
+    ]
+    assert to_anthropic_content("hi") == "hi"
+
+
+CITATION = {
+    "type": "page_location",
+    "cited_text": "The term ends on 31 December 2026.",
+    "document_index": 0,
+    "document_title": "Contract",
+    "start_page_number": 3,
+    "end_page_number": 4,
+}
+
+
+def test_extract_citations_from_anthropic_blocks_and_litellm_fields():
+    blocks = [
+        {"type": "text", "text": "According to the contract, "},
+        {"type": "text", "text": "it ends on 31 December 2026.", "citations": [CITATION]},
+    ]
+    (citation,) = extract_citations({"content": blocks})
+    assert (citation.start, citation.end, citation.text) == (3, 4, "it ends on 31 December 2026.")
+
+    grouped = extract_citations({"provider_specific_fields": {"citations": [[CITATION], []]}})
+    assert [c.cited_text for c in grouped] == [CITATION["cited_text"]]
+    assert extract_citations({"provider_specific_fields": {"citation": CITATION}})[0].type == "page_location"
+    assert extract_citations({"content": "no citations"}) is None
+
+
+@pytest.mark.asyncio
+async def test_openai_wire_sends_documents_and_surfaces_citations():
+    sent = []
+
+    def handler(request):
+        sent.append(json.loads(request.content))
+        message = {"role": "assistant", "content": "It ends in 2026.", "citations": [CITATION]}
+        return httpx.Response(200, json={"choices": [{"index": 0, "message": message, "finish_reason": "stop"}]})
+
+    client = OpenAIWireClient(
+        ModelConfig(provider="gateway", model="claude-sonnet", api_url="https://gw.example.com/v1/chat/completions"),
+        client=httpx.AsyncClient(transport=httpx.MockTransport(handler)),
+    )
+    doc = document_part(b"%PDF", citations=True)
+    params = RunParams(messages=[Message.create_user([doc, {"type": "text", "text": "When?"}])], stream=False)
+    responses = [r async for r in client.arun(params)]
+    await client.aclose()
+
+    assert sent[0]["messages"][0]["content"][0] == doc
+    assert responses[-1].get_citations()[0].document_title == "Contract"
+    assert "choices.0.message.citations" not in responses[-1].extra
+
+
+def test_file_part_and_openai_conversion():
+    assert file_part(file_id="file-1") == {"type": "file", "file": {"file_id": "file-1"}}
+    inline = file_part(filename="a.pdf", data=b"%PDF")
+    assert inline["file"] == {"filename": "a.pdf", "file_data": "data:application/pdf;base64,JVBERg=="}
+    with pytest.raises(ValueError):
+        file_part(filename="a.pdf")
+
+    url_doc = document_part("https://example.com/a.pdf")
+    messages = [{"role": "user", "content": [document_part(b"%PDF", title="a.pdf"), url_doc]}]
+    assert to_openai_file_parts(messages)[0]["content"] == [inline, url_doc]
+
+
+def files_api(sent):
+    def handler(request):
+        sent.append(request)
+        if request.url.path.endswith("/files"):
+            return httpx.Response(200, json={"id": "file-abc", "object": "file"})
+        message = {"role": "assistant", "content": "ok"}
+        return httpx.Response(200, json={"choices": [{"index": 0, "message": message, "finish_reason": "stop"}]})
+
+    return handler
+
+
+@pytest.mark.asyncio
+async def test_openai_client_inlines_small_files_and_uploads_large_ones():
+    sent = []
+    client = OpenAIClient(
+        ModelConfig(provider="openai", model="gpt-4o", api_key="k", max_inline_file_bytes=8),
+        client=httpx.AsyncClient(transport=httpx.MockTransport(files_api(sent))),
+    )
+    assert (await client.aprepare_file("small.pdf", b"%PDF"))["file"]["filename"] == "small.pdf"
+    assert sent == []
+    assert await client.aprepare_file("big.pdf", b"%PDF" * 10) == {"type": "file", "file": {"file_id": "file-abc"}}
+    upload = sent[0]
+    assert str(upload.url) == "https://api.openai.com/v1/files"
+    assert upload.headers["authorization"] == "Bearer k"
+    assert upload.headers["content-type"].startswith("multipart/form-data")
+    body = upload.read()
+    assert b'name="purpose"\r\n\r\nuser_data' in body and b'filename="big.pdf"' in body
+
+    # document parts are sent to OpenAI as file parts
+    params = RunParams(messages=[Message.create_user([document_part(b"%PDF", title="a.pdf")])], stream=False)
+    _ = [r async for r in client.arun(params)]
+    await client.aclose()
+    assert json.loads(sent[-1].content)["messages"][0]["content"][0]["type"] == "file"
+
+
+def test_sync_azure_client_uploads_to_resource_files_endpoint():
+    sent = []
+    cfg = ModelConfig(provider="azure", model="dep", api_url="https://r.openai.azure.com", max_inline_file_bytes=0)
+    client = SyncAzureOpenAIClient(cfg, client=httpx.Client(transport=httpx.MockTransport(files_api(sent))))
+    assert client.prepare_file("a.pdf", b"%PDF")["file"] == {"file_id": "file-abc"}
+    assert str(sent[0].url) == "https://r.openai.azure.com/openai/files?api-version=2024-06-01"
+    client.close()
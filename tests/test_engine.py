@@ -300,3 +300,25 @@ async def test_direct_messages():
         # 由于我们无法直接访问客户端的run方法调用参数
         # 我们只验证模型配置和结果
         assert out[0].model == "direct_model"
+
+
+def test_model_overrides_fill_unset_fields():
+    engine = PromptEngine(
+        [],
+        global_model_config=ModelConfig(provider="openai", model="gpt-4o"),
+        model_overrides={
+            "gpt-4o": ModelConfig(temperature=0.2, max_tokens=512),
+            "o4-mini": ModelConfig(max_tokens=4096),
+        },
+    )
+    cfg = engine._merge_model_configs(input_cfg=None, template_cfg=None)
+    assert (cfg.temperature, cfg.max_tokens) == (0.2, 512)
+
+    # Explicit values win over the per-model defaults.
+    cfg = engine._merge_model_configs(
+        input_cfg=ModelConfig(provider="openai", model="gpt-4o", temperature=0.9), template_cfg=None
+    )
+    assert (cfg.temperature, cfg.max_tokens) == (0.9, 512)
+
+    cfg = engine._merge_model_configs(input_cfg=ModelConfig(provider="openai", model="o4-mini"), template_cfg=None)
+    assert (cfg.temperature, cfg.max_tokens) == (None, 4096)
@@ -0,0 +1,94 @@
+from unittest.mock import patch
+
+import pytest
+
+from prompti.engine import PromptEngine
+from prompti.fragments import FragmentNotFoundError, PromptLibrary
+from prompti.message import Message, ModelResponse
+from prompti.model_client import ModelClient, ModelConfig, RunParams
+from prompti.template import PromptTemplate, Variant
+
+
+def library():
+    return PromptLibrary.from_dict(
+        {
+            "tone": {"1": "Be concise.", "2": "Be concise and friendly.", "10": "Be brief, {{ name }}."},
+            "footer": "{{ fragment('tone@1') }} Thanks!",
+        }
+    )
+
+
+def test_get_resolves_pinned_and_latest_versions():
+    lib = library()
+    assert lib.versions("tone") == ["1", "2", "10"]
+    assert lib.get("tone@2").text == "Be concise and friendly."
+    assert lib.get("tone", version="1").text == "Be concise."
+    assert lib.get("tone").version == "10"
+    with pytest.raises(FragmentNotFoundError, match="no version '3'"):
+        lib.get("tone@3")
+    with pytest.raises(FragmentNotFoundError, match="Unknown"):
+        lib.get("missing")
+
+
+def test_render_includes_nested_fragments_and_tracks_versions():
+    lib = library()
+    with PromptLibrary.track() as used:
+        assert lib.render("footer") == "Be concise. Thanks!"
+        assert lib.render("tone", {"name": "Ann"}) == "Be brief, Ann."
+    assert used == {"footer": "1", "tone": "10"}
+
+
+def test_render_rejects_cycles():
+    lib = PromptLibrary.from_dict({"a": "{{ fragment('b') }}", "b": "{{ fragment('a') }}"})
+    with pytest.raises(ValueError, match="a -> b -> a"):
+        lib.render("a")
+
+
+def test_template_format_includes_fragments():
+    tmpl = PromptTemplate(
+        id="t",
+        name="t",
+        version="1",
+        variants={
+            "default": Variant(
+                model_cfg=ModelConfig(provider="dummy", model="m"),
+                messages=[{"role": "system", "content": "{{ fragment('tone@2') }} Topic: {{ topic }}"}],
+            )
+        },
+    )
+    messages, _ = tmpl.format({"topic": "billing"}, fragments=library())
+    assert messages[0]["content"] == "Be concise and friendly. Topic: billing"
+
+
+class RecordingModel(ModelClient):
+    seen: list[RunParams] = []
+
+    async def _run(self, params: RunParams):
+        RecordingModel.seen.append(params)
+        message = Message(role="assistant", content="ok")
+        yield ModelResponse(choices=[{"index": 0, "message": message, "finish_reason": "stop"}])
+
+
+@pytest.mark.asyncio
+async def test_engine_records_fragment_versions_in_trace_context():
+    tmpl = PromptTemplate(
+        id="support",
+        name="support",
+        version="1",
+        variants={
+            "default": Variant(
+                model_cfg=ModelConfig(provider="dummy", model="m"),
+                messages=[
+                    {"role": "system", "content": "{{ fragment('footer') }}"},
+                    {"role": "user", "content": "{{ question }}"},
+                ],
+            )
+        },
+    )
+    engine = PromptEngine([], fragments=library())
+    RecordingModel.seen = []
+    with patch("prompti.engine.create_client", side_effect=lambda cfg, **kw: RecordingModel(cfg)):
+        _ = [r async for r in engine.acompletion("support", template=tmpl, variables={"question": "hi"})]
+    params = RecordingModel.seen[-1]
+    assert params.trace_context["fragments"] == {"footer": "1", "tone": "1"}
+    assert params.messages[0].content == "Be concise. Thanks!"
@@ -0,0 +1,75 @@
+import base64
+import io
+
+import httpx
+import pytest
+
+from prompti.images import ImageLimits, fit_image, image_data_url, prepare_images
+from prompti.message import Message
+from prompti.model_client.base import ModelConfig, RunParams
+from prompti.model_client.openai_client import SyncOpenAIClient
+from prompti.model_client.transport import RequestTooLargeError
+
+
+def data_url(data, mime="image/png"):
+    return f"data:{mime};base64,{base64.b64encode(data).decode()}"
+
+
+def fake_fit(data, mime, limits):
+    # stands in for Pillow: "shrinks" anything over the limit to its first bytes
+    if len(data) <= limits.max_bytes:
+        return data, mime
+    return data[: limits.max_bytes], "image/jpeg"
+
+
+def test_prepare_images_fits_only_inline_images():
+    big, small = data_url(b"x" * 100), data_url(b"y" * 5)
+    remote = "https://example.com/cat.png"
+    messages = [
+        Message.create_user("text only"),
+        Message.create_user(
+            [
+                {"type": "text", "text": "look"},
+                {"type": "image_url", "image_url": {"url": big, "detail": "high"}},
+                {"type": "image_url", "image_url": {"url": remote}},
+                {"type": "image_url", "image_url": {"url": small}},
+            ]
+        ),
+    ]
+    result = prepare_images(messages, ImageLimits(max_bytes=10), fit=fake_fit)
+    assert result[0] is messages[0]
+    parts = result[1].content
+    assert parts[1]["image_url"] == {"url": data_url(b"x" * 10, "image/jpeg"), "detail": "high"}
+    assert parts[2]["image_url"]["url"] == remote
+    assert parts[3]["image_url"]["url"] == small
+    assert messages[1].content[1]["image_url"]["url"] == big  # input is not modified
+
+
+def test_prepare_images_returns_input_when_nothing_changes():
+    messages = [Message.create_user([{"type": "image_url", "image_url": {"url": data_url(b"tiny")}}])]
+    assert prepare_images(messages, ImageLimits(max_bytes=1024), fit=fake_fit) is messages
+
+
+def test_images_within_byte_limit_need_no_pillow():
+    assert fit_image(b"\x89PNG small", "image/png", ImageLimits(max_bytes=1024)) == (b"\x89PNG small", "image/png")
+    assert image_data_url(b"abc", "image/gif") == "data:image/gif;base64,YWJj"
+
+
+def test_fit_image_downscales_with_pillow():
+    Image = pytest.importorskip("PIL.Image")
+    buffer = io.BytesIO()
+    Image.effect_noise((1200, 800), 64).convert("RGB").save(buffer, format="PNG")
+    data, mime = fit_image(buffer.getvalue(), "image/png", ImageLimits(max_bytes=60_000, max_dimension=600))
+    assert mime == "image/jpeg" and len(data) <= 60_000
+    assert max(Image.open(io.BytesIO(data)).size) <= 600
+
+
+def test_oversized_request_is_rejected_before_sending():
+    def handler(request):
+        raise AssertionError("request must not be sent")
+
+    cfg = ModelConfig(provider="openai", model="m", max_request_bytes=1000)
+    client = SyncOpenAIClient(cfg, client=httpx.Client(transport=httpx.MockTransport(handler)))
+    params = RunParams(messages=[Message.create_user("x" * 2000)], stream=False)
+    with pytest.raises(RequestTooLargeError, match="over the max_request_bytes limit of 1000"):
+        list(client.run(params))
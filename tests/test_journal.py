@@ -0,0 +1,53 @@
+import httpx
+import pytest
+
+from prompti.journal import RequestJournal
+from prompti.message import Message
+from prompti.model_client import ModelConfig, RunParams
+from prompti.model_client.openai_client import OpenAIClient
+
+
+def make_client(seen):
+    def handler(request):
+        prompt = request.read().decode()
+        seen.append(prompt)
+        if "bad" in prompt:
+            return httpx.Response(400, json={"error": {"message": "rejected"}})
+        return httpx.Response(200, json={"choices": [{"index": 0, "message": {"role": "assistant", "content": "ok"}}]})
+
+    cfg = ModelConfig(provider="openai", model="gpt-4o-mini", api_key="k")
+    return OpenAIClient(cfg, client=httpx.AsyncClient(transport=httpx.MockTransport(handler)))
+
+
+def requests(*prompts):
+    return [RunParams(messages=[Message.create_user(p)]) for p in prompts]
+
+
+@pytest.mark.asyncio
+async def test_resumes_after_a_crash_without_resending_completed_requests(tmp_path):
+    path = tmp_path / "job.sqlite"
+    seen = []
+    with RequestJournal(path) as journal:
+        with pytest.raises(KeyboardInterrupt):
+            async for _ in journal.arun(make_client(seen), requests("one", "two", "three", "four")):
+                if "two" in seen[-1]:
+                    raise KeyboardInterrupt  # the job dies after the second request
+        assert journal.counts() == {"completed": 2}
+
+    seen.clear()
+    with RequestJournal(path) as journal:
+        entries = [e async for e in journal.arun(make_client(seen), requests("one", "two", "three", "four"))]
+        assert [e.resumed for e in entries] == [True, True, False, False]
+        assert all(e.response.get_text_content() == "ok" for e in entries)
+        assert len(seen) == 2 and "three" in seen[0] and "four" in seen[1]
+        assert journal.counts() == {"completed": 4}
+
+
+@pytest.mark.asyncio
+async def test_error_responses_are_marked_failed_and_listed_as_pending():
+    with RequestJournal(":memory:") as journal:
+        [entry] = [e async for e in journal.arun(make_client([]), requests("bad"))]
+        assert entry.response.error
+        assert journal.status(entry.key) == "failed"
+        assert journal.response(entry.key) is None
+        assert [p.messages[0].content for p in journal.pending()] == ["bad"]
@@ -0,0 +1,77 @@
+from unittest.mock import patch
+
+import pytest
+
+from prompti.engine import PromptEngine
+from prompti.langdetect import detect_language
+from prompti.message import Message, ModelResponse
+from prompti.model_client import ModelClient, ModelConfig, RunParams
+from prompti.telemetry import get_metrics
+from prompti.template import PromptTemplate, Variant
+
+
+@pytest.mark.parametrize(
+    "text,lang",
+    [
+        ("你好，请帮我查一下订单", "zh"),
+        ("帮我看下 order 12345 的 status", "zh"),
+        ("こんにちは、注文はどこですか", "ja"),
+        ("주문이 어디에 있나요", "ko"),
+        ("Где мой заказ?", "ru"),
+        ("Where is my order?", "en"),
+        ("¿Dónde está mi pedido?", "es"),
+        ("Wo ist meine Bestellung? Ich sehe sie nicht", "de"),
+        ("Hello", None),
+        ("12345 !", None),
+    ],
+)
+def test_detect_language(text, lang):
+    assert detect_language(text) == lang
+
+
+class EchoModel(ModelClient):
+    async def _run(self, params: RunParams):
+        message = Message(role="assistant", content=self.cfg.model)
+        yield ModelResponse(choices=[{"index": 0, "message": message, "finish_reason": "stop"}])
+
+
+def support_template():
+    messages = [{"role": "user", "content": "{{ question }}"}]
+    return PromptTemplate(
+        id="support",
+        name="support",
+        version="1",
+        variants={
+            "zh": Variant(
+                selector=["zh"], model_cfg=ModelConfig(provider="dummy", model="zh-model"), messages=messages
+            ),
+            "default": Variant(model_cfg=ModelConfig(provider="dummy", model="general"), messages=messages),
+        },
+    )
+
+
+async def answer(engine, **kwargs):
+    with patch("prompti.engine.create_client", side_effect=lambda cfg, **kw: EchoModel(cfg)):
+        responses = [r async for r in engine.acompletion("support", template=support_template(), **kwargs)]
+    return responses[-1].get_text_content()
+
+
+@pytest.mark.asyncio
+async def test_engine_routes_variants_on_detected_language():
+    engine = PromptEngine([], language_detector=detect_language)
+    metric = get_metrics().language_requests.labels("dummy", "zh-model", "zh")
+    assert await answer(engine, variables={"question": "我的订单到哪了？"}) == "zh-model"
+    assert await answer(engine, variables={"question": "Where is my order?"}) == "general"
+    # an explicit lang in the selector context wins, and detection is off by default
+    assert await answer(engine, variables={"question": "我的订单"}, ctx={"lang": "en"}) == "general"
+    assert await answer(PromptEngine([]), variables={"question": "我的订单到哪了？"}) == "general"
+    if hasattr(metric, "_value"):
+        assert metric._value.get() >= 1
+
+
+@pytest.mark.asyncio
+async def test_engine_detects_language_of_last_user_message():
+    engine = PromptEngine([], language_detector=detect_language)
+    messages = [Message.create_user("Hi"), Message.create_assistant("Hello!")]
+    messages.append(Message.create_user("帮我取消订单"))
+    assert await answer(engine, variables={"question": ""}, messages=messages) == "zh-model"
@@ -0,0 +1,65 @@
+import base64
+import json
+
+import httpx
+import pytest
+
+from prompti.trace import LangfuseTraceService, TraceEvent
+
+
+def make_event():
+    return TraceEvent(
+        template_name="support_reply",
+        template_version="1.2.0",
+        variant="default",
+        model="gpt-4o",
+        request_id="req-1",
+        user_id="u-1",
+        conversation_id="c-1",
+        timestamp=1_700_000_000.0,
+        token_usage={"prompt_tokens": 10, "completion_tokens": 4, "total_tokens": 14},
+        perf_metrics={"first_package_latency": 0.2, "total_latency": 1.5},
+        llm_request_body={"messages": [{"role": "user", "content": "hi"}]},
+        llm_response_body={
+            "final_responses": [
+                {"choices": [{"index": 0, "delta": {"role": "assistant", "content": "Hel"}}]},
+                {"choices": [{"index": 0, "delta": {"role": "assistant", "content": "lo"}}]},
+            ]
+        },
+    )
+
+
+def test_payload_follows_langfuse_ingestion_schema():
+    service = LangfuseTraceService("pk-lf", "sk-lf", tags=["prod"])
+    batch = service._build_payload(make_event())["batch"]
+    trace, generation = batch
+    assert trace["type"] == "trace-create"
+    assert trace["body"]["id"] == "req-1"
+    assert trace["body"]["tags"] == ["prod", "template:support_reply", "variant:default"]
+    body = generation["body"]
+    assert generation["type"] == "generation-create"
+    assert body["traceId"] == "req-1"
+    assert body["output"] == {"role": "assistant", "content": "Hello"}
+    assert body["usage"] == {"input": 10, "output": 4, "total": 14, "unit": "TOKENS"}
+    assert body["startTime"] == "2023-11-14T22:13:20Z"
+    assert body["endTime"] == "2023-11-14T22:13:21.500000Z"
+    assert body["completionStartTime"] == "2023-11-14T22:13:20.200000Z"
+
+
+@pytest.mark.asyncio
+async def test_areport_posts_with_basic_auth():
+    seen = {}
+
+    def handler(request):
+        seen["url"] = str(request.url)
+        seen["auth"] = request.headers["authorization"]
+        seen["body"] = json.loads(request.content)
+        return httpx.Response(207, json={"successes": [], "errors": []})
+
+    service = LangfuseTraceService("pk-lf", "sk-lf", host="https://langfuse.example.com/")
+    service._http_client = httpx.AsyncClient(transport=httpx.MockTransport(handler), headers=service._headers())
+    assert await service.areport(make_event()) is True
+    await service.aclose()
+    assert seen["url"] == "https://langfuse.example.com/api/public/ingestion"
+    assert seen["auth"] == "Basic " + base64.b64encode(b"pk-lf:sk-lf").decode()
+    assert len(seen["body"]["batch"]) == 2
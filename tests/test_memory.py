@@ -0,0 +1,99 @@
+import pytest
+
+from prompti.memory import MEMORY_PREFIX, SummarizationError, SummarizingMemory, estimate_message_tokens
+from prompti.message import Message, ModelResponse
+from prompti.model_client import ModelClient, ModelConfig, RunParams
+from prompti.model_client.base import SyncModelClient
+
+
+def reply(text):
+    return ModelResponse(choices=[{"index": 0, "message": Message.create_assistant(text), "finish_reason": "stop"}])
+
+
+class Summarizer(ModelClient):
+    def __init__(self, summary="S"):
+        super().__init__(ModelConfig(provider="dummy", model="cheap"))
+        self.summary = summary
+        self.calls = []
+
+    async def _run(self, params: RunParams):
+        self.calls.append(params)
+        yield reply(self.summary)
+
+
+def conversation(turns):
+    messages = [Message.create_system("You are helpful.")]
+    for i in range(turns):
+        messages += [Message.create_user(f"question {i} " + "x" * 200), Message.create_assistant(f"answer {i}")]
+    return messages
+
+
+def test_estimate_message_tokens():
+    assert estimate_message_tokens([Message.create_user("abcdefgh")]) == 6
+
+
+@pytest.mark.asyncio
+async def test_under_budget_is_left_alone():
+    summarizer = Summarizer()
+    messages = conversation(2)
+    assert await SummarizingMemory(summarizer, max_tokens=10_000).acompact(messages) is messages
+    assert summarizer.calls == []
+
+
+@pytest.mark.asyncio
+async def test_oldest_turns_are_replaced_by_a_memory_message():
+    summarizer = Summarizer("the user asked 4 questions")
+    memory = SummarizingMemory(summarizer, max_tokens=200, keep_recent=2)
+    messages = conversation(6)
+    compacted = await memory.acompact(messages)
+    assert compacted[0] is messages[0]
+    assert compacted[1].role == "system"
+    assert compacted[1].content == MEMORY_PREFIX + "the user asked 4 questions"
+    assert compacted[2:] == messages[-4:]
+    [call] = summarizer.calls
+    transcript = call.messages[1].content
+    assert "question 0" in transcript and "answer 3" in transcript and "question 4" not in transcript
+    assert call.stream is False
+
+    # the next compaction folds the previous summary in
+    compacted += [Message.create_user("question 6 " + "y" * 400), Message.create_assistant("answer 6")]
+    again = await memory.acompact(compacted)
+    assert "(earlier summary) the user asked 4 questions" in summarizer.calls[-1].messages[1].content
+    assert [m.content for m in again[2:]] == [m.content for m in compacted[-4:]]
+    assert sum(m.content.startswith(MEMORY_PREFIX) for m in again) == 1
+
+
+@pytest.mark.asyncio
+async def test_tool_results_stay_with_their_turn():
+    call = {"id": "c1", "type": "function", "function": {"name": "f", "arguments": "{}"}}
+    messages = conversation(3) + [
+        Message.create_user("use the tool " + "z" * 300),
+        Message.create_tool_call([call]),
+        Message.create_tool_result("42", "c1"),
+        Message.create_assistant("it is 42"),
+    ]
+    compacted = await SummarizingMemory(Summarizer(), max_tokens=50, keep_recent=1).acompact(messages)
+    assert [m.role for m in compacted] == ["system", "system", "user", "assistant", "tool", "assistant"]
+
+
+@pytest.mark.asyncio
+async def test_errors_raise():
+    class Failing(Summarizer):
+        async def _run(self, params):
+            yield ModelResponse(error={"message": "boom"})
+
+    with pytest.raises(SummarizationError, match="boom"):
+        await SummarizingMemory(Failing(), max_tokens=10, keep_recent=1).acompact(conversation(3))
+    with pytest.raises(SummarizationError):
+        await SummarizingMemory(Summarizer(" "), max_tokens=10, keep_recent=1).acompact(conversation(3))
+
+
+def test_sync_compact():
+    class SyncSummarizer(SyncModelClient):
+        def _run(self, params):
+            yield reply("sync summary")
+
+    memory = SummarizingMemory(SyncSummarizer(ModelConfig(provider="dummy", model="cheap")), max_tokens=10)
+    compacted = memory.compact(conversation(6))
+    assert compacted[1].content == MEMORY_PREFIX + "sync summary"
+    assert len(compacted) == 2 + 2 * 4
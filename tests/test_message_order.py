@@ -0,0 +1,89 @@
+import pytest
+
+from prompti.message import Message
+from prompti.message_order import MessageOrderError, RoleRules, normalize_messages, rules_for
+from prompti.model_client.base import ModelClient, ModelConfig, RunParams
+
+STRICT = rules_for("anthropic")
+
+
+def call(call_id):
+    return {"id": call_id, "type": "function", "function": {"name": "f", "arguments": "{}"}}
+
+
+def test_valid_conversation_is_unchanged():
+    messages = [
+        Message.create_system("be brief"),
+        Message.create_user("hi"),
+        Message.create_tool_call([call("c1")]),
+        Message.create_tool_result("42", "c1"),
+        Message.create_assistant("done"),
+        Message.create_user("thanks"),
+    ]
+    assert normalize_messages(messages, STRICT) == messages
+
+
+def test_error_mode_lists_every_problem():
+    messages = [
+        Message.create_user("a"),
+        Message.create_user("b"),
+        Message.create_system("late"),
+        Message.create_tool_result("x", "nope"),
+    ]
+    with pytest.raises(MessageOrderError) as exc:
+        normalize_messages(messages, STRICT)
+    assert [issue.index for issue in exc.value.issues] == [1, 2, 3]
+    assert "message 3: tool result 'nope'" in str(exc.value)
+
+
+def test_fix_mode_moves_merges_and_drops():
+    messages = [
+        Message.create_user("a"),
+        Message.create_system("late"),
+        Message.create_user("b"),
+        Message.create_tool_call([call("c1"), call("c2")]),
+        Message.create_tool_result("1", "c1"),
+        Message.create_tool_result("orphan", "c9"),
+        Message.create_assistant("ok"),
+        Message.create_user("c"),
+    ]
+    fixed = normalize_messages(messages, STRICT, mode="fix")
+    assert [m.role for m in fixed] == ["system", "user", "assistant", "tool", "assistant", "user"]
+    assert fixed[1].content == "a\n\nb"
+    assert [c["id"] for c in fixed[2].tool_calls] == ["c1"]
+
+
+def test_unfixable_problems_raise_in_fix_mode():
+    with pytest.raises(MessageOrderError):
+        normalize_messages([Message.create_assistant("hi")], STRICT, mode="fix")
+    with pytest.raises(MessageOrderError):
+        normalize_messages([Message(role="robot", content="x")], RoleRules(), mode="fix")
+
+
+def test_trailing_assistant_allowed_with_prefill():
+    messages = [Message.create_user("q"), Message.create_assistant("{")]
+    assert normalize_messages(messages, STRICT, prefill=True) == messages
+    assert normalize_messages(messages, RoleRules()) == messages
+
+
+class EchoClient(ModelClient):
+    provider = "anthropic"
+
+    async def _run(self, params):
+        self.sent = params.messages
+        return
+        yield
+
+
+@pytest.mark.asyncio
+async def test_client_applies_configured_normalization():
+    client = EchoClient(ModelConfig(provider="anthropic", model="m", message_normalization="fix"))
+    params = RunParams(messages=[Message.create_user("a"), Message.create_user("b")])
+    async for _ in client.arun(params):
+        pass
+    assert [m.content for m in client.sent] == ["a\n\nb"]
+
+    client.cfg.message_normalization = "error"
+    with pytest.raises(MessageOrderError):
+        async for _ in client.arun(params):
+            pass
@@ -0,0 +1,159 @@
+import json
+from typing import Literal
+
+import pytest
+from pydantic import BaseModel
+
+from prompti.message import Message, StreamingChoice, StreamingModelResponse
+from prompti.partial_json import (
+    PartialJSONParser,
+    ToolCallStream,
+    astream_json,
+    astream_tool_calls,
+    parse_partial_json,
+    partial_model,
+)
+
+
+@pytest.mark.parametrize(
+    "text,expected",
+    [
+        ("", None),
+        ('{"title": "Hel', {"title": "Hel"}),
+        ('{"title": "Hello", "count": 1', {"title": "Hello"}),
+        ('{"title": "Hello", "count": 12,', {"title": "Hello", "count": 12}),
+        ('{"tags": ["a", "b', {"tags": ["a", "b"]}),
+        ('{"ok": tr', {}),
+        ('{"ok": true, "note": "line\\', {"ok": True, "note": "line"}),
+        ('{"e": "caf\\u00e', {"e": "caf"}),
+        ('{"e": "\\ud83d\\ude00!"}', {"e": "\U0001f600!"}),
+        ('{"e": "a\\ud83d', {"e": "a"}),
+        ('{"e": "a\\ud83d\\ude', {"e": "a"}),
+        ('{"nested": {"x": null}}', {"nested": {"x": None}}),
+    ],
+)
+def test_parse_partial_json(text, expected):
+    assert parse_partial_json(text)[0] == expected
+
+
+def test_surrogate_pairs_decode_like_json_loads():
+    text = '"\\ud83d\\ude00 \\u00e9"'
+    assert parse_partial_json(text) == (json.loads(text), True)
+    assert parse_partial_json(text)[0].encode("utf-8") == "\U0001f600 \u00e9".encode("utf-8")
+
+
+def test_finish_completes_a_trailing_number():
+    parser = PartialJSONParser()
+    assert parser.feed("4").value is None
+    assert not parser.feed("2").complete
+    final = parser.finish()
+    assert (final.value, final.complete, final.completed) == (42, True, [()])
+
+    truncated = PartialJSONParser()
+    truncated.feed("[tru")
+    with pytest.raises(ValueError):
+        truncated.finish()
+
+
+def test_invalid_prefix_raises():
+    with pytest.raises(ValueError):
+        parse_partial_json('{"a": x')
+
+
+def test_parser_reports_fields_as_they_complete():
+    parser = PartialJSONParser()
+    first = parser.feed('{"name": "Ada", "skills": ["ma')
+    assert first.completed == [("name",)]
+    second = parser.feed('th", "code"], "age": 36}')
+    assert second.complete
+    assert second.completed == [("skills", 0), ("skills", 1), ("skills",), ("age",), ()]
+    assert second.value == {"name": "Ada", "skills": ["math", "code"], "age": 36}
+
+
+class Person(BaseModel):
+    name: str
+    age: int
+
+
+def chunk(text):
+    return StreamingModelResponse(choices=[StreamingChoice(index=0, delta=Message(role="assistant", content=text))])
+
+
+@pytest.mark.asyncio
+async def test_astream_json_validates_final_value():
+    async def responses():
+        for text in ['{"name": "A', 'da", "ag', 'e": 36}']:
+            yield chunk(text)
+
+    snapshots = [s async for s in astream_json(responses(), model=Person)]
+    assert [s.value for s in snapshots] == [{"name": "A"}, {"name": "Ada"}, {"name": "Ada", "age": 36}]
+    assert snapshots[-1].parsed == Person(name="Ada", age=36)
+
+
+@pytest.mark.asyncio
+async def test_astream_json_finishes_a_top_level_number():
+    async def responses():
+        for text in ["4", "2"]:
+            yield chunk(text)
+
+    snapshots = [s async for s in astream_json(responses())]
+    assert [(s.value, s.complete) for s in snapshots] == [(None, False), (None, False), (42, True)]
+
+
+class Filter(BaseModel):
+    field: str
+    value: str
+
+
+class SearchArgs(BaseModel):
+    query: str
+    order: Literal["asc", "desc"] = "desc"
+    filters: list[Filter] = []
+
+
+def tool_chunk(index, arguments, name=None, call_id=None):
+    delta = {"index": index, "function": {"arguments": arguments}}
+    if name:
+        delta["function"]["name"] = name
+    if call_id:
+        delta["id"] = call_id
+    message = Message(role="assistant", content=None, tool_calls=[delta])
+    return StreamingModelResponse(choices=[StreamingChoice(index=0, delta=message)])
+
+
+@pytest.mark.asyncio
+async def test_astream_tool_calls_yields_typed_partial_arguments():
+    async def responses():
+        yield tool_chunk(0, "", name="search", call_id="c1")
+        yield tool_chunk(0, '{"query": "rust as')
+        yield tool_chunk(0, 'ync", "order": "as')
+        yield tool_chunk(0, 'c", "filters": [{"field": "la')
+        yield tool_chunk(1, '{"city": "Par', name="weather", call_id="c2")
+        yield tool_chunk(0, 'ng", "value": "en"}]}')
+
+    snapshots = [s async for s in astream_tool_calls(responses(), models={"search": SearchArgs})]
+    search = [s for s in snapshots if s.index == 0]
+    queries = [s.partial.query if s.partial else None for s in search]
+    assert queries == [None, "rust as", "rust async", "rust async", "rust async"]
+    # "as" is not a valid order yet, the field is left out until it completes
+    assert search[2].partial.order is None
+    assert search[3].partial.order == "asc"
+    assert search[3].partial.filters[0].field == "la" and search[3].partial.filters[0].value is None
+    assert search[-1].parsed == SearchArgs(query="rust async", order="asc", filters=[Filter(field="lang", value="en")])
+    weather = [s for s in snapshots if s.index == 1]
+    assert (weather[0].id, weather[0].name, weather[0].arguments) == ("c2", "weather", {"city": "Par"})
+    assert weather[0].partial is None and not weather[0].complete
+
+
+def test_tool_call_stream_accumulates_calls():
+    stream = ToolCallStream()
+    stream.feed([{"index": 0, "id": "c1", "function": {"name": "get_", "arguments": '{"a"'}}])
+    [snapshot] = stream.feed([{"index": 0, "function": {"name": "time", "arguments": ": 1}"}}])
+    assert (snapshot.name, snapshot.arguments, snapshot.complete) == ("get_time", {"a": 1}, True)
+    assert stream.calls[0]["function"]["arguments"] == '{"a": 1}'
+
+
+def test_partial_model_is_cached_and_optional():
+    partial = partial_model(SearchArgs)
+    assert partial is partial_model(SearchArgs)
+    assert partial().query is None
@@ -0,0 +1,182 @@
+import json
+
+import httpx
+import pytest
+
+from prompti.message import Message
+from prompti.model_client.base import ModelConfig, RunParams
+from prompti.model_client.openai_client import OpenAIClient, SyncOpenAIClient
+from prompti.postprocess import _REGISTRY, PostprocessChain, Postprocessor, estimate_tokens, register_postprocessor
+
+CASES = [
+    (["normalize_newlines"], "a\r\nb\rc\r", "a\nb\nc\n"),
+    (["strip_thinking"], "<think>plan\nsteps</think>answer <b>", "answer <b>"),
+    (["strip_thinking"], "before<think>unclosed", "before"),
+    (["strip_fences"], '```json\n{"a": 1}\n```\n', '{"a": 1}'),
+    (["strip_fences"], "no fence `here`", "no fence `here`"),
+    (["strip_fences"], "``x``", "``x``"),
+    (["trim"], "  \n hello \n world \n ", "hello \n world"),
+    (
+        ["normalize_newlines", "strip_thinking", "strip_fences", "trim"],
+        "<think>hm</think>\r\n```python\r\nprint(1)\r\n```\r\n",
+        "print(1)",
+    ),
+    (
+        ["repair_json"],
+        'Here you go:\n```json\n{"a": [1, 2,], "b": "x}\\"",\n}\n```\nAnything else?',
+        '{"a": [1, 2], "b": "x}\\""}',
+    ),
+    (["repair_json"], '[{"k": "cut \\', '[{"k": "cut \\\\"}]'),
+    (["repair_json"], '{"a": {"b":', '{"a": {"b":null}}'),
+    (["repair_json"], "no JSON at all", "no JSON at all"),
+]
+
+
+@pytest.mark.parametrize("names,text,expected", CASES)
+def test_streamed_output_matches_complete_output_for_every_split(names, text, expected):
+    assert PostprocessChain(names).process(text) == expected
+    for split in range(len(text) + 1):
+        chain = PostprocessChain(names)
+        assert chain.feed(text[:split]) + chain.feed(text[split:]) + chain.flush() == expected
+    chain = PostprocessChain(names)
+    assert "".join(chain.feed(ch) for ch in text) + chain.flush() == expected
+
+
+def test_text_is_released_as_soon_as_it_is_decided():
+    chain = PostprocessChain(["strip_thinking", "trim"])
+    assert chain.feed("<think>x</th") == ""
+    assert chain.feed("ink> Hello ") == "Hello"
+    assert chain.feed("world") == " world"
+
+
+def test_unknown_and_custom_postprocessors():
+    with pytest.raises(ValueError, match="Unknown postprocessors: shout"):
+        PostprocessChain(["shout"])
+
+    class Upper(Postprocessor):
+        def feed(self, text):
+            return text.upper()
+
+    register_postprocessor("shout", Upper)
+    try:
+        assert PostprocessChain(["trim", "shout"]).process(" hi ") == "HI"
+    finally:
+        _REGISTRY.pop("shout")
+
+
+def sse(deltas):
+    events = [{"choices": [{"index": 0, "delta": {"content": d}}]} for d in deltas]
+    events.append({"choices": [{"index": 0, "delta": {}, "finish_reason": "stop"}]})
+    return ("".join(f"data: {json.dumps(e)}\n\n" for e in events) + "data: [DONE]\n\n").encode()
+
+
+DELTAS = ["<thi", "nk>let me see</think>\n\n```", "json\n{\"ok\":", " true}\n``", "`\n"]
+PARAMS = dict(messages=[Message.create_user("q")], postprocessors=["strip_thinking", "strip_fences", "trim"])
+
+
+def handler(request):
+    if json.loads(request.content)["stream"]:
+        return httpx.Response(200, content=sse(DELTAS))
+    message = {"role": "assistant", "content": "".join(DELTAS)}
+    return httpx.Response(200, json={"choices": [{"index": 0, "message": message, "finish_reason": "stop"}]})
+
+
+@pytest.mark.asyncio
+async def test_client_applies_postprocessors_in_both_modes():
+    http = httpx.AsyncClient(transport=httpx.MockTransport(handler))
+    client = OpenAIClient(ModelConfig(provider="openai", model="m"), client=http)
+    streamed = [r async for r in client.arun(RunParams(**PARAMS))]
+    assert "".join(r.get_text_content() or "" for r in streamed) == '{"ok": true}'
+    [complete] = [r async for r in client.arun(RunParams(stream=False, **PARAMS))]
+    assert complete.get_text_content() == '{"ok": true}'
+
+
+def test_sync_client_flushes_held_text_when_stream_ends_without_finish_reason():
+    body = "".join(
+        f"data: {json.dumps({'choices': [{'index': 0, 'delta': {'content': d}}]})}\n\n" for d in ["a\r", "b\r"]
+    )
+
+    def truncated(request):
+        return httpx.Response(200, content=(body + "data: [DONE]\n\n").encode())
+
+    client = SyncOpenAIClient(
+        ModelConfig(provider="openai", model="m"), client=httpx.Client(transport=httpx.MockTransport(truncated))
+    )
+    params = RunParams(messages=[Message.create_user("q")], postprocessors=["normalize_newlines"])
+    assert "".join(r.get_text_content() or "" for r in client.run(params)) == "a\nb\n"
+
+
+def test_stop_at_cuts_at_the_earliest_stop_across_splits():
+    text = "one two END three STOP"
+    for split in range(len(text) + 1):
+        chain = PostprocessChain([], stop=["STOP", "END"])
+        assert chain.feed(text[:split]) + chain.feed(text[split:]) + chain.flush() == "one two "
+        assert chain.finish_reason == "stop"
+
+
+def test_emulated_stop_truncates_and_aborts_the_stream():
+    sent = []
+
+    def chunks():
+        for delta in ["Hello wor", "ld\n\nObserv", "ation: ignored", " more", " and more"]:
+            sent.append(delta)
+            yield f"data: {json.dumps({'choices': [{'index': 0, 'delta': {'content': delta}}]})}\n\n".encode()
+        yield b"data: [DONE]\n\n"
+
+    def stream_handler(request):
+        return httpx.Response(200, content=chunks(), headers={"content-type": "text/event-stream"})
+
+    cfg = ModelConfig(provider="openai", model="m", emulate_stop=True)
+    client = SyncOpenAIClient(cfg, client=httpx.Client(transport=httpx.MockTransport(stream_handler)))
+    params = RunParams(messages=[Message.create_user("q")], stop=["\nObservation:"])
+    responses = list(client.run(params))
+    assert "".join(r.get_text_content() or "" for r in responses) == "Hello world\n"
+    assert responses[-1].get_finish_reason() == "stop"
+    assert len(sent) == 3
+
+    # without emulation the provider's output is passed through untouched
+    sent.clear()
+    plain = SyncOpenAIClient(
+        ModelConfig(provider="openai", model="m"), client=httpx.Client(transport=httpx.MockTransport(stream_handler))
+    )
+    assert "Observation" in "".join(r.get_text_content() or "" for r in plain.run(params))
+
+
+@pytest.mark.asyncio
+async def test_emulated_stop_for_complete_responses():
+    http = httpx.AsyncClient(transport=httpx.MockTransport(handler))
+    client = OpenAIClient(ModelConfig(provider="openai", model="m", emulate_stop=True), client=http)
+    params = RunParams(messages=[Message.create_user("q")], stream=False, stop="```")
+    [response] = [r async for r in client.arun(params)]
+    assert response.get_text_content() == "<think>let me see</think>\n\n"
+    assert response.get_finish_reason() == "stop"
+
+
+def test_length_limit_counts_chars_and_estimated_tokens():
+    assert estimate_tokens("abcdefgh") == 2
+    assert estimate_tokens("你好ab") == 3
+    chain = PostprocessChain([], max_chars=5)
+    assert chain.feed("abc") + chain.feed("defg") == "abcde"
+    assert chain.finish_reason == "length"
+    assert chain.feed("more") == ""
+    assert PostprocessChain([], max_tokens=2).process("你好世界") == "你好"
+    assert PostprocessChain([], max_tokens=2).process("abcdefghij") == "abcdefgh"
+
+
+def test_max_output_chars_aborts_a_runaway_stream():
+    sent = []
+
+    def chunks():
+        for _ in range(1000):
+            sent.append(1)
+            yield f"data: {json.dumps({'choices': [{'index': 0, 'delta': {'content': 'la '}}]})}\n\n".encode()
+
+    def runaway(request):
+        return httpx.Response(200, content=chunks(), headers={"content-type": "text/event-stream"})
+
+    cfg = ModelConfig(provider="openai", model="m", max_output_chars=10)
+    client = SyncOpenAIClient(cfg, client=httpx.Client(transport=httpx.MockTransport(runaway)))
+    responses = list(client.run(RunParams(messages=[Message.create_user("sing")])))
+    assert "".join(r.get_text_content() or "" for r in responses) == "la la la l"
+    assert responses[-1].get_finish_reason() == "length"
+    assert len(sent) == 4
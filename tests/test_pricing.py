@@ -0,0 +1,69 @@
+import base64
+import json
+
+import httpx
+import pytest
+from cryptography.hazmat.primitives.asymmetric.ed25519 import Ed25519PrivateKey
+from cryptography.hazmat.primitives.serialization import Encoding, PublicFormat
+
+from prompti.message import Usage
+from prompti.pricing import PriceTable, PriceTableError, arefresh_prices, get_price_table, set_price_table
+
+
+@pytest.fixture(autouse=True)
+def restore_prices():
+    yield
+    set_price_table(None)
+
+
+def test_bundled_table_lookup_and_cost():
+    table = PriceTable.bundled()
+    assert table.get("gpt-4o", provider="openai").input == 2.5
+    # dated snapshots and gateway prefixes use the base model's price
+    assert table.get("claude-3-5-sonnet-20241022") == table.models["anthropic/claude-3-5-sonnet"]
+    assert table.get("gpt-4o-mini-2024-07-18") == table.models["openai/gpt-4o-mini"]
+    assert table.get("litellm_proxy/qwen-max").output == 6.4
+    assert table.get("unknown-model") is None
+    usage = Usage(prompt_tokens=1_000_000, completion_tokens=500_000, total_tokens=1_500_000)
+    assert table.cost("gpt-4o", usage, provider="openai") == pytest.approx(7.5)
+    assert table.cost("gpt-4o", None) is None
+
+
+def signed(document):
+    key = Ed25519PrivateKey.generate()
+    public = key.public_key().public_bytes(Encoding.Raw, PublicFormat.Raw)
+    body = json.dumps(document).encode()
+    return body, base64.b64encode(key.sign(body)), base64.b64encode(public).decode()
+
+
+def serve(body, signature):
+    def handler(request):
+        if request.url.path.endswith(".sig"):
+            return httpx.Response(200, content=signature)
+        return httpx.Response(200, content=body)
+
+    return httpx.AsyncClient(transport=httpx.MockTransport(handler))
+
+
+@pytest.mark.asyncio
+async def test_refresh_merges_verified_remote_table():
+    document = {"updated": "2026-10-01", "models": {"openai/gpt-4o": {"input": 2, "output": 8}}}
+    body, signature, public_key = signed(document)
+    table = await arefresh_prices("https://p/prices.json", public_key=public_key, client=serve(body, signature))
+    assert get_price_table() is table
+    assert table.updated == "2026-10-01"
+    assert table.get("gpt-4o").output == 8
+    assert table.get("claude-opus-4").input == 15.0  # bundled entries are kept
+
+
+@pytest.mark.asyncio
+async def test_refresh_rejects_bad_signature_and_keeps_active_table():
+    before = get_price_table()
+    body, _, public_key = signed({"models": {}})
+    _, other_signature, _ = signed({"models": {}})
+    with pytest.raises(PriceTableError, match="signature is invalid"):
+        await arefresh_prices("https://p/x.json", public_key=public_key, client=serve(body, other_signature))
+    body, signature, public_key = signed({"models": {"m": {"input": "free"}}})
+    with pytest.raises(PriceTableError, match="Invalid price table"):
+        await arefresh_prices("https://p/x.json", public_key=public_key, client=serve(body, signature))
+    assert get_price_table() is before
@@ -3,10 +3,12 @@
 import json
 from unittest.mock import AsyncMock, MagicMock
 
+import httpx
 import pytest
 
-from prompti.model_client import LiteLLMClient, Message, ModelConfig, RunParams
-from prompti.replay import ModelClientRecorder, ReplayEngine
+from prompti.message import Choice, ModelResponse
+from prompti.model_client import LiteLLMClient, Message, ModelClient, ModelConfig, RunParams
+from prompti.replay import ModelClientRecorder, ReplayClient, ReplayEngine, ReplayError, ReplayMismatchError
 
 
 @pytest.mark.asyncio
@@ -48,3 +50,47 @@ async def test_record_and_replay(tmp_path):
     engine = ReplayEngine(factory)
     out = [m async for m in engine.replay(rows)]
     assert out[0].content == "pong"
+
+
+class EchoClient(ModelClient):
+    provider = "echo"
+
+    async def _run(self, params):
+        text = params.messages[-1].content
+        yield ModelResponse(choices=[Choice(index=0, message=Message(role="assistant", content=f"echo: {text}"))])
+
+
+async def record(tmp_path, *texts, temperature=None):
+    client = EchoClient(ModelConfig(provider="echo", model="m"), client=httpx.AsyncClient())
+    recorder = ModelClientRecorder(client, "sess", output_dir=tmp_path)
+    for text in texts:
+        [r async for r in recorder.run(RunParams(messages=[Message.create_user(text)], temperature=temperature))]
+    log_file = next(tmp_path.iterdir())
+    return [json.loads(line) for line in log_file.read_text().splitlines()]
+
+
+@pytest.mark.asyncio
+async def test_replay_client_serves_matching_requests(tmp_path):
+    rows = await record(tmp_path, "one", "two")
+    assert rows[0]["meta"]["request_hash"] == RunParams(messages=[Message.create_user("one")]).canonical_hash("m")
+    client = ReplayClient(rows, client=httpx.AsyncClient())
+    assert client.cfg.model == "m"
+    for text in ("one", "two"):
+        [response] = [r async for r in client.arun(RunParams(messages=[Message.create_user(text)], stream=True))]
+        assert response.get_text_content() == f"echo: {text}"
+    with pytest.raises(ReplayError, match="No recorded request"):
+        [r async for r in client.arun(RunParams(messages=[Message.create_user("three")]))]
+
+
+@pytest.mark.asyncio
+async def test_replay_client_rejects_drifted_prompt_with_diff(tmp_path):
+    rows = await record(tmp_path, "Summarize the report", temperature=0.2)
+    client = ReplayClient(rows, client=httpx.AsyncClient())
+    params = RunParams(messages=[Message.create_user("Summarise the report")], temperature=0.2)
+    with pytest.raises(ReplayMismatchError) as info:
+        [r async for r in client.arun(params)]
+    assert info.value.index == 0
+    assert info.value.incoming_hash == params.canonical_hash("m")
+    assert '-      "content": "Summarize the report",' in info.value.diff
+    assert '+      "content": "Summarise the report",' in info.value.diff
+    assert "temperature" not in info.value.diff
@@ -0,0 +1,49 @@
+import pytest
+
+from prompti.message import Message
+from prompti.model_client.base import ModelClient, ModelConfig, RunParams
+from prompti.roles import default_role_map, map_roles
+
+
+@pytest.mark.parametrize(
+    "model,expected",
+    [
+        ("o3-mini", {"system": "developer"}),
+        ("openai/gpt-5", {"system": "developer"}),
+        ("gpt-4o", {"developer": "system"}),
+        (None, {"developer": "system"}),
+    ],
+)
+def test_default_role_map(model, expected):
+    assert default_role_map(model) == expected
+
+
+def test_map_roles_only_copies_when_needed():
+    messages = [Message.create_user("q")]
+    assert map_roles(messages, {"system": "developer"}) is messages
+    mapped = map_roles([Message.create_system("s"), *messages], {"system": "developer"})
+    assert [m.role for m in mapped] == ["developer", "user"]
+
+
+class RecordingClient(ModelClient):
+    provider = "recording"
+
+    async def _run(self, params):
+        self.roles = [m.role for m in params.messages]
+        return
+        yield
+
+
+async def sent_roles(cfg):
+    client = RecordingClient(cfg)
+    params = RunParams(messages=[Message.create_system("s"), Message.create_developer("d"), Message.create_user("q")])
+    async for _ in client.arun(params):
+        pass
+    return client.roles
+
+
+@pytest.mark.asyncio
+async def test_client_translates_roles_per_model():
+    assert await sent_roles(ModelConfig(model="o4-mini")) == ["developer", "developer", "user"]
+    assert await sent_roles(ModelConfig(model="gpt-4o")) == ["system", "system", "user"]
+    assert await sent_roles(ModelConfig(model="gpt-4o", role_map={})) == ["system", "developer", "user"]
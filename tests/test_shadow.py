@@ -0,0 +1,84 @@
+import asyncio
+
+import pytest
+
+from prompti.message import Message, ModelResponse, Usage
+from prompti.model_client import ModelClient, ModelConfig, RunParams
+from prompti.shadow import ShadowClient
+
+
+class FixedModel(ModelClient):
+    def __init__(self, cfg, text, delay=0.0, fail=False):
+        super().__init__(cfg)
+        self.text = text
+        self.delay = delay
+        self.fail = fail
+        self.calls = 0
+
+    async def _run(self, params: RunParams):
+        self.calls += 1
+        await asyncio.sleep(self.delay)
+        if self.fail:
+            raise RuntimeError("shadow down")
+        message = Message(role="assistant", content=self.text)
+        usage = Usage(prompt_tokens=100, completion_tokens=10, total_tokens=110)
+        yield ModelResponse(choices=[{"index": 0, "message": message, "finish_reason": "stop"}], usage=usage)
+
+
+def params():
+    return RunParams(messages=[Message.create_user("hi")], stream=False)
+
+
+def make(primary_text="hello world", shadow_text="hello there", **kwargs):
+    primary = FixedModel(ModelConfig(provider="dummy", model="old"), primary_text)
+    shadow = FixedModel(ModelConfig(provider="dummy", model="new"), shadow_text, **kwargs)
+    return primary, shadow
+
+
+@pytest.mark.asyncio
+async def test_shadow_compares_without_delaying_primary():
+    primary, shadow = make(delay=0.05)
+    seen = []
+    client = ShadowClient(primary, shadow, sample_rate=1, prices={"new": (1.0, 2.0)}, on_compare=seen.append)
+
+    responses = [r async for r in client.arun(params())]
+    assert [r.get_text_content() for r in responses] == ["hello world"]
+    assert seen == []  # the shadow is still running
+
+    await client.aclose()
+    (comparison,) = seen
+    assert comparison.primary.model == "old" and comparison.shadow.model == "new"
+    assert comparison.shadow.text == "hello there"
+    assert comparison.shadow.latency_s >= 0.05
+    assert comparison.primary.cost is None
+    assert comparison.shadow.cost == pytest.approx((100 * 1.0 + 10 * 2.0) / 1_000_000)
+    assert 0 < comparison.similarity < 1
+    assert "-hello world\n+hello there" in comparison.diff
+
+
+@pytest.mark.asyncio
+async def test_shadow_errors_never_reach_the_caller():
+    primary, shadow = make(fail=True)
+    seen = []
+    client = ShadowClient(primary, shadow, sample_rate=1, on_compare=seen.append)
+
+    responses = [r async for r in client.arun(params())]
+    await client.drain()
+    assert responses[-1].get_text_content() == "hello world"
+    assert seen[0].shadow.error == "RuntimeError: shadow down"
+    assert seen[0].similarity is None
+
+
+@pytest.mark.asyncio
+async def test_unsampled_requests_are_not_mirrored():
+    primary, shadow = make()
+    client = ShadowClient(primary, shadow, sample_rate=0)
+    _ = [r async for r in client.arun(params())]
+    await client.drain()
+    assert (primary.calls, shadow.calls) == (1, 0)
+
+
+def test_sample_rate_is_validated():
+    primary, shadow = make()
+    with pytest.raises(ValueError):
+        ShadowClient(primary, shadow, sample_rate=5)
@@ -0,0 +1,347 @@
+import asyncio
+import io
+import json
+import time
+
+import pytest
+
+from prompti.message import (
+    Choice,
+    Message,
+    ModelResponse,
+    StreamingChoice,
+    StreamingModelResponse,
+    Timing,
+    ToolCallDelta,
+    Usage,
+)
+from prompti.streaming import (
+    HEARTBEAT,
+    SharedChatStream,
+    StreamError,
+    ToolCallAccumulator,
+    acollect,
+    apaced,
+    apipe_to,
+    asse_stream,
+    collect,
+    pipe_to,
+)
+
+
+def chunk(text=None, error=None):
+    return StreamingModelResponse(
+        choices=[StreamingChoice(index=0, delta=Message(role="assistant", content=text))], error=error
+    )
+
+
+async def responses(*items):
+    for item in items:
+        yield item
+
+
+class AsyncWriter:
+    def __init__(self):
+        self.parts = []
+        self.flushes = 0
+
+    async def write(self, data):
+        self.parts.append(data)
+
+    async def flush(self):
+        self.flushes += 1
+
+
+@pytest.mark.asyncio
+async def test_apipe_to_async_writer_flushes_per_chunk():
+    writer = AsyncWriter()
+    text = await apipe_to(responses(chunk("Hel"), chunk(None), chunk("lo")), writer, flush=True)
+    assert text == "Hello"
+    assert writer.parts == ["Hel", "lo"]
+    assert writer.flushes == 2
+
+
+@pytest.mark.asyncio
+async def test_apipe_to_stream_writer_sends_utf8():
+    received = asyncio.get_running_loop().create_future()
+
+    async def serve(reader, writer):
+        received.set_result(await reader.read())
+        writer.close()
+
+    server = await asyncio.start_server(serve, "127.0.0.1", 0)
+    port = server.sockets[0].getsockname()[1]
+    _, writer = await asyncio.open_connection("127.0.0.1", port)
+    await apipe_to(responses(chunk("你好"), chunk(" 👋")), writer)
+    writer.close()
+    assert await received == "你好 👋".encode()
+    server.close()
+    await server.wait_closed()
+
+
+@pytest.mark.asyncio
+async def test_apipe_to_raises_on_error_after_writing_earlier_text():
+    writer = AsyncWriter()
+    with pytest.raises(StreamError) as info:
+        await apipe_to(responses(chunk("partial"), chunk(error={"message": "boom"})), writer)
+    assert info.value.text == "partial"
+    assert writer.parts == ["partial"]
+
+
+def test_pipe_to_sync_file():
+    out = io.BytesIO()
+    assert pipe_to([chunk("a"), chunk("é")], out, encoding="utf-8") == "aé"
+    assert out.getvalue() == "aé".encode()
+
+
+def event_types(frames):
+    return [f.split("\n")[0].removeprefix("event: ") if f != HEARTBEAT else "heartbeat" for f in frames]
+
+
+@pytest.mark.asyncio
+async def test_asse_stream_sends_heartbeats_while_upstream_is_silent():
+    async def slow():
+        yield chunk("a")
+        await asyncio.sleep(0.05)
+        yield chunk("b")
+
+    frames = [f async for f in asse_stream(slow(), heartbeat_s=0.02)]
+    types = event_types(frames)
+    assert types[0] == "content_delta" and types[-2:] == ["content_delta", "done"]
+    assert "heartbeat" in types
+    assert json.loads(frames[-2].split("data: ", 1)[1])["content"] == "b"
+
+
+@pytest.mark.asyncio
+async def test_asse_stream_turns_upstream_disconnects_into_error_events():
+    async def broken():
+        yield chunk("a")
+        raise ConnectionResetError("peer closed")
+
+    frames = [f async for f in asse_stream(broken())]
+    assert event_types(frames) == ["content_delta", "error", "done"]
+    error = json.loads(frames[1].split("data: ", 1)[1])["error"]
+    assert error["type"] == "upstream_disconnected" and "peer closed" in error["message"]
+
+
+@pytest.mark.asyncio
+async def test_asse_stream_closes_upstream_when_the_client_goes_away():
+    closed = []
+
+    async def endless():
+        try:
+            while True:
+                await asyncio.sleep(1)
+                yield chunk("x")
+        finally:
+            closed.append(True)
+
+    stream = asse_stream(endless(), heartbeat_s=0.01)
+    assert await anext(stream) == HEARTBEAT
+    await stream.aclose()
+    assert closed == [True]
+
+
+@pytest.mark.asyncio
+async def test_apaced_spreads_bursts_and_keeps_order():
+    async def bursty():
+        yield chunk("Hello, ")
+        yield chunk("world! How are you?")
+        tool = StreamingChoice(index=0, delta=Message(role="assistant", tool_calls=[{"index": 0, "id": "c1"}]))
+        yield StreamingModelResponse(choices=[tool])
+        await asyncio.sleep(0.1)
+        done = StreamingChoice(index=0, delta=Message(role="assistant", content="!"), finish_reason="stop")
+        yield StreamingModelResponse(choices=[done])
+
+    start = time.monotonic()
+    out = [r async for r in apaced(bursty(), chars_per_sec=400, tick_s=0.005)]
+    texts = [r.get_text_content() for r in out]
+    assert "".join(t or "" for t in texts) == "Hello, world! How are you?!"
+    assert len(out) > 6 and max(len(t or "") for t in texts) < 10
+    # the tool call follows the text that came before it, the finish reason stays on the last piece
+    tool_at = next(i for i, r in enumerate(out) if r.get_tool_calls())
+    assert "".join(t or "" for t in texts[:tool_at]) == "Hello, world! How are you?"
+    assert [r.get_finish_reason() for r in out].count("stop") == 1 and out[-1].get_finish_reason() == "stop"
+    assert time.monotonic() - start >= 0.05
+
+
+@pytest.mark.asyncio
+async def test_apaced_catches_up_when_the_stream_ends():
+    start = time.monotonic()
+    out = [r async for r in apaced(responses(chunk("x" * 200)), chars_per_sec=10, catch_up_s=0.1, tick_s=0.01)]
+    assert "".join(r.get_text_content() for r in out) == "x" * 200
+    assert len(out) > 1 and time.monotonic() - start < 1
+
+
+@pytest.mark.asyncio
+async def test_apaced_raises_upstream_errors_after_the_text():
+    async def broken():
+        yield chunk("partial answer")
+        raise ConnectionResetError("peer closed")
+
+    seen = []
+    with pytest.raises(ConnectionResetError):
+        async for response in apaced(broken(), chars_per_sec=1000, tick_s=0.005):
+            seen.append(response.get_text_content())
+    assert "".join(seen) == "partial answer"
+
+
+async def texts(subscriber):
+    return [r.get_text_content() async for r in subscriber]
+
+
+@pytest.mark.asyncio
+async def test_shared_stream_fans_out_and_replays_to_late_subscribers():
+    shared = SharedChatStream(responses(chunk("a"), chunk("b"), chunk("c")), max_lag=1)
+    first, second = shared.subscribe(), shared.subscribe()
+    assert await anext(first) is await anext(second)
+    live = shared.subscribe(replay=False)
+    late = shared.subscribe()
+    assert await asyncio.gather(texts(first), texts(second), texts(live), texts(late)) == [
+        ["b", "c"],
+        ["b", "c"],
+        ["b", "c"],
+        ["a", "b", "c"],
+    ]
+    # subscribing after the end still replays everything
+    assert await texts(shared.subscribe()) == ["a", "b", "c"]
+
+
+@pytest.mark.asyncio
+async def test_shared_stream_backpressure_follows_the_slowest_subscriber():
+    produced = []
+
+    async def upstream():
+        for i in range(10):
+            produced.append(i)
+            yield chunk(str(i))
+
+    shared = SharedChatStream(upstream(), max_lag=2)
+    fast, slow = shared.subscribe(), shared.subscribe()
+    fast_task = asyncio.ensure_future(texts(fast))
+    await anext(slow)
+    await asyncio.sleep(0.01)
+    # the slow subscriber read one chunk, so the upstream stopped two chunks ahead of it
+    assert len(produced) == 3
+    assert not fast_task.done()
+    # a subscriber that leaves no longer holds the stream back
+    await slow.aclose()
+    assert await fast_task == [str(i) for i in range(10)]
+
+
+@pytest.mark.asyncio
+async def test_shared_stream_raises_upstream_errors_in_every_subscriber():
+    async def upstream():
+        yield chunk("partial")
+        raise ConnectionError("reset")
+
+    shared = SharedChatStream(upstream())
+    subscribers = [shared.subscribe(), shared.subscribe()]
+    for subscriber in subscribers:
+        assert (await anext(subscriber)).get_text_content() == "partial"
+    for subscriber in subscribers:
+        with pytest.raises(ConnectionError, match="reset"):
+            await anext(subscriber)
+
+
+@pytest.mark.asyncio
+async def test_shared_stream_aclose_stops_the_upstream():
+    closed = asyncio.Event()
+
+    async def upstream():
+        try:
+            yield chunk("a")
+            await asyncio.sleep(60)
+            yield chunk("b")
+        finally:
+            closed.set()
+
+    async with SharedChatStream(upstream()) as shared:
+        subscriber = shared.subscribe()
+        assert (await anext(subscriber)).get_text_content() == "a"
+    assert closed.is_set()
+    assert await texts(subscriber) == []
+    assert [r.get_text_content() for r in shared.history] == ["a"]
+
+
+def tool_chunk(*tool_calls, finish_reason=None):
+    delta = Message(role="assistant", tool_calls=list(tool_calls) or None)
+    return StreamingModelResponse(choices=[StreamingChoice(index=0, delta=delta, finish_reason=finish_reason)])
+
+
+def test_tool_call_deltas_default_index_to_position():
+    deltas = ToolCallDelta.from_openai([{"function": {"arguments": "{}"}}, {"index": 5, "id": "b"}])
+    assert [(d.index, d.id) for d in deltas] == [(0, None), (5, "b")]
+    assert tool_chunk({"index": 1, "function": {"arguments": "{"}}).get_tool_call_deltas()[0].function.arguments == "{"
+
+
+def test_tool_call_accumulator_completes_calls_as_they_stream():
+    calls = ToolCallAccumulator()
+    signature = {"google": {"thought_signature": "sig"}}
+    first = {"index": 0, "id": "call_1", "type": "function", "function": {"name": "weather", "arguments": ""}}
+    assert calls.add(tool_chunk(first)) == []
+    assert calls.add(tool_chunk({"index": 0, "function": {"arguments": '{"city": '}})) == []
+    assert calls.add(tool_chunk({"index": 0, "function": {"arguments": '"Oslo"}'}, "extra_content": signature})) == []
+    second = {"index": 1, "id": "call_2", "function": {"name": "time", "arguments": "{}"}}
+    done = calls.add(tool_chunk(second))
+    assert [c.id for c in done] == ["call_1"]
+    assert done[0].parse_arguments() == {"city": "Oslo"}
+    assert done[0].to_openai() == {
+        "id": "call_1",
+        "type": "function",
+        "function": {"name": "weather", "arguments": '{"city": "Oslo"}'},
+        "extra_content": signature,
+    }
+
+    assert [c.function.name for c in calls.add(tool_chunk(finish_reason="tool_calls"))] == ["time"]
+    assert calls.finish() == []
+    assert [c.id for c in calls.calls] == ["call_1", "call_2"]
+
+
+def test_tool_call_accumulator_finish_and_complete_responses():
+    calls = ToolCallAccumulator()
+    calls.add(tool_chunk({"index": 0, "id": "a", "function": {"name": "f", "arguments": "{"}}))
+    assert [c.id for c in calls.finish()] == ["a"]
+
+    whole = Message.create_tool_call([{"id": "b", "type": "function", "function": {"name": "g", "arguments": ""}}])
+    response = ModelResponse(choices=[Choice(index=0, message=whole, finish_reason="tool_calls")])
+    done = ToolCallAccumulator().add(response)
+    assert [(c.id, c.parse_arguments()) for c in done] == [("b", {})]
+
+
+@pytest.mark.asyncio
+async def test_collect_aggregates_a_stream_into_a_complete_response():
+    usage = Usage(prompt_tokens=5, completion_tokens=7, total_tokens=12)
+    stream = [
+        StreamingModelResponse(
+            id="r1",
+            model="m",
+            choices=[StreamingChoice(index=0, delta=Message(role="assistant", reasoning_content="think"))],
+            timing=Timing(first_token_latency=0.1),
+        ),
+        chunk("It is "),
+        chunk("sunny."),
+        tool_chunk({"index": 0, "id": "call_1", "function": {"name": "weather", "arguments": '{"city"'}}),
+        tool_chunk({"index": 0, "function": {"arguments": ': "Oslo"}'}}, finish_reason="tool_calls"),
+        StreamingModelResponse(model="m", choices=[], usage=usage, timing=Timing(total_duration=1.5)),
+    ]
+    response = await acollect(responses(*stream))
+
+    assert isinstance(response, ModelResponse)
+    assert (response.id, response.model, response.usage, response.timing.total_duration) == ("r1", "m", usage, 1.5)
+    message = response.get_message()
+    assert (message.content, message.reasoning_content) == ("It is sunny.", "think")
+    assert message.tool_calls == [
+        {"id": "call_1", "type": "function", "function": {"name": "weather", "arguments": '{"city": "Oslo"}'}}
+    ]
+    assert response.choices[0].finish_reason == "tool_calls"
+
+
+def test_collect_keeps_complete_responses_and_errors():
+    choice = Choice(index=0, message=Message.create_assistant("hi"), finish_reason="stop")
+    complete = ModelResponse(id="r", choices=[choice])
+    assert collect([complete]).model_dump(exclude={"object"}) == complete.model_dump(exclude={"object"})
+
+    response = collect([chunk("partial"), chunk(error={"message": "boom"})])
+    assert response.get_text_content() == "partial"
+    assert response.error == {"message": "boom"}
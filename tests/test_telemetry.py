@@ -0,0 +1,49 @@
+import os
+import subprocess
+import sys
+import textwrap
+
+from prometheus_client import CollectorRegistry
+
+from prompti.telemetry import TelemetryConfig, configure_telemetry, get_metrics
+
+
+def test_configure_namespace_and_buckets():
+    registry = CollectorRegistry()
+    config = TelemetryConfig(namespace="myapp", latency_buckets=[0.5, 1, 30], token_buckets=[10, 100])
+    metrics = configure_telemetry(config, registry=registry)
+    try:
+        assert get_metrics() is metrics
+        assert metrics.config.namespace == "myapp"
+        # Reconfiguring replaces the registered collectors instead of colliding with them.
+        again = configure_telemetry(config, registry=registry)
+        assert get_metrics() is again
+    finally:
+        configure_telemetry(registry=CollectorRegistry())
+
+
+def test_minimal_install_without_metrics_and_tracing():
+    # Simulate an install without the ``metrics`` and ``tracing`` extras.
+    script = textwrap.dedent(
+        """
+        import sys
+        for name in ("prometheus_client", "opentelemetry", "opentelemetry.trace", "opentelemetry.baggage"):
+            sys.modules[name] = None
+
+        import httpx
+        from prompti import telemetry, _otel
+        from prompti.model_client import Message, ModelConfig, RunParams
+        from prompti.model_client.openai_client import SyncOpenAIClient
+
+        assert not telemetry.METRICS_ENABLED and not _otel.TRACING_ENABLED
+        body = {"choices": [{"index": 0, "message": {"role": "assistant", "content": "ok"}}]}
+        http = httpx.Client(transport=httpx.MockTransport(lambda request: httpx.Response(200, json=body)))
+        client = SyncOpenAIClient(ModelConfig(provider="openai", model="m"), client=http)
+        params = RunParams(messages=[Message.create_user("hi")], stream=False)
+        print([r.get_text_content() for r in client.run(params)])
+        """
+    )
+    env = {**os.environ, "PYTHONPATH": os.pathsep.join(sys.path)}
+    result = subprocess.run([sys.executable, "-c", script], capture_output=True, text=True, env=env)  # noqa: S603
+    assert result.returncode == 0, result.stderr
+    assert result.stdout.strip() == "['ok']"
@@ -0,0 +1,84 @@
+import pytest
+
+from prompti.message import Message
+from prompti.model_client.base import ModelConfig, RunParams, ToolParams, ToolSpec
+from prompti.model_client.openai_client import SyncOpenAIClient
+from prompti.testing import InvariantViolation, check_provider_invariants, snapshot_request
+
+
+def params(**kw):
+    return RunParams(messages=[Message.create_system("be brief"), Message.create_user("hi")], **kw)
+
+
+def test_openai_snapshot_is_the_wire_body():
+    tool = ToolSpec(name="lookup", description="Look up", parameters={"type": "object", "properties": {}})
+    call = params(temperature=0.1, tool_params=ToolParams(tools=[tool]), stream=False)
+    body = snapshot_request("openai", call, model="gpt-4o")
+    assert body == {
+        "model": "gpt-4o",
+        "messages": [{"role": "system", "content": "be brief"}, {"role": "user", "content": "hi"}],
+        "stream": False,
+        "temperature": 0.1,
+        "tools": [
+            {
+                "type": "function",
+                "function": {
+                    "name": "lookup",
+                    "description": "Look up",
+                    "parameters": {"type": "object", "properties": {}},
+                },
+            }
+        ],
+        "tool_choice": "auto",
+    }
+
+
+def test_snapshot_applies_role_mapping():
+    body = snapshot_request("openai", params(), model="o3")
+    assert [m["role"] for m in body["messages"]] == ["developer", "user"]
+
+
+def test_snapshot_redacts_credentials_in_the_body():
+    pytest.importorskip("litellm")
+    body = snapshot_request(ModelConfig(provider="litellm", model="gpt-4o", api_key="sk-secret"), params())
+    assert body["api_key"] == "[REDACTED]"
+
+
+def test_unknown_provider():
+    with pytest.raises(ValueError):
+        snapshot_request("nope", params())
+
+
+@pytest.mark.parametrize("provider", ["openai", "azure", "qianfan"])
+def test_providers_keep_translation_invariants(provider):
+    check_provider_invariants(provider, examples=100)
+
+
+def test_reasoning_models_keep_translation_invariants():
+    check_provider_invariants(ModelConfig(provider="openai", model="gpt-5", max_tokens=4096), examples=50)
+
+
+def broken(monkeypatch, change):
+    build = SyncOpenAIClient._build_request_data
+
+    def patched(self, params):
+        data = build(self, params)
+        change(data)
+        return data
+
+    monkeypatch.setattr(SyncOpenAIClient, "_build_request_data", patched)
+
+
+def test_dropped_messages_are_reported_with_a_shrunk_request(monkeypatch):
+    broken(monkeypatch, lambda data: data.update(messages=[m for m in data["messages"] if m["role"] != "tool"]))
+    with pytest.raises(InvariantViolation) as info:
+        check_provider_invariants("openai")
+    assert info.value.invariant == "no message loss"
+    # everything but one tool result is shrunk away
+    assert [m.role for m in info.value.params.messages] == ["tool"]
+
+
+def test_raised_token_limits_are_reported(monkeypatch):
+    broken(monkeypatch, lambda data: data.update(max_tokens=data["max_tokens"] * 2) if "max_tokens" in data else None)
+    with pytest.raises(InvariantViolation, match="token params within bounds"):
+        check_provider_invariants("openai")
@@ -0,0 +1,34 @@
+from prompti.message import Message
+from prompti.model_client.base import ModelConfig, RunParams
+from prompti.postprocess import PostprocessChain
+from prompti.testing import snapshot_request
+from prompti.textnorm import normalize_message_text, normalize_text
+
+
+def test_normalize_text():
+    text = "\ufeff\u201cDon\u2019t\u201d say\u00a0\u2018hi\u2019\u200b\u202fnow\u2060"
+    assert normalize_text(text) == "\"Don't\" say 'hi' now"
+
+
+def test_joiners_and_ideographic_space_are_kept():
+    family = "\U0001f469\u200d\U0001f469\u200d\U0001f467"
+    assert normalize_text(family) == family
+    assert normalize_text("你好\u3000世界") == "你好\u3000世界"
+
+
+def test_normalize_message_text_copies_only_changed_messages():
+    plain = Message.create_user("plain")
+    fancy = Message.create_user([{"type": "text", "text": "“x”"}, {"type": "image_url", "image_url": {"url": "u"}}])
+    messages = [plain, fancy]
+    result = normalize_message_text(messages)
+    assert result[0] is plain
+    assert result[1].content[0]["text"] == '"x"'
+    assert fancy.content[0]["text"] == "“x”"
+    assert normalize_message_text([plain]) == [plain]
+
+
+def test_output_postprocessor_and_prompt_option():
+    assert PostprocessChain(["normalize_text"]).feed("it’s ok") == "it's ok"
+    cfg = ModelConfig(provider="openai", model="gpt-4o", normalize_prompt_text=True)
+    body = snapshot_request(cfg, RunParams(messages=[Message.create_user("\u201cquoted\u201d\u200b")]))
+    assert body["messages"] == [{"role": "user", "content": '"quoted"'}]
@@ -0,0 +1,72 @@
+import json
+
+import pytest
+
+from prompti.message import Message
+from prompti.model_client.base import ModelConfig, RunParams
+from prompti.testing import snapshot_request
+from prompti.tool_content import json_part, to_anthropic_tool_result, to_openai_tool_messages
+
+PNG = "data:image/png;base64,iVBORw0KGgo="
+
+
+def conversation():
+    call = {"id": "c1", "type": "function", "function": {"name": "chart", "arguments": "{}"}}
+    return [
+        Message.create_user("plot it"),
+        Message.create_tool_call([call, {**call, "id": "c2"}]),
+        Message.create_tool_result(
+            [{"type": "text", "text": "rendered"}, {"type": "image_url", "image_url": {"url": PNG}}], "c1"
+        ),
+        Message.create_tool_result([json_part({"rows": 3, "ok": True})], "c2"),
+    ]
+
+
+def test_openai_tool_messages_are_text_and_images_follow_the_results():
+    messages = to_openai_tool_messages([m.to_openai() for m in conversation()])
+    assert [m["role"] for m in messages] == ["user", "assistant", "tool", "tool", "user"]
+    assert messages[2]["content"] == "rendered"
+    assert messages[3]["content"] == '{"rows": 3, "ok": true}'
+    assert messages[4]["content"][0]["text"] == "Images returned by tool call c1:"
+    assert messages[4]["content"][1]["image_url"]["url"] == PNG
+
+
+def test_openai_request_uses_translated_tool_messages():
+    data = snapshot_request("openai", RunParams(messages=conversation(), stream=False), model="gpt-4o")
+    assert [m["role"] for m in data["messages"]] == ["user", "assistant", "tool", "tool", "user"]
+
+
+def test_anthropic_tool_result_blocks():
+    image_result, json_result = conversation()[2:]
+    block = to_anthropic_tool_result(image_result)
+    assert block["tool_use_id"] == "c1"
+    assert block["content"] == [
+        {"type": "text", "text": "rendered"},
+        {"type": "image", "source": {"type": "base64", "media_type": "image/png", "data": "iVBORw0KGgo="}},
+    ]
+    assert to_anthropic_tool_result(json_result, is_error=True)["content"] == [
+        {"type": "text", "text": '{"rows": 3, "ok": true}'}
+    ]
+    assert to_anthropic_tool_result(Message.create_tool_result("plain", "c3"))["content"] == "plain"
+
+
+@pytest.mark.parametrize(
+    "cfg",
+    [
+        ModelConfig(provider="gemini", model="gemini-2.5-flash"),
+        ModelConfig(provider="bedrock", model="anthropic.claude-3-5-sonnet-20240620-v1:0", api_key="k"),
+        ModelConfig(provider="ollama", model="llama3.1"),
+    ],
+)
+def test_malformed_tool_call_arguments_are_sent_raw(cfg):
+    # 上一轮被 max_tokens 截断的参数不应让整个请求失败
+    call = {"id": "c1", "type": "function", "function": {"name": "chart", "arguments": '{"rows": 3'}}
+    params = RunParams(
+        messages=[
+            Message.create_user("plot it"),
+            Message.create_tool_call([call]),
+            Message.create_tool_result("done", "c1"),
+        ]
+    )
+    body = json.dumps(snapshot_request(cfg, params))
+    assert json.dumps({"_raw": '{"rows": 3'}) in body
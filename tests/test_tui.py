@@ -0,0 +1,82 @@
+import sys
+
+import pytest
+
+from prompti.message import Message, StreamingChoice, StreamingModelResponse, Usage
+from prompti.model_client import ModelConfig
+from prompti.tui import ChatSession, arun_app
+
+
+class StreamClient:
+    def __init__(self, cfg):
+        self.model = cfg.model
+        self.seen = []
+        self.closed = False
+
+    async def arun(self, params):
+        self.seen.append([m.content for m in params.messages])
+        if self.model == "broken":
+            yield StreamingModelResponse(error={"message": "boom"})
+            return
+        for piece in ("Hello", " there"):
+            delta = Message(role="assistant", content=piece)
+            yield StreamingModelResponse(choices=[StreamingChoice(index=0, delta=delta)])
+        usage = Usage(prompt_tokens=1000, completion_tokens=100, total_tokens=1100)
+        yield StreamingModelResponse(choices=[], usage=usage)
+
+    async def aclose(self):
+        self.closed = True
+
+
+def _session(*models):
+    clients = {}
+
+    def factory(cfg):
+        clients[cfg.model] = StreamClient(cfg)
+        return clients[cfg.model]
+
+    configs = [ModelConfig(provider="openai", model=m) for m in models]
+    return ChatSession(configs, client_factory=factory), clients
+
+
+@pytest.mark.asyncio
+async def test_send_streams_and_keeps_the_history():
+    session, clients = _session("gpt-4o", "local-model")
+    texts = [entry.text async for entry in session.asend("Hi")]
+    assert texts == ["Hello", "Hello there", "Hello there"]
+    assert session.footer() == "openai:gpt-4o | tokens 1000 in / 100 out | cost $0.0035"
+
+    assert session.command("/model local-model") == "Switched to openai:local-model"
+    [entry async for entry in session.asend("Again")]
+    assert clients["local-model"].seen == [["Hi", "Hello there", "Again"]]
+    # local-model 不在价格表里
+    assert session.footer() == "openai:local-model | tokens 2000 in / 200 out | cost $0.0035+"
+    await session.aclose()
+    assert all(c.closed for c in clients.values())
+
+
+@pytest.mark.asyncio
+async def test_failed_message_is_left_out_of_the_history():
+    session, _ = _session("broken")
+    [entry] = [entry async for entry in session.asend("Hi")]
+    assert (entry.error, session.messages) == ("boom", [])
+    assert [e.role for e in session.entries] == ["user", "assistant"]
+
+
+def test_commands():
+    session, _ = _session("a", "b")
+    assert session.command("hello") is None
+    assert session.command("/models") == "* openai:a\n  openai:b"
+    assert session.command("/model") == "Switched to openai:b"
+    assert session.command("/model openai:a") == "Switched to openai:a"
+    assert session.command("/model c").startswith("Unknown model 'c'")
+    assert session.command("/clear") == "Conversation cleared"
+    assert session.command("/nope").startswith("Unknown command /nope")
+
+
+@pytest.mark.asyncio
+async def test_app_needs_the_tui_extra(monkeypatch):
+    monkeypatch.setitem(sys.modules, "textual.app", None)
+    session, _ = _session("a")
+    with pytest.raises(ImportError, match=r"prompti\[tui\]"):
+        await arun_app(session)